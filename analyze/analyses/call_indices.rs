@@ -0,0 +1,346 @@
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use csv;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `call-indices` analysis.
+#[derive(Debug)]
+pub struct CallIndicesResult {
+    entries: Vec<CallIndicesEntry>,
+    called: usize,
+    call_sites: u64,
+    bytes: u64,
+    optimal_bytes: u64,
+    limit: usize,
+}
+
+impl CallIndicesResult {
+    /// The functions whose call sites would shrink if functions were
+    /// renumbered by how often they are called, most savings first.
+    pub fn entries(&self) -> &[CallIndicesEntry] {
+        &self.entries
+    }
+
+    /// The bytes currently spent on the function indices of all calls.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The bytes that the function indices of all calls would take up after
+    /// renumbering.
+    pub fn optimal_bytes(&self) -> u64 {
+        self.optimal_bytes
+    }
+
+    /// The JSON output, as a value that serde can serialize: a row for each
+    /// listed function, then rows for the rest and the total.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        let row = |name: String, sums: &Sums| JsonRow {
+            id: None,
+            name,
+            index: None,
+            optimal_index: None,
+            call_sites: sums.call_sites,
+            bytes: sums.bytes,
+            optimal_bytes: sums.optimal_bytes,
+            savings: sums.bytes - sums.optimal_bytes,
+        };
+
+        let mut rows: Vec<_> = self
+            .entries
+            .iter()
+            .take(self.limit)
+            .map(|entry| JsonRow {
+                id: Some(entry.id.to_string()),
+                index: Some(entry.index),
+                optimal_index: Some(entry.optimal_index),
+                ..row(
+                    items[entry.id].name().to_string(),
+                    &Sums::new(Some(entry).into_iter()),
+                )
+            })
+            .collect();
+
+        let rest = &self.entries[self.entries.len().min(self.limit)..];
+        if !rest.is_empty() {
+            rows.push(row(
+                format!("... and {} more", rest.len()),
+                &Sums::new(rest.iter()),
+            ));
+        }
+
+        rows.push(row(
+            format!("Σ [{} Called Functions]", self.called),
+            &Sums {
+                call_sites: self.call_sites,
+                bytes: self.bytes,
+                optimal_bytes: self.optimal_bytes,
+            },
+        ));
+
+        rows
+    }
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonRow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    optimal_index: Option<u32>,
+    call_sites: u64,
+    bytes: u64,
+    optimal_bytes: u64,
+    savings: u64,
+}
+
+/// How the calls of a single function are encoded, now and after renumbering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallIndicesEntry {
+    id: ir::Id,
+    call_sites: u64,
+    index: u32,
+    optimal_index: u32,
+}
+
+impl CallIndicesEntry {
+    /// The called function's id.
+    pub fn id(&self) -> ir::Id {
+        self.id
+    }
+
+    /// How many call instructions call the function.
+    pub fn call_sites(&self) -> u64 {
+        self.call_sites
+    }
+
+    /// The function's current index.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The function's index after renumbering.
+    pub fn optimal_index(&self) -> u32 {
+        self.optimal_index
+    }
+
+    /// The bytes currently spent on the function's index at its call sites.
+    pub fn bytes(&self) -> u64 {
+        self.call_sites * leb128_len(self.index)
+    }
+
+    /// The bytes that would be spent on the function's index at its call
+    /// sites after renumbering.
+    pub fn optimal_bytes(&self) -> u64 {
+        self.call_sites * leb128_len(self.optimal_index)
+    }
+
+    /// The bytes that renumbering would save at the function's call sites.
+    pub fn savings(&self) -> u64 {
+        self.bytes() - self.optimal_bytes()
+    }
+}
+
+/// The sizes of several entries, summed, for the summary rows.
+struct Sums {
+    call_sites: u64,
+    bytes: u64,
+    optimal_bytes: u64,
+}
+
+impl Sums {
+    fn new<'a>(entries: impl Iterator<Item = &'a CallIndicesEntry>) -> Sums {
+        entries.fold(
+            Sums {
+                call_sites: 0,
+                bytes: 0,
+                optimal_bytes: 0,
+            },
+            |sums, entry| Sums {
+                call_sites: sums.call_sites + entry.call_sites,
+                bytes: sums.bytes + entry.bytes(),
+                optimal_bytes: sums.optimal_bytes + entry.optimal_bytes(),
+            },
+        )
+    }
+}
+
+impl traits::Emit for CallIndicesResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Call Sites".to_string()),
+            (Align::Right, "Index".to_string()),
+            (Align::Right, "New Index".to_string()),
+            (Align::Right, "Bytes".to_string()),
+            (Align::Right, "New Bytes".to_string()),
+            (Align::Right, "Saved".to_string()),
+            (Align::Left, "Function".to_string()),
+        ]);
+
+        for entry in self.entries.iter().take(self.limit) {
+            table.add_row(vec![
+                entry.call_sites.to_string(),
+                entry.index.to_string(),
+                entry.optimal_index.to_string(),
+                entry.bytes().to_string(),
+                entry.optimal_bytes().to_string(),
+                entry.savings().to_string(),
+                items[entry.id].name().to_string(),
+            ]);
+        }
+
+        let rest = &self.entries[self.entries.len().min(self.limit)..];
+        if !rest.is_empty() {
+            let sums = Sums::new(rest.iter());
+            table.add_row(vec![
+                sums.call_sites.to_string(),
+                String::new(),
+                String::new(),
+                sums.bytes.to_string(),
+                sums.optimal_bytes.to_string(),
+                (sums.bytes - sums.optimal_bytes).to_string(),
+                format!("... and {} more", rest.len()),
+            ]);
+        }
+
+        table.add_row(vec![
+            self.call_sites.to_string(),
+            String::new(),
+            String::new(),
+            self.bytes.to_string(),
+            self.optimal_bytes.to_string(),
+            (self.bytes - self.optimal_bytes).to_string(),
+            format!("Σ [{} Called Functions]", self.called),
+        ]);
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            id: String,
+            name: &'a str,
+            call_sites: u64,
+            index: u32,
+            optimal_index: u32,
+            bytes: u64,
+            optimal_bytes: u64,
+            savings: u64,
+        }
+
+        for entry in self.entries.iter().take(self.limit) {
+            wtr.serialize(CsvRecord {
+                id: entry.id.to_string(),
+                name: items[entry.id].name(),
+                call_sites: entry.call_sites,
+                index: entry.index,
+                optimal_index: entry.optimal_index,
+                bytes: entry.bytes(),
+                optimal_bytes: entry.optimal_bytes(),
+                savings: entry.savings(),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate how many bytes of call instructions' function indices could be
+/// saved by renumbering functions so that the most frequently called ones get
+/// the smallest indices, which take the fewest bytes to encode as LEB128.
+///
+/// Imported functions always come before the functions defined in the binary,
+/// so each are only renumbered among the indices they already occupy. Only
+/// direct calls are counted; other references to function indices, such as
+/// in element segments and exports, are not.
+pub fn call_indices(
+    items: &mut ir::Items,
+    opts: &opt::CallIndices,
+) -> traits::Result<CallIndicesResult> {
+    let items = &*items;
+
+    let (imported, defined): (Vec<_>, Vec<_>) = items
+        .binary_indices()
+        .partition(|&(id, _)| items[id].category() == ir::ItemCategory::Import);
+
+    let mut entries = vec![];
+    for functions in [imported, defined] {
+        let mut indices: Vec<u32> = functions.iter().map(|&(_, index)| index).collect();
+        indices.sort_unstable();
+
+        let mut called: Vec<(ir::Id, u32, u64)> = functions
+            .into_iter()
+            .map(|(id, index)| (id, index, items.call_sites(id)))
+            .filter(|&(_, _, call_sites)| call_sites > 0)
+            .collect();
+        // Hand out the smallest indices to the most frequently called
+        // functions first. Ties keep their current order.
+        called.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.1.cmp(&b.1)));
+
+        entries.extend(called.into_iter().zip(indices).map(
+            |((id, index, call_sites), optimal_index)| CallIndicesEntry {
+                id,
+                call_sites,
+                index,
+                optimal_index,
+            },
+        ));
+    }
+
+    let called = entries.len();
+    let Sums {
+        call_sites,
+        bytes,
+        optimal_bytes,
+    } = Sums::new(entries.iter());
+
+    entries.retain(|entry| entry.optimal_bytes() < entry.bytes());
+    entries.sort_by(|a, b| {
+        b.savings()
+            .cmp(&a.savings())
+            .then_with(|| b.call_sites.cmp(&a.call_sites))
+            .then_with(|| a.index.cmp(&b.index))
+    });
+
+    Ok(CallIndicesResult {
+        entries,
+        called,
+        call_sites,
+        bytes,
+        optimal_bytes,
+        limit: opts.max_items() as usize,
+    })
+}
+
+/// The number of bytes it takes to encode the given index as unsigned LEB128.
+fn leb128_len(index: u32) -> u64 {
+    match index {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        0x4000..=0x1f_ffff => 3,
+        0x20_0000..=0xfff_ffff => 4,
+        _ => 5,
+    }
+}
@@ -0,0 +1,228 @@
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use csv;
+use regex;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `callsites` analysis.
+#[derive(Debug)]
+pub struct CallsitesResult {
+    entries: Vec<CallsitesEntry>,
+    limit: usize,
+}
+
+impl CallsitesResult {
+    /// The selected functions and their callers.
+    pub fn entries(&self) -> &[CallsitesEntry] {
+        &self.entries
+    }
+}
+
+/// The callers of a single function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallsitesEntry {
+    id: ir::Id,
+    callers: Vec<(ir::Id, ir::CallSiteBytes)>,
+}
+
+impl CallsitesEntry {
+    /// The called function's id.
+    pub fn id(&self) -> ir::Id {
+        self.id
+    }
+
+    /// The functions that call the function directly, along with their calls
+    /// of it, most bytes first.
+    pub fn callers(&self) -> &[(ir::Id, ir::CallSiteBytes)] {
+        &self.callers
+    }
+
+    /// The number of calls of the function from all of its callers.
+    pub fn calls(&self) -> u64 {
+        self.callers.iter().map(|(_, sites)| sites.calls()).sum()
+    }
+
+    /// The bytes that all calls of the function take up in its callers.
+    pub fn bytes(&self) -> u64 {
+        self.callers.iter().map(|(_, sites)| sites.bytes()).sum()
+    }
+}
+
+/// The calls of several callers, summed, for the summary rows.
+fn sum<'a>(callers: impl Iterator<Item = &'a (ir::Id, ir::CallSiteBytes)>) -> ir::CallSiteBytes {
+    let (calls, bytes) = callers.fold((0, 0), |(calls, bytes), (_, sites)| {
+        (calls + sites.calls(), bytes + sites.bytes())
+    });
+    ir::CallSiteBytes::new(calls, bytes)
+}
+
+impl traits::Emit for CallsitesResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Calls".to_string()),
+            (Align::Right, "Call Bytes".to_string()),
+            (Align::Left, "Caller".to_string()),
+        ]);
+
+        for entry in &self.entries {
+            for (caller, sites) in entry.callers.iter().take(self.limit) {
+                table.add_row(vec![
+                    sites.calls().to_string(),
+                    sites.bytes().to_string(),
+                    items[*caller].name().to_string(),
+                ]);
+            }
+
+            let rest = &entry.callers[entry.callers.len().min(self.limit)..];
+            if !rest.is_empty() {
+                let sums = sum(rest.iter());
+                table.add_row(vec![
+                    sums.calls().to_string(),
+                    sums.bytes().to_string(),
+                    format!("... and {} more", rest.len()),
+                ]);
+            }
+
+            table.add_row(vec![
+                entry.calls().to_string(),
+                entry.bytes().to_string(),
+                format!(
+                    "Σ [{} {} of {}]",
+                    entry.callers.len(),
+                    if entry.callers.len() == 1 {
+                        "Caller"
+                    } else {
+                        "Callers"
+                    },
+                    items[entry.id].name()
+                ),
+            ]);
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut arr = json::array(dest)?;
+
+        for entry in &self.entries {
+            let mut obj = arr.object()?;
+            obj.field("id", entry.id.to_string().as_str())?;
+            obj.field("name", items[entry.id].name())?;
+            obj.field("size", items[entry.id].size())?;
+            obj.field("calls", entry.calls())?;
+            obj.field("call_bytes", entry.bytes())?;
+
+            let mut callers = obj.array("callers")?;
+            for (caller, sites) in entry.callers.iter().take(self.limit) {
+                let mut obj = callers.object()?;
+                obj.field("id", caller.to_string().as_str())?;
+                obj.field("name", items[*caller].name())?;
+                obj.field("calls", sites.calls())?;
+                obj.field("call_bytes", sites.bytes())?;
+            }
+
+            let rest = &entry.callers[entry.callers.len().min(self.limit)..];
+            if !rest.is_empty() {
+                let sums = sum(rest.iter());
+                let mut obj = callers.object()?;
+                obj.field("name", format!("... and {} more", rest.len()).as_str())?;
+                obj.field("calls", sums.calls())?;
+                obj.field("call_bytes", sums.bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            callee_id: String,
+            callee: &'a str,
+            caller_id: String,
+            caller: &'a str,
+            calls: u64,
+            call_bytes: u64,
+        }
+
+        for entry in &self.entries {
+            for (caller, sites) in entry.callers.iter().take(self.limit) {
+                wtr.serialize(CsvRecord {
+                    callee_id: entry.id.to_string(),
+                    callee: items[entry.id].name(),
+                    caller_id: caller.to_string(),
+                    caller: items[*caller].name(),
+                    calls: sites.calls(),
+                    call_bytes: sites.bytes(),
+                })?;
+                wtr.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// List the functions that call the given functions directly, and how many
+/// bytes of each caller those call instructions take up.
+///
+/// This relies on the parser having recorded call site bytes, see
+/// `twiggy_parser::ParseOptions::set_call_site_bytes`; otherwise every function
+/// is listed without callers. Only `call` instructions are counted.
+pub fn callsites(items: &mut ir::Items, opts: &opt::Callsites) -> traits::Result<CallsitesResult> {
+    let items = &*items;
+
+    let mut matches: Vec<ir::Id> = if opts.functions().is_empty() {
+        vec![]
+    } else if opts.using_regexps() {
+        // Only functions that are called at all, so that a broad pattern
+        // does not list every other item without callers.
+        let regexps = regex::RegexSet::new(opts.functions())?;
+        items
+            .iter()
+            .filter(|item| regexps.is_match(item.name()))
+            .map(|item| item.id())
+            .filter(|&id| items.call_site_bytes(id).next().is_some())
+            .collect()
+    } else {
+        // Several functions can share a name, in which case all of them are
+        // used.
+        opts.functions()
+            .iter()
+            .flat_map(|name| items.get_items_by_name(name))
+            .map(|item| item.id())
+            .collect()
+    };
+    super::select_by_id(items, opts.ids(), &mut matches)?;
+
+    let entries = matches
+        .into_iter()
+        .map(|id| {
+            let mut callers: Vec<_> = items.call_site_bytes(id).collect();
+            callers.sort_by(|(a_id, a), (b_id, b)| {
+                b.bytes()
+                    .cmp(&a.bytes())
+                    .then_with(|| b.calls().cmp(&a.calls()))
+                    .then_with(|| a_id.cmp(b_id))
+            });
+            CallsitesEntry { id, callers }
+        })
+        .collect();
+
+    Ok(CallsitesResult {
+        entries,
+        limit: opts.max_items() as usize,
+    })
+}
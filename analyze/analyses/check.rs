@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use csv;
+use regex::Regex;
+use serde_derive::{Deserialize, Serialize};
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// The size limits that `check` holds a binary to.
+///
+/// A budget has a limit for the whole binary, and limits for sections by
+/// name, for crates by name, and for the items whose names match regular
+/// expressions, such as:
+///
+/// ```toml
+/// [total]
+/// max-size = 3000
+/// max-growth = 100
+///
+/// [sections."code section"]
+/// max-size = 1000
+///
+/// [crates.wee_alloc]
+/// max-size = 700
+///
+/// [items."^wee_alloc::"]
+/// max-growth = 0
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    #[serde(default)]
+    total: Option<Limit>,
+    #[serde(default)]
+    sections: BTreeMap<String, Limit>,
+    #[serde(default)]
+    crates: BTreeMap<String, Limit>,
+    #[serde(default)]
+    items: BTreeMap<String, Limit>,
+}
+
+/// The most bytes that a part of the binary may take up, and the most bytes
+/// that it may grow by over the baseline.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Limit {
+    max_size: Option<u64>,
+    max_growth: Option<i64>,
+}
+
+impl Budget {
+    /// Read a budget from TOML.
+    pub fn from_toml(s: &str) -> traits::Result<Budget> {
+        let budget: Budget = toml::from_str(s)
+            .map_err(|e| traits::Error::Analysis(format!("invalid budget: {}", e)))?;
+        budget.validate()?;
+        Ok(budget)
+    }
+
+    /// Read a budget from JSON.
+    pub fn from_json(s: &str) -> traits::Result<Budget> {
+        let budget: Budget = serde_json::from_str(s)
+            .map_err(|e| traits::Error::Analysis(format!("invalid budget: {}", e)))?;
+        budget.validate()?;
+        Ok(budget)
+    }
+
+    /// Check that every limit limits something, and that every item pattern
+    /// is a valid regular expression.
+    fn validate(&self) -> traits::Result<()> {
+        for (rule, limit) in self.rules() {
+            if limit.max_size.is_none() && limit.max_growth.is_none() {
+                return Err(traits::Error::Analysis(format!(
+                    "invalid budget: the limit for {} has neither `max-size` nor `max-growth`",
+                    rule
+                )));
+            }
+            if let Rule::Items(pattern) = rule {
+                Regex::new(&pattern)
+                    .map_err(|e| traits::Error::Analysis(format!("invalid budget: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every rule of the budget, along with its limit.
+    fn rules(&self) -> Vec<(Rule, &Limit)> {
+        let mut rules = vec![];
+        if let Some(limit) = &self.total {
+            rules.push((Rule::Total, limit));
+        }
+        for (name, limit) in &self.sections {
+            rules.push((Rule::Section(name.clone()), limit));
+        }
+        for (name, limit) in &self.crates {
+            rules.push((Rule::Crate(name.clone()), limit));
+        }
+        for (pattern, limit) in &self.items {
+            rules.push((Rule::Items(pattern.clone()), limit));
+        }
+        rules
+    }
+}
+
+/// What part of the binary a rule of a budget limits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// The whole binary.
+    Total,
+    /// The section with the given name, as `twiggy sections` names it, such
+    /// as `code section`.
+    Section(String),
+    /// The items of the given crate, as `twiggy crates` groups them.
+    Crate(String),
+    /// The items whose names match the given regular expression.
+    Items(String),
+}
+
+impl Rule {
+    /// The kind of rule: `total`, `section`, `crate` or `items`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Rule::Total => "total",
+            Rule::Section(_) => "section",
+            Rule::Crate(_) => "crate",
+            Rule::Items(_) => "items",
+        }
+    }
+
+    /// The name of the section or crate, or the pattern of the items, that the
+    /// rule limits. Empty for the total.
+    pub fn name(&self) -> &str {
+        match self {
+            Rule::Total => "",
+            Rule::Section(name) | Rule::Crate(name) | Rule::Items(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::Total => write!(f, "total"),
+            Rule::Section(name) => write!(f, "section '{}'", name),
+            Rule::Crate(name) => write!(f, "crate '{}'", name),
+            Rule::Items(pattern) => write!(f, "items matching /{}/", pattern),
+        }
+    }
+}
+
+/// The result of the `check` analysis.
+#[derive(Debug)]
+pub struct CheckResult {
+    rules: Vec<RuleResult>,
+}
+
+impl CheckResult {
+    /// The result of each rule of the budget: the total first, then the
+    /// sections, crates and items, each by name.
+    pub fn rules(&self) -> &[RuleResult] {
+        &self.rules
+    }
+
+    /// How many of the rules the binary broke.
+    pub fn failed(&self) -> usize {
+        self.rules.iter().filter(|rule| !rule.passed()).count()
+    }
+}
+
+/// How a binary measured up against a rule of the budget.
+#[derive(Debug, Clone)]
+pub struct RuleResult {
+    rule: Rule,
+    size: u64,
+    baseline_size: Option<u64>,
+    max_size: Option<u64>,
+    max_growth: Option<i64>,
+}
+
+impl RuleResult {
+    /// The rule.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// How many bytes of the binary the rule covers.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// How many bytes of the baseline the rule covers, if there is one.
+    pub fn baseline_size(&self) -> Option<u64> {
+        self.baseline_size
+    }
+
+    /// How many bytes larger the binary's part is than the baseline's, if
+    /// there is a baseline.
+    pub fn growth(&self) -> Option<i64> {
+        self.baseline_size
+            .map(|baseline| self.size as i64 - baseline as i64)
+    }
+
+    /// The most bytes the rule allows, if it limits the size.
+    pub fn max_size(&self) -> Option<u64> {
+        self.max_size
+    }
+
+    /// The most bytes the rule allows the binary's part to grow by, if it
+    /// limits the growth.
+    pub fn max_growth(&self) -> Option<i64> {
+        self.max_growth
+    }
+
+    /// Whether the binary is within the rule's limits. Without a baseline,
+    /// growth limits are not checked.
+    pub fn passed(&self) -> bool {
+        let size_ok = self.max_size.is_none_or(|max| self.size <= max);
+        let growth_ok = match (self.growth(), self.max_growth) {
+            (Some(growth), Some(max)) => growth <= max,
+            _ => true,
+        };
+        size_ok && growth_ok
+    }
+}
+
+fn status(passed: bool) -> &'static str {
+    if passed {
+        "ok"
+    } else {
+        "FAIL"
+    }
+}
+
+impl traits::Emit for CheckResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        let signed = |delta: i64| format!("{:+}", delta);
+
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Bytes".to_string()),
+            (Align::Right, "Max Bytes".to_string()),
+            (Align::Right, "Growth".to_string()),
+            (Align::Right, "Max Growth".to_string()),
+            (Align::Left, "Status".to_string()),
+            (Align::Left, "Rule".to_string()),
+        ]);
+
+        for rule in &self.rules {
+            table.add_row(vec![
+                rule.size.to_string(),
+                or_dash(rule.max_size.map(|max| max.to_string())),
+                or_dash(rule.growth().map(signed)),
+                or_dash(rule.max_growth.map(signed)),
+                status(rule.passed()).to_string(),
+                rule.rule.to_string(),
+            ]);
+        }
+
+        table.add_row(vec![
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+            "".to_string(),
+            status(self.failed() == 0).to_string(),
+            format!("Σ [{} Rules, {} Failed]", self.rules.len(), self.failed()),
+        ]);
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut obj = json::object(dest)?;
+        obj.field("passed", self.failed() == 0)?;
+        obj.field("failed", self.failed() as u64)?;
+
+        let mut arr = obj.array("rules")?;
+        for rule in &self.rules {
+            let mut obj = arr.object()?;
+            obj.field("kind", rule.rule.kind())?;
+            obj.field("name", rule.rule.name())?;
+            obj.field("size", rule.size)?;
+            if let Some(max_size) = rule.max_size {
+                obj.field("max_size", max_size)?;
+            }
+            if let Some(baseline_size) = rule.baseline_size {
+                obj.field("baseline_size", baseline_size)?;
+            }
+            if let Some(growth) = rule.growth() {
+                obj.field("growth", growth as f64)?;
+            }
+            if let Some(max_growth) = rule.max_growth {
+                obj.field("max_growth", max_growth as f64)?;
+            }
+            obj.field("passed", rule.passed())?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            kind: &'a str,
+            name: &'a str,
+            size: u64,
+            max_size: Option<u64>,
+            baseline_size: Option<u64>,
+            growth: Option<i64>,
+            max_growth: Option<i64>,
+            passed: bool,
+        }
+
+        for rule in &self.rules {
+            wtr.serialize(CsvRecord {
+                kind: rule.rule.kind(),
+                name: rule.rule.name(),
+                size: rule.size,
+                max_size: rule.max_size,
+                baseline_size: rule.baseline_size,
+                growth: rule.growth(),
+                max_growth: rule.max_growth,
+                passed: rule.passed(),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// How many bytes of `items` the given rule covers.
+fn measure(items: &ir::Items, rule: &Rule) -> u64 {
+    let mut covers: Box<dyn FnMut(&ir::Item) -> bool> = match rule {
+        Rule::Total => return items.size(),
+        Rule::Section(name) => {
+            let mut matches = BTreeMap::new();
+            Box::new(move |item| {
+                item.id().containing_section().is_some_and(|section| {
+                    *matches
+                        .entry(section)
+                        .or_insert_with(|| super::sections::section_name(items, section) == *name)
+                })
+            })
+        }
+        Rule::Crate(name) => Box::new(move |item| super::crates::bucket(item) == *name),
+        Rule::Items(pattern) => {
+            // Patterns were checked when the budget was read.
+            let regex = Regex::new(pattern).unwrap();
+            Box::new(move |item| regex.is_match(item.name()))
+        }
+    };
+    items
+        .iter()
+        .filter(|item| item.id() != items.meta_root() && covers(item))
+        .map(|item| item.size())
+        .sum()
+}
+
+/// Measure the part of the binary that each rule of the budget covers, and the
+/// same part of the baseline, if there is one, to check its growth against.
+pub fn check(
+    items: &ir::Items,
+    baseline: Option<&ir::Items>,
+    budget: &Budget,
+) -> traits::Result<CheckResult> {
+    let rules = budget
+        .rules()
+        .into_iter()
+        .map(|(rule, limit)| RuleResult {
+            size: measure(items, &rule),
+            baseline_size: baseline.map(|baseline| measure(baseline, &rule)),
+            max_size: limit.max_size,
+            max_growth: limit.max_growth,
+            rule,
+        })
+        .collect();
+    Ok(CheckResult { rules })
+}
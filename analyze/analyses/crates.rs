@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use crate::formats::truncated::Truncated;
+use csv;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `crates` analysis.
+#[derive(Debug)]
+pub struct CratesResult {
+    crates: Vec<CrateEntry>,
+    truncated: Truncated,
+    truncated_items: usize,
+}
+
+impl CratesResult {
+    /// The crates that were listed, in the requested order.
+    pub fn crates(&self) -> &[CrateEntry] {
+        &self.crates
+    }
+
+    /// The crates beyond the requested number of crates.
+    pub fn truncated(&self) -> Truncated {
+        self.truncated
+    }
+}
+
+/// How much of the binary a crate accounts for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateEntry {
+    name: String,
+    items: usize,
+    shallow_size: u64,
+    retained_size: u64,
+}
+
+impl CrateEntry {
+    /// The crate's name. Items that do not look like Rust symbols are grouped
+    /// by their kind instead, in buckets such as `<data_segment>`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many items belong to the crate.
+    pub fn items(&self) -> usize {
+        self.items
+    }
+
+    /// The sum of the shallow sizes of the crate's items.
+    pub fn shallow_size(&self) -> u64 {
+        self.shallow_size
+    }
+
+    /// The size of everything that would be removed along with all of the
+    /// crate's items: the items themselves, and the items only they retain.
+    pub fn retained_size(&self) -> u64 {
+        self.retained_size
+    }
+}
+
+impl traits::Emit for CratesResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let percent = |size: u64| format!("{:.2}%", size as f64 / items.size() as f64 * 100.0);
+
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Shallow Bytes".to_string()),
+            (Align::Right, "Shallow %".to_string()),
+            (Align::Right, "Retained Bytes".to_string()),
+            (Align::Right, "Retained %".to_string()),
+            (Align::Right, "Items".to_string()),
+            (Align::Left, "Crate".to_string()),
+        ]);
+
+        for krate in &self.crates {
+            table.add_row(vec![
+                krate.shallow_size.to_string(),
+                percent(krate.shallow_size),
+                krate.retained_size.to_string(),
+                percent(krate.retained_size),
+                krate.items.to_string(),
+                krate.name.clone(),
+            ]);
+        }
+
+        // Retained sizes overlap between crates, so they are not summed up.
+        let truncated_size = self.truncated.bytes() as u64;
+        if !self.truncated.is_empty() {
+            table.add_row(vec![
+                truncated_size.to_string(),
+                percent(truncated_size),
+                "...".to_string(),
+                "...".to_string(),
+                self.truncated_items.to_string(),
+                format!(
+                    "... and {} more crates totaling {} bytes ({})",
+                    self.truncated.count(),
+                    truncated_size,
+                    percent(truncated_size)
+                ),
+            ]);
+        }
+
+        let shown_size: u64 = self.crates.iter().map(|krate| krate.shallow_size).sum();
+        let shown_items: usize = self.crates.iter().map(|krate| krate.items).sum();
+        let total_size = shown_size + truncated_size;
+        table.add_row(vec![
+            total_size.to_string(),
+            percent(total_size),
+            "...".to_string(),
+            "...".to_string(),
+            (shown_items + self.truncated_items).to_string(),
+            format!(
+                "Σ [{} Total Crates]",
+                self.crates.len() + self.truncated.count()
+            ),
+        ]);
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let size_percent = |size: u64| size as f64 / items.size() as f64 * 100.0;
+
+        let mut arr = json::array(dest)?;
+        for krate in &self.crates {
+            let mut obj = arr.object()?;
+            obj.field("name", krate.name.as_str())?;
+            obj.field("items", krate.items as u64)?;
+            obj.field("shallow_size", krate.shallow_size)?;
+            obj.field("shallow_size_percent", size_percent(krate.shallow_size))?;
+            obj.field("retained_size", krate.retained_size)?;
+            obj.field("retained_size_percent", size_percent(krate.retained_size))?;
+        }
+
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            let mut obj = arr.object()?;
+            obj.field(
+                "name",
+                format!("... and {} more crates", self.truncated.count()).as_str(),
+            )?;
+            obj.field("shallow_size", size)?;
+            obj.field("shallow_size_percent", size_percent(size))?;
+            self.truncated.json_fields(&mut obj)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            name: &'a str,
+            items: usize,
+            shallow_size: u64,
+            shallow_size_percent: f64,
+            retained_size: u64,
+            retained_size_percent: f64,
+        }
+
+        for krate in &self.crates {
+            wtr.serialize(CsvRecord {
+                name: &krate.name,
+                items: krate.items,
+                shallow_size: krate.shallow_size,
+                shallow_size_percent: krate.shallow_size as f64 / items.size() as f64 * 100.0,
+                retained_size: krate.retained_size,
+                retained_size_percent: krate.retained_size as f64 / items.size() as f64 * 100.0,
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// The crate an item is attributed to, or, for items that do not look like
+/// Rust symbols, a bucket for its kind, such as `<data_segment>`.
+pub(super) fn bucket(item: &ir::Item) -> String {
+    match super::crate_name(item.name()) {
+        Some(krate) => krate.to_string(),
+        None => format!("<{}>", item.category()),
+    }
+}
+
+/// Sum up the shallow sizes of each crate's items, and the size of everything
+/// those items retain.
+pub fn crates(items: &mut ir::Items, opts: &opt::Crates) -> traits::Result<CratesResult> {
+    items.set_threads(opts.threads());
+    items.compute_retained_sizes();
+    let items = &*items;
+
+    let buckets: BTreeMap<ir::Id, String> = items
+        .iter()
+        .filter(|item| item.id() != items.meta_root())
+        .map(|item| (item.id(), bucket(item)))
+        .collect();
+
+    let mut crates: BTreeMap<&str, CrateEntry> = BTreeMap::new();
+    for (&id, name) in &buckets {
+        let krate = crates.entry(name).or_insert_with(|| CrateEntry {
+            name: name.clone(),
+            items: 0,
+            shallow_size: 0,
+            retained_size: 0,
+        });
+        krate.items += 1;
+        krate.shallow_size += items[id].size();
+    }
+
+    // A crate retains the subtrees of the dominator tree rooted at its items,
+    // so walk the tree and count each item's retained size towards its crate
+    // unless one of its dominators already belongs to the same crate.
+    // Unreachable items are not part of the tree, and only retain themselves.
+    let tree = items.dominator_tree();
+    let mut enclosing: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut stack = vec![(tree.root(), false)];
+    while let Some((id, leaving)) = stack.pop() {
+        let name = buckets.get(&id).map(String::as_str);
+        if leaving {
+            if let Some(name) = name {
+                *enclosing.get_mut(name).unwrap() -= 1;
+            }
+            continue;
+        }
+        if let Some(name) = name {
+            let depth = enclosing.entry(name).or_insert(0);
+            if *depth == 0 {
+                crates.get_mut(name).unwrap().retained_size += items.retained_size(id);
+            }
+            *depth += 1;
+            stack.push((id, true));
+        }
+        stack.extend(tree.children(id).iter().map(|&child| (child, false)));
+    }
+    for (&id, name) in &buckets {
+        if !tree.contains(id) {
+            crates.get_mut(name.as_str()).unwrap().retained_size += items[id].size();
+        }
+    }
+
+    // Ties are broken by name, so that the output is deterministic.
+    let mut crates: Vec<CrateEntry> = crates.into_values().collect();
+    match opts.order() {
+        opt::CrateOrder::Shallow => crates.sort_by(|a, b| b.shallow_size.cmp(&a.shallow_size)),
+        opt::CrateOrder::Retained => crates.sort_by(|a, b| b.retained_size.cmp(&a.retained_size)),
+        opt::CrateOrder::Name => {}
+    }
+
+    let max_items = opts.max_items() as usize;
+    let rest = crates.split_off(max_items.min(crates.len()));
+    let truncated = Truncated::of(rest.iter().map(|krate| krate.shallow_size));
+    let truncated_items = rest.iter().map(|krate| krate.items).sum();
+
+    Ok(CratesResult {
+        crates,
+        truncated,
+        truncated_items,
+    })
+}
@@ -0,0 +1,450 @@
+use std::cmp;
+use std::io;
+use std::str;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use crate::formats::truncated::Truncated;
+use csv;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// Parts of the strings that Rust's panics and formatting machinery leave in a
+/// binary: panic messages, and the source file names of panic locations.
+const PANIC_MARKERS: &[&str] = &[
+    "panicked",
+    "called `",
+    "index out of bounds",
+    "attempt to ",
+    ".rs",
+    "{}",
+    "{:",
+];
+
+/// The result of the `data` analysis.
+#[derive(Debug)]
+pub struct DataResult {
+    strings: Vec<DataString>,
+    truncated: Truncated,
+    segments: usize,
+    data_bytes: u64,
+    string_count: usize,
+    string_bytes: u64,
+    panic_count: usize,
+    panic_bytes: u64,
+}
+
+impl DataResult {
+    /// The strings that were listed, largest first.
+    pub fn strings(&self) -> &[DataString] {
+        &self.strings
+    }
+
+    /// The strings beyond the requested number of strings.
+    pub fn truncated(&self) -> Truncated {
+        self.truncated
+    }
+
+    /// How many data segments were looked into.
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+
+    /// The total size of those segments' payloads.
+    pub fn data_bytes(&self) -> u64 {
+        self.data_bytes
+    }
+
+    /// How many strings the payloads hold, listed or not.
+    pub fn string_count(&self) -> usize {
+        self.string_count
+    }
+
+    /// The total size of those strings.
+    pub fn string_bytes(&self) -> u64 {
+        self.string_bytes
+    }
+
+    /// How many of the strings look like panic messages, format strings, or
+    /// the source file names of panic locations.
+    pub fn panic_count(&self) -> usize {
+        self.panic_count
+    }
+
+    /// The total size of those strings.
+    pub fn panic_bytes(&self) -> u64 {
+        self.panic_bytes
+    }
+
+    /// The bytes of the payloads that are not strings.
+    pub fn binary_bytes(&self) -> u64 {
+        self.data_bytes - self.string_bytes
+    }
+}
+
+/// A run of printable text inside a data segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataString {
+    segment: String,
+    memory: u32,
+    address: u64,
+    offset: u64,
+    size: u64,
+    preview: String,
+    referenced_by: Vec<String>,
+}
+
+impl DataString {
+    /// The name of the data segment holding the string.
+    pub fn segment(&self) -> &str {
+        &self.segment
+    }
+
+    /// The index of the memory that the segment is copied into.
+    pub fn memory(&self) -> u32 {
+        self.memory
+    }
+
+    /// The address of the string's first byte in that memory.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// The offset of the string's first byte into the segment's payload.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The size of the string in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The start of the string, with line breaks and tabs escaped, and cut
+    /// short after the requested number of characters.
+    pub fn preview(&self) -> &str {
+        &self.preview
+    }
+
+    /// The names of the items that refer to the string.
+    pub fn referenced_by(&self) -> &[String] {
+        &self.referenced_by
+    }
+}
+
+impl traits::Emit for DataResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let percent = |size: u64| {
+            if self.data_bytes == 0 {
+                String::new()
+            } else {
+                format!("{:.2}%", size as f64 / self.data_bytes as f64 * 100.0)
+            }
+        };
+
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Bytes".to_string()),
+            (Align::Right, "Data %".to_string()),
+            (Align::Right, "Address".to_string()),
+            (Align::Left, "Segment".to_string()),
+            (Align::Left, "String".to_string()),
+            (Align::Left, "Referenced By".to_string()),
+        ]);
+        table.measure_chars();
+
+        for string in &self.strings {
+            let referenced_by = match string.referenced_by.split_first() {
+                None => String::new(),
+                Some((first, [])) => first.clone(),
+                Some((first, rest)) => format!("{} and {} more", first, rest.len()),
+            };
+            table.add_row(vec![
+                string.size.to_string(),
+                percent(string.size),
+                format!("{:#x}", string.address),
+                string.segment.clone(),
+                format!("\"{}\"", string.preview),
+                referenced_by,
+            ]);
+        }
+
+        let mut add_summary = |size: u64, name: String| {
+            table.add_row(vec![
+                size.to_string(),
+                percent(size),
+                String::new(),
+                String::new(),
+                name,
+                String::new(),
+            ]);
+        };
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            add_summary(
+                size,
+                format!(
+                    "... and {} more strings totaling {} bytes",
+                    self.truncated.count(),
+                    size
+                ),
+            );
+        }
+        add_summary(
+            self.string_bytes,
+            format!("Σ [{} Total Strings]", self.string_count),
+        );
+        add_summary(
+            self.panic_bytes,
+            format!("Σ [{} Panic and Format Strings]", self.panic_count),
+        );
+        add_summary(self.binary_bytes(), "Σ Binary Data".to_string());
+        add_summary(
+            self.data_bytes,
+            format!("Σ [{} Total Segments]", self.segments),
+        );
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let data_percent = |size: u64| size as f64 / self.data_bytes as f64 * 100.0;
+
+        let mut obj = json::object(dest)?;
+        {
+            let mut arr = obj.array("strings")?;
+            for string in &self.strings {
+                let mut obj = arr.object()?;
+                obj.field("segment", string.segment.as_str())?;
+                obj.field("memory", string.memory)?;
+                obj.field("address", string.address)?;
+                obj.field("offset", string.offset)?;
+                obj.field("size", string.size)?;
+                obj.field("data_percent", data_percent(string.size))?;
+                obj.field("preview", string.preview.as_str())?;
+                let mut referenced_by = obj.array("referenced_by")?;
+                for name in &string.referenced_by {
+                    referenced_by.elem(name.as_str())?;
+                }
+            }
+        }
+        if !self.truncated.is_empty() {
+            self.truncated.json_fields(&mut obj)?;
+        }
+        obj.field("segments", self.segments as u64)?;
+        obj.field("data_bytes", self.data_bytes)?;
+        obj.field("string_count", self.string_count as u64)?;
+        obj.field("string_bytes", self.string_bytes)?;
+        obj.field("panic_count", self.panic_count as u64)?;
+        obj.field("panic_bytes", self.panic_bytes)?;
+        obj.field("binary_bytes", self.binary_bytes())?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            segment: &'a str,
+            memory: u32,
+            address: u64,
+            offset: u64,
+            size: u64,
+            data_percent: f64,
+            preview: &'a str,
+            referenced_by: String,
+        }
+
+        for string in &self.strings {
+            wtr.serialize(CsvRecord {
+                segment: &string.segment,
+                memory: string.memory,
+                address: string.address,
+                offset: string.offset,
+                size: string.size,
+                data_percent: string.size as f64 / self.data_bytes as f64 * 100.0,
+                preview: &string.preview,
+                referenced_by: string.referenced_by.join(", "),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Is the character text, rather than a control character? Line breaks and
+/// tabs are text.
+fn is_printable(c: char) -> bool {
+    !c.is_control() || matches!(c, '\n' | '\r' | '\t')
+}
+
+/// Split the bytes into the maximal runs of printable UTF-8 text that are at
+/// least `min_len` bytes long, as ranges of offsets into them.
+fn printable_runs(bytes: &[u8], min_len: usize) -> Vec<(usize, usize)> {
+    let mut runs = vec![];
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        // A UTF-8 lead byte says how long its character is.
+        let len = match bytes[i] {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => 0,
+        };
+        let c = bytes
+            .get(i..i + len)
+            .filter(|_| len > 0)
+            .and_then(|bytes| str::from_utf8(bytes).ok())
+            .and_then(|s| s.chars().next())
+            .filter(|&c| is_printable(c));
+        match c {
+            Some(_) => i += len,
+            None => {
+                if i - start >= min_len.max(1) {
+                    runs.push((start, i));
+                }
+                i += 1;
+                start = i;
+            }
+        }
+    }
+    if i - start >= min_len.max(1) {
+        runs.push((start, i));
+    }
+    runs
+}
+
+/// The first `chars` characters of the text, with line breaks and tabs
+/// escaped, and `...` in place of the rest.
+fn preview(text: &str, chars: usize) -> String {
+    let mut preview = String::new();
+    for (i, c) in text.chars().enumerate() {
+        if i == chars {
+            preview.push_str("...");
+            break;
+        }
+        match c {
+            '\n' => preview.push_str("\\n"),
+            '\r' => preview.push_str("\\r"),
+            '\t' => preview.push_str("\\t"),
+            c => preview.push(c),
+        }
+    }
+    preview
+}
+
+/// Does the text look like it is there for a panic, or for formatting?
+fn is_panic_string(text: &str) -> bool {
+    PANIC_MARKERS.iter().any(|marker| text.contains(marker))
+}
+
+/// The payloads of the item's data segments that are copied to a constant
+/// address, along with the memory and address that each is copied to. Empty
+/// for an item standing for several segments, some of which are not.
+fn payloads<'a>(items: &ir::Items, data: &'a [u8], id: ir::Id) -> Vec<(&'a [u8], u32, u64)> {
+    let byte_ranges = items.byte_ranges(id);
+    let addresses = items.data_addresses(id);
+    if byte_ranges.len() != addresses.len() {
+        return vec![];
+    }
+
+    // Each segment's payload is at the end of its bytes, after its headers.
+    byte_ranges
+        .iter()
+        .zip(addresses)
+        .filter_map(|(bytes, (memory, address))| {
+            let len = address.end - address.start;
+            let start = bytes.end.checked_sub(len)?.max(bytes.start);
+            let payload = data.get(start as usize..bytes.end as usize)?;
+            Some((payload, *memory, address.start))
+        })
+        .collect()
+}
+
+/// List the strings in the active data segments, largest first.
+///
+/// A string is a run of printable UTF-8 text that is at least `--min-len`
+/// bytes long; everything else is binary data. Rust does not terminate its
+/// strings, so neighbouring strings are listed as one. A string is referenced
+/// by the functions that load from any of its addresses, or, when the parser
+/// knows of none and it is the only string in its segment, by the items with
+/// an edge to the segment.
+pub fn data(items: &mut ir::Items, data: &[u8], opts: &opt::Data) -> traits::Result<DataResult> {
+    items.compute_predecessors();
+    let items = &*items;
+    let min_len = opts.min_len() as usize;
+
+    let mut result = DataResult {
+        strings: vec![],
+        truncated: Truncated::default(),
+        segments: 0,
+        data_bytes: 0,
+        string_count: 0,
+        string_bytes: 0,
+        panic_count: 0,
+        panic_bytes: 0,
+    };
+    let mut strings = vec![];
+    for item in items.iter() {
+        if !matches!(item.kind(), ir::ItemKind::Data(_)) {
+            continue;
+        }
+        let id = item.id();
+        for (payload, memory, base) in payloads(items, data, id) {
+            result.segments += 1;
+            result.data_bytes += payload.len() as u64;
+
+            let runs = printable_runs(payload, min_len);
+            let only_string = runs.len() == 1;
+            for (start, end) in runs {
+                let text = str::from_utf8(&payload[start..end]).expect("runs are valid UTF-8");
+                let size = (end - start) as u64;
+                result.string_count += 1;
+                result.string_bytes += size;
+                if is_panic_string(text) {
+                    result.panic_count += 1;
+                    result.panic_bytes += size;
+                }
+
+                let address = base + start as u64;
+                let mut referenced_by = items.data_referrers(memory, address..address + size);
+                if referenced_by.is_empty() && only_string {
+                    referenced_by = items
+                        .predecessors(id)
+                        .filter(|&from| from != items.meta_root())
+                        .collect();
+                }
+                strings.push(DataString {
+                    segment: item.name().to_string(),
+                    memory,
+                    address,
+                    offset: start as u64,
+                    size,
+                    preview: preview(text, opts.preview() as usize),
+                    referenced_by: referenced_by
+                        .into_iter()
+                        .map(|id| items[id].name().to_string())
+                        .collect(),
+                });
+            }
+        }
+    }
+
+    // Ties are broken by memory and address, so that the output is
+    // deterministic.
+    strings.sort_by_key(|string| (cmp::Reverse(string.size), string.memory, string.address));
+    let max_strings = opts.max_strings() as usize;
+    let rest = strings.split_off(max_strings.min(strings.len()));
+    result.truncated = Truncated::of(rest.iter().map(|string| string.size));
+    result.strings = strings;
+    Ok(result)
+}
@@ -1,25 +1,379 @@
+use crate::analyses::wire::{CompressedSizes, WireDiff, WireEstimate};
 use crate::formats::json;
 use crate::formats::table::{Align, Table};
-use anyhow::anyhow;
+use crate::formats::truncated::Truncated;
 use csv;
+use foldhash::fast::FixedState;
+use foldhash::{HashMap, HashMapExt};
 use regex;
 use serde::{self, ser::SerializeStruct};
+use serde_derive::Serialize;
 use std::cmp;
-use std::collections::{HashMap, HashSet};
+use std::collections::BTreeMap;
+use std::hash::BuildHasher;
 use std::io;
 use twiggy_ir as ir;
 use twiggy_opt as opt;
 use twiggy_traits as traits;
 
+/// The result of the `diff` analysis.
 #[derive(Debug)]
-struct Diff {
+pub struct DiffResult {
+    matched: Vec<MatchedItem>,
+    added: Vec<DiffItem>,
+    removed: Vec<DiffItem>,
+    total_delta: i64,
     deltas: Vec<DiffEntry>,
+    truncated_ids: Vec<(Option<ir::Id>, Option<ir::Id>)>,
+    whole_binary: bool,
+    crates: Option<Vec<CrateDiff>>,
+    verbose: bool,
+    wire: Option<WireDiff>,
+    compressed: Option<(CompressedSizes, CompressedSizes)>,
 }
 
+impl DiffResult {
+    /// The items found in both binaries, largest change first. Items whose
+    /// size did not change are included.
+    pub fn matched(&self) -> &[MatchedItem] {
+        &self.matched
+    }
+
+    /// The items only found in the new binary, largest first.
+    pub fn added(&self) -> &[DiffItem] {
+        &self.added
+    }
+
+    /// The items only found in the old binary, largest first.
+    pub fn removed(&self) -> &[DiffItem] {
+        &self.removed
+    }
+
+    /// The net change in size, in bytes. This is the change of the whole
+    /// binary, unless only some items were asked for.
+    pub fn total_delta(&self) -> i64 {
+        self.total_delta
+    }
+
+    /// The changed items, largest change first. The final entries summarize
+    /// the truncated rows and the total change.
+    pub fn deltas(&self) -> &[DiffEntry] {
+        &self.deltas
+    }
+
+    /// The change in size of each crate, largest change first, if the diff
+    /// was done by crate. The final entries summarize the truncated rows and
+    /// the total change.
+    pub fn crates(&self) -> Option<&[CrateDiff]> {
+        self.crates.as_deref()
+    }
+
+    /// The change in gzipped size, if it was estimated with `wire_diff`.
+    pub fn wire(&self) -> Option<&WireDiff> {
+        self.wire.as_ref()
+    }
+
+    /// Report the change in gzipped size along with the change in size.
+    pub fn set_wire(&mut self, wire: WireDiff) {
+        self.wire = Some(wire);
+    }
+
+    /// The estimated gzipped size of each item of the old and of the new
+    /// binary, if they were estimated with `compressed_sizes`.
+    pub fn compressed(&self) -> Option<(&CompressedSizes, &CompressedSizes)> {
+        self.compressed.as_ref().map(|(old, new)| (old, new))
+    }
+
+    /// Report the estimated gzipped size of each changed item in both
+    /// binaries along with its change in size.
+    pub fn set_compressed(&mut self, old: CompressedSizes, new: CompressedSizes) {
+        self.compressed = Some((old, new));
+    }
+
+    /// The estimated gzipped sizes of the given entry in the old and the new
+    /// binary, if the sizes were estimated. An item's size is zero in the
+    /// binary it is not in, and `None` where its bytes are not known. The
+    /// truncated rows' sizes are the totals of those that are known, and the
+    /// total row's are those of the whole stream, unless only some items were
+    /// asked for.
+    fn compressed_sizes(&self, entry: &DiffEntry) -> Option<(Option<u64>, Option<u64>)> {
+        let (old, new) = self.compressed.as_ref()?;
+        let sizes = |old_id: Option<ir::Id>, new_id: Option<ir::Id>| {
+            (
+                old_id.map_or(Some(0), |id| old.size(id)),
+                new_id.map_or(Some(0), |id| new.size(id)),
+            )
+        };
+        let sum = |ids: &mut dyn Iterator<Item = (Option<ir::Id>, Option<ir::Id>)>| {
+            let (mut old_total, mut new_total) = (None, None);
+            for (old_id, new_id) in ids {
+                let (old_size, new_size) = sizes(old_id, new_id);
+                if let Some(size) = old_size {
+                    *old_total.get_or_insert(0) += size;
+                }
+                if let Some(size) = new_size {
+                    *new_total.get_or_insert(0) += size;
+                }
+            }
+            (old_total, new_total)
+        };
+
+        let mut truncated = self.truncated_ids.iter().copied();
+        Some(match (entry.status, entry.truncated) {
+            (Some(_), _) => sizes(entry.old_id, entry.new_id),
+            (None, Some(_)) => sum(&mut truncated),
+            (None, None) if self.whole_binary => (Some(old.stream_size()), Some(new.stream_size())),
+            (None, None) => sum(&mut self
+                .deltas
+                .iter()
+                .filter(|entry| entry.status.is_some())
+                .map(|entry| (entry.old_id, entry.new_id))
+                .chain(truncated)),
+        })
+    }
+}
+
+/// The change in an estimated gzipped size, if both sizes are known.
+fn compressed_delta(sizes: (Option<u64>, Option<u64>)) -> Option<i64> {
+    let (old, new) = sizes;
+    Some(new? as i64 - old? as i64)
+}
+
+/// The change in size of the items attributed to one crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrateDiff {
+    name: String,
+    old_size: u64,
+    new_size: u64,
+    top_items: Vec<DiffEntry>,
+    truncated: Option<Truncated>,
+}
+
+impl CrateDiff {
+    /// The crate's name, `[unknown]` for the items that cannot be attributed
+    /// to a crate.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The total size of the crate's items in the old binary, in bytes.
+    pub fn old_size(&self) -> u64 {
+        self.old_size
+    }
+
+    /// The total size of the crate's items in the new binary, in bytes.
+    pub fn new_size(&self) -> u64 {
+        self.new_size
+    }
+
+    /// The crate's new size minus its old size, in bytes.
+    pub fn delta(&self) -> i64 {
+        self.new_size as i64 - self.old_size as i64
+    }
+
+    /// The changed items that contributed most to the crate's change, at most
+    /// three, largest change first.
+    pub fn top_items(&self) -> &[DiffEntry] {
+        &self.top_items
+    }
+
+    /// The crates this row summarizes, if it is the row summarizing the
+    /// crates beyond `max_items`.
+    pub fn truncated(&self) -> Option<Truncated> {
+        self.truncated
+    }
+}
+
+/// An item that is in both the old and the new binary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchedItem {
+    old_id: ir::Id,
+    new_id: ir::Id,
+    old_name: String,
+    new_name: String,
+    old_size: u64,
+    new_size: u64,
+    moved: Option<(String, String)>,
+}
+
+impl MatchedItem {
+    /// The item's id in the old binary.
+    pub fn old_id(&self) -> ir::Id {
+        self.old_id
+    }
+
+    /// The item's id in the new binary.
+    pub fn new_id(&self) -> ir::Id {
+        self.new_id
+    }
+
+    /// The item's name in the old binary.
+    pub fn old_name(&self) -> &str {
+        &self.old_name
+    }
+
+    /// The item's name in the new binary. This only differs from the old name
+    /// when matching names fuzzily with their hashes kept.
+    pub fn new_name(&self) -> &str {
+        &self.new_name
+    }
+
+    /// The item's size in the old binary, in bytes.
+    pub fn old_size(&self) -> u64 {
+        self.old_size
+    }
+
+    /// The item's size in the new binary, in bytes.
+    pub fn new_size(&self) -> u64 {
+        self.new_size
+    }
+
+    /// The item's new size minus its old size, in bytes.
+    pub fn delta(&self) -> i64 {
+        self.new_size as i64 - self.old_size as i64
+    }
+
+    /// The item's category and section in the old binary, if moves are
+    /// tracked and they differ from those in the new binary.
+    pub fn moved_from(&self) -> Option<&str> {
+        self.moved.as_ref().map(|(from, _)| from.as_str())
+    }
+
+    /// The item's category and section in the new binary, if moves are
+    /// tracked and they differ from those in the old binary.
+    pub fn moved_to(&self) -> Option<&str> {
+        self.moved.as_ref().map(|(_, to)| to.as_str())
+    }
+}
+
+/// An item that is only in one of the two binaries.
 #[derive(Clone, Debug, PartialEq, Eq)]
-struct DiffEntry {
+pub struct DiffItem {
+    id: ir::Id,
     name: String,
+    size: u64,
+}
+
+impl DiffItem {
+    /// The item's id, in the binary it is in.
+    pub fn id(&self) -> ir::Id {
+        self.id
+    }
+
+    /// The item's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The item's size, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Whether an item was added, removed, or is in both binaries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffStatus {
+    /// The item is only in the new binary.
+    Added,
+
+    /// The item is only in the old binary.
+    Removed,
+
+    /// The item is in both binaries, and its size or location changed.
+    Changed,
+}
+
+impl DiffStatus {
+    /// The status' name, as used in JSON and CSV output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiffStatus::Added => "added",
+            DiffStatus::Removed => "removed",
+            DiffStatus::Changed => "changed",
+        }
+    }
+}
+
+/// The change in size of a single item between the old and new binaries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffEntry {
+    name: String,
+    old_size: u64,
+    new_size: u64,
     delta: i64,
+    status: Option<DiffStatus>,
+    moved: Option<(String, String)>,
+    truncated: Option<Truncated>,
+    old_id: Option<ir::Id>,
+    new_id: Option<ir::Id>,
+}
+
+impl DiffEntry {
+    /// The item's id in the old binary, unless it was added, or this entry
+    /// summarizes other rows.
+    pub fn old_id(&self) -> Option<ir::Id> {
+        self.old_id
+    }
+
+    /// The item's id in the new binary, unless it was removed, or this entry
+    /// summarizes other rows.
+    pub fn new_id(&self) -> Option<ir::Id> {
+        self.new_id
+    }
+
+    /// The item's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The item's size in the old binary, in bytes, or zero if it was added.
+    /// For the entries summarizing other rows, the sum of their old sizes.
+    pub fn old_size(&self) -> u64 {
+        self.old_size
+    }
+
+    /// The item's size in the new binary, in bytes, or zero if it was
+    /// removed. For the entries summarizing other rows, the sum of their new
+    /// sizes.
+    pub fn new_size(&self) -> u64 {
+        self.new_size
+    }
+
+    /// The item's new size minus its old size, in bytes.
+    pub fn delta(&self) -> i64 {
+        self.delta
+    }
+
+    /// Whether the item was added, removed or changed, or `None` if this entry
+    /// summarizes other rows.
+    pub fn status(&self) -> Option<DiffStatus> {
+        self.status
+    }
+
+    /// Where the item was in the old binary, if it moved.
+    pub fn moved_from(&self) -> Option<&str> {
+        self.moved.as_ref().map(|(from, _)| from.as_str())
+    }
+
+    /// Where the item is in the new binary, if it moved.
+    pub fn moved_to(&self) -> Option<&str> {
+        self.moved.as_ref().map(|(_, to)| to.as_str())
+    }
+
+    /// The rows this entry summarizes, if it is the entry summarizing the rows
+    /// beyond `max_items`.
+    pub fn truncated(&self) -> Option<Truncated> {
+        self.truncated
+    }
+
+    /// The item's name, followed by where it moved, if it did.
+    fn label(&self) -> String {
+        match self.moved {
+            Some((ref from, ref to)) => format!("{} (moved from {} to {})", self.name, from, to),
+            None => self.name.clone(),
+        }
+    }
 }
 
 impl PartialOrd for DiffEntry {
@@ -42,49 +396,346 @@ impl serde::Serialize for DiffEntry {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("DiffEntry", 2)?;
+        let mut state = serializer.serialize_struct("DiffEntry", 7)?;
         state.serialize_field("DeltaBytes", &format!("{:+}", self.delta))?;
-        state.serialize_field("Item", &self.name)?;
+        state.serialize_field("Item", &self.label())?;
+        state.serialize_field("TruncatedCount", &self.truncated.map(|t| t.count()))?;
+        state.serialize_field("TruncatedBytes", &self.truncated.map(|t| t.bytes()))?;
+        state.serialize_field("OldBytes", &self.old_size)?;
+        state.serialize_field("NewBytes", &self.new_size)?;
+        state.serialize_field("Status", &self.status.map(DiffStatus::as_str))?;
         state.end()
     }
 }
 
-impl traits::Emit for Diff {
-    #[cfg(feature = "emit_text")]
-    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        let mut table = Table::with_header(vec![
-            (Align::Right, "Delta Bytes".into()),
-            (Align::Left, "Item".to_string()),
-        ]);
+impl DiffResult {
+    /// The JSON output, as a value that serde can serialize: a row for each
+    /// changed item, or for each crate when grouped by crate. The last row
+    /// has the change in gzipped size, if it was measured.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value(&self) -> impl serde::Serialize + '_ {
+        // Only the last row has the change in gzipped size.
+        let wire = |i: usize, len: usize| self.wire.as_ref().filter(|_| i + 1 == len);
 
-        self.deltas
-            .iter()
-            .map(|entry| vec![format!("{:+}", entry.delta), entry.name.clone()])
-            .for_each(|row| table.add_row(row));
+        if let Some(ref crates) = self.crates {
+            return JsonOutput::Crates(
+                crates
+                    .iter()
+                    .enumerate()
+                    .map(|(i, krate)| JsonCrate {
+                        krate: &krate.name,
+                        old_size: krate.old_size,
+                        new_size: krate.new_size,
+                        delta_bytes: krate.delta(),
+                        truncated_count: krate.truncated.map(|t| t.count()),
+                        truncated_bytes: krate.truncated.map(|t| t.bytes()),
+                        old_gzipped_size: wire(i, crates.len()).map(WireDiff::old_size),
+                        new_gzipped_size: wire(i, crates.len()).map(WireDiff::new_size),
+                        gzipped_delta_bytes: wire(i, crates.len()).map(WireDiff::delta),
+                        top_items: krate
+                            .top_items
+                            .iter()
+                            .map(|entry| JsonTopItem {
+                                delta_bytes: entry.delta,
+                                name: &entry.name,
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            );
+        }
 
-        write!(dest, "{}", &table)?;
-        Ok(())
+        JsonOutput::Deltas(
+            self.deltas
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    let last = wire(i, self.deltas.len());
+                    let estimated_gzipped_delta_bytes = match self.wire {
+                        Some(ref wire) if last.is_none() => {
+                            wire.estimate(entry).and_then(WireEstimate::delta)
+                        }
+                        _ => None,
+                    };
+                    let compressed = self.compressed_sizes(entry);
+                    JsonEntry {
+                        delta_bytes: entry.delta,
+                        name: &entry.name,
+                        old_size: entry.old_size,
+                        new_size: entry.new_size,
+                        status: entry.status.map(DiffStatus::as_str),
+                        moved_from: entry.moved.as_ref().map(|(from, _)| from.as_str()),
+                        moved_to: entry.moved.as_ref().map(|(_, to)| to.as_str()),
+                        truncated_count: entry.truncated.map(|t| t.count()),
+                        truncated_bytes: entry.truncated.map(|t| t.bytes()),
+                        old_gzipped_size: last.map(WireDiff::old_size),
+                        new_gzipped_size: last.map(WireDiff::new_size),
+                        gzipped_delta_bytes: last.map(WireDiff::delta),
+                        estimated_gzipped_delta_bytes,
+                        old_compressed_size: compressed.and_then(|(old, _)| old),
+                        new_compressed_size: compressed.and_then(|(_, new)| new),
+                        compressed_delta_bytes: compressed.and_then(compressed_delta),
+                    }
+                })
+                .collect(),
+        )
     }
+}
 
-    #[cfg(feature = "emit_json")]
-    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        let mut arr = json::array(dest)?;
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum JsonOutput<'a> {
+    Deltas(Vec<JsonEntry<'a>>),
+    Crates(Vec<JsonCrate<'a>>),
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonEntry<'a> {
+    delta_bytes: i64,
+    name: &'a str,
+    old_size: u64,
+    new_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    moved_from: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    moved_to: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_gzipped_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_gzipped_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gzipped_delta_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimated_gzipped_delta_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_compressed_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_compressed_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed_delta_bytes: Option<i64>,
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonCrate<'a> {
+    #[serde(rename = "crate")]
+    krate: &'a str,
+    old_size: u64,
+    new_size: u64,
+    delta_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_gzipped_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_gzipped_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gzipped_delta_bytes: Option<i64>,
+    top_items: Vec<JsonTopItem<'a>>,
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonTopItem<'a> {
+    delta_bytes: i64,
+    name: &'a str,
+}
 
-        for entry in &self.deltas {
-            let mut obj = arr.object()?;
-            obj.field("delta_bytes", entry.delta as f64)?;
-            obj.field("name", entry.name.as_str())?;
+impl traits::Emit for DiffResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        if let Some(ref crates) = self.crates {
+            let mut table = Table::with_header(vec![
+                (Align::Right, "Old Bytes".into()),
+                (Align::Right, "New Bytes".into()),
+                (Align::Right, "Delta Bytes".into()),
+                (Align::Left, "Crate".to_string()),
+            ]);
+            for krate in crates {
+                table.add_row(vec![
+                    krate.old_size.to_string(),
+                    krate.new_size.to_string(),
+                    format!("{:+}", krate.delta()),
+                    krate.name.clone(),
+                ]);
+                if self.verbose {
+                    for entry in &krate.top_items {
+                        table.add_row(vec![
+                            String::new(),
+                            String::new(),
+                            format!("{:+}", entry.delta),
+                            format!("  ⤷ {}", entry.label()),
+                        ]);
+                    }
+                }
+            }
+            write!(dest, "{}", &table)?;
+            if let Some(ref wire) = self.wire {
+                writeln!(dest)?;
+                writeln!(dest, "{}", wire_summary(wire))?;
+            }
+            return Ok(());
+        }
+
+        let mut header = vec![(Align::Right, "Delta Bytes".into())];
+        if self.wire.is_some() {
+            header.push((Align::Right, "Gzipped Delta".into()));
+        }
+        if self.compressed.is_some() {
+            header.push((Align::Right, "Old Compressed".into()));
+            header.push((Align::Right, "New Compressed".into()));
+            header.push((Align::Right, "Compressed Delta".into()));
+            writeln!(
+                dest,
+                "Compressed sizes are estimates: each binary is deflated as one gzip stream, in \
+                 file order, flushed after each item, and each item gets the bytes written \
+                 since the previous flush, less the flush marker.\n"
+            )?;
         }
+        header.push((Align::Left, "Item".to_string()));
+        let mut table = Table::with_header(header);
+        let unknown = || "?".to_string();
 
+        for (i, entry) in self.deltas.iter().enumerate() {
+            let mut row = vec![format!("{:+}", entry.delta)];
+            if let Some(ref wire) = self.wire {
+                row.push(if i + 1 == self.deltas.len() {
+                    format!("{:+}", wire.delta())
+                } else {
+                    wire.estimate(entry)
+                        .map_or_else(String::new, WireEstimate::label)
+                });
+            }
+            if let Some(sizes) = self.compressed_sizes(entry) {
+                row.push(sizes.0.map_or_else(unknown, |size| size.to_string()));
+                row.push(sizes.1.map_or_else(unknown, |size| size.to_string()));
+                row.push(
+                    compressed_delta(sizes).map_or_else(unknown, |delta| format!("{:+}", delta)),
+                );
+            }
+            row.push(entry.label());
+            table.add_row(row);
+        }
+
+        write!(dest, "{}", &table)?;
+        if let Some((ref old, ref new)) = self.compressed {
+            writeln!(
+                dest,
+                "compressed: {} -> {} bytes ({:+}) for the whole stream",
+                old.stream_size(),
+                new.stream_size(),
+                new.stream_size() as i64 - old.stream_size() as i64,
+            )?;
+            writeln!(
+                dest,
+                "gzipped in one go: {} -> {} bytes ({:+})",
+                old.whole_file_size(),
+                new.whole_file_size(),
+                new.whole_file_size() as i64 - old.whole_file_size() as i64,
+            )?;
+        }
+        if let Some(ref wire) = self.wire {
+            writeln!(dest)?;
+            writeln!(dest, "{}", wire_summary(wire))?;
+            writeln!(
+                dest,
+                "~ Estimated: how many more bytes the gzipped binary takes up with the \
+                 item's bytes than with them zeroed out, in the new binary minus the old."
+            )?;
+        }
         Ok(())
     }
 
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value())?)
+    }
+
     #[cfg(feature = "emit_csv")]
-    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         let mut wtr = csv::Writer::from_writer(dest);
 
-        for entry in &self.deltas {
-            wtr.serialize(entry)?;
+        if let Some(ref crates) = self.crates {
+            #[derive(Serialize, Debug)]
+            #[serde(rename_all = "PascalCase")]
+            struct CsvRecord<'a> {
+                #[serde(rename = "Crate")]
+                krate: &'a str,
+                old_bytes: u64,
+                new_bytes: u64,
+                delta_bytes: String,
+                truncated_count: Option<usize>,
+                truncated_bytes: Option<i64>,
+            }
+
+            for krate in crates {
+                wtr.serialize(CsvRecord {
+                    krate: &krate.name,
+                    old_bytes: krate.old_size,
+                    new_bytes: krate.new_size,
+                    delta_bytes: format!("{:+}", krate.delta()),
+                    truncated_count: krate.truncated.map(|truncated| truncated.count()),
+                    truncated_bytes: krate.truncated.map(|truncated| truncated.bytes()),
+                })?;
+                wtr.flush()?;
+            }
+            return Ok(());
+        }
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord {
+            delta_bytes: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            gzipped_delta_bytes: Option<String>,
+            item: String,
+            truncated_count: Option<usize>,
+            truncated_bytes: Option<i64>,
+            old_bytes: u64,
+            new_bytes: u64,
+            status: Option<&'static str>,
+            // Only with compressed sizes, when an entry without one is empty.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            old_compressed_bytes: Option<Option<u64>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            new_compressed_bytes: Option<Option<u64>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            compressed_delta_bytes: Option<Option<String>>,
+        }
+
+        for (i, entry) in self.deltas.iter().enumerate() {
+            let compressed = self.compressed_sizes(entry);
+            wtr.serialize(CsvRecord {
+                delta_bytes: format!("{:+}", entry.delta),
+                gzipped_delta_bytes: self.wire.as_ref().map(|wire| {
+                    if i + 1 == self.deltas.len() {
+                        format!("{:+}", wire.delta())
+                    } else {
+                        wire.estimate(entry)
+                            .map_or_else(String::new, WireEstimate::label)
+                    }
+                }),
+                item: entry.label(),
+                truncated_count: entry.truncated.map(|t| t.count()),
+                truncated_bytes: entry.truncated.map(|t| t.bytes()),
+                old_bytes: entry.old_size,
+                new_bytes: entry.new_size,
+                status: entry.status.map(DiffStatus::as_str),
+                old_compressed_bytes: compressed.map(|(old, _)| old),
+                new_compressed_bytes: compressed.map(|(_, new)| new),
+                compressed_delta_bytes: compressed
+                    .map(|sizes| compressed_delta(sizes).map(|delta| format!("{:+}", delta))),
+            })?;
             wtr.flush()?;
         }
 
@@ -92,124 +743,630 @@ impl traits::Emit for Diff {
     }
 }
 
+/// The line summarizing the change in gzipped size.
+#[cfg(feature = "emit_text")]
+fn wire_summary(wire: &WireDiff) -> String {
+    format!(
+        "Gzipped: {} -> {} bytes ({:+})",
+        wire.old_size(),
+        wire.new_size(),
+        wire.delta()
+    )
+}
+
 /// Compute the diff between two sets of items.
 pub fn diff(
     old_items: &mut ir::Items,
     new_items: &mut ir::Items,
     opts: &opt::Diff,
-) -> anyhow::Result<Box<dyn traits::Emit>> {
-    let max_items = opts.max_items() as usize;
+) -> traits::Result<DiffResult> {
+    diff_items(old_items, new_items, opts)
+}
 
-    // Given a set of items, create a HashMap of the items' names and sizes.
-    fn get_names_and_sizes(items: &ir::Items) -> HashMap<&str, i64> {
-        items
-            .iter()
-            .map(|item| (item.name(), i64::from(item.size())))
-            .collect()
-    }
+/// Compare the items of an old and a new binary, matching them up by name
+/// according to `opts.name_matching()`.
+///
+/// With `opts.disambiguators()` set to strip, the hashes that `rustc` appends
+/// to symbol names are removed from the items' names, and items are matched
+/// by the names without them. Items whose names are the same without their
+/// hashes are matched with each other as a group, closest sizes first, and
+/// each of them is still counted with its own size.
+///
+/// Items are matched by name alone, so an item whose category or section
+/// changed is still matched. With `opts.track_moves()`, such items are
+/// reported as moved, along with where they were and are.
+pub fn diff_items(
+    old_items: &ir::Items,
+    new_items: &ir::Items,
+    opts: &opt::Diff,
+) -> traits::Result<DiffResult> {
+    let disambiguators = opts.disambiguators();
+    let matching = match disambiguators {
+        opt::Disambiguators::Keep => opts.name_matching(),
+        opt::Disambiguators::Strip => opt::NameMatching::Fuzzy,
+    };
 
-    // Collect the names and sizes of the items in the old and new collections.
-    let old_sizes = get_names_and_sizes(old_items);
-    let new_sizes = get_names_and_sizes(new_items);
-
-    // Given an item name, create a `DiffEntry` object representing the
-    // change in size, or an error if the name could not be found in
-    // either of the item collections.
-    let get_item_delta = |name: String| -> anyhow::Result<DiffEntry> {
-        let old_size = old_sizes.get::<str>(&name);
-        let new_size = new_sizes.get::<str>(&name);
-        let delta: i64 = match (old_size, new_size) {
-            (Some(old_size), Some(new_size)) => new_size - old_size,
-            (Some(old_size), None) => -old_size,
-            (None, Some(new_size)) => *new_size,
-            (None, None) => {
-                return Err(anyhow!("Could not find item with name `{}`", name));
+    // If arguments were given to the command, we should filter out items that
+    // do not match any of the given names or expressions.
+    let regexps = if opts.using_regexps() && !opts.items().is_empty() {
+        Some(regex::RegexSet::new(opts.items())?)
+    } else {
+        None
+    };
+    let is_requested = |name: &str| -> bool {
+        let name = display_name(disambiguators, name);
+        match regexps {
+            _ if opts.items().is_empty() => true,
+            Some(ref regexps) => regexps.is_match(name),
+            None => opts.items().iter().any(|item| item == name),
+        }
+    };
+
+    let old_groups = KeyGroups::new(old_items, matching);
+    let new_groups = KeyGroups::new(new_items, matching);
+
+    let mut matched = vec![];
+    let mut added = vec![];
+    let mut removed = vec![];
+    let mut old_is_matched = vec![false; old_groups.groups.len()];
+    for group in &new_groups.groups {
+        let old = match old_groups.find(group) {
+            Some(i) => {
+                old_is_matched[i] = true;
+                &old_groups.groups[i].items[..]
             }
+            None => &[],
         };
-        Ok(DiffEntry { name, delta })
+        let (pairs, old_rest, new_rest) = pair_by_size(old, &group.items);
+        matched.extend(
+            pairs
+                .into_iter()
+                .filter(|(old, new)| is_requested(old.name()) || is_requested(new.name()))
+                .map(|(old, new)| {
+                    let moved = if opts.track_moves() {
+                        moved(old_items, old, new_items, new)
+                    } else {
+                        None
+                    };
+                    matched_item(old, new, moved, disambiguators)
+                }),
+        );
+        removed.extend(old_rest);
+        added.extend(new_rest);
+    }
+    removed.extend(
+        old_groups
+            .groups
+            .iter()
+            .zip(old_is_matched)
+            .filter(|(_, is_matched)| !is_matched)
+            .flat_map(|(group, _)| group.items.iter().copied()),
+    );
+    if opts.track_moves() {
+        let moves;
+        (moves, removed, added) = pair_moves(old_items, removed, new_items, added, matching);
+        matched.extend(
+            moves
+                .into_iter()
+                .filter(|(old, new, _)| is_requested(old.name()) || is_requested(new.name()))
+                .map(|(old, new, moved)| matched_item(old, new, Some(moved), disambiguators)),
+        );
+    }
+    let into_diff_items = |items: Vec<&ir::Item>| -> Vec<DiffItem> {
+        items
+            .into_iter()
+            .filter(|item| is_requested(item.name()))
+            .map(|item| DiffItem {
+                id: item.id(),
+                name: display_name(disambiguators, item.name()).to_string(),
+                size: item.size(),
+            })
+            .collect()
     };
+    let mut added = into_diff_items(added);
+    let mut removed = into_diff_items(removed);
+
+    matched.sort_by(|a, b| {
+        b.delta()
+            .abs()
+            .cmp(&a.delta().abs())
+            .then_with(|| a.new_name.cmp(&b.new_name))
+            .then_with(|| a.old_name.cmp(&b.old_name))
+            .then_with(|| a.delta().cmp(&b.delta()))
+    });
+    added.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    removed.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+    // Flatten the changed items into `DiffEntry` objects, and sort them.
+    let mut deltas: Vec<_> = matched
+        .iter()
+        .filter(|item| item.delta() != 0 || item.moved.is_some())
+        .map(|item| DiffEntry {
+            name: item.new_name.clone(),
+            old_size: item.old_size,
+            new_size: item.new_size,
+            delta: item.delta(),
+            status: Some(DiffStatus::Changed),
+            moved: item.moved.clone(),
+            truncated: None,
+            old_id: Some(item.old_id),
+            new_id: Some(item.new_id),
+        })
+        .chain(added.iter().map(|item| DiffEntry {
+            name: item.name.clone(),
+            old_size: 0,
+            new_size: item.size,
+            delta: item.size as i64,
+            status: Some(DiffStatus::Added),
+            moved: None,
+            truncated: None,
+            old_id: None,
+            new_id: Some(item.id),
+        }))
+        .chain(removed.iter().map(|item| DiffEntry {
+            name: item.name.clone(),
+            old_size: item.size,
+            new_size: 0,
+            delta: -(item.size as i64),
+            status: Some(DiffStatus::Removed),
+            moved: None,
+            truncated: None,
+            old_id: Some(item.id),
+            new_id: None,
+        }))
+        .collect();
+    deltas.sort();
+
+    // If specifying arguments were not given, the total is the net change of
+    // the whole binary, otherwise it is the sum of the changes listed.
+    let total_sizes = if opts.items().is_empty() {
+        (old_items.size(), new_items.size())
+    } else {
+        sum_sizes(&deltas)
+    };
+    let total_delta = total_sizes.1 as i64 - total_sizes.0 as i64;
+    let crates = if opts.by_crate() {
+        let (crates, total) = crate_diffs(old_items, new_items, opts, &is_requested, &deltas);
+        Some(summarize_crates(
+            crates,
+            opts.max_items() as usize,
+            total,
+            old_items.size(),
+        ))
+    } else {
+        None
+    };
+    let truncated_ids = deltas
+        .iter()
+        .skip(opts.max_items() as usize)
+        .map(|entry| (entry.old_id, entry.new_id))
+        .collect();
+    let deltas = summarize(
+        deltas,
+        opts.max_items() as usize,
+        total_sizes,
+        old_items.size(),
+    );
+
+    Ok(DiffResult {
+        matched,
+        added,
+        removed,
+        total_delta,
+        deltas,
+        truncated_ids,
+        whole_binary: opts.items().is_empty(),
+        crates,
+        verbose: opts.verbose(),
+        wire: None,
+        compressed: None,
+    })
+}
+
+/// The items of one binary, grouped by their matching key.
+///
+/// Each item's key is computed and hashed once. Groups are looked up by hash,
+/// and keys are only compared in full when their hashes are equal, so names
+/// are neither hashed repeatedly nor copied to build the groups.
+struct KeyGroups<'a> {
+    groups: Vec<KeyGroup<'a>>,
+    by_hash: HashMap<u64, Vec<usize>>,
+}
+
+/// The items that share a matching key, in `Id` order.
+struct KeyGroup<'a> {
+    hash: u64,
+    key: &'a str,
+    items: Vec<&'a ir::Item>,
+}
+
+impl<'a> KeyGroups<'a> {
+    fn new(items: &'a ir::Items, matching: opt::NameMatching) -> KeyGroups<'a> {
+        let hasher = FixedState::default();
+        let mut groups = KeyGroups {
+            groups: vec![],
+            by_hash: HashMap::new(),
+        };
+        for item in items.iter() {
+            let key = match_key(matching, item.name());
+            let hash = hasher.hash_one(key);
+            match groups.position(hash, key) {
+                Some(i) => groups.groups[i].items.push(item),
+                None => {
+                    groups
+                        .by_hash
+                        .entry(hash)
+                        .or_default()
+                        .push(groups.groups.len());
+                    groups.groups.push(KeyGroup {
+                        hash,
+                        key,
+                        items: vec![item],
+                    });
+                }
+            }
+        }
+        groups
+    }
+
+    /// The index of the group with the same key as the given group of another
+    /// binary's items, if there is one.
+    fn find(&self, group: &KeyGroup<'_>) -> Option<usize> {
+        self.position(group.hash, group.key)
+    }
+
+    fn position(&self, hash: u64, key: &str) -> Option<usize> {
+        self.by_hash
+            .get(&hash)?
+            .iter()
+            .copied()
+            .find(|&i| self.groups[i].key == key)
+    }
+}
+
+/// Pair up old and new items that share a matching key, such as several
+/// items with the same name, so that the sizes of paired items are as close as
+/// possible. Returns the pairs, and the old and new items left unpaired.
+#[allow(clippy::type_complexity)]
+fn pair_by_size<'a>(
+    old: &[&'a ir::Item],
+    new: &[&'a ir::Item],
+) -> (
+    Vec<(&'a ir::Item, &'a ir::Item)>,
+    Vec<&'a ir::Item>,
+    Vec<&'a ir::Item>,
+) {
+    if old.len() == 1 && new.len() == 1 {
+        return (vec![(old[0], new[0])], vec![], vec![]);
+    }
 
-    // Given a result returned by `get_item_delta`, return false if the result
-    // represents an unchanged item. Ignore errors, these are handled separately.
-    let unchanged_items_filter = |res: &anyhow::Result<DiffEntry>| -> bool {
-        if let Ok(DiffEntry { delta: 0, .. }) = res {
-            false
+    // Both sides are sorted by size, ties broken by position so that the result
+    // only depends on the inputs' order, and merged: the next smallest items
+    // of each side are paired, unless the side with more items left has one
+    // after its next that is closer in size to the other side's next.
+    let by_size = |items: &[&ir::Item]| {
+        let mut sorted: Vec<usize> = (0..items.len()).collect();
+        sorted.sort_by_key(|&i| (items[i].size(), i));
+        sorted
+    };
+    let (old_sorted, new_sorted) = (by_size(old), by_size(new));
+    let mut old_paired = vec![false; old.len()];
+    let mut new_paired = vec![false; new.len()];
+    let mut pairs = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old_sorted.len() && j < new_sorted.len() {
+        let (o, n) = (old[old_sorted[i]], new[new_sorted[j]]);
+        let (old_left, new_left) = (old_sorted.len() - i, new_sorted.len() - j);
+        let closer = |next: &ir::Item, item: &ir::Item, other: &ir::Item| {
+            next.size().abs_diff(other.size()) < item.size().abs_diff(other.size())
+        };
+        if old_left > new_left && closer(old[old_sorted[i + 1]], o, n) {
+            i += 1;
+        } else if new_left > old_left && closer(new[new_sorted[j + 1]], n, o) {
+            j += 1;
         } else {
-            true
+            old_paired[old_sorted[i]] = true;
+            new_paired[new_sorted[j]] = true;
+            pairs.push((o, n));
+            i += 1;
+            j += 1;
         }
+    }
+
+    let rest = |items: &[&'a ir::Item], paired: &[bool]| -> Vec<&'a ir::Item> {
+        items
+            .iter()
+            .zip(paired)
+            .filter(|(_, &paired)| !paired)
+            .map(|(&item, _)| item)
+            .collect()
     };
+    let old_rest = rest(old, &old_paired);
+    let new_rest = rest(new, &new_paired);
+    (pairs, old_rest, new_rest)
+}
 
-    // Create a set of item names from the new and old item collections.
-    let names = old_sizes
-        .keys()
-        .chain(new_sizes.keys())
-        .map(|k| k.to_string());
+fn matched_item(
+    old: &ir::Item,
+    new: &ir::Item,
+    moved: Option<(String, String)>,
+    disambiguators: opt::Disambiguators,
+) -> MatchedItem {
+    MatchedItem {
+        old_id: old.id(),
+        new_id: new.id(),
+        old_name: display_name(disambiguators, old.name()).to_string(),
+        new_name: display_name(disambiguators, new.name()).to_string(),
+        old_size: old.size(),
+        new_size: new.size(),
+        moved,
+    }
+}
 
-    // If arguments were given to the command, we should filter out items that
-    // do not match any of the given names or expressions.
-    let names: HashSet<String> = if !opts.items().is_empty() {
-        if opts.using_regexps() {
-            let regexps = regex::RegexSet::new(opts.items())?;
-            names.filter(|name| regexps.is_match(name)).collect()
-        } else {
-            let item_names = opts.items().iter().collect::<HashSet<_>>();
-            names.filter(|name| item_names.contains(&name)).collect()
+/// Pair up removed and added items that are the same symbol in a different
+/// category or section, such as a function that became a data segment, whose
+/// names only differ in how they are decorated. Returns the pairs along with
+/// where each item was and is, and the removed and added items left unpaired.
+#[allow(clippy::type_complexity)]
+fn pair_moves<'a>(
+    old_items: &ir::Items,
+    removed: Vec<&'a ir::Item>,
+    new_items: &ir::Items,
+    added: Vec<&'a ir::Item>,
+    matching: opt::NameMatching,
+) -> (
+    Vec<(&'a ir::Item, &'a ir::Item, (String, String))>,
+    Vec<&'a ir::Item>,
+    Vec<&'a ir::Item>,
+) {
+    let mut by_symbol: BTreeMap<&str, (Vec<&ir::Item>, Vec<&ir::Item>)> = BTreeMap::new();
+    for item in removed {
+        let symbol = match_key(matching, symbol_name(item.name()));
+        by_symbol.entry(symbol).or_default().0.push(item);
+    }
+    for item in added {
+        let symbol = match_key(matching, symbol_name(item.name()));
+        by_symbol.entry(symbol).or_default().1.push(item);
+    }
+
+    let mut moves = vec![];
+    let mut removed = vec![];
+    let mut added = vec![];
+    for (old, new) in by_symbol.into_values() {
+        let (pairs, old_rest, new_rest) = pair_by_size(&old, &new);
+        for (old, new) in pairs {
+            match moved(old_items, old, new_items, new) {
+                Some(moved) => moves.push((old, new, moved)),
+                None => {
+                    removed.push(old);
+                    added.push(new);
+                }
+            }
         }
-    } else {
-        names.collect()
+        removed.extend(old_rest);
+        added.extend(new_rest);
+    }
+    (moves, removed, added)
+}
+
+/// The name of the symbol that an item's name refers to, without the
+/// decorations that say what kind of item it is.
+fn symbol_name(name: &str) -> &str {
+    let quoted = |prefix: &str| {
+        name.strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix('"'))
+            .and_then(|rest| rest.strip_suffix('"'))
     };
+    if let Some(symbol) = quoted("data segment ").or_else(|| quoted("export ")) {
+        return symbol;
+    }
+    match name.strip_prefix("import ") {
+        Some(import) => import.split_once("::").map_or(import, |(_, field)| field),
+        None => name,
+    }
+}
 
-    // Iterate through the set of item names, and use the closure above to map
-    // each item into a `DiffEntry` object. Then, sort the collection.
-    let mut deltas = names
+/// Where the old item was and the new item is, if those differ.
+fn moved(
+    old_items: &ir::Items,
+    old: &ir::Item,
+    new_items: &ir::Items,
+    new: &ir::Item,
+) -> Option<(String, String)> {
+    let from = location(old_items, old);
+    let to = location(new_items, new);
+    if from != to {
+        Some((from, to))
+    } else {
+        None
+    }
+}
+
+/// Where an item is in its binary, for telling whether it moved: its category,
+/// and the section it is in, named after the section's own item.
+fn location(items: &ir::Items, item: &ir::Item) -> String {
+    let section = item
+        .id()
+        .containing_section()
+        .and_then(|id| items.get(id))
+        .map(|section| section.name().trim_end_matches(" headers"));
+    match section {
+        Some(section) => format!("{} in {}", item.category(), section),
+        None => item.category().to_string(),
+    }
+}
+
+/// The part of an item's name that is compared when matching items between
+/// the old and new binaries.
+fn match_key(matching: opt::NameMatching, name: &str) -> &str {
+    match matching {
+        opt::NameMatching::Exact => name,
+        opt::NameMatching::Fuzzy => super::strip_hash(name),
+    }
+}
+
+/// An item's name as it is shown, without its hash if the hashes are stripped.
+fn display_name(disambiguators: opt::Disambiguators, name: &str) -> &str {
+    match disambiguators {
+        opt::Disambiguators::Keep => name,
+        opt::Disambiguators::Strip => super::strip_hash(name),
+    }
+}
+
+/// The bucket for items whose names do not start with a crate's name.
+const UNKNOWN_CRATE: &str = "[unknown]";
+
+/// Sum up the sizes of the requested items of each binary by crate, and pick
+/// the three changed items that contributed most to each crate's change.
+/// Returns the crates whose size changed, and the old and new sizes of all
+/// crates.
+///
+/// Unless only some items were asked for, whatever the items do not account
+/// for is put in the `[unknown]` bucket, so that the crates' changes add up to
+/// the change of the whole binary.
+fn crate_diffs(
+    old_items: &ir::Items,
+    new_items: &ir::Items,
+    opts: &opt::Diff,
+    is_requested: &dyn Fn(&str) -> bool,
+    deltas: &[DiffEntry],
+) -> (Vec<CrateDiff>, (u64, u64)) {
+    let mut sizes: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+    for (items, new) in [(old_items, false), (new_items, true)] {
+        let mut add = |krate, size| {
+            let sizes = sizes.entry(krate).or_default();
+            if new {
+                sizes.1 += size;
+            } else {
+                sizes.0 += size;
+            }
+        };
+        let mut total = 0;
+        for item in items.iter().filter(|item| is_requested(item.name())) {
+            add(
+                super::crate_name(item.name()).unwrap_or(UNKNOWN_CRATE),
+                item.size(),
+            );
+            total += item.size();
+        }
+        if opts.items().is_empty() && items.size() > total {
+            add(UNKNOWN_CRATE, items.size() - total);
+        }
+    }
+    let total = sizes
+        .values()
+        .fold((0, 0), |(old, new), sizes| (old + sizes.0, new + sizes.1));
+
+    let mut top_items: BTreeMap<&str, Vec<DiffEntry>> = BTreeMap::new();
+    for entry in deltas.iter().filter(|entry| entry.delta != 0) {
+        let krate = super::crate_name(&entry.name).unwrap_or(UNKNOWN_CRATE);
+        let top = top_items.entry(krate).or_default();
+        if top.len() < 3 {
+            top.push(entry.clone());
+        }
+    }
+
+    let mut crates: Vec<CrateDiff> = sizes
         .into_iter()
-        .map(get_item_delta)
-        .filter(unchanged_items_filter)
-        .collect::<anyhow::Result<Vec<_>>>()?;
-    deltas.sort();
+        .filter(|(_, (old_size, new_size))| old_size != new_size)
+        .map(|(name, (old_size, new_size))| CrateDiff {
+            name: name.to_string(),
+            old_size,
+            new_size,
+            top_items: top_items.remove(name).unwrap_or_default(),
+            truncated: None,
+        })
+        .collect();
+    crates.sort_by(|a, b| {
+        b.delta()
+            .abs()
+            .cmp(&a.delta().abs())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    (crates, total)
+}
 
-    // Create an entry to summarize the diff rows that will be truncated.
-    let (rem_cnt, rem_delta): (u32, i64) = deltas
-        .iter()
-        .skip(max_items)
-        .fold((0, 0), |(cnt, rem_delta), DiffEntry { delta, .. }| {
-            (cnt + 1, rem_delta + delta)
+/// Truncate the sorted crates to `max_items` rows, and append rows summarizing
+/// the truncated rows and the total change. The truncated rows' change is
+/// described relative to the old binary's size.
+fn summarize_crates(
+    mut crates: Vec<CrateDiff>,
+    max_items: usize,
+    (old_size, new_size): (u64, u64),
+    old_items_size: u64,
+) -> Vec<CrateDiff> {
+    let total = CrateDiff {
+        name: format!("Σ [{} Changed Crates]", crates.len()),
+        old_size,
+        new_size,
+        top_items: vec![],
+        truncated: None,
+    };
+    if crates.len() > max_items {
+        let rest = crates.split_off(max_items);
+        let truncated = Truncated::of_deltas(rest.iter().map(CrateDiff::delta));
+        crates.push(CrateDiff {
+            name: truncated.label(old_items_size),
+            old_size: rest.iter().map(|krate| krate.old_size).sum(),
+            new_size: rest.iter().map(|krate| krate.new_size).sum(),
+            top_items: vec![],
+            truncated: Some(truncated),
         });
+    }
+    crates.push(total);
+    crates
+}
+
+/// The total old and new sizes of the given entries.
+fn sum_sizes(deltas: &[DiffEntry]) -> (u64, u64) {
+    deltas.iter().fold((0, 0), |(old, new), entry| {
+        (old + entry.old_size, new + entry.new_size)
+    })
+}
+
+/// Truncate the sorted deltas to `max_items` rows, and append rows summarizing
+/// the truncated rows and the total change, from the old and new sizes in
+/// `total_sizes`. The truncated rows' change is described relative to the old
+/// binary's size.
+fn summarize(
+    mut deltas: Vec<DiffEntry>,
+    max_items: usize,
+    total_sizes: (u64, u64),
+    old_items_size: u64,
+) -> Vec<DiffEntry> {
+    // Create an entry to summarize the diff rows that will be truncated.
+    let rest = &deltas[deltas.len().min(max_items)..];
+    let truncated = Truncated::of_deltas(rest.iter().map(|entry| entry.delta));
+    let (old_size, new_size) = sum_sizes(rest);
     let remaining = DiffEntry {
-        name: format!("... and {} more.", rem_cnt),
-        delta: rem_delta,
+        name: truncated.label(old_items_size),
+        old_size,
+        new_size,
+        delta: truncated.bytes(),
+        status: None,
+        moved: None,
+        truncated: Some(truncated),
+        old_id: None,
+        new_id: None,
     };
 
     // Create a `DiffEntry` representing the net change, and total row count.
-    // If specifying arguments were not given, calculate the total net changes,
-    // otherwise find the total values only for items in the the deltas collection.
-    let (total_cnt, total_delta) = if opts.items().is_empty() {
-        (
-            deltas.len(),
-            i64::from(new_items.size()) - i64::from(old_items.size()),
-        )
-    } else {
-        deltas
-            .iter()
-            .fold((0, 0), |(cnt, rem_delta), DiffEntry { delta, .. }| {
-                (cnt + 1, rem_delta + delta)
-            })
-    };
     let total = DiffEntry {
-        name: format!("Σ [{} Total Rows]", total_cnt),
-        delta: total_delta,
+        name: format!("Σ [{} Total Rows]", deltas.len()),
+        old_size: total_sizes.0,
+        new_size: total_sizes.1,
+        delta: total_sizes.1 as i64 - total_sizes.0 as i64,
+        status: None,
+        moved: None,
+        truncated: None,
+        old_id: None,
+        new_id: None,
     };
 
-    // Now that the 'remaining' and 'total' summary entries have been created,
-    // truncate the vector of deltas before we box up the result, and push
-    // the remaining and total rows to the deltas vector.
     deltas.truncate(max_items);
-    if rem_cnt > 0 {
+    if !truncated.is_empty() {
         deltas.push(remaining);
     }
     deltas.push(total);
-
-    // Return the results so that they can be emitted.
-    let diff = Diff { deltas };
-    Ok(Box::new(diff) as Box<_>)
+    deltas
 }
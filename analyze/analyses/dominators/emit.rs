@@ -1,4 +1,3 @@
-use std::collections::BTreeMap;
 use std::io;
 
 use csv;
@@ -9,13 +8,49 @@ use twiggy_opt as opt;
 use twiggy_traits as traits;
 
 use super::UnreachableItemsSummary;
-use crate::analyses::dominators::DominatorTree;
+use crate::analyses::dominators::DominatorsResult;
+#[cfg(feature = "emit_text")]
+use crate::formats::dot::Digraph;
 use crate::formats::json;
 use crate::formats::table::{Align, Table};
 
-impl traits::Emit for DominatorTree {
+impl DominatorsResult {
+    /// The JSON output, as a value that serde can serialize: the subtree of
+    /// each requested item, and a summary of the unreachable items, if any.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        let tree_items = self
+            .items
+            .iter()
+            .map(|&id| {
+                let mut row = 0;
+                json_item(
+                    items,
+                    &self.opts,
+                    &self.tree,
+                    start_depth(items, id),
+                    &mut row,
+                    id,
+                )
+            })
+            .collect();
+        let summary = self.unreachable_items_summary.as_ref().map(|summary| {
+            vec![JsonSummary {
+                name: format!("[{} Unreachable Items]", summary.count),
+                retained_size: summary.size,
+                retained_size_percent: summary.size_percent,
+            }]
+        });
+        JsonTree {
+            items: tree_items,
+            summary,
+        }
+    }
+}
+
+impl traits::Emit for DominatorsResult {
     #[cfg(feature = "emit_text")]
-    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         let mut table = Table::with_header(vec![
             (Align::Right, "Retained Bytes".to_string()),
             (Align::Right, "Retained %".to_string()),
@@ -23,12 +58,11 @@ impl traits::Emit for DominatorTree {
         ]);
 
         let opts = &self.opts;
-        let mut row = 0 as u32;
 
         fn recursive_add_rows(
             table: &mut Table,
             items: &ir::Items,
-            dominator_tree: &BTreeMap<ir::Id, Vec<ir::Id>>,
+            dominator_tree: &ir::DominatorTree,
             depth: u32,
             mut row: &mut u32,
             opts: &opt::Dominators,
@@ -44,33 +78,45 @@ impl traits::Emit for DominatorTree {
                 add_text_item(items, depth, id, table);
             }
 
-            if let Some(children) = dominator_tree.get(&id) {
-                let mut children = children.to_vec();
-                children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
-                for child in children {
-                    *row += 1;
-                    recursive_add_rows(
-                        table,
-                        items,
-                        dominator_tree,
-                        depth + 1,
-                        &mut row,
-                        &opts,
-                        child,
-                    );
-                }
+            let mut children = dominator_tree.children(id).to_vec();
+            children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
+            for child in children {
+                *row += 1;
+                recursive_add_rows(
+                    table,
+                    items,
+                    dominator_tree,
+                    depth + 1,
+                    &mut row,
+                    &opts,
+                    child,
+                );
             }
         }
 
-        for id in &self.items {
-            let start_depth = if *id == items.meta_root() { 0 } else { 1 };
+        // Each subtree gets its own rows, up to the maximum, and when there
+        // are several, a header to tell where one ends and the next begins.
+        for (i, id) in self.items.iter().enumerate() {
+            if self.items.len() > 1 {
+                table.add_row(vec![
+                    String::new(),
+                    String::new(),
+                    format!(
+                        "[Subtree {} of {}: {}]",
+                        i + 1,
+                        self.items.len(),
+                        items[*id].name()
+                    ),
+                ]);
+            }
+            let mut row = 0;
             recursive_add_rows(
                 &mut table,
                 items,
                 &self.tree,
-                start_depth,
+                start_depth(items, *id),
                 &mut row,
-                &opts,
+                opts,
                 *id,
             );
         }
@@ -93,79 +139,50 @@ impl traits::Emit for DominatorTree {
     }
 
     #[cfg(feature = "emit_json")]
-    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        fn recursive_add_children(
-            items: &ir::Items,
-            opts: &opt::Dominators,
-            dominator_tree: &BTreeMap<ir::Id, Vec<ir::Id>>,
-            id: ir::Id,
-            obj: &mut json::Object,
-        ) -> anyhow::Result<()> {
-            add_json_item(items, id, obj)?;
-
-            if let Some(children) = dominator_tree.get(&id) {
-                let mut children = children.to_vec();
-                children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
-
-                let mut arr = obj.array("children")?;
-                for child in children {
-                    let mut obj = arr.object()?;
-                    recursive_add_children(items, opts, dominator_tree, child, &mut obj)?;
-                }
-            }
-
-            Ok(())
-        }
-
-        // Scoping the borrow of `arr` so we can get another object in the next block
-        let mut obj = json::object(dest)?;
-        {
-            let mut arr = obj.array("items")?;
-            for curr_id in &self.items {
-                let mut item = arr.object()?;
-                recursive_add_children(items, &self.opts, &self.tree, *curr_id, &mut item)?;
-            }
-        }
-
-        if let Some(UnreachableItemsSummary {
-            count,
-            size,
-            size_percent,
-        }) = self.unreachable_items_summary
-        {
-            let mut summary_obj = obj.array("summary")?;
-            let mut unreachable_items_obj = summary_obj.object()?;
-            unreachable_items_obj
-                .field("name", format!("[{} Unreachable Items]", count).as_ref())?;
-            unreachable_items_obj.field("retained_size", size)?;
-            unreachable_items_obj.field("retained_size_percent", size_percent)?;
-        }
-
-        Ok(())
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
     }
 
     #[cfg(feature = "emit_csv")]
-    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         fn recursive_add_children(
             items: &ir::Items,
             opts: &opt::Dominators,
-            dominator_tree: &BTreeMap<ir::Id, Vec<ir::Id>>,
+            dominator_tree: &ir::DominatorTree,
+            depth: u32,
+            row: &mut u32,
             id: ir::Id,
             wtr: &mut csv::Writer<&mut dyn io::Write>,
-        ) -> anyhow::Result<()> {
+        ) -> traits::Result<()> {
             add_csv_item(items, id, wtr)?;
-            if let Some(children) = dominator_tree.get(&id) {
-                let mut children = children.to_vec();
-                children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
-                for child in children {
-                    recursive_add_children(items, opts, dominator_tree, child, wtr)?;
+            if depth >= opts.max_depth() {
+                return Ok(());
+            }
+            let mut children = dominator_tree.children(id).to_vec();
+            children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
+            for child in children {
+                *row += 1;
+                if *row > opts.max_rows() {
+                    break;
                 }
+                recursive_add_children(items, opts, dominator_tree, depth + 1, row, child, wtr)?;
             }
             Ok(())
         }
 
         let mut wtr = csv::Writer::from_writer(dest);
-        recursive_add_children(items, &self.opts, &self.tree, items.meta_root(), &mut wtr)?;
+        for id in &self.items {
+            let mut row = 0;
+            recursive_add_children(
+                items,
+                &self.opts,
+                &self.tree,
+                start_depth(items, *id),
+                &mut row,
+                *id,
+                &mut wtr,
+            )?;
+        }
 
         if let Some(UnreachableItemsSummary {
             count,
@@ -176,6 +193,7 @@ impl traits::Emit for DominatorTree {
             let rc = CsvRecord {
                 id: None,
                 name: format!("[{} Unreachable Items]", count),
+                kind: None,
                 shallow_size: size,
                 shallow_size_percent: size_percent,
                 retained_size: size,
@@ -188,6 +206,168 @@ impl traits::Emit for DominatorTree {
 
         Ok(())
     }
+
+    #[cfg(feature = "emit_text")]
+    fn emit_folded(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        // Each item gets a line for its shallow size, under a stack of its
+        // dominators, so that every frame of a flamegraph is as wide as the
+        // retained size of its item. Items at the maximum depth take up their
+        // whole retained size instead, so that cutting the tree short does not
+        // make it any smaller.
+        fn recursive_add_lines(
+            items: &ir::Items,
+            dominator_tree: &ir::DominatorTree,
+            row: &mut u32,
+            opts: &opt::Dominators,
+            id: ir::Id,
+            stack: &mut Vec<String>,
+            dest: &mut dyn io::Write,
+        ) -> traits::Result<()> {
+            // The meta root has no frame of its own.
+            let is_meta_root = id == items.meta_root();
+            if !is_meta_root {
+                stack.push(folded_frame(items[id].name()));
+            }
+            let depth = stack.len() as u32;
+
+            if !is_meta_root {
+                let size = if depth < opts.max_depth() {
+                    items[id].size()
+                } else {
+                    items.retained_size(id)
+                };
+                if size > 0 {
+                    writeln!(dest, "{} {}", stack.join(";"), size)?;
+                }
+            }
+
+            if depth < opts.max_depth() {
+                let mut children = dominator_tree.children(id).to_vec();
+                children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
+                for child in children {
+                    *row += 1;
+                    if *row > opts.max_rows() {
+                        break;
+                    }
+                    recursive_add_lines(items, dominator_tree, row, opts, child, stack, dest)?;
+                }
+            }
+
+            if !is_meta_root {
+                stack.pop();
+            }
+            Ok(())
+        }
+
+        let mut stack = vec![];
+        for id in &self.items {
+            let mut row = 0;
+            recursive_add_lines(
+                items, &self.tree, &mut row, &self.opts, *id, &mut stack, dest,
+            )?;
+        }
+
+        if let Some(UnreachableItemsSummary { count, .. }) = self.unreachable_items_summary {
+            let frame = format!("[{} Unreachable Items]", count);
+            for id in crate::analyses::reachable::unreachable_items(items) {
+                let item = &items[id];
+                if item.size() > 0 {
+                    writeln!(
+                        dest,
+                        "{};{} {}",
+                        frame,
+                        folded_frame(item.name()),
+                        item.size()
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_text")]
+    fn emit_dot(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        // The same items as the text output lists, each with an edge from its
+        // immediate dominator.
+        fn recursive_add_nodes(
+            items: &ir::Items,
+            dominator_tree: &ir::DominatorTree,
+            depth: u32,
+            row: &mut u32,
+            opts: &opt::Dominators,
+            id: ir::Id,
+            nodes: &mut Vec<(ir::Id, Option<ir::Id>)>,
+        ) {
+            if *row > opts.max_rows() || depth > opts.max_depth() {
+                return;
+            }
+            let mut children = dominator_tree.children(id).to_vec();
+            children.sort_by_key(|&child| std::cmp::Reverse(items.retained_size(child)));
+            for child in children {
+                *row += 1;
+                if *row > opts.max_rows() || depth + 1 > opts.max_depth() {
+                    break;
+                }
+                let parent = Some(id).filter(|&id| id != items.meta_root());
+                nodes.push((child, parent));
+                recursive_add_nodes(items, dominator_tree, depth + 1, row, opts, child, nodes);
+            }
+        }
+
+        let mut nodes = vec![];
+        for id in &self.items {
+            let depth = start_depth(items, *id);
+            if depth > 0 && depth <= self.opts.max_depth() {
+                nodes.push((*id, None));
+            }
+            let mut row = 0;
+            recursive_add_nodes(
+                items, &self.tree, depth, &mut row, &self.opts, *id, &mut nodes,
+            );
+        }
+
+        let unreachable = self.unreachable_items_summary.as_ref();
+        let max_retained_size = nodes
+            .iter()
+            .map(|&(id, _)| items.retained_size(id))
+            .chain(unreachable.map(|summary| summary.size))
+            .max()
+            .unwrap_or(0);
+        let mut graph = Digraph::new(dest, max_retained_size)?;
+        for (id, parent) in nodes {
+            let size = items.retained_size(id);
+            graph.node(&id.to_string(), items[id].name(), size, size)?;
+            if let Some(parent) = parent {
+                graph.edge(&parent.to_string(), &id.to_string())?;
+            }
+        }
+        if let Some(UnreachableItemsSummary { count, size, .. }) = unreachable {
+            let name = format!("[{} Unreachable Items]", count);
+            graph.node("unreachable", &name, *size, *size)?;
+        }
+        graph.finish()?;
+        Ok(())
+    }
+}
+
+/// The depth in the output of the root of a subtree: the meta root's children
+/// are at the top level, as is a requested item, so that the maximum depth
+/// counts from it.
+#[cfg(any(feature = "emit_text", feature = "emit_json", feature = "emit_csv"))]
+fn start_depth(items: &ir::Items, id: ir::Id) -> u32 {
+    if id == items.meta_root() {
+        0
+    } else {
+        1
+    }
+}
+
+/// An item's name as a frame of a folded stack, in which semicolons separate
+/// the frames.
+#[cfg(feature = "emit_text")]
+fn folded_frame(name: &str) -> String {
+    name.replace(';', ",")
 }
 
 #[cfg(feature = "emit_text")]
@@ -195,7 +375,7 @@ fn add_text_item(items: &ir::Items, depth: u32, id: ir::Id, table: &mut Table) {
     let item = &items[id];
 
     let size = items.retained_size(id);
-    let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
+    let size_percent = (size as f64) / (items.size() as f64) * 100.0;
 
     let mut label = String::with_capacity(depth as usize * 4 + item.name().len() + "⤷ ".len());
     for _ in 2..depth {
@@ -214,21 +394,88 @@ fn add_text_item(items: &ir::Items, depth: u32, id: ir::Id, table: &mut Table) {
 }
 
 #[cfg(feature = "emit_json")]
-fn add_json_item(items: &ir::Items, id: ir::Id, obj: &mut json::Object) -> anyhow::Result<()> {
-    let item = &items[id];
+#[derive(Debug, Serialize)]
+struct JsonTree<'a> {
+    items: Vec<JsonItem<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<Vec<JsonSummary>>,
+}
 
-    obj.field("name", item.name())?;
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonItem<'a> {
+    id: String,
+    name: &'a str,
+    kind: &'static str,
+    shallow_size: u64,
+    shallow_size_percent: f64,
+    retained_size: u64,
+    retained_size_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_location: Option<json::SourceLocation<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<JsonItem<'a>>>,
+}
 
-    let shallow_size = item.size();
-    let shallow_size_percent = f64::from(shallow_size) / f64::from(items.size()) * 100.0;
-    obj.field("shallow_size", shallow_size)?;
-    obj.field("shallow_size_percent", shallow_size_percent)?;
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonSummary {
+    name: String,
+    retained_size: u64,
+    retained_size_percent: f64,
+}
 
-    let retained_size = items.retained_size(id);
-    let retained_size_percent = f64::from(retained_size) / f64::from(items.size()) * 100.0;
-    obj.field("retained_size", retained_size)?;
-    obj.field("retained_size_percent", retained_size_percent)?;
-    Ok(())
+/// The JSON object of an item and its subtree. The children of an item are
+/// left out past the maximum depth or number of rows, as they are in the
+/// text output.
+#[cfg(feature = "emit_json")]
+fn json_item<'a>(
+    items: &'a ir::Items,
+    opts: &opt::Dominators,
+    dominator_tree: &ir::DominatorTree,
+    depth: u32,
+    row: &mut u32,
+    id: ir::Id,
+) -> JsonItem<'a> {
+    let item = &items[id];
+    let percent = |size: u64| size as f64 / items.size() as f64 * 100.0;
+
+    let children = dominator_tree.children(id);
+    let children = if !children.is_empty() && depth < opts.max_depth() && *row < opts.max_rows() {
+        let mut children = children.to_vec();
+        children.sort_by(|a, b| items.retained_size(*b).cmp(&items.retained_size(*a)));
+
+        let mut objs = vec![];
+        for child in children {
+            *row += 1;
+            if *row > opts.max_rows() {
+                break;
+            }
+            objs.push(json_item(
+                items,
+                opts,
+                dominator_tree,
+                depth + 1,
+                row,
+                child,
+            ));
+        }
+        Some(objs)
+    } else {
+        None
+    };
+
+    JsonItem {
+        id: id.to_string(),
+        name: item.name(),
+        kind: item.category().as_str(),
+        shallow_size: item.size(),
+        shallow_size_percent: percent(item.size()),
+        retained_size: items.retained_size(id),
+        retained_size_percent: percent(items.retained_size(id)),
+        source_location: item.source_location().map(json::SourceLocation::new),
+        children,
+    }
 }
 
 #[cfg(feature = "emit_csv")]
@@ -237,9 +484,10 @@ fn add_json_item(items: &ir::Items, id: ir::Id, obj: &mut json::Object) -> anyho
 struct CsvRecord {
     pub id: Option<u64>,
     pub name: String,
-    pub shallow_size: u32,
+    pub kind: Option<&'static str>,
+    pub shallow_size: u64,
     pub shallow_size_percent: f64,
-    pub retained_size: u32,
+    pub retained_size: u64,
     pub retained_size_percent: f64,
     pub immediate_dominator: Option<u64>,
 }
@@ -249,17 +497,17 @@ fn add_csv_item(
     items: &ir::Items,
     id: ir::Id,
     wtr: &mut csv::Writer<&mut dyn io::Write>,
-) -> anyhow::Result<()> {
+) -> traits::Result<()> {
     let item = &items[id];
     let (shallow_size, shallow_size_percent) = (
         item.size(),
-        f64::from(item.size()) / f64::from(items.size()) * 100.0,
+        item.size() as f64 / items.size() as f64 * 100.0,
     );
     let (retained_size, retained_size_percent) = (
         items.retained_size(id),
-        f64::from(items.retained_size(id)) / f64::from(items.size()) * 100.0,
+        (items.retained_size(id)) as f64 / items.size() as f64 * 100.0,
     );
-    let idom = if let Some(idom) = items.immediate_dominators().get(&id) {
+    let idom = if let Some(idom) = items.dominator_tree().immediate_dominator(id) {
         idom.serializable()
     } else {
         id.serializable()
@@ -268,6 +516,7 @@ fn add_csv_item(
     let rc = CsvRecord {
         id: Some(item.id().serializable()),
         name: item.name().to_string(),
+        kind: Some(item.category().as_str()),
         shallow_size,
         shallow_size_percent,
         retained_size,
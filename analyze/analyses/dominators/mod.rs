@@ -1,41 +1,88 @@
-use std::collections::BTreeMap;
-
 use regex;
 
 use twiggy_ir as ir;
 use twiggy_opt as opt;
 use twiggy_traits as traits;
 
-use crate::analyses::garbage;
+use crate::analyses::reachable;
 
 mod emit;
 
-struct DominatorTree {
-    tree: BTreeMap<ir::Id, Vec<ir::Id>>,
+/// The result of the `dominators` analysis.
+#[derive(Debug)]
+pub struct DominatorsResult {
+    tree: ir::DominatorTree,
     items: Vec<ir::Id>,
     opts: opt::Dominators,
     unreachable_items_summary: Option<UnreachableItemsSummary>,
 }
 
-struct UnreachableItemsSummary {
+impl DominatorsResult {
+    /// The items whose dominator subtrees were requested, or just the meta
+    /// root when no items were named.
+    pub fn items(&self) -> &[ir::Id] {
+        &self.items
+    }
+
+    /// The dominator tree of the whole IR graph.
+    pub fn tree(&self) -> &ir::DominatorTree {
+        &self.tree
+    }
+
+    /// The items immediately dominated by the given item.
+    pub fn children(&self, id: ir::Id) -> &[ir::Id] {
+        self.tree.children(id)
+    }
+
+    /// A summary of the items that are unreachable from the meta root, if any
+    /// were found while emitting the whole tree.
+    pub fn unreachable_items_summary(&self) -> Option<&UnreachableItemsSummary> {
+        self.unreachable_items_summary.as_ref()
+    }
+}
+
+/// A summary of the items that are not part of the dominator tree because
+/// they are unreachable.
+#[derive(Clone, Debug)]
+pub struct UnreachableItemsSummary {
     count: usize,
-    size: u32,
+    size: u64,
     size_percent: f64,
 }
 
+impl UnreachableItemsSummary {
+    /// The number of unreachable items.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The combined size of the unreachable items, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The combined size of the unreachable items, as a percentage of the
+    /// binary's size.
+    pub fn size_percent(&self) -> f64 {
+        self.size_percent
+    }
+}
+
 /// Compute the dominator tree for the given IR graph.
 pub fn dominators(
     items: &mut ir::Items,
     opts: &opt::Dominators,
-) -> anyhow::Result<Box<dyn traits::Emit>> {
+) -> traits::Result<DominatorsResult> {
+    items.set_threads(opts.threads());
     items.compute_dominator_tree();
-    items.compute_dominators();
     items.compute_retained_sizes();
     items.compute_predecessors();
 
     let arguments = opts.items();
-    let dominator_items = if arguments.is_empty() {
+    let mut dominator_items = if arguments.is_empty() && opts.ids().is_empty() {
         vec![items.meta_root()]
+    } else if arguments.is_empty() {
+        vec![]
     } else if opts.using_regexps() {
         let regexps = regex::RegexSet::new(arguments)?;
         let mut sorted_items: Vec<_> = items
@@ -43,38 +90,39 @@ pub fn dominators(
             .filter(|item| regexps.is_match(&item.name()))
             .map(|item| item.id())
             .collect();
-        sorted_items.sort_by_key(|id| -i64::from(items.retained_size(*id)));
+        sorted_items.sort_by_key(|id| -(items.retained_size(*id) as i64));
         sorted_items
     } else {
+        // Several items can share a name, in which case all of them are used.
         arguments
             .iter()
-            .filter_map(|name| items.get_item_by_name(name))
+            .flat_map(|name| items.get_items_by_name(name))
             .map(|item| item.id())
             .collect()
     };
+    super::select_by_id(items, opts.ids(), &mut dominator_items)?;
 
-    let tree = DominatorTree {
+    Ok(DominatorsResult {
         tree: items.dominator_tree().clone(),
         items: dominator_items,
         opts: opts.clone(),
         unreachable_items_summary: summarize_unreachable_items(items, opts),
-    };
-
-    Ok(Box::new(tree) as Box<_>)
+    })
 }
 
 fn summarize_unreachable_items(
     items: &mut ir::Items,
     opts: &opt::Dominators,
 ) -> Option<UnreachableItemsSummary> {
-    let (size, count) = garbage::get_unreachable_items(&items)
-        .map(|item| item.size())
+    let (size, count) = reachable::unreachable_items(items)
+        .into_iter()
+        .map(|id| items[id].size())
         .fold((0, 0), |(s, c), curr| (s + curr, c + 1));
-    if opts.items().is_empty() && size > 0 {
+    if opts.items().is_empty() && opts.ids().is_empty() && size > 0 {
         Some(UnreachableItemsSummary {
             count,
             size,
-            size_percent: (f64::from(size)) / (f64::from(items.size())) * 100.0,
+            size_percent: (size as f64) / (items.size() as f64) * 100.0,
         })
     } else {
         None
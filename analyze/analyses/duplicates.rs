@@ -0,0 +1,355 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use csv;
+use regex;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `duplicates` analysis.
+#[derive(Debug)]
+pub struct DuplicatesResult {
+    crates: Vec<DuplicateCrate>,
+    max_crates: usize,
+    max_functions: usize,
+}
+
+impl DuplicatesResult {
+    /// The crates with functions in more than one version, most duplicated
+    /// bytes first.
+    pub fn crates(&self) -> &[DuplicateCrate] {
+        &self.crates
+    }
+}
+
+/// A crate whose functions are in the binary more than once, because more than
+/// one version of the crate was linked in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateCrate {
+    name: String,
+    versions: Vec<String>,
+    functions: Vec<DuplicateFunction>,
+}
+
+impl DuplicateCrate {
+    /// The crate's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The versions of the crate that the duplicated functions come from:
+    /// their semver versions, or their crate disambiguators, in order.
+    pub fn versions(&self) -> &[String] {
+        &self.versions
+    }
+
+    /// The duplicated functions, most duplicated bytes first.
+    pub fn functions(&self) -> &[DuplicateFunction] {
+        &self.functions
+    }
+
+    /// The bytes that all but the largest copy of each function take up.
+    pub fn duplicated_bytes(&self) -> u64 {
+        self.functions.iter().map(|f| f.duplicated_bytes()).sum()
+    }
+
+    /// The bytes that all copies of the duplicated functions take up.
+    pub fn size(&self) -> u64 {
+        self.functions.iter().map(|f| f.size()).sum()
+    }
+}
+
+/// A function that is in the binary once for each of several versions of its
+/// crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateFunction {
+    name: String,
+    copies: Vec<(ir::Id, u64)>,
+}
+
+impl DuplicateFunction {
+    /// The function's name, without crate versions, disambiguators, or hash.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The ids and sizes of the copies of the function, in `Id` order.
+    pub fn copies(&self) -> &[(ir::Id, u64)] {
+        &self.copies
+    }
+
+    /// The bytes that all copies of the function take up.
+    pub fn size(&self) -> u64 {
+        self.copies.iter().map(|(_, size)| size).sum()
+    }
+
+    /// The bytes that all but the largest copy of the function take up.
+    pub fn duplicated_bytes(&self) -> u64 {
+        let largest = self.copies.iter().map(|(_, size)| *size).max();
+        self.size() - largest.unwrap_or(0)
+    }
+}
+
+impl traits::Emit for DuplicatesResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Duplicated Bytes".to_string()),
+            (Align::Right, "Bytes".to_string()),
+            (Align::Left, "Crate or Function".to_string()),
+        ]);
+
+        for krate in self.crates.iter().take(self.max_crates) {
+            table.add_row(vec![
+                krate.duplicated_bytes().to_string(),
+                krate.size().to_string(),
+                format!("{} ({})", krate.name, krate.versions.join(", ")),
+            ]);
+            for function in krate.functions.iter().take(self.max_functions) {
+                table.add_row(vec![
+                    function.duplicated_bytes().to_string(),
+                    function.size().to_string(),
+                    format!("    {} ({} copies)", function.name, function.copies.len()),
+                ]);
+            }
+            let rest = &krate.functions[krate.functions.len().min(self.max_functions)..];
+            if !rest.is_empty() {
+                table.add_row(vec![
+                    rest.iter()
+                        .map(|f| f.duplicated_bytes())
+                        .sum::<u64>()
+                        .to_string(),
+                    rest.iter().map(|f| f.size()).sum::<u64>().to_string(),
+                    format!("    ... and {} more.", rest.len()),
+                ]);
+            }
+        }
+
+        let rest = &self.crates[self.crates.len().min(self.max_crates)..];
+        if !rest.is_empty() {
+            table.add_row(vec![
+                rest.iter()
+                    .map(|c| c.duplicated_bytes())
+                    .sum::<u64>()
+                    .to_string(),
+                rest.iter().map(|c| c.size()).sum::<u64>().to_string(),
+                format!("... and {} more.", rest.len()),
+            ]);
+        }
+
+        table.add_row(vec![
+            self.crates
+                .iter()
+                .map(|c| c.duplicated_bytes())
+                .sum::<u64>()
+                .to_string(),
+            self.crates
+                .iter()
+                .map(|c| c.size())
+                .sum::<u64>()
+                .to_string(),
+            format!("Σ [{} Crates With Multiple Versions]", self.crates.len()),
+        ]);
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut arr = json::array(dest)?;
+
+        for krate in self.crates.iter().take(self.max_crates) {
+            let mut obj = arr.object()?;
+            obj.field("crate", krate.name.as_str())?;
+            obj.field("duplicated_bytes", krate.duplicated_bytes())?;
+            obj.field("size", krate.size())?;
+            {
+                let mut versions = obj.array("versions")?;
+                for version in &krate.versions {
+                    versions.elem(version.as_str())?;
+                }
+            }
+
+            let mut functions = obj.array("functions")?;
+            for function in krate.functions.iter().take(self.max_functions) {
+                let mut obj = functions.object()?;
+                obj.field("name", function.name.as_str())?;
+                obj.field("duplicated_bytes", function.duplicated_bytes())?;
+                obj.field("size", function.size())?;
+                let mut ids = obj.array("ids")?;
+                for (id, _) in &function.copies {
+                    ids.elem(id.to_string().as_str())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            #[serde(rename = "Crate")]
+            krate: &'a str,
+            versions: String,
+            function: &'a str,
+            copies: usize,
+            duplicated_bytes: u64,
+            bytes: u64,
+        }
+
+        for krate in self.crates.iter().take(self.max_crates) {
+            for function in krate.functions.iter().take(self.max_functions) {
+                wtr.serialize(CsvRecord {
+                    krate: &krate.name,
+                    versions: krate.versions.join(" "),
+                    function: &function.name,
+                    copies: function.copies.len(),
+                    duplicated_bytes: function.duplicated_bytes(),
+                    bytes: function.size(),
+                })?;
+                wtr.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A function's name with the versions of the crates in it taken out, and
+/// those versions, as pairs of a crate name and its version.
+struct Normalized<'a> {
+    name: String,
+    versions: Vec<(&'a str, &'a str)>,
+}
+
+/// Take the crate disambiguators, as in `serde[d4f6a1c0]::de`, and semver
+/// versions, as in `serde-1.0.188::de`, out of a demangled name, along with
+/// the trailing hash of legacy mangled names.
+///
+/// The hashes of legacy mangled names also tell apart the instantiations of
+/// generic functions, so they are not counted as versions.
+fn normalize<'a>(versioned: &regex::Regex, name: &'a str) -> Normalized<'a> {
+    let mut normalized = String::with_capacity(name.len());
+    let mut versions = vec![];
+    let mut last = 0;
+    for caps in versioned.captures_iter(name) {
+        let krate = caps.get(1).unwrap();
+        let version = caps.get(2).or_else(|| caps.get(3)).unwrap().as_str();
+        normalized.push_str(&name[last..krate.end()]);
+        last = caps.get(0).unwrap().end();
+        versions.push((krate.as_str(), version));
+    }
+    normalized.push_str(&name[last..]);
+
+    if let Some(i) = normalized.rfind("::h") {
+        if normalized.len() - i == "::h".len() + 16
+            && normalized[i + "::h".len()..]
+                .chars()
+                .all(|c| c.is_ascii_hexdigit())
+        {
+            normalized.truncate(i);
+        }
+    }
+    Normalized {
+        name: normalized,
+        versions,
+    }
+}
+
+/// Find the functions that are in the binary once for each of several versions
+/// of a crate, such as when two dependencies require incompatible versions of
+/// it, or when feature unification failed.
+///
+/// Functions are grouped by their names with crate versions and disambiguators
+/// taken out. A group of several functions whose names differ in the version
+/// of a crate is attributed to the first such crate in their names.
+pub fn duplicates(
+    items: &mut ir::Items,
+    opts: &opt::Duplicates,
+) -> traits::Result<DuplicatesResult> {
+    let versioned = regex::Regex::new(
+        r"\b([A-Za-z_][A-Za-z0-9_]*)(?:\[([0-9a-f]+)\]|-([0-9]+\.[0-9]+\.[0-9]+(?:-[0-9A-Za-z.]+)?))",
+    )?;
+
+    let mut groups: BTreeMap<String, Vec<(&ir::Item, Normalized)>> = BTreeMap::new();
+    for item in items.iter() {
+        if !matches!(item.kind(), ir::ItemKind::Code(_)) {
+            continue;
+        }
+        let normalized = normalize(&versioned, item.name());
+        if !normalized.versions.is_empty() {
+            groups
+                .entry(normalized.name.clone())
+                .or_default()
+                .push((item, normalized));
+        }
+    }
+
+    let mut crates: BTreeMap<&str, (BTreeSet<&str>, Vec<DuplicateFunction>)> = BTreeMap::new();
+    for (name, copies) in groups {
+        if copies.len() < 2 {
+            continue;
+        }
+        let mut versions: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for (_, normalized) in &copies {
+            for &(krate, version) in &normalized.versions {
+                versions.entry(krate).or_default().insert(version);
+            }
+        }
+        let krate = copies[0]
+            .1
+            .versions
+            .iter()
+            .map(|&(krate, _)| krate)
+            .find(|krate| versions[krate].len() > 1);
+        let Some(krate) = krate else {
+            continue;
+        };
+
+        let entry = crates.entry(krate).or_default();
+        entry.0.extend(versions.remove(krate).unwrap_or_default());
+        entry.1.push(DuplicateFunction {
+            name,
+            copies: copies
+                .iter()
+                .map(|(item, _)| (item.id(), item.size()))
+                .collect(),
+        });
+    }
+
+    let mut crates: Vec<DuplicateCrate> = crates
+        .into_iter()
+        .map(|(name, (versions, mut functions))| {
+            functions.sort_by(|a, b| {
+                b.duplicated_bytes()
+                    .cmp(&a.duplicated_bytes())
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            DuplicateCrate {
+                name: name.to_string(),
+                versions: versions.into_iter().map(String::from).collect(),
+                functions,
+            }
+        })
+        .collect();
+    crates.sort_by(|a, b| {
+        b.duplicated_bytes()
+            .cmp(&a.duplicated_bytes())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(DuplicatesResult {
+        crates,
+        max_crates: opts.max_items() as usize,
+        max_functions: opts.max_functions() as usize,
+    })
+}
@@ -0,0 +1,204 @@
+use std::cmp;
+use std::collections::BTreeMap;
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use crate::formats::truncated::Truncated;
+use csv;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The bucket for the functions that no source file is known for.
+const UNMAPPED: &str = "<unmapped>";
+
+/// The result of the `files` analysis.
+#[derive(Debug)]
+pub struct FilesResult {
+    files: Vec<FileEntry>,
+    truncated: Truncated,
+    truncated_functions: usize,
+}
+
+impl FilesResult {
+    /// The source files that were listed, largest first.
+    pub fn files(&self) -> &[FileEntry] {
+        &self.files
+    }
+
+    /// The source files beyond the requested number of files.
+    pub fn truncated(&self) -> Truncated {
+        self.truncated
+    }
+}
+
+/// How much of the binary's code a source file accounts for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    name: String,
+    functions: usize,
+    size: u64,
+}
+
+impl FileEntry {
+    /// The source file's name, or `<unmapped>` for the functions that no
+    /// source file is known for.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many functions were compiled from the source file.
+    pub fn functions(&self) -> usize {
+        self.functions
+    }
+
+    /// The sum of the sizes of those functions.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl traits::Emit for FilesResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let percent = |size: u64| format!("{:.2}%", size as f64 / items.size() as f64 * 100.0);
+
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Bytes".to_string()),
+            (Align::Right, "Size %".to_string()),
+            (Align::Right, "Functions".to_string()),
+            (Align::Left, "File".to_string()),
+        ]);
+
+        for file in &self.files {
+            table.add_row(vec![
+                file.size.to_string(),
+                percent(file.size),
+                file.functions.to_string(),
+                file.name.clone(),
+            ]);
+        }
+
+        let truncated_size = self.truncated.bytes() as u64;
+        if !self.truncated.is_empty() {
+            table.add_row(vec![
+                truncated_size.to_string(),
+                percent(truncated_size),
+                self.truncated_functions.to_string(),
+                format!(
+                    "... and {} more files totaling {} bytes ({})",
+                    self.truncated.count(),
+                    truncated_size,
+                    percent(truncated_size)
+                ),
+            ]);
+        }
+
+        let shown_size: u64 = self.files.iter().map(|file| file.size).sum();
+        let shown_functions: usize = self.files.iter().map(|file| file.functions).sum();
+        let total_size = shown_size + truncated_size;
+        table.add_row(vec![
+            total_size.to_string(),
+            percent(total_size),
+            (shown_functions + self.truncated_functions).to_string(),
+            format!(
+                "Σ [{} Total Files]",
+                self.files.len() + self.truncated.count()
+            ),
+        ]);
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let size_percent = |size: u64| size as f64 / items.size() as f64 * 100.0;
+
+        let mut arr = json::array(dest)?;
+        for file in &self.files {
+            let mut obj = arr.object()?;
+            obj.field("name", file.name.as_str())?;
+            obj.field("functions", file.functions as u64)?;
+            obj.field("size", file.size)?;
+            obj.field("size_percent", size_percent(file.size))?;
+        }
+
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            let mut obj = arr.object()?;
+            obj.field(
+                "name",
+                format!("... and {} more files", self.truncated.count()).as_str(),
+            )?;
+            obj.field("size", size)?;
+            obj.field("size_percent", size_percent(size))?;
+            self.truncated.json_fields(&mut obj)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            name: &'a str,
+            functions: usize,
+            size: u64,
+            size_percent: f64,
+        }
+
+        for file in &self.files {
+            wtr.serialize(CsvRecord {
+                name: &file.name,
+                functions: file.functions,
+                size: file.size,
+                size_percent: file.size as f64 / items.size() as f64 * 100.0,
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Sum up the sizes of the functions compiled from each source file. The
+/// functions without a known source file are summed up as `<unmapped>`.
+pub fn files(items: &mut ir::Items, opts: &opt::Files) -> traits::Result<FilesResult> {
+    let mut files: BTreeMap<&str, FileEntry> = BTreeMap::new();
+    for item in items.iter() {
+        if !matches!(item.kind(), ir::ItemKind::Code(_)) {
+            continue;
+        }
+        let name = item
+            .source_location()
+            .map_or(UNMAPPED, |location| location.file().as_str());
+        let file = files.entry(name).or_insert_with(|| FileEntry {
+            name: name.to_string(),
+            functions: 0,
+            size: 0,
+        });
+        file.functions += 1;
+        file.size += item.size();
+    }
+
+    // Ties are broken by name, so that the output is deterministic.
+    let mut files: Vec<FileEntry> = files.into_values().collect();
+    files.sort_by_key(|file| cmp::Reverse(file.size));
+
+    let max_items = opts.max_items() as usize;
+    let rest = files.split_off(max_items.min(files.len()));
+    let truncated = Truncated::of(rest.iter().map(|file| file.size));
+    let truncated_functions = rest.iter().map(|file| file.functions).sum();
+
+    Ok(FilesResult {
+        files,
+        truncated,
+        truncated_functions,
+    })
+}
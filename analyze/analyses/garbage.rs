@@ -1,68 +1,244 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::io;
 
-use petgraph::visit::Walker;
-
+use crate::analyses::reachable::unreachable_items_without;
 use crate::formats::json;
 use crate::formats::table::{Align, Table};
+use crate::formats::truncated::Truncated;
+use csv;
+use serde_derive::Serialize;
 use twiggy_ir as ir;
 use twiggy_opt as opt;
 use twiggy_traits as traits;
 
+/// What references a garbage item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferencedBy {
+    /// No item references it.
+    Nothing,
+    /// Only other garbage references it.
+    Garbage,
+    /// A reachable item references it, but only through an edge of one of
+    /// the excluded kinds.
+    ExcludedEdges,
+}
+
+impl ReferencedBy {
+    /// How this is displayed in the output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReferencedBy::Nothing => "nothing",
+            ReferencedBy::Garbage => "garbage",
+            ReferencedBy::ExcludedEdges => "excluded edges",
+        }
+    }
+}
+
+/// Why a garbage item is not reachable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    referenced_by: ReferencedBy,
+    incoming_edges: usize,
+    retainer: Option<ir::Id>,
+}
+
+impl Explanation {
+    /// What references the item.
+    pub fn referenced_by(&self) -> ReferencedBy {
+        self.referenced_by
+    }
+
+    /// The number of items that reference the item.
+    pub fn incoming_edges(&self) -> usize {
+        self.incoming_edges
+    }
+
+    /// The closest item that would retain the item if it were reachable: a
+    /// reachable item that references it through an excluded edge, or else
+    /// garbage that nothing references and that it can be reached from with
+    /// the fewest edges. `None` when nothing references the item, or when it
+    /// is only referenced from a cycle of garbage.
+    pub fn retainer(&self) -> Option<ir::Id> {
+        self.retainer
+    }
+}
+
+/// The result of the `garbage` analysis.
 #[derive(Debug)]
-struct Garbage {
+pub struct GarbageResult {
     items: Vec<ir::Id>,
+    explanations: Vec<Explanation>,
     data_segments: Vec<ir::Id>,
     limit: usize,
+    truncated: Truncated,
+}
+
+impl GarbageResult {
+    /// The unreachable items, largest first. Data segments are only included
+    /// here when `--show-data-segments` was given.
+    pub fn items(&self) -> &[ir::Id] {
+        &self.items
+    }
+
+    /// Why each of the unreachable items is unreachable, in the same order as
+    /// `items`.
+    pub fn explanations(&self) -> &[Explanation] {
+        &self.explanations
+    }
+
+    /// The unreachable data segments, largest first, when they are summarized
+    /// separately from the other items.
+    pub fn data_segments(&self) -> &[ir::Id] {
+        &self.data_segments
+    }
+
+    /// The items beyond `max_items`, which are summarized rather than
+    /// displayed.
+    pub fn truncated(&self) -> Truncated {
+        self.truncated
+    }
 }
 
-impl traits::Emit for Garbage {
+impl GarbageResult {
+    /// The JSON output, as a value that serde can serialize: a row for each
+    /// listed item, then rows for the truncated items, the total, and the
+    /// data segments that were left out.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        let percent = |size: u64| size as f64 / items.size() as f64 * 100.0;
+        let mut rows: Vec<_> = self
+            .items
+            .iter()
+            .zip(&self.explanations)
+            .take(self.limit)
+            .map(|(&id, explanation)| {
+                let item = &items[id];
+                JsonRow {
+                    id: Some(id.to_string()),
+                    name: item.name().to_string(),
+                    kind: Some(item.category().as_str()),
+                    bytes: item.size(),
+                    size_percent: percent(item.size()),
+                    referenced_by: Some(explanation.referenced_by.as_str()),
+                    incoming_edges: Some(explanation.incoming_edges),
+                    retainer: explanation.retainer.map(|retainer| items[retainer].name()),
+                    source_location: item.source_location().map(json::SourceLocation::new),
+                    ..JsonRow::default()
+                }
+            })
+            .collect();
+
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            rows.push(JsonRow {
+                name: self.truncated.label(items.size()),
+                bytes: size,
+                size_percent: percent(size),
+                truncated_count: Some(self.truncated.count()),
+                truncated_bytes: Some(self.truncated.bytes()),
+                ..JsonRow::default()
+            });
+        }
+
+        let total_size: u64 = self.items.iter().map(|&id| items[id].size()).sum();
+        rows.push(JsonRow {
+            name: format!("Σ [{} Total Rows]", self.items.len()),
+            bytes: total_size,
+            size_percent: percent(total_size),
+            ..JsonRow::default()
+        });
+
+        if !self.data_segments.is_empty() {
+            let size: u64 = self.data_segments.iter().map(|&id| items[id].size()).sum();
+            rows.push(JsonRow {
+                name: format!(
+                    "{} potential false-positive data segments",
+                    self.data_segments.len()
+                ),
+                bytes: size,
+                size_percent: percent(size),
+                ..JsonRow::default()
+            });
+        }
+
+        rows
+    }
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Default, Serialize)]
+struct JsonRow<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<&'static str>,
+    bytes: u64,
+    size_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    referenced_by: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    incoming_edges: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retainer: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_location: Option<json::SourceLocation<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_bytes: Option<i64>,
+}
+
+impl traits::Emit for GarbageResult {
     #[cfg(feature = "emit_text")]
-    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         let mut table = Table::with_header(vec![
             (Align::Right, "Bytes".to_string()),
             (Align::Right, "Size %".to_string()),
             (Align::Left, "Garbage Item".to_string()),
+            (Align::Left, "Referenced By".to_string()),
+            (Align::Left, "Would-Be Retainer".to_string()),
         ]);
         let items_iter = self.items.iter().map(|id| &items[*id]);
 
-        for item in items_iter.clone().take(self.limit) {
+        for (item, explanation) in items_iter.clone().zip(&self.explanations).take(self.limit) {
             let size = item.size();
-            let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
+            let size_percent = (size as f64) / (items.size() as f64) * 100.0;
+            let retainer = explanation.retainer.map_or("", |id| items[id].name());
             table.add_row(vec![
                 size.to_string(),
                 format!("{:.2}%", size_percent),
                 item.name().to_string(),
+                explanation.referenced_by.as_str().to_string(),
+                retainer.to_string(),
             ]);
         }
 
-        match items_iter
-            .clone()
-            .skip(self.limit)
-            .fold((0, 0), |(size, cnt), item| (size + item.size(), cnt + 1))
-        {
-            (size, cnt) if cnt > 0 => {
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
-                table.add_row(vec![
-                    size.to_string(),
-                    format!("{:.2}%", size_percent),
-                    format!("... and {} more", cnt),
-                ]);
-            }
-            _ => {}
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            let size_percent = size as f64 / items.size() as f64 * 100.0;
+            table.add_row(vec![
+                size.to_string(),
+                format!("{:.2}%", size_percent),
+                self.truncated.label(items.size()),
+                String::new(),
+                String::new(),
+            ]);
         }
 
-        let total_size: u32 = items_iter.map(|item| item.size()).sum();
-        let total_percent = (f64::from(total_size)) / (f64::from(items.size())) * 100.0;
+        let total_size: u64 = items_iter.map(|item| item.size()).sum();
+        let total_percent = (total_size as f64) / (items.size() as f64) * 100.0;
         table.add_row(vec![
             total_size.to_string(),
             format!("{:.2}%", total_percent),
             format!("Σ [{} Total Rows]", self.items.len()),
+            String::new(),
+            String::new(),
         ]);
 
         if !self.data_segments.is_empty() {
-            let total_size: u32 = self.data_segments.iter().map(|&id| items[id].size()).sum();
-            let size_percent = f64::from(total_size) / f64::from(items.size()) * 100.0;
+            let total_size: u64 = self.data_segments.iter().map(|&id| items[id].size()).sum();
+            let size_percent = total_size as f64 / items.size() as f64 * 100.0;
             table.add_row(vec![
                 total_size.to_string(),
                 format!("{:.2}%", size_percent),
@@ -70,6 +246,8 @@ impl traits::Emit for Garbage {
                     "{} potential false-positive data segments",
                     self.data_segments.len()
                 ),
+                String::new(),
+                String::new(),
             ]);
         }
 
@@ -78,110 +256,192 @@ impl traits::Emit for Garbage {
     }
 
     #[cfg(feature = "emit_json")]
-    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        let mut arr = json::array(dest)?;
-
-        for &id in self.items.iter().take(self.limit) {
-            let item = &items[id];
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
+    }
 
-            let mut obj = arr.object()?;
-            obj.field("name", item.name())?;
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
 
-            let size = item.size();
-            let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
-            obj.field("bytes", size)?;
-            obj.field("size_percent", size_percent)?;
+        #[derive(Serialize, Debug, Default)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord {
+            id: Option<String>,
+            name: String,
+            kind: Option<&'static str>,
+            bytes: u64,
+            size_percent: f64,
+            referenced_by: Option<&'static str>,
+            incoming_edges: Option<usize>,
+            retainer: Option<String>,
+            truncated_count: Option<usize>,
+            truncated_bytes: Option<i64>,
         }
 
-        let (total_size, total_cnt) = self
-            .items
-            .iter()
-            .skip(self.limit)
-            .map(|id| &items[*id])
-            .fold((0, 0), |(size, cnt), item| (size + item.size(), cnt + 1));
-        if total_cnt > 0 {
-            let name = format!("... and {} more", total_cnt);
-            let total_size_percent = (f64::from(total_size)) / (f64::from(items.size())) * 100.0;
-            let mut obj = arr.object()?;
-            obj.field("name", name.as_str())?;
-            obj.field("bytes", total_size)?;
-            obj.field("size_percent", total_size_percent)?;
+        let size_percent = |size: u64| size as f64 / items.size() as f64 * 100.0;
+
+        for (&id, explanation) in self.items.iter().zip(&self.explanations).take(self.limit) {
+            let item = &items[id];
+            wtr.serialize(CsvRecord {
+                id: Some(id.to_string()),
+                name: item.name().to_string(),
+                kind: Some(item.category().as_str()),
+                bytes: item.size(),
+                size_percent: size_percent(item.size()),
+                referenced_by: Some(explanation.referenced_by.as_str()),
+                incoming_edges: Some(explanation.incoming_edges),
+                retainer: explanation
+                    .retainer
+                    .map(|retainer| items[retainer].name().to_string()),
+                ..Default::default()
+            })?;
+            wtr.flush()?;
         }
 
-        // Scoping the borrow of `arr` so we can get another object in the next block
-        {
-            let total_name = format!("Σ [{} Total Rows]", self.items.len());
-            let total_size: u32 = self.items.iter().map(|&id| items[id].size()).sum();
-            let total_size_percent = (f64::from(total_size)) / (f64::from(items.size())) * 100.0;
-            let mut obj = arr.object()?;
-            obj.field("name", total_name.as_str())?;
-            obj.field("bytes", total_size)?;
-            obj.field("size_percent", total_size_percent)?;
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            wtr.serialize(CsvRecord {
+                name: self.truncated.label(items.size()),
+                bytes: size,
+                size_percent: size_percent(size),
+                truncated_count: Some(self.truncated.count()),
+                truncated_bytes: Some(self.truncated.bytes()),
+                ..Default::default()
+            })?;
+            wtr.flush()?;
         }
 
+        let total_size: u64 = self.items.iter().map(|&id| items[id].size()).sum();
+        wtr.serialize(CsvRecord {
+            name: format!("Σ [{} Total Rows]", self.items.len()),
+            bytes: total_size,
+            size_percent: size_percent(total_size),
+            ..Default::default()
+        })?;
+        wtr.flush()?;
+
         if !self.data_segments.is_empty() {
-            let name = format!(
-                "{} potential false-positive data segments",
-                self.data_segments.len()
-            );
-            let size: u32 = self.data_segments.iter().map(|&id| items[id].size()).sum();
-            let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
-
-            let mut obj = arr.object()?;
-            obj.field("name", name.as_str())?;
-            obj.field("bytes", size)?;
-            obj.field("size_percent", size_percent)?;
+            let size: u64 = self.data_segments.iter().map(|&id| items[id].size()).sum();
+            wtr.serialize(CsvRecord {
+                name: format!(
+                    "{} potential false-positive data segments",
+                    self.data_segments.len()
+                ),
+                bytes: size,
+                size_percent: size_percent(size),
+                ..Default::default()
+            })?;
+            wtr.flush()?;
         }
-
         Ok(())
     }
-
-    #[cfg(feature = "emit_csv")]
-    fn emit_csv(&self, _items: &ir::Items, _dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        unimplemented!();
-    }
 }
 
 /// Find items that are not transitively referenced by any exports or public functions.
-pub fn garbage(items: &ir::Items, opts: &opt::Garbage) -> anyhow::Result<Box<dyn traits::Emit>> {
-    let mut unreachable_items = get_unreachable_items(&items).collect::<Vec<_>>();
-    unreachable_items.sort_by(|a, b| b.size().cmp(&a.size()));
+pub fn garbage(items: &mut ir::Items, opts: &opt::Garbage) -> traits::Result<GarbageResult> {
+    items.set_threads(opts.threads());
+    items.compute_predecessors();
+    let items = &*items;
+
+    let mut unreachable_items = unreachable_items_without(items, opts.excluded_edges());
+    let garbage: BTreeSet<ir::Id> = unreachable_items.iter().copied().collect();
+    // Filter by kind only after collecting all of the garbage, so that items
+    // of other kinds can still be would-be retainers.
+    unreachable_items.retain(|&id| opts.includes_kind(&items[id]));
 
     // Split the items into two categories if necessary
-    let (data_segments, items_non_data) = if opts.show_data_segments() {
-        (
-            vec![],
-            unreachable_items.iter().map(|item| item.id()).collect(),
-        )
+    let (data_segments, items_non_data): (Vec<_>, Vec<_>) = if opts.show_data_segments() {
+        (vec![], unreachable_items)
     } else {
-        (
-            unreachable_items
-                .iter()
-                .filter(|item| item.kind().is_data())
-                .map(|item| item.id())
-                .collect(),
-            unreachable_items
-                .iter()
-                .filter(|item| !item.kind().is_data())
-                .map(|item| item.id())
-                .collect(),
-        )
+        unreachable_items
+            .into_iter()
+            .partition(|&id| items[id].kind().is_data())
     };
+    let explanations = explain(items, &garbage, &items_non_data, opts.excluded_edges());
 
-    let garbage_items = Garbage {
+    let limit = opts.max_items() as usize;
+    let truncated = Truncated::of(
+        items_non_data
+            .iter()
+            .skip(limit)
+            .map(|&id| items[id].size()),
+    );
+    Ok(GarbageResult {
         items: items_non_data,
+        explanations,
         data_segments,
-        limit: opts.max_items() as usize,
-    };
-
-    Ok(Box::new(garbage_items) as Box<_>)
+        limit,
+        truncated,
+    })
 }
 
-pub(crate) fn get_unreachable_items(items: &ir::Items) -> impl Iterator<Item = &ir::Item> {
-    let reachable_items = petgraph::visit::Dfs::new(items, items.meta_root())
-        .iter(&items)
-        .collect::<BTreeSet<ir::Id>>();
-    items
+/// Explain why each of the given items, among all the `garbage`, is not
+/// reachable.
+///
+/// Would-be retainers are found with a breadth-first search from every item
+/// that could retain garbage, along the edges that were followed to find it:
+/// first the reachable items referencing garbage through excluded edges, then
+/// the garbage that nothing references.
+fn explain(
+    items: &ir::Items,
+    garbage: &BTreeSet<ir::Id>,
+    explained: &[ir::Id],
+    excluded_edges: &[ir::EdgeKind],
+) -> Vec<Explanation> {
+    let followed = |kind| !excluded_edges.contains(&kind);
+
+    let mut queue = VecDeque::new();
+    for &id in garbage {
+        for referrer in items.predecessors(id) {
+            if !garbage.contains(&referrer) {
+                queue.push_back((id, referrer));
+            }
+        }
+    }
+    for &id in garbage {
+        if items.predecessors(id).next().is_none() {
+            for (to, kind) in items.edges(id) {
+                if garbage.contains(&to) && followed(kind) {
+                    queue.push_back((to, id));
+                }
+            }
+        }
+    }
+    let mut retainers = BTreeMap::new();
+    while let Some((id, retainer)) = queue.pop_front() {
+        if retainers.contains_key(&id) {
+            continue;
+        }
+        retainers.insert(id, retainer);
+        for (to, kind) in items.edges(id) {
+            if garbage.contains(&to) && followed(kind) && !retainers.contains_key(&to) {
+                queue.push_back((to, retainer));
+            }
+        }
+    }
+
+    explained
         .iter()
-        .filter(move |item| !reachable_items.contains(&item.id()))
+        .map(|&id| {
+            let mut incoming_edges = 0;
+            let mut referenced_by = ReferencedBy::Nothing;
+            for referrer in items.predecessors(id) {
+                incoming_edges += 1;
+                if !garbage.contains(&referrer) {
+                    referenced_by = ReferencedBy::ExcludedEdges;
+                } else if referenced_by == ReferencedBy::Nothing {
+                    referenced_by = ReferencedBy::Garbage;
+                }
+            }
+            Explanation {
+                referenced_by,
+                incoming_edges,
+                retainer: retainers
+                    .get(&id)
+                    .copied()
+                    .filter(|&retainer| retainer != id),
+            }
+        })
+        .collect()
 }
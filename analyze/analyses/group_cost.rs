@@ -0,0 +1,202 @@
+use std::collections::BTreeSet;
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use csv;
+use regex;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `group-cost` analysis.
+#[derive(Debug)]
+pub struct GroupCostResult {
+    group: GroupBytes,
+    exclusive: GroupBytes,
+    shared: GroupBytes,
+}
+
+impl GroupCostResult {
+    /// The items in the group, and the sum of their shallow sizes.
+    pub fn group(&self) -> GroupBytes {
+        self.group
+    }
+
+    /// The items that removing the group would remove: the items in the group,
+    /// and the items they depend on that nothing else depends on.
+    pub fn exclusive(&self) -> GroupBytes {
+        self.exclusive
+    }
+
+    /// The items the group depends on that the rest of the binary still
+    /// depends on, so that removing the group would not remove them.
+    pub fn shared(&self) -> GroupBytes {
+        self.shared
+    }
+}
+
+/// A number of items, and their total size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GroupBytes {
+    count: usize,
+    bytes: u64,
+}
+
+impl GroupBytes {
+    fn of<'a>(items: &ir::Items, ids: impl Iterator<Item = &'a ir::Id>) -> GroupBytes {
+        ids.fold(GroupBytes::default(), |total, &id| GroupBytes {
+            count: total.count + 1,
+            bytes: total.bytes + items[id].size(),
+        })
+    }
+
+    /// How many items there are.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Their total size, in bytes.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+impl GroupCostResult {
+    /// The rows of the output: a label, and the items it describes.
+    fn rows(&self) -> [(&'static str, String, GroupBytes); 3] {
+        [
+            (
+                "group",
+                format!("Group: {} items", self.group.count),
+                self.group,
+            ),
+            (
+                "exclusive",
+                format!(
+                    "Exclusive: {} items removed along with the group",
+                    self.exclusive.count
+                ),
+                self.exclusive,
+            ),
+            (
+                "shared",
+                format!(
+                    "Shared: {} items the group depends on that are kept",
+                    self.shared.count
+                ),
+                self.shared,
+            ),
+        ]
+    }
+}
+
+impl traits::Emit for GroupCostResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Bytes".to_string()),
+            (Align::Right, "Size %".to_string()),
+            (Align::Left, "Group Cost".to_string()),
+        ]);
+
+        for (_, label, total) in self.rows() {
+            let size_percent = total.bytes as f64 / items.size() as f64 * 100.0;
+            table.add_row(vec![
+                total.bytes.to_string(),
+                format!("{:.2}%", size_percent),
+                label,
+            ]);
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut obj = json::object(dest)?;
+        for (name, _, total) in self.rows() {
+            let mut row = obj.object(name)?;
+            row.field("items", total.count as u64)?;
+            row.field("bytes", total.bytes)?;
+            row.field(
+                "size_percent",
+                total.bytes as f64 / items.size() as f64 * 100.0,
+            )?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord {
+            cost: &'static str,
+            items: usize,
+            bytes: u64,
+            size_percent: f64,
+        }
+
+        for (name, _, total) in self.rows() {
+            wtr.serialize(CsvRecord {
+                cost: name,
+                items: total.count,
+                bytes: total.bytes,
+                size_percent: total.bytes as f64 / items.size() as f64 * 100.0,
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate what removing a group of items together would save.
+///
+/// Summing the retained sizes of the items in a group double-counts what they
+/// retain together, and misses what none of them retains alone but all of
+/// them do together. Instead, the items depended on by the group are split
+/// into those that are still reachable from the roots once the group is
+/// removed, which are shared, and those that are not, which are removed along
+/// with the group.
+pub fn group_cost(items: &mut ir::Items, opts: &opt::GroupCost) -> traits::Result<GroupCostResult> {
+    if opts.regex().is_none() && opts.names().is_empty() {
+        return Err(traits::Error::Analysis(
+            "group-cost needs a regular expression or names to select the group".to_string(),
+        ));
+    }
+    let regex = opts.regex().map(regex::Regex::new).transpose()?;
+
+    let items = &*items;
+    let group: BTreeSet<ir::Id> = items
+        .iter()
+        .filter(|item| item.id() != items.meta_root())
+        .filter(|item| {
+            regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(item.name()))
+                || opts.names().iter().any(|name| name == item.name())
+        })
+        .map(|item| item.id())
+        .collect();
+
+    let roots: Vec<ir::Id> = group.iter().copied().collect();
+    let dependencies = items.reachable_from(&roots);
+    let remaining = items.reachable_without_items(&group);
+
+    Ok(GroupCostResult {
+        group: GroupBytes::of(items, group.iter()),
+        exclusive: GroupBytes::of(
+            items,
+            dependencies.iter().filter(|id| !remaining.contains(id)),
+        ),
+        shared: GroupBytes::of(
+            items,
+            dependencies.iter().filter(|id| remaining.contains(id)),
+        ),
+    })
+}
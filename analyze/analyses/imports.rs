@@ -0,0 +1,360 @@
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use csv;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `imports` analysis.
+#[derive(Debug)]
+pub struct ImportsResult {
+    entries: Vec<ImportsEntry>,
+    limit: usize,
+}
+
+impl ImportsResult {
+    /// Every import, costliest first.
+    pub fn entries(&self) -> &[ImportsEntry] {
+        &self.entries
+    }
+}
+
+/// What a single import costs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportsEntry {
+    id: ir::Id,
+    size: u64,
+    type_size: u64,
+    shims: Vec<ir::Id>,
+    shim_size: u64,
+    duplicate_of: Option<ir::Id>,
+}
+
+impl ImportsEntry {
+    /// The import's id.
+    pub fn id(&self) -> ir::Id {
+        self.id
+    }
+
+    /// The size of the import's own entry in the import section.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The size of the import's type, when nothing but the import and its
+    /// shims use that type. Zero otherwise, since removing the import would
+    /// not remove a type that something else still uses.
+    pub fn type_size(&self) -> u64 {
+        self.type_size
+    }
+
+    /// The functions that do nothing but call the import.
+    pub fn shims(&self) -> &[ir::Id] {
+        &self.shims
+    }
+
+    /// The total size of the import's shims.
+    pub fn shim_size(&self) -> u64 {
+        self.shim_size
+    }
+
+    /// Everything that is attributable to the import.
+    pub fn total_size(&self) -> u64 {
+        self.size + self.type_size + self.shim_size
+    }
+
+    /// The first import of the same thing, by the same module and name, if
+    /// this import duplicates it.
+    pub fn duplicate_of(&self) -> Option<ir::Id> {
+        self.duplicate_of
+    }
+}
+
+impl ImportsResult {
+    /// The JSON output, as a value that serde can serialize: a row for each
+    /// listed import with its shims, then rows for the rest and the total.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        let row = |name: String, sums: &Sums| JsonRow {
+            id: None,
+            name,
+            import_size: sums.size,
+            type_size: sums.type_size,
+            shim_size: sums.shim_size,
+            total_size: sums.total_size(),
+            total_size_percent: sums.total_size() as f64 / items.size() as f64 * 100.0,
+            duplicate_of: None,
+            shims: None,
+            duplicate_imports: None,
+            duplicate_import_size: None,
+        };
+
+        let mut rows: Vec<_> = self
+            .entries
+            .iter()
+            .take(self.limit)
+            .map(|entry| JsonRow {
+                id: Some(entry.id.to_string()),
+                duplicate_of: entry.duplicate_of.map(|first| first.to_string()),
+                shims: Some(
+                    entry
+                        .shims
+                        .iter()
+                        .map(|&id| JsonShim {
+                            id: id.to_string(),
+                            name: items[id].name(),
+                            size: items[id].size(),
+                        })
+                        .collect(),
+                ),
+                ..row(
+                    items[entry.id].name().to_string(),
+                    &Sums::new(Some(entry).into_iter()),
+                )
+            })
+            .collect();
+
+        let rest = Sums::new(self.entries.iter().skip(self.limit));
+        if rest.count > 0 {
+            rows.push(row(format!("... and {} more", rest.count), &rest));
+        }
+
+        let all = Sums::new(self.entries.iter());
+        let duplicates = self.duplicates();
+        rows.push(JsonRow {
+            duplicate_imports: Some(duplicates.count),
+            duplicate_import_size: Some(duplicates.size),
+            ..row(format!("Σ [{} Total Rows]", all.count), &all)
+        });
+
+        rows
+    }
+
+    /// The number of duplicate imports, and the bytes their entries in the
+    /// import section waste.
+    fn duplicates(&self) -> Sums {
+        let duplicates = self.entries.iter().filter(|e| e.duplicate_of.is_some());
+        duplicates.fold(Sums::default(), |sums, entry| Sums {
+            count: sums.count + 1,
+            size: sums.size + entry.size,
+            ..sums
+        })
+    }
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonRow<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    import_size: u64,
+    type_size: u64,
+    shim_size: u64,
+    total_size: u64,
+    total_size_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_of: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shims: Option<Vec<JsonShim<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_imports: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_import_size: Option<u64>,
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonShim<'a> {
+    id: String,
+    name: &'a str,
+    size: u64,
+}
+
+/// The sizes of several entries, summed, for the summary rows.
+#[derive(Default)]
+struct Sums {
+    count: usize,
+    size: u64,
+    type_size: u64,
+    shim_size: u64,
+}
+
+impl Sums {
+    fn new<'a>(entries: impl Iterator<Item = &'a ImportsEntry>) -> Sums {
+        entries.fold(Sums::default(), |sums, entry| Sums {
+            count: sums.count + 1,
+            size: sums.size + entry.size,
+            type_size: sums.type_size + entry.type_size,
+            shim_size: sums.shim_size + entry.shim_size,
+        })
+    }
+
+    fn total_size(&self) -> u64 {
+        self.size + self.type_size + self.shim_size
+    }
+}
+
+impl traits::Emit for ImportsResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Total Bytes".to_string()),
+            (Align::Right, "Total %".to_string()),
+            (Align::Right, "Import Bytes".to_string()),
+            (Align::Right, "Type Bytes".to_string()),
+            (Align::Right, "Shim Bytes".to_string()),
+            (Align::Left, "Import".to_string()),
+        ]);
+
+        let mut add_row = |sums: &Sums, name: String| {
+            let total_percent = sums.total_size() as f64 / items.size() as f64 * 100.0;
+            table.add_row(vec![
+                sums.total_size().to_string(),
+                format!("{:.2}%", total_percent),
+                sums.size.to_string(),
+                sums.type_size.to_string(),
+                sums.shim_size.to_string(),
+                name,
+            ]);
+        };
+
+        for entry in self.entries.iter().take(self.limit) {
+            let name = match entry.shims.len() {
+                0 => items[entry.id].name().to_string(),
+                1 => format!("{} (1 shim)", items[entry.id].name()),
+                n => format!("{} ({} shims)", items[entry.id].name(), n),
+            };
+            add_row(&Sums::new(Some(entry).into_iter()), name);
+        }
+
+        let rest = Sums::new(self.entries.iter().skip(self.limit));
+        if rest.count > 0 {
+            add_row(&rest, format!("... and {} more", rest.count));
+        }
+
+        let all = Sums::new(self.entries.iter());
+        add_row(&all, format!("Σ [{} Total Rows]", all.count));
+
+        let duplicates = self.duplicates();
+        if duplicates.count > 0 {
+            add_row(
+                &duplicates,
+                format!("Σ [{} Duplicate Imports]", duplicates.count),
+            );
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            id: String,
+            name: &'a str,
+            import_size: u64,
+            type_size: u64,
+            shims: usize,
+            shim_size: u64,
+            total_size: u64,
+            total_size_percent: f64,
+            duplicate_of: Option<String>,
+        }
+
+        for entry in self.entries.iter().take(self.limit) {
+            wtr.serialize(CsvRecord {
+                id: entry.id.to_string(),
+                name: items[entry.id].name(),
+                import_size: entry.size,
+                type_size: entry.type_size,
+                shims: entry.shims.len(),
+                shim_size: entry.shim_size,
+                total_size: entry.total_size(),
+                total_size_percent: entry.total_size() as f64 / items.size() as f64 * 100.0,
+                duplicate_of: entry.duplicate_of.map(|first| first.to_string()),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// List every import along with what it costs: its own entry in the import
+/// section, the type that only it uses, and the functions that only exist to
+/// call it. Imports that duplicate an earlier one are counted separately too,
+/// since their entries are wasted.
+pub fn imports(items: &mut ir::Items, opts: &opt::Imports) -> traits::Result<ImportsResult> {
+    items.compute_predecessors();
+    let items = &*items;
+    let max_shim_size = u64::from(opts.max_shim_size());
+
+    let mut entries: Vec<ImportsEntry> = items
+        .iter()
+        .filter(|item| item.category() == ir::ItemCategory::Import)
+        .map(|import| {
+            let id = import.id();
+            let shims: Vec<ir::Id> = items
+                .predecessors(id)
+                .filter(|&caller| is_shim(items, caller, id, max_shim_size))
+                .collect();
+            let shim_size = shims.iter().map(|&shim| items[shim].size()).sum();
+
+            let type_size = items
+                .edges(id)
+                .find(|&(_, kind)| kind == ir::EdgeKind::Type)
+                .filter(|&(ty, _)| {
+                    items
+                        .predecessors(ty)
+                        .all(|user| user == id || shims.contains(&user))
+                })
+                .map_or(0, |(ty, _)| items[ty].size());
+
+            ImportsEntry {
+                id,
+                size: import.size(),
+                type_size,
+                shims,
+                shim_size,
+                duplicate_of: items.duplicate_import_of(id),
+            }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| {
+        b.total_size()
+            .cmp(&a.total_size())
+            .then_with(|| items[a.id].name().cmp(items[b.id].name()))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    Ok(ImportsResult {
+        entries,
+        limit: opts.max_items() as usize,
+    })
+}
+
+/// Whether `caller` is a shim for `import`: a small function whose body is
+/// essentially a single call to the import. Besides that call, it may only
+/// reference its own type.
+fn is_shim(items: &ir::Items, caller: ir::Id, import: ir::Id, max_size: u64) -> bool {
+    let item = &items[caller];
+    matches!(item.kind(), ir::ItemKind::Code(_))
+        && item.size() <= max_size
+        && items.edge_kind(caller, import) == Some(ir::EdgeKind::Call)
+        && items
+            .edges(caller)
+            .all(|(to, kind)| to == import || kind == ir::EdgeKind::Type)
+}
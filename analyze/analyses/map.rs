@@ -0,0 +1,82 @@
+use foldhash::{HashMap, HashMapExt};
+
+use twiggy_ir as ir;
+
+/// How the unnamed functions of a binary were matched to the functions of
+/// another build of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MappedNames {
+    exact: usize,
+    normalized: usize,
+    unmatched: usize,
+}
+
+impl MappedNames {
+    /// The number of functions whose body is identical to a named function's.
+    pub fn exact(&self) -> usize {
+        self.exact
+    }
+
+    /// The number of functions whose body only differs from a named
+    /// function's in how the items it refers to are numbered.
+    pub fn normalized(&self) -> usize {
+        self.normalized
+    }
+
+    /// The number of unnamed functions that did not match any named function.
+    pub fn unmatched(&self) -> usize {
+        self.unmatched
+    }
+}
+
+/// Name the unnamed functions of `items` after the functions of `original`
+/// with the same body, such as when `items` was parsed from the output of
+/// `wasm-opt` on a binary that was stripped of its names, and `original` from
+/// its input.
+///
+/// Bodies are first matched exactly, then ignoring the indices they refer to
+/// functions and globals by, since those usually shift when functions are
+/// removed or reordered. When several named functions have the same body, the
+/// first in `Id` order is used. Unnamed functions that match nothing have
+/// ` (unmatched)` appended to their name, so that they stand out.
+///
+/// Both `items` and `original` must have been parsed with body hashes
+/// recorded; functions without them are left as they are.
+pub fn map_names(items: &mut ir::Items, original: &ir::Items) -> MappedNames {
+    let mut exact: HashMap<u64, ir::Id> = HashMap::new();
+    let mut normalized: HashMap<u64, ir::Id> = HashMap::new();
+    for item in original.iter() {
+        match (item.kind(), original.body_hash(item.id())) {
+            (ir::ItemKind::Code(code), Some(hash)) if code.is_named() => {
+                exact.entry(hash.exact()).or_insert(item.id());
+                normalized.entry(hash.normalized()).or_insert(item.id());
+            }
+            _ => {}
+        }
+    }
+
+    let mut mapped = MappedNames::default();
+    let mut names = vec![];
+    for item in items.iter() {
+        let hash = match (item.kind(), items.body_hash(item.id())) {
+            (ir::ItemKind::Code(code), Some(hash)) if !code.is_named() => hash,
+            _ => continue,
+        };
+        let name = if let Some(&id) = exact.get(&hash.exact()) {
+            mapped.exact += 1;
+            original[id].name().to_string()
+        } else if let Some(&id) = normalized.get(&hash.normalized()) {
+            mapped.normalized += 1;
+            original[id].name().to_string()
+        } else {
+            mapped.unmatched += 1;
+            format!("{} (unmatched)", item.name())
+        };
+        names.push((item.id(), name));
+    }
+
+    for (id, name) in names {
+        items.rename_function(id, &name);
+    }
+    mapped
+}
@@ -1,6 +1,97 @@
+pub mod call_indices;
+pub mod callsites;
+pub mod check;
+pub mod crates;
+pub mod data;
 pub mod diff;
 pub mod dominators;
+pub mod duplicates;
+pub mod files;
 pub mod garbage;
+pub mod group_cost;
+pub mod imports;
+pub mod map;
 pub mod monos;
+mod name_filter;
+pub mod neighbors;
 pub mod paths;
+pub mod reachable;
+pub mod roots;
+pub mod sections;
 pub mod top;
+pub mod wire;
+
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// Add the items with the given ids to `selected`, unless they are already
+/// there. Fails if no item has one of the ids.
+fn select_by_id(
+    items: &ir::Items,
+    ids: &[ir::Id],
+    selected: &mut Vec<ir::Id>,
+) -> traits::Result<()> {
+    for &id in ids {
+        if items.get(id).is_none() {
+            return Err(traits::Error::Analysis(format!(
+                "Could not find item with id `{}`",
+                id
+            )));
+        }
+        if !selected.contains(&id) {
+            selected.push(id);
+        }
+    }
+    Ok(())
+}
+
+/// A demangled Rust symbol's name without the trailing `::h` and 16 hex digits
+/// of its hash, along with any `.` and number after the hash, such as the
+/// `.18` that LLVM appends to local symbols to keep them unique. Other names
+/// are returned as they are.
+fn strip_hash(name: &str) -> &str {
+    let mut end = name.len();
+    while let Some(dot) = name[..end].rfind('.') {
+        let suffix = &name[dot + 1..end];
+        if suffix.is_empty() || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+            break;
+        }
+        end = dot;
+    }
+    let hashed = &name[..end];
+    match hashed.rfind("::h") {
+        Some(i)
+            if hashed.len() - i == "::h".len() + 16
+                && hashed[i + "::h".len()..]
+                    .chars()
+                    .all(|c| c.is_ascii_hexdigit()) =>
+        {
+            &name[..i]
+        }
+        _ => name,
+    }
+}
+
+/// The crate that an item belongs to, judging by the leading path segment of
+/// its demangled name, once its hash is stripped. For trait impls, such as
+/// `<a::T as b::Trait>::f`, this is the crate of the implementing type.
+fn crate_name(name: &str) -> Option<&str> {
+    let name = strip_hash(name);
+    let mut path = name.strip_prefix('<').unwrap_or(name);
+    loop {
+        let rest = path
+            .strip_prefix('&')
+            .or_else(|| path.strip_prefix("mut "))
+            .or_else(|| path.strip_prefix("dyn "));
+        match rest {
+            Some(rest) => path = rest,
+            None => break,
+        }
+    }
+    let (segment, _) = path.split_once("::")?;
+    if !segment.is_empty() && segment.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Some(segment)
+    } else {
+        None
+    }
+}
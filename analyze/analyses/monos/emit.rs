@@ -9,16 +9,44 @@ use crate::formats::table::{Align, Table};
 use twiggy_ir as ir;
 use twiggy_traits as traits;
 
-use super::entry::MonosEntry;
-use super::Monos;
+use super::entry::{MonosCrateEntry, MonosEntry};
+use super::MonosResult;
 
-impl traits::Emit for Monos {
+impl MonosResult {
+    /// The JSON output, as a value that serde can serialize: each generic
+    /// function and its monomorphizations, or each crate with its generic
+    /// functions nested in it when grouped by crate.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        let total_size = items.size() as f64;
+        match &self.crates {
+            Some(crates) => JsonOutput::Crates(
+                crates
+                    .iter()
+                    .map(|krate| json_crate(krate, total_size))
+                    .collect(),
+            ),
+            None => JsonOutput::Generics(
+                self.monos
+                    .iter()
+                    .map(|entry| json_entry(entry, total_size))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl traits::Emit for MonosResult {
     #[cfg(feature = "emit_text")]
-    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        if let Some(crates) = &self.crates {
+            return emit_crates_text(crates, items, dest);
+        }
+
         struct TableRow {
-            bloat: Option<u32>,
+            bloat: Option<u64>,
             bloat_percent: Option<f64>,
-            size: u32,
+            size: u64,
             size_percent: f64,
             name: String,
         }
@@ -32,11 +60,14 @@ impl traits::Emit for Monos {
             let MonosEntry {
                 name,
                 insts,
+                truncated_insts,
                 size,
                 bloat,
+                ..
             } = entry;
 
-            let get_size_percent = move |x: u32| f64::from(x) / total_size * 100.0;
+            let get_size_percent = move |x: u64| x as f64 / total_size * 100.0;
+            let truncated_size = truncated_insts.bytes() as u64;
 
             iter::once(TableRow {
                 bloat: Some(*bloat),
@@ -52,6 +83,16 @@ impl traits::Emit for Monos {
                 size_percent: get_size_percent(*size),
                 name: format!("    {}", name),
             }))
+            .chain(
+                Some(TableRow {
+                    bloat: None,
+                    bloat_percent: None,
+                    size: truncated_size,
+                    size_percent: get_size_percent(truncated_size),
+                    name: format!("    {}", truncated_insts.label(total_size as u64)),
+                })
+                .filter(|_| !truncated_insts.is_empty()),
+            )
         }
 
         let mut table = Table::with_header(vec![
@@ -71,7 +112,7 @@ impl traits::Emit for Monos {
         } in self
             .monos
             .iter()
-            .flat_map(|mono| process_entry(mono, f64::from(items.size())))
+            .flat_map(|mono| process_entry(mono, items.size() as f64))
         {
             table.add_row(vec![
                 bloat.map(|b| b.to_string()).unwrap_or_default(),
@@ -88,77 +129,44 @@ impl traits::Emit for Monos {
     }
 
     #[cfg(feature = "emit_json")]
-    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        // Given an entry representing a generic function and its various
-        // monomorphizations, add its information to the given JSON object.
-        fn process_entry(
-            entry: &MonosEntry,
-            obj: &mut json::Object,
-            total_size: f64,
-        ) -> anyhow::Result<()> {
-            let get_size_percent = |size: u32| (f64::from(size)) / total_size * 100.0;
-            let MonosEntry {
-                name,
-                insts,
-                size,
-                bloat,
-            } = entry;
-            obj.field("generic", name.as_str())?;
-            obj.field("approximate_monomorphization_bloat_bytes", *bloat)?;
-            obj.field(
-                "approximate_monomorphization_bloat_percent",
-                get_size_percent(*bloat),
-            )?;
-            obj.field("total_size", *size)?;
-            obj.field("total_size_percent", get_size_percent(*size))?;
-            let mut monos = obj.array("monomorphizations")?;
-            for (name, size, size_percent) in insts
-                .iter()
-                .map(|(name, size)| (name, size, get_size_percent(*size)))
-            {
-                let mut obj = monos.object()?;
-                obj.field("name", name.as_str())?;
-                obj.field("shallow_size", *size)?;
-                obj.field("shallow_size_percent", size_percent)?;
-            }
-            Ok(())
-        }
-
-        let items_size = f64::from(items.size());
-        let mut arr = json::array(dest)?;
-        for entry in &self.monos {
-            let mut obj = arr.object()?;
-            process_entry(entry, &mut obj, items_size)?;
-        }
-
-        Ok(())
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
     }
 
     #[cfg(feature = "emit_csv")]
-    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        if let Some(crates) = &self.crates {
+            return emit_crates_csv(crates, items, dest);
+        }
+
         // Calculate the total size of the collection of items, and define a
-        // helper closure to calculate a percent value for a given u32 size.
-        let items_size = f64::from(items.size());
-        let get_size_percent = |size: u32| (f64::from(size)) / items_size * 100.0;
+        // helper closure to calculate a percent value for a given u64 size.
+        let items_size = items.size() as f64;
+        let get_size_percent = |size: u64| (size as f64) / items_size * 100.0;
 
         #[derive(Debug, Default, Serialize)]
         #[serde(rename_all = "PascalCase")]
         struct Record {
             generic: Option<String>,
-            approximate_monomorphization_bloat_bytes: Option<u32>,
+            approximate_monomorphization_bloat_bytes: Option<u64>,
             approximate_monomorphization_bloat_percent: Option<f64>,
-            total_size: Option<u32>,
+            total_size: Option<u64>,
             total_size_percent: Option<f64>,
             monomorphizations: Option<String>,
+            truncated_count: Option<usize>,
+            truncated_bytes: Option<i64>,
         }
 
         // Given a single `MonosEntry` object, create a record object.
         let process_entry = |entry: &MonosEntry| -> Record {
-            let monos = entry
+            let mut monos = entry
                 .insts
                 .iter()
-                .map(|(name, _)| name.as_str())
+                .map(|(name, _)| name.clone())
                 .collect::<Vec<_>>();
+            if !entry.truncated_insts.is_empty() {
+                monos.push(entry.truncated_insts.label(items.size()));
+            }
             Record {
                 generic: Some(entry.name.clone()),
                 approximate_monomorphization_bloat_bytes: Some(entry.bloat),
@@ -166,6 +174,8 @@ impl traits::Emit for Monos {
                 total_size: Some(entry.size),
                 total_size_percent: Some(get_size_percent(entry.size)),
                 monomorphizations: Some(monos.join(", ")),
+                truncated_count: entry.truncated.map(|truncated| truncated.count()),
+                truncated_bytes: entry.truncated.map(|truncated| truncated.bytes()),
             }
         };
 
@@ -180,3 +190,211 @@ impl traits::Emit for Monos {
         Ok(())
     }
 }
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum JsonOutput<'a> {
+    Generics(Vec<JsonEntry<'a>>),
+    Crates(Vec<JsonCrate<'a>>),
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonEntry<'a> {
+    generic: &'a str,
+    approximate_monomorphization_bloat_bytes: u64,
+    approximate_monomorphization_bloat_percent: f64,
+    total_size: u64,
+    total_size_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_bytes: Option<i64>,
+    monomorphizations: Vec<JsonMono>,
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonMono {
+    name: String,
+    shallow_size: u64,
+    shallow_size_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_bytes: Option<i64>,
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonCrate<'a> {
+    #[serde(rename = "crate")]
+    krate: &'a str,
+    approximate_monomorphization_bloat_bytes: u64,
+    approximate_monomorphization_bloat_percent: f64,
+    total_size: u64,
+    total_size_percent: f64,
+    instantiations: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_bytes: Option<i64>,
+    generics: Vec<JsonEntry<'a>>,
+}
+
+/// The JSON object of an entry representing a generic function and its
+/// various monomorphizations.
+#[cfg(feature = "emit_json")]
+fn json_entry(entry: &MonosEntry, total_size: f64) -> JsonEntry<'_> {
+    let get_size_percent = |size: u64| (size as f64) / total_size * 100.0;
+    let MonosEntry {
+        name,
+        insts,
+        truncated_insts,
+        size,
+        bloat,
+        truncated,
+        ..
+    } = entry;
+    let mut monomorphizations: Vec<_> = insts
+        .iter()
+        .map(|(name, size)| JsonMono {
+            name: name.clone(),
+            shallow_size: *size,
+            shallow_size_percent: get_size_percent(*size),
+            truncated_count: None,
+            truncated_bytes: None,
+        })
+        .collect();
+    if !truncated_insts.is_empty() {
+        let size = truncated_insts.bytes() as u64;
+        monomorphizations.push(JsonMono {
+            name: truncated_insts.label(total_size as u64),
+            shallow_size: size,
+            shallow_size_percent: get_size_percent(size),
+            truncated_count: Some(truncated_insts.count()),
+            truncated_bytes: Some(truncated_insts.bytes()),
+        });
+    }
+    JsonEntry {
+        generic: name,
+        approximate_monomorphization_bloat_bytes: *bloat,
+        approximate_monomorphization_bloat_percent: get_size_percent(*bloat),
+        total_size: *size,
+        total_size_percent: get_size_percent(*size),
+        truncated_count: truncated.map(|truncated| truncated.count()),
+        truncated_bytes: truncated.map(|truncated| truncated.bytes()),
+        monomorphizations,
+    }
+}
+
+/// List each crate's totals, followed by its most bloaty generic function.
+#[cfg(feature = "emit_text")]
+fn emit_crates_text(
+    crates: &[MonosCrateEntry],
+    items: &ir::Items,
+    dest: &mut dyn io::Write,
+) -> traits::Result<()> {
+    let total_size = items.size() as f64;
+    let get_size_percent = |size: u64| size as f64 / total_size * 100.0;
+    let mut table = Table::with_header(vec![
+        (Align::Right, "Apprx. Bloat Bytes".into()),
+        (Align::Right, "Apprx. Bloat %".into()),
+        (Align::Right, "Bytes".into()),
+        (Align::Right, "%".into()),
+        (Align::Right, "Instantiations".into()),
+        (Align::Left, "Crate and Its Most Bloaty Generic".to_string()),
+    ]);
+    for krate in crates {
+        table.add_row(vec![
+            krate.bloat.to_string(),
+            format!("{:.2}%", get_size_percent(krate.bloat)),
+            krate.size.to_string(),
+            format!("{:.2}%", get_size_percent(krate.size)),
+            krate.count.to_string(),
+            krate.name.clone(),
+        ]);
+        if let Some(generic) = krate.generics.first() {
+            table.add_row(vec![
+                generic.bloat.to_string(),
+                format!("{:.2}%", get_size_percent(generic.bloat)),
+                generic.size.to_string(),
+                format!("{:.2}%", get_size_percent(generic.size)),
+                generic.count.to_string(),
+                format!("    {}", generic.name),
+            ]);
+        }
+    }
+    write!(dest, "{}", &table)?;
+    Ok(())
+}
+
+/// The JSON object of a crate's totals, with all of its generic functions
+/// nested in it.
+#[cfg(feature = "emit_json")]
+fn json_crate(krate: &MonosCrateEntry, total_size: f64) -> JsonCrate<'_> {
+    let get_size_percent = |size: u64| (size as f64) / total_size * 100.0;
+    JsonCrate {
+        krate: &krate.name,
+        approximate_monomorphization_bloat_bytes: krate.bloat,
+        approximate_monomorphization_bloat_percent: get_size_percent(krate.bloat),
+        total_size: krate.size,
+        total_size_percent: get_size_percent(krate.size),
+        instantiations: krate.count,
+        truncated_count: krate.truncated.map(|truncated| truncated.count()),
+        truncated_bytes: krate.truncated.map(|truncated| truncated.bytes()),
+        generics: krate
+            .generics
+            .iter()
+            .map(|generic| json_entry(generic, total_size))
+            .collect(),
+    }
+}
+
+/// List each crate's totals, and the name and bloat of its most bloaty
+/// generic function.
+#[cfg(feature = "emit_csv")]
+fn emit_crates_csv(
+    crates: &[MonosCrateEntry],
+    items: &ir::Items,
+    dest: &mut dyn io::Write,
+) -> traits::Result<()> {
+    let items_size = items.size() as f64;
+    let get_size_percent = |size: u64| (size as f64) / items_size * 100.0;
+
+    #[derive(Debug, Default, Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Record {
+        #[serde(rename = "Crate")]
+        krate: String,
+        approximate_monomorphization_bloat_bytes: u64,
+        approximate_monomorphization_bloat_percent: f64,
+        total_size: u64,
+        total_size_percent: f64,
+        instantiations: usize,
+        top_generic: Option<String>,
+        top_generic_bloat_bytes: Option<u64>,
+        truncated_count: Option<usize>,
+        truncated_bytes: Option<i64>,
+    }
+
+    let mut wtr = csv::Writer::from_writer(dest);
+    for krate in crates {
+        let top = krate.generics.first();
+        wtr.serialize(Record {
+            krate: krate.name.clone(),
+            approximate_monomorphization_bloat_bytes: krate.bloat,
+            approximate_monomorphization_bloat_percent: get_size_percent(krate.bloat),
+            total_size: krate.size,
+            total_size_percent: get_size_percent(krate.size),
+            instantiations: krate.count,
+            top_generic: top.map(|generic| generic.name.clone()),
+            top_generic_bloat_bytes: top.map(|generic| generic.bloat),
+            truncated_count: krate.truncated.map(|truncated| truncated.count()),
+            truncated_bytes: krate.truncated.map(|truncated| truncated.bytes()),
+        })?;
+        wtr.flush()?;
+    }
+    Ok(())
+}
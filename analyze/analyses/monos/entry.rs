@@ -1,9 +1,55 @@
+use crate::formats::truncated::Truncated;
+
+/// A generic function and its monomorphizations.
 #[derive(Debug, PartialEq, Eq)]
-pub(super) struct MonosEntry {
-    pub name: String,
-    pub insts: Vec<(String, u32)>,
-    pub size: u32,
-    pub bloat: u32,
+pub struct MonosEntry {
+    pub(super) name: String,
+    pub(super) insts: Vec<(String, u64)>,
+    pub(super) truncated_insts: Truncated,
+    pub(super) count: usize,
+    pub(super) size: u64,
+    pub(super) bloat: u64,
+    pub(super) truncated: Option<Truncated>,
+}
+
+impl MonosEntry {
+    /// The generic function's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The names and sizes of the generic function's instantiations.
+    pub fn insts(&self) -> &[(String, u64)] {
+        &self.insts
+    }
+
+    /// The instantiations beyond `max_monos`, which are summarized rather
+    /// than displayed.
+    pub fn truncated_insts(&self) -> Truncated {
+        self.truncated_insts
+    }
+
+    /// The number of instantiations, including those that are not listed.
+    pub fn instantiations(&self) -> usize {
+        self.count
+    }
+
+    /// The combined size of all instantiations, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The approximate number of bytes that could be saved by removing
+    /// all but the largest instantiation.
+    pub fn bloat(&self) -> u64 {
+        self.bloat
+    }
+
+    /// The rows this entry summarizes, if it is the entry summarizing the
+    /// generic functions beyond `max_generics` and their instantiations.
+    pub fn truncated(&self) -> Option<Truncated> {
+        self.truncated
+    }
 }
 
 impl PartialOrd for MonosEntry {
@@ -21,3 +67,49 @@ impl Ord for MonosEntry {
             .then(self.name.cmp(&rhs.name))
     }
 }
+
+/// The generic functions that a crate defines, and their monomorphizations.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MonosCrateEntry {
+    pub(super) name: String,
+    pub(super) generics: Vec<MonosEntry>,
+    pub(super) count: usize,
+    pub(super) size: u64,
+    pub(super) bloat: u64,
+    pub(super) truncated: Option<Truncated>,
+}
+
+impl MonosCrateEntry {
+    /// The crate's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The crate's generic functions, most bloaty first.
+    pub fn generics(&self) -> &[MonosEntry] {
+        &self.generics
+    }
+
+    /// The number of instantiations of all of the crate's generic functions.
+    pub fn instantiations(&self) -> usize {
+        self.count
+    }
+
+    /// The combined size of all of the crate's instantiations, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The approximate number of bytes that could be saved by removing all
+    /// but the largest instantiation of each of the crate's generic
+    /// functions.
+    pub fn bloat(&self) -> u64 {
+        self.bloat
+    }
+
+    /// The crates this entry summarizes, if it is the entry summarizing the
+    /// crates beyond `max_generics`.
+    pub fn truncated(&self) -> Option<Truncated> {
+        self.truncated
+    }
+}
@@ -1,6 +1,8 @@
+use foldhash::{HashMap, HashMapExt};
 use std::cmp;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeMap;
 
+use crate::formats::truncated::Truncated;
 use regex;
 use twiggy_ir as ir;
 use twiggy_opt as opt;
@@ -9,72 +11,91 @@ use twiggy_traits as traits;
 mod emit;
 mod entry;
 
-use self::entry::MonosEntry;
+pub use self::entry::{MonosCrateEntry, MonosEntry};
 
+/// The result of the `monos` analysis.
 #[derive(Debug)]
-struct Monos {
+pub struct MonosResult {
     monos: Vec<MonosEntry>,
+    crates: Option<Vec<MonosCrateEntry>>,
+}
+
+impl MonosResult {
+    /// The generic functions, most bloaty first. The final entries summarize
+    /// the truncated rows and the total. Empty if the generic functions are
+    /// grouped by crate.
+    pub fn entries(&self) -> &[MonosEntry] {
+        &self.monos
+    }
+
+    /// The crates that define the generic functions, most bloaty first, if
+    /// they are grouped by crate. The final entries summarize the truncated
+    /// crates and the total.
+    pub fn crates(&self) -> Option<&[MonosCrateEntry]> {
+        self.crates.as_deref()
+    }
 }
 
 /// Type alias used to represent a map of generic function names and instantiations.
-type MonosMap<'a> = BTreeMap<&'a str, Vec<(String, u32)>>;
+type MonosMap<'a> = HashMap<&'a str, Vec<(&'a str, u64)>>;
 
 /// Collect the monomorphizations of generic functions into a map, then
 /// process the entries and sort the resulting vector.
 fn collect_monomorphizations<'a>(
     items: &'a ir::Items,
     opts: &opt::Monos,
-) -> anyhow::Result<MonosMap<'a>> {
+) -> traits::Result<MonosMap<'a>> {
     let args_given = !opts.functions().is_empty();
     let using_regexps = opts.using_regexps();
     let regexps = regex::RegexSet::new(opts.functions())?;
 
-    let unsorted_monos: BTreeMap<&'a str, BTreeSet<(String, u32)>> = items
-        .iter()
-        .filter_map(|item| {
-            if let Some(generic) = item.monomorphization_of() {
-                Some((generic, item))
-            } else {
-                None
-            }
-        })
-        .filter(|(generic, _)| match (args_given, using_regexps) {
+    // Group the instantiations by generic function in a single pass. Names are
+    // borrowed from the items, and only copied for the rows that are shown.
+    let mut monos: MonosMap<'a> = HashMap::new();
+    for item in items.iter() {
+        let Some(generic) = item.monomorphization_of() else {
+            continue;
+        };
+        let requested = match (args_given, using_regexps) {
             (true, true) => regexps.is_match(generic),
             (true, false) => opts.functions().iter().any(|name| name == generic),
             (false, _) => true,
-        })
-        .fold(BTreeMap::new(), |mut monos, (generic, inst)| {
+        };
+        if requested {
             monos
                 .entry(generic)
-                .or_insert_with(BTreeSet::new)
-                .insert((inst.name().to_string(), inst.size()));
-            monos
-        });
+                .or_default()
+                .push((item.name(), item.size()));
+        }
+    }
 
-    Ok(unsorted_monos
-        .into_iter()
-        .map(|(generic, inst_set)| {
-            let mut insts = inst_set.into_iter().collect::<Vec<_>>();
-            insts.sort_by(|(a_name, a_size), (b_name, b_size)| {
-                b_size.cmp(a_size).then(a_name.cmp(b_name))
-            });
-            (generic, insts)
-        })
-        .collect())
+    // Each generic's instantiations are sorted once, largest first, with ties
+    // sorted by name.
+    for insts in monos.values_mut() {
+        insts.sort_unstable_by(|(a_name, a_size), (b_name, b_size)| {
+            b_size.cmp(a_size).then(a_name.cmp(b_name))
+        });
+        insts.dedup();
+    }
+    Ok(monos)
 }
 
 /// Helper function usedd to summarize a sequence of `MonosEntry` objects.
 /// Returns a tuple representing the number of items summarized, the total
 /// size of the items, and the total approximate potential savings.
-fn summarize_entries<'a>(entries: impl Iterator<Item = &'a MonosEntry>) -> (usize, u32, u32) {
+fn summarize_entries<'a>(entries: impl Iterator<Item = &'a MonosEntry>) -> (usize, u64, u64) {
     entries.fold(
         (0, 0, 0),
         |(total_cnt, total_size, total_savings),
          MonosEntry {
-             insts, size, bloat, ..
+             insts,
+             truncated_insts,
+             size,
+             bloat,
+             ..
          }| {
             (
-                total_cnt + 1 + insts.len(),
+                total_cnt + 1 + insts.len() + truncated_insts.count(),
                 total_size + size,
                 total_savings + bloat,
             )
@@ -82,22 +103,13 @@ fn summarize_entries<'a>(entries: impl Iterator<Item = &'a MonosEntry>) -> (usiz
     )
 }
 
-/// Helper function used to summarize a sequence of tuples representing
-/// instantiations of a generic function. Returns a tuple representing the
-/// number of instantiations found, and the total size.
-fn summarize_insts<'a>(entries: impl Iterator<Item = &'a (String, u32)>) -> (u32, u32) {
-    entries.fold((0, 0), |(total_cnt, total_size), (_, size)| {
-        (total_cnt + 1, total_size + size)
-    })
-}
-
 /// Find the approximate potential savings by calculating the benefits of
 /// removing the largest instantiation, and the benefits of removing an
 /// average instantiation. Returns a tuple containing total size, and bloat.
-fn calculate_total_and_bloat(insts: &[(String, u32)]) -> Option<(u32, u32)> {
+fn calculate_total_and_bloat(insts: &[(&str, u64)]) -> Option<(u64, u64)> {
     if let Some(max) = insts.iter().map(|(_, size)| size).max() {
-        let total_size = insts.iter().map(|(_, size)| size).sum::<u32>();
-        let inst_cnt = insts.len() as u32;
+        let total_size = insts.iter().map(|(_, size)| size).sum::<u64>();
+        let inst_cnt = insts.len() as u64;
         let size_per_inst = total_size / inst_cnt;
         let avg_savings = size_per_inst * (inst_cnt - 1);
         let removing_largest_savings = total_size - max;
@@ -108,6 +120,36 @@ fn calculate_total_and_bloat(insts: &[(String, u32)]) -> Option<(u32, u32)> {
     }
 }
 
+/// The instantiations to list, largest first, named as they are shown. With
+/// the hashes stripped, instantiations whose names are then the same are
+/// listed as one, with their sizes added up. The generic function's count,
+/// size and bloat are still those of the individual instantiations.
+fn listed_insts<'a>(
+    insts: &[(&'a str, u64)],
+    disambiguators: opt::Disambiguators,
+) -> Vec<(&'a str, u64)> {
+    if disambiguators == opt::Disambiguators::Keep {
+        return insts.to_vec();
+    }
+
+    let mut listed: Vec<(&str, u64)> = vec![];
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for &(name, size) in insts {
+        let name = super::strip_hash(name);
+        match positions.get(name) {
+            Some(&i) => listed[i].1 += size,
+            None => {
+                positions.insert(name, listed.len());
+                listed.push((name, size));
+            }
+        }
+    }
+    listed.sort_unstable_by(|(a_name, a_size), (b_name, b_size)| {
+        b_size.cmp(a_size).then(a_name.cmp(b_name))
+    });
+    listed
+}
+
 /// Process all of the monorphizations, into a vector of `MonosEntry` objects.
 fn process_monomorphizations(monos_map: MonosMap, opts: &opt::Monos) -> Vec<MonosEntry> {
     let mut monos = monos_map
@@ -115,27 +157,36 @@ fn process_monomorphizations(monos_map: MonosMap, opts: &opt::Monos) -> Vec<Mono
         .filter_map(|(g, insts)| {
             calculate_total_and_bloat(&insts).map(|(total, bloat)| (g, insts, total, bloat))
         })
-        .map(|(g, mut insts, t, b)| {
+        .map(|(g, insts, t, b)| {
+            let count = insts.len();
             // Truncate `insts` according to the relevant options before
             // we map these values into `MonosEntry` objects.
-            if opts.only_generics() {
-                insts.truncate(0);
+            let (insts, truncated_insts) = if opts.only_generics() {
+                (vec![], Truncated::default())
             } else {
+                let insts = listed_insts(&insts, opts.disambiguators());
                 let max_monos = opts.max_monos() as usize;
-                let (rem_cnt, rem_size) = summarize_insts(insts.iter().skip(max_monos));
-                insts.truncate(max_monos);
-                if rem_cnt > 0 {
-                    insts.push((format!("... and {} more.", rem_cnt), rem_size));
-                }
+                let truncated = Truncated::of(insts.iter().skip(max_monos).map(|(_, size)| *size));
+                let shown: Vec<(String, u64)> = insts
+                    .into_iter()
+                    .take(max_monos)
+                    .map(|(name, size)| (name.to_string(), size))
+                    .collect();
+                (shown, truncated)
             };
-            (g, insts, t, b)
-        })
-        .map(|(name, insts, size, bloat)| MonosEntry {
-            name: name.to_string(),
-            insts,
-            size,
-            bloat,
+            (g, insts, truncated_insts, count, t, b)
         })
+        .map(
+            |(name, insts, truncated_insts, count, size, bloat)| MonosEntry {
+                name: name.to_string(),
+                insts,
+                truncated_insts,
+                count,
+                size,
+                bloat,
+                truncated: None,
+            },
+        )
         .collect::<Vec<_>>();
     monos.sort();
     monos
@@ -143,7 +194,7 @@ fn process_monomorphizations(monos_map: MonosMap, opts: &opt::Monos) -> Vec<Mono
 
 /// Adds entries to summarize remaining rows that will be truncated, and
 /// totals for the entire set of monomorphizations.
-fn add_stats(mut monos: Vec<MonosEntry>, opts: &opt::Monos) -> Vec<MonosEntry> {
+fn add_stats(mut monos: Vec<MonosEntry>, opts: &opt::Monos, items_size: u64) -> Vec<MonosEntry> {
     let max_generics = opts.max_generics() as usize;
 
     // Create an entry to represent the remaining rows that will be truncated,
@@ -152,11 +203,15 @@ fn add_stats(mut monos: Vec<MonosEntry>, opts: &opt::Monos) -> Vec<MonosEntry> {
         if monos.len() > max_generics {
             let (rem_cnt, rem_size, rem_savings) =
                 summarize_entries(monos.iter().skip(max_generics));
+            let truncated = Truncated::new(rem_cnt, rem_size as i64);
             Some(MonosEntry {
-                name: format!("... and {} more.", rem_cnt),
+                name: truncated.label(items_size),
                 size: rem_size,
                 insts: vec![],
+                truncated_insts: Truncated::default(),
+                count: monos.iter().skip(max_generics).map(|mono| mono.count).sum(),
                 bloat: rem_savings,
+                truncated: Some(truncated),
             })
         } else {
             None
@@ -170,7 +225,10 @@ fn add_stats(mut monos: Vec<MonosEntry>, opts: &opt::Monos) -> Vec<MonosEntry> {
             name: format!("Σ [{} Total Rows]", total_cnt),
             size: total_size,
             insts: vec![],
+            truncated_insts: Truncated::default(),
+            count: monos.iter().map(|mono| mono.count).sum(),
             bloat: total_savings,
+            truncated: None,
         }
     };
 
@@ -183,10 +241,92 @@ fn add_stats(mut monos: Vec<MonosEntry>, opts: &opt::Monos) -> Vec<MonosEntry> {
     monos
 }
 
+/// The crate that defines a generic function, by the first path segment of
+/// its name. For the generic functions of trait impls on type parameters,
+/// such as `<&T as core::fmt::Debug>::fmt`, that is the trait's crate.
+fn defining_crate(generic: &str) -> &str {
+    let trait_crate = || {
+        let (_, path) = generic.split_once(" as ")?;
+        super::crate_name(path)
+    };
+    super::crate_name(generic)
+        .or_else(trait_crate)
+        .unwrap_or("<unknown>")
+}
+
+/// Bucket the generic functions by the crates that define them, most bloaty
+/// first, and add entries to summarize the crates beyond `max_generics` and
+/// the total.
+fn group_by_crate(
+    monos: Vec<MonosEntry>,
+    opts: &opt::Monos,
+    items_size: u64,
+) -> Vec<MonosCrateEntry> {
+    let mut crates: BTreeMap<String, MonosCrateEntry> = BTreeMap::new();
+    for mono in monos {
+        let name = defining_crate(&mono.name);
+        let krate = crates
+            .entry(name.to_string())
+            .or_insert_with(|| MonosCrateEntry {
+                name: name.to_string(),
+                generics: vec![],
+                count: 0,
+                size: 0,
+                bloat: 0,
+                truncated: None,
+            });
+        krate.count += mono.count;
+        krate.size += mono.size;
+        krate.bloat += mono.bloat;
+        krate.generics.push(mono);
+    }
+    let mut crates: Vec<MonosCrateEntry> = crates.into_values().collect();
+    crates.sort_by(|a, b| {
+        b.bloat
+            .cmp(&a.bloat)
+            .then(b.size.cmp(&a.size))
+            .then(a.name.cmp(&b.name))
+    });
+
+    let summarize =
+        |crates: &[MonosCrateEntry], name: String, truncated: Option<Truncated>| MonosCrateEntry {
+            name,
+            generics: vec![],
+            count: crates.iter().map(|krate| krate.count).sum(),
+            size: crates.iter().map(|krate| krate.size).sum(),
+            bloat: crates.iter().map(|krate| krate.bloat).sum(),
+            truncated,
+        };
+    let total = summarize(&crates, format!("Σ [{} Total Rows]", crates.len()), None);
+    let max_crates = opts.max_generics() as usize;
+    if crates.len() > max_crates {
+        let rest = crates.split_off(max_crates);
+        let truncated = Truncated::of(rest.iter().map(|krate| krate.size));
+        crates.push(summarize(
+            &rest,
+            truncated.label(items_size),
+            Some(truncated),
+        ));
+    }
+    crates.push(total);
+    crates
+}
+
 /// Find bloaty monomorphizations of generic functions.
-pub fn monos(items: &mut ir::Items, opts: &opt::Monos) -> anyhow::Result<Box<dyn traits::Emit>> {
+pub fn monos(items: &mut ir::Items, opts: &opt::Monos) -> traits::Result<MonosResult> {
     let monos_map = collect_monomorphizations(items, opts)?;
-    let mut monos = process_monomorphizations(monos_map, opts);
-    monos = add_stats(monos, opts);
-    Ok(Box::new(Monos { monos }) as Box<_>)
+    let monos = process_monomorphizations(monos_map, opts);
+    match opts.group_by() {
+        None => Ok(MonosResult {
+            monos: add_stats(monos, opts, items.size()),
+            crates: None,
+        }),
+        Some(opt::GroupBy::Crate) => Ok(MonosResult {
+            monos: vec![],
+            crates: Some(group_by_crate(monos, opts, items.size())),
+        }),
+        Some(opt::GroupBy::File) => Err(traits::Error::Analysis(
+            "`monos` can only group generic functions by `crate`".to_string(),
+        )),
+    }
 }
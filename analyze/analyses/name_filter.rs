@@ -0,0 +1,44 @@
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// Filters items by their demangled names, for the `--only` and `--exclude`
+/// options of the analyses that take them.
+#[derive(Debug)]
+pub(crate) struct NameFilter {
+    only: Option<regex::RegexSet>,
+    excluded: Option<regex::RegexSet>,
+}
+
+impl NameFilter {
+    /// Keep the items whose names match one of the `only` patterns, if there
+    /// are any, and then leave out those whose names match one of the
+    /// `excluded` patterns.
+    pub(crate) fn new(only: &[String], excluded: &[String]) -> traits::Result<NameFilter> {
+        let set = |patterns: &[String]| {
+            if patterns.is_empty() {
+                Ok(None)
+            } else {
+                regex::RegexSet::new(patterns).map(Some)
+            }
+        };
+        Ok(NameFilter {
+            only: set(only)?,
+            excluded: set(excluded)?,
+        })
+    }
+
+    /// Whether any patterns were given at all.
+    pub(crate) fn is_active(&self) -> bool {
+        self.only.is_some() || self.excluded.is_some()
+    }
+
+    /// Whether the given item passes the filter.
+    pub(crate) fn includes(&self, item: &ir::Item) -> bool {
+        let name = item.name();
+        self.only.as_ref().is_none_or(|only| only.is_match(name))
+            && !self
+                .excluded
+                .as_ref()
+                .is_some_and(|excluded| excluded.is_match(name))
+    }
+}
@@ -0,0 +1,193 @@
+use std::io;
+
+use csv;
+use regex;
+use serde_derive::Serialize;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `neighbors` analysis.
+#[derive(Debug)]
+pub struct NeighborsResult {
+    entries: Vec<NeighborsEntry>,
+}
+
+impl NeighborsResult {
+    /// One entry for each item that matched the requested names.
+    pub fn entries(&self) -> &[NeighborsEntry] {
+        &self.entries
+    }
+
+    /// The JSON output, as a value that serde can serialize: each matched
+    /// item, with its neighbors.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let item = &items[entry.id];
+                JsonEntry {
+                    id: entry.id.to_string(),
+                    name: item.name(),
+                    size: item.size(),
+                    neighbors: entry
+                        .neighbors
+                        .iter()
+                        .map(|neighbor| JsonNeighbor {
+                            id: neighbor.id().to_string(),
+                            name: neighbor.name(),
+                            size: neighbor.size(),
+                            edge_kind: neighbor.edge_kind().as_str(),
+                            direction: neighbor.direction().as_str(),
+                        })
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonEntry<'a> {
+    id: String,
+    name: &'a str,
+    size: u64,
+    neighbors: Vec<JsonNeighbor<'a>>,
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonNeighbor<'a> {
+    id: String,
+    name: &'a str,
+    size: u64,
+    edge_kind: &'static str,
+    direction: &'static str,
+}
+
+/// An item that matched the requested names, and its neighbors.
+#[derive(Debug)]
+pub struct NeighborsEntry {
+    id: ir::Id,
+    neighbors: Vec<ir::Neighbor>,
+}
+
+impl NeighborsEntry {
+    /// The matched item.
+    pub fn id(&self) -> ir::Id {
+        self.id
+    }
+
+    /// The items adjacent to the matched item, outgoing before incoming.
+    pub fn neighbors(&self) -> &[ir::Neighbor] {
+        &self.neighbors
+    }
+}
+
+impl traits::Emit for NeighborsResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Bytes".to_string()),
+            (Align::Left, "Edge".to_string()),
+            (Align::Left, "Neighbors".to_string()),
+        ]);
+
+        for entry in &self.entries {
+            let item = &items[entry.id];
+            table.add_row(vec![
+                item.size().to_string(),
+                String::new(),
+                item.name().to_string(),
+            ]);
+            for neighbor in &entry.neighbors {
+                let arrow = match neighbor.direction() {
+                    ir::Direction::Incoming => "<-",
+                    _ => "->",
+                };
+                table.add_row(vec![
+                    neighbor.size().to_string(),
+                    format!("{} {}", arrow, neighbor.edge_kind()),
+                    format!("  ⤷ {}", neighbor.name()),
+                ]);
+            }
+        }
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            item: &'a str,
+            neighbor: &'a str,
+            size: u64,
+            edge_kind: &'static str,
+            direction: &'static str,
+        }
+
+        let mut wtr = csv::Writer::from_writer(dest);
+        for entry in &self.entries {
+            for neighbor in &entry.neighbors {
+                wtr.serialize(CsvRecord {
+                    item: items[entry.id].name(),
+                    neighbor: neighbor.name(),
+                    size: neighbor.size(),
+                    edge_kind: neighbor.edge_kind().as_str(),
+                    direction: neighbor.direction().as_str(),
+                })?;
+            }
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// List the neighbors of the given items.
+pub fn neighbors(items: &mut ir::Items, opts: &opt::Neighbors) -> traits::Result<NeighborsResult> {
+    if opts.direction() != ir::Direction::Outgoing {
+        items.compute_predecessors();
+    }
+
+    let mut matches: Vec<ir::Id> = if opts.items().is_empty() {
+        vec![]
+    } else if opts.using_regexps() {
+        let regexps = regex::RegexSet::new(opts.items())?;
+        items
+            .iter()
+            .filter(|item| regexps.is_match(item.name()))
+            .map(|item| item.id())
+            .collect()
+    } else {
+        // Several items can share a name, in which case all of them are used.
+        opts.items()
+            .iter()
+            .flat_map(|name| items.get_items_by_name(name))
+            .map(|item| item.id())
+            .collect()
+    };
+    super::select_by_id(items, opts.ids(), &mut matches)?;
+
+    let entries = matches
+        .into_iter()
+        .map(|id| NeighborsEntry {
+            id,
+            neighbors: items.neighbor_items(id, opts.direction(), opts.edges()),
+        })
+        .collect();
+
+    Ok(NeighborsResult { entries })
+}
@@ -9,37 +9,67 @@ use twiggy_traits as traits;
 mod paths_emit;
 mod paths_entry;
 
-use self::paths_entry::PathsEntry;
+pub use self::paths_entry::PathsEntry;
 
+/// The result of the `paths` analysis.
 #[derive(Debug)]
-struct Paths {
+pub struct PathsResult {
     opts: opt::Paths,
     entries: Vec<PathsEntry>,
 }
 
+impl PathsResult {
+    /// The starting items, each with its retaining (or, when descending,
+    /// retained) paths as children.
+    pub fn entries(&self) -> &[PathsEntry] {
+        &self.entries
+    }
+}
+
 /// Find all retaining paths for the given items.
-pub fn paths(items: &mut ir::Items, opts: &opt::Paths) -> anyhow::Result<Box<dyn traits::Emit>> {
+pub fn paths(items: &mut ir::Items, opts: &opt::Paths) -> traits::Result<PathsResult> {
     // The predecessor tree only needs to be computed if we are ascending
     // through the retaining paths.
     if !opts.descending() {
         items.compute_predecessors();
     }
+    items.compute_retained_sizes();
+
+    let regexps = if opts.using_regexps() {
+        Some(regex::RegexSet::new(opts.functions())?)
+    } else {
+        None
+    };
 
     // Initialize the collection of Id values whose retaining paths we will emit.
+    // When they were matched by regular expressions, each one also records
+    // which of those it matched.
     let opts = opts.clone();
-    let entries = get_starting_positions(items, &opts)?
+    let entries = get_starting_positions(items, &opts, regexps.as_ref())?
         .iter()
-        .map(|id| create_entry(*id, items, &opts, &mut BTreeSet::new()))
+        .map(|id| {
+            let mut entry = create_entry(*id, None, items, &opts, &mut BTreeSet::new());
+            if let Some(regexps) = &regexps {
+                entry.patterns = regexps
+                    .matches(&entry.name)
+                    .into_iter()
+                    .map(|i| regexps.patterns()[i].clone())
+                    .collect();
+            }
+            entry
+        })
         .collect();
 
-    let paths = Paths { opts, entries };
-
-    Ok(Box::new(paths) as Box<_>)
+    Ok(PathsResult { opts, entries })
 }
 
 /// This helper function is used to collect the `ir::Id` values for the top-most
-/// path entries for the `Paths` object, based on the given options.
-fn get_starting_positions(items: &ir::Items, opts: &opt::Paths) -> anyhow::Result<Vec<ir::Id>> {
+/// path entries for the `PathsResult` object, based on the given options.
+fn get_starting_positions(
+    items: &ir::Items,
+    opts: &opt::Paths,
+    regexps: Option<&regex::RegexSet>,
+) -> traits::Result<Vec<ir::Id>> {
     // Collect Id's if no arguments are given and we are ascending the retaining paths.
     let get_functions_default = || -> Vec<ir::Id> {
         let mut sorted_items = items
@@ -60,22 +90,33 @@ fn get_starting_positions(items: &ir::Items, opts: &opt::Paths) -> anyhow::Resul
         roots.into_iter().map(|item| item.id()).collect()
     };
 
-    // Collect Id's if arguments were given that should be used as regular expressions.
-    let get_regexp_matches = || -> anyhow::Result<Vec<ir::Id>> {
-        let regexps = regex::RegexSet::new(opts.functions())?;
-        let matches = items
-            .iter()
-            .filter(|item| regexps.is_match(item.name()))
-            .map(|item| item.id())
-            .collect();
+    // Collect Id's if arguments were given that should be used as regular
+    // expressions. Each of them must match at least one item.
+    let get_regexp_matches = |regexps: &regex::RegexSet| -> traits::Result<Vec<ir::Id>> {
+        let mut matched = vec![false; regexps.len()];
+        let mut matches = vec![];
+        for item in items.iter() {
+            let item_matches = regexps.matches(item.name());
+            if item_matches.matched_any() {
+                item_matches.iter().for_each(|i| matched[i] = true);
+                matches.push(item.id());
+            }
+        }
+        if let Some(i) = matched.iter().position(|&matched| !matched) {
+            return Err(traits::Error::Analysis(format!(
+                "Could not find any item matching the regular expression `{}`",
+                regexps.patterns()[i]
+            )));
+        }
         Ok(matches)
     };
 
     // Collect Id's if arguments were given that should be used as exact names.
+    // Several items can share a name, in which case all of them are used.
     let get_exact_matches = || -> Vec<ir::Id> {
         opts.functions()
             .iter()
-            .filter_map(|s| items.get_item_by_name(s))
+            .flat_map(|s| items.get_items_by_name(s))
             .map(|item| item.id())
             .collect()
     };
@@ -85,21 +126,24 @@ fn get_starting_positions(items: &ir::Items, opts: &opt::Paths) -> anyhow::Resul
     // not these should be treated as regular expressions. Otherwise, collect
     // the starting positions based on the direction we will be traversing.
     let args_given = !opts.functions().is_empty();
-    let using_regexps = opts.using_regexps();
     let descending = opts.descending();
-    let res = match (args_given, using_regexps, descending) {
-        (true, true, _) => get_regexp_matches()?,
-        (true, false, _) => get_exact_matches(),
+    let mut res = match (args_given, regexps, descending) {
+        (true, Some(regexps), _) => get_regexp_matches(regexps)?,
+        (true, None, _) => get_exact_matches(),
+        (false, _, _) if !opts.ids().is_empty() => vec![],
         (false, _, true) => get_functions_default_desc(),
         (false, _, false) => get_functions_default(),
     };
+    super::select_by_id(items, opts.ids(), &mut res)?;
 
     Ok(res)
 }
 
-/// Create a `PathsEntry` object for the given item.
+/// Create a `PathsEntry` object for the given item, which was reached through
+/// an edge of the given kind.
 fn create_entry(
     id: ir::Id,
+    edge_kind: Option<ir::EdgeKind>,
     items: &ir::Items,
     opts: &opt::Paths,
     seen: &mut BTreeSet<ir::Id>,
@@ -108,22 +152,24 @@ fn create_entry(
     let item = &items[id];
     let name = item.name().to_string();
     let size = item.size();
+    let retained_size = items.retained_size(id);
 
     // Collect the `ir::Id` values of this entry's children, depending on
     // whether we are ascending or descending the IR-tree.
-    let children_ids: Vec<ir::Id> = if opts.descending() {
+    let children_ids: Vec<(ir::Id, ir::EdgeKind)> = if opts.descending() {
         items
-            .neighbors(id)
-            .map(|id| id as ir::Id)
-            .filter(|id| !seen.contains(id))
-            .filter(|&id| id != items.meta_root())
+            .edges(id)
+            .filter(|(id, _)| !seen.contains(id))
+            .filter(|&(id, _)| id != items.meta_root())
+            .filter(|&(_, kind)| opts.follows_edge(kind))
             .collect()
     } else {
         items
             .predecessors(id)
-            .map(|id| id as ir::Id)
-            .filter(|id| !seen.contains(id))
-            .filter(|&id| id != items.meta_root())
+            .filter_map(|pred| Some((pred, items.edge_kind(pred, id)?)))
+            .filter(|(id, _)| !seen.contains(id))
+            .filter(|&(id, _)| id != items.meta_root())
+            .filter(|&(_, kind)| opts.follows_edge(kind))
             .collect()
     };
 
@@ -132,13 +178,17 @@ fn create_entry(
     seen.insert(id);
     let children = children_ids
         .into_iter()
-        .map(|id| create_entry(id, items, opts, seen))
+        .map(|(id, kind)| create_entry(id, Some(kind), items, opts, seen))
         .collect();
     seen.remove(&id);
 
     PathsEntry {
+        id,
         name,
         size,
+        retained_size,
+        edge_kind,
         children,
+        patterns: vec![],
     }
 }
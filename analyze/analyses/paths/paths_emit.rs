@@ -2,15 +2,32 @@ use std::io;
 
 use csv;
 
-use crate::analyses::paths::Paths;
+use crate::analyses::paths::PathsResult;
+#[cfg(feature = "emit_text")]
+use crate::formats::dot::Digraph;
 use crate::formats::json;
 use crate::formats::table::{Align, Table};
 use twiggy_ir as ir;
 use twiggy_traits as traits;
 
-impl traits::Emit for Paths {
+impl PathsResult {
+    /// The JSON output, as a value that serde can serialize: each starting
+    /// item, with the paths through it nested in its `callers`.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        use self::emit_json_helpers::process_entry;
+
+        let paths = self.opts.max_paths() as usize;
+        self.entries
+            .iter()
+            .map(|entry| process_entry(entry, 0, paths, items, &self.opts))
+            .collect::<Vec<_>>()
+    }
+}
+
+impl traits::Emit for PathsResult {
     #[cfg(feature = "emit_text")]
-    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         use self::emit_text_helpers::{process_entry, TableRow};
 
         // Flat map each entry and its children into a sequence of table rows.
@@ -55,29 +72,12 @@ impl traits::Emit for Paths {
     }
 
     #[cfg(feature = "emit_json")]
-    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        use self::emit_json_helpers::process_entry;
-
-        // Initialize a JSON array. For each path entry, add a object to the
-        // array, and add that entry's information to the new JSON object.
-        let mut arr = json::array(dest)?;
-        for entry in &self.entries {
-            let mut obj = arr.object()?;
-            process_entry(
-                entry,
-                &mut obj,
-                0,
-                self.opts.max_paths() as usize,
-                items,
-                &self.opts,
-            )?;
-        }
-
-        Ok(())
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
     }
 
     #[cfg(feature = "emit_csv")]
-    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         use self::emit_csv_helpers::process_entry;
 
         // First, initialize a CSV writer. Then, flat map each entry and its
@@ -93,6 +93,25 @@ impl traits::Emit for Paths {
 
         Ok(())
     }
+
+    #[cfg(feature = "emit_text")]
+    fn emit_dot(&self, _items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        use self::emit_dot_helpers::{max_retained_size, process_entry};
+
+        let paths = self.opts.max_paths() as usize;
+        let max = self
+            .entries
+            .iter()
+            .map(|entry| max_retained_size(entry, 0, paths, &self.opts))
+            .max()
+            .unwrap_or(0);
+        let mut graph = Digraph::new(dest, max)?;
+        for entry in &self.entries {
+            process_entry(entry, &mut graph, 0, paths, &self.opts)?;
+        }
+        graph.finish()?;
+        Ok(())
+    }
 }
 
 /// This module contains helper functions and structs used by the `emit_text`
@@ -107,7 +126,7 @@ mod emit_text_helpers {
     /// This structure represents a row in the emitted text table. Size, and size
     /// percentage are only shown for the top-most rows.
     pub(super) struct TableRow {
-        pub size: Option<u32>,
+        pub size: Option<u64>,
         pub size_percent: Option<f64>,
         pub name: String,
     }
@@ -126,7 +145,7 @@ mod emit_text_helpers {
         let (size, size_percent) = if depth == 0 {
             (
                 Some(entry.size),
-                Some(f64::from(entry.size) / f64::from(items.size()) * 100.0),
+                Some(entry.size as f64 / items.size() as f64 * 100.0),
             )
         } else {
             (None, None)
@@ -181,47 +200,115 @@ mod emit_text_helpers {
     }
 }
 
-/// This module contains helper functions and structs used by the `emit_json`
-/// method in Path's implementation of the `traits::Emit` trait.
-#[cfg(feature = "emit_json")]
-mod emit_json_helpers {
+/// This module contains helper functions used by the `emit_dot` method in
+/// Path's implementation of the `traits::Emit` trait.
+#[cfg(feature = "emit_text")]
+mod emit_dot_helpers {
     use crate::analyses::paths::paths_entry::PathsEntry;
-    use crate::formats::json::Object;
+    use crate::formats::dot::Digraph;
     use std::io;
-    use twiggy_ir::Items;
     use twiggy_opt::Paths;
 
-    // Process a paths entry, by adding its name and size to the given JSON object.
+    /// The largest retained size of the entries that will be drawn.
+    pub(super) fn max_retained_size(
+        entry: &PathsEntry,
+        depth: u32,
+        paths: usize,
+        opts: &Paths,
+    ) -> u64 {
+        let children = if depth < opts.max_depth() {
+            &entry.children[..paths.min(entry.children.len())]
+        } else {
+            &[]
+        };
+        children
+            .iter()
+            .map(|child| max_retained_size(child, depth + 1, paths, opts))
+            .fold(entry.retained_size, u64::max)
+    }
+
+    /// Add a node for the given entry, and for each of its children, along
+    /// with an edge from each item to the item it refers to: from a child to
+    /// its parent when ascending, and from a parent to its child when
+    /// descending.
     pub(super) fn process_entry(
         entry: &PathsEntry,
-        obj: &mut Object,
+        graph: &mut Digraph,
         depth: u32,
         paths: usize,
-        items: &Items,
         opts: &Paths,
     ) -> io::Result<()> {
-        let PathsEntry {
-            name,
-            size,
-            children,
-        } = entry;
-        obj.field("name", name.as_str())?;
-        obj.field("shallow_size", *size)?;
-        let size_percent = f64::from(*size) / f64::from(items.size()) * 100.0;
-        obj.field("shallow_size_percent", size_percent)?;
-
-        let mut callers = obj.array("callers")?;
+        let key = entry.id.to_string();
+        graph.node(&key, &entry.name, entry.size, entry.retained_size)?;
         if depth < opts.max_depth() {
-            for child in children.iter().take(paths) {
-                let mut obj = callers.object()?;
-                process_entry(child, &mut obj, depth + 1, paths, items, opts)?;
+            for child in entry.children.iter().take(paths) {
+                process_entry(child, graph, depth + 1, paths, opts)?;
+                let child_key = child.id.to_string();
+                if opts.descending() {
+                    graph.edge(&key, &child_key)?;
+                } else {
+                    graph.edge(&child_key, &key)?;
+                }
             }
         }
-
         Ok(())
     }
 }
 
+/// This module contains helper functions and structs used by the `emit_json`
+/// method in Path's implementation of the `traits::Emit` trait.
+#[cfg(feature = "emit_json")]
+mod emit_json_helpers {
+    use crate::analyses::paths::paths_entry::PathsEntry;
+    use serde_derive::Serialize;
+    use twiggy_ir::Items;
+    use twiggy_opt::Paths;
+
+    /// This structure represents a paths entry in the JSON output.
+    #[derive(Serialize, Debug)]
+    pub(super) struct JsonEntry<'a> {
+        id: String,
+        name: &'a str,
+        shallow_size: u64,
+        shallow_size_percent: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        edge_kind: Option<&'static str>,
+        #[serde(skip_serializing_if = "<[String]>::is_empty")]
+        matched_by: &'a [String],
+        callers: Vec<JsonEntry<'a>>,
+    }
+
+    // Process a paths entry, and the entries along the paths through it.
+    pub(super) fn process_entry<'a>(
+        entry: &'a PathsEntry,
+        depth: u32,
+        paths: usize,
+        items: &Items,
+        opts: &Paths,
+    ) -> JsonEntry<'a> {
+        let callers = if depth < opts.max_depth() {
+            entry
+                .children
+                .iter()
+                .take(paths)
+                .map(|child| process_entry(child, depth + 1, paths, items, opts))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        JsonEntry {
+            id: entry.id.to_string(),
+            name: &entry.name,
+            shallow_size: entry.size,
+            shallow_size_percent: entry.size as f64 / items.size() as f64 * 100.0,
+            edge_kind: entry.edge_kind.map(|edge_kind| edge_kind.as_str()),
+            matched_by: &entry.patterns,
+            callers,
+        }
+    }
+}
+
 /// This module contains helper functions and structs used by the `emit_csv`
 /// method in Path's implementation of the `traits::Emit` trait.
 #[cfg(feature = "emit_csv")]
@@ -237,7 +324,7 @@ mod emit_csv_helpers {
     #[serde(rename_all = "PascalCase")]
     pub(super) struct CsvRecord {
         pub name: String,
-        pub shallow_size: u32,
+        pub shallow_size: u64,
         pub shallow_size_percent: f64,
         pub path: Option<String>,
     }
@@ -252,7 +339,7 @@ mod emit_csv_helpers {
     ) -> Box<dyn Iterator<Item = CsvRecord> + 'a> {
         let name = entry.name.clone();
         let shallow_size = entry.size;
-        let shallow_size_percent = f64::from(entry.size) / f64::from(items.size()) * 100.0;
+        let shallow_size_percent = entry.size as f64 / items.size() as f64 * 100.0;
         let path = get_path(entry);
 
         // Create an iterator containing the current entry's CSV record.
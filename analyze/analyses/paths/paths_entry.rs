@@ -1,14 +1,59 @@
 use std::cmp;
 
+use twiggy_ir as ir;
+
+/// An item along a retaining path.
 #[derive(Debug, PartialEq, Eq)]
-pub(super) struct PathsEntry {
-    pub name: String,
-    pub size: u32,
-    pub children: Vec<PathsEntry>,
+pub struct PathsEntry {
+    pub(super) id: ir::Id,
+    pub(super) name: String,
+    pub(super) size: u64,
+    pub(super) retained_size: u64,
+    pub(super) edge_kind: Option<ir::EdgeKind>,
+    pub(super) children: Vec<PathsEntry>,
+    pub(super) patterns: Vec<String>,
 }
 
 impl PathsEntry {
-    pub fn _count(&self) -> u32 {
+    /// The item's identifier.
+    pub fn id(&self) -> ir::Id {
+        self.id
+    }
+
+    /// The item's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The item's shallow size, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The item's retained size, in bytes.
+    pub fn retained_size(&self) -> u64 {
+        self.retained_size
+    }
+
+    /// The kind of the edge between this item and its parent entry, or `None`
+    /// for the entries the paths start from.
+    pub fn edge_kind(&self) -> Option<ir::EdgeKind> {
+        self.edge_kind
+    }
+
+    /// The next items along the paths through this item.
+    pub fn children(&self) -> &[PathsEntry] {
+        &self.children
+    }
+
+    /// The regular expressions that matched this entry's name, when it is one
+    /// of the entries the paths start from and those were selected with
+    /// `--regex`.
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub(super) fn _count(&self) -> u32 {
         1 + self.children.iter().map(|c| c._count()).sum::<u32>()
     }
 }
@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use twiggy_ir as ir;
+
+/// A set of item ids, which may borrow a set that the `Items` it was computed
+/// from has cached.
+#[derive(Clone, Debug)]
+pub struct ItemIdSet<'a>(Cow<'a, BTreeSet<ir::Id>>);
+
+impl ItemIdSet<'_> {
+    /// Does the set contain the given id?
+    pub fn contains(&self, id: ir::Id) -> bool {
+        self.0.contains(&id)
+    }
+
+    /// The number of ids in the set.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Is the set empty?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the ids in the set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = ir::Id> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Take the ids out of the set, copying them if they were borrowed.
+    pub fn into_set(self) -> BTreeSet<ir::Id> {
+        self.0.into_owned()
+    }
+}
+
+/// The ids of the items reachable from the given roots, including the roots
+/// themselves, following every kind of edge.
+///
+/// When `roots` is `None`, the items are reached from the meta root, which is
+/// included in the set, and the set borrows the reachability that `items`
+/// caches for every analysis. Otherwise it is computed from scratch, which
+/// can be used to simulate e.g. removing some exports. Panics if one of the
+/// roots is not an item.
+pub fn reachable<'a>(items: &'a ir::Items, roots: Option<&[ir::Id]>) -> ItemIdSet<'a> {
+    match roots {
+        None => ItemIdSet(Cow::Borrowed(items.reachable())),
+        Some(roots) => ItemIdSet(Cow::Owned(items.reachable_from(roots))),
+    }
+}
+
+/// The ids of the items that are not reachable from the meta root, largest
+/// first.
+pub fn unreachable_items(items: &ir::Items) -> Vec<ir::Id> {
+    unreachable_items_without(items, &[])
+}
+
+/// The ids of the items that are not reachable from the meta root without
+/// following edges of the excluded kinds, largest first. The meta root's own
+/// edges are always followed.
+pub(crate) fn unreachable_items_without(
+    items: &ir::Items,
+    excluded_edges: &[ir::EdgeKind],
+) -> Vec<ir::Id> {
+    let reachable = items.reachable_without(excluded_edges);
+    items
+        .by_size()
+        .iter()
+        .copied()
+        .filter(|id| !reachable.contains(id))
+        .collect()
+}
@@ -0,0 +1,37 @@
+use regex;
+
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// Treat every item whose name matches one of the given patterns as a root,
+/// before running an analysis that depends on which items are reachable.
+///
+/// A pattern matches an item when it is the item's exact name, or when it is a
+/// regular expression that matches the name. Returns the patterns that did not
+/// match any item, so that callers can warn about them.
+pub fn add_extra_roots(items: &mut ir::Items, patterns: &[String]) -> traits::Result<Vec<String>> {
+    let meta_root = items.meta_root();
+    let mut unmatched = vec![];
+    for pattern in patterns {
+        // Demangled names are full of characters that mean something in a
+        // regular expression, so a pattern that does not even parse as one is
+        // only compared literally.
+        let regex = regex::Regex::new(pattern).ok();
+        let ids: Vec<ir::Id> = items
+            .iter()
+            .filter(|item| item.id() != meta_root)
+            .filter(|item| {
+                item.name() == pattern || regex.as_ref().is_some_and(|r| r.is_match(item.name()))
+            })
+            .map(|item| item.id())
+            .collect();
+
+        if ids.is_empty() {
+            unmatched.push(pattern.clone());
+        }
+        for id in ids {
+            items.promote_to_root(id);
+        }
+    }
+    Ok(unmatched)
+}
@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use crate::formats::json;
+use crate::formats::table::{Align, Table};
+use csv;
+use serde_derive::Serialize;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits as traits;
+
+/// The result of the `sections` analysis.
+#[derive(Debug)]
+pub struct SectionsResult {
+    sections: Vec<SectionEntry>,
+}
+
+impl SectionsResult {
+    /// Every section, in the requested order.
+    pub fn sections(&self) -> &[SectionEntry] {
+        &self.sections
+    }
+
+    /// The total size of the sections that roll up into the given group.
+    pub fn rollup_size(&self, rollup: Rollup) -> u64 {
+        self.sections
+            .iter()
+            .filter(|section| section.rollup == rollup)
+            .map(|section| section.size)
+            .sum()
+    }
+
+    /// The total size of every section.
+    pub fn total_size(&self) -> u64 {
+        self.sections.iter().map(|section| section.size).sum()
+    }
+}
+
+/// The size of a single section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionEntry {
+    id: ir::Id,
+    name: String,
+    size: u64,
+    rollup: Rollup,
+}
+
+impl SectionEntry {
+    /// The section's id.
+    pub fn id(&self) -> ir::Id {
+        self.id
+    }
+
+    /// The section's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The size of the whole section: its headers, and all of its entries.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The group of sections this section rolls up into.
+    pub fn rollup(&self) -> Rollup {
+        self.rollup
+    }
+}
+
+/// A group of sections that hold the same kind of thing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rollup {
+    /// Sections that are mostly functions.
+    Code,
+
+    /// Sections that are mostly data segments.
+    Data,
+
+    /// Sections that are mostly debugging information and names.
+    DebugAndNames,
+
+    /// Every other section.
+    Other,
+}
+
+impl Rollup {
+    const ALL: [Rollup; 4] = [
+        Rollup::Code,
+        Rollup::Data,
+        Rollup::DebugAndNames,
+        Rollup::Other,
+    ];
+
+    /// The name of the group.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rollup::Code => "code",
+            Rollup::Data => "data",
+            Rollup::DebugAndNames => "debug/names",
+            Rollup::Other => "other",
+        }
+    }
+
+    /// The group of a section with items of the given categories, of the
+    /// given total sizes: the group of the category that takes up most of the
+    /// section, not counting its headers.
+    fn of(categories: &BTreeMap<ir::ItemCategory, u64>) -> Rollup {
+        let largest = categories
+            .iter()
+            .filter(|&(&category, _)| category != ir::ItemCategory::Section)
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)));
+        match largest.map(|(&category, _)| category) {
+            Some(ir::ItemCategory::Function) => Rollup::Code,
+            Some(ir::ItemCategory::DataSegment) => Rollup::Data,
+            Some(ir::ItemCategory::DebugInfo) => Rollup::DebugAndNames,
+            _ => Rollup::Other,
+        }
+    }
+}
+
+/// The number of characters in the bar of something as large as the whole
+/// binary.
+const BAR_WIDTH: u64 = 20;
+
+/// A bar as long as `size`'s share of `total`, to the nearest eighth of a
+/// character.
+fn bar(size: u64, total: u64) -> String {
+    const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    if total == 0 {
+        return String::new();
+    }
+    let eighths = (size * BAR_WIDTH * 8 + total / 2) / total;
+    let mut bar = "█".repeat((eighths / 8) as usize);
+    if eighths % 8 != 0 {
+        bar.push(EIGHTHS[(eighths % 8) as usize]);
+    }
+    bar
+}
+
+impl traits::Emit for SectionsResult {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut table = Table::with_header(vec![
+            (Align::Right, "Bytes".to_string()),
+            (Align::Right, "Size %".to_string()),
+            (Align::Left, "Section".to_string()),
+            (Align::Left, "Share".to_string()),
+        ]);
+        table.measure_chars();
+
+        let mut add_row = |size: u64, name: String| {
+            let size_percent = size as f64 / items.size() as f64 * 100.0;
+            table.add_row(vec![
+                size.to_string(),
+                format!("{:.2}%", size_percent),
+                name,
+                bar(size, items.size()),
+            ]);
+        };
+
+        for section in &self.sections {
+            add_row(section.size, section.name.clone());
+        }
+        for rollup in Rollup::ALL {
+            add_row(self.rollup_size(rollup), format!("Σ {}", rollup.name()));
+        }
+        add_row(
+            self.total_size(),
+            format!("Σ [{} Total Rows]", self.sections.len()),
+        );
+
+        write!(dest, "{}", &table)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let size_percent = |size: u64| size as f64 / items.size() as f64 * 100.0;
+
+        let mut obj = json::object(dest)?;
+        {
+            let mut arr = obj.array("sections")?;
+            for section in &self.sections {
+                let mut obj = arr.object()?;
+                obj.field("id", section.id.to_string().as_str())?;
+                obj.field("name", section.name.as_str())?;
+                obj.field("size", section.size)?;
+                obj.field("size_percent", size_percent(section.size))?;
+                obj.field("rollup", section.rollup.name())?;
+            }
+        }
+        {
+            let mut arr = obj.array("rollups")?;
+            for rollup in Rollup::ALL {
+                let size = self.rollup_size(rollup);
+                let mut obj = arr.object()?;
+                obj.field("name", rollup.name())?;
+                obj.field("size", size)?;
+                obj.field("size_percent", size_percent(size))?;
+            }
+        }
+        obj.field("total_size", self.total_size())?;
+        obj.field("total_size_percent", size_percent(self.total_size()))?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let mut wtr = csv::Writer::from_writer(dest);
+
+        #[derive(Serialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct CsvRecord<'a> {
+            id: String,
+            name: &'a str,
+            size: u64,
+            size_percent: f64,
+            rollup: &'static str,
+        }
+
+        for section in &self.sections {
+            wtr.serialize(CsvRecord {
+                id: section.id.to_string(),
+                name: &section.name,
+                size: section.size,
+                size_percent: section.size as f64 / items.size() as f64 * 100.0,
+                rollup: section.rollup.name(),
+            })?;
+            wtr.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// The name of a section, from the name of the item for its headers. Sections
+/// without such an item are named after their id.
+pub(super) fn section_name(items: &ir::Items, id: ir::Id) -> String {
+    match items.get(id) {
+        Some(item) => {
+            let name = item.name();
+            name.strip_suffix(" headers").unwrap_or(name).to_string()
+        }
+        None => format!("section {}", id),
+    }
+}
+
+/// Summarize the size of each section: its headers, and the sum of the sizes
+/// of its entries. Each section also rolls up into code, data, debugging
+/// information and names, or anything else, by what most of its entries are.
+pub fn sections(items: &mut ir::Items, opts: &opt::Sections) -> traits::Result<SectionsResult> {
+    let items = &*items;
+
+    let mut sizes: BTreeMap<ir::Id, BTreeMap<ir::ItemCategory, u64>> = BTreeMap::new();
+    for item in items.iter() {
+        if let Some(section) = item.id().containing_section() {
+            *sizes
+                .entry(section)
+                .or_default()
+                .entry(item.category())
+                .or_insert(0) += item.size();
+        }
+    }
+
+    // Sections are in `Id` order, which is the order they appear in the binary.
+    let mut sections: Vec<SectionEntry> = sizes
+        .into_iter()
+        .map(|(id, categories)| SectionEntry {
+            id,
+            name: section_name(items, id),
+            size: categories.values().sum(),
+            rollup: Rollup::of(&categories),
+        })
+        .collect();
+    if opts.order() == opt::SectionOrder::Size {
+        sections.sort_by(|a, b| b.size.cmp(&a.size));
+    }
+
+    Ok(SectionsResult { sections })
+}
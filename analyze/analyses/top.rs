@@ -1,40 +1,383 @@
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 
+use super::name_filter::NameFilter;
+use super::wire::CompressedSizes;
 use crate::formats::json;
 use crate::formats::table::{Align, Table};
-use anyhow::anyhow;
+use crate::formats::truncated::Truncated;
 use csv;
 use serde_derive::Serialize;
 use twiggy_ir as ir;
 use twiggy_opt as opt;
 use twiggy_traits as traits;
 
-struct Top {
-    items: Vec<ir::Id>,
+/// The result of the `top` analysis.
+#[derive(Debug)]
+pub struct TopResult {
+    rows: Vec<TopRow>,
+    truncated: Truncated,
+    truncated_ids: Vec<ir::Id>,
+    compressed: Option<CompressedSizes>,
+    excluded: Option<Truncated>,
+    filtered: Option<Truncated>,
+    names: Vec<NameBytes>,
+    data_headers: Option<DataHeaders>,
     opts: opt::Top,
 }
 
-impl traits::Emit for Top {
+impl TopResult {
+    /// The displayed rows, largest first. At most `max_items` rows are kept;
+    /// the rest are only counted and summarized.
+    pub fn rows(&self) -> &[TopRow] {
+        &self.rows
+    }
+
+    /// The rows beyond `max_items`. Their bytes are their shallow sizes, even
+    /// when retained sizes were requested, since those overlap.
+    pub fn truncated(&self) -> Truncated {
+        self.truncated
+    }
+
+    /// The items left out by `--kind` and `--exclude-kind`, if any kinds were
+    /// given. Like the truncated rows, their bytes are shallow sizes.
+    pub fn excluded(&self) -> Option<Truncated> {
+        self.excluded
+    }
+
+    /// The items of the listed kinds left out by `--only` and `--exclude`, if
+    /// any patterns were given. Their bytes are shallow sizes too.
+    pub fn filtered(&self) -> Option<Truncated> {
+        self.filtered
+    }
+
+    /// How many bytes the names of all imports and of all exports take up,
+    /// if requested with `name_bytes`. Kinds without any items are left out.
+    pub fn names(&self) -> &[NameBytes] {
+        &self.names
+    }
+
+    /// How many bytes the headers of all data segments take up, if requested
+    /// with `data_headers` and there are any data segments.
+    pub fn data_headers(&self) -> Option<DataHeaders> {
+        self.data_headers
+    }
+
+    /// The estimated gzipped size of each item, if it was estimated with
+    /// `compressed_sizes`.
+    pub fn compressed(&self) -> Option<&CompressedSizes> {
+        self.compressed.as_ref()
+    }
+
+    /// Report the estimated gzipped size of each item along with its size.
+    pub fn set_compressed(&mut self, compressed: CompressedSizes) {
+        self.compressed = Some(compressed);
+    }
+
+    /// The estimated gzipped size of the given row, if it is an item's and
+    /// the sizes were estimated.
+    fn compressed_size(&self, row: &TopRow) -> Option<u64> {
+        self.compressed.as_ref()?.size(row.id?)
+    }
+
+    /// The total estimated gzipped size of the truncated rows' items, if any
+    /// of them has one.
+    fn truncated_compressed_size(&self) -> Option<u64> {
+        let compressed = self.compressed.as_ref()?;
+        self.truncated_ids
+            .iter()
+            .filter_map(|&id| compressed.size(id))
+            .reduce(|a, b| a + b)
+    }
+}
+
+/// How many bytes the names of every item of a kind take up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NameBytes {
+    kind: ir::ItemCategory,
+    count: usize,
+    bytes: u64,
+}
+
+impl NameBytes {
+    /// The kind of the items, either imports or exports.
+    pub fn kind(&self) -> ir::ItemCategory {
+        self.kind
+    }
+
+    /// How many items of the kind there are.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The total size of their names, in bytes.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// How many bytes of every data segment are headers rather than payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataHeaders {
+    count: usize,
+    header_bytes: u64,
+    payload_bytes: u64,
+}
+
+impl DataHeaders {
+    /// How many data segment items there are.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The total size of their headers, in bytes.
+    pub fn header_bytes(&self) -> u64 {
+        self.header_bytes
+    }
+
+    /// The total size of their payloads, in bytes.
+    pub fn payload_bytes(&self) -> u64 {
+        self.payload_bytes
+    }
+}
+
+/// The share of all data segment bytes, as a percentage, that their headers
+/// must at least take up for `top` to suggest merging segments.
+const DATA_HEADERS_HINT_PERCENT: f64 = 10.0;
+
+/// A single row of `top`'s output: either an individual IR item, or the items
+/// defined in a single source file when grouping by file.
+#[derive(Clone, Debug)]
+pub struct TopRow {
+    id: Option<ir::Id>,
+    name: String,
+    kind: Option<ir::ItemCategory>,
+    shallow_size: u64,
+    retained_size: Option<u64>,
+    source_location: Option<ir::SourceLocation>,
+    control_flow: Option<ir::ControlFlow>,
+    name_bytes: Option<u64>,
+    payload_bytes: Option<u64>,
+}
+
+impl TopRow {
+    fn item(items: &ir::Items, item: &ir::Item, retained: bool) -> TopRow {
+        TopRow {
+            id: Some(item.id()),
+            name: item.name().to_string(),
+            kind: Some(item.category()),
+            shallow_size: item.size(),
+            retained_size: if retained {
+                Some(items.retained_size(item.id()))
+            } else {
+                None
+            },
+            source_location: item.source_location().cloned(),
+            control_flow: items.control_flow(item.id()),
+            name_bytes: items.name_bytes(item.id()),
+            payload_bytes: items.payload_bytes(item.id()),
+        }
+    }
+
+    /// The item's id, or `None` if this row aggregates several items.
+    pub fn id(&self) -> Option<ir::Id> {
+        self.id
+    }
+
+    /// The item's name, or the source file's name when grouping by file.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The item's kind, or `None` if this row aggregates several items.
+    pub fn kind(&self) -> Option<ir::ItemCategory> {
+        self.kind
+    }
+
+    /// The shallow size of this row, in bytes.
+    pub fn shallow_size(&self) -> u64 {
+        self.shallow_size
+    }
+
+    /// The retained size of this row, in bytes, if retained sizes were
+    /// requested.
+    pub fn retained_size(&self) -> Option<u64> {
+        self.retained_size
+    }
+
+    /// Where the item was defined, if known.
+    pub fn source_location(&self) -> Option<&ir::SourceLocation> {
+        self.source_location.as_ref()
+    }
+
+    /// The function's control structures, if they were recorded when parsing.
+    pub fn control_flow(&self) -> Option<ir::ControlFlow> {
+        self.control_flow
+    }
+
+    /// How many of the import's or export's bytes encode its name, if known.
+    pub fn name_bytes(&self) -> Option<u64> {
+        self.name_bytes
+    }
+
+    /// How many of the data segment's bytes are its payload, if known.
+    pub fn payload_bytes(&self) -> Option<u64> {
+        self.payload_bytes
+    }
+}
+
+impl TopResult {
+    /// The JSON output, as a value that serde can serialize: the rows, or the
+    /// rows and the edges between them if `edges` was requested.
+    #[cfg(feature = "emit_json")]
+    pub fn json_value<'a>(&'a self, items: &'a ir::Items) -> impl serde::Serialize + 'a {
+        let rows = self.json_rows(items);
+        if !self.opts.edges() {
+            return JsonOutput::Rows(rows);
+        }
+
+        // Only the edges between listed items, so that every id in `edges`
+        // can be looked up in `items`.
+        let listed: BTreeSet<ir::Id> = self.rows.iter().filter_map(|row| row.id).collect();
+        let mut edges = vec![];
+        for &from in &listed {
+            for (to, kind) in items.edges(from) {
+                if listed.contains(&to) {
+                    edges.push(JsonEdge {
+                        from_id: from.to_string(),
+                        to_id: to.to_string(),
+                        kind: kind.as_str(),
+                    });
+                }
+            }
+        }
+        JsonOutput::WithEdges { items: rows, edges }
+    }
+
+    /// The JSON object of each listed row, and of the truncated rows.
+    #[cfg(feature = "emit_json")]
+    fn json_rows<'a>(&'a self, items: &ir::Items) -> Vec<JsonRow<'a>> {
+        let percent = |size: u64| size as f64 / items.size() as f64 * 100.0;
+        let mut rows: Vec<_> = self
+            .rows
+            .iter()
+            .map(|row| JsonRow {
+                id: row.id.map(|id| id.to_string()),
+                name: row.name.clone(),
+                kind: row.kind.map(ir::ItemCategory::as_str),
+                shallow_size: row.shallow_size,
+                shallow_size_percent: percent(row.shallow_size),
+                compressed_size: self.compressed_size(row),
+                retained_size: row.retained_size,
+                retained_size_percent: row.retained_size.map(percent),
+                source_location: row.source_location.as_ref().map(json::SourceLocation::new),
+                control_structures: row.control_flow.map(|c| c.structures()),
+                max_nesting_depth: row.control_flow.map(|c| c.max_depth()),
+                largest_br_table_bytes: row.control_flow.map(|c| c.largest_br_table()),
+                name_bytes: row.name_bytes,
+                descriptor_bytes: row.name_bytes.map(|name| row.shallow_size - name),
+                payload_bytes: row.payload_bytes,
+                header_bytes: row.payload_bytes.map(|payload| row.shallow_size - payload),
+                ..JsonRow::default()
+            })
+            .collect();
+
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            rows.push(JsonRow {
+                name: self.truncated.label(items.size()),
+                shallow_size: size,
+                shallow_size_percent: percent(size),
+                compressed_size: self.truncated_compressed_size(),
+                truncated_count: Some(self.truncated.count()),
+                truncated_bytes: Some(self.truncated.bytes()),
+                ..JsonRow::default()
+            });
+        }
+
+        rows
+    }
+}
+
+/// The JSON output of `top`, which is an object only when edges are listed.
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum JsonOutput<'a> {
+    Rows(Vec<JsonRow<'a>>),
+    WithEdges {
+        items: Vec<JsonRow<'a>>,
+        edges: Vec<JsonEdge>,
+    },
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Default, Serialize)]
+struct JsonRow<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<&'static str>,
+    shallow_size: u64,
+    shallow_size_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compressed_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retained_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retained_size_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_location: Option<json::SourceLocation<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    control_structures: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_nesting_depth: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    largest_br_table_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    descriptor_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_bytes: Option<i64>,
+}
+
+#[cfg(feature = "emit_json")]
+#[derive(Debug, Serialize)]
+struct JsonEdge {
+    from_id: String,
+    to_id: String,
+    kind: &'static str,
+}
+
+impl traits::Emit for TopResult {
     #[cfg(feature = "emit_text")]
-    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         // A struct used to represent a row in the table that will be emitted.
         struct TableRow {
-            size: u32,
+            size: u64,
             size_percent: f64,
             name: String,
         }
 
         // Helper function used to process an item, and return a struct
         // representing a row containing its size and name.
-        fn process_item(id: ir::Id, items: &ir::Items, retained: bool) -> TableRow {
-            let item = &items[id];
+        fn process_item(row: &TopRow, items: &ir::Items, retained: bool) -> TableRow {
             let size = if retained {
-                items.retained_size(id)
+                row.retained_size.unwrap()
             } else {
-                item.size()
+                row.shallow_size
             };
-            let size_percent = (f64::from(size)) / (f64::from(items.size())) * 100.0;
-            let name = item.name().to_string();
+            let size_percent = (size as f64) / (items.size() as f64) * 100.0;
+            let name = row.name.clone();
             TableRow {
                 size,
                 size_percent,
@@ -42,165 +385,247 @@ impl traits::Emit for Top {
             }
         }
 
-        // Helper function used to summnarize a sequence of table rows. This is
-        // used to generate the remaining summary and total rows. Returns a tuple
-        // containing the total size, total size percentage, and number of items.
-        fn summarize_rows(rows: impl Iterator<Item = TableRow>) -> (u32, f64, u32) {
-            rows.fold(
-                (0, 0.0, 0),
-                |(total_size, total_percent, remaining_count),
-                 TableRow {
-                     size, size_percent, ..
-                 }| {
-                    (
-                        total_size + size,
-                        total_percent + size_percent,
-                        remaining_count + 1,
-                    )
-                },
-            )
-        }
-
         // Access the options that are relevant to emitting the correct output.
-        let max_items = self.opts.max_items() as usize;
         let retained = self.opts.retained();
         let sort_label = if retained { "Retained" } else { "Shallow" };
 
-        // Initialize a new table.
-        let mut table = Table::with_header(vec![
+        // Initialize a new table, with a column for the estimated gzipped
+        // sizes if there are any, which are explained above it.
+        let compressed = self.compressed.as_ref();
+        let mut header = vec![
             (Align::Right, format!("{} Bytes", sort_label)),
             (Align::Right, format!("{} %", sort_label)),
-            (Align::Left, "Item".to_string()),
-        ]);
+        ];
+        if compressed.is_some() {
+            header.push((Align::Right, "Compressed Bytes".to_string()));
+            writeln!(
+                dest,
+                "Compressed Bytes are estimates: the binary is deflated as one gzip stream, in \
+                 file order, flushed after each item, and each item gets the bytes written \
+                 since the previous flush, less the flush marker.\n"
+            )?;
+        }
+        header.push((Align::Left, "Item".to_string()));
+        let mut table = Table::with_header(header);
+        let compressed_col =
+            |size: Option<u64>| size.map_or_else(|| "?".to_string(), |size| size.to_string());
 
         // Process the number of items specified, and add them to the table.
-        self.items
-            .iter()
-            .take(max_items)
-            .map(|&id| process_item(id, items, retained))
-            .for_each(
-                |TableRow {
-                     size,
-                     size_percent,
-                     name,
-                 }| {
-                    table.add_row(vec![
-                        size.to_string(),
-                        format!("{:.2}%", size_percent),
-                        name,
-                    ])
-                },
-            );
-
-        // Find the summary statistics by processing the remaining items.
-        let remaining_rows = self
-            .items
-            .iter()
-            .skip(max_items)
-            .map(|&id| process_item(id, items, retained));
-        let (rem_size, rem_size_percent, rem_count) = summarize_rows(remaining_rows);
+        for row in &self.rows {
+            let TableRow {
+                size,
+                size_percent,
+                name,
+            } = process_item(row, items, retained);
+            let mut cols = vec![size.to_string(), format!("{:.2}%", size_percent)];
+            if compressed.is_some() {
+                cols.push(compressed_col(self.compressed_size(row)));
+            }
+            cols.push(name);
+            table.add_row(cols);
+        }
 
-        // If there were items remaining, add a summary row to the table.
-        if rem_count > 0 {
-            let rem_name_col = format!("... and {} more.", rem_count);
+        // If there were rows truncated, add a summary row to the table.
+        let truncated_size = self.truncated.bytes() as u64;
+        let truncated_percent = (truncated_size as f64) / (items.size() as f64) * 100.0;
+        if !self.truncated.is_empty() {
             let (rem_size_col, rem_size_percent_col) = if retained {
                 ("...".to_string(), "...".to_string())
             } else {
-                (rem_size.to_string(), format!("{:.2}%", rem_size_percent))
+                (
+                    truncated_size.to_string(),
+                    format!("{:.2}%", truncated_percent),
+                )
             };
-            table.add_row(vec![rem_size_col, rem_size_percent_col, rem_name_col]);
+            let mut cols = vec![rem_size_col, rem_size_percent_col];
+            if compressed.is_some() {
+                cols.push(compressed_col(self.truncated_compressed_size()));
+            }
+            cols.push(self.truncated.label(items.size()));
+            table.add_row(cols);
         }
 
         // Add a row containing the totals to the table.
-        let all_rows = self
-            .items
+        let (shown_size, shown_size_percent) = self
+            .rows
             .iter()
-            .map(|&id| process_item(id, items, retained));
-        let (total_size, total_size_percent, total_count) = summarize_rows(all_rows);
+            .map(|row| process_item(row, items, retained))
+            .fold((0, 0.0), |(size, percent), row| {
+                (size + row.size, percent + row.size_percent)
+            });
+        let total_count = self.rows.len() + self.truncated.count();
         let total_name_col = format!("Σ [{} Total Rows]", total_count);
+        // Retained sizes overlap, so rather than their sum show that of the
+        // items that retain everything else, which is the whole binary.
         let (total_size_col, total_size_percent_col) = if retained {
-            ("...".to_string(), "...".to_string())
+            let total: u64 = items
+                .retained_roots()
+                .map(|id| items.retained_size(id))
+                .sum();
+            let total_percent = (total as f64) / (items.size() as f64) * 100.0;
+            (total.to_string(), format!("{:.2}%", total_percent))
         } else {
             (
-                total_size.to_string(),
-                format!("{:.2}%", total_size_percent),
+                (shown_size + truncated_size).to_string(),
+                format!("{:.2}%", shown_size_percent + truncated_percent),
             )
         };
-        table.add_row(vec![total_size_col, total_size_percent_col, total_name_col]);
+        let mut cols = vec![total_size_col, total_size_percent_col];
+        if compressed.is_some() {
+            let total = self
+                .rows
+                .iter()
+                .filter_map(|row| self.compressed_size(row))
+                .chain(self.truncated_compressed_size())
+                .reduce(|a, b| a + b);
+            cols.push(total.map_or_else(|| "?".to_string(), |size| size.to_string()));
+        }
+        cols.push(total_name_col);
+        table.add_row(cols);
 
         // Write the generated table out to the destination and return.
         write!(dest, "{}", &table)?;
+        if let Some(compressed) = compressed {
+            let whole_file = compressed.whole_file_size();
+            let off = compressed.stream_size() as i64 - whole_file as i64;
+            writeln!(
+                dest,
+                "compressed: {} bytes across all items, {} bytes for the whole stream",
+                compressed.items_total(),
+                compressed.stream_size(),
+            )?;
+            writeln!(
+                dest,
+                "gzipped in one go: {} bytes, which the stream is {:+} bytes ({:+.2}%) off from",
+                whole_file,
+                off,
+                off as f64 / whole_file as f64 * 100.0,
+            )?;
+        }
+        if let Some(excluded) = self.excluded {
+            writeln!(
+                dest,
+                "excluded by kind: {} bytes ({:.2}%) across {} items",
+                excluded.bytes(),
+                excluded.bytes() as f64 / items.size() as f64 * 100.0,
+                excluded.count(),
+            )?;
+        }
+        if let Some(filtered) = self.filtered {
+            writeln!(
+                dest,
+                "filtered out by name: {} bytes ({:.2}%) across {} items",
+                filtered.bytes(),
+                filtered.bytes() as f64 / items.size() as f64 * 100.0,
+                filtered.count(),
+            )?;
+        }
+        if !self.names.is_empty() {
+            writeln!(dest)?;
+        }
+        for names in &self.names {
+            let noun = names.kind.as_str();
+            writeln!(
+                dest,
+                "{} names: {} bytes ({:.2}%) across {} {}s",
+                noun,
+                names.bytes,
+                names.bytes as f64 / items.size() as f64 * 100.0,
+                names.count,
+                noun,
+            )?;
+        }
+        if let Some(data) = self.data_headers {
+            let total = data.header_bytes + data.payload_bytes;
+            let header_percent = data.header_bytes as f64 / total as f64 * 100.0;
+            writeln!(dest)?;
+            writeln!(
+                dest,
+                "data segment headers: {} bytes ({:.2}%) across {} data segments",
+                data.header_bytes,
+                data.header_bytes as f64 / items.size() as f64 * 100.0,
+                data.count,
+            )?;
+            if header_percent >= DATA_HEADERS_HINT_PERCENT {
+                writeln!(
+                    dest,
+                    "hint: headers are {:.2}% of all data segment bytes; merging adjacent \
+                     segments would save most of them",
+                    header_percent,
+                )?;
+            }
+        }
         Ok(())
     }
 
     #[cfg(feature = "emit_json")]
-    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
-        let mut arr = json::array(dest)?;
-
-        let max_items = self.opts.max_items() as usize;
-        let items_iter = self.items.iter();
-
-        for &id in items_iter.take(max_items) {
-            let item = &items[id];
-
-            let mut obj = arr.object()?;
-            obj.field("name", item.name())?;
-
-            let size = item.size();
-            let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
-            obj.field("shallow_size", size)?;
-            obj.field("shallow_size_percent", size_percent)?;
-
-            if self.opts.retained() {
-                let size = items.retained_size(id);
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
-                obj.field("retained_size", size)?;
-                obj.field("retained_size_percent", size_percent)?;
-            }
-        }
-
-        Ok(())
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        Ok(json::write(dest, &self.json_value(items))?)
     }
 
     #[cfg(feature = "emit_csv")]
-    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> anyhow::Result<()> {
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
         let mut wtr = csv::Writer::from_writer(dest);
 
         #[derive(Serialize, Debug)]
         #[serde(rename_all = "PascalCase")]
         struct CsvRecord {
             name: String,
-            shallow_size: u32,
+            kind: Option<&'static str>,
+            shallow_size: u64,
             shallow_size_percent: f64,
-            retained_size: Option<u32>,
+            retained_size: Option<u64>,
             retained_size_percent: Option<f64>,
+            truncated_count: Option<usize>,
+            truncated_bytes: Option<i64>,
+            // Only with compressed sizes, when a row without one is empty.
+            #[serde(skip_serializing_if = "Option::is_none")]
+            compressed_size: Option<Option<u64>>,
         }
 
-        let max_items = self.opts.max_items() as usize;
-        let items_iter = self.items.iter();
-
-        for &id in items_iter.take(max_items) {
-            let item = &items[id];
-
+        for row in &self.rows {
             let (shallow_size, shallow_size_percent) = {
-                let size = item.size();
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
+                let size = row.shallow_size;
+                let size_percent = size as f64 / items.size() as f64 * 100.0;
                 (size, size_percent)
             };
-            let (retained_size, retained_size_percent) = if self.opts.retained() {
-                let size = items.retained_size(id);
-                let size_percent = f64::from(size) / f64::from(items.size()) * 100.0;
-                (Some(size), Some(size_percent))
-            } else {
-                (None, None)
+            let (retained_size, retained_size_percent) = match row.retained_size {
+                Some(size) => {
+                    let size_percent = size as f64 / items.size() as f64 * 100.0;
+                    (Some(size), Some(size_percent))
+                }
+                None => (None, None),
             };
 
             wtr.serialize(CsvRecord {
-                name: item.name().to_string(),
+                name: row.name.clone(),
+                kind: row.kind.map(ir::ItemCategory::as_str),
                 shallow_size,
                 shallow_size_percent,
                 retained_size,
                 retained_size_percent,
+                truncated_count: None,
+                truncated_bytes: None,
+                compressed_size: self.compressed.as_ref().map(|_| self.compressed_size(row)),
+            })?;
+            wtr.flush()?;
+        }
+
+        if !self.truncated.is_empty() {
+            let size = self.truncated.bytes() as u64;
+            wtr.serialize(CsvRecord {
+                name: self.truncated.label(items.size()),
+                kind: None,
+                shallow_size: size,
+                shallow_size_percent: size as f64 / items.size() as f64 * 100.0,
+                retained_size: None,
+                retained_size_percent: None,
+                truncated_count: Some(self.truncated.count()),
+                truncated_bytes: Some(self.truncated.bytes()),
+                compressed_size: self
+                    .compressed
+                    .as_ref()
+                    .map(|_| self.truncated_compressed_size()),
             })?;
             wtr.flush()?;
         }
@@ -209,36 +634,225 @@ impl traits::Emit for Top {
 }
 
 /// Run the `top` analysis on the given IR items.
-pub fn top(items: &mut ir::Items, opts: &opt::Top) -> anyhow::Result<Box<dyn traits::Emit>> {
+pub fn top(items: &mut ir::Items, opts: &opt::Top) -> traits::Result<TopResult> {
     if opts.retaining_paths() {
-        return Err(anyhow!("retaining paths are not yet implemented",));
+        return Err(traits::Error::Unsupported {
+            feature: "retaining paths".to_string(),
+        });
+    }
+
+    if opts.group_by() == Some(opt::GroupBy::Crate) {
+        return Err(traits::Error::Analysis(
+            "`top` can only group items by `file`; `twiggy crates` sums up the items of each crate"
+                .to_string(),
+        ));
     }
 
     if opts.retained() {
+        if opts.group_by().is_some() {
+            return Err(traits::Error::Analysis(
+                "grouped items do not have a retained size, so `--group-by` cannot be combined with `--retained`"
+                    .to_string(),
+            ));
+        }
+        items.set_threads(opts.threads());
         items.compute_retained_sizes();
     }
 
-    let mut top_items: Vec<_> = items
+    let names = NameFilter::new(opts.only(), opts.excluded_names())?;
+
+    let items = &*items;
+    let max_items = opts.max_items() as usize;
+    let listed_items = items
         .iter()
         .filter(|item| item.id() != items.meta_root())
-        .collect();
+        .filter(|item| opts.includes_kind(item))
+        .filter(|item| names.includes(item))
+        .filter(|item| match opts.min_brtable_bytes() {
+            Some(min) => items
+                .control_flow(item.id())
+                .is_some_and(|control_flow| control_flow.largest_br_table() >= min),
+            None => true,
+        });
+
+    let mut truncated_ids = vec![];
+    let (rows, truncated) = match opts.group_by() {
+        None => {
+            let size = |item: &ir::Item| {
+                if opts.retained() {
+                    items.retained_size(item.id())
+                } else {
+                    item.size()
+                }
+            };
+            let mut listed: Vec<(u64, ir::Id)> =
+                listed_items.map(|item| (size(item), item.id())).collect();
+
+            // Largest first, and items of the same size in `Id` order. Items
+            // of the same retained size are first ordered by shallow size,
+            // largest first, and then by name. Only the first `max_items` are
+            // displayed, so when there are more than that, select them rather
+            // than sorting every item.
+            let order = |a: &(u64, ir::Id), b: &(u64, ir::Id)| {
+                let (x, y) = (&items[a.1], &items[b.1]);
+                b.0.cmp(&a.0)
+                    .then_with(|| {
+                        if opts.retained() {
+                            y.size().cmp(&x.size()).then_with(|| x.name().cmp(y.name()))
+                        } else {
+                            cmp::Ordering::Equal
+                        }
+                    })
+                    .then(a.1.cmp(&b.1))
+            };
+            let mut truncated = Truncated::default();
+            if max_items < listed.len() {
+                listed.select_nth_unstable_by(max_items, order);
+                truncated_ids = listed.drain(max_items..).map(|(_, id)| id).collect();
+                truncated = Truncated::of(truncated_ids.iter().map(|&id| items[id].size()));
+            }
+            listed.sort_unstable_by(order);
 
-    top_items.sort_by(|a, b| {
-        if opts.retained() {
-            items
-                .retained_size(b.id())
-                .cmp(&items.retained_size(a.id()))
-        } else {
-            b.size().cmp(&a.size())
+            let rows = listed
+                .into_iter()
+                .map(|(_, id)| TopRow::item(items, &items[id], opts.retained()))
+                .collect();
+            (rows, truncated)
         }
-    });
+        // Only by `file`, since `crate` was rejected above.
+        Some(_) => {
+            let mut rows = group_by_file(items, listed_items);
+            rows.sort_by(|a, b| {
+                b.shallow_size
+                    .cmp(&a.shallow_size)
+                    .then_with(|| a.id.is_none().cmp(&b.id.is_none()))
+                    .then_with(|| a.id.cmp(&b.id))
+            });
+            let truncated = if max_items < rows.len() {
+                Truncated::of(rows.drain(max_items..).map(|row| row.shallow_size))
+            } else {
+                Truncated::default()
+            };
+            (rows, truncated)
+        }
+    };
+
+    let excluded = if opts.filters_kinds() {
+        Some(Truncated::of(
+            items
+                .iter()
+                .filter(|item| item.id() != items.meta_root() && !opts.includes_kind(item))
+                .map(|item| item.size()),
+        ))
+    } else {
+        None
+    };
+
+    // Only the items that the kind filter let through, so that no item is
+    // counted twice.
+    let filtered = if names.is_active() {
+        Some(Truncated::of(
+            items
+                .iter()
+                .filter(|item| item.id() != items.meta_root() && opts.includes_kind(item))
+                .filter(|item| !names.includes(item))
+                .map(|item| item.size()),
+        ))
+    } else {
+        None
+    };
 
-    let top_items: Vec<_> = top_items.into_iter().map(|i| i.id()).collect();
+    let name_bytes = if opts.name_bytes() {
+        [ir::ItemCategory::Export, ir::ItemCategory::Import]
+            .iter()
+            .map(|&kind| name_bytes(items, kind))
+            .filter(|names| names.count > 0)
+            .collect()
+    } else {
+        vec![]
+    };
 
-    let top = Top {
-        items: top_items,
+    let data_headers = if opts.data_headers() {
+        data_headers(items)
+    } else {
+        None
+    };
+
+    Ok(TopResult {
+        rows,
+        truncated,
+        truncated_ids,
+        compressed: None,
+        excluded,
+        filtered,
+        names: name_bytes,
+        data_headers,
         opts: opts.clone(),
+    })
+}
+
+/// Total the sizes of the names of every item of the given kind.
+fn name_bytes(items: &ir::Items, kind: ir::ItemCategory) -> NameBytes {
+    items.iter().filter(|item| item.category() == kind).fold(
+        NameBytes {
+            kind,
+            count: 0,
+            bytes: 0,
+        },
+        |names, item| NameBytes {
+            kind,
+            count: names.count + 1,
+            bytes: names.bytes + items.name_bytes(item.id()).unwrap_or(0),
+        },
+    )
+}
+
+/// Total the sizes of the headers and payloads of every data segment, if there
+/// are any.
+fn data_headers(items: &ir::Items) -> Option<DataHeaders> {
+    let mut data = DataHeaders {
+        count: 0,
+        header_bytes: 0,
+        payload_bytes: 0,
     };
+    for item in items.iter() {
+        if let Some(payload_bytes) = items.payload_bytes(item.id()) {
+            data.count += 1;
+            data.header_bytes += item.size() - payload_bytes;
+            data.payload_bytes += payload_bytes;
+        }
+    }
+    if data.count == 0 {
+        None
+    } else {
+        Some(data)
+    }
+}
 
-    Ok(Box::new(top) as Box<_>)
+/// Aggregate the items with a known source file into one row per file, and
+/// keep the rest as individual rows.
+fn group_by_file<'a>(
+    items: &ir::Items,
+    listed_items: impl Iterator<Item = &'a ir::Item>,
+) -> Vec<TopRow> {
+    let mut files: BTreeMap<&str, u64> = BTreeMap::new();
+    let mut rows = vec![];
+    for item in listed_items {
+        match item.source_location() {
+            Some(location) => *files.entry(location.file().as_str()).or_insert(0) += item.size(),
+            None => rows.push(TopRow::item(items, item, false)),
+        }
+    }
+    rows.extend(files.into_iter().map(|(name, size)| TopRow {
+        id: None,
+        name: name.to_string(),
+        kind: None,
+        shallow_size: size,
+        retained_size: None,
+        source_location: None,
+        control_flow: None,
+        name_bytes: None,
+        payload_bytes: None,
+    }));
+    rows
 }
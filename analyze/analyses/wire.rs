@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use flate2::write::GzEncoder;
+use flate2::{Compress, Compression, FlushCompress, Status};
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+use super::diff::{DiffEntry, DiffResult};
+
+/// How many of the largest changes have their change in gzipped size
+/// estimated. Each estimate compresses both binaries once more.
+pub const WIRE_ITEMS: usize = 10;
+
+/// The change in gzipped size between an old and a new binary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WireDiff {
+    old_size: u64,
+    new_size: u64,
+    estimates: Vec<WireEstimate>,
+}
+
+impl WireDiff {
+    /// The old binary's gzipped size, in bytes.
+    pub fn old_size(&self) -> u64 {
+        self.old_size
+    }
+
+    /// The new binary's gzipped size, in bytes.
+    pub fn new_size(&self) -> u64 {
+        self.new_size
+    }
+
+    /// The new binary's gzipped size minus the old one's, in bytes.
+    pub fn delta(&self) -> i64 {
+        self.new_size as i64 - self.old_size as i64
+    }
+
+    /// The estimates for the largest changes, largest change first.
+    pub fn estimates(&self) -> &[WireEstimate] {
+        &self.estimates
+    }
+
+    /// The estimate for the given entry of a diff, if it was estimated.
+    pub fn estimate(&self, entry: &DiffEntry) -> Option<&WireEstimate> {
+        if entry.old_id().is_none() && entry.new_id().is_none() {
+            return None;
+        }
+        self.estimates
+            .iter()
+            .find(|estimate| (estimate.old_id, estimate.new_id) == (entry.old_id(), entry.new_id()))
+    }
+}
+
+/// An estimate of how much the change to one item changed the gzipped size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WireEstimate {
+    name: String,
+    old_id: Option<ir::Id>,
+    new_id: Option<ir::Id>,
+    delta: Option<i64>,
+}
+
+impl WireEstimate {
+    /// The item's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// How many more bytes the item takes up in the new gzipped binary than
+    /// in the old one, or `None` if where the item's bytes are in either
+    /// binary is not known.
+    pub fn delta(&self) -> Option<i64> {
+        self.delta
+    }
+
+    /// The estimate, as displayed: marked as an estimate, or `?` if there is
+    /// none.
+    #[cfg(any(feature = "emit_text", feature = "emit_csv"))]
+    pub(crate) fn label(&self) -> String {
+        match self.delta {
+            Some(delta) => format!("~{:+}", delta),
+            None => "?".to_string(),
+        }
+    }
+}
+
+/// Compress the old and new binaries, and estimate how much the largest
+/// changes of `diff` contributed to the change in gzipped size.
+///
+/// Gzip compresses each part of a binary depending on what came before it, so
+/// an item's gzipped size is not well defined. Instead, how many bytes an item
+/// adds to a gzipped binary is estimated as the binary's gzipped size minus
+/// its gzipped size with the item's bytes zeroed out. An item's change is the
+/// difference of these in the new and old binaries.
+pub fn wire_diff(
+    diff: &DiffResult,
+    old_items: &ir::Items,
+    old_data: &[u8],
+    new_items: &ir::Items,
+    new_data: &[u8],
+) -> traits::Result<WireDiff> {
+    let old_size = gzip_size(old_data)?;
+    let new_size = gzip_size(new_data)?;
+
+    let estimates = diff
+        .deltas()
+        .iter()
+        .filter(|entry| entry.delta() != 0)
+        .filter(|entry| entry.old_id().is_some() || entry.new_id().is_some())
+        .take(WIRE_ITEMS)
+        .map(|entry| {
+            let old = gzipped_bytes(old_items, old_data, old_size, entry.old_id())?;
+            let new = gzipped_bytes(new_items, new_data, new_size, entry.new_id())?;
+            Ok(WireEstimate {
+                name: entry.name().to_string(),
+                old_id: entry.old_id(),
+                new_id: entry.new_id(),
+                delta: new.zip(old).map(|(new, old)| new - old),
+            })
+        })
+        .collect::<traits::Result<_>>()?;
+
+    Ok(WireDiff {
+        old_size,
+        new_size,
+        estimates,
+    })
+}
+
+/// Estimate how many bytes the given item adds to the gzipped binary, which is
+/// `gzipped_size` bytes. Zero if there is no item, and `None` if where its
+/// bytes are is not known.
+fn gzipped_bytes(
+    items: &ir::Items,
+    data: &[u8],
+    gzipped_size: u64,
+    id: Option<ir::Id>,
+) -> traits::Result<Option<i64>> {
+    let id = match id {
+        Some(id) => id,
+        None => return Ok(Some(0)),
+    };
+    let ranges = items.byte_ranges(id);
+    if ranges.is_empty() {
+        return Ok(None);
+    }
+
+    let mut zeroed = data.to_vec();
+    for range in ranges {
+        zeroed
+            .get_mut(range.start as usize..range.end as usize)
+            .ok_or_else(|| {
+                traits::Error::Analysis(format!(
+                    "the bytes of `{}` are not in the binary",
+                    items[id].name()
+                ))
+            })?
+            .fill(0);
+    }
+    Ok(Some(gzipped_size as i64 - gzip_size(&zeroed)? as i64))
+}
+
+/// The bytes that gzip adds around the deflate stream: a 10-byte header, and an
+/// 8-byte trailer with the CRC-32 and length of the data.
+const GZIP_FRAMING: u64 = 18;
+
+/// The empty stored block that a sync flush ends with: its zero length and
+/// that length's complement.
+const SYNC_FLUSH_MARKER: u64 = 4;
+
+/// An estimate of how many bytes each item takes up in the gzipped binary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedSizes {
+    sizes: BTreeMap<ir::Id, u64>,
+    unattributed: u64,
+    whole_file: u64,
+}
+
+impl CompressedSizes {
+    /// The given item's estimated gzipped size, in bytes, or `None` if where
+    /// its bytes are in the binary is not known.
+    pub fn size(&self, id: ir::Id) -> Option<u64> {
+        self.sizes.get(&id).copied()
+    }
+
+    /// The total of every item's estimated gzipped size, in bytes.
+    pub fn items_total(&self) -> u64 {
+        self.sizes.values().sum()
+    }
+
+    /// The size of the whole stream that the estimates were taken from, less
+    /// its flush markers, including the bytes that are not part of any item,
+    /// and gzip's header and trailer.
+    pub fn stream_size(&self) -> u64 {
+        self.items_total() + self.unattributed + GZIP_FRAMING
+    }
+
+    /// The size of the whole binary once gzipped in one go, in bytes.
+    pub fn whole_file_size(&self) -> u64 {
+        self.whole_file
+    }
+}
+
+/// Estimate how many bytes each item takes up in the gzipped binary.
+///
+/// The binary is deflated as a single stream, in file order, so that each
+/// item is compressed against what comes before it as it would be in the
+/// gzipped binary. The stream is flushed at the end of each item's bytes, and
+/// the item's estimate is how many bytes were written out since the previous
+/// flush, less the marker that the flush ends with. Each flush still ends a
+/// deflate block early, so the estimates usually add up to somewhat more than
+/// the gzipped binary.
+pub fn compressed_sizes(items: &ir::Items, data: &[u8]) -> traits::Result<CompressedSizes> {
+    let mut ranges: Vec<(u64, u64, ir::Id)> = items
+        .iter()
+        .flat_map(|item| {
+            items
+                .byte_ranges(item.id())
+                .iter()
+                .map(move |range| (range.start, range.end, item.id()))
+        })
+        .collect();
+    ranges.sort_unstable();
+
+    let mut compress = Compress::new(Compression::default(), false);
+    let mut sizes = BTreeMap::new();
+    let mut unattributed = 0;
+    let mut position = 0;
+    for (start, end, id) in ranges {
+        let chunk = |start: u64, end: u64| {
+            data.get(start as usize..end as usize).ok_or_else(|| {
+                traits::Error::Analysis(format!(
+                    "the bytes of `{}` are not in the binary",
+                    items[id].name()
+                ))
+            })
+        };
+        // Overlapping ranges only count once, for the item that starts
+        // first.
+        let start = start.max(position);
+        if start > position {
+            unattributed += deflate(&mut compress, chunk(position, start)?, FlushCompress::Sync)?;
+        }
+        let size = sizes.entry(id).or_insert(0);
+        if end > start {
+            *size += deflate(&mut compress, chunk(start, end)?, FlushCompress::Sync)?;
+            position = end;
+        }
+    }
+    let rest = data.get(position as usize..).unwrap_or(&[]);
+    unattributed += deflate(&mut compress, rest, FlushCompress::Finish)?;
+
+    Ok(CompressedSizes {
+        sizes,
+        unattributed,
+        whole_file: gzip_size(data)?,
+    })
+}
+
+/// Feed `input` to the deflate stream, flushing it as given, and return how
+/// many bytes of output that took, not counting a sync flush's marker.
+fn deflate(compress: &mut Compress, mut input: &[u8], flush: FlushCompress) -> io::Result<u64> {
+    let before = compress.total_out();
+    let mut output = [0; 16 * 1024];
+    loop {
+        let consumed = compress.total_in();
+        let written = compress.total_out();
+        let status = compress.compress(input, &mut output, flush)?;
+        input = &input[(compress.total_in() - consumed) as usize..];
+        let full = compress.total_out() - written == output.len() as u64;
+        if status == Status::StreamEnd
+            || (input.is_empty() && !full && flush != FlushCompress::Finish)
+        {
+            break;
+        }
+    }
+    let written = compress.total_out() - before;
+    if flush == FlushCompress::Sync {
+        Ok(written.saturating_sub(SYNC_FLUSH_MARKER))
+    } else {
+        Ok(written)
+    }
+}
+
+/// The size of `data` once gzipped at the default compression level.
+fn gzip_size(data: &[u8]) -> io::Result<u64> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?.len() as u64)
+}
@@ -5,7 +5,30 @@
 
 mod analyses;
 mod formats;
+mod provenance;
 
 pub use analyses::{
-    diff::diff, dominators::dominators, garbage::garbage, monos::monos, paths::paths, top::top,
+    call_indices::{call_indices, CallIndicesEntry, CallIndicesResult},
+    callsites::{callsites, CallsitesEntry, CallsitesResult},
+    check::{check, Budget, CheckResult, Rule, RuleResult},
+    crates::{crates, CrateEntry, CratesResult},
+    data::{data, DataResult, DataString},
+    diff::{diff, diff_items, CrateDiff, DiffEntry, DiffItem, DiffResult, DiffStatus, MatchedItem},
+    dominators::{dominators, DominatorsResult, UnreachableItemsSummary},
+    duplicates::{duplicates, DuplicateCrate, DuplicateFunction, DuplicatesResult},
+    files::{files, FileEntry, FilesResult},
+    garbage::{garbage, Explanation, GarbageResult, ReferencedBy},
+    group_cost::{group_cost, GroupBytes, GroupCostResult},
+    imports::{imports, ImportsEntry, ImportsResult},
+    map::{map_names, MappedNames},
+    monos::{monos, MonosCrateEntry, MonosEntry, MonosResult},
+    neighbors::{neighbors, NeighborsEntry, NeighborsResult},
+    paths::{paths, PathsEntry, PathsResult},
+    reachable::{reachable, unreachable_items, ItemIdSet},
+    roots::add_extra_roots,
+    sections::{sections, Rollup, SectionEntry, SectionsResult},
+    top::{top, DataHeaders, NameBytes, TopResult, TopRow},
+    wire::{compressed_sizes, wire_diff, CompressedSizes, WireDiff, WireEstimate, WIRE_ITEMS},
 };
+pub use formats::truncated::Truncated;
+pub use provenance::{Provenance, ProvenanceInput, WithProvenance};
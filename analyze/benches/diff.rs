@@ -0,0 +1,79 @@
+//! Benchmark for `diff` between two large synthetic binaries whose items
+//! mostly share names, the way two builds of the same program do.
+//!
+//! Run with `cargo bench -p twiggy-analyze --bench diff`. Building the items is
+//! not timed; matching them up and emitting the text output are.
+
+use std::io;
+use std::time::{Duration, Instant};
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits::Emit;
+
+const SYMBOLS: usize = 500_000;
+const ITERATIONS: u32 = 5;
+
+/// Build one binary's items. The new binary drops every tenth item, adds as
+/// many new ones, changes the size of every seventh, and has different symbol
+/// hashes, so that fuzzy matching has something to do.
+fn items(new: bool) -> ir::Items {
+    let mut builder = ir::ItemsBuilder::new((SYMBOLS * 1_000) as u64);
+    for i in 0..SYMBOLS {
+        let (index, hash) = match (new, i % 10) {
+            (true, 0) => (SYMBOLS + i, i as u64),
+            (true, _) => (i, !(i as u64)),
+            (false, _) => (i, i as u64),
+        };
+        let name = format!(
+            "core::ptr::drop_in_place<alloc::vec::Vec<module{}::Type{}>>::h{:016x}",
+            index % 100,
+            index,
+            hash
+        );
+        let size = if new && i % 7 == 0 {
+            (index % 1_000 + 1) as u64 * 2
+        } else {
+            (index % 1_000 + 1) as u64
+        };
+        let id = ir::Id::entry(0, i);
+        builder.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+        builder.add_root_by_id(id);
+    }
+    builder.finish()
+}
+
+fn main() {
+    let mut old_items = items(false);
+    let mut new_items = items(true);
+
+    for matching in [opt::NameMatching::Exact, opt::NameMatching::Fuzzy] {
+        let mut opts = opt::Diff::default();
+        opts.set_name_matching(matching);
+        opts.set_disambiguators(opt::Disambiguators::Keep);
+
+        let mut min = Duration::MAX;
+        let mut total = Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            let result =
+                analyze::diff(&mut old_items, &mut new_items, &opts).expect("should run diff");
+            result
+                .emit_text(&new_items, &mut io::sink())
+                .expect("should emit diff");
+            let elapsed = start.elapsed();
+
+            min = min.min(elapsed);
+            total += elapsed;
+        }
+
+        println!(
+            "diff {:?} of {} items: min {:?}, mean {:?} over {} iterations",
+            matching,
+            SYMBOLS,
+            min,
+            total / ITERATIONS,
+            ITERATIONS
+        );
+    }
+}
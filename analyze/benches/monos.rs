@@ -0,0 +1,69 @@
+//! Benchmark for `monos` over synthetic binaries with many instantiations of
+//! many generic functions, at several sizes, to show how it scales.
+//!
+//! Run with `cargo bench -p twiggy-analyze --bench monos`. Building the items
+//! is not timed; grouping the instantiations and emitting the text output are.
+
+use std::io;
+use std::time::{Duration, Instant};
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits::Emit;
+
+const SIZES: [usize; 3] = [50_000, 100_000, 200_000];
+const ITERATIONS: u32 = 5;
+
+/// Build `count` instantiations, spread over `count / 20` generic functions.
+/// Many of them share a size, so ties have to be broken by name.
+fn items(count: usize) -> ir::Items {
+    let generics = count / 20;
+    let mut builder = ir::ItemsBuilder::new((count * 100) as u64);
+    for i in 0..count {
+        let name = format!(
+            "core::ptr::drop_in_place<alloc::vec::Vec<module{}::Type{}>>::h{:016x}",
+            i % generics,
+            i,
+            i
+        );
+        let name = name.replacen(
+            "drop_in_place<",
+            &format!("generic{}::drop_in_place<", i % generics),
+            1,
+        );
+        let id = ir::Id::entry(0, i);
+        let size = (i % 50 + 1) as u64;
+        builder.add_item(ir::Item::new(id, name.as_str(), size, ir::Code::new(&name)));
+        builder.add_root_by_id(id);
+    }
+    builder.finish()
+}
+
+fn main() {
+    let opts = opt::Monos::default();
+    for count in SIZES {
+        let mut min = Duration::MAX;
+        let mut total = Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            let mut items = items(count);
+
+            let start = Instant::now();
+            let result = analyze::monos(&mut items, &opts).expect("should run monos");
+            result
+                .emit_text(&items, &mut io::sink())
+                .expect("should emit monos");
+            let elapsed = start.elapsed();
+
+            min = min.min(elapsed);
+            total += elapsed;
+        }
+
+        println!(
+            "monos over {} instantiations: min {:?}, mean {:?} over {} iterations",
+            count,
+            min,
+            total / ITERATIONS,
+            ITERATIONS
+        );
+    }
+}
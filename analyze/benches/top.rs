@@ -0,0 +1,69 @@
+//! Benchmark for `top -n 25` over a large synthetic set of items, the way it
+//! runs for a native binary with about a million symbols.
+//!
+//! Run with `cargo bench -p twiggy-analyze`. Building the items is not timed;
+//! the analysis and emitting its text output are.
+
+use std::io;
+use std::time::{Duration, Instant};
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use twiggy_traits::Emit;
+
+const SYMBOLS: usize = 1_000_000;
+const SHOWN: u32 = 25;
+const ITERATIONS: u32 = 5;
+
+fn items() -> ir::Items {
+    let mut builder = ir::ItemsBuilder::new((SYMBOLS * 10_000) as u64);
+    for i in 0..SYMBOLS {
+        let module = format!("module{}", i % 100);
+        let function = format!("function{}", i);
+        let name = format!(
+            "_ZN{}{}{}{}17h{:016x}E",
+            module.len(),
+            module,
+            function.len(),
+            function,
+            i
+        );
+        // Plenty of items share a size, so ties have to be broken.
+        let size = (i as u64 * 7919) % 10_007;
+        let id = ir::Id::entry(0, i);
+        let item = ir::Item::new(id, name.clone(), size, ir::Code::new(&name));
+        builder.add_item(item);
+        builder.add_root_by_id(id);
+    }
+    builder.finish()
+}
+
+fn main() {
+    let mut opts = opt::Top::default();
+    opts.set_max_items(SHOWN);
+
+    let mut min = Duration::MAX;
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let mut items = items();
+
+        let start = Instant::now();
+        let result = analyze::top(&mut items, &opts).expect("should run top");
+        result
+            .emit_text(&items, &mut io::sink())
+            .expect("should emit top");
+        let elapsed = start.elapsed();
+
+        min = min.min(elapsed);
+        total += elapsed;
+    }
+
+    println!(
+        "top -n {} over {} items: min {:?}, mean {:?} over {} iterations",
+        SHOWN,
+        SYMBOLS,
+        min,
+        total / ITERATIONS,
+        ITERATIONS
+    );
+}
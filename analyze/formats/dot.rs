@@ -0,0 +1,86 @@
+use std::collections::BTreeSet;
+use std::io;
+
+/// A Graphviz DOT digraph, written out node by node and edge by edge. Nodes
+/// and edges that were already written are skipped, so that an item reached
+/// along several paths is drawn once.
+pub(crate) struct Digraph<'a> {
+    dest: &'a mut dyn io::Write,
+    max_retained_size: u64,
+    nodes: BTreeSet<String>,
+    edges: BTreeSet<(String, String)>,
+}
+
+impl<'a> Digraph<'a> {
+    /// Start a digraph, in which the node with the given retained size, the
+    /// largest of any, is shaded darkest.
+    pub(crate) fn new(dest: &'a mut dyn io::Write, max_retained_size: u64) -> io::Result<Self> {
+        writeln!(dest, "digraph twiggy {{")?;
+        writeln!(dest, "  node [shape=box, style=filled];")?;
+        Ok(Digraph {
+            dest,
+            max_retained_size,
+            nodes: BTreeSet::new(),
+            edges: BTreeSet::new(),
+        })
+    }
+
+    /// Add a node labeled with the given name and size, and filled with a
+    /// shade of red proportional to its retained size.
+    pub(crate) fn node(
+        &mut self,
+        key: &str,
+        name: &str,
+        size: u64,
+        retained_size: u64,
+    ) -> io::Result<()> {
+        if !self.nodes.insert(key.to_string()) {
+            return Ok(());
+        }
+        let share = if self.max_retained_size == 0 {
+            0.0
+        } else {
+            retained_size as f64 / self.max_retained_size as f64
+        };
+        // From white, for nothing retained, to a red that black text is still
+        // readable on.
+        let light = 255 - (share.min(1.0) * 200.0).round() as u8;
+        writeln!(
+            self.dest,
+            "  \"{}\" [label=\"{}\\n({} bytes)\", fillcolor=\"#ff{:02x}{:02x}\"];",
+            escape(key),
+            escape(name),
+            size,
+            light,
+            light,
+        )
+    }
+
+    /// Add an edge between two nodes.
+    pub(crate) fn edge(&mut self, from: &str, to: &str) -> io::Result<()> {
+        if !self.edges.insert((from.to_string(), to.to_string())) {
+            return Ok(());
+        }
+        writeln!(self.dest, "  \"{}\" -> \"{}\";", escape(from), escape(to))
+    }
+
+    /// Close the digraph.
+    pub(crate) fn finish(self) -> io::Result<()> {
+        writeln!(self.dest, "}}")
+    }
+}
+
+/// Escape a string for a quoted DOT ID. Backslashes would otherwise start
+/// escapes of Graphviz's own, such as `\n` and `\N`.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -2,7 +2,11 @@
 // imbalanced and we might want to use them in some future analysis.
 #![allow(dead_code)]
 
+use serde::ser;
+use serde_derive::Serialize;
+use std::fmt;
 use std::io;
+use twiggy_ir as ir;
 
 pub trait JsonPrimitive {
     fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()>;
@@ -14,7 +18,7 @@ impl<'a> JsonPrimitive for &'a str {
         for c in self.chars() {
             match c {
                 '"' => write!(w, "\\\"")?,
-                '\\' => write!(w, "\\")?,
+                '\\' => write!(w, "\\\\")?,
                 '\n' => writeln!(w,)?,
                 c => write!(w, "{}", c)?,
             }
@@ -35,6 +39,18 @@ impl JsonPrimitive for u32 {
     }
 }
 
+impl JsonPrimitive for u64 {
+    fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+}
+
+impl JsonPrimitive for bool {
+    fn json_primitive(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+}
+
 pub fn array(w: &mut dyn io::Write) -> io::Result<Array> {
     write!(w, "[")?;
     Ok(Array {
@@ -130,6 +146,16 @@ impl<'a> Object<'a> {
         array(&mut *self.w)
     }
 
+    /// Start a field whose value the caller writes itself, such as another
+    /// analysis' JSON output.
+    pub fn raw<S>(&mut self, name: S) -> io::Result<&mut dyn io::Write>
+    where
+        S: AsRef<str>,
+    {
+        self.comma_and_name(name)?;
+        Ok(&mut *self.w)
+    }
+
     pub fn field<S, P>(&mut self, name: S, val: P) -> io::Result<()>
     where
         S: AsRef<str>,
@@ -139,3 +165,348 @@ impl<'a> Object<'a> {
         val.json_primitive(self.w)
     }
 }
+
+/// Add a `source_location` field describing the given location to `obj`. The
+/// line is left out when it is unknown.
+pub fn source_location(obj: &mut Object, location: &ir::SourceLocation) -> io::Result<()> {
+    let mut loc = obj.object("source_location")?;
+    loc.field("file", location.file().as_str())?;
+    if let Some(line) = location.line() {
+        loc.field("line", line)?;
+    }
+    Ok(())
+}
+
+/// The `source_location` field of a serialized row. The line is left out
+/// when it is unknown.
+#[derive(Debug, Serialize)]
+pub struct SourceLocation<'a> {
+    file: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+}
+
+impl<'a> SourceLocation<'a> {
+    pub fn new(location: &'a ir::SourceLocation) -> SourceLocation<'a> {
+        SourceLocation {
+            file: location.file().as_str(),
+            line: location.line(),
+        }
+    }
+}
+
+/// Write `value` as JSON, formatted just like the output of the writers
+/// above.
+///
+/// Analyses whose results are also handed to JavaScript as plain objects by
+/// the wasm API describe their JSON output with `Serialize` types, so that
+/// both come from the same rows.
+pub fn write<T>(w: &mut dyn io::Write, value: &T) -> io::Result<()>
+where
+    T: ?Sized + ser::Serialize,
+{
+    value.serialize(Writer { w }).map_err(|Error(e)| e)
+}
+
+/// The error of serializing to a writer: either the writer's, or a value
+/// that has no JSON equivalent here.
+#[derive(Debug)]
+pub struct Error(io::Error);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error(io::Error::other(msg.to_string()))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error(e)
+    }
+}
+
+fn unsupported(what: &str) -> Error {
+    ser::Error::custom(format!("{} cannot be written as JSON", what))
+}
+
+struct Writer<'a> {
+    w: &'a mut dyn io::Write,
+}
+
+impl<'a> ser::Serializer for Writer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = ser::Impossible<(), Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        Ok(v.json_primitive(self.w)?)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        Ok(write!(self.w, "{}", v)?)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        Ok(v.json_primitive(self.w)?)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        Ok(v.json_primitive(self.w)?)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        Ok(v.json_primitive(self.w)?)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(write!(self.w, "null")?)
+    }
+
+    fn serialize_some<T: ?Sized + ser::Serialize>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, Error> {
+        Compound::new(self.w, "[", "]")
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, Error> {
+        Compound::new(self.w, "{", "}")
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a>, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("enum variants with data"))
+    }
+}
+
+/// An array or object being written, which is closed by `end`.
+struct Compound<'a> {
+    w: &'a mut dyn io::Write,
+    need_comma: bool,
+    close: &'static str,
+}
+
+impl<'a> Compound<'a> {
+    fn new(
+        w: &'a mut dyn io::Write,
+        open: &'static str,
+        close: &'static str,
+    ) -> Result<Compound<'a>, Error> {
+        write!(w, "{}", open)?;
+        Ok(Compound {
+            w,
+            need_comma: false,
+            close,
+        })
+    }
+
+    fn comma(&mut self) -> Result<(), Error> {
+        if self.need_comma {
+            write!(self.w, ",")?;
+        }
+        self.need_comma = true;
+        Ok(())
+    }
+
+    fn element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.comma()?;
+        value.serialize(Writer { w: &mut *self.w })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(write!(self.w, "{}", self.close)?)
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeMap for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + ser::Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        write!(self.w, ":")?;
+        value.serialize(Writer { w: &mut *self.w })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.element(key)?;
+        write!(self.w, ":")?;
+        value.serialize(Writer { w: &mut *self.w })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Compound::end(self)
+    }
+}
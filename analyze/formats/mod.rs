@@ -1,2 +1,5 @@
+#[cfg(feature = "emit_text")]
+pub mod dot;
 pub mod json;
 pub mod table;
+pub mod truncated;
@@ -11,6 +11,7 @@ pub enum Align {
 pub struct Table {
     header: Vec<(Align, String)>,
     rows: Vec<Vec<String>>,
+    measure_chars: bool,
 }
 
 impl Table {
@@ -19,6 +20,21 @@ impl Table {
         Table {
             header,
             rows: vec![],
+            measure_chars: false,
+        }
+    }
+
+    /// Measure cells by their number of `char`s rather than their number of
+    /// bytes, for tables with cells that are not ASCII, such as bars.
+    pub(crate) fn measure_chars(&mut self) {
+        self.measure_chars = true;
+    }
+
+    fn width(&self, cell: &str) -> usize {
+        if self.measure_chars {
+            cell.chars().count()
+        } else {
+            cell.len()
         }
     }
 
@@ -30,11 +46,11 @@ impl Table {
 
 impl fmt::Display for Table {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut maxs: Vec<_> = self.header.iter().map(|h| h.1.len()).collect();
+        let mut maxs: Vec<_> = self.header.iter().map(|h| self.width(&h.1)).collect();
 
         for row in &self.rows {
             for (i, x) in row.iter().enumerate() {
-                maxs[i] = cmp::max(maxs[i], x.len());
+                maxs[i] = cmp::max(maxs[i], self.width(x));
             }
         }
 
@@ -49,7 +65,7 @@ impl fmt::Display for Table {
 
             write!(f, "{}", h)?;
             if i != last {
-                for _ in 0..maxs[i] - h.len() {
+                for _ in 0..maxs[i] - self.width(h) {
                     write!(f, " ")?;
                 }
             }
@@ -80,13 +96,13 @@ impl fmt::Display for Table {
                     Align::Left => {
                         write!(f, "{}", x)?;
                         if i != last {
-                            for _ in 0..maxs[i] - x.len() {
+                            for _ in 0..maxs[i] - self.width(x) {
                                 write!(f, " ")?;
                             }
                         }
                     }
                     Align::Right => {
-                        for _ in 0..maxs[i] - x.len() {
+                        for _ in 0..maxs[i] - self.width(x) {
                             write!(f, " ")?;
                         }
                         write!(f, "{}", x)?;
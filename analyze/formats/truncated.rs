@@ -0,0 +1,68 @@
+/// The rows beyond an analysis' `max_items`, which are summarized in a single
+/// row rather than displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Truncated {
+    count: usize,
+    bytes: i64,
+}
+
+impl Truncated {
+    /// Summarize `count` rows that total `bytes`.
+    pub(crate) fn new(count: usize, bytes: i64) -> Truncated {
+        Truncated { count, bytes }
+    }
+
+    /// Count and total the sizes of the rows that will not be displayed.
+    pub(crate) fn of(sizes: impl IntoIterator<Item = u64>) -> Truncated {
+        Truncated::of_deltas(sizes.into_iter().map(|size| size as i64))
+    }
+
+    /// Count and total the changes in size of the rows that will not be
+    /// displayed.
+    pub(crate) fn of_deltas(deltas: impl IntoIterator<Item = i64>) -> Truncated {
+        deltas
+            .into_iter()
+            .fold(Truncated::default(), |truncated, delta| Truncated {
+                count: truncated.count + 1,
+                bytes: truncated.bytes + delta,
+            })
+    }
+
+    /// How many rows were not displayed.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The total size of the rows that were not displayed, in bytes. For a
+    /// diff, this is their net change in size, which may be negative.
+    pub fn bytes(&self) -> i64 {
+        self.bytes
+    }
+
+    /// Whether every row was displayed.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The name of the row summarizing the truncated rows, with their size as
+    /// a percentage of `total_size`.
+    pub(crate) fn label(&self, total_size: u64) -> String {
+        format!(
+            "... and {} more items totaling {} bytes ({:.2}%)",
+            self.count,
+            self.bytes,
+            self.bytes as f64 / total_size as f64 * 100.0
+        )
+    }
+
+    /// Add the `truncated_count` and `truncated_bytes` fields to the JSON
+    /// object summarizing the truncated rows.
+    #[cfg(feature = "emit_json")]
+    pub(crate) fn json_fields(
+        &self,
+        obj: &mut crate::formats::json::Object,
+    ) -> std::io::Result<()> {
+        obj.field("truncated_count", self.count as u64)?;
+        obj.field("truncated_bytes", self.bytes as f64)
+    }
+}
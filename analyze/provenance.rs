@@ -0,0 +1,312 @@
+//! Recording how an analysis' output was produced, so that archived reports
+//! can be reproduced.
+
+use std::env;
+use std::fmt::Write as _;
+use std::io;
+use std::path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::formats::json;
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+/// How an analysis' output was produced: the twiggy version, the sub-command
+/// and its options, the inputs, and when.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    version: String,
+    subcommand: String,
+    options: String,
+    inputs: Vec<ProvenanceInput>,
+    timestamp: String,
+}
+
+/// An input file that an analysis read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProvenanceInput {
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+impl Provenance {
+    /// Record that the given version of twiggy ran the given sub-command with
+    /// the given options, now. `options` should include the options' default
+    /// values, not just those that were given.
+    ///
+    /// The time is taken from the `SOURCE_DATE_EPOCH` environment variable
+    /// instead, if it is set to a number of seconds since the Unix epoch, so
+    /// that reproducible builds of reports are possible.
+    pub fn new(version: &str, subcommand: &str, options: String) -> Provenance {
+        let seconds = env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |now| now.as_secs())
+            });
+        Provenance {
+            version: version.to_string(),
+            subcommand: subcommand.to_string(),
+            options,
+            inputs: vec![],
+            timestamp: rfc3339(seconds),
+        }
+    }
+
+    /// Record an input file and its contents.
+    pub fn add_input(&mut self, path: &path::Path, data: &[u8]) {
+        self.inputs.push(ProvenanceInput {
+            path: path.display().to_string(),
+            size: data.len() as u64,
+            sha256: sha256(data)
+                .iter()
+                .fold(String::with_capacity(64), |mut hex, byte| {
+                    let _ = write!(hex, "{:02x}", byte);
+                    hex
+                }),
+        });
+    }
+
+    /// The twiggy version.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The sub-command.
+    pub fn subcommand(&self) -> &str {
+        &self.subcommand
+    }
+
+    /// The sub-command's options.
+    pub fn options(&self) -> &str {
+        &self.options
+    }
+
+    /// The input files, in the order they were read.
+    pub fn inputs(&self) -> &[ProvenanceInput] {
+        &self.inputs
+    }
+
+    /// When the analysis ran, in RFC 3339 format, in UTC.
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// Wrap an analysis' result, so that emitting it also emits this
+    /// provenance: as a footer in text, as `#` comment lines before the header
+    /// in CSV, and in JSON, as the `provenance` field of an object whose
+    /// `results` field is the result.
+    pub fn wrap<'a>(&'a self, data: &'a dyn traits::Emit) -> WithProvenance<'a> {
+        WithProvenance {
+            data,
+            provenance: self,
+        }
+    }
+
+    #[cfg(any(feature = "emit_text", feature = "emit_csv"))]
+    fn comment_lines(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(
+            dest,
+            "# twiggy {} {} at {}",
+            self.version, self.subcommand, self.timestamp
+        )?;
+        for input in &self.inputs {
+            writeln!(
+                dest,
+                "# input {}: {} bytes, sha256 {}",
+                input.path, input.size, input.sha256
+            )?;
+        }
+        writeln!(dest, "# options: {}", self.options)
+    }
+}
+
+impl ProvenanceInput {
+    /// The file's path, as given.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The file's size, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The SHA-256 hash of the file's contents, in lowercase hex.
+    pub fn sha256(&self) -> &str {
+        &self.sha256
+    }
+}
+
+/// An analysis' result along with how it was produced. See
+/// `Provenance::wrap`.
+pub struct WithProvenance<'a> {
+    data: &'a dyn traits::Emit,
+    provenance: &'a Provenance,
+}
+
+impl std::fmt::Debug for WithProvenance<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("WithProvenance")
+            .field("provenance", self.provenance)
+            .finish_non_exhaustive()
+    }
+}
+
+impl traits::Emit for WithProvenance<'_> {
+    #[cfg(feature = "emit_text")]
+    fn emit_text(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        self.data.emit_text(items, dest)?;
+        self.provenance.comment_lines(dest)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "emit_json")]
+    fn emit_json(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        let provenance = self.provenance;
+        let mut obj = json::object(dest)?;
+        {
+            let mut prov = obj.object("provenance")?;
+            prov.field("twiggy_version", provenance.version.as_str())?;
+            prov.field("subcommand", provenance.subcommand.as_str())?;
+            prov.field("options", provenance.options.as_str())?;
+            prov.field("timestamp", provenance.timestamp.as_str())?;
+            let mut inputs = prov.array("inputs")?;
+            for input in &provenance.inputs {
+                let mut obj = inputs.object()?;
+                obj.field("path", input.path.as_str())?;
+                obj.field("size", input.size)?;
+                obj.field("sha256", input.sha256.as_str())?;
+            }
+        }
+        self.data.emit_json(items, obj.raw("results")?)
+    }
+
+    #[cfg(feature = "emit_csv")]
+    fn emit_csv(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        self.provenance.comment_lines(dest)?;
+        self.data.emit_csv(items, dest)
+    }
+
+    // Folded stacks have no comment syntax to record the provenance in.
+    #[cfg(feature = "emit_text")]
+    fn emit_folded(&self, _items: &ir::Items, _dest: &mut dyn io::Write) -> traits::Result<()> {
+        Err(traits::Error::Unsupported {
+            feature: "recording provenance in folded output".to_string(),
+        })
+    }
+
+    // Graphviz discards lines that start with `#`, as C preprocessor output.
+    #[cfg(feature = "emit_text")]
+    fn emit_dot(&self, items: &ir::Items, dest: &mut dyn io::Write) -> traits::Result<()> {
+        self.provenance.comment_lines(dest)?;
+        self.data.emit_dot(items, dest)
+    }
+}
+
+/// Format a number of seconds since the Unix epoch as an RFC 3339 timestamp
+/// in UTC.
+fn rfc3339(seconds: u64) -> String {
+    let (days, secs) = (seconds / 86_400, seconds % 86_400);
+
+    // Convert days since the epoch to a civil date, following Howard
+    // Hinnant's `civil_from_days`.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// The SHA-256 hash of the given data, as specified in FIPS 180-4.
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    // Pad the data with a one bit, zeros, and its length in bits, to a
+    // multiple of 64 bytes. Only the final blocks are copied.
+    let full = data.len() / 64 * 64;
+    let mut tail = data[full..].to_vec();
+    tail.push(0x80);
+    while tail.len() % 64 != 56 {
+        tail.push(0);
+    }
+    tail.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in data[..full].chunks_exact(64).chain(tail.chunks_exact(64)) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (h, x) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *h = h.wrapping_add(x);
+        }
+    }
+
+    let mut hash = [0; 32];
+    for (bytes, word) in hash.chunks_exact_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    hash
+}
@@ -0,0 +1,61 @@
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+
+#[path = "../../ir/tests/support/mod.rs"]
+mod support;
+
+// Functions that are all named `f`, of the given sizes.
+fn items(sizes: &[u64]) -> ir::Items {
+    let functions: Vec<_> = sizes.iter().map(|&size| ("f", size)).collect();
+    support::functions(&functions).0.finish()
+}
+
+fn rows(old: &[u64], new: &[u64]) -> Vec<(analyze::DiffStatus, u64, u64)> {
+    let mut opts = opt::Diff::new();
+    opts.set_all_items(true);
+    let diff = analyze::diff_items(&items(old), &items(new), &opts).unwrap();
+    let mut rows: Vec<_> = diff
+        .deltas()
+        .iter()
+        .filter_map(|entry| Some((entry.status()?, entry.old_size(), entry.new_size())))
+        .collect();
+    rows.sort_by_key(|&(_, old_size, new_size)| (old_size, new_size));
+    rows
+}
+
+#[test]
+fn same_named_items_are_paired_by_closest_size() {
+    assert_eq!(
+        rows(&[50, 1, 10], &[48, 9]),
+        [
+            (analyze::DiffStatus::Removed, 1, 0),
+            (analyze::DiffStatus::Changed, 10, 9),
+            (analyze::DiffStatus::Changed, 50, 48),
+        ]
+    );
+    assert_eq!(
+        rows(&[9], &[1, 8, 30]),
+        [
+            (analyze::DiffStatus::Added, 0, 1),
+            (analyze::DiffStatus::Added, 0, 30),
+            (analyze::DiffStatus::Changed, 9, 8),
+        ]
+    );
+}
+
+// Pairing does not compare every old item with every new one, which would take
+// a hundred million comparisons here.
+#[test]
+fn large_groups_of_same_named_items_are_paired_in_size_order() {
+    let old: Vec<u64> = (1..=10_000).collect();
+    let new: Vec<u64> = old.iter().map(|size| size + 1).collect();
+    let rows = rows(&old, &new);
+    assert_eq!(rows.len(), old.len());
+    for (status, old_size, new_size) in rows {
+        assert_eq!(
+            (status, new_size),
+            (analyze::DiffStatus::Changed, old_size + 1)
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+
+#[path = "../../ir/tests/support/mod.rs"]
+mod support;
+
+// `export` calls `a` and `c`; `a` calls `b` and `c`; `b` calls `d`.
+fn items() -> ir::Items {
+    let (mut builder, ids) =
+        support::functions(&[("export", 1), ("a", 10), ("b", 20), ("c", 30), ("d", 40)]);
+    for &(from, to) in &[(0, 1), (0, 3), (1, 2), (1, 3), (2, 4)] {
+        builder.add_edge(ids[from], ids[to]);
+    }
+    builder.finish()
+}
+
+#[test]
+fn group_cost_splits_exclusive_and_shared_dependencies() {
+    let mut items = items();
+    let mut opts = opt::GroupCost::new();
+    opts.add_name("a".to_string());
+    let cost = analyze::group_cost(&mut items, &opts).unwrap();
+
+    assert_eq!((cost.group().count(), cost.group().bytes()), (1, 10));
+    // `a`, and `b` and `d`, which only `a` keeps alive.
+    assert_eq!(
+        (cost.exclusive().count(), cost.exclusive().bytes()),
+        (3, 70)
+    );
+    // `export` also calls `c`.
+    assert_eq!((cost.shared().count(), cost.shared().bytes()), (1, 30));
+}
+
+#[test]
+fn group_cost_needs_a_group() {
+    let mut items = items();
+    assert!(analyze::group_cost(&mut items, &opt::GroupCost::new()).is_err());
+}
@@ -0,0 +1,39 @@
+use std::path::Path;
+use twiggy_analyze as analyze;
+
+fn sha256(data: &[u8]) -> String {
+    let mut provenance = analyze::Provenance::new("0.0.0", "top", String::new());
+    provenance.add_input(Path::new("input.wasm"), data);
+    assert_eq!(provenance.inputs()[0].size(), data.len() as u64);
+    provenance.inputs()[0].sha256().to_string()
+}
+
+#[test]
+fn inputs_are_hashed_with_sha256() {
+    assert_eq!(
+        sha256(b""),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        sha256(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // Padding this takes a second block.
+    assert_eq!(
+        sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(
+        sha256(&vec![b'a'; 1_000_000]),
+        "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+}
+
+#[test]
+fn timestamp_is_rfc3339_utc() {
+    let provenance = analyze::Provenance::new("0.8.0", "top", String::new());
+    let timestamp = provenance.timestamp();
+    assert_eq!(timestamp.len(), "2000-01-01T00:00:00Z".len());
+    assert!(timestamp.ends_with('Z'), "{}", timestamp);
+    assert_eq!(&timestamp[10..11], "T");
+}
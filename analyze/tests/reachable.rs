@@ -0,0 +1,40 @@
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+
+#[path = "../../ir/tests/support/mod.rs"]
+mod support;
+
+// The root `export` calls `a`, which calls `b`. `c` and the larger `d` are
+// unreachable.
+fn items() -> (ir::Items, Vec<ir::Id>) {
+    let (mut builder, ids) =
+        support::functions(&[("export", 10), ("a", 10), ("b", 10), ("c", 10), ("d", 20)]);
+    builder.add_edge(ids[0], ids[1]);
+    builder.add_edge(ids[1], ids[2]);
+    (builder.finish(), ids)
+}
+
+#[test]
+fn reachable_from_the_meta_root_borrows_the_cache() {
+    let (items, ids) = items();
+    let reachable = analyze::reachable(&items, None);
+    assert!(reachable.contains(items.meta_root()));
+    assert!(reachable.contains(ids[2]));
+    assert!(!reachable.contains(ids[3]));
+    assert_eq!(reachable.into_set(), *items.reachable());
+}
+
+#[test]
+fn reachable_from_custom_roots() {
+    let (items, ids) = items();
+    // Pretend `export` was not exported, and `a` was instead.
+    let reachable = analyze::reachable(&items, Some(&[ids[1]]));
+    assert_eq!(reachable.iter().collect::<Vec<_>>(), vec![ids[1], ids[2]]);
+    assert!(!reachable.contains(ids[0]));
+}
+
+#[test]
+fn unreachable_items_are_largest_first() {
+    let (items, ids) = items();
+    assert_eq!(analyze::unreachable_items(&items), vec![ids[4], ids[3]]);
+}
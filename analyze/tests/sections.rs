@@ -0,0 +1,68 @@
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+
+#[path = "../../ir/tests/support/mod.rs"]
+mod support;
+
+// Section 0 holds code, section 1 data, and section 2 only has entries, the way
+// native binaries' sections do.
+fn items() -> ir::Items {
+    let section = |index, name| ir::Item::new(ir::Id::section(index), name, 5, ir::Misc::new());
+    support::items(
+        vec![
+            section(0, "code section headers"),
+            section(1, "data section headers"),
+            ir::Item::new(ir::Id::entry(2, 0), "s", 30, ir::Misc::new()),
+        ],
+        vec![
+            ir::Item::new(ir::Id::entry(0, 0), "f", 40, ir::Code::new("f")),
+            ir::Item::new(ir::Id::entry(1, 0), "d", 20, ir::Data::new(None)),
+        ],
+    )
+    .finish()
+}
+
+fn sections(order: opt::SectionOrder) -> Vec<(String, u64, analyze::Rollup)> {
+    let mut items = items();
+    let mut opts = opt::Sections::new();
+    opts.set_order(order);
+    let result = analyze::sections(&mut items, &opts).unwrap();
+    result
+        .sections()
+        .iter()
+        .map(|section| (section.name().to_string(), section.size(), section.rollup()))
+        .collect()
+}
+
+#[test]
+fn sections_sum_their_entries() {
+    assert_eq!(
+        sections(opt::SectionOrder::Size),
+        [
+            ("code section".to_string(), 45, analyze::Rollup::Code),
+            ("section s2".to_string(), 30, analyze::Rollup::Other),
+            ("data section".to_string(), 25, analyze::Rollup::Data),
+        ]
+    );
+}
+
+#[test]
+fn sections_in_file_order() {
+    let names: Vec<String> = sections(opt::SectionOrder::File)
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+    assert_eq!(names, ["code section", "data section", "section s2"]);
+}
+
+#[test]
+fn sections_roll_up() {
+    let mut items = items();
+    let result = analyze::sections(&mut items, &opt::Sections::new()).unwrap();
+    assert_eq!(result.rollup_size(analyze::Rollup::Code), 45);
+    assert_eq!(result.rollup_size(analyze::Rollup::Data), 25);
+    assert_eq!(result.rollup_size(analyze::Rollup::DebugAndNames), 0);
+    assert_eq!(result.rollup_size(analyze::Rollup::Other), 30);
+    assert_eq!(result.total_size(), 100);
+}
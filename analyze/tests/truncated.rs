@@ -0,0 +1,124 @@
+use twiggy_analyze as analyze;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+
+#[path = "../../ir/tests/support/mod.rs"]
+mod support;
+
+// The root `export` references nothing, so the three instantiations of `f` and
+// the two of `g` are all garbage.
+fn items(functions: &[(&str, u64)]) -> ir::Items {
+    let mut all = vec![("export", 10)];
+    all.extend_from_slice(functions);
+    support::functions(&all).0.finish()
+}
+
+fn functions() -> ir::Items {
+    items(&[
+        ("f::h1", 40),
+        ("f::h2", 30),
+        ("f::h3", 20),
+        ("g::h1", 10),
+        ("g::h2", 5),
+    ])
+}
+
+#[test]
+fn top_truncates_beyond_max_items() {
+    let mut items = functions();
+    let mut opts = opt::Top::new();
+    opts.set_max_items(2);
+    let top = analyze::top(&mut items, &opts).unwrap();
+
+    assert_eq!(top.rows().len(), 2);
+    assert_eq!(top.truncated().count(), 4);
+    assert_eq!(top.truncated().bytes(), 20 + 10 + 10 + 5);
+}
+
+#[test]
+fn top_truncates_shallow_sizes_when_retained() {
+    let mut items = functions();
+    let mut opts = opt::Top::new();
+    opts.set_max_items(5);
+    opts.set_retained(true);
+    let top = analyze::top(&mut items, &opts).unwrap();
+
+    // The root retains every item, but only its own size is truncated.
+    assert_eq!(top.truncated().count(), 1);
+    assert_eq!(top.truncated().bytes(), 5);
+}
+
+#[test]
+fn garbage_truncates_beyond_max_items() {
+    let mut items = functions();
+    let mut opts = opt::Garbage::new();
+    opts.set_max_items(1);
+    let garbage = analyze::garbage(&mut items, &opts).unwrap();
+
+    assert_eq!(garbage.items().len(), 5);
+    assert_eq!(garbage.truncated().count(), 4);
+    assert_eq!(garbage.truncated().bytes(), 30 + 20 + 10 + 5);
+}
+
+#[test]
+fn monos_count_truncated_instantiations() {
+    let mut items = functions();
+    let mut opts = opt::Monos::new();
+    opts.set_max_generics(1);
+    opts.set_max_monos(1);
+    let monos = analyze::monos(&mut items, &opts).unwrap();
+    let entries = monos.entries();
+
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].name(), "f");
+    assert_eq!(entries[0].insts().len(), 1);
+    assert_eq!(entries[0].truncated_insts().count(), 2);
+    assert_eq!(entries[0].truncated_insts().bytes(), 30 + 20);
+    assert_eq!(entries[0].truncated(), None);
+
+    // `g` and both of its instantiations.
+    let truncated = entries[1].truncated().unwrap();
+    assert_eq!(truncated.count(), 3);
+    assert_eq!(truncated.bytes(), 10 + 5);
+
+    // The total counts the truncated instantiations of `f`, not the row
+    // summarizing them.
+    assert_eq!(entries[2].name(), "Σ [7 Total Rows]");
+}
+
+#[test]
+fn diff_truncates_net_change_beyond_max_items() {
+    let old = items(&[("a", 10), ("b", 10), ("c", 10)]);
+    let new = items(&[("a", 20), ("b", 5)]);
+    let mut opts = opt::Diff::new();
+    opts.set_max_items(1);
+    let diff = analyze::diff_items(&old, &new, &opts).unwrap();
+    let deltas = diff.deltas();
+
+    assert_eq!(deltas.len(), 3);
+    assert_eq!(deltas[0].name(), "a");
+    assert_eq!(deltas[0].truncated(), None);
+    let truncated = deltas[1].truncated().unwrap();
+    assert_eq!(truncated.count(), 2);
+    assert_eq!(truncated.bytes(), -10 - 5);
+    assert_eq!(deltas[1].delta(), -15);
+}
+
+#[test]
+fn diff_truncated_rows_sum_old_and_new_sizes() {
+    let old = items(&[("a", 10), ("b", 10), ("c", 10)]);
+    let new = items(&[("a", 20), ("b", 5), ("d", 1)]);
+    let mut opts = opt::Diff::new();
+    opts.set_max_items(1);
+    let diff = analyze::diff_items(&old, &new, &opts).unwrap();
+    let deltas = diff.deltas();
+
+    assert_eq!(deltas.len(), 3);
+    assert_eq!(deltas[0].status(), Some(analyze::DiffStatus::Changed));
+    assert_eq!((deltas[0].old_size(), deltas[0].new_size()), (10, 20));
+
+    // `b` changed, `c` was removed and `d` was added.
+    assert_eq!(deltas[1].status(), None);
+    assert_eq!((deltas[1].old_size(), deltas[1].new_size()), (20, 6));
+    assert_eq!(deltas[1].delta(), -14);
+}
@@ -0,0 +1,68 @@
+//! Benchmark for building a large set of code items and displaying a few of
+//! them, the way `twiggy top -n 20` does for a native binary with many symbols.
+//!
+//! Run with `cargo bench -p twiggy-ir`. Only the displayed names should need
+//! demangling, so the time should be dominated by building the items.
+
+use std::time::{Duration, Instant};
+use twiggy_ir as ir;
+
+const SYMBOLS: usize = 1_000_000;
+const SHOWN: usize = 20;
+const ITERATIONS: u32 = 5;
+
+fn mangled_names() -> Vec<String> {
+    (0..SYMBOLS)
+        .map(|i| {
+            let module = format!("module{}", i % 100);
+            let function = format!("function{}", i);
+            format!(
+                "_ZN{}{}{}{}17h{:016x}E",
+                module.len(),
+                module,
+                function.len(),
+                function,
+                i
+            )
+        })
+        .collect()
+}
+
+fn main() {
+    let names = mangled_names();
+
+    let mut min = Duration::MAX;
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+
+        let mut builder = ir::ItemsBuilder::new((SYMBOLS * 10) as u64);
+        for (i, name) in names.iter().enumerate() {
+            let item = ir::Item::new(ir::Id::entry(0, i), name.clone(), 10, ir::Code::new(name));
+            builder.add_item(item);
+        }
+        let items = builder.finish();
+        let shown: usize = items
+            .by_size()
+            .iter()
+            .take(SHOWN)
+            .map(|&id| items.get(id).unwrap().name().len())
+            .sum();
+        assert!(shown > 0);
+
+        let elapsed = start.elapsed();
+        drop(items);
+
+        min = min.min(elapsed);
+        total += elapsed;
+    }
+
+    println!(
+        "build {} code items and show {}: min {:?}, mean {:?} over {} iterations",
+        SYMBOLS,
+        SHOWN,
+        min,
+        total / ITERATIONS,
+        ITERATIONS
+    );
+}
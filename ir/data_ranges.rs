@@ -0,0 +1,103 @@
+use super::Id;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A map from ranges of memory addresses to the data items that define them.
+///
+/// Ranges may overlap, like active data segments that write to the same
+/// memory. Where they do, the most recently inserted range wins, which matches
+/// how later segments overwrite earlier ones when a wasm module is
+/// instantiated.
+///
+/// Internally this keeps a sorted set of disjoint pieces of ranges, so memory
+/// use is linear in the number of inserted ranges, no matter how large they
+/// are, and lookups are logarithmic.
+#[derive(Clone, Debug, Default)]
+pub struct DataRanges {
+    // Maps the start of each disjoint piece to its end and its item.
+    pieces: BTreeMap<u64, (u64, Id)>,
+}
+
+impl DataRanges {
+    /// Construct a new, empty set of data ranges.
+    pub fn new() -> DataRanges {
+        DataRanges::default()
+    }
+
+    /// Map the given range of addresses to the given item, replacing whatever
+    /// the range previously mapped to. Empty ranges are ignored.
+    pub fn insert(&mut self, range: Range<u64>, id: Id) {
+        if range.start >= range.end {
+            return;
+        }
+
+        // A piece that starts before the new range and overlaps it keeps only
+        // its part before the new range, and also its part after the new
+        // range if it extends past it.
+        if let Some((&start, &(end, old_id))) = self.pieces.range(..range.start).next_back() {
+            if end > range.start {
+                self.pieces.insert(start, (range.start, old_id));
+                if end > range.end {
+                    self.pieces.insert(range.end, (end, old_id));
+                }
+            }
+        }
+
+        // Pieces that start inside the new range are removed, except for any
+        // part of the last one that extends past the new range.
+        let covered: Vec<u64> = self
+            .pieces
+            .range(range.start..range.end)
+            .map(|(&start, _)| start)
+            .collect();
+        for start in covered {
+            let (end, old_id) = self.pieces.remove(&start).unwrap();
+            if end > range.end {
+                self.pieces.insert(range.end, (end, old_id));
+            }
+        }
+
+        self.pieces.insert(range.start, (range.end, id));
+    }
+
+    /// The item that defines the data at the given address, if any.
+    pub fn get(&self, address: u64) -> Option<Id> {
+        self.pieces
+            .range(..=address)
+            .next_back()
+            .and_then(|(_, &(end, id))| if address < end { Some(id) } else { None })
+    }
+
+    /// The items that define any of the data in the given range of addresses,
+    /// in address order and without duplicates.
+    pub fn get_range(&self, range: Range<u64>) -> Vec<Id> {
+        let mut ids = vec![];
+        if range.start >= range.end {
+            return ids;
+        }
+
+        let first = self
+            .pieces
+            .range(..range.start)
+            .next_back()
+            .filter(|(_, &(end, _))| end > range.start);
+        for (_, &(_, id)) in first.into_iter().chain(self.pieces.range(range)) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        ids
+    }
+
+    /// The number of disjoint pieces that the inserted ranges were split into.
+    ///
+    /// This is at most twice the number of inserted ranges.
+    pub fn pieces(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Is nothing mapped?
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+}
@@ -0,0 +1,132 @@
+use super::{Id, Items};
+use std::collections::BTreeMap;
+
+/// The dominator tree of an IR graph, rooted at the meta root.
+///
+/// An item `a` dominates an item `b` when every path from the meta root to `b`
+/// goes through `a`. Each reachable item's parent in the tree is its immediate
+/// dominator. Items that are not reachable from the meta root are not part of
+/// the tree.
+///
+/// Computed with `Items::compute_dominator_tree`.
+#[derive(Clone, Debug)]
+pub struct DominatorTree {
+    root: Id,
+    children: BTreeMap<Id, Vec<Id>>,
+    immediate_dominators: BTreeMap<Id, Id>,
+    depths: BTreeMap<Id, u32>,
+}
+
+impl DominatorTree {
+    pub(crate) fn new(items: &Items) -> DominatorTree {
+        let root = items.meta_root();
+        let dominators = petgraph::algo::dominators::simple_fast(items, root);
+
+        let mut children = BTreeMap::new();
+        let mut immediate_dominators = BTreeMap::new();
+        for item in items.iter() {
+            let id = item.id();
+            if id == root {
+                continue;
+            }
+            if let Some(idom) = dominators.immediate_dominator(id) {
+                immediate_dominators.insert(id, idom);
+                // Items are iterated in `Id` order, so every list of children
+                // ends up sorted too.
+                children.entry(idom).or_insert_with(Vec::new).push(id);
+            }
+        }
+
+        let mut tree = DominatorTree {
+            root,
+            children,
+            immediate_dominators,
+            depths: BTreeMap::new(),
+        };
+
+        let mut depths = BTreeMap::new();
+        let mut stack = vec![(root, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            depths.insert(id, depth);
+            stack.extend(tree.children(id).iter().map(|&child| (child, depth + 1)));
+        }
+        tree.depths = depths;
+
+        tree
+    }
+
+    /// The root of the tree, which is always the meta root.
+    pub fn root(&self) -> Id {
+        self.root
+    }
+
+    /// Is the given item part of the tree, that is, reachable from the root?
+    pub fn contains(&self, id: Id) -> bool {
+        self.depths.contains_key(&id)
+    }
+
+    /// The items immediately dominated by the given item, in `Id` order.
+    pub fn children(&self, id: Id) -> &[Id] {
+        self.children.get(&id).map_or(&[], |children| &children[..])
+    }
+
+    /// The given item's immediate dominator, or `None` for the root and for
+    /// items that are not part of the tree.
+    pub fn immediate_dominator(&self, id: Id) -> Option<Id> {
+        self.immediate_dominators.get(&id).cloned()
+    }
+
+    /// Every item's immediate dominator.
+    pub fn immediate_dominators(&self) -> &BTreeMap<Id, Id> {
+        &self.immediate_dominators
+    }
+
+    /// The given item's distance from the root, or `None` if it is not part of
+    /// the tree. The root's depth is zero.
+    pub fn depth(&self, id: Id) -> Option<u32> {
+        self.depths.get(&id).cloned()
+    }
+
+    /// Iterate over the given item and everything it dominates, in pre-order.
+    ///
+    /// Yields nothing if the item is not part of the tree.
+    pub fn subtree_ids(&self, id: Id) -> SubtreeIds<'_> {
+        let stack = if self.contains(id) { vec![id] } else { vec![] };
+        SubtreeIds { tree: self, stack }
+    }
+
+    /// Iterate over every item in the tree, in pre-order from the root.
+    pub fn iter(&self) -> SubtreeIds<'_> {
+        self.subtree_ids(self.root)
+    }
+}
+
+impl<'a> IntoIterator for &'a DominatorTree {
+    type Item = Id;
+    type IntoIter = SubtreeIds<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A pre-order iterator over a dominator subtree.
+///
+/// Each item comes before the items it dominates, and siblings are visited in
+/// `Id` order.
+#[derive(Debug)]
+pub struct SubtreeIds<'a> {
+    tree: &'a DominatorTree,
+    stack: Vec<Id>,
+}
+
+impl<'a> Iterator for SubtreeIds<'a> {
+    type Item = Id;
+
+    fn next(&mut self) -> Option<Id> {
+        let id = self.stack.pop()?;
+        self.stack
+            .extend(self.tree.children(id).iter().rev().cloned());
+        Some(id)
+    }
+}
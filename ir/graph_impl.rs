@@ -1,6 +1,6 @@
 use super::{Id, Items, Neighbors};
+use foldhash::{HashSet, HashSetExt};
 use petgraph::visit;
-use std::collections::HashSet;
 
 impl visit::GraphBase for Items {
     type EdgeId = ();
@@ -3,54 +3,166 @@
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 
+mod data_ranges;
+mod dominator_tree;
 mod graph_impl;
+mod reachability;
+mod snapshot;
+mod summary;
 
+pub use data_ranges::DataRanges;
+pub use dominator_tree::{DominatorTree, SubtreeIds};
+pub use snapshot::{SNAPSHOT_MAGIC, SNAPSHOT_VERSION};
+pub use summary::{CategorySummary, ItemsSummary};
+
+use foldhash::{HashMap, HashMapExt};
 use frozen::Frozen;
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::cmp;
 use std::collections::btree_map;
+use std::collections::hash_map;
 use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::mem;
 use std::ops;
 use std::slice;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::thread;
 use std::u32;
 
 /// Build up a a set of `Items`.
+///
+/// This is the interface between parsers and the rest of `twiggy`, and is
+/// usable by parsers for formats outside of `twiggy_parser` too. A parser is
+/// expected to:
+///
+/// * Add every item exactly once, with `add_item` or `add_root`. The sum of the
+///   added items' sizes should not exceed the size given to `new`, otherwise
+///   size percentages will be nonsensical.
+///
+/// * Only add edges once both endpoints have been added, which is why parsers
+///   make one pass for items and a second pass for edges.
+///
+/// * Mark everything that is retained by something outside the binary, such as
+///   exports and entry points, as a root. Anything not transitively reachable
+///   from a root is considered garbage.
+///
+/// * Optionally, register the address ranges of static data in each memory with
+///   `link_data`, so that code referencing those addresses can be given an edge
+///   to the data. Which addresses code refers to can be recorded with
+///   `add_data_reference`.
+///
+/// * Optionally, record the indices that the binary refers to items by with
+///   `set_binary_index`, and every direct call with `add_call_site`, for
+///   analyses of how calls are encoded. `add_call_site_bytes` also records
+///   which function made each call and how many bytes it took.
+///
+/// * Optionally, record the control structures of each function body with
+///   `set_control_flow`, their hashes with `set_body_hash`, and how many bytes
+///   of each import and export are its name with `set_name_bytes`. Imports
+///   that repeat an earlier one are marked with `set_duplicate_import`, and
+///   how many bytes of each data segment are its payload is recorded with
+///   `add_payload_bytes`.
+///
+/// * Optionally, record where in the input binary each item's bytes are with
+///   `add_byte_range`.
+///
+/// * Optionally, reserve room for the items and edges that a binary declares
+///   up front with `reserve_items` and `reserve_edges`.
+///
+/// * Report anything that was left out of the graph, such as references to
+///   items that do not exist in a malformed binary, with `add_warning`.
 #[derive(Debug)]
 pub struct ItemsBuilder {
-    size: u32,
-    size_added: u32,
-    parsed: BTreeSet<Id>,
-    items: BTreeMap<Id, Item>,
-    edges: BTreeMap<Id, BTreeSet<Id>>,
+    size: u64,
+    size_added: u64,
+    // Items and edges are hashed while they are added, so that parsers can
+    // reserve room for them up front, and only sorted once, by `finish`.
+    items: HashMap<Id, Item>,
+    edges: HashMap<(Id, Id), EdgeKind>,
     roots: BTreeSet<Id>,
 
-    // Maps the offset some data begins at to its IR item's identifier, and the
-    // byte length of the data.
-    data: BTreeMap<u64, (Id, u64)>,
+    // The number of distinct edges in `edges`, and the number above which
+    // heuristic edges are dropped. While there is a maximum, heuristic edges
+    // are only added once every other edge has been.
+    edge_count: u64,
+    max_edges: Option<u64>,
+    deferred_edges: Vec<(Id, Id)>,
+
+    // The file names used by source locations, so that every item from the
+    // same file shares a single allocation.
+    files: BTreeSet<Arc<str>>,
+
+    // Maps ranges of addresses in each memory, by its index, to the data items
+    // that define them.
+    data: BTreeMap<u32, DataRanges>,
+    // The ranges that `link_data` was given for each item, and the items
+    // referring to each address of each memory.
+    data_addresses: DataAddresses,
+    data_references: BTreeMap<(u32, u64), BTreeSet<Id>>,
+
+    binary_indices: BTreeMap<Id, u32>,
+    call_sites: BTreeMap<Id, u64>,
+    // The calls of each callee, by caller.
+    call_site_bytes: BTreeMap<Id, BTreeMap<Id, CallSiteBytes>>,
+    control_flow: BTreeMap<Id, ControlFlow>,
+    name_bytes: BTreeMap<Id, u64>,
+    payload_bytes: BTreeMap<Id, u64>,
+    body_hashes: BTreeMap<Id, BodyHash>,
+    // Maps each import to the first import of the same thing, by the same
+    // module and name, when it is not that first import.
+    duplicate_imports: BTreeMap<Id, Id>,
+    byte_ranges: BTreeMap<Id, Vec<ops::Range<u64>>>,
+    warnings: Vec<String>,
+    producers: Vec<Producer>,
 }
 
 impl ItemsBuilder {
     /// Construct a new builder, with the given size.
-    pub fn new(size: u32) -> ItemsBuilder {
+    ///
+    /// The size is the total size of the input binary in bytes. Every item's
+    /// size percentage is computed relative to it.
+    pub fn new(size: u64) -> ItemsBuilder {
         ItemsBuilder {
             size,
             size_added: 0,
-            parsed: Default::default(),
-            items: Default::default(),
-            edges: Default::default(),
+            items: HashMap::new(),
+            edges: HashMap::new(),
             roots: Default::default(),
+            edge_count: 0,
+            max_edges: None,
+            deferred_edges: Default::default(),
+            files: Default::default(),
             data: Default::default(),
+            data_addresses: Default::default(),
+            data_references: Default::default(),
+            binary_indices: Default::default(),
+            call_sites: Default::default(),
+            call_site_bytes: Default::default(),
+            control_flow: Default::default(),
+            name_bytes: Default::default(),
+            payload_bytes: Default::default(),
+            body_hashes: Default::default(),
+            duplicate_imports: Default::default(),
+            byte_ranges: Default::default(),
+            warnings: Default::default(),
+            producers: Default::default(),
         }
     }
 
     /// Add the given item to to the graph and return the `Id` that it was
     /// assigned.
+    ///
+    /// Panics if an item with the same `Id` has already been added.
     pub fn add_item(&mut self, item: Item) -> Id {
         let id = item.id;
         self.size_added += item.size;
-        self.items.insert(id, item);
-        let old_value = self.parsed.insert(id);
+        let old_value = self.items.insert(id, item);
         assert!(
-            old_value,
+            old_value.is_none(),
             "should not parse the same key into multiple items"
         );
 
@@ -59,94 +171,353 @@ impl ItemsBuilder {
 
     /// Add the given item to the graph as a root and return the `Id` that it
     /// was assigned.
+    ///
+    /// Roots are the items that are kept alive by something outside of the
+    /// binary, such as exports or the entry point. The meta root created by
+    /// `finish` has an edge to every one of them.
     pub fn add_root(&mut self, item: Item) -> Id {
         let id = self.add_item(item);
         self.roots.insert(id);
         id
     }
 
+    /// Mark the already-added item with the given `Id` as a root.
+    ///
+    /// This is for items that turn out to be kept alive from outside the
+    /// binary only after they were added, such as functions that a host
+    /// registers through a custom section. Marking a root twice has no effect.
+    pub fn add_root_by_id(&mut self, id: Id) {
+        assert!(
+            self.items.contains_key(&id),
+            "can only mark an added item as a root"
+        );
+        self.roots.insert(id);
+    }
+
     /// Add an edge between the given keys that have already been parsed into
     /// items.
+    ///
+    /// An edge means that `from` references `to`, so that `to` cannot be
+    /// removed without also removing `from`. Adding the same edge twice has no
+    /// effect. The edge's kind is `EdgeKind::Generic`; use `add_edge_kind` to
+    /// say how `from` references `to`.
     pub fn add_edge(&mut self, from: Id, to: Id) {
+        self.add_edge_kind(from, to, EdgeKind::Generic);
+    }
+
+    /// Add an edge of the given kind between the given keys that have already
+    /// been parsed into items.
+    ///
+    /// If the edge was already added, a specific kind replaces
+    /// `EdgeKind::Generic`, but otherwise the first kind added is kept.
+    ///
+    /// If a maximum number of edges was set with `set_max_edges`, new
+    /// `EdgeKind::Heuristic` edges are held back until `finish`, and only
+    /// added while there is room for them.
+    pub fn add_edge_kind(&mut self, from: Id, to: Id, kind: EdgeKind) {
         debug_assert!(self.items.contains_key(&from), "`from` is not known");
         debug_assert!(self.items.contains_key(&to), "`to` is not known");
 
-        self.edges
-            .entry(from)
-            .or_insert_with(BTreeSet::new)
-            .insert(to);
+        let defer = kind == EdgeKind::Heuristic && self.max_edges.is_some();
+        match self.edges.entry((from, to)) {
+            hash_map::Entry::Occupied(mut existing) => {
+                if *existing.get() == EdgeKind::Generic {
+                    existing.insert(kind);
+                }
+            }
+            hash_map::Entry::Vacant(_) if defer => self.deferred_edges.push((from, to)),
+            hash_map::Entry::Vacant(edge) => {
+                edge.insert(kind);
+                self.edge_count += 1;
+            }
+        }
     }
 
-    /// Add a range of static data and the `Id` that defines it.
-    pub fn link_data(&mut self, offset: i64, len: usize, id: Id) {
-        if offset >= 0 && offset <= i64::from(u32::MAX) && offset as usize + len < u32::MAX as usize
-        {
-            self.data.insert(offset as u64, (id, len as u64));
-        }
+    /// Reserve room for at least `additional` more items, such as the entries
+    /// that a section declares, so that adding them does not grow the builder
+    /// over and over.
+    pub fn reserve_items(&mut self, additional: usize) {
+        self.items.reserve(additional);
+    }
+
+    /// Reserve room for at least `additional` more edges.
+    pub fn reserve_edges(&mut self, additional: usize) {
+        self.edges.reserve(additional);
+    }
+
+    /// Set the number of edges that heuristic edges may fill the graph up to,
+    /// so that approximations cannot blow it up. Heuristic edges beyond that
+    /// are dropped, but other edges are always added. `None`, the default,
+    /// drops nothing.
+    pub fn set_max_edges(&mut self, max_edges: Option<u64>) {
+        self.max_edges = max_edges;
+    }
+
+    /// Return the number of distinct edges added so far, not counting
+    /// heuristic edges that are held back until `finish`.
+    pub fn edge_count(&self) -> u64 {
+        self.edge_count
+    }
+
+    /// Record where in the source code the already-added item with the given
+    /// `Id` was defined.
+    pub fn set_source_location(&mut self, id: Id, file: &str, line: Option<u32>) {
+        let file = match self.files.get(file) {
+            Some(file) => file.clone(),
+            None => {
+                let file: Arc<str> = Arc::from(file);
+                self.files.insert(file.clone());
+                file
+            }
+        };
+        let item = self
+            .items
+            .get_mut(&id)
+            .expect("can only set the source location of an added item");
+        item.source_location = Some(SourceLocation {
+            file: InternedStr(file),
+            line,
+        });
+    }
+
+    /// Record the index that the binary refers to the already-added item with
+    /// the given `Id` by, such as a wasm function's index in the function
+    /// index space.
+    pub fn set_binary_index(&mut self, id: Id, index: u32) {
+        self.binary_indices.insert(id, index);
+    }
+
+    /// Record one direct call of the item with the given `Id`.
+    ///
+    /// Unlike edges, every call site is counted, so a function that calls
+    /// another three times adds three call sites but only a single edge.
+    pub fn add_call_site(&mut self, callee: Id) {
+        *self.call_sites.entry(callee).or_insert(0) += 1;
+    }
+
+    /// Record one direct call of the item `callee` made by the item `caller`,
+    /// and the number of bytes the call instruction takes up in the caller.
+    pub fn add_call_site_bytes(&mut self, caller: Id, callee: Id, bytes: u64) {
+        let sites = self
+            .call_site_bytes
+            .entry(callee)
+            .or_default()
+            .entry(caller)
+            .or_default();
+        sites.calls += 1;
+        sites.bytes += bytes;
+    }
+
+    /// Record the control structures of the already-added function body with
+    /// the given `Id`.
+    pub fn set_control_flow(&mut self, id: Id, control_flow: ControlFlow) {
+        self.control_flow.insert(id, control_flow);
+    }
+
+    /// Record how many of the bytes of the already-added item with the given
+    /// `Id` encode the name it is imported or exported by, rather than what
+    /// it describes.
+    pub fn set_name_bytes(&mut self, id: Id, bytes: u64) {
+        self.name_bytes.insert(id, bytes);
+    }
+
+    /// Record that the given number of the bytes of the data segment with the
+    /// given `Id` are payload, copied into memory, rather than its headers.
+    /// An item that stands for several segments has the payloads of each
+    /// added up.
+    pub fn add_payload_bytes(&mut self, id: Id, bytes: u64) {
+        *self.payload_bytes.entry(id).or_insert(0) += bytes;
+    }
+
+    /// Record that the already-added import with the given `Id` imports the
+    /// same thing, by the same module and name, as the earlier import
+    /// `first`.
+    pub fn set_duplicate_import(&mut self, id: Id, first: Id) {
+        self.duplicate_imports.insert(id, first);
+    }
+
+    /// Record that the given range of offsets into the input binary holds
+    /// some of the bytes of the item with the given `Id`. An item whose bytes
+    /// are not contiguous, such as a function's entries in the function and
+    /// code sections, has a range for each.
+    pub fn add_byte_range(&mut self, id: Id, range: ops::Range<u64>) {
+        self.byte_ranges.entry(id).or_default().push(range);
+    }
+
+    /// Record a problem with the input that did not stop it from being
+    /// parsed, but left something out of the graph.
+    pub fn add_warning(&mut self, message: String) {
+        self.warnings.push(message);
+    }
+
+    /// Record a language, tool or SDK that the binary says produced it.
+    pub fn add_producer(&mut self, producer: Producer) {
+        self.producers.push(producer);
+    }
+
+    /// Record the hashes of the already-added function body with the given
+    /// `Id`.
+    pub fn set_body_hash(&mut self, id: Id, hash: BodyHash) {
+        self.body_hashes.insert(id, hash);
     }
 
-    /// Locate the data section defining memory at the given offset.
-    pub fn get_data(&self, offset: u64) -> Option<Id> {
+    /// Add a range of static data in the memory with the given index, and the
+    /// `Id` that defines it. A binary with a single address space puts all of
+    /// its data in memory 0.
+    ///
+    /// Ranges may overlap. Where they do, the range that was linked last
+    /// defines the data, like a later active data segment overwriting an
+    /// earlier one. Ranges in different memories never overlap.
+    pub fn link_data(&mut self, memory: u32, range: ops::Range<u64>, id: Id) {
         self.data
-            .range(offset..)
-            .next()
-            .and_then(
-                |(start, &(id, len))| {
-                    if offset < start + len {
-                        Some(id)
-                    } else {
-                        None
-                    }
-                },
-            )
+            .entry(memory)
+            .or_default()
+            .insert(range.clone(), id);
+        self.data_addresses
+            .entry(id)
+            .or_default()
+            .push((memory, range));
+    }
+
+    /// Record that the item with the given `Id` refers to the given address
+    /// of the memory with the given index, such as by loading from it.
+    pub fn add_data_reference(&mut self, from: Id, memory: u32, address: u64) {
+        self.data_references
+            .entry((memory, address))
+            .or_default()
+            .insert(from);
+    }
+
+    /// Locate the data item defining the given memory at the given address.
+    pub fn get_data(&self, memory: u32, address: u64) -> Option<Id> {
+        self.data.get(&memory)?.get(address)
     }
 
-    /// Return the size of all added items so far
-    pub fn size_added(&self) -> u32 {
+    /// Locate the data items defining any of the `len` bytes of the given
+    /// memory starting at the given address, in address order.
+    pub fn get_data_range(&self, memory: u32, address: u64, len: u64) -> Vec<Id> {
+        self.data.get(&memory).map_or_else(Vec::new, |data| {
+            data.get_range(address..address.saturating_add(len))
+        })
+    }
+
+    /// Return the size of all added items so far.
+    ///
+    /// Parsers use this to attribute whatever part of a section was not
+    /// claimed by the section's entries to an item for the section itself, by
+    /// comparing `size_added` before and after adding the entries.
+    pub fn size_added(&self) -> u64 {
         self.size_added
     }
 
     /// Finish building the IR graph and return the resulting `Items`.
+    ///
+    /// This adds the meta root item, with an edge to every root.
     pub fn finish(mut self) -> Items {
+        let mut deferred_edges = mem::take(&mut self.deferred_edges);
+        deferred_edges.sort_unstable();
+        deferred_edges.dedup();
+        let mut dropped_edges = 0;
+        for (from, to) in deferred_edges {
+            if self.max_edges.is_some_and(|max| self.edge_count >= max) {
+                dropped_edges += 1;
+            } else if let hash_map::Entry::Vacant(edge) = self.edges.entry((from, to)) {
+                edge.insert(EdgeKind::Heuristic);
+                self.edge_count += 1;
+            }
+        }
+
         let meta_root_id = Id::root();
         let meta_root = Item::new(meta_root_id, "<meta root>", 0, Misc::new());
         self.items.insert(meta_root_id, meta_root);
-        self.edges.insert(meta_root_id, self.roots.clone());
+
+        // Each item's edges are sorted by their targets.
+        let mut sorted_edges: Vec<((Id, Id), EdgeKind)> = self.edges.into_iter().collect();
+        sorted_edges.sort_unstable_by_key(|&(edge, _)| edge);
+        let mut edges: BTreeMap<Id, Vec<Id>> = BTreeMap::new();
+        let mut edge_kinds: BTreeMap<Id, Vec<EdgeKind>> = BTreeMap::new();
+        for ((from, to), kind) in sorted_edges {
+            edges.entry(from).or_default().push(to);
+            edge_kinds.entry(from).or_default().push(kind);
+        }
+        edges.insert(meta_root_id, self.roots.iter().cloned().collect());
+        edge_kinds.insert(meta_root_id, vec![EdgeKind::Generic; self.roots.len()]);
 
         Items {
             size: self.size,
             dominator_tree: None,
             retained_sizes: None,
             predecessors: None,
-            immediate_dominators: None,
-            items: Frozen::freeze(self.items),
-            edges: Frozen::freeze(
-                self.edges
-                    .into_iter()
-                    .map(|(from, tos)| (from, tos.into_iter().collect::<Vec<_>>()))
-                    .collect(),
-            ),
+            items: Frozen::freeze(self.items.into_iter().collect()),
+            edges: Frozen::freeze(edges),
+            edge_kinds: Frozen::freeze(edge_kinds),
             roots: Frozen::freeze(self.roots),
+            edge_count: self.edge_count,
+            dropped_edges,
+            binary_indices: Frozen::freeze(self.binary_indices),
+            call_sites: Frozen::freeze(self.call_sites),
+            call_site_bytes: Frozen::freeze(self.call_site_bytes),
+            control_flow: Frozen::freeze(self.control_flow),
+            name_bytes: Frozen::freeze(self.name_bytes),
+            payload_bytes: Frozen::freeze(self.payload_bytes),
+            duplicate_imports: Frozen::freeze(self.duplicate_imports),
+            byte_ranges: Frozen::freeze(self.byte_ranges),
+            data_addresses: Frozen::freeze(self.data_addresses),
+            data_references: Frozen::freeze(self.data_references),
+            warnings: self.warnings,
+            producers: self.producers,
+            body_hashes: Frozen::freeze(self.body_hashes),
             meta_root: meta_root_id,
+            threads: 1,
+            by_size: OnceLock::new(),
+            category_sizes: OnceLock::new(),
+            summary: OnceLock::new(),
+            reachable: OnceLock::new(),
         }
     }
 }
 
+/// The memory indices and address ranges that each data item was linked at.
+type DataAddresses = BTreeMap<Id, Vec<(u32, ops::Range<u64>)>>;
+
 /// The architecture- and target-independent internal representation of
 /// functions, sections, etc in a file that is being size profiled.
 ///
 /// Constructed with `ItemsBuilder`.
 #[derive(Debug)]
 pub struct Items {
-    size: u32,
-    dominator_tree: Option<BTreeMap<Id, Vec<Id>>>,
-    immediate_dominators: Option<BTreeMap<Id, Id>>,
-    retained_sizes: Option<BTreeMap<Id, u32>>,
+    size: u64,
+    dominator_tree: Option<DominatorTree>,
+    retained_sizes: Option<BTreeMap<Id, u64>>,
     predecessors: Option<BTreeMap<Id, Vec<Id>>>,
     items: Frozen<BTreeMap<Id, Item>>,
     edges: Frozen<BTreeMap<Id, Vec<Id>>>,
+    // The kind of each edge in `edges`, at the same position.
+    edge_kinds: Frozen<BTreeMap<Id, Vec<EdgeKind>>>,
     roots: Frozen<BTreeSet<Id>>,
+    edge_count: u64,
+    dropped_edges: u64,
+    binary_indices: Frozen<BTreeMap<Id, u32>>,
+    call_sites: Frozen<BTreeMap<Id, u64>>,
+    call_site_bytes: Frozen<BTreeMap<Id, BTreeMap<Id, CallSiteBytes>>>,
+    control_flow: Frozen<BTreeMap<Id, ControlFlow>>,
+    name_bytes: Frozen<BTreeMap<Id, u64>>,
+    payload_bytes: Frozen<BTreeMap<Id, u64>>,
+    duplicate_imports: Frozen<BTreeMap<Id, Id>>,
+    byte_ranges: Frozen<BTreeMap<Id, Vec<ops::Range<u64>>>>,
+    data_addresses: Frozen<DataAddresses>,
+    data_references: Frozen<BTreeMap<(u32, u64), BTreeSet<Id>>>,
+    warnings: Vec<String>,
+    producers: Vec<Producer>,
+    body_hashes: Frozen<BTreeMap<Id, BodyHash>>,
     meta_root: Id,
+    threads: usize,
+
+    // Views of the items that several analyses need, computed on first use.
+    by_size: OnceLock<Vec<Id>>,
+    category_sizes: OnceLock<BTreeMap<ItemCategory, u64>>,
+    summary: OnceLock<ItemsSummary>,
+    reachable: OnceLock<BTreeSet<Id>>,
 }
 
 impl ops::Index<Id> for Items {
@@ -175,6 +546,69 @@ impl Items {
         }
     }
 
+    /// Iterate over an item's neighbors, along with the kind of the edge to
+    /// each of them.
+    pub fn edges(&self, id: Id) -> Edges<'_> {
+        Edges {
+            neighbors: self.neighbors(id),
+            kinds: self
+                .edge_kinds
+                .get(&id)
+                .map_or_else(|| [].iter(), |kinds| kinds.iter()),
+        }
+    }
+
+    /// Get the kind of the edge from `from` to `to`, if there is one.
+    pub fn edge_kind(&self, from: Id, to: Id) -> Option<EdgeKind> {
+        let i = self.edges.get(&from)?.binary_search(&to).ok()?;
+        Some(self.edge_kinds[&from][i])
+    }
+
+    /// List the items adjacent to the given item, along with their sizes and
+    /// the kinds of the edges to them.
+    ///
+    /// Outgoing neighbors come first, in `Id` order, followed by incoming
+    /// neighbors, in `Id` order. Only edges whose kind is in `kinds` are
+    /// listed, unless `kinds` is empty. The meta root is never listed.
+    ///
+    /// Listing incoming neighbors requires having already called
+    /// `compute_predecessors`.
+    pub fn neighbor_items(
+        &self,
+        id: Id,
+        direction: Direction,
+        kinds: &[EdgeKind],
+    ) -> Vec<Neighbor> {
+        let mut neighbors = vec![];
+        let mut push = |other: Id, edge_kind: EdgeKind, direction: Direction| {
+            if other == self.meta_root || !(kinds.is_empty() || kinds.contains(&edge_kind)) {
+                return;
+            }
+            let item = &self[other];
+            neighbors.push(Neighbor {
+                id: other,
+                name: item.name().to_string(),
+                size: item.size(),
+                edge_kind,
+                direction,
+            });
+        };
+
+        if direction.follows_outgoing() {
+            for (to, edge_kind) in self.edges(id) {
+                push(to, edge_kind, Direction::Outgoing);
+            }
+        }
+        if direction.follows_incoming() {
+            for from in self.predecessors(id) {
+                let edge_kind = self.edge_kind(from, id).unwrap_or(EdgeKind::Generic);
+                push(from, edge_kind, Direction::Incoming);
+            }
+        }
+
+        neighbors
+    }
+
     /// Iterate over an item's predecessors.
     pub fn predecessors(&self, id: Id) -> Predecessors {
         Predecessors {
@@ -187,8 +621,195 @@ impl Items {
         }
     }
 
+    /// Get the number of edges between items, not counting the meta root's
+    /// edges to the roots.
+    pub fn edge_count(&self) -> u64 {
+        self.edge_count
+    }
+
+    /// Get the number of heuristic edges that were dropped to stay within the
+    /// builder's maximum number of edges.
+    pub fn dropped_edges(&self) -> u64 {
+        self.dropped_edges
+    }
+
+    /// Get the problems with the input that left something out of the graph,
+    /// in the order they were found.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Get the languages, tools and SDKs that the binary says produced it, in
+    /// the order it lists them. Empty for binaries that do not say.
+    pub fn producers(&self) -> &[Producer] {
+        &self.producers
+    }
+
+    /// Get the index that the binary refers to the given item by, if the
+    /// parser recorded one.
+    pub fn binary_index(&self, id: Id) -> Option<u32> {
+        self.binary_indices.get(&id).copied()
+    }
+
+    /// Iterate over every item that the parser recorded a binary index for,
+    /// along with that index, in `Id` order.
+    pub fn binary_indices(&self) -> impl Iterator<Item = (Id, u32)> + '_ {
+        self.binary_indices.iter().map(|(&id, &index)| (id, index))
+    }
+
+    /// Get the number of direct calls of the given item. Zero when the parser
+    /// did not record call sites.
+    pub fn call_sites(&self, id: Id) -> u64 {
+        self.call_sites.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Iterate over the items that directly call the given item, along with
+    /// how many calls each makes and how many bytes those calls take up, in
+    /// `Id` order. Empty when the parser did not record call site bytes.
+    pub fn call_site_bytes(&self, callee: Id) -> impl Iterator<Item = (Id, CallSiteBytes)> + '_ {
+        self.call_site_bytes
+            .get(&callee)
+            .into_iter()
+            .flatten()
+            .map(|(&caller, &sites)| (caller, sites))
+    }
+
+    /// Get the control structures of the given function body, if the parser
+    /// recorded them.
+    pub fn control_flow(&self, id: Id) -> Option<ControlFlow> {
+        self.control_flow.get(&id).copied()
+    }
+
+    /// Get how many of the given import's or export's bytes encode its name,
+    /// if the parser recorded it. The rest of its bytes describe what is
+    /// imported or exported.
+    pub fn name_bytes(&self, id: Id) -> Option<u64> {
+        self.name_bytes.get(&id).copied()
+    }
+
+    /// Get how many of the given data segment's bytes are its payload, if
+    /// the parser recorded it. The rest of its bytes are its headers: its
+    /// kind, memory index, offset expression, and length.
+    pub fn payload_bytes(&self, id: Id) -> Option<u64> {
+        self.payload_bytes.get(&id).copied()
+    }
+
+    /// Get the first import of the same thing as the given import, by the
+    /// same module and name, if the given import duplicates it.
+    pub fn duplicate_import_of(&self, id: Id) -> Option<Id> {
+        self.duplicate_imports.get(&id).copied()
+    }
+
+    /// Get the ranges of offsets into the input binary that hold the given
+    /// item's bytes. Empty if the parser did not record them.
+    pub fn byte_ranges(&self, id: Id) -> &[ops::Range<u64>] {
+        self.byte_ranges.get(&id).map_or(&[], |ranges| &ranges[..])
+    }
+
+    /// Get the memory indices and ranges of addresses that the given item was
+    /// linked at with `link_data`, in the order they were linked. Empty if it
+    /// is not static data at a known address.
+    pub fn data_addresses(&self, id: Id) -> &[(u32, ops::Range<u64>)] {
+        self.data_addresses
+            .get(&id)
+            .map_or(&[], |ranges| &ranges[..])
+    }
+
+    /// Get the items that refer to any of the given range of addresses of the
+    /// memory with the given index, without duplicates. Empty if the parser
+    /// did not record any references.
+    pub fn data_referrers(&self, memory: u32, range: ops::Range<u64>) -> Vec<Id> {
+        if range.start >= range.end {
+            return vec![];
+        }
+        let referrers: BTreeSet<Id> = self
+            .data_references
+            .range((memory, range.start)..(memory, range.end))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        referrers.into_iter().collect()
+    }
+
+    /// Get the hashes of the given function body, if the parser recorded
+    /// them.
+    pub fn body_hash(&self, id: Id) -> Option<BodyHash> {
+        self.body_hashes.get(&id).copied()
+    }
+
+    /// Give the function with the given `Id` a new symbol name, such as one
+    /// recovered from another build of the same binary. Panics if there is no
+    /// function with the given `Id`.
+    pub fn rename_function(&mut self, id: Id, name: &str) {
+        let mut items = mem::take(&mut self.items).thaw();
+        let item = items
+            .get_mut(&id)
+            .expect("can only rename an existing item");
+        assert!(
+            matches!(item.kind, ItemKind::Code(_)),
+            "can only rename a function"
+        );
+        item.name = name.into();
+        item.kind = ItemKind::Code(Code::new(name));
+        self.items = Frozen::freeze(items);
+    }
+
+    /// Is the given item a root, that is, does the meta root have an edge to
+    /// it?
+    pub fn is_root(&self, id: Id) -> bool {
+        self.roots.contains(&id)
+    }
+
+    /// Iterate over the roots, in `Id` order.
+    pub fn roots(&self) -> impl Iterator<Item = Id> + '_ {
+        self.roots.iter().cloned()
+    }
+
+    /// Make the given item a root after parsing, by adding an edge from the
+    /// meta root to it.
+    ///
+    /// This lets users declare entry points that the parser could not see,
+    /// such as functions that a host reaches by manipulating a table. Returns
+    /// `false`, and does nothing, if the item was already a root. Panics if
+    /// there is no item with the given `Id`.
+    ///
+    /// Predecessors, the dominator tree, retained sizes, the reachable items
+    /// and the summary all depend on the roots, so they are discarded and must
+    /// be computed again.
+    pub fn promote_to_root(&mut self, id: Id) -> bool {
+        assert!(
+            self.items.contains_key(&id),
+            "can only promote an existing item to a root"
+        );
+        assert!(id != self.meta_root, "the meta root cannot be a root");
+
+        let mut roots = mem::take(&mut self.roots).thaw();
+        let added = roots.insert(id);
+        self.roots = Frozen::freeze(roots);
+        if !added {
+            return false;
+        }
+
+        let mut edges = mem::take(&mut self.edges).thaw();
+        let mut edge_kinds = mem::take(&mut self.edge_kinds).thaw();
+        let tos = edges.entry(self.meta_root).or_default();
+        let kinds = edge_kinds.entry(self.meta_root).or_default();
+        if let Err(i) = tos.binary_search(&id) {
+            tos.insert(i, id);
+            kinds.insert(i, EdgeKind::Generic);
+        }
+        self.edges = Frozen::freeze(edges);
+        self.edge_kinds = Frozen::freeze(edge_kinds);
+
+        self.predecessors = None;
+        self.dominator_tree = None;
+        self.retained_sizes = None;
+        self.summary = OnceLock::new();
+        self.reachable = OnceLock::new();
+        true
+    }
+
     /// The size of the total binary, containing all items.
-    pub fn size(&self) -> u32 {
+    pub fn size(&self) -> u64 {
         self.size
     }
 
@@ -198,6 +819,63 @@ impl Items {
         self.meta_root
     }
 
+    /// The ids of all items, including the meta root, sorted by shallow size
+    /// from largest to smallest. Items of the same size are in `Id` order.
+    ///
+    /// This is computed the first time it is needed and then reused.
+    pub fn by_size(&self) -> &[Id] {
+        self.by_size.get_or_init(|| {
+            let mut ids: Vec<Id> = self.items.keys().cloned().collect();
+            ids.sort_by(|a, b| self.items[b].size.cmp(&self.items[a].size));
+            ids
+        })
+    }
+
+    /// The sum of the shallow sizes of all items. This can be less than
+    /// `size` when parts of the binary are not attributed to any item.
+    pub fn total_size(&self) -> u64 {
+        self.category_sizes().values().sum()
+    }
+
+    /// The sum of the shallow sizes of all items of the given category.
+    pub fn category_size(&self, category: ItemCategory) -> u64 {
+        self.category_sizes().get(&category).cloned().unwrap_or(0)
+    }
+
+    /// The sum of the shallow sizes of the items of each category present.
+    ///
+    /// This is computed the first time it is needed and then reused.
+    pub fn category_sizes(&self) -> &BTreeMap<ItemCategory, u64> {
+        self.category_sizes.get_or_init(|| {
+            let mut sizes = BTreeMap::new();
+            for item in self.items.values() {
+                *sizes.entry(item.category).or_insert(0) += item.size;
+            }
+            sizes
+        })
+    }
+
+    /// Set the number of worker threads to use when computing retained sizes
+    /// and other whole-graph properties. `0` means use all available
+    /// parallelism, and `1` (the default) computes everything sequentially.
+    ///
+    /// The work is split into a chunk per thread, each walked on a scoped
+    /// `std` thread, rather than handed to a `rayon` pool: that would add a
+    /// dependency to every build, including the WebAssembly one, which cannot
+    /// spawn threads at all. There, everything is computed sequentially,
+    /// whatever the count.
+    pub fn set_threads(&mut self, threads: u32) {
+        self.threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n as usize,
+        };
+    }
+
+    /// The number of worker threads to use for whole-graph computations.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
     /// Force computation of predecessors.
     pub fn compute_predecessors(&mut self) {
         if self.predecessors.is_some() {
@@ -223,29 +901,79 @@ impl Items {
         );
     }
 
-    /// Compute dominators for each item.
-    pub fn compute_dominators(&mut self) {
-        if self.immediate_dominators.is_some() {
-            return;
-        }
+    /// Summary statistics about the items, such as how many there are of
+    /// each category and how many edges there are between them.
+    ///
+    /// This is computed the first time it is needed and then reused.
+    pub fn summary(&self) -> &ItemsSummary {
+        self.summary.get_or_init(|| ItemsSummary::new(self))
+    }
 
-        let mut immediate_dominators = BTreeMap::new();
-        let dominators = petgraph::algo::dominators::simple_fast(&*self, self.meta_root);
+    /// The ids of the items reachable from the meta root, including the meta
+    /// root itself.
+    ///
+    /// This is computed the first time it is needed, on the worker threads set
+    /// with `set_threads`, and then reused by every analysis of these items.
+    pub fn reachable(&self) -> &BTreeSet<Id> {
+        self.reachable
+            .get_or_init(|| reachability::reachable(self, &[]))
+    }
 
-        for item in self.iter() {
-            if let Some(idom) = dominators.immediate_dominator(item.id()) {
-                immediate_dominators.insert(item.id(), idom);
-            }
+    /// Is the given item reachable from the meta root?
+    pub fn is_reachable(&self, id: Id) -> bool {
+        self.reachable().contains(&id)
+    }
+
+    /// Compute the ids of the items reachable from the meta root without
+    /// following edges of the excluded kinds. The meta root's own edges are
+    /// always followed.
+    ///
+    /// When no edge kinds are excluded, this borrows the cached result of
+    /// `reachable`. Otherwise it is computed from scratch every time.
+    pub fn reachable_without(&self, excluded_edges: &[EdgeKind]) -> Cow<'_, BTreeSet<Id>> {
+        if excluded_edges.is_empty() {
+            Cow::Borrowed(self.reachable())
+        } else {
+            Cow::Owned(reachability::reachable(self, excluded_edges))
         }
+    }
+
+    /// Compute the ids of the items reachable from the given roots, rather
+    /// than from the meta root, including the roots themselves. Every kind of
+    /// edge is followed. Panics if one of the roots is not an item.
+    ///
+    /// This is computed from scratch every time, on the worker threads set
+    /// with `set_threads`.
+    pub fn reachable_from(&self, roots: &[Id]) -> BTreeSet<Id> {
+        for root in roots {
+            assert!(
+                self.items.contains_key(root),
+                "can only compute what is reachable from existing items"
+            );
+        }
+        reachability::reachable_from(self, roots, &[], &BTreeSet::new())
+    }
+
+    /// Compute the ids of the items that would still be reachable from the
+    /// meta root if the given items were removed: those reachable without
+    /// passing through any of them. Every kind of edge is followed.
+    ///
+    /// This is computed from scratch every time, on the worker threads set
+    /// with `set_threads`.
+    pub fn reachable_without_items(&self, removed: &BTreeSet<Id>) -> BTreeSet<Id> {
+        reachability::reachable_from(self, &[self.meta_root()], &[], removed)
+    }
 
-        self.immediate_dominators = Some(immediate_dominators);
+    /// Compute dominators for each item.
+    ///
+    /// This is the same as `compute_dominator_tree`.
+    pub fn compute_dominators(&mut self) {
+        self.compute_dominator_tree();
     }
 
     /// Get a refercence to immediate dominators
     pub fn immediate_dominators(&self) -> &BTreeMap<Id, Id> {
-        self.immediate_dominators
-            .as_ref()
-            .expect("must call compute_immediate_dominators before calling immediate_dominators")
+        self.dominator_tree().immediate_dominators()
     }
 
     /// Force computation of the dominator tree.
@@ -253,30 +981,13 @@ impl Items {
         if self.dominator_tree.is_some() {
             return;
         }
-
-        let mut dominator_tree = BTreeMap::new();
-        let dominators = petgraph::algo::dominators::simple_fast(&*self, self.meta_root);
-        for item in self.iter() {
-            if let Some(idom) = dominators.immediate_dominator(item.id()) {
-                dominator_tree
-                    .entry(idom)
-                    .or_insert_with(BTreeSet::new)
-                    .insert(item.id());
-            }
-        }
-
-        self.dominator_tree = Some(
-            dominator_tree
-                .into_iter()
-                .map(|(k, v)| (k, v.into_iter().collect()))
-                .collect(),
-        );
+        self.dominator_tree = Some(DominatorTree::new(self));
     }
 
     /// Get a reference to the dominator tree.
     ///
     /// Must have already called `compute_dominator_tree`.
-    pub fn dominator_tree(&self) -> &BTreeMap<Id, Vec<Id>> {
+    pub fn dominator_tree(&self) -> &DominatorTree {
         self.dominator_tree
             .as_ref()
             .expect("must call compute_dominator_tree before calling dominator_tree")
@@ -290,11 +1001,11 @@ impl Items {
         self.compute_dominator_tree();
 
         fn recursive_retained_size(
-            retained_sizes: &mut BTreeMap<Id, u32>,
+            retained_sizes: &mut BTreeMap<Id, u64>,
             items: &Items,
             item: &Item,
-            dominator_tree: &BTreeMap<Id, Vec<Id>>,
-        ) -> u32 {
+            dominator_tree: &DominatorTree,
+        ) -> u64 {
             // Although the dominator tree cannot have cycles, because we
             // compute retained sizes in item iteration order, rather than from
             // the bottom of the dominator tree up, it is possible we have
@@ -304,15 +1015,9 @@ impl Items {
             }
 
             let mut rsize = item.size();
-            if let Some(children) = dominator_tree.get(&item.id()) {
-                for child in children {
-                    rsize += recursive_retained_size(
-                        retained_sizes,
-                        items,
-                        &items[*child],
-                        dominator_tree,
-                    );
-                }
+            for child in dominator_tree.children(item.id()) {
+                rsize +=
+                    recursive_retained_size(retained_sizes, items, &items[*child], dominator_tree);
             }
 
             let old_value = retained_sizes.insert(item.id(), rsize);
@@ -322,18 +1027,23 @@ impl Items {
             rsize
         }
 
-        let mut retained_sizes = BTreeMap::new();
+        let dominator_tree = self.dominator_tree.as_ref().unwrap();
+        #[cfg(not(target_arch = "wasm32"))]
         {
-            let dominator_tree = self.dominator_tree.as_ref().unwrap();
-            for item in self.iter() {
-                recursive_retained_size(&mut retained_sizes, self, item, dominator_tree);
+            if self.threads > 1 {
+                self.retained_sizes = Some(parallel_retained_sizes(self, dominator_tree));
+                return;
             }
         }
+        let mut retained_sizes = BTreeMap::new();
+        for item in self.iter() {
+            recursive_retained_size(&mut retained_sizes, self, item, dominator_tree);
+        }
         self.retained_sizes = Some(retained_sizes);
     }
 
     /// Get the given item's retained size.
-    pub fn retained_size(&self, id: Id) -> u32 {
+    pub fn retained_size(&self, id: Id) -> u64 {
         self.retained_sizes
             .as_ref()
             .expect(
@@ -345,15 +1055,106 @@ impl Items {
             .unwrap()
     }
 
+    /// The items whose retained sizes add up to the size of the whole binary:
+    /// those immediately dominated by the meta root, and those that are not
+    /// reachable from it at all. Every other item is retained by exactly one
+    /// of them.
+    ///
+    /// Must have already called `compute_dominator_tree`.
+    pub fn retained_roots(&self) -> impl Iterator<Item = Id> + '_ {
+        let tree = self.dominator_tree();
+        self.items.keys().cloned().filter(move |&id| {
+            id != self.meta_root
+                && match tree.immediate_dominator(id) {
+                    Some(dominator) => dominator == self.meta_root,
+                    None => !tree.contains(id),
+                }
+        })
+    }
+
+    /// Get the item with the given `Id`, if there is one.
+    pub fn get(&self, id: Id) -> Option<&Item> {
+        self.items.get(&id)
+    }
+
     /// Get an item with the given name.
+    ///
+    /// Several items can share the same name, in which case this returns the
+    /// one with the smallest `Id`. Use `get_items_by_name` to get all of them.
     pub fn get_item_by_name(&self, name: &str) -> Option<&Item> {
-        for item in self.iter() {
-            if item.name() == name {
-                return Some(item);
-            }
+        self.iter().find(|item| item.name() == name)
+    }
+
+    /// Iterate over every item with the given name, in `Id` order.
+    pub fn get_items_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Item> + 'a {
+        self.iter().filter(move |item| item.name() == name)
+    }
+}
+
+/// Compute retained sizes by splitting the meta root's dominator subtrees
+/// between worker threads. Every subtree is independent of the others, so the
+/// result is identical to the sequential computation.
+#[cfg(not(target_arch = "wasm32"))]
+fn parallel_retained_sizes(items: &Items, dominator_tree: &DominatorTree) -> BTreeMap<Id, u64> {
+    let subtrees = dominator_tree.children(items.meta_root);
+    let chunk_size = cmp::max(1, subtrees.len().div_ceil(items.threads));
+
+    let mut retained_sizes = BTreeMap::new();
+    thread::scope(|scope| {
+        let workers: Vec<_> = subtrees
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut retained_sizes = BTreeMap::new();
+                    for &root in chunk {
+                        subtree_retained_sizes(&mut retained_sizes, items, dominator_tree, root);
+                    }
+                    retained_sizes
+                })
+            })
+            .collect();
+        for worker in workers {
+            retained_sizes.extend(worker.join().expect("retained size worker panicked"));
+        }
+    });
+
+    // Whatever is left is either the meta root itself, or an item that is not
+    // reachable from it and therefore has no children in the dominator tree.
+    for item in items.iter() {
+        if retained_sizes.contains_key(&item.id()) {
+            continue;
         }
+        let children_size: u64 = dominator_tree
+            .children(item.id())
+            .iter()
+            .map(|c| retained_sizes[c])
+            .sum();
+        retained_sizes.insert(item.id(), item.size() + children_size);
+    }
+
+    retained_sizes
+}
 
-        None // Return `None` if `name` did not match any items.
+/// Compute the retained sizes of every item in the dominator subtree rooted at
+/// `root`, in post-order and without recursing, so that deep trees do not
+/// overflow a worker thread's stack.
+#[cfg(not(target_arch = "wasm32"))]
+fn subtree_retained_sizes(
+    retained_sizes: &mut BTreeMap<Id, u64>,
+    items: &Items,
+    dominator_tree: &DominatorTree,
+    root: Id,
+) {
+    let mut stack = vec![(root, false)];
+    while let Some((id, children_done)) = stack.pop() {
+        let children = dominator_tree.children(id);
+        if children_done {
+            let children_size: u64 = children.iter().map(|c| retained_sizes[c]).sum();
+            retained_sizes.insert(id, items[id].size() + children_size);
+        } else {
+            stack.push((id, true));
+            stack.extend(children.iter().map(|&child| (child, false)));
+        }
     }
 }
 
@@ -372,6 +1173,22 @@ impl<'a> Iterator for Neighbors<'a> {
     }
 }
 
+/// An iterator over an item's neighbors and the kinds of the edges to them.
+#[derive(Debug)]
+pub struct Edges<'a> {
+    neighbors: Neighbors<'a>,
+    kinds: slice::Iter<'a, EdgeKind>,
+}
+
+impl<'a> Iterator for Edges<'a> {
+    type Item = (Id, EdgeKind);
+
+    #[inline]
+    fn next(&mut self) -> Option<(Id, EdgeKind)> {
+        Some((self.neighbors.next()?, *self.kinds.next()?))
+    }
+}
+
 /// An iterator over an item's predecessors.
 #[derive(Debug)]
 pub struct Predecessors<'a> {
@@ -403,7 +1220,25 @@ impl<'a> Iterator for Iter<'a> {
 
 /// An item's unique identifier.
 /// (section index, item within that section index)
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// An `Id` is displayed, and parsed back, as `s<section>.e<entry>` for an
+/// entry, such as `s12.e345`, as `s<section>` for a section, and as `root` for
+/// the meta root. This form is what JSON output uses to refer to items. It is
+/// stable within one parse of one file, so it can be used to join the output of
+/// several analyses of that file, but the same item can have a different `Id`
+/// in a different file, or in a different version of the same file.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize
+)]
 pub struct Id(u32, u32);
 
 impl Id {
@@ -425,6 +1260,15 @@ impl Id {
         Id(u32::MAX, u32::MAX)
     }
 
+    /// Get the `Id` of the section that this item is an entry of, or this
+    /// `Id` itself for a section. `None` for the meta root.
+    pub fn containing_section(self) -> Option<Id> {
+        match self {
+            Id(u32::MAX, u32::MAX) => None,
+            Id(section, _) => Some(Id(section, u32::MAX)),
+        }
+    }
+
     /// Get the real id of a item.
     pub fn serializable(self) -> u64 {
         let top = (u64::from(self.0)) << 32;
@@ -432,31 +1276,86 @@ impl Id {
     }
 }
 
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.0, self.1) {
+            (u32::MAX, u32::MAX) => write!(f, "root"),
+            (section, u32::MAX) => write!(f, "s{}", section),
+            (section, entry) => write!(f, "s{}.e{}", section, entry),
+        }
+    }
+}
+
+impl FromStr for Id {
+    type Err = String;
+
+    /// Parse an `Id` from the form that it is displayed in.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid item id: {}", s);
+        let index = |digits: &str| -> Result<usize, String> {
+            match digits.parse::<u32>() {
+                Ok(index) if index != u32::MAX && !digits.starts_with('+') => Ok(index as usize),
+                _ => Err(invalid()),
+            }
+        };
+
+        if s == "root" {
+            return Ok(Id::root());
+        }
+        let rest = s.strip_prefix('s').ok_or_else(invalid)?;
+        match rest.split_once('.') {
+            None => Ok(Id::section(index(rest)?)),
+            Some((section, entry)) => {
+                let entry = entry.strip_prefix('e').ok_or_else(invalid)?;
+                Ok(Id::entry(index(section)?, index(entry)?))
+            }
+        }
+    }
+}
+
 /// An item in the binary.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Item {
     id: Id,
-    name: String,
-    size: u32,
+    name: Name,
+    size: u64,
     kind: ItemKind,
+    category: ItemCategory,
+    source_location: Option<SourceLocation>,
 }
 
 impl Item {
     /// Construct a new `Item` of the given kind.
-    pub fn new<S, K>(id: Id, name: S, size: u32, kind: K) -> Item
+    pub fn new<S, K>(id: Id, name: S, size: u64, kind: K) -> Item
     where
-        S: Into<String>,
+        S: Into<Name>,
         K: Into<ItemKind>,
     {
         let name = name.into();
+        let kind = kind.into();
+        let category = match kind {
+            ItemKind::Code(_) => ItemCategory::Function,
+            ItemKind::Data(_) => ItemCategory::DataSegment,
+            ItemKind::Debug(_) => ItemCategory::DebugInfo,
+            ItemKind::Misc(_) => ItemCategory::Misc,
+        };
         Item {
             id,
             name,
             size,
-            kind: kind.into(),
+            kind,
+            category,
+            source_location: None,
         }
     }
 
+    /// Set this item's category, when it is more specific than what can be
+    /// derived from its kind.
+    pub fn with_category(mut self, category: ItemCategory) -> Item {
+        self.category = category;
+        self
+    }
+
     /// Get this item's identifier.
     #[inline]
     pub fn id(&self) -> Id {
@@ -465,7 +1364,7 @@ impl Item {
 
     /// Get this item's size.
     #[inline]
-    pub fn size(&self) -> u32 {
+    pub fn size(&self) -> u64 {
         self.size
     }
 
@@ -473,9 +1372,9 @@ impl Item {
     #[inline]
     pub fn name(&self) -> &str {
         if let ItemKind::Code(ref code) = self.kind {
-            code.demangled().unwrap_or(&self.name)
+            code.demangled().unwrap_or_else(|| self.name.as_str())
         } else {
-            &self.name
+            self.name.as_str()
         }
     }
 
@@ -485,6 +1384,18 @@ impl Item {
         &self.kind
     }
 
+    /// Get this item's category.
+    #[inline]
+    pub fn category(&self) -> ItemCategory {
+        self.category
+    }
+
+    /// Get where in the source code this item was defined, if known.
+    #[inline]
+    pub fn source_location(&self) -> Option<&SourceLocation> {
+        self.source_location.as_ref()
+    }
+
     /// The the name of the generic function that this is a monomorphization of
     /// (if any).
     #[inline]
@@ -509,6 +1420,267 @@ impl Ord for Item {
     }
 }
 
+/// An item's name.
+///
+/// Names that are fully determined by a prefix and an index, like `func[42]`,
+/// are only formatted the first time they are asked for. Binaries with many
+/// unnamed entries would otherwise allocate a string for every one of them
+/// while parsing, even though most are never displayed.
+#[derive(Clone, Debug)]
+pub struct Name(NameRepr);
+
+#[derive(Clone, Debug)]
+enum NameRepr {
+    Owned(Box<str>),
+    Indexed {
+        prefix: &'static str,
+        index: u32,
+        formatted: OnceLock<Box<str>>,
+    },
+}
+
+impl Name {
+    /// Construct the name `{prefix}[{index}]`, without formatting it yet.
+    pub fn indexed(prefix: &'static str, index: usize) -> Name {
+        match u32::try_from(index) {
+            Ok(index) => Name(NameRepr::Indexed {
+                prefix,
+                index,
+                formatted: OnceLock::new(),
+            }),
+            Err(_) => Name::from(format!("{}[{}]", prefix, index)),
+        }
+    }
+
+    /// Get this name as a `&str`, formatting it if it has not been yet.
+    pub fn as_str(&self) -> &str {
+        match self.0 {
+            NameRepr::Owned(ref name) => name,
+            NameRepr::Indexed {
+                prefix,
+                index,
+                ref formatted,
+            } => formatted.get_or_init(|| format!("{}[{}]", prefix, index).into_boxed_str()),
+        }
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Name {
+        Name(NameRepr::Owned(name.into_boxed_str()))
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Name {
+        Name(NameRepr::Owned(name.into()))
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, rhs: &Name) -> bool {
+        self.as_str() == rhs.as_str()
+    }
+}
+
+impl Eq for Name {}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A string that is shared between every item referring to it, rather than
+/// being allocated for each of them.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InternedStr(Arc<str>);
+
+impl InternedStr {
+    /// Get this string as a `&str`.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ops::Deref for InternedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for InternedStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Where in the source code an item was defined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    file: InternedStr,
+    line: Option<u32>,
+}
+
+impl SourceLocation {
+    /// The source file the item was defined in.
+    #[inline]
+    pub fn file(&self) -> &InternedStr {
+        &self.file
+    }
+
+    /// The line the item was defined on, if known.
+    #[inline]
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+}
+
+/// A summary of the control structures in a function body.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlFlow {
+    structures: u32,
+    max_depth: u32,
+    largest_br_table: u64,
+}
+
+impl ControlFlow {
+    /// Construct a new `ControlFlow` summary.
+    pub fn new(structures: u32, max_depth: u32, largest_br_table: u64) -> ControlFlow {
+        ControlFlow {
+            structures,
+            max_depth,
+            largest_br_table,
+        }
+    }
+
+    /// The number of blocks, loops and ifs in the body.
+    #[inline]
+    pub fn structures(&self) -> u32 {
+        self.structures
+    }
+
+    /// How deeply the body's blocks, loops and ifs are nested. Zero for a body
+    /// without any.
+    #[inline]
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// The size in bytes of the body's largest `br_table` instruction, or zero
+    /// for a body without any.
+    #[inline]
+    pub fn largest_br_table(&self) -> u64 {
+        self.largest_br_table
+    }
+}
+
+/// The direct calls that one function makes to another.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallSiteBytes {
+    calls: u64,
+    bytes: u64,
+}
+
+impl CallSiteBytes {
+    /// Construct a new `CallSiteBytes`.
+    pub fn new(calls: u64, bytes: u64) -> CallSiteBytes {
+        CallSiteBytes { calls, bytes }
+    }
+
+    /// The number of call instructions.
+    #[inline]
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// The size in bytes of all of the call instructions together.
+    #[inline]
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+/// The hashes of a function body's bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BodyHash {
+    exact: u64,
+    normalized: u64,
+}
+
+impl BodyHash {
+    /// Construct a new `BodyHash`.
+    pub fn new(exact: u64, normalized: u64) -> BodyHash {
+        BodyHash { exact, normalized }
+    }
+
+    /// The hash of every byte of the body.
+    #[inline]
+    pub fn exact(&self) -> u64 {
+        self.exact
+    }
+
+    /// The hash of the body with the indices it refers to functions, types
+    /// and globals by left out, so that bodies that only differ in how those
+    /// are numbered hash the same.
+    #[inline]
+    pub fn normalized(&self) -> u64 {
+        self.normalized
+    }
+}
+
+/// A language, tool or SDK that produced a binary, such as those listed in a
+/// wasm `producers` custom section.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Producer {
+    field: String,
+    name: String,
+    version: String,
+}
+
+impl Producer {
+    /// Construct a new `Producer`.
+    pub fn new(field: &str, name: &str, version: &str) -> Producer {
+        Producer {
+            field: field.to_string(),
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    /// What the producer did to the binary: `language`, `processed-by` or
+    /// `sdk`.
+    #[inline]
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// The producer's name, such as `rustc`.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The producer's version, which may be empty.
+    #[inline]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+}
+
+impl fmt::Display for Producer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.name)?;
+        if !self.version.is_empty() {
+            write!(f, " {}", self.version)?;
+        }
+        Ok(())
+    }
+}
+
 /// The kind of item in the binary.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ItemKind {
@@ -536,6 +1708,356 @@ impl ItemKind {
     }
 }
 
+/// A finer-grained classification of an item than its `ItemKind`, describing
+/// what part of the binary's structure it is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ItemCategory {
+    /// A function, including its body.
+    Function,
+
+    /// A segment of static data.
+    DataSegment,
+
+    /// A global variable.
+    Global,
+
+    /// A table.
+    Table,
+
+    /// A linear memory.
+    Memory,
+
+    /// An imported item.
+    Import,
+
+    /// An exported item.
+    Export,
+
+    /// A type definition.
+    Type,
+
+    /// A segment of table elements.
+    ElementSegment,
+
+    /// An exception tag.
+    Tag,
+
+    /// A section's headers, or some other part of a section that is not
+    /// attributed to any of its entries.
+    Section,
+
+    /// Debugging symbols and information.
+    DebugInfo,
+
+    /// Part of a core dump: a snapshot of a crashed instance's state that was
+    /// attached to the module, and is not part of the module that is shipped.
+    Coredump,
+
+    /// Anything else.
+    Misc,
+}
+
+impl ItemCategory {
+    /// Every category, in declaration order.
+    pub const ALL: [ItemCategory; 14] = [
+        ItemCategory::Function,
+        ItemCategory::DataSegment,
+        ItemCategory::Global,
+        ItemCategory::Table,
+        ItemCategory::Memory,
+        ItemCategory::Import,
+        ItemCategory::Export,
+        ItemCategory::Type,
+        ItemCategory::ElementSegment,
+        ItemCategory::Tag,
+        ItemCategory::Section,
+        ItemCategory::DebugInfo,
+        ItemCategory::Coredump,
+        ItemCategory::Misc,
+    ];
+
+    /// The name used for this category in output and on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ItemCategory::Function => "function",
+            ItemCategory::DataSegment => "data_segment",
+            ItemCategory::Global => "global",
+            ItemCategory::Table => "table",
+            ItemCategory::Memory => "memory",
+            ItemCategory::Import => "import",
+            ItemCategory::Export => "export",
+            ItemCategory::Type => "type",
+            ItemCategory::ElementSegment => "element_segment",
+            ItemCategory::Tag => "tag",
+            ItemCategory::Section => "section",
+            ItemCategory::DebugInfo => "debug_info",
+            ItemCategory::Coredump => "coredump",
+            ItemCategory::Misc => "misc",
+        }
+    }
+}
+
+impl fmt::Display for ItemCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ItemCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ItemCategory::ALL
+            .iter()
+            .find(|category| category.as_str() == s)
+            .cloned()
+            .ok_or_else(|| format!("Unknown item kind: {}", s))
+    }
+}
+
+/// A kind of item to select on the command line: either one of the broad
+/// `ItemKind`s, `code`, `data`, `debug` or `misc`, or an `ItemCategory`, such
+/// as `function` or `export`.
+///
+/// `misc` names the broad kind, which takes in the `misc` category along with
+/// sections, types, imports, exports and everything else that is neither code,
+/// data nor debug info.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KindFilter {
+    /// Items whose kind is `ItemKind::Code`.
+    Code,
+
+    /// Items whose kind is `ItemKind::Data`.
+    Data,
+
+    /// Items whose kind is `ItemKind::Debug`.
+    Debug,
+
+    /// Items whose kind is `ItemKind::Misc`.
+    Misc,
+
+    /// Items of the given category.
+    Category(ItemCategory),
+}
+
+impl KindFilter {
+    /// Whether the given item is of this kind.
+    pub fn matches(self, item: &Item) -> bool {
+        match (self, item.kind()) {
+            (KindFilter::Code, ItemKind::Code(_))
+            | (KindFilter::Data, ItemKind::Data(_))
+            | (KindFilter::Debug, ItemKind::Debug(_))
+            | (KindFilter::Misc, ItemKind::Misc(_)) => true,
+            (KindFilter::Category(category), _) => item.category() == category,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for KindFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KindFilter::Code => f.write_str("code"),
+            KindFilter::Data => f.write_str("data"),
+            KindFilter::Debug => f.write_str("debug"),
+            KindFilter::Misc => f.write_str("misc"),
+            KindFilter::Category(category) => category.fmt(f),
+        }
+    }
+}
+
+impl FromStr for KindFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "code" => Ok(KindFilter::Code),
+            "data" => Ok(KindFilter::Data),
+            "debug" => Ok(KindFilter::Debug),
+            "misc" => Ok(KindFilter::Misc),
+            _ => s.parse().map(KindFilter::Category),
+        }
+    }
+}
+
+/// How one item references another.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize
+)]
+pub enum EdgeKind {
+    /// A reference that is not classified any further.
+    Generic,
+
+    /// A direct call of a function.
+    Call,
+
+    /// A reference to static data, a global, or a memory.
+    Data,
+
+    /// An export referencing the item it exports.
+    Export,
+
+    /// A table referencing an element segment, or an element segment
+    /// referencing a function it places in a table.
+    TableElement,
+
+    /// A function, an imported function, or a tag referencing its type, or
+    /// code referencing the type of a function it calls through a table or a
+    /// reference.
+    Type,
+
+    /// A reference that was guessed, such as code loading from a constant
+    /// address that falls inside a data segment, or an indirect call of a
+    /// function that its table holds and whose type matches.
+    Heuristic,
+}
+
+impl EdgeKind {
+    /// Every edge kind, in declaration order.
+    pub const ALL: [EdgeKind; 7] = [
+        EdgeKind::Generic,
+        EdgeKind::Call,
+        EdgeKind::Data,
+        EdgeKind::Export,
+        EdgeKind::TableElement,
+        EdgeKind::Type,
+        EdgeKind::Heuristic,
+    ];
+
+    /// The name used for this edge kind in output and on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EdgeKind::Generic => "generic",
+            EdgeKind::Call => "call",
+            EdgeKind::Data => "data",
+            EdgeKind::Export => "export",
+            EdgeKind::TableElement => "table_element",
+            EdgeKind::Type => "type",
+            EdgeKind::Heuristic => "heuristic",
+        }
+    }
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EdgeKind {
+    type Err = String;
+
+    /// Parse an edge kind from its name, or its plural such as `calls`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EdgeKind::ALL
+            .iter()
+            .find(|kind| kind.as_str() == s || s.strip_suffix('s') == Some(kind.as_str()))
+            .cloned()
+            .ok_or_else(|| format!("Unknown edge kind: {}", s))
+    }
+}
+
+/// Which of an item's edges to follow when listing its neighbors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    /// The items this item references.
+    #[default]
+    Outgoing,
+
+    /// The items that reference this item.
+    Incoming,
+
+    /// Both the items this item references and the items that reference it.
+    Both,
+}
+
+impl Direction {
+    /// The name used for this direction in output and on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::Outgoing => "outgoing",
+            Direction::Incoming => "incoming",
+            Direction::Both => "both",
+        }
+    }
+
+    fn follows_outgoing(self) -> bool {
+        self != Direction::Incoming
+    }
+
+    fn follows_incoming(self) -> bool {
+        self != Direction::Outgoing
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "outgoing" => Ok(Direction::Outgoing),
+            "incoming" => Ok(Direction::Incoming),
+            "both" => Ok(Direction::Both),
+            _ => Err(format!("Unknown direction: {}", s)),
+        }
+    }
+}
+
+/// An item that is adjacent to another item in the IR graph.
+///
+/// Returned by `Items::neighbor_items`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Neighbor {
+    id: Id,
+    name: String,
+    size: u64,
+    edge_kind: EdgeKind,
+    direction: Direction,
+}
+
+impl Neighbor {
+    /// The neighboring item's identifier.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The neighboring item's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The neighboring item's shallow size, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The kind of the edge between the two items.
+    pub fn edge_kind(&self) -> EdgeKind {
+        self.edge_kind
+    }
+
+    /// Whether the edge goes from the queried item to this neighbor
+    /// (`Outgoing`) or from this neighbor to the queried item (`Incoming`).
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+}
+
 impl From<Code> for ItemKind {
     fn from(c: Code) -> ItemKind {
         ItemKind::Code(c)
@@ -561,33 +2083,57 @@ impl From<Misc> for ItemKind {
 }
 
 /// Executable code. Function bodies.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// The function's name is demangled the first time it is needed, rather than
+/// when the item is constructed, so that parsing a binary with many symbols
+/// does not pay for demangling names that are never displayed or matched.
+#[derive(Clone, Debug)]
 pub struct Code {
-    demangled: Option<String>,
-    monomorphization_of: Option<String>,
+    mangled: String,
+    demangled: OnceLock<Option<String>>,
+    monomorphization_of: OnceLock<Option<String>>,
 }
 
 impl Code {
     /// Construct a new IR item for executable code.
     pub fn new(name: &str) -> Code {
-        let demangled = Self::demangle(&name);
-        let monomorphization_of =
-            Self::extract_generic_function(demangled.as_ref().map(|s| s.as_str()).unwrap_or(name));
         Code {
-            demangled,
-            monomorphization_of,
+            mangled: name.to_string(),
+            demangled: OnceLock::new(),
+            monomorphization_of: OnceLock::new(),
+        }
+    }
+
+    /// Construct a new IR item for executable code that has no symbol name,
+    /// and therefore nothing to demangle.
+    pub fn unnamed() -> Code {
+        Code {
+            mangled: String::new(),
+            demangled: OnceLock::from(None),
+            monomorphization_of: OnceLock::from(None),
         }
     }
 
+    /// Whether this function has a symbol name, rather than only an index.
+    pub fn is_named(&self) -> bool {
+        !self.mangled.is_empty()
+    }
+
     /// Get the demangled name of this function, if any.
     pub fn demangled(&self) -> Option<&str> {
-        self.demangled.as_ref().map(|s| s.as_str())
+        self.demangled
+            .get_or_init(|| Self::demangle(&self.mangled))
+            .as_deref()
     }
 
     /// Get the name of the generic function that this is a monomorphization of,
     /// if any.
     pub fn monomorphization_of(&self) -> Option<&str> {
-        self.monomorphization_of.as_ref().map(|s| s.as_str())
+        self.monomorphization_of
+            .get_or_init(|| {
+                Self::extract_generic_function(self.demangled().unwrap_or(&self.mangled))
+            })
+            .as_deref()
     }
 
     fn demangle(s: &str) -> Option<String> {
@@ -595,6 +2141,15 @@ impl Code {
             return Some(sym.to_string());
         }
 
+        // MSVC-mangled C++ symbols, as in PE binaries built with MSVC, all
+        // start with "?".
+        if s.starts_with('?') {
+            let flags = msvc_demangler::DemangleFlags::llvm();
+            if let Ok(sym) = msvc_demangler::demangle(s, flags) {
+                return Some(sym);
+            }
+        }
+
         // If the Rust demangle failed, we'll try C or C++.  C++
         // symbols almost all start with the prefixes "_Z", "__Z", and
         // ""_GLOBAL_", except for a special case.
@@ -674,6 +2229,16 @@ impl Code {
     }
 }
 
+// The demangled names are derived from the mangled one, so whether they have
+// been computed yet does not make two functions' code any different.
+impl PartialEq for Code {
+    fn eq(&self, rhs: &Code) -> bool {
+        self.mangled == rhs.mangled
+    }
+}
+
+impl Eq for Code {}
+
 /// Data inside the binary that may or may not end up loaded into memory
 /// with the executable code.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -0,0 +1,113 @@
+use super::{EdgeKind, Id, Items};
+use std::collections::BTreeSet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+
+/// Find every item reachable from the meta root without following edges of
+/// the excluded kinds. The meta root's own edges are always followed.
+pub(crate) fn reachable(items: &Items, excluded_edges: &[EdgeKind]) -> BTreeSet<Id> {
+    reachable_from(
+        items,
+        &[items.meta_root()],
+        excluded_edges,
+        &BTreeSet::new(),
+    )
+}
+
+/// Find every item reachable from the given roots, including the roots
+/// themselves, without following edges of the excluded kinds or passing
+/// through the removed items. The meta root's own edges are always followed,
+/// but not to removed items.
+///
+/// When the items are set to use several threads, this is a level-synchronous
+/// breadth-first search where each level's frontier is split between worker
+/// threads. Either way, the result is the same.
+pub(crate) fn reachable_from(
+    items: &Items,
+    roots: &[Id],
+    excluded_edges: &[EdgeKind],
+    removed: &BTreeSet<Id>,
+) -> BTreeSet<Id> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if items.threads() > 1 {
+            return parallel_reachable(items, roots, excluded_edges, removed);
+        }
+    }
+    sequential_reachable(items, roots, excluded_edges, removed)
+}
+
+/// The neighbors of `id` that are reached through edges of kinds that are not
+/// excluded, and that are not removed.
+fn followed_neighbors<'a>(
+    items: &'a Items,
+    id: Id,
+    excluded_edges: &'a [EdgeKind],
+    removed: &'a BTreeSet<Id>,
+) -> impl Iterator<Item = Id> + 'a {
+    let is_meta_root = id == items.meta_root();
+    items
+        .edges(id)
+        .filter(move |(_, kind)| is_meta_root || !excluded_edges.contains(kind))
+        .map(|(neighbor, _)| neighbor)
+        .filter(move |neighbor| !removed.contains(neighbor))
+}
+
+fn sequential_reachable(
+    items: &Items,
+    roots: &[Id],
+    excluded_edges: &[EdgeKind],
+    removed: &BTreeSet<Id>,
+) -> BTreeSet<Id> {
+    let mut reachable: BTreeSet<Id> = roots.iter().copied().collect();
+    let mut stack: Vec<Id> = reachable.iter().copied().collect();
+    while let Some(id) = stack.pop() {
+        stack.extend(
+            followed_neighbors(items, id, excluded_edges, removed)
+                .filter(|&id| reachable.insert(id)),
+        );
+    }
+    reachable
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn parallel_reachable(
+    items: &Items,
+    roots: &[Id],
+    excluded_edges: &[EdgeKind],
+    removed: &BTreeSet<Id>,
+) -> BTreeSet<Id> {
+    let mut reachable: BTreeSet<Id> = roots.iter().copied().collect();
+    let mut frontier: Vec<Id> = reachable.iter().copied().collect();
+
+    while !frontier.is_empty() {
+        let chunk_size = frontier.len().div_ceil(items.threads());
+        let discovered: Vec<Vec<Id>> = thread::scope(|scope| {
+            let reachable = &reachable;
+            let workers: Vec<_> = frontier
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .flat_map(|&id| followed_neighbors(items, id, excluded_edges, removed))
+                            .filter(|id| !reachable.contains(id))
+                            .collect()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("reachability worker panicked"))
+                .collect()
+        });
+
+        frontier = discovered
+            .into_iter()
+            .flatten()
+            .filter(|&id| reachable.insert(id))
+            .collect();
+    }
+
+    reachable
+}
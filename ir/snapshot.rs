@@ -0,0 +1,292 @@
+//! Saving `Items` to a compact file, and reading them back, so that a binary
+//! can be compared against later without parsing it, or even having it, again.
+//!
+//! A snapshot is `SNAPSHOT_MAGIC`, then the format version as a little endian
+//! `u32`, then the items encoded with `bincode`. The encoding of a version is
+//! never changed; anything that changes it bumps `SNAPSHOT_VERSION`, so that
+//! an old snapshot is turned away rather than misread.
+
+use serde::de::{self, Deserializer};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::ops;
+use std::sync::{Arc, OnceLock};
+
+use bincode::Options;
+use frozen::Frozen;
+
+use super::{
+    BodyHash, CallSiteBytes, Code, ControlFlow, Data, DebugInfo, EdgeKind, Id, InternedStr, Item,
+    ItemCategory, ItemKind, Items, Misc, Producer, SourceLocation,
+};
+
+/// The bytes that every snapshot starts with.
+pub const SNAPSHOT_MAGIC: &[u8] = b"\0twiggy-snapshot\0";
+
+/// The version of the snapshot format that this version of `twiggy` writes,
+/// and the only one that it reads.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// Everything about `Items` that is not computed from the rest on demand.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    size: u64,
+    items: Vec<SnapshotItem>,
+    // The edges from each item, with their kinds, in the order they were
+    // stored in.
+    edges: Vec<(Id, Vec<(Id, EdgeKind)>)>,
+    roots: BTreeSet<Id>,
+    edge_count: u64,
+    dropped_edges: u64,
+    binary_indices: BTreeMap<Id, u32>,
+    call_sites: BTreeMap<Id, u64>,
+    call_site_bytes: BTreeMap<Id, BTreeMap<Id, CallSiteBytes>>,
+    control_flow: BTreeMap<Id, ControlFlow>,
+    name_bytes: BTreeMap<Id, u64>,
+    payload_bytes: BTreeMap<Id, u64>,
+    duplicate_imports: BTreeMap<Id, Id>,
+    byte_ranges: BTreeMap<Id, Vec<ops::Range<u64>>>,
+    data_addresses: BTreeMap<Id, Vec<(u32, ops::Range<u64>)>>,
+    data_references: BTreeMap<(u32, u64), BTreeSet<Id>>,
+    warnings: Vec<String>,
+    producers: Vec<Producer>,
+    body_hashes: BTreeMap<Id, BodyHash>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotItem {
+    id: Id,
+    name: String,
+    size: u64,
+    kind: SnapshotKind,
+    category: ItemCategory,
+    source_location: Option<(String, Option<u32>)>,
+}
+
+/// An `ItemKind`, without the names that `Code` demangles on demand.
+#[derive(Serialize, Deserialize)]
+enum SnapshotKind {
+    // The mangled name, or `None` for an unnamed function.
+    Code(Option<String>),
+    Data(Option<String>),
+    Debug,
+    Misc,
+}
+
+// Categories are serialized by name, as in JSON output.
+impl<'de> de::Deserialize<'de> for ItemCategory {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = <String as de::Deserialize>::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
+}
+
+fn options() -> impl bincode::Options {
+    bincode::DefaultOptions::new()
+}
+
+impl Items {
+    /// Whether the given data is a snapshot written by `write_snapshot`, of
+    /// any version.
+    pub fn is_snapshot(data: &[u8]) -> bool {
+        data.starts_with(SNAPSHOT_MAGIC)
+    }
+
+    /// Write these items as a snapshot, that `read_snapshot` reads back into
+    /// the same items. Only what parsing found is saved: analyses compute
+    /// everything else again from it.
+    pub fn write_snapshot(&self, dest: &mut dyn io::Write) -> io::Result<()> {
+        let items = self
+            .items
+            .values()
+            .map(|item| SnapshotItem {
+                id: item.id,
+                name: item.name.as_str().to_string(),
+                size: item.size,
+                kind: match item.kind {
+                    ItemKind::Code(ref code) if code.is_named() => {
+                        SnapshotKind::Code(Some(code.mangled.clone()))
+                    }
+                    ItemKind::Code(_) => SnapshotKind::Code(None),
+                    ItemKind::Data(ref data) => SnapshotKind::Data(data.ty.clone()),
+                    ItemKind::Debug(_) => SnapshotKind::Debug,
+                    ItemKind::Misc(_) => SnapshotKind::Misc,
+                },
+                category: item.category,
+                source_location: item
+                    .source_location
+                    .as_ref()
+                    .map(|location| (location.file.to_string(), location.line)),
+            })
+            .collect();
+        let edges = self
+            .edges
+            .iter()
+            .map(|(from, tos)| {
+                let kinds = &self.edge_kinds[from];
+                (
+                    *from,
+                    tos.iter().cloned().zip(kinds.iter().cloned()).collect(),
+                )
+            })
+            .collect();
+        let snapshot = Snapshot {
+            size: self.size,
+            items,
+            edges,
+            roots: (*self.roots).clone(),
+            edge_count: self.edge_count,
+            dropped_edges: self.dropped_edges,
+            binary_indices: (*self.binary_indices).clone(),
+            call_sites: (*self.call_sites).clone(),
+            call_site_bytes: (*self.call_site_bytes).clone(),
+            control_flow: (*self.control_flow).clone(),
+            name_bytes: (*self.name_bytes).clone(),
+            payload_bytes: (*self.payload_bytes).clone(),
+            duplicate_imports: (*self.duplicate_imports).clone(),
+            byte_ranges: (*self.byte_ranges).clone(),
+            data_addresses: (*self.data_addresses).clone(),
+            data_references: (*self.data_references).clone(),
+            warnings: self.warnings.clone(),
+            producers: self.producers.clone(),
+            body_hashes: (*self.body_hashes).clone(),
+        };
+
+        dest.write_all(SNAPSHOT_MAGIC)?;
+        dest.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        options()
+            .serialize_into(dest, &snapshot)
+            .map_err(|e| match *e {
+                bincode::ErrorKind::Io(e) => e,
+                e => io::Error::other(e),
+            })
+    }
+
+    /// Read back the items from a snapshot written by `write_snapshot`. A
+    /// snapshot of another version of the format, or one that is cut short
+    /// or otherwise malformed, is an error.
+    pub fn read_snapshot(data: &[u8]) -> Result<Items, String> {
+        let rest = data
+            .strip_prefix(SNAPSHOT_MAGIC)
+            .ok_or_else(|| "not a twiggy snapshot".to_string())?;
+        if rest.len() < 4 {
+            return Err("malformed twiggy snapshot: it ends before its version".to_string());
+        }
+        let (version, rest) = rest.split_at(4);
+        let version = u32::from_le_bytes([version[0], version[1], version[2], version[3]]);
+        if version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "the snapshot is in version {} of the twiggy snapshot format, but this twiggy \
+                 only reads version {}; take the snapshot again with this version of twiggy",
+                version, SNAPSHOT_VERSION
+            ));
+        }
+        let snapshot: Snapshot = options()
+            .deserialize(rest)
+            .map_err(|e| format!("malformed twiggy snapshot: {}", e))?;
+        snapshot.into_items()
+    }
+}
+
+impl Snapshot {
+    fn into_items(self) -> Result<Items, String> {
+        let malformed = |what: &str, id: Id| {
+            Err(format!(
+                "malformed twiggy snapshot: {} {} is not an item",
+                what, id
+            ))
+        };
+
+        // Items from the same file share a single allocation for its name,
+        // as they do when they are parsed.
+        let mut files: BTreeSet<Arc<str>> = BTreeSet::new();
+        let mut items = BTreeMap::new();
+        for item in self.items {
+            let kind = match item.kind {
+                SnapshotKind::Code(Some(name)) => ItemKind::Code(Code::new(&name)),
+                SnapshotKind::Code(None) => ItemKind::Code(Code::unnamed()),
+                SnapshotKind::Data(ty) => ItemKind::Data(Data::new(ty)),
+                SnapshotKind::Debug => ItemKind::Debug(DebugInfo::new()),
+                SnapshotKind::Misc => ItemKind::Misc(Misc::new()),
+            };
+            let source_location = item.source_location.map(|(file, line)| {
+                let file = match files.get(file.as_str()) {
+                    Some(file) => file.clone(),
+                    None => {
+                        let file: Arc<str> = Arc::from(file);
+                        files.insert(file.clone());
+                        file
+                    }
+                };
+                SourceLocation {
+                    file: InternedStr(file),
+                    line,
+                }
+            });
+            let item = Item {
+                source_location,
+                ..Item::new(item.id, item.name, item.size, kind).with_category(item.category)
+            };
+            items.insert(item.id, item);
+        }
+
+        let meta_root = Id::root();
+        if !items.contains_key(&meta_root) {
+            return malformed("the meta root", meta_root);
+        }
+        for root in &self.roots {
+            if !items.contains_key(root) {
+                return malformed("the root", *root);
+            }
+        }
+        let mut edges = BTreeMap::new();
+        let mut edge_kinds = BTreeMap::new();
+        for (from, tos) in self.edges {
+            if !items.contains_key(&from) {
+                return malformed("the edge source", from);
+            }
+            if let Some((to, _)) = tos.iter().find(|(to, _)| !items.contains_key(to)) {
+                return malformed("the edge target", *to);
+            }
+            let (tos, kinds): (Vec<Id>, Vec<EdgeKind>) = tos.into_iter().unzip();
+            edges.insert(from, tos);
+            edge_kinds.insert(from, kinds);
+        }
+
+        Ok(Items {
+            size: self.size,
+            dominator_tree: None,
+            retained_sizes: None,
+            predecessors: None,
+            items: Frozen::freeze(items),
+            edges: Frozen::freeze(edges),
+            edge_kinds: Frozen::freeze(edge_kinds),
+            roots: Frozen::freeze(self.roots),
+            edge_count: self.edge_count,
+            dropped_edges: self.dropped_edges,
+            binary_indices: Frozen::freeze(self.binary_indices),
+            call_sites: Frozen::freeze(self.call_sites),
+            call_site_bytes: Frozen::freeze(self.call_site_bytes),
+            control_flow: Frozen::freeze(self.control_flow),
+            name_bytes: Frozen::freeze(self.name_bytes),
+            payload_bytes: Frozen::freeze(self.payload_bytes),
+            duplicate_imports: Frozen::freeze(self.duplicate_imports),
+            byte_ranges: Frozen::freeze(self.byte_ranges),
+            data_addresses: Frozen::freeze(self.data_addresses),
+            data_references: Frozen::freeze(self.data_references),
+            warnings: self.warnings,
+            producers: self.producers,
+            body_hashes: Frozen::freeze(self.body_hashes),
+            meta_root,
+            threads: 1,
+            by_size: OnceLock::new(),
+            category_sizes: OnceLock::new(),
+            summary: OnceLock::new(),
+            reachable: OnceLock::new(),
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use super::{ItemCategory, Items};
+use serde::ser::{Serialize, Serializer};
+use serde_derive::Serialize;
+use std::collections::BTreeMap;
+
+/// Summary statistics about a set of IR items.
+///
+/// The meta root, and its edges to the real roots, are not counted.
+///
+/// Computed with `Items::summary`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ItemsSummary {
+    size: u64,
+    total_size: u64,
+    item_count: usize,
+    root_count: usize,
+    edge_count: usize,
+    categories: BTreeMap<ItemCategory, CategorySummary>,
+}
+
+/// The number and combined size of the items of one category.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct CategorySummary {
+    count: usize,
+    size: u64,
+}
+
+impl ItemsSummary {
+    pub(crate) fn new(items: &Items) -> ItemsSummary {
+        let meta_root = items.meta_root();
+
+        let mut categories = BTreeMap::new();
+        let mut item_count = 0;
+        let mut edge_count = 0;
+        for item in items.iter().filter(|item| item.id() != meta_root) {
+            let category: &mut CategorySummary = categories.entry(item.category()).or_default();
+            category.count += 1;
+            category.size += item.size();
+            item_count += 1;
+            edge_count += items.neighbors(item.id()).count();
+        }
+
+        ItemsSummary {
+            size: items.size(),
+            total_size: categories.values().map(|category| category.size).sum(),
+            item_count,
+            root_count: items.neighbors(meta_root).count(),
+            edge_count,
+            categories,
+        }
+    }
+
+    /// The size of the whole binary.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The sum of the shallow sizes of all items.
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    /// The number of items.
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+
+    /// The number of roots.
+    pub fn root_count(&self) -> usize {
+        self.root_count
+    }
+
+    /// The number of edges between items.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// The number of items of the given category.
+    pub fn category_count(&self, category: ItemCategory) -> usize {
+        self.categories.get(&category).map_or(0, |c| c.count)
+    }
+
+    /// The combined shallow size of the items of the given category.
+    pub fn category_size(&self, category: ItemCategory) -> u64 {
+        self.categories.get(&category).map_or(0, |c| c.size)
+    }
+
+    /// The number and combined size of the items of each category present.
+    pub fn categories(&self) -> &BTreeMap<ItemCategory, CategorySummary> {
+        &self.categories
+    }
+}
+
+impl CategorySummary {
+    /// The number of items.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The combined shallow size of the items, in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Serialize for ItemCategory {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
@@ -0,0 +1,65 @@
+use twiggy_ir as ir;
+
+#[test]
+fn every_call_site_is_counted() {
+    let mut builder = ir::ItemsBuilder::new(20);
+    let caller = builder.add_root(ir::Item::new(
+        ir::Id::entry(0, 0),
+        "caller",
+        10,
+        ir::Code::new("caller"),
+    ));
+    let callee = builder.add_item(ir::Item::new(
+        ir::Id::entry(0, 1),
+        "callee",
+        10,
+        ir::Code::new("callee"),
+    ));
+    builder.set_binary_index(caller, 0);
+    builder.set_binary_index(callee, 1);
+    for _ in 0..3 {
+        builder.add_edge_kind(caller, callee, ir::EdgeKind::Call);
+        builder.add_call_site(callee);
+    }
+    let items = builder.finish();
+
+    assert_eq!(items.neighbors(caller).count(), 1);
+    assert_eq!(items.call_sites(callee), 3);
+    assert_eq!(items.call_sites(caller), 0);
+    assert_eq!(items.binary_index(callee), Some(1));
+    assert_eq!(items.binary_index(items.meta_root()), None);
+    assert_eq!(
+        items.binary_indices().collect::<Vec<_>>(),
+        vec![(caller, 0), (callee, 1)]
+    );
+}
+
+#[test]
+fn call_site_bytes_are_summed_per_caller() {
+    let mut builder = ir::ItemsBuilder::new(30);
+    let ids: Vec<ir::Id> = (0..3)
+        .map(|i| {
+            let name = format!("f{}", i);
+            builder.add_root(ir::Item::new(
+                ir::Id::entry(0, i),
+                name.as_str(),
+                10,
+                ir::Code::new(&name),
+            ))
+        })
+        .collect();
+    let (a, b, callee) = (ids[0], ids[1], ids[2]);
+    builder.add_call_site_bytes(a, callee, 2);
+    builder.add_call_site_bytes(a, callee, 6);
+    builder.add_call_site_bytes(b, callee, 2);
+    let items = builder.finish();
+
+    assert_eq!(
+        items.call_site_bytes(callee).collect::<Vec<_>>(),
+        vec![
+            (a, ir::CallSiteBytes::new(2, 8)),
+            (b, ir::CallSiteBytes::new(1, 2))
+        ]
+    );
+    assert_eq!(items.call_site_bytes(a).count(), 0);
+}
@@ -0,0 +1,53 @@
+use twiggy_ir as ir;
+
+const RUST: &str = "_ZN4core3ptr13drop_in_place17h0123456789abcdefE";
+const CPP: &str = "_Z3maxIiET_S0_S0_";
+const MSVC: &str = "?add@@YAHHH@Z";
+
+#[test]
+fn code_is_demangled_on_demand() {
+    let code = ir::Code::new(RUST);
+    assert_eq!(
+        code.demangled(),
+        Some("core::ptr::drop_in_place::h0123456789abcdef")
+    );
+    assert_eq!(code.monomorphization_of(), Some("core::ptr::drop_in_place"));
+
+    let code = ir::Code::new(CPP);
+    assert_eq!(code.demangled(), Some("int max<int>(int, int)"));
+    assert_eq!(code.monomorphization_of(), Some("int max"));
+
+    let code = ir::Code::new(MSVC);
+    assert_eq!(code.demangled(), Some("int __cdecl add(int, int)"));
+}
+
+#[test]
+fn names_are_matched_in_demangled_form() {
+    let mut builder = ir::ItemsBuilder::new(20);
+    let id = builder.add_item(ir::Item::new(
+        ir::Id::entry(0, 0),
+        RUST,
+        10,
+        ir::Code::new(RUST),
+    ));
+    builder.add_root_by_id(id);
+    let items = builder.finish();
+
+    let item = items
+        .get_item_by_name("core::ptr::drop_in_place::h0123456789abcdef")
+        .unwrap();
+    assert_eq!(item.id(), id);
+    assert!(items.get_item_by_name(RUST).is_none());
+}
+
+#[test]
+fn code_equality_does_not_depend_on_demangling() {
+    let demangled = ir::Code::new(RUST);
+    demangled.demangled();
+    demangled.monomorphization_of();
+    assert_eq!(demangled, ir::Code::new(RUST));
+    assert_ne!(demangled, ir::Code::new(CPP));
+
+    let item = |code: ir::Code| ir::Item::new(ir::Id::entry(0, 0), RUST, 10, code);
+    assert_eq!(item(demangled), item(ir::Code::new(RUST)));
+}
@@ -0,0 +1,127 @@
+use twiggy_ir as ir;
+
+fn id(i: usize) -> ir::Id {
+    ir::Id::entry(0, i)
+}
+
+#[test]
+fn point_lookups() {
+    let mut data = ir::DataRanges::new();
+    data.insert(10..20, id(0));
+    assert_eq!(data.get(9), None);
+    assert_eq!(data.get(10), Some(id(0)));
+    assert_eq!(data.get(15), Some(id(0)));
+    assert_eq!(data.get(19), Some(id(0)));
+    assert_eq!(data.get(20), None);
+}
+
+#[test]
+fn adjacent_ranges() {
+    let mut data = ir::DataRanges::new();
+    data.insert(0..10, id(0));
+    data.insert(10..20, id(1));
+    assert_eq!(data.get(9), Some(id(0)));
+    assert_eq!(data.get(10), Some(id(1)));
+    assert_eq!(data.get_range(5..15), vec![id(0), id(1)]);
+    assert_eq!(data.get_range(10..15), vec![id(1)]);
+    assert_eq!(data.pieces(), 2);
+}
+
+#[test]
+fn later_ranges_win() {
+    let mut data = ir::DataRanges::new();
+    data.insert(0..100, id(0));
+    data.insert(40..60, id(1));
+    assert_eq!(data.get(39), Some(id(0)));
+    assert_eq!(data.get(40), Some(id(1)));
+    assert_eq!(data.get(59), Some(id(1)));
+    assert_eq!(data.get(60), Some(id(0)));
+    assert_eq!(data.get_range(0..100), vec![id(0), id(1)]);
+
+    // Cover the end of one piece and the start of the next.
+    data.insert(50..70, id(2));
+    assert_eq!(data.get(49), Some(id(1)));
+    assert_eq!(data.get(50), Some(id(2)));
+    assert_eq!(data.get(70), Some(id(0)));
+    assert_eq!(data.get_range(45..75), vec![id(1), id(2), id(0)]);
+
+    // Replace everything.
+    data.insert(0..100, id(3));
+    assert_eq!(data.get_range(0..100), vec![id(3)]);
+    assert_eq!(data.pieces(), 1);
+}
+
+#[test]
+fn same_start() {
+    let mut data = ir::DataRanges::new();
+    data.insert(0..10, id(0));
+    data.insert(0..5, id(1));
+    assert_eq!(data.get(4), Some(id(1)));
+    assert_eq!(data.get(5), Some(id(0)));
+}
+
+#[test]
+fn empty_ranges() {
+    let mut data = ir::DataRanges::new();
+    data.insert(5..5, id(0));
+    assert!(data.is_empty());
+    data.insert(0..10, id(1));
+    assert!(data.get_range(3..3).is_empty());
+    assert!(data.get_range(10..20).is_empty());
+}
+
+#[test]
+fn memory_is_linear_in_ranges() {
+    // Huge, nested ranges must not be stored byte by byte.
+    let mut data = ir::DataRanges::new();
+    let n = 1000;
+    for i in 0..n {
+        let start = i as u64 * 0x10_0000;
+        data.insert(start..u64::MAX - start, id(i));
+    }
+    assert!(data.pieces() <= 2 * n);
+    assert_eq!(data.get(u64::MAX / 2), Some(id(n - 1)));
+    assert_eq!(data.get(0), Some(id(0)));
+}
+
+#[test]
+fn builder_links_data() {
+    let mut builder = ir::ItemsBuilder::new(30);
+    builder.link_data(0, 0..20, id(0));
+    builder.link_data(0, 10..30, id(1));
+    assert_eq!(builder.get_data(0, 5), Some(id(0)));
+    assert_eq!(builder.get_data(0, 15), Some(id(1)));
+    assert_eq!(builder.get_data_range(0, 0, 30), vec![id(0), id(1)]);
+    assert_eq!(builder.get_data_range(0, 20, 0), vec![]);
+}
+
+#[test]
+fn builder_keeps_memories_apart() {
+    let mut builder = ir::ItemsBuilder::new(30);
+    builder.link_data(0, 0..10, id(0));
+    builder.link_data(1, 5..20, id(1));
+    assert_eq!(builder.get_data(0, 5), Some(id(0)));
+    assert_eq!(builder.get_data(1, 5), Some(id(1)));
+    assert_eq!(builder.get_data(0, 15), None);
+    assert_eq!(builder.get_data(2, 5), None);
+    assert_eq!(builder.get_data_range(1, 0, 30), vec![id(1)]);
+    assert_eq!(builder.get_data_range(2, 0, 30), vec![]);
+}
+
+#[test]
+fn items_keep_addresses_and_references() {
+    let mut builder = ir::ItemsBuilder::new(30);
+    builder.link_data(0, 0..10, id(0));
+    builder.link_data(1, 0..10, id(1));
+    builder.add_data_reference(id(2), 0, 4);
+    builder.add_data_reference(id(3), 0, 9);
+    builder.add_data_reference(id(3), 1, 4);
+    let items = builder.finish();
+
+    assert_eq!(items.data_addresses(id(1)), &[(1, 0..10)]);
+    assert_eq!(items.data_addresses(id(2)), &[]);
+    assert_eq!(items.data_referrers(0, 0..10), vec![id(2), id(3)]);
+    assert_eq!(items.data_referrers(0, 5..10), vec![id(3)]);
+    assert_eq!(items.data_referrers(1, 0..10), vec![id(3)]);
+    assert_eq!(items.data_referrers(0, 4..4), vec![]);
+}
@@ -0,0 +1,96 @@
+use twiggy_ir as ir;
+
+mod support;
+
+// a -> b -> d -> e
+// a -> c -> d
+// x -> y, not reachable from the root `a`.
+fn items() -> (ir::Items, Vec<ir::Id>) {
+    let (mut items, ids) = support::graph(
+        &["a", "b", "c", "d", "e", "x", "y"],
+        &[(0, 1), (0, 2), (1, 3), (2, 3), (3, 4), (5, 6)],
+    );
+    items.compute_dominator_tree();
+    (items, ids)
+}
+
+#[test]
+fn children() {
+    let (items, ids) = items();
+    let tree = items.dominator_tree();
+    assert_eq!(tree.root(), items.meta_root());
+    assert_eq!(tree.children(tree.root()), &[ids[0]]);
+    assert_eq!(tree.children(ids[0]), &[ids[1], ids[2], ids[3]]);
+    assert_eq!(tree.children(ids[3]), &[ids[4]]);
+    assert!(tree.children(ids[4]).is_empty());
+    assert!(tree.children(ids[5]).is_empty());
+}
+
+#[test]
+fn immediate_dominator() {
+    let (items, ids) = items();
+    let tree = items.dominator_tree();
+    assert_eq!(tree.immediate_dominator(tree.root()), None);
+    assert_eq!(tree.immediate_dominator(ids[0]), Some(tree.root()));
+    assert_eq!(tree.immediate_dominator(ids[3]), Some(ids[0]));
+    assert_eq!(tree.immediate_dominator(ids[4]), Some(ids[3]));
+    assert_eq!(tree.immediate_dominator(ids[6]), None);
+}
+
+#[test]
+fn depth() {
+    let (items, ids) = items();
+    let tree = items.dominator_tree();
+    assert_eq!(tree.depth(tree.root()), Some(0));
+    assert_eq!(tree.depth(ids[0]), Some(1));
+    assert_eq!(tree.depth(ids[2]), Some(2));
+    assert_eq!(tree.depth(ids[4]), Some(3));
+    assert_eq!(tree.depth(ids[5]), None);
+    assert_eq!(tree.depth(ids[6]), None);
+}
+
+#[test]
+fn pre_order() {
+    let (items, ids) = items();
+    let tree = items.dominator_tree();
+
+    let all: Vec<_> = tree.iter().collect();
+    let mut expected = vec![tree.root()];
+    expected.extend_from_slice(&ids[..5]);
+    assert_eq!(all, expected);
+
+    let subtree: Vec<_> = tree.subtree_ids(ids[3]).collect();
+    assert_eq!(subtree, vec![ids[3], ids[4]]);
+}
+
+#[test]
+fn unreachable_items_are_not_in_the_tree() {
+    let (items, ids) = items();
+    let tree = items.dominator_tree();
+    assert!(tree.contains(ids[4]));
+    assert!(!tree.contains(ids[5]));
+    assert!(!tree.contains(ids[6]));
+    assert_eq!(tree.subtree_ids(ids[5]).count(), 0);
+    assert!(tree.iter().all(|id| id != ids[5] && id != ids[6]));
+}
+
+#[test]
+fn retained_sizes_follow_the_tree() {
+    let (mut items, ids) = items();
+    items.compute_retained_sizes();
+    assert_eq!(items.retained_size(ids[0]), 50);
+    assert_eq!(items.retained_size(ids[3]), 20);
+    assert_eq!(items.retained_size(ids[5]), 10);
+}
+
+#[test]
+fn retained_roots_add_up_to_the_whole_binary() {
+    let (mut items, ids) = items();
+    items.compute_retained_sizes();
+    let roots: Vec<_> = items.retained_roots().collect();
+    assert_eq!(roots, vec![ids[0], ids[5], ids[6]]);
+    assert_eq!(items.retained_size(ids[0]), 50);
+
+    let total: u64 = roots.iter().map(|&id| items.retained_size(id)).sum();
+    assert_eq!(total, items.size());
+}
@@ -0,0 +1,63 @@
+use twiggy_ir as ir;
+
+fn builder() -> (ir::ItemsBuilder, Vec<ir::Id>) {
+    let mut builder = ir::ItemsBuilder::new(3 * 10);
+    let mut ids = vec![];
+    for (i, name) in ["r", "a", "b"].iter().enumerate() {
+        let item = ir::Item::new(ir::Id::entry(0, i), *name, 10, ir::Code::new(name));
+        ids.push(builder.add_item(item));
+    }
+    builder.add_root_by_id(ids[0]);
+    (builder, ids)
+}
+
+#[test]
+fn add_edge_is_idempotent() {
+    let (mut builder, ids) = builder();
+    for _ in 0..500 {
+        builder.add_edge_kind(ids[0], ids[1], ir::EdgeKind::Call);
+    }
+    builder.add_edge(ids[0], ids[2]);
+    builder.add_edge(ids[0], ids[2]);
+    let mut items = builder.finish();
+    items.compute_predecessors();
+
+    assert_eq!(
+        items.neighbors(ids[0]).collect::<Vec<_>>(),
+        vec![ids[1], ids[2]]
+    );
+    assert_eq!(items.predecessors(ids[1]).count(), 1);
+}
+
+#[test]
+fn specific_kind_replaces_generic() {
+    let (mut builder, ids) = builder();
+    builder.add_edge(ids[0], ids[1]);
+    builder.add_edge_kind(ids[0], ids[1], ir::EdgeKind::Call);
+    builder.add_edge_kind(ids[0], ids[1], ir::EdgeKind::Data);
+    builder.add_edge(ids[0], ids[1]);
+    let items = builder.finish();
+
+    assert_eq!(items.neighbors(ids[0]).count(), 1);
+    assert_eq!(items.edge_kind(ids[0], ids[1]), Some(ir::EdgeKind::Call));
+}
+
+#[test]
+fn heuristic_edges_fill_up_to_max_edges() {
+    let (mut builder, ids) = builder();
+    builder.set_max_edges(Some(2));
+    builder.add_edge_kind(ids[1], ids[2], ir::EdgeKind::Heuristic);
+    builder.add_edge_kind(ids[2], ids[1], ir::EdgeKind::Heuristic);
+    builder.add_edge_kind(ids[0], ids[1], ir::EdgeKind::Call);
+    assert_eq!(builder.edge_count(), 1);
+    let items = builder.finish();
+
+    assert_eq!(items.edge_count(), 2);
+    assert_eq!(items.dropped_edges(), 1);
+    assert_eq!(items.edge_kind(ids[0], ids[1]), Some(ir::EdgeKind::Call));
+    assert_eq!(
+        items.edge_kind(ids[1], ids[2]),
+        Some(ir::EdgeKind::Heuristic)
+    );
+    assert_eq!(items.edge_kind(ids[2], ids[1]), None);
+}
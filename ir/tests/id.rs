@@ -0,0 +1,29 @@
+use twiggy_ir as ir;
+
+#[test]
+fn display() {
+    assert_eq!(ir::Id::entry(12, 345).to_string(), "s12.e345");
+    assert_eq!(ir::Id::section(12).to_string(), "s12");
+    assert_eq!(ir::Id::root().to_string(), "root");
+}
+
+#[test]
+fn round_trip() {
+    for id in &[
+        ir::Id::entry(0, 0),
+        ir::Id::entry(12, 345),
+        ir::Id::section(7),
+        ir::Id::root(),
+    ] {
+        assert_eq!(id.to_string().parse::<ir::Id>(), Ok(*id));
+    }
+}
+
+#[test]
+fn invalid() {
+    for s in &[
+        "", "12", "s", "s1.", "s1.345", "s1.e", "s-1", "s+1", "sx.e1", "s1.e2.e3",
+    ] {
+        assert!(s.parse::<ir::Id>().is_err(), "{:?} should not parse", s);
+    }
+}
@@ -0,0 +1,43 @@
+use twiggy_ir as ir;
+
+const GIB: u64 = 1 << 30;
+
+// Native binaries can be larger than 4 GiB, so none of the sizes and totals
+// may be truncated to 32 bits.
+#[test]
+fn sizes_larger_than_4_gib() {
+    let mut builder = ir::ItemsBuilder::new(6 * GIB);
+    let main = builder.add_root(ir::Item::new(
+        ir::Id::entry(0, 0),
+        "main",
+        GIB,
+        ir::Code::new("main"),
+    ));
+    let big = builder.add_item(ir::Item::new(
+        ir::Id::entry(0, 1),
+        "big",
+        3 * GIB,
+        ir::Code::new("big"),
+    ));
+    let data = builder.add_item(ir::Item::new(
+        ir::Id::entry(1, 0),
+        "data",
+        2 * GIB,
+        ir::Data::new(None),
+    ));
+    builder.add_edge(main, big);
+    builder.add_edge(big, data);
+    assert_eq!(builder.size_added(), 6 * GIB);
+
+    let mut items = builder.finish();
+    assert_eq!(items.size(), 6 * GIB);
+    assert_eq!(items[big].size(), 3 * GIB);
+    assert_eq!(items.total_size(), 6 * GIB);
+    assert_eq!(items.category_size(ir::ItemCategory::Function), 4 * GIB);
+    assert_eq!(items.summary().total_size(), 6 * GIB);
+
+    items.compute_retained_sizes();
+    assert_eq!(items.retained_size(main), 6 * GIB);
+    assert_eq!(items.retained_size(big), 5 * GIB);
+    assert_eq!(items.retained_size(items.meta_root()), 6 * GIB);
+}
@@ -0,0 +1,56 @@
+use twiggy_ir as ir;
+
+#[test]
+fn indexed_names_are_formatted_on_demand() {
+    let name = ir::Name::indexed("func", 42);
+    assert_eq!(name.as_str(), "func[42]");
+    assert_eq!(name.to_string(), "func[42]");
+    assert_eq!(name, ir::Name::from("func[42]"));
+    assert_ne!(name, ir::Name::indexed("func", 43));
+}
+
+#[test]
+fn unnamed_code_uses_its_indexed_name() {
+    let item = ir::Item::new(
+        ir::Id::entry(0, 7),
+        ir::Name::indexed("code", 7),
+        10,
+        ir::Code::unnamed(),
+    );
+    assert_eq!(item.name(), "code[7]");
+    assert_eq!(item.monomorphization_of(), None);
+
+    let mut builder = ir::ItemsBuilder::new(10);
+    let id = builder.add_item(item);
+    builder.add_root_by_id(id);
+    let items = builder.finish();
+    assert_eq!(
+        items.get_item_by_name("code[7]").map(|item| item.id()),
+        Some(id)
+    );
+}
+
+#[test]
+fn renamed_functions_are_named() {
+    let mut builder = ir::ItemsBuilder::new(10);
+    let id = builder.add_root(ir::Item::new(
+        ir::Id::entry(0, 3),
+        ir::Name::indexed("code", 3),
+        10,
+        ir::Code::unnamed(),
+    ));
+    let mut items = builder.finish();
+
+    items.rename_function(id, "_ZN4core3fmt5write17h0123456789abcdefE");
+    assert_eq!(items[id].name(), "core::fmt::write::h0123456789abcdef");
+    match items[id].kind() {
+        ir::ItemKind::Code(code) => assert!(code.is_named()),
+        _ => unreachable!(),
+    }
+    assert_eq!(
+        items
+            .get_item_by_name("core::fmt::write::h0123456789abcdef")
+            .map(|item| item.id()),
+        Some(id)
+    );
+}
@@ -0,0 +1,90 @@
+use std::borrow::Cow;
+use twiggy_ir as ir;
+
+mod support;
+
+// r -> a -> b through a call and a heuristic edge, and c -> d, which are not
+// reachable from the root `r`.
+fn items() -> (ir::Items, Vec<ir::Id>) {
+    let (mut builder, ids) =
+        support::functions(&[("r", 10), ("a", 10), ("b", 10), ("c", 10), ("d", 10)]);
+    builder.add_edge_kind(ids[0], ids[1], ir::EdgeKind::Call);
+    builder.add_edge_kind(ids[1], ids[2], ir::EdgeKind::Heuristic);
+    builder.add_edge(ids[3], ids[4]);
+    (builder.finish(), ids)
+}
+
+#[test]
+fn reachable() {
+    let (items, ids) = items();
+    assert_eq!(
+        items.reachable().iter().cloned().collect::<Vec<_>>(),
+        vec![ids[0], ids[1], ids[2], items.meta_root()]
+    );
+    assert!(items.is_reachable(ids[2]));
+    assert!(!items.is_reachable(ids[3]));
+}
+
+#[test]
+fn reachable_without_borrows_the_cached_set() {
+    let (items, ids) = items();
+    assert!(matches!(items.reachable_without(&[]), Cow::Borrowed(_)));
+
+    let reachable = items.reachable_without(&[ir::EdgeKind::Heuristic]);
+    assert!(reachable.contains(&ids[1]));
+    assert!(!reachable.contains(&ids[2]));
+    assert!(items.is_reachable(ids[2]));
+}
+
+#[test]
+fn reachable_on_several_threads() {
+    let (sequential, _) = items();
+    let (mut threaded, _) = items();
+    threaded.set_threads(4);
+    assert_eq!(threaded.reachable(), sequential.reachable());
+    assert_eq!(
+        threaded.reachable_without(&[ir::EdgeKind::Heuristic]),
+        sequential.reachable_without(&[ir::EdgeKind::Heuristic])
+    );
+}
+
+#[test]
+fn promote_to_root_discards_the_reachable_items() {
+    let (mut items, ids) = items();
+    assert!(!items.is_reachable(ids[4]));
+    assert!(items.promote_to_root(ids[3]));
+    assert!(items.is_reachable(ids[3]));
+    assert!(items.is_reachable(ids[4]));
+}
+
+#[test]
+fn reachable_from_custom_roots() {
+    let (mut items, ids) = items();
+    assert_eq!(
+        items.reachable_from(&[ids[1], ids[3]]),
+        [ids[1], ids[2], ids[3], ids[4]].iter().cloned().collect()
+    );
+    assert!(items.reachable_from(&[]).is_empty());
+
+    let sequential = items.reachable_from(&[ids[0]]);
+    items.set_threads(4);
+    assert_eq!(items.reachable_from(&[ids[0]]), sequential);
+}
+
+#[test]
+fn reachable_without_items_does_not_pass_through_them() {
+    let (mut items, ids) = items();
+    let removed = [ids[1]].iter().cloned().collect();
+    assert_eq!(
+        items.reachable_without_items(&removed),
+        [ids[0], items.meta_root()].iter().cloned().collect()
+    );
+    assert_eq!(
+        items.reachable_without_items(&Default::default()),
+        *items.reachable()
+    );
+
+    let sequential = items.reachable_without_items(&removed);
+    items.set_threads(4);
+    assert_eq!(items.reachable_without_items(&removed), sequential);
+}
@@ -0,0 +1,50 @@
+use twiggy_ir as ir;
+
+mod support;
+
+// r -> a, and b -> c, not reachable from the root `r`.
+fn items() -> (ir::Items, Vec<ir::Id>) {
+    support::graph(&["r", "a", "b", "c"], &[(0, 1), (2, 3)])
+}
+
+#[test]
+fn add_root_by_id() {
+    let (items, ids) = items();
+    assert!(items.is_root(ids[0]));
+    assert!(!items.is_root(ids[1]));
+    assert_eq!(items.roots().collect::<Vec<_>>(), vec![ids[0]]);
+    assert_eq!(
+        items.neighbors(items.meta_root()).collect::<Vec<_>>(),
+        vec![ids[0]]
+    );
+}
+
+#[test]
+fn promote_to_root() {
+    let (mut items, ids) = items();
+    assert!(items.promote_to_root(ids[2]));
+    assert!(!items.promote_to_root(ids[2]));
+    assert!(items.is_root(ids[2]));
+    assert_eq!(
+        items.neighbors(items.meta_root()).collect::<Vec<_>>(),
+        vec![ids[0], ids[2]]
+    );
+    assert_eq!(
+        items.edge_kind(items.meta_root(), ids[2]),
+        Some(ir::EdgeKind::Generic)
+    );
+    assert_eq!(items.summary().root_count(), 2);
+}
+
+#[test]
+fn promote_to_root_discards_the_dominator_tree() {
+    let (mut items, ids) = items();
+    items.compute_retained_sizes();
+    assert!(!items.dominator_tree().contains(ids[3]));
+    assert_eq!(items.retained_size(ids[2]), 10);
+
+    items.promote_to_root(ids[2]);
+    items.compute_retained_sizes();
+    assert!(items.dominator_tree().contains(ids[3]));
+    assert_eq!(items.retained_size(ids[2]), 20);
+}
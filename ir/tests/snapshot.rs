@@ -0,0 +1,103 @@
+use twiggy_ir as ir;
+
+fn items() -> ir::Items {
+    let mut builder = ir::ItemsBuilder::new(100);
+    let main = builder.add_root(ir::Item::new(
+        ir::Id::entry(0, 0),
+        "main",
+        30,
+        ir::Code::new("_ZN4core3fmt5write17h2f9d4c1c7e2d3b1aE"),
+    ));
+    let helper = builder.add_item(ir::Item::new(
+        ir::Id::entry(0, 1),
+        ir::Name::indexed("func", 1),
+        20,
+        ir::Code::unnamed(),
+    ));
+    let data = builder.add_item(ir::Item::new(
+        ir::Id::entry(1, 0),
+        "data[0]",
+        40,
+        ir::Data::new(Some("u8".to_string())),
+    ));
+    builder.add_edge_kind(main, helper, ir::EdgeKind::Call);
+    builder.add_edge_kind(helper, data, ir::EdgeKind::Data);
+    builder.set_source_location(main, "src/main.rs", Some(3));
+    builder.add_call_site_bytes(main, helper, 2);
+    builder.add_byte_range(data, 60..100);
+    builder.add_warning("something was left out".to_string());
+    builder.add_producer(ir::Producer::new("language", "Rust", "1.0"));
+    builder.finish()
+}
+
+fn snapshot(items: &ir::Items) -> Vec<u8> {
+    let mut data = vec![];
+    items.write_snapshot(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn snapshot_reads_back_into_the_same_items() {
+    let items = items();
+    let data = snapshot(&items);
+    assert!(ir::Items::is_snapshot(&data));
+    let read = ir::Items::read_snapshot(&data).unwrap();
+
+    assert_eq!(read.size(), items.size());
+    assert_eq!(
+        read.iter().collect::<Vec<_>>(),
+        items.iter().collect::<Vec<_>>()
+    );
+    for item in items.iter() {
+        assert_eq!(
+            read.edges(item.id()).collect::<Vec<_>>(),
+            items.edges(item.id()).collect::<Vec<_>>()
+        );
+        assert_eq!(read.byte_ranges(item.id()), items.byte_ranges(item.id()));
+    }
+    assert_eq!(
+        read.roots().collect::<Vec<_>>(),
+        items.roots().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        read.call_site_bytes(ir::Id::entry(0, 1))
+            .collect::<Vec<_>>(),
+        items
+            .call_site_bytes(ir::Id::entry(0, 1))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(read.warnings(), items.warnings());
+    assert_eq!(read.producers(), items.producers());
+
+    // Function names are demangled again on demand.
+    match read[ir::Id::entry(0, 0)].kind() {
+        ir::ItemKind::Code(code) => assert_eq!(
+            code.demangled(),
+            Some("core::fmt::write::h2f9d4c1c7e2d3b1a")
+        ),
+        kind => panic!("expected code, found {:?}", kind),
+    }
+}
+
+#[test]
+fn snapshot_of_another_version_is_an_error() {
+    let mut data = snapshot(&items());
+    let version = ir::SNAPSHOT_MAGIC.len();
+    data[version..version + 4].copy_from_slice(&(ir::SNAPSHOT_VERSION + 1).to_le_bytes());
+
+    let error = ir::Items::read_snapshot(&data).unwrap_err();
+    assert!(
+        error.contains(&format!("version {}", ir::SNAPSHOT_VERSION + 1)),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn truncated_snapshot_is_an_error() {
+    let data = snapshot(&items());
+    for len in [ir::SNAPSHOT_MAGIC.len() + 2, data.len() / 2, data.len() - 1] {
+        let error = ir::Items::read_snapshot(&data[..len]).unwrap_err();
+        assert!(error.starts_with("malformed twiggy snapshot"), "{}", error);
+    }
+}
@@ -0,0 +1,51 @@
+use twiggy_ir as ir;
+
+#[test]
+fn summary() {
+    let mut builder = ir::ItemsBuilder::new(100);
+    let main = builder.add_root(ir::Item::new(
+        ir::Id::entry(0, 0),
+        "main",
+        30,
+        ir::Code::new("main"),
+    ));
+    let helper = builder.add_item(ir::Item::new(
+        ir::Id::entry(0, 1),
+        "helper",
+        20,
+        ir::Code::new("helper"),
+    ));
+    let data = builder.add_item(ir::Item::new(
+        ir::Id::entry(1, 0),
+        "data[0]",
+        40,
+        ir::Data::new(None),
+    ));
+    builder.add_edge(main, helper);
+    builder.add_edge(helper, data);
+    builder.add_edge(main, data);
+
+    let items = builder.finish();
+    let summary = items.summary();
+    assert_eq!(summary.size(), 100);
+    assert_eq!(summary.total_size(), 90);
+    assert_eq!(summary.item_count(), 3);
+    assert_eq!(summary.root_count(), 1);
+    assert_eq!(summary.edge_count(), 3);
+    assert_eq!(summary.category_count(ir::ItemCategory::Function), 2);
+    assert_eq!(summary.category_size(ir::ItemCategory::Function), 50);
+    assert_eq!(summary.category_count(ir::ItemCategory::DataSegment), 1);
+    assert_eq!(summary.category_count(ir::ItemCategory::Misc), 0);
+    assert_eq!(summary.categories().len(), 2);
+}
+
+#[test]
+fn empty_summary() {
+    let items = ir::ItemsBuilder::new(8).finish();
+    let summary = items.summary();
+    assert_eq!(summary.item_count(), 0);
+    assert_eq!(summary.root_count(), 0);
+    assert_eq!(summary.edge_count(), 0);
+    assert_eq!(summary.total_size(), 0);
+    assert!(summary.categories().is_empty());
+}
@@ -0,0 +1,50 @@
+//! Small graphs of items for tests to check the IR and analyses against,
+//! shared by the tests of `twiggy-ir` and of `twiggy-analyze`.
+
+// Each test uses what it needs of these.
+#![allow(dead_code)]
+
+use twiggy_ir as ir;
+
+/// A builder of items with a function for each of the given names and sizes,
+/// the first of which is the only root, and the functions' ids, in order.
+/// The binary is only as large as the functions.
+pub fn functions(functions: &[(&str, u64)]) -> (ir::ItemsBuilder, Vec<ir::Id>) {
+    let size = functions.iter().map(|&(_, size)| size).sum();
+    let mut builder = ir::ItemsBuilder::new(size);
+    let mut ids = vec![];
+    for (i, &(name, size)) in functions.iter().enumerate() {
+        let item = ir::Item::new(ir::Id::entry(0, i), name, size, ir::Code::new(name));
+        ids.push(builder.add_item(item));
+    }
+    if let Some(&root) = ids.first() {
+        builder.add_root_by_id(root);
+    }
+    (builder, ids)
+}
+
+/// A builder of the given roots and other items. The binary is only as large
+/// as the items.
+pub fn items(roots: Vec<ir::Item>, others: Vec<ir::Item>) -> ir::ItemsBuilder {
+    let size = roots.iter().chain(&others).map(|item| item.size()).sum();
+    let mut builder = ir::ItemsBuilder::new(size);
+    for root in roots {
+        builder.add_root(root);
+    }
+    for item in others {
+        builder.add_item(item);
+    }
+    builder
+}
+
+/// Items with a function of ten bytes for each of the given names, the first
+/// of which is the only root, and an edge between the functions at each pair
+/// of indices.
+pub fn graph(names: &[&str], edges: &[(usize, usize)]) -> (ir::Items, Vec<ir::Id>) {
+    let functions: Vec<_> = names.iter().map(|&name| (name, 10)).collect();
+    let (mut builder, ids) = self::functions(&functions);
+    for &(from, to) in edges {
+        builder.add_edge(ids[from], ids[to]);
+    }
+    (builder.finish(), ids)
+}
@@ -28,6 +28,11 @@ pub enum Options {
     #[structopt(name = "paths")]
     Paths(Paths),
 
+    /// List the items that the given items reference, or are referenced by,
+    /// along with their sizes and the kinds of the references.
+    #[structopt(name = "neighbors")]
+    Neighbors(Neighbors),
+
     /// List the generic function monomorphizations that are contributing to
     /// code bloat.
     #[structopt(name = "monos")]
@@ -41,6 +46,62 @@ pub enum Options {
     /// any exports or public functions.
     #[structopt(name = "garbage")]
     Garbage(Garbage),
+
+    /// List the imports, and how much of the binary each of them costs,
+    /// including their types and the functions that only exist to call them.
+    #[structopt(name = "imports")]
+    Imports(Imports),
+
+    /// Estimate how many bytes of call instructions' function indices could be
+    /// saved by giving the most frequently called functions the smallest
+    /// indices.
+    #[structopt(name = "call-indices")]
+    CallIndices(CallIndices),
+
+    /// List the functions that call the given functions directly, and how many
+    /// bytes of each caller those calls take up.
+    #[structopt(name = "callsites")]
+    Callsites(Callsites),
+
+    /// Find functions that are duplicated across multiple versions of the
+    /// same crate.
+    #[structopt(name = "duplicates")]
+    Duplicates(Duplicates),
+
+    /// Estimate how many bytes removing a group of items together would save,
+    /// and how many of the bytes they depend on the rest of the binary shares.
+    #[structopt(name = "group-cost")]
+    GroupCost(GroupCost),
+
+    /// Summarize how large each section of a binary is, and how much of it is
+    /// code, data, debugging information and names, or anything else.
+    #[structopt(name = "sections")]
+    Sections(Sections),
+
+    /// Summarize how much of a binary each Rust crate accounts for, judging
+    /// by the leading path segment of the items' demangled names.
+    #[structopt(name = "crates")]
+    Crates(Crates),
+
+    /// Check a binary against a budget of size limits for the whole binary,
+    /// its sections, crates and items, and fail if it breaks any of them.
+    #[structopt(name = "check")]
+    Check(Check),
+
+    /// Summarize how much of a binary's code each source file accounts for,
+    /// judging by its debug info or by a source map.
+    #[structopt(name = "files")]
+    Files(Files),
+
+    /// List the strings inside the data segments of a wasm binary, largest
+    /// first, and how much of the data is strings rather than binary data.
+    #[structopt(name = "data")]
+    Data(Data),
+
+    /// Save the parsed items of a binary to a snapshot file, that `diff` and
+    /// the other sub-commands accept in place of the binary itself.
+    #[structopt(name = "snapshot")]
+    Snapshot(Snapshot),
 }
 
 /// List the top code size offenders in a binary.
@@ -48,7 +109,8 @@ pub enum Options {
 #[derive(Clone, Debug)]
 #[derive(StructOpt)]
 pub struct Top {
-    /// The path to the input binary to size profile.
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
     input: path::PathBuf,
@@ -58,6 +120,37 @@ pub struct Top {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// Leave out heuristic edges, such as those from constant addresses in code
+    /// to the data at them, once the graph has this many edges. Abort with an
+    /// error if the input has more than this many other edges.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "max-edges")]
+    max_edges: Option<u64>,
+
+    /// Print how many items and edges the input was parsed into.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "verbose")]
+    verbose: bool,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -68,6 +161,39 @@ pub struct Top {
     #[structopt(short = "f", long = "format", default_value = "text")]
     output_format: traits::OutputFormat,
 
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Also treat the items whose names match the given regular expression
+    /// as roots, for entry points that are reached from outside the binary in
+    /// ways twiggy cannot see. Exact names are matched too.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "extra-root", number_of_values = 1)]
+    extra_roots: Vec<String>,
+
+    /// Name the unnamed functions of the input after the functions with the
+    /// same body in this other build of it, such as the input to `wasm-opt`.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "map", parse(from_os_str))]
+    map: Option<path::PathBuf>,
+
+    /// The JSON source map of a wasm input, or the directory holding the one
+    /// that its `sourceMappingURL` names. Each function is given the source
+    /// file that most of its body was compiled from, for `--group-by file`.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "source-map", parse(from_os_str))]
+    source_map: Option<path::PathBuf>,
+
+    /// Also estimate how many bytes each item takes up once the binary is
+    /// gzipped, and compare the estimates with the gzipped binary.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "compressed")]
+    compressed: bool,
+
     /// The maximum number of items to display.
     #[structopt(short = "n", default_value = "4294967295")]
     max_items: u32,
@@ -79,6 +205,70 @@ pub struct Top {
     /// Sort list by retained size, rather than shallow size.
     #[structopt(long = "retained")]
     retained: bool,
+
+    /// The number of threads to use when parsing the input and computing
+    /// retained sizes. Zero means use all available cores.
+    #[structopt(long = "threads", default_value = "1")]
+    threads: u32,
+
+    /// Aggregate items by their source file, with `file`, when it is known.
+    /// Items without a known source file are still listed individually.
+    #[structopt(long = "group-by")]
+    group_by: Option<GroupBy>,
+
+    /// Only list items of the given kinds: a broad kind, `code`, `data`,
+    /// `debug` or `misc`, or a finer one, such as `function`, `data_segment`
+    /// or `export`.
+    #[structopt(long = "kind", number_of_values = 1)]
+    kinds: Vec<ir::KindFilter>,
+
+    /// Do not list items of the given kinds.
+    #[structopt(long = "exclude-kind", number_of_values = 1)]
+    excluded_kinds: Vec<ir::KindFilter>,
+
+    /// Only list items whose demangled names match the given regular
+    /// expression. Can be repeated, to list the items matching any of them.
+    #[structopt(long = "only", number_of_values = 1)]
+    only: Vec<String>,
+
+    /// Do not list items whose demangled names match the given regular
+    /// expression. Applied after `--only`.
+    #[structopt(long = "exclude", number_of_values = 1)]
+    excluded_names: Vec<String>,
+
+    /// Record the control structures of each function: how many blocks,
+    /// loops and ifs it has, how deeply they are nested, and the size of its
+    /// largest `br_table`. These are included in JSON output.
+    #[structopt(long = "control-flow")]
+    control_flow: bool,
+
+    /// Only list functions with a `br_table` of at least this many bytes.
+    /// Implies `--control-flow`.
+    #[structopt(long = "min-brtable-bytes")]
+    min_brtable_bytes: Option<u64>,
+
+    /// Split each wasm function into an item for the declarations of its
+    /// locals, named `<function> (locals)`, and one for its instructions,
+    /// named `<function> (instructions)`. The function keeps the rest of its
+    /// bytes, and references both.
+    #[structopt(long = "locals-split")]
+    locals_split: bool,
+
+    /// Below the table, summarize how many bytes the names of all imports
+    /// and exports take up.
+    #[structopt(long = "name-bytes")]
+    name_bytes: bool,
+
+    /// Below the table, summarize how many bytes the headers of all data
+    /// segments take up, rather than their payloads.
+    #[structopt(long = "data-headers")]
+    data_headers: bool,
+
+    /// In JSON output, list the items under `items`, along with an `edges`
+    /// array of every reference between them, by the items' ids. Ids are
+    /// only stable within one invocation.
+    #[structopt(long = "edges")]
+    edges: bool,
 }
 
 impl Default for Top {
@@ -89,17 +279,192 @@ impl Default for Top {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            max_edges: None,
+            #[cfg(feature = "cli")]
+            verbose: false,
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+            #[cfg(feature = "cli")]
+            extra_roots: Default::default(),
+            #[cfg(feature = "cli")]
+            map: None,
+            #[cfg(feature = "cli")]
+            source_map: None,
+            #[cfg(feature = "cli")]
+            compressed: false,
 
             max_items: 4_294_967_295,
             retaining_paths: false,
             retained: false,
+            threads: 1,
+            group_by: None,
+            kinds: Default::default(),
+            excluded_kinds: Default::default(),
+            only: Default::default(),
+            excluded_names: Default::default(),
+            control_flow: false,
+            min_brtable_bytes: None,
+            locals_split: false,
+            name_bytes: false,
+            data_headers: false,
+            edges: false,
         }
     }
 }
 
+impl Top {
+    // TODO: wasm-bindgen does not support sending Rust enums across the wasm
+    // ABI boundary yet.
+
+    /// What to aggregate items by, if anything.
+    pub fn group_by(&self) -> Option<GroupBy> {
+        self.group_by
+    }
+
+    /// Set what to aggregate items by, if anything.
+    pub fn set_group_by(&mut self, group_by: Option<GroupBy>) {
+        self.group_by = group_by;
+    }
+
+    /// The kinds of items to list. Empty means all kinds.
+    pub fn kinds(&self) -> &[ir::KindFilter] {
+        &self.kinds
+    }
+
+    /// The kinds of items not to list.
+    pub fn excluded_kinds(&self) -> &[ir::KindFilter] {
+        &self.excluded_kinds
+    }
+
+    /// Only list items of the given kind, in addition to any other kinds
+    /// already added.
+    pub fn add_kind(&mut self, kind: ir::KindFilter) {
+        self.kinds.push(kind);
+    }
+
+    /// Do not list items of the given kind.
+    pub fn add_excluded_kind(&mut self, kind: ir::KindFilter) {
+        self.excluded_kinds.push(kind);
+    }
+
+    /// Whether the items are filtered by kind at all.
+    pub fn filters_kinds(&self) -> bool {
+        !self.kinds.is_empty() || !self.excluded_kinds.is_empty()
+    }
+
+    /// Whether the given item is of a kind that should be listed.
+    pub fn includes_kind(&self, item: &ir::Item) -> bool {
+        (self.kinds.is_empty() || self.kinds.iter().any(|kind| kind.matches(item)))
+            && !self.excluded_kinds.iter().any(|kind| kind.matches(item))
+    }
+
+    /// The regular expressions that the names of listed items must match
+    /// one of. Empty means any name.
+    pub fn only(&self) -> &[String] {
+        &self.only
+    }
+
+    /// The regular expressions that the names of listed items must not
+    /// match.
+    pub fn excluded_names(&self) -> &[String] {
+        &self.excluded_names
+    }
+
+    /// Only list items whose names match the given regular expression, or
+    /// any other one already added.
+    pub fn add_only(&mut self, pattern: String) {
+        self.only.push(pattern);
+    }
+
+    /// Do not list items whose names match the given regular expression.
+    pub fn add_excluded_name(&mut self, pattern: String) {
+        self.excluded_names.push(pattern);
+    }
+
+    /// The size in bytes that a function's largest `br_table` must at least
+    /// have for the function to be listed, if any. Only functions whose
+    /// control structures were recorded when parsing can pass this filter.
+    pub fn min_brtable_bytes(&self) -> Option<u64> {
+        self.min_brtable_bytes
+    }
+
+    /// Set the size in bytes that a function's largest `br_table` must at
+    /// least have for the function to be listed.
+    pub fn set_min_brtable_bytes(&mut self, bytes: Option<u64>) {
+        self.min_brtable_bytes = bytes;
+    }
+
+    /// Whether to record the control structures of each function when
+    /// parsing, and include them in the output.
+    pub fn control_flow(&self) -> bool {
+        self.control_flow
+    }
+
+    /// Set whether to record the control structures of each function when
+    /// parsing, and include them in the output.
+    pub fn set_control_flow(&mut self, control_flow: bool) {
+        self.control_flow = control_flow;
+    }
+
+    /// Whether to split each function into an item for its locals and one
+    /// for its instructions when parsing.
+    pub fn locals_split(&self) -> bool {
+        self.locals_split
+    }
+
+    /// Set whether to split each function into an item for its locals and
+    /// one for its instructions when parsing.
+    pub fn set_locals_split(&mut self, locals_split: bool) {
+        self.locals_split = locals_split;
+    }
+
+    /// Whether to summarize how many bytes the names of all imports and
+    /// exports take up.
+    pub fn name_bytes(&self) -> bool {
+        self.name_bytes
+    }
+
+    /// Set whether to summarize how many bytes the names of all imports and
+    /// exports take up.
+    pub fn set_name_bytes(&mut self, name_bytes: bool) {
+        self.name_bytes = name_bytes;
+    }
+
+    /// Whether to summarize how many bytes the headers of all data segments
+    /// take up.
+    pub fn data_headers(&self) -> bool {
+        self.data_headers
+    }
+
+    /// Set whether to summarize how many bytes the headers of all data
+    /// segments take up.
+    pub fn set_data_headers(&mut self, data_headers: bool) {
+        self.data_headers = data_headers;
+    }
+
+    /// Whether to list the references between the listed items in JSON
+    /// output.
+    pub fn edges(&self) -> bool {
+        self.edges
+    }
+
+    /// Set whether to list the references between the listed items in JSON
+    /// output.
+    pub fn set_edges(&mut self, edges: bool) {
+        self.edges = edges;
+    }
+}
+
 #[wasm_bindgen]
 impl Top {
     /// Construct a new, default `Top`.
@@ -136,14 +501,25 @@ impl Top {
     pub fn set_retained(&mut self, do_it: bool) {
         self.retained = do_it;
     }
+
+    /// The number of threads to use when computing retained sizes.
+    pub fn threads(&self) -> u32 {
+        self.threads
+    }
+
+    /// Set the number of threads to use when computing retained sizes.
+    pub fn set_threads(&mut self, threads: u32) {
+        self.threads = threads;
+    }
 }
 
 /// Compute and display the dominator tree for a binary's call graph.
 #[wasm_bindgen]
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 #[derive(StructOpt)]
 pub struct Dominators {
-    /// The path to the input binary to size profile.
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
     input: path::PathBuf,
@@ -153,6 +529,37 @@ pub struct Dominators {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// Leave out heuristic edges, such as those from constant addresses in code
+    /// to the data at them, once the graph has this many edges. Abort with an
+    /// error if the input has more than this many other edges.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "max-edges")]
+    max_edges: Option<u64>,
+
+    /// Print how many items and edges the input was parsed into.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "verbose")]
+    verbose: bool,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -163,6 +570,20 @@ pub struct Dominators {
     #[structopt(short = "f", long = "format", default_value = "text")]
     output_format: traits::OutputFormat,
 
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Also treat the items whose names match the given regular expression
+    /// as roots, for entry points that are reached from outside the binary in
+    /// ways twiggy cannot see. Exact names are matched too.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "extra-root", number_of_values = 1)]
+    extra_roots: Vec<String>,
+
     /// The name of the function whose dominator subtree should be printed.
     items: Vec<String>,
 
@@ -174,9 +595,60 @@ pub struct Dominators {
     #[structopt(short = "r")]
     max_rows: Option<u32>,
 
+    /// Display the whole tree. Overrides -d <max_depth> and -r <max_rows>
+    #[structopt(short = "a", long = "all")]
+    all: bool,
+
     /// Whether or not `items` should be treated as regular expressions.
     #[structopt(long = "regex")]
     using_regexps: bool,
+
+    /// The number of threads to use when parsing the input and computing the
+    /// dominator tree and retained sizes. Zero means use all available cores.
+    #[structopt(long = "threads", default_value = "1")]
+    threads: u32,
+
+    /// Also select the item with the given id, such as `s3.e412`, to tell
+    /// apart items that share a name. Ids are shown in JSON output.
+    #[structopt(long = "id", number_of_values = 1)]
+    ids: Vec<ir::Id>,
+}
+
+impl Default for Dominators {
+    fn default() -> Dominators {
+        Dominators {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: None,
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            max_edges: None,
+            #[cfg(feature = "cli")]
+            verbose: false,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+            #[cfg(feature = "cli")]
+            extra_roots: Default::default(),
+
+            items: Default::default(),
+            max_depth: None,
+            max_rows: None,
+            all: false,
+            using_regexps: false,
+            threads: 1,
+            ids: Default::default(),
+        }
+    }
 }
 
 impl Dominators {
@@ -187,6 +659,17 @@ impl Dominators {
     pub fn items(&self) -> &[String] {
         &self.items
     }
+
+    /// The ids of the items to select, in addition to those selected by name.
+    pub fn ids(&self) -> &[ir::Id] {
+        &self.ids
+    }
+
+    /// Select the item with the given id, in addition to those selected by
+    /// name.
+    pub fn add_id(&mut self, id: ir::Id) {
+        self.ids.push(id);
+    }
 }
 
 #[wasm_bindgen]
@@ -196,14 +679,27 @@ impl Dominators {
         Dominators::default()
     }
 
+    /// Add an item whose dominator subtree should be printed.
+    pub fn add_item(&mut self, item: String) {
+        self.items.push(item);
+    }
+
     /// The maximum depth to print the dominators tree.
     pub fn max_depth(&self) -> u32 {
-        self.max_depth.unwrap_or(u32::MAX)
+        if self.all {
+            u32::MAX
+        } else {
+            self.max_depth.unwrap_or(u32::MAX)
+        }
     }
 
     /// The maximum number of rows, regardless of depth in the tree, to display.
     pub fn max_rows(&self) -> u32 {
-        self.max_rows.unwrap_or(u32::MAX)
+        if self.all {
+            u32::MAX
+        } else {
+            self.max_rows.unwrap_or(u32::MAX)
+        }
     }
 
     /// Whether or not `items` should be treated as regular expressions.
@@ -214,17 +710,35 @@ impl Dominators {
     /// Set the maximum depth to print the dominators tree.
     pub fn set_max_depth(&mut self, max_depth: u32) {
         self.max_depth = Some(max_depth);
+        self.all = false;
     }
 
     /// Set the maximum number of rows, regardless of depth in the tree, to display.
     pub fn set_max_rows(&mut self, max_rows: u32) {
         self.max_rows = Some(max_rows);
+        self.all = false;
+    }
+
+    /// Set whether to display the whole tree, regardless of the maximum depth
+    /// and number of rows to display.
+    pub fn set_all(&mut self, all: bool) {
+        self.all = all;
     }
 
     /// Set whether or not `items` should be treated as regular expressions.
     pub fn set_using_regexps(&mut self, using_regexps: bool) {
         self.using_regexps = using_regexps;
     }
+
+    /// The number of threads to use when computing retained sizes.
+    pub fn threads(&self) -> u32 {
+        self.threads
+    }
+
+    /// Set the number of threads to use when computing retained sizes.
+    pub fn set_threads(&mut self, threads: u32) {
+        self.threads = threads;
+    }
 }
 
 /// Find and display the call paths to a function in the given binary's call
@@ -233,7 +747,8 @@ impl Dominators {
 #[derive(Clone, Debug)]
 #[derive(StructOpt)]
 pub struct Paths {
-    /// The path to the input binary to size profile.
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
     input: path::PathBuf,
@@ -243,6 +758,37 @@ pub struct Paths {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// Leave out heuristic edges, such as those from constant addresses in code
+    /// to the data at them, once the graph has this many edges. Abort with an
+    /// error if the input has more than this many other edges.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "max-edges")]
+    max_edges: Option<u64>,
+
+    /// Print how many items and edges the input was parsed into.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "verbose")]
+    verbose: bool,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -253,6 +799,20 @@ pub struct Paths {
     #[structopt(short = "f", long = "format", default_value = "text")]
     output_format: traits::OutputFormat,
 
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Also treat the items whose names match the given regular expression
+    /// as roots, for entry points that are reached from outside the binary in
+    /// ways twiggy cannot see. Exact names are matched too.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "extra-root", number_of_values = 1)]
+    extra_roots: Vec<String>,
+
     /// The functions to find call paths to.
     functions: Vec<String>,
 
@@ -271,6 +831,15 @@ pub struct Paths {
     /// Whether or not `functions` should be treated as regular expressions.
     #[structopt(long = "regex")]
     using_regexps: bool,
+
+    /// Only follow edges of the given kinds, such as `calls`.
+    #[structopt(long = "edges", number_of_values = 1)]
+    edges: Vec<ir::EdgeKind>,
+
+    /// Also select the item with the given id, such as `s3.e412`, to tell
+    /// apart items that share a name. Ids are shown in JSON output.
+    #[structopt(long = "id", number_of_values = 1)]
+    ids: Vec<ir::Id>,
 }
 
 impl Default for Paths {
@@ -281,15 +850,31 @@ impl Default for Paths {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            max_edges: None,
+            #[cfg(feature = "cli")]
+            verbose: false,
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+            #[cfg(feature = "cli")]
+            extra_roots: Default::default(),
 
             functions: Default::default(),
             max_depth: 10,
             max_paths: 10,
             descending: false,
             using_regexps: false,
+            edges: Default::default(),
+            ids: Default::default(),
         }
     }
 }
@@ -302,6 +887,33 @@ impl Paths {
     pub fn functions(&self) -> &[String] {
         &self.functions
     }
+
+    /// The kinds of edges to follow. Empty means all kinds.
+    pub fn edges(&self) -> &[ir::EdgeKind] {
+        &self.edges
+    }
+
+    /// Follow edges of the given kind, in addition to any other kinds already
+    /// added.
+    pub fn add_edge_kind(&mut self, kind: ir::EdgeKind) {
+        self.edges.push(kind);
+    }
+
+    /// Whether an edge of the given kind should be followed.
+    pub fn follows_edge(&self, kind: ir::EdgeKind) -> bool {
+        self.edges.is_empty() || self.edges.contains(&kind)
+    }
+
+    /// The ids of the items to select, in addition to those selected by name.
+    pub fn ids(&self) -> &[ir::Id] {
+        &self.ids
+    }
+
+    /// Select the item with the given id, in addition to those selected by
+    /// name.
+    pub fn add_id(&mut self, id: ir::Id) {
+        self.ids.push(id);
+    }
 }
 
 #[wasm_bindgen]
@@ -357,26 +969,42 @@ impl Paths {
     }
 }
 
-/// List the generic function monomorphizations that are contributing to
-/// code bloat.
+/// List the items that the given items reference, or are referenced by,
+/// along with their sizes and the kinds of the references.
 #[wasm_bindgen]
 #[derive(Clone, Debug)]
 #[derive(StructOpt)]
-pub struct Monos {
-    /// The path to the input binary to size profile.
+pub struct Neighbors {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
     input: path::PathBuf,
 
-    /// The names of the generic functions whose monomorphizations
-    /// should be printed.
-    functions: Vec<String>,
-
     /// The parse mode for the input binary data.
     #[cfg(feature = "cli")]
-    #[structopt(short = "d", long = "mode", default_value = "auto")]
+    #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -387,78 +1015,298 @@ pub struct Monos {
     #[structopt(short = "f", long = "format", default_value = "text")]
     output_format: traits::OutputFormat,
 
-    /// Hide individual monomorphizations and only show the generic functions.
-    #[structopt(short = "g", long = "only-generics")]
-    only_generics: bool,
-
-    /// The maximum number of generics to list.
-    #[structopt(short = "m", long = "max-generics", default_value = "10")]
-    max_generics: u32,
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
 
-    /// The maximum number of individual monomorphizations to list for each
-    /// listed generic function.
-    #[structopt(short = "n", long = "max-monos", default_value = "10")]
-    max_monos: u32,
+    /// The items to list the neighbors of.
+    items: Vec<String>,
 
-    /// List all generics and all of their individual monomorphizations.
-    /// If combined with -g then monomorphizations are hidden.
-    /// Overrides -m <max_generics> and -n <max_monos>
-    #[structopt(short = "a", long = "all")]
-    all_generics_and_monos: bool,
+    /// Whether or not `items` should be treated as regular expressions.
+    #[structopt(long = "regex")]
+    using_regexps: bool,
 
-    /// List all generics. Overrides -m <max_generics>
-    #[structopt(long = "all-generics")]
-    all_generics: bool,
+    /// Which references to list: `outgoing`, `incoming` or `both`.
+    #[structopt(long = "direction", default_value = "outgoing")]
+    direction: ir::Direction,
 
-    /// List all individual monomorphizations for each listed generic
-    /// function. Overrides -n <max_monos>
-    #[structopt(long = "all-monos")]
-    all_monos: bool,
+    /// Only list references of the given kinds, such as `calls`.
+    #[structopt(long = "edges", number_of_values = 1)]
+    edges: Vec<ir::EdgeKind>,
 
-    /// Whether or not `names` should be treated as regular expressions.
-    #[structopt(long = "regex")]
-    using_regexps: bool,
+    /// Also select the item with the given id, such as `s3.e412`, to tell
+    /// apart items that share a name. Ids are shown in JSON output.
+    #[structopt(long = "id", number_of_values = 1)]
+    ids: Vec<ir::Id>,
 }
 
-impl Default for Monos {
-    fn default() -> Monos {
-        Monos {
+impl Default for Neighbors {
+    fn default() -> Neighbors {
+        Neighbors {
             #[cfg(feature = "cli")]
             input: Default::default(),
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
 
-            functions: Default::default(),
-
-            only_generics: false,
-            max_generics: 10,
-            max_monos: 10,
-
-            all_generics_and_monos: false,
-            all_generics: false,
-            all_monos: false,
-
+            items: Default::default(),
             using_regexps: false,
+            direction: Default::default(),
+            edges: Default::default(),
+            ids: Default::default(),
         }
     }
 }
 
-impl Monos {
-    // TODO: wasm-bindgen doesn't support sending Vec<String> across the wasm
-    // ABI boundary yet.
+impl Neighbors {
+    // TODO: wasm-bindgen does not support sending Vec<String> or Rust enums
+    // across the wasm ABI boundary yet.
 
-    /// The functions to find call paths to.
-    pub fn functions(&self) -> &[String] {
-        &self.functions
+    /// The items to list the neighbors of.
+    pub fn items(&self) -> &[String] {
+        &self.items
     }
-}
 
-#[wasm_bindgen]
-impl Monos {
+    /// Which references to list.
+    pub fn direction(&self) -> ir::Direction {
+        self.direction
+    }
+
+    /// Set which references to list.
+    pub fn set_direction(&mut self, direction: ir::Direction) {
+        self.direction = direction;
+    }
+
+    /// The kinds of references to list. Empty means all kinds.
+    pub fn edges(&self) -> &[ir::EdgeKind] {
+        &self.edges
+    }
+
+    /// List references of the given kind, in addition to any other kinds
+    /// already added.
+    pub fn add_edge_kind(&mut self, kind: ir::EdgeKind) {
+        self.edges.push(kind);
+    }
+
+    /// The ids of the items to select, in addition to those selected by name.
+    pub fn ids(&self) -> &[ir::Id] {
+        &self.ids
+    }
+
+    /// Select the item with the given id, in addition to those selected by
+    /// name.
+    pub fn add_id(&mut self, id: ir::Id) {
+        self.ids.push(id);
+    }
+}
+
+#[wasm_bindgen]
+impl Neighbors {
+    /// Construct a new, default `Neighbors`.
+    pub fn new() -> Neighbors {
+        Neighbors::default()
+    }
+
+    /// Add an item to list the neighbors of.
+    pub fn add_item(&mut self, item: String) {
+        self.items.push(item);
+    }
+
+    /// Whether or not `items` should be treated as regular expressions.
+    pub fn using_regexps(&self) -> bool {
+        self.using_regexps
+    }
+
+    /// Set whether or not `items` should be treated as regular expressions.
+    pub fn set_using_regexps(&mut self, using_regexps: bool) {
+        self.using_regexps = using_regexps;
+    }
+}
+
+/// List the generic function monomorphizations that are contributing to
+/// code bloat.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Monos {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The names of the generic functions whose monomorphizations
+    /// should be printed.
+    functions: Vec<String>,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "d", long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Hide individual monomorphizations and only show the generic functions.
+    #[structopt(short = "g", long = "only-generics")]
+    only_generics: bool,
+
+    /// The maximum number of generics to list.
+    #[structopt(short = "m", long = "max-generics", default_value = "10")]
+    max_generics: u32,
+
+    /// The maximum number of individual monomorphizations to list for each
+    /// listed generic function.
+    #[structopt(short = "n", long = "max-monos", default_value = "10")]
+    max_monos: u32,
+
+    /// List all generics and all of their individual monomorphizations.
+    /// If combined with -g then monomorphizations are hidden.
+    /// Overrides -m <max_generics> and -n <max_monos>
+    #[structopt(short = "a", long = "all")]
+    all_generics_and_monos: bool,
+
+    /// List all generics. Overrides -m <max_generics>
+    #[structopt(long = "all-generics")]
+    all_generics: bool,
+
+    /// List all individual monomorphizations for each listed generic
+    /// function. Overrides -n <max_monos>
+    #[structopt(long = "all-monos")]
+    all_monos: bool,
+
+    /// Whether or not `names` should be treated as regular expressions.
+    #[structopt(long = "regex")]
+    using_regexps: bool,
+
+    /// Bucket the generic functions by the crate that defines them, with
+    /// `crate`, and list each crate's totals and its most bloaty generic.
+    #[structopt(long = "group-by")]
+    group_by: Option<GroupBy>,
+
+    /// Whether to keep the hashes that `rustc` appends to symbol names in the
+    /// listed monomorphizations: `keep` or `strip`. Stripped, the
+    /// monomorphizations left with the same name are listed as one.
+    #[structopt(long = "disambiguators", default_value = "strip")]
+    disambiguators: Disambiguators,
+}
+
+impl Default for Monos {
+    fn default() -> Monos {
+        Monos {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            functions: Default::default(),
+
+            only_generics: false,
+            max_generics: 10,
+            max_monos: 10,
+
+            all_generics_and_monos: false,
+            all_generics: false,
+            all_monos: false,
+
+            using_regexps: false,
+            group_by: None,
+            disambiguators: Default::default(),
+        }
+    }
+}
+
+impl Monos {
+    // TODO: wasm-bindgen doesn't support sending Vec<String> across the wasm
+    // ABI boundary yet.
+
+    /// The functions to find call paths to.
+    pub fn functions(&self) -> &[String] {
+        &self.functions
+    }
+
+    /// What to bucket the generic functions by, if anything.
+    pub fn group_by(&self) -> Option<GroupBy> {
+        self.group_by
+    }
+
+    /// Set what to bucket the generic functions by, if anything.
+    pub fn set_group_by(&mut self, group_by: Option<GroupBy>) {
+        self.group_by = group_by;
+    }
+
+    /// Whether to keep the hashes in the names of monomorphizations.
+    pub fn disambiguators(&self) -> Disambiguators {
+        self.disambiguators
+    }
+
+    /// Set whether to keep the hashes in the names of monomorphizations.
+    pub fn set_disambiguators(&mut self, disambiguators: Disambiguators) {
+        self.disambiguators = disambiguators;
+    }
+}
+
+#[wasm_bindgen]
+impl Monos {
     /// Construct a new, default `Monos`.
     pub fn new() -> Monos {
         Monos::default()
@@ -519,6 +1367,27 @@ impl Monos {
             self.all_generics = true;
         }
     }
+
+    /// Set whether to list all generics and all of their individual
+    /// monomorphizations, regardless of the maximum numbers to list.
+    pub fn set_all(&mut self, all: bool) {
+        self.all_generics_and_monos = all;
+        if !all {
+            self.all_generics = false;
+            self.all_monos = false;
+        }
+    }
+
+    /// Add a generic function whose monomorphizations should be listed.
+    pub fn add_function(&mut self, function: String) {
+        self.functions.push(function);
+    }
+
+    /// Set whether or not `functions` should be treated as regular
+    /// expressions.
+    pub fn set_using_regexps(&mut self, using_regexps: bool) {
+        self.using_regexps = using_regexps;
+    }
 }
 
 /// Diff the old and new versions of a binary to see what sizes changed.
@@ -526,7 +1395,8 @@ impl Monos {
 #[derive(Clone, Debug)]
 #[derive(StructOpt)]
 pub struct Diff {
-    /// The path to the old version of the input binary.
+    /// The path to the old version of the input binary, or `-` to read it
+    /// from stdin.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
     old_input: path::PathBuf,
@@ -536,11 +1406,30 @@ pub struct Diff {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
-    /// The path to the new version of the input binary.
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The path to the new version of the input binary, or `-` to read it
+    /// from stdin.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
     new_input: path::PathBuf,
 
+    /// Name the unnamed functions of both inputs after the functions with the
+    /// same body in this other build of them, such as the input to `wasm-opt`.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "map", parse(from_os_str))]
+    map: Option<path::PathBuf>,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -551,6 +1440,25 @@ pub struct Diff {
     #[structopt(short = "f", long = "format", default_value = "text")]
     output_format: traits::OutputFormat,
 
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Also report the change in size of both binaries once gzipped, and
+    /// estimate the gzipped size of the largest changes.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "wire")]
+    wire: bool,
+
+    /// Also estimate how many bytes each changed item takes up once each
+    /// binary is gzipped, as `top --compressed` does.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "compressed")]
+    compressed: bool,
+
     /// The name of the item(s) whose diff should be printed.
     items: Vec<String>,
 
@@ -565,6 +1473,31 @@ pub struct Diff {
     /// Displays all items. Overrides -n <max_items>
     #[structopt(short = "a", long = "all")]
     all_items: bool,
+
+    /// How to match items between the two binaries: `exact` or `fuzzy`.
+    #[structopt(long = "match", default_value = "exact")]
+    name_matching: NameMatching,
+
+    /// Whether to keep the hashes that `rustc` appends to symbol names:
+    /// `keep` or `strip`. Stripped, items are matched by their names without
+    /// the hashes, as with `--match fuzzy`.
+    #[structopt(long = "disambiguators", default_value = "strip")]
+    disambiguators: Disambiguators,
+
+    /// Report items whose category or section changed between the two
+    /// binaries as moved, even if their size did not change.
+    #[structopt(long = "track-moves")]
+    track_moves: bool,
+
+    /// Report the change in size of each crate, attributing items to crates
+    /// by the leading path segment of their names.
+    #[structopt(long = "by-crate")]
+    by_crate: bool,
+
+    /// With `--by-crate`, also list the items that contributed most to each
+    /// crate's change.
+    #[structopt(long = "verbose")]
+    verbose: bool,
 }
 
 impl Default for Diff {
@@ -575,32 +1508,103 @@ impl Default for Diff {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
             new_input: Default::default(),
             #[cfg(feature = "cli")]
+            map: None,
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+            #[cfg(feature = "cli")]
+            wire: false,
+            #[cfg(feature = "cli")]
+            compressed: false,
 
             items: Default::default(),
             max_items: 20,
             using_regexps: false,
             all_items: false,
+            name_matching: Default::default(),
+            disambiguators: Default::default(),
+            track_moves: false,
+            by_crate: false,
+            verbose: false,
         }
     }
 }
 
 impl Diff {
-    // TODO: wasm-bindgen does not support sending Vec<String> across
-    // the wasm ABI boundary yet.
+    // TODO: wasm-bindgen does not support sending Vec<String> or Rust enums
+    // across the wasm ABI boundary yet.
 
     /// The items whose dominators subtree should be printed.
     pub fn items(&self) -> &[String] {
         &self.items
     }
+
+    /// How to match items between the two binaries.
+    pub fn name_matching(&self) -> NameMatching {
+        self.name_matching
+    }
+
+    /// Set how to match items between the two binaries.
+    pub fn set_name_matching(&mut self, name_matching: NameMatching) {
+        self.name_matching = name_matching;
+    }
+
+    /// Whether to keep the hashes in the names of items.
+    pub fn disambiguators(&self) -> Disambiguators {
+        self.disambiguators
+    }
+
+    /// Set whether to keep the hashes in the names of items.
+    pub fn set_disambiguators(&mut self, disambiguators: Disambiguators) {
+        self.disambiguators = disambiguators;
+    }
+
+    /// Whether to report the change in size of each crate rather than of
+    /// each item.
+    pub fn by_crate(&self) -> bool {
+        self.by_crate
+    }
+
+    /// Set whether to report the change in size of each crate rather than of
+    /// each item.
+    pub fn set_by_crate(&mut self, by_crate: bool) {
+        self.by_crate = by_crate;
+    }
+
+    /// Whether to list the items that contributed most to each crate's
+    /// change.
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// Set whether to list the items that contributed most to each crate's
+    /// change.
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
 }
 
 #[wasm_bindgen]
 impl Diff {
+    /// Construct a new, default `Diff`.
+    pub fn new() -> Diff {
+        Diff::default()
+    }
+
+    /// Add an item whose diff should be printed.
+    pub fn add_item(&mut self, item: String) {
+        self.items.push(item);
+    }
+
     /// The maximum number of items to display.
     pub fn max_items(&self) -> u32 {
         if self.all_items {
@@ -625,6 +1629,23 @@ impl Diff {
     pub fn set_using_regexps(&mut self, using_regexps: bool) {
         self.using_regexps = using_regexps;
     }
+
+    /// Set whether to display all items, regardless of the maximum number of
+    /// items to display.
+    pub fn set_all_items(&mut self, all_items: bool) {
+        self.all_items = all_items;
+    }
+
+    /// Whether to report items whose category or section changed as moved.
+    pub fn track_moves(&self) -> bool {
+        self.track_moves
+    }
+
+    /// Set whether to report items whose category or section changed as
+    /// moved.
+    pub fn set_track_moves(&mut self, track_moves: bool) {
+        self.track_moves = track_moves;
+    }
 }
 
 /// Find and display code and data that is not transitively referenced by any
@@ -633,7 +1654,8 @@ impl Diff {
 #[derive(Clone, Debug)]
 #[derive(StructOpt)]
 pub struct Garbage {
-    /// The path to the input binary to size profile.
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
     #[cfg(feature = "cli")]
     #[structopt(parse(from_os_str))]
     input: path::PathBuf,
@@ -643,6 +1665,37 @@ pub struct Garbage {
     #[structopt(long = "mode", default_value = "auto")]
     parse_mode: traits::ParseMode,
 
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// Leave out heuristic edges, such as those from constant addresses in code
+    /// to the data at them, once the graph has this many edges. Abort with an
+    /// error if the input has more than this many other edges.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "max-edges")]
+    max_edges: Option<u64>,
+
+    /// Print how many items and edges the input was parsed into.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "verbose")]
+    verbose: bool,
+
     /// The destination to write the output to. Defaults to `stdout`.
     #[cfg(feature = "cli")]
     #[structopt(short = "o", default_value = "-")]
@@ -653,6 +1706,20 @@ pub struct Garbage {
     #[structopt(short = "f", long = "format", default_value = "text")]
     output_format: traits::OutputFormat,
 
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Also treat the items whose names match the given regular expression
+    /// as roots, for entry points that are reached from outside the binary in
+    /// ways twiggy cannot see. Exact names are matched too.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "extra-root", number_of_values = 1)]
+    extra_roots: Vec<String>,
+
     /// The maximum number of items to display.
     #[structopt(short = "n", default_value = "10")]
     max_items: u32,
@@ -664,6 +1731,25 @@ pub struct Garbage {
     /// Show data segments rather than summarizing them in a single line.
     #[structopt(long = "show-data-segments")]
     show_data_segments: bool,
+
+    /// The number of threads to use when parsing the input and searching for
+    /// reachable items. Zero means use all available cores.
+    #[structopt(long = "threads", default_value = "1")]
+    threads: u32,
+
+    /// Do not follow edges of the given kinds, such as `heuristic`, when
+    /// searching for reachable items.
+    #[structopt(long = "exclude-edges", number_of_values = 1)]
+    excluded_edges: Vec<ir::EdgeKind>,
+
+    /// Only list garbage items of the given kinds: a broad kind, `code`,
+    /// `data`, `debug` or `misc`, or a finer one, such as `function`.
+    #[structopt(long = "kind", number_of_values = 1)]
+    kinds: Vec<ir::KindFilter>,
+
+    /// Do not list garbage items of the given kinds.
+    #[structopt(long = "exclude-kind", number_of_values = 1)]
+    excluded_kinds: Vec<ir::KindFilter>,
 }
 
 impl Default for Garbage {
@@ -674,17 +1760,78 @@ impl Default for Garbage {
             #[cfg(feature = "cli")]
             parse_mode: Default::default(),
             #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            max_edges: None,
+            #[cfg(feature = "cli")]
+            verbose: false,
+            #[cfg(feature = "cli")]
             output_destination: Default::default(),
             #[cfg(feature = "cli")]
             output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+            #[cfg(feature = "cli")]
+            extra_roots: Default::default(),
 
             max_items: 10,
             all_items: false,
             show_data_segments: false,
+            threads: 1,
+            excluded_edges: Default::default(),
+            kinds: Default::default(),
+            excluded_kinds: Default::default(),
         }
     }
 }
 
+impl Garbage {
+    // TODO: wasm-bindgen does not support sending Rust enums across the wasm
+    // ABI boundary yet.
+
+    /// The kinds of edges not to follow when searching for reachable items.
+    pub fn excluded_edges(&self) -> &[ir::EdgeKind] {
+        &self.excluded_edges
+    }
+
+    /// Do not follow edges of the given kind when searching for reachable
+    /// items.
+    pub fn add_excluded_edge(&mut self, kind: ir::EdgeKind) {
+        self.excluded_edges.push(kind);
+    }
+
+    /// The kinds of garbage items to list. Empty means all kinds.
+    pub fn kinds(&self) -> &[ir::KindFilter] {
+        &self.kinds
+    }
+
+    /// The kinds of garbage items not to list.
+    pub fn excluded_kinds(&self) -> &[ir::KindFilter] {
+        &self.excluded_kinds
+    }
+
+    /// Only list garbage items of the given kind, in addition to any other
+    /// kinds already added.
+    pub fn add_kind(&mut self, kind: ir::KindFilter) {
+        self.kinds.push(kind);
+    }
+
+    /// Do not list garbage items of the given kind.
+    pub fn add_excluded_kind(&mut self, kind: ir::KindFilter) {
+        self.excluded_kinds.push(kind);
+    }
+
+    /// Whether the given garbage item is of a kind that should be listed.
+    pub fn includes_kind(&self, item: &ir::Item) -> bool {
+        (self.kinds.is_empty() || self.kinds.iter().any(|kind| kind.matches(item)))
+            && !self.excluded_kinds.iter().any(|kind| kind.matches(item))
+    }
+}
+
 #[wasm_bindgen]
 impl Garbage {
     /// Construct a new, default `Garbage`
@@ -711,4 +1858,1386 @@ impl Garbage {
     pub fn show_data_segments(&self) -> bool {
         self.show_data_segments
     }
+
+    /// Set whether data segments should be shown normally or summarized in a
+    /// single line.
+    pub fn set_show_data_segments(&mut self, show: bool) {
+        self.show_data_segments = show;
+    }
+
+    /// Set whether to display all items, regardless of the maximum number of
+    /// items to display.
+    pub fn set_all_items(&mut self, all_items: bool) {
+        self.all_items = all_items;
+    }
+
+    /// The number of threads to use when searching for reachable items.
+    pub fn threads(&self) -> u32 {
+        self.threads
+    }
+
+    /// Set the number of threads to use when searching for reachable items.
+    pub fn set_threads(&mut self, threads: u32) {
+        self.threads = threads;
+    }
+}
+
+/// List the imports, and how much of the binary each of them costs, including
+/// their types and the functions that only exist to call them.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Imports {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// The maximum number of imports to display.
+    #[structopt(short = "n", default_value = "10")]
+    max_items: u32,
+
+    /// Display all imports. Overrides -n <max_items>
+    #[structopt(short = "a", long = "all")]
+    all_items: bool,
+
+    /// The largest function, in bytes, that is counted as a shim when all it
+    /// does is call an import.
+    #[structopt(long = "max-shim-size", default_value = "32")]
+    max_shim_size: u32,
+}
+
+impl Default for Imports {
+    fn default() -> Imports {
+        Imports {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            max_items: 10,
+            all_items: false,
+            max_shim_size: 32,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Imports {
+    /// Construct a new, default `Imports`.
+    pub fn new() -> Imports {
+        Imports::default()
+    }
+
+    /// The maximum number of imports to display.
+    pub fn max_items(&self) -> u32 {
+        if self.all_items {
+            u32::MAX
+        } else {
+            self.max_items
+        }
+    }
+
+    /// Set the maximum number of imports to display.
+    pub fn set_max_items(&mut self, max: u32) {
+        self.max_items = max;
+        self.all_items = false;
+    }
+
+    /// Set whether to display all imports, regardless of the maximum number
+    /// of imports to display.
+    pub fn set_all_items(&mut self, all_items: bool) {
+        self.all_items = all_items;
+    }
+
+    /// The largest function, in bytes, that is counted as a shim when all it
+    /// does is call an import.
+    pub fn max_shim_size(&self) -> u32 {
+        self.max_shim_size
+    }
+
+    /// Set the largest function, in bytes, that is counted as a shim.
+    pub fn set_max_shim_size(&mut self, size: u32) {
+        self.max_shim_size = size;
+    }
+}
+
+/// Estimate how many bytes of call instructions' function indices could be
+/// saved by giving the most frequently called functions the smallest indices.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct CallIndices {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// The maximum number of functions to display.
+    #[structopt(short = "n", default_value = "10")]
+    max_items: u32,
+
+    /// Display all functions whose call sites would shrink. Overrides -n
+    /// <max_items>
+    #[structopt(short = "a", long = "all")]
+    all_items: bool,
+}
+
+impl Default for CallIndices {
+    fn default() -> CallIndices {
+        CallIndices {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            max_items: 10,
+            all_items: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl CallIndices {
+    /// Construct a new, default `CallIndices`.
+    pub fn new() -> CallIndices {
+        CallIndices::default()
+    }
+
+    /// The maximum number of functions to display.
+    pub fn max_items(&self) -> u32 {
+        if self.all_items {
+            u32::MAX
+        } else {
+            self.max_items
+        }
+    }
+
+    /// Set the maximum number of functions to display.
+    pub fn set_max_items(&mut self, max: u32) {
+        self.max_items = max;
+        self.all_items = false;
+    }
+
+    /// Set whether to display all functions whose call sites would shrink,
+    /// regardless of the maximum number of functions to display.
+    pub fn set_all_items(&mut self, all_items: bool) {
+        self.all_items = all_items;
+    }
+}
+
+/// List the functions that call the given functions directly, and how many
+/// bytes of each caller those calls take up.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Callsites {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// The functions to list the callers of.
+    functions: Vec<String>,
+
+    /// Whether or not `functions` should be treated as regular expressions.
+    #[structopt(long = "regex")]
+    using_regexps: bool,
+
+    /// Also select the function with the given id, such as `s3.e412`, to tell
+    /// apart functions that share a name. Ids are shown in JSON output.
+    #[structopt(long = "id", number_of_values = 1)]
+    ids: Vec<ir::Id>,
+
+    /// The maximum number of callers to display for each function.
+    #[structopt(short = "n", default_value = "10")]
+    max_items: u32,
+
+    /// Display all callers. Overrides -n <max_items>
+    #[structopt(short = "a", long = "all")]
+    all_items: bool,
+}
+
+impl Default for Callsites {
+    fn default() -> Callsites {
+        Callsites {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            functions: Default::default(),
+            using_regexps: false,
+            ids: Default::default(),
+            max_items: 10,
+            all_items: false,
+        }
+    }
+}
+
+impl Callsites {
+    // TODO: wasm-bindgen does not support sending Vec<String> across the wasm
+    // ABI boundary yet.
+
+    /// The functions to list the callers of.
+    pub fn functions(&self) -> &[String] {
+        &self.functions
+    }
+
+    /// The ids of the functions to select, in addition to those selected by
+    /// name.
+    pub fn ids(&self) -> &[ir::Id] {
+        &self.ids
+    }
+
+    /// Select the function with the given id, in addition to those selected
+    /// by name.
+    pub fn add_id(&mut self, id: ir::Id) {
+        self.ids.push(id);
+    }
+}
+
+#[wasm_bindgen]
+impl Callsites {
+    /// Construct a new, default `Callsites`.
+    pub fn new() -> Callsites {
+        Callsites::default()
+    }
+
+    /// Add a function to list the callers of.
+    pub fn add_function(&mut self, function: String) {
+        self.functions.push(function);
+    }
+
+    /// Whether or not `functions` should be treated as regular expressions.
+    pub fn using_regexps(&self) -> bool {
+        self.using_regexps
+    }
+
+    /// Set whether or not `functions` should be treated as regular
+    /// expressions.
+    pub fn set_using_regexps(&mut self, using_regexps: bool) {
+        self.using_regexps = using_regexps;
+    }
+
+    /// The maximum number of callers to display for each function.
+    pub fn max_items(&self) -> u32 {
+        if self.all_items {
+            u32::MAX
+        } else {
+            self.max_items
+        }
+    }
+
+    /// Set the maximum number of callers to display for each function.
+    pub fn set_max_items(&mut self, max: u32) {
+        self.max_items = max;
+        self.all_items = false;
+    }
+
+    /// Set whether to display all callers, regardless of the maximum number
+    /// of callers to display.
+    pub fn set_all_items(&mut self, all_items: bool) {
+        self.all_items = all_items;
+    }
+}
+
+/// Find functions that are duplicated across multiple versions of the same
+/// crate.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Duplicates {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// The maximum number of crates to list.
+    #[structopt(short = "n", default_value = "10")]
+    max_items: u32,
+
+    /// The maximum number of duplicated functions to list for each crate.
+    #[structopt(short = "m", long = "max-functions", default_value = "10")]
+    max_functions: u32,
+
+    /// List all crates and all of their duplicated functions. Overrides
+    /// -n <max_items> and -m <max_functions>
+    #[structopt(short = "a", long = "all")]
+    all_items: bool,
+}
+
+impl Default for Duplicates {
+    fn default() -> Duplicates {
+        Duplicates {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            max_items: 10,
+            max_functions: 10,
+            all_items: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Duplicates {
+    /// Construct a new, default `Duplicates`.
+    pub fn new() -> Duplicates {
+        Duplicates::default()
+    }
+
+    /// The maximum number of crates to list.
+    pub fn max_items(&self) -> u32 {
+        if self.all_items {
+            u32::MAX
+        } else {
+            self.max_items
+        }
+    }
+
+    /// The maximum number of duplicated functions to list for each crate.
+    pub fn max_functions(&self) -> u32 {
+        if self.all_items {
+            u32::MAX
+        } else {
+            self.max_functions
+        }
+    }
+
+    /// Set the maximum number of crates to list.
+    pub fn set_max_items(&mut self, max: u32) {
+        self.max_items = max;
+        self.all_items = false;
+    }
+
+    /// Set the maximum number of duplicated functions to list for each crate.
+    pub fn set_max_functions(&mut self, max: u32) {
+        self.max_functions = max;
+        self.all_items = false;
+    }
+
+    /// Set whether to list all crates and all of their duplicated functions.
+    pub fn set_all_items(&mut self, all_items: bool) {
+        self.all_items = all_items;
+    }
+}
+
+/// Estimate how many bytes removing a group of items together would save,
+/// and how many of the bytes they depend on the rest of the binary shares.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct GroupCost {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Also include the items named in the given file, one name per line.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "names-file", parse(from_os_str))]
+    names_file: Option<path::PathBuf>,
+
+    /// A regular expression matching the names of the items in the group.
+    regex: Option<String>,
+
+    /// Also include the item with exactly the given name.
+    #[structopt(long = "name", number_of_values = 1)]
+    names: Vec<String>,
+}
+
+impl Default for GroupCost {
+    fn default() -> GroupCost {
+        GroupCost {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+            #[cfg(feature = "cli")]
+            names_file: None,
+
+            regex: None,
+            names: Default::default(),
+        }
+    }
+}
+
+impl GroupCost {
+    /// The regular expression matching the names of the items in the group.
+    pub fn regex(&self) -> Option<&str> {
+        self.regex.as_deref()
+    }
+
+    /// The exact names of items in the group.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The file naming more items in the group, one name per line.
+    #[cfg(feature = "cli")]
+    pub fn names_file(&self) -> Option<&path::Path> {
+        self.names_file.as_deref()
+    }
+}
+
+#[wasm_bindgen]
+impl GroupCost {
+    /// Construct a new, default `GroupCost`.
+    pub fn new() -> GroupCost {
+        GroupCost::default()
+    }
+
+    /// Set the regular expression matching the names of the items in the
+    /// group.
+    pub fn set_regex(&mut self, regex: String) {
+        self.regex = Some(regex);
+    }
+
+    /// Add the item with exactly the given name to the group.
+    pub fn add_name(&mut self, name: String) {
+        self.names.push(name);
+    }
+}
+
+/// Summarize how large each section of a binary is, and how much of it is
+/// code, data, debugging information and names, or anything else.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Sections {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// How to order the sections: `size`, largest first, or `file`, in the
+    /// order they appear in the binary.
+    #[structopt(long = "sort", default_value = "size")]
+    order: SectionOrder,
+}
+
+impl Default for Sections {
+    fn default() -> Sections {
+        Sections {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            order: Default::default(),
+        }
+    }
+}
+
+impl Sections {
+    // TODO: wasm-bindgen does not support sending Rust enums across the wasm
+    // ABI boundary yet.
+
+    /// What order to list the sections in.
+    pub fn order(&self) -> SectionOrder {
+        self.order
+    }
+
+    /// Set what order to list the sections in.
+    pub fn set_order(&mut self, order: SectionOrder) {
+        self.order = order;
+    }
+}
+
+#[wasm_bindgen]
+impl Sections {
+    /// Construct a new, default `Sections`.
+    pub fn new() -> Sections {
+        Sections::default()
+    }
+}
+
+/// Summarize how much of a binary each Rust crate accounts for, judging by the
+/// leading path segment of the items' demangled names.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Crates {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// Also treat the items whose names match the given regular expression
+    /// as roots, for entry points that are reached from outside the binary in
+    /// ways twiggy cannot see. Exact names are matched too.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "extra-root", number_of_values = 1)]
+    extra_roots: Vec<String>,
+
+    /// The maximum number of crates to list.
+    #[structopt(short = "n", default_value = "4294967295")]
+    max_items: u32,
+
+    /// How to order the crates: `shallow` or `retained`, largest first, or
+    /// `name`.
+    #[structopt(long = "sort", default_value = "shallow")]
+    order: CrateOrder,
+
+    /// The number of threads to use when parsing the input and computing
+    /// retained sizes. Zero means use all available cores.
+    #[structopt(long = "threads", default_value = "1")]
+    threads: u32,
+}
+
+impl Default for Crates {
+    fn default() -> Crates {
+        Crates {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+            #[cfg(feature = "cli")]
+            extra_roots: Default::default(),
+
+            max_items: 4_294_967_295,
+            order: Default::default(),
+            threads: 1,
+        }
+    }
+}
+
+impl Crates {
+    // TODO: wasm-bindgen does not support sending Rust enums across the wasm
+    // ABI boundary yet.
+
+    /// What order to list the crates in.
+    pub fn order(&self) -> CrateOrder {
+        self.order
+    }
+
+    /// Set what order to list the crates in.
+    pub fn set_order(&mut self, order: CrateOrder) {
+        self.order = order;
+    }
+}
+
+#[wasm_bindgen]
+impl Crates {
+    /// Construct a new, default `Crates`.
+    pub fn new() -> Crates {
+        Crates::default()
+    }
+
+    /// The maximum number of crates to list.
+    pub fn max_items(&self) -> u32 {
+        self.max_items
+    }
+
+    /// Set the maximum number of crates to list.
+    pub fn set_max_items(&mut self, n: u32) {
+        self.max_items = n;
+    }
+
+    /// The number of threads to use when computing retained sizes.
+    pub fn threads(&self) -> u32 {
+        self.threads
+    }
+
+    /// Set the number of threads to use when computing retained sizes.
+    pub fn set_threads(&mut self, threads: u32) {
+        self.threads = threads;
+    }
+}
+
+/// Check a binary against a budget of size limits for the whole binary, its
+/// sections, crates and items, and fail if it breaks any of them.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Check {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The path to the budget: a TOML file, or a JSON file if its name ends
+    /// in `.json`, with `total`, `sections`, `crates` and `items` tables of
+    /// `max-size` and `max-growth` limits.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    budget: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The path to an earlier build of the input, such as the one on the main
+    /// branch, to check the `max-growth` limits against. Those limits are not
+    /// checked without it.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "baseline", parse(from_os_str))]
+    baseline: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+}
+
+impl Default for Check {
+    fn default() -> Check {
+        Check {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            budget: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            baseline: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Check {
+    /// Construct a new, default `Check`.
+    pub fn new() -> Check {
+        Check::default()
+    }
+}
+
+/// Summarize how much of a binary's code each source file accounts for,
+/// judging by its debug info or by a source map.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Files {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The JSON source map of a wasm input, or the directory holding the one
+    /// that its `sourceMappingURL` names. Each function is given the source
+    /// file that most of its body was compiled from.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "source-map", parse(from_os_str))]
+    source_map: Option<path::PathBuf>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// The maximum number of source files to list.
+    #[structopt(short = "n", default_value = "4294967295")]
+    max_items: u32,
+}
+
+impl Default for Files {
+    fn default() -> Files {
+        Files {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            source_map: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            max_items: 4_294_967_295,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Files {
+    /// Construct a new, default `Files`.
+    pub fn new() -> Files {
+        Files::default()
+    }
+
+    /// The maximum number of source files to list.
+    pub fn max_items(&self) -> u32 {
+        self.max_items
+    }
+
+    /// Set the maximum number of source files to list.
+    pub fn set_max_items(&mut self, n: u32) {
+        self.max_items = n;
+    }
+}
+
+/// List the strings inside the data segments of a wasm binary, largest first,
+/// and how much of the data is strings rather than binary data.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Data {
+    /// The path to the input binary to size profile, or `-` to read it from
+    /// stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The destination to write the output to. Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+
+    /// The format the output should be written in.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "f", long = "format", default_value = "text")]
+    output_format: traits::OutputFormat,
+
+    /// Record how the output was produced: the twiggy version, the
+    /// sub-command and its options, the inputs' sizes and SHA-256 hashes, and
+    /// a timestamp.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "provenance")]
+    provenance: bool,
+
+    /// The maximum number of strings to list.
+    #[structopt(short = "n", default_value = "10")]
+    max_strings: u32,
+
+    /// The number of characters of each string to show, after which it is
+    /// cut short.
+    #[structopt(long = "preview", default_value = "40")]
+    preview: u32,
+
+    /// The fewest bytes of printable text that count as a string. Shorter
+    /// runs of text are counted as binary data.
+    #[structopt(long = "min-len", default_value = "4")]
+    min_len: u32,
+}
+
+impl Default for Data {
+    fn default() -> Data {
+        Data {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+            #[cfg(feature = "cli")]
+            output_format: Default::default(),
+            #[cfg(feature = "cli")]
+            provenance: false,
+
+            max_strings: 10,
+            preview: 40,
+            min_len: 4,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Data {
+    /// Construct a new, default `Data`.
+    pub fn new() -> Data {
+        Data::default()
+    }
+
+    /// The maximum number of strings to list.
+    pub fn max_strings(&self) -> u32 {
+        self.max_strings
+    }
+
+    /// The number of characters of each string to show.
+    pub fn preview(&self) -> u32 {
+        self.preview
+    }
+
+    /// The fewest bytes of printable text that count as a string.
+    pub fn min_len(&self) -> u32 {
+        self.min_len
+    }
+
+    /// Set the maximum number of strings to list.
+    pub fn set_max_strings(&mut self, n: u32) {
+        self.max_strings = n;
+    }
+
+    /// Set the number of characters of each string to show.
+    pub fn set_preview(&mut self, preview: u32) {
+        self.preview = preview;
+    }
+
+    /// Set the fewest bytes of printable text that count as a string.
+    pub fn set_min_len(&mut self, min_len: u32) {
+        self.min_len = min_len;
+    }
+}
+
+/// Save the parsed items of a binary to a snapshot file, that `diff` and the
+/// other sub-commands accept in place of the binary itself.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+#[derive(StructOpt)]
+pub struct Snapshot {
+    /// The path to the input binary to take a snapshot of, or `-` to read it
+    /// from stdin.
+    #[cfg(feature = "cli")]
+    #[structopt(parse(from_os_str))]
+    input: path::PathBuf,
+
+    /// The parse mode for the input binary data.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "mode", default_value = "auto")]
+    parse_mode: traits::ParseMode,
+
+    /// Represent each element or data section with more than this many
+    /// entries as a single aggregate item, rather than one item per entry.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "aggregate-threshold")]
+    aggregate_threshold: Option<u32>,
+
+    /// The architecture to parse out of a universal Mach-O binary, such as
+    /// `x86_64` or `arm64`. Required if it has more than one.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "arch")]
+    arch: Option<String>,
+
+    /// The dSYM of a Mach-O input: a `.dSYM` bundle or the DWARF file in it.
+    /// Functions are named, sized and given source files after its debug
+    /// info, including those that the input has no symbols for.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "dsym", parse(from_os_str))]
+    dsym: Option<path::PathBuf>,
+
+    /// The JSON source map of a wasm input, or the directory holding the one
+    /// that its `sourceMappingURL` names. Each function is given the source
+    /// file that most of its body was compiled from.
+    #[cfg(feature = "cli")]
+    #[structopt(long = "source-map", parse(from_os_str))]
+    source_map: Option<path::PathBuf>,
+
+    /// The destination to write the snapshot to, such as `old.twiggy`.
+    /// Defaults to `stdout`.
+    #[cfg(feature = "cli")]
+    #[structopt(short = "o", default_value = "-")]
+    output_destination: OutputDestination,
+}
+
+impl Default for Snapshot {
+    fn default() -> Snapshot {
+        Snapshot {
+            #[cfg(feature = "cli")]
+            input: Default::default(),
+            #[cfg(feature = "cli")]
+            parse_mode: Default::default(),
+            #[cfg(feature = "cli")]
+            aggregate_threshold: Default::default(),
+            #[cfg(feature = "cli")]
+            arch: None,
+            #[cfg(feature = "cli")]
+            dsym: None,
+            #[cfg(feature = "cli")]
+            source_map: None,
+            #[cfg(feature = "cli")]
+            output_destination: Default::default(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Snapshot {
+    /// Construct a new, default `Snapshot`.
+    pub fn new() -> Snapshot {
+        Snapshot::default()
+    }
 }
@@ -3,6 +3,7 @@
 #![deny(missing_debug_implementations)]
 
 use cfg_if::cfg_if;
+use twiggy_ir as ir;
 use twiggy_traits as traits;
 
 cfg_if! {
@@ -16,14 +17,138 @@ cfg_if! {
     }
 }
 
+use std::str::FromStr;
 use std::u32;
 
+/// What to aggregate items by, rather than listing each of them individually.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Aggregate items that are defined in the same source file.
+    File,
+
+    /// Aggregate items that are defined in the same crate.
+    Crate,
+}
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "file" => Ok(GroupBy::File),
+            "crate" => Ok(GroupBy::Crate),
+            _ => Err(anyhow::anyhow!("Unknown grouping: {}", s)),
+        }
+    }
+}
+
+/// How `diff` decides that an item in the old binary and an item in the new
+/// binary are the same item.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NameMatching {
+    /// Items match when their names are identical.
+    #[default]
+    Exact,
+
+    /// Items match when their names are identical after removing the hash
+    /// that `rustc` appends to mangled symbol names, so that items whose hash
+    /// changed between builds are still compared with each other.
+    Fuzzy,
+}
+
+impl FromStr for NameMatching {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "exact" => Ok(NameMatching::Exact),
+            "fuzzy" => Ok(NameMatching::Fuzzy),
+            _ => Err(anyhow::anyhow!("Unknown name matching: {}", s)),
+        }
+    }
+}
+
+/// Whether to keep the hash that `rustc` appends to mangled symbol names, such
+/// as the `::h0123456789abcdef` in `core::fmt::write::h0123456789abcdef`, in
+/// the names of functions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Disambiguators {
+    /// Show names as they are demangled, hash and all.
+    Keep,
+
+    /// Remove the hash, which changes whenever the code is rebuilt, so that
+    /// the same function has the same name in every build.
+    #[default]
+    Strip,
+}
+
+impl FromStr for Disambiguators {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "keep" => Ok(Disambiguators::Keep),
+            "strip" => Ok(Disambiguators::Strip),
+            _ => Err(anyhow::anyhow!("Unknown disambiguators: {}", s)),
+        }
+    }
+}
+
+/// What order `sections` lists the sections of a binary in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SectionOrder {
+    /// Largest first.
+    #[default]
+    Size,
+
+    /// In the order the sections appear in the binary.
+    File,
+}
+
+impl FromStr for SectionOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "size" => Ok(SectionOrder::Size),
+            "file" => Ok(SectionOrder::File),
+            _ => Err(anyhow::anyhow!("Unknown section order: {}", s)),
+        }
+    }
+}
+
+/// What order `crates` lists the crates in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CrateOrder {
+    /// Largest shallow size first.
+    #[default]
+    Shallow,
+
+    /// Largest retained size first.
+    Retained,
+
+    /// By name.
+    Name,
+}
+
+impl FromStr for CrateOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "shallow" => Ok(CrateOrder::Shallow),
+            "retained" => Ok(CrateOrder::Retained),
+            "name" => Ok(CrateOrder::Name),
+            _ => Err(anyhow::anyhow!("Unknown crate order: {}", s)),
+        }
+    }
+}
+
 cfg_if! {
     if #[cfg(feature = "cli")] {
         use std::fs;
         use std::io;
         use std::path;
-        use std::str::FromStr;
 
         /// Options that are common to all commands.
         pub trait CommonCliOptions {
@@ -33,11 +158,82 @@ cfg_if! {
             /// Get the input data parse mode.
             fn parse_mode(&self) -> traits::ParseMode;
 
+            /// Get the number of entries above which an element or data
+            /// section is parsed into a single aggregate item, if any.
+            fn aggregate_threshold(&self) -> Option<u32>;
+
+            /// Get the architecture to parse out of a universal Mach-O
+            /// binary, if any.
+            fn arch(&self) -> Option<&str>;
+
+            /// Get the path to the dSYM of a Mach-O input, if any.
+            fn dsym(&self) -> Option<&path::Path> {
+                None
+            }
+
+            /// Get the path to the source map of a wasm input, or to the
+            /// directory to look for the one its `sourceMappingURL` names in,
+            /// if any.
+            fn source_map(&self) -> Option<&path::Path> {
+                None
+            }
+
             /// Get the output destination.
             fn output_destination(&self) -> &OutputDestination;
 
             /// Get the output format.
             fn output_format(&self) -> traits::OutputFormat;
+
+            /// Get whether to record how the output was produced along with
+            /// it.
+            fn provenance(&self) -> bool;
+
+            /// Get the patterns naming items that should be treated as extra
+            /// roots. Empty for commands that do not depend on reachability.
+            fn extra_roots(&self) -> &[String] {
+                &[]
+            }
+
+            /// Get the number of threads to parse the input with. Zero means
+            /// use all available cores.
+            fn threads(&self) -> u32 {
+                1
+            }
+
+            /// Get whether to record the control structures of each function
+            /// body while parsing the input.
+            fn control_flow(&self) -> bool {
+                false
+            }
+
+            /// Get whether to split each function into an item for its
+            /// locals and one for its instructions while parsing the input.
+            fn locals_split(&self) -> bool {
+                false
+            }
+
+            /// Get the path to another build of the input, to name the
+            /// input's unnamed functions after, if any.
+            fn map(&self) -> Option<&path::Path> {
+                None
+            }
+
+            /// Get whether to record how many bytes each function's calls to
+            /// each other function take up while parsing the input.
+            fn call_site_bytes(&self) -> bool {
+                false
+            }
+
+            /// Get the number of edges between the input's items above which
+            /// to give up, if any.
+            fn max_edges(&self) -> Option<u64> {
+                None
+            }
+
+            /// Get whether to print statistics about the parsed input.
+            fn verbose(&self) -> bool {
+                false
+            }
         }
 
         impl CommonCliOptions for Options {
@@ -46,9 +242,21 @@ cfg_if! {
                     Options::Top(ref top) => top.input(),
                     Options::Dominators(ref doms) => doms.input(),
                     Options::Paths(ref paths) => paths.input(),
+                    Options::Neighbors(ref neighbors) => neighbors.input(),
                     Options::Monos(ref monos) => monos.input(),
                     Options::Diff(ref diff) => diff.input(),
                     Options::Garbage(ref garbo) => garbo.input(),
+                    Options::Imports(ref imports) => imports.input(),
+                    Options::CallIndices(ref calls) => calls.input(),
+                    Options::Callsites(ref callsites) => callsites.input(),
+                    Options::Duplicates(ref dupes) => dupes.input(),
+                    Options::GroupCost(ref group) => group.input(),
+                    Options::Sections(ref sections) => sections.input(),
+                    Options::Crates(ref crates) => crates.input(),
+                    Options::Check(ref check) => check.input(),
+                    Options::Files(ref files) => files.input(),
+                    Options::Data(ref data) => data.input(),
+                    Options::Snapshot(ref snapshot) => snapshot.input(),
                 }
             }
 
@@ -57,9 +265,90 @@ cfg_if! {
                     Options::Top(ref top) => top.parse_mode(),
                     Options::Dominators(ref doms) => doms.parse_mode(),
                     Options::Paths(ref paths) => paths.parse_mode(),
+                    Options::Neighbors(ref neighbors) => neighbors.parse_mode(),
                     Options::Monos(ref monos) => monos.parse_mode(),
                     Options::Diff(ref diff) => diff.parse_mode(),
                     Options::Garbage(ref garbo) => garbo.parse_mode(),
+                    Options::Imports(ref imports) => imports.parse_mode(),
+                    Options::CallIndices(ref calls) => calls.parse_mode(),
+                    Options::Callsites(ref callsites) => callsites.parse_mode(),
+                    Options::Duplicates(ref dupes) => dupes.parse_mode(),
+                    Options::GroupCost(ref group) => group.parse_mode(),
+                    Options::Sections(ref sections) => sections.parse_mode(),
+                    Options::Crates(ref crates) => crates.parse_mode(),
+                    Options::Check(ref check) => check.parse_mode(),
+                    Options::Files(ref files) => files.parse_mode(),
+                    Options::Data(ref data) => data.parse_mode(),
+                    Options::Snapshot(ref snapshot) => snapshot.parse_mode(),
+                }
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                match *self {
+                    Options::Top(ref top) => top.aggregate_threshold(),
+                    Options::Dominators(ref doms) => doms.aggregate_threshold(),
+                    Options::Paths(ref paths) => paths.aggregate_threshold(),
+                    Options::Neighbors(ref neighbors) => neighbors.aggregate_threshold(),
+                    Options::Monos(ref monos) => monos.aggregate_threshold(),
+                    Options::Diff(ref diff) => diff.aggregate_threshold(),
+                    Options::Garbage(ref garbo) => garbo.aggregate_threshold(),
+                    Options::Imports(ref imports) => imports.aggregate_threshold(),
+                    Options::CallIndices(ref calls) => calls.aggregate_threshold(),
+                    Options::Callsites(ref callsites) => callsites.aggregate_threshold(),
+                    Options::Duplicates(ref dupes) => dupes.aggregate_threshold(),
+                    Options::GroupCost(ref group) => group.aggregate_threshold(),
+                    Options::Sections(ref sections) => sections.aggregate_threshold(),
+                    Options::Crates(ref crates) => crates.aggregate_threshold(),
+                    Options::Check(ref check) => check.aggregate_threshold(),
+                    Options::Files(ref files) => files.aggregate_threshold(),
+                    Options::Data(ref data) => data.aggregate_threshold(),
+                    Options::Snapshot(ref snapshot) => snapshot.aggregate_threshold(),
+                }
+            }
+
+            fn arch(&self) -> Option<&str> {
+                match *self {
+                    Options::Top(ref top) => top.arch(),
+                    Options::Dominators(ref doms) => doms.arch(),
+                    Options::Paths(ref paths) => paths.arch(),
+                    Options::Neighbors(ref neighbors) => neighbors.arch(),
+                    Options::Monos(ref monos) => monos.arch(),
+                    Options::Diff(ref diff) => diff.arch(),
+                    Options::Garbage(ref garbo) => garbo.arch(),
+                    Options::Imports(ref imports) => imports.arch(),
+                    Options::CallIndices(ref calls) => calls.arch(),
+                    Options::Callsites(ref callsites) => callsites.arch(),
+                    Options::Duplicates(ref dupes) => dupes.arch(),
+                    Options::GroupCost(ref group) => group.arch(),
+                    Options::Sections(ref sections) => sections.arch(),
+                    Options::Crates(ref crates) => crates.arch(),
+                    Options::Check(ref check) => check.arch(),
+                    Options::Files(ref files) => files.arch(),
+                    Options::Data(ref data) => data.arch(),
+                    Options::Snapshot(ref snapshot) => snapshot.arch(),
+                }
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                match *self {
+                    Options::Top(ref top) => top.dsym(),
+                    Options::Dominators(ref doms) => doms.dsym(),
+                    Options::Paths(ref paths) => paths.dsym(),
+                    Options::Neighbors(ref neighbors) => neighbors.dsym(),
+                    Options::Monos(ref monos) => monos.dsym(),
+                    Options::Garbage(ref garbo) => garbo.dsym(),
+                    Options::Imports(ref imports) => imports.dsym(),
+                    Options::CallIndices(ref calls) => calls.dsym(),
+                    Options::Callsites(ref callsites) => callsites.dsym(),
+                    Options::Duplicates(ref dupes) => dupes.dsym(),
+                    Options::GroupCost(ref group) => group.dsym(),
+                    Options::Sections(ref sections) => sections.dsym(),
+                    Options::Crates(ref crates) => crates.dsym(),
+                    Options::Check(ref check) => check.dsym(),
+                    Options::Files(ref files) => files.dsym(),
+                    Options::Data(ref data) => data.dsym(),
+                    Options::Snapshot(ref snapshot) => snapshot.dsym(),
+                    Options::Diff(_) => None,
                 }
             }
 
@@ -68,9 +357,21 @@ cfg_if! {
                     Options::Top(ref top) => top.output_destination(),
                     Options::Dominators(ref doms) => doms.output_destination(),
                     Options::Paths(ref paths) => paths.output_destination(),
+                    Options::Neighbors(ref neighbors) => neighbors.output_destination(),
                     Options::Monos(ref monos) => monos.output_destination(),
                     Options::Diff(ref diff) => diff.output_destination(),
                     Options::Garbage(ref garbo) => garbo.output_destination(),
+                    Options::Imports(ref imports) => imports.output_destination(),
+                    Options::CallIndices(ref calls) => calls.output_destination(),
+                    Options::Callsites(ref callsites) => callsites.output_destination(),
+                    Options::Duplicates(ref dupes) => dupes.output_destination(),
+                    Options::GroupCost(ref group) => group.output_destination(),
+                    Options::Sections(ref sections) => sections.output_destination(),
+                    Options::Crates(ref crates) => crates.output_destination(),
+                    Options::Check(ref check) => check.output_destination(),
+                    Options::Files(ref files) => files.output_destination(),
+                    Options::Data(ref data) => data.output_destination(),
+                    Options::Snapshot(ref snapshot) => snapshot.output_destination(),
                 }
             }
 
@@ -79,9 +380,149 @@ cfg_if! {
                     Options::Top(ref top) => top.output_format(),
                     Options::Dominators(ref doms) => doms.output_format(),
                     Options::Paths(ref paths) => paths.output_format(),
+                    Options::Neighbors(ref neighbors) => neighbors.output_format(),
                     Options::Monos(ref monos) => monos.output_format(),
                     Options::Diff(ref diff) => diff.output_format(),
                     Options::Garbage(ref garbo) => garbo.output_format(),
+                    Options::Imports(ref imports) => imports.output_format(),
+                    Options::CallIndices(ref calls) => calls.output_format(),
+                    Options::Callsites(ref callsites) => callsites.output_format(),
+                    Options::Duplicates(ref dupes) => dupes.output_format(),
+                    Options::GroupCost(ref group) => group.output_format(),
+                    Options::Sections(ref sections) => sections.output_format(),
+                    Options::Crates(ref crates) => crates.output_format(),
+                    Options::Check(ref check) => check.output_format(),
+                    Options::Files(ref files) => files.output_format(),
+                    Options::Data(ref data) => data.output_format(),
+                    Options::Snapshot(ref snapshot) => snapshot.output_format(),
+                }
+            }
+
+            fn provenance(&self) -> bool {
+                match *self {
+                    Options::Top(ref top) => top.provenance(),
+                    Options::Dominators(ref doms) => doms.provenance(),
+                    Options::Paths(ref paths) => paths.provenance(),
+                    Options::Neighbors(ref neighbors) => neighbors.provenance(),
+                    Options::Monos(ref monos) => monos.provenance(),
+                    Options::Diff(ref diff) => diff.provenance(),
+                    Options::Garbage(ref garbo) => garbo.provenance(),
+                    Options::Imports(ref imports) => imports.provenance(),
+                    Options::CallIndices(ref calls) => calls.provenance(),
+                    Options::Callsites(ref callsites) => callsites.provenance(),
+                    Options::Duplicates(ref dupes) => dupes.provenance(),
+                    Options::GroupCost(ref group) => group.provenance(),
+                    Options::Sections(ref sections) => sections.provenance(),
+                    Options::Crates(ref crates) => crates.provenance(),
+                    Options::Check(ref check) => check.provenance(),
+                    Options::Files(ref files) => files.provenance(),
+                    Options::Data(ref data) => data.provenance(),
+                    Options::Snapshot(ref snapshot) => snapshot.provenance(),
+                }
+            }
+
+            fn extra_roots(&self) -> &[String] {
+                match *self {
+                    Options::Top(ref top) => top.extra_roots(),
+                    Options::Dominators(ref doms) => doms.extra_roots(),
+                    Options::Paths(ref paths) => paths.extra_roots(),
+                    Options::Neighbors(ref neighbors) => neighbors.extra_roots(),
+                    Options::Monos(ref monos) => monos.extra_roots(),
+                    Options::Diff(ref diff) => diff.extra_roots(),
+                    Options::Garbage(ref garbo) => garbo.extra_roots(),
+                    Options::Imports(ref imports) => imports.extra_roots(),
+                    Options::CallIndices(ref calls) => calls.extra_roots(),
+                    Options::Callsites(ref callsites) => callsites.extra_roots(),
+                    Options::Duplicates(ref dupes) => dupes.extra_roots(),
+                    Options::GroupCost(ref group) => group.extra_roots(),
+                    Options::Sections(ref sections) => sections.extra_roots(),
+                    Options::Crates(ref crates) => crates.extra_roots(),
+                    Options::Check(ref check) => check.extra_roots(),
+                    Options::Files(ref files) => files.extra_roots(),
+                    Options::Data(ref data) => data.extra_roots(),
+                    Options::Snapshot(ref snapshot) => snapshot.extra_roots(),
+                }
+            }
+
+            fn threads(&self) -> u32 {
+                match *self {
+                    Options::Top(ref top) => CommonCliOptions::threads(top),
+                    Options::Dominators(ref doms) => CommonCliOptions::threads(doms),
+                    Options::Paths(ref paths) => CommonCliOptions::threads(paths),
+                    Options::Neighbors(ref neighbors) => CommonCliOptions::threads(neighbors),
+                    Options::Monos(ref monos) => CommonCliOptions::threads(monos),
+                    Options::Diff(ref diff) => CommonCliOptions::threads(diff),
+                    Options::Garbage(ref garbo) => CommonCliOptions::threads(garbo),
+                    Options::Imports(ref imports) => CommonCliOptions::threads(imports),
+                    Options::CallIndices(ref calls) => CommonCliOptions::threads(calls),
+                    Options::Callsites(ref callsites) => CommonCliOptions::threads(callsites),
+                    Options::Duplicates(ref dupes) => CommonCliOptions::threads(dupes),
+                    Options::GroupCost(ref group) => CommonCliOptions::threads(group),
+                    Options::Sections(ref sections) => CommonCliOptions::threads(sections),
+                    Options::Crates(ref crates) => CommonCliOptions::threads(crates),
+                    Options::Check(ref check) => CommonCliOptions::threads(check),
+                    Options::Files(ref files) => CommonCliOptions::threads(files),
+                    Options::Data(ref data) => CommonCliOptions::threads(data),
+                    Options::Snapshot(ref snapshot) => CommonCliOptions::threads(snapshot),
+                }
+            }
+
+            fn control_flow(&self) -> bool {
+                match *self {
+                    Options::Top(ref top) => CommonCliOptions::control_flow(top),
+                    _ => false,
+                }
+            }
+
+            fn locals_split(&self) -> bool {
+                match *self {
+                    Options::Top(ref top) => CommonCliOptions::locals_split(top),
+                    _ => false,
+                }
+            }
+
+            fn source_map(&self) -> Option<&path::Path> {
+                match *self {
+                    Options::Top(ref top) => top.source_map(),
+                    Options::Files(ref files) => files.source_map(),
+                    Options::Data(ref data) => data.source_map(),
+                    Options::Snapshot(ref snapshot) => snapshot.source_map(),
+                    _ => None,
+                }
+            }
+
+            fn map(&self) -> Option<&path::Path> {
+                match *self {
+                    Options::Top(ref top) => top.map(),
+                    Options::Diff(ref diff) => diff.map(),
+                    _ => None,
+                }
+            }
+
+            fn call_site_bytes(&self) -> bool {
+                match *self {
+                    Options::Callsites(ref callsites) => callsites.call_site_bytes(),
+                    _ => false,
+                }
+            }
+
+            fn max_edges(&self) -> Option<u64> {
+                match *self {
+                    Options::Top(ref top) => top.max_edges(),
+                    Options::Dominators(ref doms) => doms.max_edges(),
+                    Options::Paths(ref paths) => paths.max_edges(),
+                    Options::Garbage(ref garbo) => garbo.max_edges(),
+                    _ => None,
+                }
+            }
+
+            fn verbose(&self) -> bool {
+                match *self {
+                    Options::Top(ref top) => top.verbose(),
+                    Options::Dominators(ref doms) => doms.verbose(),
+                    Options::Paths(ref paths) => paths.verbose(),
+                    Options::Garbage(ref garbo) => garbo.verbose(),
+                    _ => false,
                 }
             }
         }
@@ -95,6 +536,22 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn source_map(&self) -> Option<&path::Path> {
+                self.source_map.as_deref()
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -102,6 +559,38 @@ cfg_if! {
             fn output_format(&self) -> traits::OutputFormat {
                 self.output_format
             }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+
+            fn extra_roots(&self) -> &[String] {
+                &self.extra_roots
+            }
+
+            fn threads(&self) -> u32 {
+                Top::threads(self)
+            }
+
+            fn control_flow(&self) -> bool {
+                Top::control_flow(self) || self.min_brtable_bytes().is_some()
+            }
+
+            fn locals_split(&self) -> bool {
+                Top::locals_split(self)
+            }
+
+            fn map(&self) -> Option<&path::Path> {
+                self.map.as_deref()
+            }
+
+            fn max_edges(&self) -> Option<u64> {
+                self.max_edges
+            }
+
+            fn verbose(&self) -> bool {
+                self.verbose
+            }
         }
 
         impl CommonCliOptions for Dominators {
@@ -113,6 +602,18 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -120,6 +621,26 @@ cfg_if! {
             fn output_format(&self) -> traits::OutputFormat {
                 self.output_format
             }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+
+            fn extra_roots(&self) -> &[String] {
+                &self.extra_roots
+            }
+
+            fn threads(&self) -> u32 {
+                Dominators::threads(self)
+            }
+
+            fn max_edges(&self) -> Option<u64> {
+                self.max_edges
+            }
+
+            fn verbose(&self) -> bool {
+                self.verbose
+            }
         }
 
         impl CommonCliOptions for Paths {
@@ -131,6 +652,64 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+
+            fn extra_roots(&self) -> &[String] {
+                &self.extra_roots
+            }
+
+            fn max_edges(&self) -> Option<u64> {
+                self.max_edges
+            }
+
+            fn verbose(&self) -> bool {
+                self.verbose
+            }
+        }
+
+        impl CommonCliOptions for Neighbors {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -138,6 +717,10 @@ cfg_if! {
             fn output_format(&self) -> traits::OutputFormat {
                 self.output_format
             }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
         }
 
         impl CommonCliOptions for Monos {
@@ -149,6 +732,18 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -156,6 +751,10 @@ cfg_if! {
             fn output_format(&self) -> traits::OutputFormat {
                 self.output_format
             }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
         }
 
         impl CommonCliOptions for Diff {
@@ -167,6 +766,14 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -174,6 +781,21 @@ cfg_if! {
             fn output_format(&self) -> traits::OutputFormat {
                 self.output_format
             }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+
+            fn map(&self) -> Option<&path::Path> {
+                self.map.as_deref()
+            }
+        }
+
+        impl Top {
+            /// Whether to also estimate each item's gzipped size.
+            pub fn compressed(&self) -> bool {
+                self.compressed
+            }
         }
 
         impl Diff {
@@ -181,6 +803,17 @@ cfg_if! {
             pub fn new_input(&self) -> &path::Path {
                 &self.new_input
             }
+
+            /// Whether to also report the change in gzipped size.
+            pub fn wire(&self) -> bool {
+                self.wire
+            }
+
+            /// Whether to also estimate each changed item's gzipped size in
+            /// both binaries.
+            pub fn compressed(&self) -> bool {
+                self.compressed
+            }
         }
 
         impl CommonCliOptions for Garbage {
@@ -192,6 +825,18 @@ cfg_if! {
                 self.parse_mode
             }
 
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
             fn output_destination(&self) -> &OutputDestination {
                 &self.output_destination
             }
@@ -199,6 +844,428 @@ cfg_if! {
             fn output_format(&self) -> traits::OutputFormat {
                 self.output_format
             }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+
+            fn extra_roots(&self) -> &[String] {
+                &self.extra_roots
+            }
+
+            fn threads(&self) -> u32 {
+                Garbage::threads(self)
+            }
+
+            fn max_edges(&self) -> Option<u64> {
+                self.max_edges
+            }
+
+            fn verbose(&self) -> bool {
+                self.verbose
+            }
+        }
+
+        impl CommonCliOptions for Imports {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+        }
+
+        impl CommonCliOptions for CallIndices {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+        }
+
+        impl CommonCliOptions for Callsites {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+
+            fn call_site_bytes(&self) -> bool {
+                true
+            }
+        }
+
+        impl CommonCliOptions for GroupCost {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+        }
+
+        impl CommonCliOptions for Sections {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+        }
+
+        impl CommonCliOptions for Crates {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+
+            fn extra_roots(&self) -> &[String] {
+                &self.extra_roots
+            }
+
+            fn threads(&self) -> u32 {
+                Crates::threads(self)
+            }
+        }
+
+        impl CommonCliOptions for Check {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+        }
+
+        impl CommonCliOptions for Files {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn source_map(&self) -> Option<&path::Path> {
+                self.source_map.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+        }
+
+        impl CommonCliOptions for Data {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
+        }
+
+        impl CommonCliOptions for Snapshot {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn source_map(&self) -> Option<&path::Path> {
+                self.source_map.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                traits::OutputFormat::default()
+            }
+
+            fn provenance(&self) -> bool {
+                false
+            }
+        }
+
+        impl Check {
+            /// The path to the budget to check the input against.
+            pub fn budget(&self) -> &path::Path {
+                &self.budget
+            }
+
+            /// The path to the earlier build to check growth against, if any.
+            pub fn baseline(&self) -> Option<&path::Path> {
+                self.baseline.as_deref()
+            }
+        }
+
+        impl CommonCliOptions for Duplicates {
+            fn input(&self) -> &path::Path {
+                &self.input
+            }
+
+            fn parse_mode(&self) -> traits::ParseMode {
+                self.parse_mode
+            }
+
+            fn aggregate_threshold(&self) -> Option<u32> {
+                self.aggregate_threshold
+            }
+
+            fn arch(&self) -> Option<&str> {
+                self.arch.as_deref()
+            }
+
+            fn dsym(&self) -> Option<&path::Path> {
+                self.dsym.as_deref()
+            }
+
+            fn output_destination(&self) -> &OutputDestination {
+                &self.output_destination
+            }
+
+            fn output_format(&self) -> traits::OutputFormat {
+                self.output_format
+            }
+
+            fn provenance(&self) -> bool {
+                self.provenance
+            }
         }
 
         /// Where to output results.
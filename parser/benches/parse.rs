@@ -0,0 +1,103 @@
+//! Benchmark for parsing a large wasm binary.
+//!
+//! Run with `cargo bench -p twiggy-parser`. Each iteration parses the binary
+//! from scratch; the minimum and mean wall-clock times are reported so that
+//! regressions in the parser's hot paths are easy to spot. The binary is
+//! parsed sequentially and with all available threads. The number of heap
+//! allocations made by a single sequential parse is reported as well, and so
+//! is the time it takes to only decode the binary's sections, which parsing
+//! does once and shares between its item and edge passes.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Counts allocations so that parsing's allocation behavior can be compared
+/// without an external heap profiler.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const FIXTURE: &str = "../twiggy/tests/all/fixtures/mono.wasm";
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURE);
+    let data = fs::read(&path).expect("should read the benchmark fixture");
+
+    // Warm up the allocator and caches before measuring.
+    twiggy_parser::parse(&data).expect("should parse the benchmark fixture");
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let items =
+        twiggy_parser::parse_with_threads(&data, 1).expect("should parse the benchmark fixture");
+    println!(
+        "parse {}: {} allocations, {} bytes allocated",
+        FIXTURE,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    );
+    drop(items);
+
+    let (min, mean) = time(|| {
+        for payload in wasmparser::Parser::new(0).parse_all(&data) {
+            payload.expect("should decode the benchmark fixture");
+        }
+    });
+    println!(
+        "decode sections of {}: min {:?}, mean {:?} over {} iterations",
+        FIXTURE, min, mean, ITERATIONS
+    );
+
+    for threads in [1, 0] {
+        let (min, mean) = time(|| {
+            twiggy_parser::parse_with_threads(&data, threads)
+                .expect("should parse the benchmark fixture")
+        });
+        println!(
+            "parse {} ({} bytes, --threads {}): min {:?}, mean {:?} over {} iterations",
+            FIXTURE,
+            data.len(),
+            threads,
+            min,
+            mean,
+            ITERATIONS
+        );
+    }
+}
+
+/// The minimum and mean wall-clock times of running `f` `ITERATIONS` times,
+/// not counting dropping what it returns.
+fn time<T>(mut f: impl FnMut() -> T) -> (Duration, Duration) {
+    let mut min = Duration::MAX;
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        drop(result);
+
+        min = min.min(elapsed);
+        total += elapsed;
+    }
+    (min, total / ITERATIONS)
+}
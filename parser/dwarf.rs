@@ -0,0 +1,687 @@
+//! Reading the compilation units of DWARF debug info, and the functions
+//! defined in each.
+//!
+//! Only as much of the format is read as it takes to find those: the units of
+//! `.debug_info` and `.debug_line`, and the name and addresses of each
+//! function in a unit. This is shared by the wasm parser, which splits up a
+//! module's own DWARF sections, and the object parser, which attributes the
+//! functions of a Mach-O binary with the DWARF of its dSYM, and those of a PE
+//! binary with the DWARF that MinGW embeds in it.
+
+use foldhash::{HashMap, HashMapExt};
+#[cfg(feature = "dwarf")]
+use object::{Object, ObjectSection};
+use std::convert::TryFrom;
+use std::ops::Range;
+#[cfg(feature = "dwarf")]
+use twiggy_traits as traits;
+
+const DW_TAG_CLASS_TYPE: u64 = 0x02;
+const DW_TAG_ENUMERATION_TYPE: u64 = 0x04;
+const DW_TAG_STRUCTURE_TYPE: u64 = 0x13;
+const DW_TAG_UNION_TYPE: u64 = 0x17;
+const DW_TAG_SUBPROGRAM: u64 = 0x2e;
+const DW_TAG_NAMESPACE: u64 = 0x39;
+
+const DW_AT_NAME: u64 = 0x03;
+const DW_AT_STMT_LIST: u64 = 0x10;
+const DW_AT_LOW_PC: u64 = 0x11;
+const DW_AT_HIGH_PC: u64 = 0x12;
+const DW_AT_ABSTRACT_ORIGIN: u64 = 0x31;
+const DW_AT_SPECIFICATION: u64 = 0x47;
+const DW_AT_LINKAGE_NAME: u64 = 0x6e;
+const DW_AT_STR_OFFSETS_BASE: u64 = 0x72;
+const DW_AT_ADDR_BASE: u64 = 0x73;
+const DW_AT_MIPS_LINKAGE_NAME: u64 = 0x2007;
+const DW_AT_GNU_ADDR_BASE: u64 = 0x2133;
+
+const DW_FORM_IMPLICIT_CONST: u64 = 0x21;
+
+/// The bytes of the DWARF sections that reading `.debug_info` takes, of those
+/// that the binary has.
+#[derive(Default)]
+pub(crate) struct Sections<'a> {
+    pub(crate) info: Option<&'a [u8]>,
+    pub(crate) abbrev: Option<&'a [u8]>,
+    pub(crate) str: Option<&'a [u8]>,
+    pub(crate) line_str: Option<&'a [u8]>,
+    pub(crate) str_offsets: Option<&'a [u8]>,
+    pub(crate) addr: Option<&'a [u8]>,
+}
+
+/// A unit of `.debug_info`.
+pub(crate) struct Unit {
+    /// Where the unit is in `.debug_info`, header included.
+    pub(crate) range: Range<usize>,
+    /// The name of the unit's root DIE, which is usually its source file.
+    pub(crate) name: Option<String>,
+    /// The offset of the unit's line program in `.debug_line`.
+    pub(crate) stmt_list: Option<u64>,
+    /// The functions defined in the unit, in the order of their DIEs.
+    pub(crate) functions: Vec<Function>,
+}
+
+/// A function defined in a unit.
+pub(crate) struct Function {
+    /// Where the function's DIE and its children are in `.debug_info`.
+    pub(crate) range: Range<usize>,
+    /// The function's name, qualified by its namespaces and types, or its
+    /// linkage name if it has no name of its own.
+    pub(crate) name: Option<String>,
+    pub(crate) low_pc: u64,
+    // Only the object parser sizes functions by their high PC.
+    #[cfg_attr(not(feature = "dwarf"), allow(dead_code))]
+    pub(crate) high_pc: Option<u64>,
+}
+
+/// A function whose name may be left to the DIE that declares it.
+struct Defined {
+    start: usize,
+    end: usize,
+    name: Option<String>,
+    // The offset in the unit of the DIE that declares the function, if the
+    // name is left to that.
+    origin: Option<u64>,
+    low_pc: u64,
+    high_pc: Option<u64>,
+}
+
+/// The attributes of a DIE that finding the functions takes.
+#[derive(Default)]
+struct Die<'a> {
+    name: Option<Value<'a>>,
+    linkage_name: Option<Value<'a>>,
+    low_pc: Option<Value<'a>>,
+    high_pc: Option<Value<'a>>,
+    stmt_list: Option<Value<'a>>,
+    origin: Option<u64>,
+}
+
+impl<'a> Sections<'a> {
+    /// Read the units of `.debug_info` and the functions defined in them, one
+    /// unit at a time. A unit that is malformed is `None`, and the last.
+    pub(crate) fn units(&self) -> Units<'_, 'a> {
+        Units {
+            sections: self,
+            start: 0,
+            abbrevs: None,
+            done: false,
+        }
+    }
+
+    /// Read the unit at `start` of `.debug_info`, with the abbreviation table
+    /// that the last unit used, if this one uses it too. Only the table is
+    /// kept for the next unit: whatever else reading a unit takes is dropped
+    /// once it is read.
+    fn unit(&self, start: usize, abbrevs: &mut Option<(u64, Abbrevs)>) -> Option<Unit> {
+        let info = self.info?;
+        let abbrev = self.abbrev?;
+        let (mut header, mut reader) = Header::new(info, start)?;
+        if abbrevs.as_ref().map(|(offset, _)| *offset) != Some(header.abbrev_offset) {
+            let table = self::abbrevs(abbrev, header.abbrev_offset)?;
+            *abbrevs = Some((header.abbrev_offset, table));
+        }
+        let table = &abbrevs.as_ref()?.1;
+
+        let mut unit = Unit {
+            range: start..header.end,
+            name: None,
+            stmt_list: None,
+            functions: vec![],
+        };
+        // The name of each enclosing namespace or type, if any.
+        let mut scopes: Vec<Option<String>> = vec![];
+        // The names of the unit's functions, by their offset in the unit, for
+        // the functions whose definitions only refer to them.
+        let mut names: HashMap<u64, String> = HashMap::new();
+        let mut functions: Vec<Defined> = vec![];
+        let mut open: Option<(usize, Defined)> = None;
+        while !reader.eof() {
+            let offset = reader.pos;
+            let code = reader.uleb()?;
+            if code == 0 {
+                // The end of a DIE's children, or padding after the last.
+                if scopes.pop().is_none() {
+                    continue;
+                }
+                if let Some((depth, mut function)) = open.take() {
+                    if depth == scopes.len() {
+                        function.end = reader.pos;
+                        functions.push(function);
+                    } else {
+                        open = Some((depth, function));
+                    }
+                }
+                continue;
+            }
+
+            let abbrev = table.get(&code)?;
+            let mut die = Die::default();
+            for &(attr, form) in &abbrev.attrs {
+                let value = value(&mut reader, form, &header)?;
+                match attr {
+                    DW_AT_NAME => die.name = Some(value),
+                    DW_AT_LINKAGE_NAME | DW_AT_MIPS_LINKAGE_NAME => die.linkage_name = Some(value),
+                    DW_AT_LOW_PC => die.low_pc = Some(value),
+                    DW_AT_HIGH_PC => die.high_pc = Some(value),
+                    DW_AT_STMT_LIST => die.stmt_list = Some(value),
+                    DW_AT_STR_OFFSETS_BASE => header.str_offsets_base = Some(value.unsigned()?),
+                    DW_AT_ADDR_BASE | DW_AT_GNU_ADDR_BASE => {
+                        header.addr_base = Some(value.unsigned()?)
+                    }
+                    DW_AT_ABSTRACT_ORIGIN | DW_AT_SPECIFICATION => die.origin = value.unit_ref(),
+                    _ => {}
+                }
+            }
+            // Only the names of scopes and functions are kept, so others are
+            // not looked up at all.
+            let named = scopes.is_empty()
+                || abbrev.children && is_scope(abbrev.tag)
+                || abbrev.tag == DW_TAG_SUBPROGRAM;
+            let name = match die.name {
+                Some(name) if named => Some(self.string(name, &header)?),
+                _ => None,
+            };
+
+            if scopes.is_empty() {
+                if name.is_some() {
+                    unit.name = name.clone();
+                }
+                if let Some(stmt_list) = die.stmt_list {
+                    unit.stmt_list = Some(stmt_list.unsigned()?);
+                }
+            } else if abbrev.tag == DW_TAG_SUBPROGRAM {
+                let path: Vec<&str> = scopes
+                    .iter()
+                    .flatten()
+                    .map(String::as_str)
+                    .chain(name.as_deref())
+                    .collect();
+                let qualified = name.as_ref().map(|_| path.join("::"));
+                if let Some(qualified) = &qualified {
+                    names.insert((offset - header.start) as u64, qualified.clone());
+                }
+                if let (None, Some(low_pc)) = (&open, die.low_pc) {
+                    let linkage_name = match die.linkage_name {
+                        Some(name) => Some(self.string(name, &header)?),
+                        None => None,
+                    };
+                    let low_pc = self.address(low_pc, &header)?;
+                    // A high PC that is a constant is the function's size.
+                    let high_pc = match die.high_pc {
+                        Some(Value::Unsigned(size)) => low_pc.checked_add(size),
+                        Some(high_pc) => self.address(high_pc, &header),
+                        None => None,
+                    };
+                    let function = Defined {
+                        start: offset,
+                        end: reader.pos,
+                        name: qualified.or(linkage_name),
+                        origin: die.origin,
+                        low_pc,
+                        high_pc,
+                    };
+                    if abbrev.children {
+                        open = Some((scopes.len(), function));
+                    } else {
+                        functions.push(function);
+                    }
+                }
+            }
+
+            if abbrev.children {
+                scopes.push(name.filter(|_| is_scope(abbrev.tag)));
+            }
+        }
+        // A unit whose last DIEs are not terminated ends with them.
+        if let Some((_, mut function)) = open.take() {
+            function.end = reader.pos;
+            functions.push(function);
+        }
+
+        for function in functions {
+            let origin = function.origin;
+            unit.functions.push(Function {
+                range: function.start..function.end,
+                name: function
+                    .name
+                    .or_else(|| origin.and_then(|origin| names.get(&origin).cloned())),
+                low_pc: function.low_pc,
+                high_pc: function.high_pc,
+            });
+        }
+        Some(unit)
+    }
+
+    /// The string that an attribute's value is or refers to.
+    fn string(&self, value: Value<'a>, header: &Header) -> Option<String> {
+        let (section, offset) = match value {
+            Value::String(bytes) => return Some(String::from_utf8_lossy(bytes).into_owned()),
+            Value::Str(offset) => (self.str, offset),
+            Value::LineStr(offset) => (self.line_str, offset),
+            Value::StrIndex(index) => {
+                // Without a base, the offsets follow the section's only header.
+                let base = header
+                    .str_offsets_base
+                    .unwrap_or(2 * header.offset_size as u64);
+                let offsets = self.str_offsets?;
+                let at = base.checked_add(index.checked_mul(header.offset_size as u64)?)?;
+                let offset =
+                    Reader::new(offsets, usize::try_from(at).ok()?).uint(header.offset_size)?;
+                (self.str, offset)
+            }
+            _ => return None,
+        };
+        let mut reader = Reader::new(section?, usize::try_from(offset).ok()?);
+        Some(String::from_utf8_lossy(reader.cstr()?).into_owned())
+    }
+
+    /// The address that an attribute's value is or refers to.
+    fn address(&self, value: Value<'a>, header: &Header) -> Option<u64> {
+        match value {
+            Value::Address(address) => Some(address),
+            Value::AddrIndex(index) => {
+                // Without a base, the addresses follow the section's header.
+                let base = header.addr_base.unwrap_or(8);
+                let addresses = self.addr?;
+                let at = base.checked_add(index.checked_mul(header.address_size as u64)?)?;
+                Reader::new(addresses, usize::try_from(at).ok()?).uint(header.address_size)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The units of `.debug_info`, as `Sections::units` reads them.
+pub(crate) struct Units<'s, 'a> {
+    sections: &'s Sections<'a>,
+    start: usize,
+    abbrevs: Option<(u64, Abbrevs)>,
+    done: bool,
+}
+
+impl Iterator for Units<'_, '_> {
+    type Item = Option<Unit>;
+
+    fn next(&mut self) -> Option<Option<Unit>> {
+        let len = self.sections.info.map_or(1, <[u8]>::len);
+        if self.done || self.start >= len {
+            return None;
+        }
+        let unit = self.sections.unit(self.start, &mut self.abbrevs);
+        match &unit {
+            Some(unit) => self.start = unit.range.end,
+            None => self.done = true,
+        }
+        Some(unit)
+    }
+}
+
+/// Read the units of the DWARF sections of the given object file, and pass
+/// each to `f` as it is read, so that no more than one unit's functions are
+/// held at once. `whose` says whose debug info it is, for errors.
+#[cfg(feature = "dwarf")]
+pub(crate) fn object_units(
+    file: &object::File,
+    whose: &str,
+    mut f: impl FnMut(Unit),
+) -> traits::Result<()> {
+    let data = |name: &str| {
+        file.section_by_name(name)
+            .and_then(|section| section.data().ok())
+    };
+    let sections = Sections {
+        info: data(".debug_info"),
+        abbrev: data(".debug_abbrev"),
+        str: data(".debug_str"),
+        line_str: data(".debug_line_str"),
+        str_offsets: data(".debug_str_offsets"),
+        addr: data(".debug_addr"),
+    };
+    for unit in sections.units() {
+        let unit = unit.ok_or_else(|| traits::Error::Malformed {
+            section: Some(format!("{} .debug_info", whose)),
+            offset: 0,
+            message: format!("Failed to read the units of {} debug info", whose),
+        })?;
+        f(unit);
+    }
+    Ok(())
+}
+
+/// Whether DIEs with the given tag are scopes that qualify the names of the
+/// functions in them.
+fn is_scope(tag: u64) -> bool {
+    matches!(
+        tag,
+        DW_TAG_NAMESPACE
+            | DW_TAG_STRUCTURE_TYPE
+            | DW_TAG_CLASS_TYPE
+            | DW_TAG_UNION_TYPE
+            | DW_TAG_ENUMERATION_TYPE
+    )
+}
+
+/// Where each unit's line program is in `.debug_line`, or `None` if the
+/// section is malformed.
+pub(crate) fn line_programs(line: &[u8]) -> Option<Vec<Range<usize>>> {
+    let mut programs = vec![];
+    let mut start = 0;
+    while start < line.len() {
+        let (end, _) = Reader::new(line, start).unit_length()?;
+        programs.push(start..end);
+        start = end;
+    }
+    Some(programs)
+}
+
+/// The header of a unit in `.debug_info`, and the bases for the indices that
+/// its DIEs' attributes may use.
+struct Header {
+    start: usize,
+    end: usize,
+    version: u16,
+    offset_size: usize,
+    address_size: usize,
+    abbrev_offset: u64,
+    str_offsets_base: Option<u64>,
+    addr_base: Option<u64>,
+}
+
+impl Header {
+    /// Read the header of the unit at `start`, and return it with a reader of
+    /// the unit's DIEs.
+    fn new(data: &[u8], start: usize) -> Option<(Header, Reader<'_>)> {
+        let (end, offset_size) = Reader::new(data, start).unit_length()?;
+        let mut reader = Reader::new(&data[..end], start + if offset_size == 8 { 12 } else { 4 });
+        let version = reader.uint(2)? as u16;
+        let (address_size, abbrev_offset) = match version {
+            2..=4 => {
+                let abbrev_offset = reader.uint(offset_size)?;
+                (reader.uint(1)?, abbrev_offset)
+            }
+            5 => {
+                let unit_type = reader.uint(1)?;
+                let address_size = reader.uint(1)?;
+                let abbrev_offset = reader.uint(offset_size)?;
+                match unit_type {
+                    // DW_UT_compile and DW_UT_partial.
+                    0x01 | 0x03 => {}
+                    // DW_UT_skeleton and DW_UT_split_compile, with a unit ID.
+                    0x04 | 0x05 => {
+                        reader.uint(8)?;
+                    }
+                    // DW_UT_type and DW_UT_split_type, with a type signature
+                    // and the offset of the type.
+                    0x02 | 0x06 => {
+                        reader.uint(8)?;
+                        reader.uint(offset_size)?;
+                    }
+                    _ => return None,
+                }
+                (address_size, abbrev_offset)
+            }
+            _ => return None,
+        };
+        let header = Header {
+            start,
+            end,
+            version,
+            offset_size,
+            address_size: address_size as usize,
+            abbrev_offset,
+            str_offsets_base: None,
+            addr_base: None,
+        };
+        Some((header, reader))
+    }
+}
+
+/// An abbreviation: the tag of the DIEs that use it, whether they have
+/// children, and the name and form of each of their attributes.
+struct Abbrev {
+    tag: u64,
+    children: bool,
+    attrs: Vec<(u64, u64)>,
+}
+
+/// An abbreviation table, by code.
+type Abbrevs = HashMap<u64, Abbrev>;
+
+/// The abbreviation table at the given offset of `.debug_abbrev`.
+fn abbrevs(data: &[u8], offset: u64) -> Option<Abbrevs> {
+    let mut reader = Reader::new(data, usize::try_from(offset).ok()?);
+    let mut abbrevs = HashMap::new();
+    loop {
+        let code = reader.uleb()?;
+        if code == 0 {
+            return Some(abbrevs);
+        }
+        let tag = reader.uleb()?;
+        let children = reader.uint(1)? == 1;
+        let mut attrs = vec![];
+        loop {
+            let (attr, form) = (reader.uleb()?, reader.uleb()?);
+            if (attr, form) == (0, 0) {
+                break;
+            }
+            // The value of an implicit constant is in the abbreviation, and
+            // none of the attributes that are needed here use one.
+            if form == DW_FORM_IMPLICIT_CONST {
+                reader.uleb()?;
+            }
+            attrs.push((attr, form));
+        }
+        abbrevs.insert(
+            code,
+            Abbrev {
+                tag,
+                children,
+                attrs,
+            },
+        );
+    }
+}
+
+/// The value of an attribute, as far as it matters here.
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    Unsigned(u64),
+    UnitRef(u64),
+    Address(u64),
+    AddrIndex(u64),
+    String(&'a [u8]),
+    Str(u64),
+    LineStr(u64),
+    StrIndex(u64),
+    Other,
+}
+
+impl Value<'_> {
+    fn unsigned(self) -> Option<u64> {
+        match self {
+            Value::Unsigned(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn unit_ref(self) -> Option<u64> {
+        match self {
+            Value::UnitRef(offset) => Some(offset),
+            _ => None,
+        }
+    }
+}
+
+/// Read an attribute's value of the given form. An unknown form is an error,
+/// since the size of its value, and so where the next attribute starts, is
+/// unknown too.
+fn value<'a>(reader: &mut Reader<'a>, form: u64, header: &Header) -> Option<Value<'a>> {
+    let offset_size = header.offset_size;
+    let value = match form {
+        // DW_FORM_addr.
+        0x01 => Value::Address(reader.uint(header.address_size)?),
+        // DW_FORM_addrx and DW_FORM_GNU_addr_index.
+        0x1b | 0x1f01 => Value::AddrIndex(reader.uleb()?),
+        // DW_FORM_addrx1 to DW_FORM_addrx4.
+        0x29..=0x2c => Value::AddrIndex(reader.uint(form as usize - 0x28)?),
+        // DW_FORM_data1, DW_FORM_data2, DW_FORM_data4 and DW_FORM_data8.
+        0x0b => Value::Unsigned(reader.uint(1)?),
+        0x05 => Value::Unsigned(reader.uint(2)?),
+        0x06 => Value::Unsigned(reader.uint(4)?),
+        0x07 => Value::Unsigned(reader.uint(8)?),
+        // DW_FORM_udata.
+        0x0f => Value::Unsigned(reader.uleb()?),
+        // DW_FORM_sec_offset.
+        0x17 => Value::Unsigned(reader.uint(offset_size)?),
+        // DW_FORM_ref1 to DW_FORM_ref8, and DW_FORM_ref_udata.
+        0x11..=0x14 => Value::UnitRef(reader.uint(1 << (form - 0x11))?),
+        0x15 => Value::UnitRef(reader.uleb()?),
+        // DW_FORM_string.
+        0x08 => Value::String(reader.cstr()?),
+        // DW_FORM_strp and DW_FORM_line_strp.
+        0x0e => Value::Str(reader.uint(offset_size)?),
+        0x1f => Value::LineStr(reader.uint(offset_size)?),
+        // DW_FORM_strx and DW_FORM_GNU_str_index.
+        0x1a | 0x1f02 => Value::StrIndex(reader.uleb()?),
+        // DW_FORM_strx1 to DW_FORM_strx4.
+        0x25..=0x28 => Value::StrIndex(reader.uint(form as usize - 0x24)?),
+        // DW_FORM_indirect, whose form comes first.
+        0x16 => {
+            let form = reader.uleb()?;
+            return value(reader, form, header);
+        }
+        // DW_FORM_block2, DW_FORM_block4, DW_FORM_block1, DW_FORM_block and
+        // DW_FORM_exprloc.
+        0x03 | 0x04 | 0x0a | 0x09 | 0x18 => {
+            let len = match form {
+                0x03 => reader.uint(2)?,
+                0x04 => reader.uint(4)?,
+                0x0a => reader.uint(1)?,
+                _ => reader.uleb()?,
+            };
+            reader.bytes(usize::try_from(len).ok()?)?;
+            Value::Other
+        }
+        // DW_FORM_flag.
+        0x0c => {
+            reader.bytes(1)?;
+            Value::Other
+        }
+        // DW_FORM_sdata, DW_FORM_loclistx and DW_FORM_rnglistx.
+        0x0d | 0x22 | 0x23 => {
+            reader.uleb()?;
+            Value::Other
+        }
+        // DW_FORM_ref_addr, which is address-sized in DWARF 2.
+        0x10 => {
+            let size = if header.version == 2 {
+                header.address_size
+            } else {
+                offset_size
+            };
+            reader.bytes(size)?;
+            Value::Other
+        }
+        // DW_FORM_flag_present and DW_FORM_implicit_const.
+        0x19 | DW_FORM_IMPLICIT_CONST => Value::Other,
+        // DW_FORM_ref_sup4.
+        0x1c => {
+            reader.bytes(4)?;
+            Value::Other
+        }
+        // DW_FORM_strp_sup, DW_FORM_GNU_ref_alt and DW_FORM_GNU_strp_alt.
+        0x1d | 0x1f20 | 0x1f21 => {
+            reader.bytes(offset_size)?;
+            Value::Other
+        }
+        // DW_FORM_data16.
+        0x1e => {
+            reader.bytes(16)?;
+            Value::Other
+        }
+        // DW_FORM_ref_sig8 and DW_FORM_ref_sup8.
+        0x20 | 0x24 => {
+            reader.bytes(8)?;
+            Value::Other
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// A little-endian reader of a DWARF section, from some position on.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Reader<'a> {
+        Reader { data, pos }
+    }
+
+    fn eof(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    fn uint(&mut self, size: usize) -> Option<u64> {
+        if size > 8 {
+            return None;
+        }
+        let bytes = self.bytes(size)?;
+        Some(
+            bytes
+                .iter()
+                .rev()
+                .fold(0, |value, &b| value << 8 | b as u64),
+        )
+    }
+
+    /// Read an unsigned LEB128 number, or skip a signed one. Bits beyond the
+    /// 64th are dropped.
+    fn uleb(&mut self) -> Option<u64> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.uint(1)?;
+            if shift < 64 {
+                value |= (byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    }
+
+    fn cstr(&mut self) -> Option<&'a [u8]> {
+        let len = self.data.get(self.pos..)?.iter().position(|&b| b == 0)?;
+        let s = self.bytes(len)?;
+        self.pos += 1;
+        Some(s)
+    }
+
+    /// Read the length that a unit starts with, and return where the unit
+    /// ends and whether it is in the 32-bit or the 64-bit format, by the size
+    /// of its offsets.
+    fn unit_length(&mut self) -> Option<(usize, usize)> {
+        let (length, offset_size) = match self.uint(4)? {
+            0xffff_ffff => (self.uint(8)?, 8),
+            length if length < 0xffff_fff0 => (length, 4),
+            _ => return None,
+        };
+        let end = self.pos.checked_add(usize::try_from(length).ok()?)?;
+        if end > self.data.len() {
+            return None;
+        }
+        Some((end, offset_size))
+    }
+}
@@ -0,0 +1,173 @@
+//! Picking the architecture to parse out of a universal Mach-O binary, and
+//! reading the functions that a dSYM's DWARF describes, or the DWARF that a
+//! binary embeds.
+
+use std::convert::TryInto;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use object::macho::{
+    CPU_SUBTYPE_ARM64E, CPU_SUBTYPE_MASK, CPU_SUBTYPE_X86_64_H, CPU_TYPE_ARM, CPU_TYPE_ARM64,
+    CPU_TYPE_ARM64_32, CPU_TYPE_POWERPC, CPU_TYPE_POWERPC64, CPU_TYPE_X86, CPU_TYPE_X86_64,
+};
+use object::read::macho::{FatArch, MachOFatFile32, MachOFatFile64};
+use object::{File, FileKind, Object};
+use twiggy_traits as traits;
+
+use crate::dwarf;
+
+/// The name that Apple's tools, such as `lipo` and `dsymutil`, give the
+/// architecture with the given CPU type and subtype.
+fn arch_name(cputype: u32, cpusubtype: u32) -> String {
+    let name = match (cputype, cpusubtype & !CPU_SUBTYPE_MASK) {
+        (CPU_TYPE_X86_64, CPU_SUBTYPE_X86_64_H) => "x86_64h",
+        (CPU_TYPE_X86_64, _) => "x86_64",
+        (CPU_TYPE_X86, _) => "i386",
+        (CPU_TYPE_ARM64, CPU_SUBTYPE_ARM64E) => "arm64e",
+        (CPU_TYPE_ARM64, _) => "arm64",
+        (CPU_TYPE_ARM64_32, _) => "arm64_32",
+        (CPU_TYPE_ARM, 9) => "armv7",
+        (CPU_TYPE_ARM, 11) => "armv7s",
+        (CPU_TYPE_ARM, 12) => "armv7k",
+        (CPU_TYPE_ARM, _) => "arm",
+        (CPU_TYPE_POWERPC, _) => "ppc",
+        (CPU_TYPE_POWERPC64, _) => "ppc64",
+        _ => return format!("cputype {}", cputype),
+    };
+    name.to_string()
+}
+
+/// The name and data of each architecture of a universal binary.
+fn fat_slices<'a, A: FatArch>(data: &'a [u8], arches: &[A]) -> Vec<(String, &'a [u8])> {
+    arches
+        .iter()
+        .filter_map(|arch| {
+            let slice = arch.data(data).ok()?;
+            Some((arch_name(arch.cputype(), arch.cpusubtype()), slice))
+        })
+        .collect()
+}
+
+/// The architecture of a single-architecture Mach-O binary, from its header.
+fn thin_arch_name(data: &[u8]) -> Option<String> {
+    let word = |at: usize| -> Option<u32> {
+        let bytes = data.get(at..at + 4)?.try_into().ok()?;
+        Some(match data.get(..4)? {
+            [0xce, 0xfa, 0xed, 0xfe] | [0xcf, 0xfa, 0xed, 0xfe] => u32::from_le_bytes(bytes),
+            _ => u32::from_be_bytes(bytes),
+        })
+    };
+    Some(arch_name(word(4)?, word(8)?))
+}
+
+fn arch_list(slices: &[(String, &[u8])]) -> String {
+    slices
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The part of `data` to parse: the slice for `arch` if `data` is a universal
+/// binary, or `data` itself otherwise. A universal binary with more than one
+/// architecture needs `arch` to pick one, and a single-architecture Mach-O
+/// binary must be of `arch`, if it is given.
+pub(super) fn slice<'a>(data: &'a [u8], arch: Option<&str>) -> traits::Result<&'a [u8]> {
+    let malformed = |err: object::Error| traits::Error::Malformed {
+        section: None,
+        offset: 0,
+        message: format!("Failed to parse universal binary with err: {:?}", err),
+    };
+    let slices = match FileKind::parse(data) {
+        Ok(FileKind::MachOFat32) => fat_slices(
+            data,
+            MachOFatFile32::parse(data).map_err(malformed)?.arches(),
+        ),
+        Ok(FileKind::MachOFat64) => fat_slices(
+            data,
+            MachOFatFile64::parse(data).map_err(malformed)?.arches(),
+        ),
+        Ok(FileKind::MachO32) | Ok(FileKind::MachO64) => {
+            if let (Some(arch), Some(name)) = (arch, thin_arch_name(data)) {
+                if arch != name {
+                    return Err(traits::Error::Analysis(format!(
+                        "the input is not a universal binary, and its only architecture is {}, \
+                         not {}",
+                        name, arch
+                    )));
+                }
+            }
+            return Ok(data);
+        }
+        _ => return Ok(data),
+    };
+
+    match (arch, slices.as_slice()) {
+        (None, [(_, slice)]) => Ok(slice),
+        (None, _) => Err(traits::Error::Analysis(format!(
+            "the input is a universal binary with the architectures {}; \
+             pick one of them to parse with `--arch`",
+            arch_list(&slices)
+        ))),
+        (Some(arch), _) => match slices.iter().find(|(name, _)| name == arch) {
+            Some((_, slice)) => Ok(slice),
+            None => Err(traits::Error::Analysis(format!(
+                "the input has no {} architecture; its architectures are {}",
+                arch,
+                arch_list(&slices)
+            ))),
+        },
+    }
+}
+
+/// Read the DWARF file at the given path, which is either the file itself, or
+/// a `.dSYM` bundle with it under `Contents/Resources/DWARF`.
+pub(super) fn read_dsym(path: &Path) -> io::Result<Vec<u8>> {
+    if !path.is_dir() {
+        return fs::read(path);
+    }
+    let dir = path.join("Contents").join("Resources").join("DWARF");
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<_>>()?;
+    match files.len() {
+        1 => fs::read(files.pop().unwrap()),
+        n => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "expected the dSYM bundle to have one DWARF file in {}, but it has {}",
+                dir.display(),
+                n
+            ),
+        )),
+    }
+}
+
+/// Read the units of the DWARF in the given dSYM, for the binary with the
+/// given architecture and UUID, and pass each to `f` as it is read. The dSYM
+/// must be of the same build of the binary as it, if they both have a UUID to
+/// tell by.
+pub(super) fn debug_units(
+    dsym: &[u8],
+    arch: Option<&str>,
+    uuid: Option<[u8; 16]>,
+    f: impl FnMut(dwarf::Unit),
+) -> traits::Result<()> {
+    let dsym = slice(dsym, arch)?;
+    let file = File::parse(dsym).map_err(|err| traits::Error::Malformed {
+        section: None,
+        offset: 0,
+        message: format!("Failed to parse dSYM with err: {:?}", err),
+    })?;
+    if let (Some(uuid), Ok(Some(dsym_uuid))) = (uuid, file.mach_uuid()) {
+        if uuid != dsym_uuid {
+            return Err(traits::Error::Analysis(
+                "the dSYM's UUID does not match the input's, so it is not of the same build"
+                    .to_string(),
+            ));
+        }
+    }
+
+    dwarf::object_units(&file, "the dSYM's", f)
+}
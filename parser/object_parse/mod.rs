@@ -1,12 +1,19 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
+use std::ops::Range;
+use std::path::Path;
 
-use anyhow::anyhow;
 use object::{
     elf, Architecture, BinaryFormat, Endianness, File, Object, ObjectSection, ObjectSegment,
-    ObjectSymbol, Relocation, RelocationFlags, RelocationTarget, SectionFlags, Symbol, SymbolIndex,
-    SymbolKind,
+    ObjectSymbol, Relocation, RelocationFlags, RelocationTarget, Section, SectionFlags, Symbol,
+    SymbolIndex, SymbolKind,
 };
 use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+mod macho;
+
+use crate::dwarf;
 
 fn maybe_thumb_real_addr(file: &File, addr: u64) -> u64 {
     match file.architecture() {
@@ -17,66 +24,287 @@ fn maybe_thumb_real_addr(file: &File, addr: u64) -> u64 {
     }
 }
 
-pub fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
-    let file: File =
-        File::parse(data).map_err(|err| anyhow!("Failed to parse data with err: {:?}", err))?;
+/// Whether a section is loaded into memory when the binary is run. For ELF,
+/// the symbols in other sections have values that are not runtime addresses.
+fn is_loaded(section: &Section) -> bool {
+    match section.flags() {
+        SectionFlags::Elf { sh_flags } => sh_flags as u32 & elf::SHF_ALLOC == elf::SHF_ALLOC,
+        _ => true,
+    }
+}
+
+/// Whether the given address is in the file data of one of the segments.
+fn is_in_segment(file: &File, addr: u64) -> bool {
+    file.segments()
+        .any(|segment| segment.data_range(addr, 1).ok().flatten().is_some())
+}
+
+/// The offsets into the file of the `size` bytes at the given address of a
+/// section, if they are in the file at all. `base` is the offset of the
+/// binary's own data, which is not zero for a slice of a universal binary.
+fn file_range(section: &Section, base: u64, start: u64, size: u64) -> Option<Range<u64>> {
+    let (offset, file_size) = section.file_range()?;
+    let relative = start.checked_sub(section.address())?;
+    if relative + size > file_size {
+        return None;
+    }
+    let start = base + offset + relative;
+    Some(start..start + size)
+}
+
+/// The address of the binary's entry point. A Mach-O binary's is an offset
+/// into its `__TEXT` segment.
+fn entry_address(file: &File) -> u64 {
+    let entry = file.entry();
+    match file.format() {
+        BinaryFormat::MachO => file
+            .segments()
+            .find(|segment| segment.name() == Ok(Some("__TEXT")))
+            .map_or(entry, |text| text.address() + entry),
+        _ => entry,
+    }
+}
+
+/// Parse an object file into IR items. A universal Mach-O binary is parsed
+/// for the given architecture. The functions that the DWARF in the given dSYM
+/// describes are named, sized and given source files after it, including
+/// those that the binary has no symbols for. So are those that the DWARF a PE
+/// binary embeds describes, as MinGW builds them with.
+pub fn parse(data: &[u8], arch: Option<&str>, dsym: Option<&Path>) -> traits::Result<ir::Items> {
+    if object::FileKind::parse(data).is_err() {
+        return Err(traits::Error::NotAFormat);
+    }
+    let whole = data;
+    let data = macho::slice(data, arch)?;
+    let base = (data.as_ptr() as usize - whole.as_ptr() as usize) as u64;
+    let file: File = File::parse(data).map_err(|err| traits::Error::Malformed {
+        section: None,
+        offset: 0,
+        message: format!("Failed to parse data with err: {:?}", err),
+    })?;
 
     let mut alloc_size = 0;
     for segment in file.segments() {
         alloc_size += segment.size();
     }
 
-    let mut items = ir::ItemsBuilder::new(alloc_size as u32);
+    let mut items = ir::ItemsBuilder::new(alloc_size);
+
+    // A binary stripped of its debug info and `.symtab` still has the
+    // `.dynsym` of the symbols it exports, which is better than nothing.
+    let dynamic = file.symbol_table().is_none();
+    let table: Vec<Symbol<'_, '_>> = if dynamic {
+        file.dynamic_symbols().collect()
+    } else {
+        file.symbols().collect()
+    };
+
+    let section_at = |addr: u64| {
+        file.sections().find(|section| {
+            is_loaded(section)
+                && (section.address()..section.address() + section.size()).contains(&addr)
+        })
+    };
+    let entry = maybe_thumb_real_addr(&file, entry_address(&file));
+
+    // Where each defined symbol and described function starts, by section, to
+    // size the symbols that do not have a size up to the next one.
+    let mut starts: BTreeMap<usize, Vec<u64>> = BTreeMap::new();
+    // The starts of the symbols that get items, which the functions that the
+    // debug info describes there size rather than getting items of their own.
+    let mut symbol_starts = BTreeSet::new();
+    for symbol in &table {
+        if let (true, Some(section)) = (symbol.is_definition(), symbol.section_index()) {
+            let start = maybe_thumb_real_addr(&file, symbol.address());
+            starts.entry(section.0).or_default().push(start);
+            let loaded = file
+                .section_by_index(section)
+                .is_ok_and(|section| is_loaded(&section));
+            if loaded && is_in_segment(&file, start) {
+                symbol_starts.insert(start);
+            }
+        }
+    }
+
+    // The bytes of each section that its symbols' items take up.
+    let mut attributed: BTreeMap<usize, u64> = BTreeMap::new();
+    // The items in each section, for PE binaries to hang them off of it.
+    let mut children: BTreeMap<usize, Vec<ir::Id>> = BTreeMap::new();
+
+    // The debug info is read one unit at a time, and only what the symbols
+    // need of each unit's functions is kept: the size of those at a symbol's
+    // start, and the name of their unit. The functions that only the debug
+    // info knows of, such as the local functions of a binary stripped with
+    // `strip -x`, get items of their own straight away, after the symbols'
+    // indices.
+    let mut by_address: BTreeMap<u64, (Option<u64>, usize)> = BTreeMap::new();
+    let mut unit_names: Vec<Option<String>> = vec![];
+    let mut next_index = table.len();
+    let mut add_unit = |unit: dwarf::Unit| {
+        for function in unit.functions {
+            let index = next_index;
+            next_index += 1;
+            let start = function.low_pc;
+            let section = section_at(start);
+            if let Some(section) = &section {
+                starts.entry(section.index().0).or_default().push(start);
+            }
+            let size = function
+                .high_pc
+                .and_then(|end| end.checked_sub(start))
+                .filter(|&size| size > 0);
+            if symbol_starts.contains(&start) {
+                by_address.insert(start, (size, unit_names.len()));
+                continue;
+            }
+            let (Some(size), Some(section)) = (size, section) else {
+                continue;
+            };
+            *attributed.entry(section.index().0).or_default() += size;
+            let id = ir::Id::entry(section.index().0, index);
+            children.entry(section.index().0).or_default().push(id);
+            let name = function
+                .name
+                .unwrap_or_else(|| format!("function at {:#x}", start));
+            let kind: ir::ItemKind = ir::Code::new(&name).into();
+            let item =
+                ir::Item::new(id, name, size, kind).with_category(ir::ItemCategory::Function);
+            if start == entry {
+                items.add_root(item);
+            } else {
+                items.add_item(item);
+            }
+            if let Some(range) = file_range(&section, base, start, size) {
+                items.add_byte_range(id, range);
+            }
+            if let Some(unit) = &unit.name {
+                items.set_source_location(id, unit, None);
+            }
+        }
+        unit_names.push(unit.name);
+    };
+    match dsym {
+        Some(dsym) => {
+            let uuid = file.mach_uuid().ok().flatten();
+            macho::debug_units(&macho::read_dsym(dsym)?, arch, uuid, &mut add_unit)?;
+        }
+        None if file.format() == BinaryFormat::Pe && file.has_debug_symbols() => {
+            dwarf::object_units(&file, "the input's", &mut add_unit)?;
+        }
+        None => {}
+    }
+    for starts in starts.values_mut() {
+        starts.sort_unstable();
+        starts.dedup();
+    }
 
     let mut symbols = vec![];
-    for symbol in file.symbols() {
-        if !symbol.is_definition() {
+    // ELF symbol tables list each object file's `STT_FILE` symbol followed by
+    // that file's local symbols, which gives us a source file for them.
+    let mut current_file = None;
+    for symbol in &table {
+        if symbol.kind() == SymbolKind::File {
+            current_file = symbol.name().ok().filter(|name| !name.is_empty());
             continue;
         }
 
-        if symbol.size() == 0 {
+        if !symbol.is_definition() {
             continue;
         }
 
-        // Filter out symbols in non-allocated sections. Their symbol values do not correspond to
-        // actual runtime addresses.
-        match file
-            .section_by_index(symbol.section_index().unwrap())
-            .unwrap()
-            .flags()
-        {
-            SectionFlags::Elf { sh_flags } => {
-                if sh_flags as u32 & elf::SHF_ALLOC != elf::SHF_ALLOC {
-                    continue;
-                }
-            }
-            _ => {}
+        let section_index = symbol.section_index().unwrap();
+        let section = file.section_by_index(section_index).unwrap();
+        if !is_loaded(&section) {
+            continue;
         }
 
-        if !file.segments().any(|segment| {
-            segment
-                .data_range(maybe_thumb_real_addr(&file, symbol.address()), 1)
-                .ok()
-                .flatten()
-                .is_some()
-        }) {
+        let start = maybe_thumb_real_addr(&file, symbol.address());
+        if !is_in_segment(&file, start) {
             // Symbol not part of any loaded segment
             continue;
         }
 
-        symbols.push((symbol.address(), symbol.size(), symbol.index()));
+        // Functions and objects without a size, as hand-written assembly
+        // often leaves them and Mach-O always does, are sized by their debug
+        // info, or else extend to the next symbol in their section, or to its
+        // end.
+        let debug_function = by_address.get(&start).copied();
+        let size = match (symbol.size(), debug_function.and_then(|(size, _)| size)) {
+            (0, Some(size)) => size,
+            (0, None) => {
+                let starts = &starts[&section_index.0];
+                let next = starts
+                    .get(starts.partition_point(|&next| next <= start))
+                    .copied()
+                    .unwrap_or_else(|| section.address() + section.size());
+                next.saturating_sub(start)
+            }
+            (size, _) => size,
+        };
+        if size == 0 {
+            continue;
+        }
+
+        symbols.push((symbol.address(), size, symbol.index()));
+        *attributed.entry(section_index.0).or_default() += size;
 
-        let id = ir::Id::entry(symbol.section_index().unwrap().0, symbol.index().0);
+        let id = ir::Id::entry(section_index.0, symbol.index().0);
+        children.entry(section_index.0).or_default().push(id);
         let name = symbol.name().unwrap();
         let kind: ir::ItemKind = ir::Code::new(name).into();
-        let item = ir::Item::new(id, name, symbol.size() as u32, kind);
-        if maybe_thumb_real_addr(&file, symbol.address())
-            == maybe_thumb_real_addr(&file, file.entry())
-        {
+        let category = match symbol.kind() {
+            SymbolKind::Text => ir::ItemCategory::Function,
+            SymbolKind::Data | SymbolKind::Tls => ir::ItemCategory::DataSegment,
+            _ => ir::ItemCategory::Misc,
+        };
+        let item = ir::Item::new(id, name, size, kind).with_category(category);
+        if start == entry {
             items.add_root(item);
         } else {
             items.add_item(item);
         }
+        if let Some(range) = file_range(&section, base, start, size) {
+            items.add_byte_range(id, range);
+        }
+        let debug_unit = debug_function.and_then(|(_, unit)| unit_names[unit].as_deref());
+        if let Some(source_file) = debug_unit.or(current_file.filter(|_| symbol.is_local())) {
+            items.set_source_location(id, source_file, None);
+        }
+    }
+
+    // The only relocations PE images keep are the base relocations in
+    // `.reloc`, which twiggy does not derive edges from, so the best we can
+    // do is make each symbol a child of its section.
+    let pe = file.format() == BinaryFormat::Pe;
+
+    // Like a wasm section, each loaded section is an item of its own, with
+    // whatever bytes of it no symbol covers, such as padding between
+    // functions.
+    for section in file.sections() {
+        if !is_loaded(&section) || !is_in_segment(&file, section.address()) {
+            continue;
+        }
+        let index = section.index().0;
+        let children = children.get(&index).filter(|_| pe);
+        let covered = attributed.get(&index).copied().unwrap_or(0);
+        let size = section.size().saturating_sub(covered);
+        if size == 0 && children.is_none() {
+            continue;
+        }
+        let id = ir::Id::section(index);
+        let name = format!("section '{}'", section.name().unwrap_or(""));
+        items.add_root(
+            ir::Item::new(id, name, size, ir::Misc::new()).with_category(ir::ItemCategory::Section),
+        );
+        for &child in children.into_iter().flatten() {
+            items.add_edge_kind(id, child, ir::EdgeKind::Generic);
+        }
+        // A section no symbol is in is one contiguous range of the file.
+        if covered == 0 {
+            if let Some(range) = file_range(&section, base, section.address(), size) {
+                items.add_byte_range(id, range);
+            }
+        }
     }
 
     if let BinaryFormat::Elf = file.format() {
@@ -88,6 +316,12 @@ pub fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
                 continue;
             }
 
+            // Relocations refer to `.symtab`, so a binary without it has
+            // none that could be resolved.
+            if dynamic {
+                break;
+            }
+
             for (offset, reloc) in section.relocations() {
                 any_relocs = true;
                 edge_for_reloc(&file, &mut items, &symbols, offset, reloc);
@@ -101,6 +335,12 @@ pub fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
                  Hint: Try recompiling the binary with --emit-relocs.\n"
             );
         }
+    } else if pe {
+        eprintln!(
+            "Warning: Note: twiggy does not derive edges from the base relocations of PE \
+                binaries, so the dominators, garbage and paths subcommands only see which section \
+                each symbol is in.\n"
+        )
     } else {
         eprintln!(
             "Warning: Note: The dominators, garbage and paths subcommands currently only support \
@@ -208,7 +448,12 @@ fn add_edge_for_symbol(
     reloc_source: Symbol<'_, '_>,
     reloc_target: Symbol<'_, '_>,
 ) {
-    items.add_edge(
+    let kind = match (reloc_source.kind(), reloc_target.kind()) {
+        (SymbolKind::Text, SymbolKind::Text) => ir::EdgeKind::Call,
+        (_, SymbolKind::Data | SymbolKind::Tls) => ir::EdgeKind::Data,
+        _ => ir::EdgeKind::Generic,
+    };
+    items.add_edge_kind(
         ir::Id::entry(
             reloc_source.section_index().unwrap().0,
             reloc_source.index().0,
@@ -217,5 +462,6 @@ fn add_edge_for_symbol(
             reloc_target.section_index().unwrap().0,
             reloc_target.index().0,
         ),
+        kind,
     );
 }
@@ -1,47 +1,342 @@
 //! Parses binaries into `twiggy_ir::Items`.
+//!
+//! Besides the built in wasm and object file parsers, this crate exposes the
+//! `Parse` trait and `build_items` so that other binary formats can be fed
+//! into `twiggy`'s analyses without forking. That surface follows the same
+//! semver rules as the rest of the crate: while `twiggy` is at `0.x`, breaking
+//! changes to `Parse` or to `twiggy_ir::ItemsBuilder` are only made in minor
+//! version bumps, and never in patch releases.
 
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 
 use std::ffi::OsStr;
 use std::fs;
-use std::io::Read;
+use std::io::{self, Read};
 use std::path;
+use std::thread;
 
 use twiggy_ir as ir;
 use twiggy_traits as traits;
 
+mod dwarf;
 #[cfg(feature = "dwarf")]
 mod object_parse;
+mod source_map;
 mod wasm_parse;
 
 const WASM_MAGIC_NUMBER: [u8; 4] = [0x00, 0x61, 0x73, 0x6D];
 
-/// Parse the file at the given path into IR items.
+/// The input path that stands for stdin rather than a file. A file named `-`
+/// can still be read as `./-`.
+pub const STDIN_PATH: &str = "-";
+
+/// Read the whole input at the given path, or all of stdin if the path is
+/// `-`. Reading nothing from stdin is an error, since that is what happens
+/// when nothing was piped into it.
+pub fn read_input<P: AsRef<path::Path>>(path: P) -> traits::Result<Vec<u8>> {
+    let path = path.as_ref();
+    let mut data = vec![];
+    if path == path::Path::new(STDIN_PATH) {
+        io::stdin().lock().read_to_end(&mut data)?;
+        if data.is_empty() {
+            return Err(traits::Error::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "no input was read from stdin; pipe a binary into it, or pass its path instead \
+                 of `-`",
+            )));
+        }
+    } else {
+        fs::File::open(path)?.read_to_end(&mut data)?;
+    }
+    Ok(data)
+}
+
+/// Parse the file at the given path, or stdin if the path is `-`, into IR
+/// items.
 pub fn read_and_parse<P: AsRef<path::Path>>(
     path: P,
     mode: traits::ParseMode,
-) -> anyhow::Result<ir::Items> {
+) -> traits::Result<ir::Items> {
+    read_and_parse_with_threads(path, mode, 1)
+}
+
+/// Parse the file at the given path, or stdin if the path is `-`, into IR
+/// items, using the given number of worker threads. `0` means use all
+/// available parallelism, and `1` parses everything sequentially. The result
+/// does not depend on the thread count.
+pub fn read_and_parse_with_threads<P: AsRef<path::Path>>(
+    path: P,
+    mode: traits::ParseMode,
+    threads: u32,
+) -> traits::Result<ir::Items> {
+    let mut options = ParseOptions::new();
+    options.set_threads(threads);
+    read_and_parse_with_options(path, mode, &options)
+}
+
+/// Parse the file at the given path, or stdin if the path is `-`, into IR
+/// items, with the given options.
+pub fn read_and_parse_with_options<P: AsRef<path::Path>>(
+    path: P,
+    mode: traits::ParseMode,
+    options: &ParseOptions,
+) -> traits::Result<ir::Items> {
     let path = path.as_ref();
-    let mut file = fs::File::open(path)?;
-    let mut data = vec![];
-    file.read_to_end(&mut data)?;
+    let data = read_input(path)?;
+    parse_file_with_options(path, &data, mode, options)
+}
 
+/// Parse the contents of the file at the given path, already read into
+/// `data`, into IR items. This is `read_and_parse_with_options` for callers
+/// that need the file's bytes for something else too. In `Auto` mode, the
+/// format is told by the path's extension, or by the data's magic number if
+/// that does not settle it, as for stdin's `-`.
+///
+/// A snapshot that `twiggy_ir::Items::write_snapshot` wrote is read back into
+/// the items it was taken of, whatever the mode. The options only apply to
+/// parsing, so they are those that the snapshot was taken with.
+pub fn parse_file_with_options<P: AsRef<path::Path>>(
+    path: P,
+    data: &[u8],
+    mode: traits::ParseMode,
+    options: &ParseOptions,
+) -> traits::Result<ir::Items> {
+    if ir::Items::is_snapshot(data) {
+        return ir::Items::read_snapshot(data).map_err(traits::Error::Snapshot);
+    }
     match mode {
-        traits::ParseMode::Wasm => parse_wasm(&data),
+        traits::ParseMode::Wasm => parse_wasm(data, options),
         #[cfg(feature = "dwarf")]
-        traits::ParseMode::Dwarf => parse_other(&data),
-        traits::ParseMode::Auto => parse_auto(path.extension(), &data),
+        traits::ParseMode::Dwarf => parse_other(data, options),
+        traits::ParseMode::Auto => parse_auto(path.as_ref().extension(), data, options),
     }
 }
 
 /// Parse the given data into IR items.
-pub fn parse(data: &[u8]) -> anyhow::Result<ir::Items> {
-    parse_fallback(data)
+pub fn parse(data: &[u8]) -> traits::Result<ir::Items> {
+    parse_with_threads(data, 1)
+}
+
+/// Parse the given data into IR items, using the given number of worker
+/// threads. `0` means use all available parallelism, and `1` parses
+/// everything sequentially. The result does not depend on the thread count.
+pub fn parse_with_threads(data: &[u8], threads: u32) -> traits::Result<ir::Items> {
+    let mut options = ParseOptions::new();
+    options.set_threads(threads);
+    parse_with_options(data, &options)
+}
+
+/// Parse the given data into IR items, with the given options.
+pub fn parse_with_options(data: &[u8], options: &ParseOptions) -> traits::Result<ir::Items> {
+    parse_fallback(data, options)
+}
+
+/// Options for parsing a binary into IR items.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    threads: u32,
+    aggregate_threshold: Option<u32>,
+    control_flow: bool,
+    body_hashes: bool,
+    call_site_bytes: bool,
+    locals_split: bool,
+    max_edges: Option<u64>,
+    arch: Option<String>,
+    dsym: Option<path::PathBuf>,
+    source_map: Option<path::PathBuf>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            threads: 1,
+            aggregate_threshold: None,
+            control_flow: false,
+            body_hashes: false,
+            call_site_bytes: false,
+            locals_split: false,
+            max_edges: None,
+            arch: None,
+            dsym: None,
+            source_map: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Construct new, default `ParseOptions`.
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    /// The number of worker threads to parse with. `0` means use all available
+    /// parallelism, and `1` parses everything sequentially.
+    pub fn threads(&self) -> u32 {
+        self.threads
+    }
+
+    /// Set the number of worker threads to parse with.
+    pub fn set_threads(&mut self, threads: u32) {
+        self.threads = threads;
+    }
+
+    /// The number of entries above which a wasm element or data section is
+    /// parsed into a single aggregate item, rather than one item per entry.
+    /// Edges out of the aggregated entries are kept, and start from the
+    /// aggregate item instead. `None`, the default, never aggregates.
+    pub fn aggregate_threshold(&self) -> Option<u32> {
+        self.aggregate_threshold
+    }
+
+    /// Set the number of entries above which a wasm element or data section
+    /// is parsed into a single aggregate item.
+    pub fn set_aggregate_threshold(&mut self, threshold: Option<u32>) {
+        self.aggregate_threshold = threshold;
+    }
+
+    /// Whether to record the control structures of each wasm function body:
+    /// how many blocks, loops and ifs it has, how deeply they are nested, and
+    /// the size of its largest `br_table`. Off by default.
+    pub fn control_flow(&self) -> bool {
+        self.control_flow
+    }
+
+    /// Set whether to record the control structures of each wasm function
+    /// body.
+    pub fn set_control_flow(&mut self, control_flow: bool) {
+        self.control_flow = control_flow;
+    }
+
+    /// Whether to record the hashes of each wasm function body, which can be
+    /// used to match functions between two builds of the same code. Off by
+    /// default.
+    pub fn body_hashes(&self) -> bool {
+        self.body_hashes
+    }
+
+    /// Set whether to record the hashes of each wasm function body.
+    pub fn set_body_hashes(&mut self, body_hashes: bool) {
+        self.body_hashes = body_hashes;
+    }
+
+    /// Whether to record, for each pair of a wasm function and a function it
+    /// calls directly, how many bytes the call instructions take up. Off by
+    /// default.
+    pub fn call_site_bytes(&self) -> bool {
+        self.call_site_bytes
+    }
+
+    /// Set whether to record how many bytes each function's calls to each
+    /// other function take up.
+    pub fn set_call_site_bytes(&mut self, call_site_bytes: bool) {
+        self.call_site_bytes = call_site_bytes;
+    }
+
+    /// Whether to split each wasm function into an item for the declarations
+    /// of its locals and one for its instructions, both referenced by the
+    /// function's own item, which keeps the rest of its bytes. Off by
+    /// default.
+    pub fn locals_split(&self) -> bool {
+        self.locals_split
+    }
+
+    /// Set whether to split each wasm function into an item for its locals
+    /// and one for its instructions.
+    pub fn set_locals_split(&mut self, locals_split: bool) {
+        self.locals_split = locals_split;
+    }
+
+    /// The number of edges between items above which parsing a wasm binary
+    /// fails, rather than produce a graph too large to analyze. Heuristic
+    /// edges, such as those from constant addresses to data, do not count
+    /// towards it: they are added last, and only while there is room for them.
+    /// `None`, the default, has no limit.
+    pub fn max_edges(&self) -> Option<u64> {
+        self.max_edges
+    }
+
+    /// Set the number of edges between items above which parsing a wasm
+    /// binary fails.
+    pub fn set_max_edges(&mut self, max_edges: Option<u64>) {
+        self.max_edges = max_edges;
+    }
+
+    /// The architecture to parse out of a universal Mach-O binary, by the
+    /// name that Apple's tools give it, such as `x86_64` or `arm64`. It may
+    /// only be left out of a universal binary with a single architecture.
+    /// `None` by default.
+    pub fn arch(&self) -> Option<&str> {
+        self.arch.as_deref()
+    }
+
+    /// Set the architecture to parse out of a universal Mach-O binary.
+    pub fn set_arch(&mut self, arch: Option<String>) {
+        self.arch = arch;
+    }
+
+    /// The path to the dSYM of a Mach-O binary: either a `.dSYM` bundle, or
+    /// the DWARF file inside it. The functions that its debug info describes
+    /// are named, sized and given source files after it. `None` by default.
+    pub fn dsym(&self) -> Option<&path::Path> {
+        self.dsym.as_deref()
+    }
+
+    /// Set the path to the dSYM of a Mach-O binary.
+    pub fn set_dsym(&mut self, dsym: Option<path::PathBuf>) {
+        self.dsym = dsym;
+    }
+
+    /// The path to a JSON source map of a wasm binary, or to the directory
+    /// that holds the source map that the binary's `sourceMappingURL` names.
+    /// Each function is given the source file that most of its body's bytes
+    /// were compiled from. `None` by default.
+    pub fn source_map(&self) -> Option<&path::Path> {
+        self.source_map.as_deref()
+    }
+
+    /// Set the path to the source map of a wasm binary.
+    pub fn set_source_map(&mut self, source_map: Option<path::PathBuf>) {
+        self.source_map = source_map;
+    }
+}
+
+fn worker_threads(threads: u32) -> usize {
+    match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n as usize,
+    }
+}
+
+/// Build IR items from some input with a `Parse` implementation for a custom
+/// binary format.
+///
+/// `size` is the total size of the input in bytes, and is what every item's
+/// size percentage is computed relative to. Items are parsed from one copy of
+/// `input` first, and then edges are parsed from another, so that every edge's
+/// endpoints are known by the time it is added.
+pub fn build_items<'a, P>(
+    size: u64,
+    input: P,
+    items_extra: P::ItemsExtra,
+    edges_extra: P::EdgesExtra,
+) -> traits::Result<ir::Items>
+where
+    P: Parse<'a> + Clone,
+{
+    let mut items = ir::ItemsBuilder::new(size);
+    input.clone().parse_items(&mut items, items_extra)?;
+    input.parse_edges(&mut items, edges_extra)?;
+    Ok(items.finish())
 }
 
 /// A trait for parsing things into `ir::Item`s.
-pub(crate) trait Parse<'a> {
+///
+/// This is how each of `twiggy`'s own parsers is structured, and it may be
+/// implemented outside of this crate to support other binary formats. See
+/// `build_items` for driving an implementation, and `twiggy_ir::ItemsBuilder`
+/// for the invariants implementations must uphold.
+pub trait Parse<'a> {
     /// Any extra data needed to parse this type's items.
     type ItemsExtra;
 
@@ -50,7 +345,7 @@ pub(crate) trait Parse<'a> {
         self,
         items: &mut ir::ItemsBuilder,
         extra: Self::ItemsExtra,
-    ) -> anyhow::Result<()>;
+    ) -> traits::Result<()>;
 
     /// Any extra data needed to parse this type's edges.
     type EdgesExtra;
@@ -61,17 +356,21 @@ pub(crate) trait Parse<'a> {
         self,
         items: &mut ir::ItemsBuilder,
         extra: Self::EdgesExtra,
-    ) -> anyhow::Result<()>;
+    ) -> traits::Result<()>;
 }
 
-fn parse_auto(extension: Option<&OsStr>, data: &[u8]) -> anyhow::Result<ir::Items> {
+fn parse_auto(
+    extension: Option<&OsStr>,
+    data: &[u8],
+    options: &ParseOptions,
+) -> traits::Result<ir::Items> {
     if sniff_wasm(extension, &data) {
-        parse_wasm(&data)
+        parse_wasm(&data, options)
     } else {
         #[cfg(feature = "dwarf")]
-        let res = parse_other(&data);
+        let res = parse_other(&data, options);
         #[cfg(not(feature = "dwarf"))]
-        let res = parse_fallback(&data);
+        let res = parse_fallback(&data, options);
         res
     }
 }
@@ -83,22 +382,56 @@ fn sniff_wasm(extension: Option<&OsStr>, data: &[u8]) -> bool {
     }
 }
 
-fn parse_wasm(data: &[u8]) -> anyhow::Result<ir::Items> {
-    let mut items = ir::ItemsBuilder::new(data.len() as u32);
+fn parse_wasm(data: &[u8], options: &ParseOptions) -> traits::Result<ir::Items> {
+    if data.get(0..4) != Some(&WASM_MAGIC_NUMBER) {
+        return Err(traits::Error::NotAFormat);
+    }
+
+    let mut items = ir::ItemsBuilder::new(data.len() as u64);
 
-    let module1 = wasm_parse::ModuleReader::new(data);
-    module1.parse_items(&mut items, ())?;
-    let module2 = wasm_parse::ModuleReader::new(data);
-    module2.parse_edges(&mut items, ())?;
+    // Sections are only decoded once; both passes read their entries from it.
+    let module = wasm_parse::ModuleReader::new(data).read_module()?;
+    (&module).parse_items(
+        &mut items,
+        (options.aggregate_threshold, options.locals_split),
+    )?;
+    items.set_max_edges(options.max_edges);
+    (&module).parse_edges(
+        &mut items,
+        (
+            worker_threads(options.threads),
+            options.aggregate_threshold,
+            wasm_parse::BodyWalk {
+                control_flow: options.control_flow,
+                body_hashes: options.body_hashes,
+                call_site_bytes: options.call_site_bytes,
+            },
+        ),
+    )?;
+    if let Some(max_edges) = options.max_edges {
+        if items.edge_count() > max_edges {
+            return Err(traits::Error::Analysis(format!(
+                "the input has {} edges between its items, more than the maximum of {}; \
+                 aggregating large element and data sections with an aggregate threshold \
+                 removes most of their edges",
+                items.edge_count(),
+                max_edges
+            )));
+        }
+    }
+    if let Some(path) = options.source_map() {
+        let map = source_map::SourceMap::read(path, module.source_mapping_url())?;
+        module.add_source_locations(&mut items, &map)?;
+    }
 
     Ok(items.finish())
 }
 
 #[cfg(feature = "dwarf")]
-fn parse_other(data: &[u8]) -> anyhow::Result<ir::Items> {
-    object_parse::parse(&data)
+fn parse_other(data: &[u8], options: &ParseOptions) -> traits::Result<ir::Items> {
+    object_parse::parse(&data, options.arch(), options.dsym())
 }
 
-fn parse_fallback(data: &[u8]) -> anyhow::Result<ir::Items> {
-    parse_wasm(data)
+fn parse_fallback(data: &[u8], options: &ParseOptions) -> traits::Result<ir::Items> {
+    parse_wasm(data, options)
 }
@@ -0,0 +1,226 @@
+//! Reading the JSON source maps that toolchains such as Emscripten emit
+//! alongside a wasm module, to attribute its function bodies to the source
+//! files they were compiled from.
+//!
+//! A wasm source map has a single line of mappings, whose columns are byte
+//! offsets into the module. Each mapping covers the bytes from its offset up to
+//! the next mapping's.
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use serde_derive::Deserialize;
+use twiggy_traits as traits;
+
+/// The parts of a version 3 source map that are needed to map offsets to
+/// source files.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Json {
+    #[serde(default)]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    mappings: String,
+}
+
+/// A mapping of the bytes from `offset` onwards to a line of a source file, by
+/// the index of the file in `SourceMap::sources` and the 1-based line number.
+/// Mappings without a source leave their bytes unmapped.
+#[derive(Clone, Copy, Debug)]
+struct Mapping {
+    offset: u64,
+    source: Option<(usize, u32)>,
+}
+
+/// A decoded source map.
+#[derive(Debug)]
+pub(crate) struct SourceMap {
+    sources: Vec<String>,
+    // In order of offset.
+    mappings: Vec<Mapping>,
+}
+
+fn invalid(message: impl std::fmt::Display) -> traits::Error {
+    traits::Error::Analysis(format!("invalid source map: {}", message))
+}
+
+/// Read the file at the given path, saying which file it was if that fails.
+fn read(path: &Path) -> io::Result<Vec<u8>> {
+    fs::read(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("failed to read source map {}: {}", path.display(), e),
+        )
+    })
+}
+
+impl SourceMap {
+    /// Read the source map at the given path. If the path is a directory, the
+    /// map is the file in it that the module's `sourceMappingURL`, which must
+    /// be a relative URL, names.
+    pub(crate) fn read(path: &Path, url: Option<&str>) -> traits::Result<SourceMap> {
+        if !path.is_dir() {
+            return SourceMap::parse(&read(path)?);
+        }
+        let url = url.ok_or_else(|| {
+            traits::Error::Analysis(format!(
+                "{} is a directory, but the input has no `sourceMappingURL` section to find \
+                 the source map in it by",
+                path.display()
+            ))
+        })?;
+        if url.contains("://") || url.starts_with('/') {
+            return Err(traits::Error::Analysis(format!(
+                "the input's `sourceMappingURL` is `{}`, which is not relative to {}; pass the \
+                 path to the source map itself instead",
+                url,
+                path.display()
+            )));
+        }
+        SourceMap::parse(&read(&path.join(url))?)
+    }
+
+    /// Parse a source map from its JSON.
+    pub(crate) fn parse(json: &[u8]) -> traits::Result<SourceMap> {
+        let json: Json = serde_json::from_slice(json).map_err(invalid)?;
+        let root = json.source_root.unwrap_or_default();
+        let sources: Vec<String> = json
+            .sources
+            .into_iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let source = source.unwrap_or_else(|| format!("<source {}>", i));
+                if root.is_empty() || root.ends_with('/') {
+                    format!("{}{}", root, source)
+                } else {
+                    format!("{}/{}", root, source)
+                }
+            })
+            .collect();
+
+        // Every field but the offset is relative to the same field of the
+        // previous mapping, even across lines. The column in the source is
+        // not needed.
+        let mut mappings = vec![];
+        let (mut source, mut line) = (0i64, 0i64);
+        for (generated_line, segments) in json.mappings.split(';').enumerate() {
+            let mut offset = 0i64;
+            for segment in segments.split(',').filter(|segment| !segment.is_empty()) {
+                let fields = decode_vlqs(segment)?;
+                offset += fields[0];
+                let mapped = match fields.len() {
+                    1 => false,
+                    4 | 5 => {
+                        source += fields[1];
+                        line += fields[2];
+                        true
+                    }
+                    n => return Err(invalid(format!("a mapping has {} fields", n))),
+                };
+                // Only the first line of a wasm source map maps anything.
+                if generated_line > 0 {
+                    continue;
+                }
+                if offset < 0 {
+                    return Err(invalid("a mapping has a negative offset"));
+                }
+                let source = if mapped {
+                    if source < 0 || source as usize >= sources.len() || line < 0 {
+                        return Err(invalid(format!(
+                            "a mapping at offset {:#x} refers to a source that does not exist",
+                            offset
+                        )));
+                    }
+                    Some((source as usize, line as u32 + 1))
+                } else {
+                    None
+                };
+                mappings.push(Mapping {
+                    offset: offset as u64,
+                    source,
+                });
+            }
+        }
+        mappings.sort_by_key(|mapping| mapping.offset);
+        Ok(SourceMap { sources, mappings })
+    }
+
+    /// The source file that most of the bytes in the given range were
+    /// compiled from, along with the line of its first mapping in the range,
+    /// or `None` if most of them are unmapped.
+    pub(crate) fn dominant_source(&self, range: Range<u64>) -> Option<(&str, u32)> {
+        // The bytes, and the first line, from each source, in the order the
+        // sources first appear in. `None` counts the unmapped bytes.
+        let mut totals: Vec<(Option<usize>, u64, u32)> = vec![];
+        let mut add = |source: Option<(usize, u32)>, bytes: u64| {
+            if bytes == 0 {
+                return;
+            }
+            let index = source.map(|(index, _)| index);
+            match totals.iter_mut().find(|total| total.0 == index) {
+                Some(total) => total.1 += bytes,
+                None => totals.push((index, bytes, source.map_or(0, |(_, line)| line))),
+            }
+        };
+
+        let first = self
+            .mappings
+            .partition_point(|mapping| mapping.offset <= range.start);
+        let mut source = first.checked_sub(1).and_then(|i| self.mappings[i].source);
+        let mut start = range.start;
+        for mapping in &self.mappings[first..] {
+            if mapping.offset >= range.end {
+                break;
+            }
+            add(source, mapping.offset - start);
+            start = mapping.offset;
+            source = mapping.source;
+        }
+        add(source, range.end - start);
+
+        // The first of the largest wins ties.
+        let (index, _, line) = totals
+            .into_iter()
+            .rev()
+            .max_by_key(|&(_, bytes, _)| bytes)?;
+        Some((&self.sources[index?], line))
+    }
+}
+
+/// Decode a segment of the mappings: a run of base64 VLQs, each with its sign
+/// in its lowest bit.
+fn decode_vlqs(segment: &str) -> traits::Result<Vec<i64>> {
+    let mut values = vec![];
+    let (mut value, mut shift) = (0u64, 0u32);
+    for c in segment.bytes() {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(invalid(format!("`{}` is not a base64 digit", c as char))),
+        } as u64;
+        if shift > 60 {
+            return Err(invalid("a mapping has a value that is too large"));
+        }
+        value |= (digit & 0x1f) << shift;
+        shift += 5;
+        if digit & 0x20 == 0 {
+            let magnitude = (value >> 1) as i64;
+            values.push(if value & 1 == 1 {
+                -magnitude
+            } else {
+                magnitude
+            });
+            value = 0;
+            shift = 0;
+        }
+    }
+    if shift > 0 {
+        return Err(invalid("a mapping ends in the middle of a value"));
+    }
+    Ok(values)
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+fn parse(data: &[u8], aggregate_threshold: Option<u32>) -> traits::Result<ir::Items> {
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_aggregate_threshold(aggregate_threshold);
+    twiggy_parser::parse_with_options(data, &options)
+}
+
+fn fixtures() -> Vec<Vec<u8>> {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("../twiggy/tests/all/fixtures");
+    let mut wasms = vec![];
+    for entry in fs::read_dir(fixtures).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) == Some("wasm") {
+            wasms.push(fs::read(&path).unwrap());
+        }
+    }
+    assert!(!wasms.is_empty());
+    wasms
+}
+
+fn segments(items: &ir::Items, category: ir::ItemCategory) -> Vec<&ir::Item> {
+    items
+        .iter()
+        .filter(|item| item.category() == category)
+        .collect()
+}
+
+/// The ids of every item that an item of the given category has an edge to.
+fn targets(items: &ir::Items, category: ir::ItemCategory) -> BTreeSet<ir::Id> {
+    segments(items, category)
+        .into_iter()
+        .flat_map(|item| items.neighbors(item.id()))
+        .collect()
+}
+
+#[test]
+fn sections_below_the_threshold_are_not_aggregated() {
+    for data in fixtures() {
+        let (Ok(items), Ok(unaggregated)) = (parse(&data, None), parse(&data, Some(u32::MAX)))
+        else {
+            continue;
+        };
+        let names = |items: &ir::Items| -> Vec<String> {
+            items.iter().map(|item| item.name().to_string()).collect()
+        };
+        assert_eq!(names(&items), names(&unaggregated));
+    }
+}
+
+#[test]
+fn aggregated_sections_keep_their_size_and_edges() {
+    let mut aggregated_any = false;
+    for data in fixtures() {
+        let (Ok(items), Ok(aggregated)) = (parse(&data, None), parse(&data, Some(1))) else {
+            continue;
+        };
+
+        let total = |items: &ir::Items| items.iter().map(|item| item.size()).sum::<u64>();
+        assert_eq!(total(&items), total(&aggregated));
+
+        for category in [
+            ir::ItemCategory::ElementSegment,
+            ir::ItemCategory::DataSegment,
+        ] {
+            let before = segments(&items, category).len();
+            let after = segments(&aggregated, category);
+            if before > 1 {
+                aggregated_any = true;
+                assert_eq!(after.len(), 1, "{:?}", category);
+                assert!(after[0]
+                    .name()
+                    .contains("aggregated by --aggregate-threshold 1"));
+            } else {
+                assert_eq!(after.len(), before);
+            }
+            assert_eq!(targets(&items, category), targets(&aggregated, category));
+        }
+    }
+    assert!(aggregated_any);
+}
@@ -0,0 +1,55 @@
+use twiggy_ir as ir;
+
+mod support;
+
+fn parse(data: &[u8], threads: u32) -> ir::Items {
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_body_hashes(true);
+    options.set_threads(threads);
+    twiggy_parser::parse_with_options(data, &options).unwrap()
+}
+
+/// The hashes of every function body, in function order.
+fn hashes(items: &ir::Items) -> Vec<ir::BodyHash> {
+    let mut functions: Vec<ir::Id> = items
+        .iter()
+        .filter(|item| matches!(item.kind(), ir::ItemKind::Code(_)))
+        .map(|item| item.id())
+        .collect();
+    functions.sort();
+    functions
+        .into_iter()
+        .map(|id| items.body_hash(id).unwrap())
+        .collect()
+}
+
+// `map_original.wasm` defines `unused`, `leaf`, `caller` and `changed`.
+// `map_optimized.wasm` drops `unused`, so `caller`'s call to `leaf` has a
+// different function index, and changes the constant in `changed`.
+
+#[test]
+fn body_hashes_ignore_indices_only_when_normalized() {
+    let original = hashes(&parse(&support::fixture("map_original.wasm"), 1));
+    let optimized = hashes(&parse(&support::fixture("map_optimized.wasm"), 1));
+
+    let (leaf, caller, changed) = (original[1], original[2], original[3]);
+    assert_eq!(optimized[0], leaf);
+    assert_ne!(optimized[1].exact(), caller.exact());
+    assert_eq!(optimized[1].normalized(), caller.normalized());
+    assert_ne!(optimized[2].exact(), changed.exact());
+    assert_ne!(optimized[2].normalized(), changed.normalized());
+}
+
+#[test]
+fn body_hashes_are_not_recorded_by_default() {
+    let items = twiggy_parser::parse(&support::fixture("map_original.wasm")).unwrap();
+    assert!(items
+        .iter()
+        .all(|item| items.body_hash(item.id()).is_none()));
+}
+
+#[test]
+fn body_hashes_do_not_depend_on_threads() {
+    let data = support::fixture("mono.wasm");
+    assert_eq!(hashes(&parse(&data, 1)), hashes(&parse(&data, 4)));
+}
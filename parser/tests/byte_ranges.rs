@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn wee_alloc() -> Vec<u8> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../twiggy/tests/all/fixtures/wee_alloc.wasm");
+    fs::read(path).unwrap()
+}
+
+/// Check that the items with byte ranges have as many bytes in them as their
+/// size, and that no two items' ranges overlap.
+fn check_byte_ranges(items: &ir::Items, len: u64) -> usize {
+    let mut ranges: Vec<_> = items
+        .iter()
+        .flat_map(|item| {
+            let ranges = items.byte_ranges(item.id());
+            if !ranges.is_empty() {
+                let size: u64 = ranges.iter().map(|range| range.end - range.start).sum();
+                assert_eq!(size, item.size(), "{}", item.name());
+            }
+            ranges.iter().cloned()
+        })
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    for pair in ranges.windows(2) {
+        assert!(pair[0].end <= pair[1].start, "{:?}", pair);
+    }
+    assert!(ranges.iter().all(|range| range.end <= len));
+    ranges.len()
+}
+
+#[test]
+fn entries_have_byte_ranges() {
+    let data = wee_alloc();
+    let items = twiggy_parser::parse(&data).unwrap();
+    assert!(check_byte_ranges(&items, data.len() as u64) > 0);
+
+    // A function's entries in the function and code sections.
+    let hello = items.iter().find(|item| item.name() == "hello").unwrap();
+    assert_eq!(items.byte_ranges(hello.id()).len(), 2);
+    // A section's headers are not contiguous.
+    let headers = items
+        .iter()
+        .find(|item| item.name() == "code section headers")
+        .unwrap();
+    assert!(items.byte_ranges(headers.id()).is_empty());
+}
+
+#[test]
+fn aggregated_entries_have_byte_ranges() {
+    let data = wee_alloc();
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_aggregate_threshold(Some(0));
+    let items = twiggy_parser::parse_with_options(&data, &options).unwrap();
+    check_byte_ranges(&items, data.len() as u64);
+
+    let data_segments = items
+        .iter()
+        .find(|item| item.name().starts_with("data["))
+        .unwrap();
+    assert!(items.byte_ranges(data_segments.id()).len() > 1);
+}
@@ -0,0 +1,55 @@
+use twiggy_ir as ir;
+
+mod support;
+
+fn callees(items: &ir::Items, caller: &str) -> Vec<String> {
+    let mut callees: Vec<String> = items
+        .edges(support::item(items, caller).id())
+        .filter(|&(_, kind)| kind == ir::EdgeKind::Heuristic)
+        .map(|(id, _)| items[id].name().to_string())
+        .collect();
+    callees.sort();
+    callees
+}
+
+// `call_indirect.wasm` places `a`, `b` and `c` in its table, and `main`
+// calls through it with a type that has the same signature as `a` and `c`,
+// but not `b`. `unused` is not in the table.
+#[test]
+fn indirect_calls_reach_table_functions_of_their_type() {
+    let items = twiggy_parser::parse(&support::fixture("call_indirect.wasm")).unwrap();
+
+    assert_eq!(callees(&items, "main"), ["a", "c"]);
+    let main = support::item(&items, "main").id();
+    let call_type = support::item(&items, "type[2]: () -> nil").id();
+    assert_eq!(items.edge_kind(main, call_type), Some(ir::EdgeKind::Type));
+}
+
+// Element segments may list their functions as `ref.func` expressions, and
+// tables other than the first are told apart.
+#[test]
+fn indirect_calls_use_expression_element_segments() {
+    let data = [
+        b"\0asm\x01\0\0\0".as_slice(),
+        // One type, `() -> ()`.
+        &[1, 4, 1, 0x60, 0, 0],
+        // Four functions of that type.
+        &[3, 5, 4, 0, 0, 0, 0],
+        // Two tables.
+        &[4, 7, 2, 0x70, 0, 1, 0x70, 0, 1],
+        // `elem (table 1) (i32.const 0) funcref (ref.func 1)`, and
+        // `elem (table 0) (i32.const 0) funcref (ref.func 2)`.
+        &[
+            9, 19, 2, 6, 1, 0x41, 0, 0x0b, 0x70, 1, 0xd2, 1, 0x0b, 4, 0x41, 0, 0x0b, 1, 0xd2, 2,
+            0x0b,
+        ],
+        // Function 0 calls through table 1; the others are empty.
+        &[
+            10, 18, 4, 7, 0, 0x41, 0, 0x11, 0, 1, 0x0b, 2, 0, 0x0b, 2, 0, 0x0b, 2, 0, 0x0b,
+        ],
+    ]
+    .concat();
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    assert_eq!(callees(&items, "code[0]"), ["code[1]"]);
+}
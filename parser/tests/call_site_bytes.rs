@@ -0,0 +1,37 @@
+use twiggy_ir as ir;
+
+// Function 0 calls function 1 twice: once with its index encoded in a single
+// byte, and once padded to five bytes, as linkers leave relocatable indices.
+const MODULE: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic and version
+    0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // type section: () -> ()
+    0x03, 0x03, 0x02, 0x00, 0x00, // function section: two functions
+    0x0a, 0x0f, 0x02, // code section: two bodies
+    0x0a, 0x00, 0x10, 0x01, 0x10, 0x81, 0x80, 0x80, 0x80, 0x00, 0x0b, // calls
+    0x02, 0x00, 0x0b, // empty
+];
+
+fn parse(call_site_bytes: bool) -> ir::Items {
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_call_site_bytes(call_site_bytes);
+    twiggy_parser::parse_with_options(MODULE, &options).unwrap()
+}
+
+#[test]
+fn call_instructions_are_measured_as_encoded() {
+    let items = parse(true);
+    let caller = ir::Id::entry(3, 0);
+    let callee = ir::Id::entry(3, 1);
+    assert_eq!(
+        items.call_site_bytes(callee).collect::<Vec<_>>(),
+        vec![(caller, ir::CallSiteBytes::new(2, 8))]
+    );
+    assert_eq!(items.call_site_bytes(caller).count(), 0);
+}
+
+#[test]
+fn call_site_bytes_are_off_by_default() {
+    let items = parse(false);
+    assert_eq!(items.call_site_bytes(ir::Id::entry(3, 1)).count(), 0);
+    assert_eq!(items.call_sites(ir::Id::entry(3, 1)), 2);
+}
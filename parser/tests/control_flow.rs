@@ -0,0 +1,53 @@
+use twiggy_ir as ir;
+
+mod support;
+
+fn parse(data: &[u8], control_flow: bool, threads: u32) -> ir::Items {
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_control_flow(control_flow);
+    options.set_threads(threads);
+    twiggy_parser::parse_with_options(data, &options).unwrap()
+}
+
+fn function(items: &ir::Items, name: &str) -> ir::Id {
+    items
+        .iter()
+        .find(|item| item.name() == name)
+        .unwrap_or_else(|| panic!("no item named {}", name))
+        .id()
+}
+
+// `control_flow.wasm` defines two functions. The first nests three blocks
+// around a five byte `br_table`, followed by an `if`; the second is empty.
+
+#[test]
+fn control_flow_is_recorded_per_function() {
+    let items = parse(&support::fixture("control_flow.wasm"), true, 1);
+    assert_eq!(
+        items.control_flow(function(&items, "code[0]")),
+        Some(ir::ControlFlow::new(4, 3, 5))
+    );
+    assert_eq!(
+        items.control_flow(function(&items, "code[1]")),
+        Some(ir::ControlFlow::new(0, 0, 0))
+    );
+}
+
+#[test]
+fn control_flow_is_not_recorded_by_default() {
+    let items = parse(&support::fixture("control_flow.wasm"), false, 1);
+    assert_eq!(items.control_flow(function(&items, "code[0]")), None);
+}
+
+#[test]
+fn control_flow_does_not_depend_on_threads() {
+    let data = support::fixture("mono.wasm");
+    let sequential = parse(&data, true, 1);
+    let parallel = parse(&data, true, 4);
+    for item in sequential.iter() {
+        assert_eq!(
+            sequential.control_flow(item.id()),
+            parallel.control_flow(item.id())
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+// `coredump.wasm` has two functions, a memory with a data segment holding the
+// memory snapshot, and a `core`, `coremodules`, `coreinstances` and
+// `corestack` custom section, the last with two frames.
+fn coredump() -> Vec<u8> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../twiggy/tests/all/fixtures/coredump.wasm");
+    fs::read(path).unwrap()
+}
+
+fn coredump_items(items: &ir::Items) -> Vec<(&str, u64)> {
+    let mut coredump: Vec<_> = items
+        .iter()
+        .filter(|item| item.category() == ir::ItemCategory::Coredump)
+        .map(|item| (item.name(), item.size()))
+        .collect();
+    coredump.sort();
+    coredump
+}
+
+#[test]
+fn coredump_sections_are_split_into_entries() {
+    let data = coredump();
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    assert_eq!(
+        coredump_items(&items),
+        [
+            ("coredump: instance[0] of module[0]", 5),
+            ("coredump: module[0] 'app.wasm'", 10),
+            ("coredump: process 'app.wasm'", 10),
+            (
+                "coredump: thread 'main' frame[0] in func[1] of instance[0]",
+                8
+            ),
+            (
+                "coredump: thread 'main' frame[1] in func[0] of instance[0]",
+                10
+            ),
+            ("custom section 'core' headers", 7),
+            ("custom section 'coreinstances' headers", 17),
+            ("custom section 'coremodules' headers", 15),
+            ("custom section 'corestack' headers", 19),
+        ]
+    );
+
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn malformed_coredump_section_is_one_item() {
+    // A `corestack` section whose only frame is cut short.
+    let data = [
+        b"\0asm\x01\0\0\0".as_slice(),
+        &[0, 18],
+        b"\x09corestack",
+        &[0, 4],
+        b"main",
+        &[1, 0],
+    ]
+    .concat();
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    assert_eq!(
+        coredump_items(&items),
+        [
+            ("coredump: custom section 'corestack'", 8),
+            ("custom section 'corestack' headers", 12),
+        ]
+    );
+}
@@ -0,0 +1,30 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn id(items: &ir::Items, name: &str) -> ir::Id {
+    items
+        .iter()
+        .find(|item| item.name() == name)
+        .unwrap_or_else(|| panic!("no item named {}", name))
+        .id()
+}
+
+// In `data_addresses.wasm`, `global[0]` is immutable and `global[1]` is
+// mutable, and both are initialized to 1024. The exported function loads from
+// `global[0]` plus 16, from the sum of two constants with a `nop` between them,
+// from `global[1]` plus an offset of 2048, and from a constant address. These
+// fall in `data[0]`, `data[1]`, `data[2]` and `data[3]` respectively, but the
+// value of the mutable global is not known.
+#[test]
+fn loads_from_computed_addresses_have_edges_to_data() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures/data_addresses.wasm");
+    let items = twiggy_parser::parse(&fs::read(path).unwrap()).unwrap();
+
+    let edge = |from, to| items.edge_kind(id(&items, from), id(&items, to));
+    assert_eq!(edge("code[0]", "data[0]"), Some(ir::EdgeKind::Heuristic));
+    assert_eq!(edge("code[0]", "data[1]"), Some(ir::EdgeKind::Heuristic));
+    assert_eq!(edge("code[0]", "data[2]"), None);
+    assert_eq!(edge("code[0]", "data[3]"), Some(ir::EdgeKind::Heuristic));
+}
@@ -0,0 +1,38 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn import(items: &ir::Items, name: &str) -> ir::Id {
+    items
+        .iter()
+        .find(|item| item.name() == name)
+        .unwrap_or_else(|| panic!("no import named {}", name))
+        .id()
+}
+
+// `duplicate_imports.wasm` imports `env::now` three times with the same type,
+// and once more with another type, which is not a duplicate.
+#[test]
+fn duplicate_imports_are_marked() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures/duplicate_imports.wasm");
+    let items = twiggy_parser::parse(&fs::read(path).unwrap()).unwrap();
+
+    let first = ir::Id::entry(2, 0);
+    assert_eq!(items[first].name(), "import env::now");
+    assert_eq!(items.duplicate_import_of(first), None);
+    for name in [
+        "import env::now (duplicate #2)",
+        "import env::now (duplicate #3)",
+    ] {
+        assert_eq!(items.duplicate_import_of(import(&items, name)), Some(first));
+    }
+
+    let other_type = ir::Id::entry(2, 3);
+    assert_eq!(items[other_type].name(), "import env::now");
+    assert_eq!(items.duplicate_import_of(other_type), None);
+    assert_eq!(
+        items.duplicate_import_of(import(&items, "import env::log")),
+        None
+    );
+}
@@ -0,0 +1,73 @@
+// A module may only have one function, start, code, and data count section.
+// Repeating one must be rejected with an error naming the section and where
+// both copies are, since only one of them would be kept.
+
+use twiggy_traits as traits;
+
+mod support;
+
+const TYPES: (u8, &[u8]) = (1, &[1, 0x60, 0, 0]);
+const FUNCTIONS: (u8, &[u8]) = (3, &[1, 0]);
+const START: (u8, &[u8]) = (8, &[0]);
+const DATA_COUNT: (u8, &[u8]) = (12, &[0]);
+const CODE: (u8, &[u8]) = (10, &[1, 2, 0, 0x0b]);
+
+fn duplicate_error(data: &[u8]) -> (String, usize, String) {
+    match twiggy_parser::parse(data) {
+        Err(traits::Error::Malformed {
+            section: Some(section),
+            offset,
+            message,
+        }) => (section, offset, message),
+        result => panic!("expected a malformed section error, got {:?}", result),
+    }
+}
+
+#[test]
+fn duplicate_code_section_fixture() {
+    let data = support::fixture("duplicate_code_section.wasm");
+    assert_eq!(
+        duplicate_error(&data),
+        (
+            "code section headers".to_string(),
+            0x18,
+            "duplicate code section, the first one is at offset 0x12".to_string()
+        )
+    );
+}
+
+#[test]
+fn duplicate_function_section() {
+    let data = support::module(&[TYPES, FUNCTIONS, FUNCTIONS, CODE]);
+    let (section, offset, message) = duplicate_error(&data);
+    assert_eq!(section, "function section headers");
+    assert_eq!(offset, 0x12);
+    assert_eq!(
+        message,
+        "duplicate function section, the first one is at offset 0xe"
+    );
+}
+
+#[test]
+fn duplicate_start_section() {
+    let data = support::module(&[TYPES, FUNCTIONS, START, START, CODE]);
+    let (section, offset, _) = duplicate_error(&data);
+    assert_eq!(section, "start section headers");
+    assert_eq!(offset, 0x15);
+}
+
+#[test]
+fn duplicate_data_count_section() {
+    let data = support::module(&[TYPES, FUNCTIONS, DATA_COUNT, DATA_COUNT, CODE]);
+    let (section, offset, _) = duplicate_error(&data);
+    assert_eq!(section, "data count section headers");
+    assert_eq!(offset, 0x15);
+}
+
+#[test]
+fn unique_sections_are_accepted() {
+    let data = support::module(&[TYPES, FUNCTIONS, START, DATA_COUNT, CODE]);
+    let items = twiggy_parser::parse(&data).unwrap();
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
@@ -0,0 +1,119 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn dwarf() -> Vec<u8> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../twiggy/tests/all/fixtures/dwarf.wasm");
+    fs::read(path).unwrap()
+}
+
+fn debug_items(items: &ir::Items) -> Vec<(&str, u64)> {
+    let mut debug_items: Vec<_> = items
+        .iter()
+        .filter(|item| item.name().starts_with(".debug_") || item.name().contains("'.debug_"))
+        .map(|item| (item.name(), item.size()))
+        .collect();
+    debug_items.sort();
+    debug_items
+}
+
+// `dwarf.wasm` has a unit for each of its two crates, with the functions
+// `add`, `sub` and `helper` in one and `first` and `second` in the other.
+#[test]
+fn debug_info_is_split_into_units_and_functions() {
+    let data = dwarf();
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    assert_eq!(
+        debug_items(&items),
+        [
+            (".debug_info: dep::first", 57),
+            (".debug_info: dep::second", 57),
+            (".debug_info: dwarf::add", 53),
+            (".debug_info: dwarf::helper", 57),
+            (".debug_info: dwarf::sub", 53),
+            (
+                ".debug_info: unit 'dwarf.rs/@/dwarf.309f2a3a28448880-cgu.0'",
+                52
+            ),
+            (
+                ".debug_info: unit 'dwarf_dep.rs/@/dep.2b5d18cda18c60b9-cgu.0'",
+                52
+            ),
+            (
+                ".debug_line: unit 'dwarf.rs/@/dwarf.309f2a3a28448880-cgu.0'",
+                114
+            ),
+            (
+                ".debug_line: unit 'dwarf_dep.rs/@/dep.2b5d18cda18c60b9-cgu.0'",
+                86
+            ),
+            ("custom section '.debug_abbrev'", 171),
+            ("custom section '.debug_abbrev' headers", 17),
+            ("custom section '.debug_info' headers", 15),
+            ("custom section '.debug_line' headers", 15),
+            ("custom section '.debug_ranges'", 56),
+            ("custom section '.debug_ranges' headers", 16),
+            ("custom section '.debug_str'", 381),
+            ("custom section '.debug_str' headers", 14),
+        ]
+    );
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn function_debug_info_refers_to_its_code() {
+    let items = twiggy_parser::parse(&dwarf()).unwrap();
+
+    let items = &items;
+    let mut edges: Vec<(&str, &str)> = items
+        .iter()
+        .filter(|item| item.name().starts_with(".debug_info: "))
+        .flat_map(|item| {
+            items
+                .neighbors(item.id())
+                .map(move |id| (item.name(), items[id].name()))
+        })
+        .collect();
+    edges.sort();
+    assert_eq!(
+        edges,
+        [
+            (".debug_info: dep::first", "dep[2b5d18cda18c60b9]::first"),
+            (".debug_info: dep::second", "dep[2b5d18cda18c60b9]::second"),
+            (".debug_info: dwarf::add", "add"),
+            (
+                ".debug_info: dwarf::helper",
+                "dwarf[309f2a3a28448880]::helper"
+            ),
+            (".debug_info: dwarf::sub", "sub"),
+        ]
+    );
+}
+
+// A `.debug_info` section that does not decode is left as a single item. The
+// line programs in `.debug_line` are still split up, but can no longer be
+// named after their units.
+#[test]
+fn malformed_debug_info_is_one_item() {
+    let mut data = dwarf();
+    let name = b"\x0b.debug_info";
+    let payload = data
+        .windows(name.len())
+        .position(|window| window == name)
+        .unwrap()
+        + name.len();
+    // DWARF 9, which does not exist, after the first unit's length.
+    data[payload + 4] = 9;
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    let debug_items = debug_items(&items);
+    assert!(debug_items.contains(&("custom section '.debug_info'", 381)));
+    assert!(debug_items.contains(&(".debug_line: unit at offset 0x0", 114)));
+    assert!(debug_items.contains(&(".debug_line: unit at offset 0x72", 86)));
+    assert!(!debug_items
+        .iter()
+        .any(|(name, _)| name.starts_with(".debug_info: ")));
+}
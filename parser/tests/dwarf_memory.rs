@@ -0,0 +1,163 @@
+//! Reading a binary's DWARF should take memory in proportion to one unit at a
+//! time, not to all of the debug info, so these tests count every allocation
+//! that parsing makes.
+
+#![cfg(feature = "dwarf")]
+
+use object::write;
+use object::{
+    Architecture, BinaryFormat, Endianness, SectionKind, SymbolFlags, SymbolKind, SymbolScope,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use twiggy_traits as traits;
+
+/// The system allocator, keeping count of the bytes allocated, and of the
+/// most that ever were at once.
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const UNITS: u64 = 100;
+const FUNCTIONS_PER_UNIT: u64 = 200;
+const VARIABLES_PER_FUNCTION: u64 = 16;
+// Each function is shorter than the gap to the next, so that the size that the
+// debug info gives it is not the one it would get without.
+const FUNCTION_SIZE: u64 = 12;
+const FUNCTION_ALIGN: u64 = 16;
+
+/// A Mach-O object with a symbol, and no size, for each function.
+fn binary() -> Vec<u8> {
+    let mut obj = write::Object::new(
+        BinaryFormat::MachO,
+        Architecture::X86_64,
+        Endianness::Little,
+    );
+    let text = obj.add_section(b"__TEXT".to_vec(), b"__text".to_vec(), SectionKind::Text);
+    let count = UNITS * FUNCTIONS_PER_UNIT;
+    obj.append_section_data(text, &vec![0xc3; (count * FUNCTION_ALIGN) as usize], 16);
+    for i in 0..count {
+        obj.add_symbol(write::Symbol {
+            name: format!("f{}", i).into_bytes(),
+            value: i * FUNCTION_ALIGN,
+            size: 0,
+            kind: SymbolKind::Text,
+            scope: SymbolScope::Linkage,
+            weak: false,
+            section: write::SymbolSection::Section(text),
+            flags: SymbolFlags::None,
+        });
+    }
+    obj.write().unwrap()
+}
+
+/// The `.debug_info` and `.debug_abbrev` of the functions of `binary`, with
+/// many variables in each, as debug info mostly describes things other than
+/// functions.
+fn debug_info() -> (Vec<u8>, Vec<u8>) {
+    let abbrev = vec![
+        // 1: DW_TAG_compile_unit, with children and a DW_FORM_string name.
+        1, 0x11, 1, 0x03, 0x08, 0, 0, //
+        // 2: DW_TAG_subprogram, with children, a DW_FORM_string name, a
+        // DW_FORM_addr low PC and a DW_FORM_data4 high PC.
+        2, 0x2e, 1, 0x03, 0x08, 0x11, 0x01, 0x12, 0x06, 0, 0, //
+        // 3: DW_TAG_variable, with a DW_FORM_string name.
+        3, 0x34, 0, 0x03, 0x08, 0, 0, //
+        0,
+    ];
+
+    let mut info = vec![];
+    for unit in 0..UNITS {
+        let mut dies = vec![1];
+        dies.extend(format!("unit{}.rs\0", unit).as_bytes());
+        for function in 0..FUNCTIONS_PER_UNIT {
+            let index = unit * FUNCTIONS_PER_UNIT + function;
+            dies.push(2);
+            dies.extend(format!("f{}\0", index).as_bytes());
+            dies.extend(&(index * FUNCTION_ALIGN).to_le_bytes());
+            dies.extend(&(FUNCTION_SIZE as u32).to_le_bytes());
+            for variable in 0..VARIABLES_PER_FUNCTION {
+                dies.push(3);
+                dies.extend(format!("a_variable_with_a_long_name_{}\0", variable).as_bytes());
+            }
+            dies.push(0);
+        }
+        dies.push(0);
+
+        // A DWARF 4 header, with the only abbreviation table and 8-byte
+        // addresses.
+        info.extend(&(dies.len() as u32 + 7).to_le_bytes());
+        info.extend(&4u16.to_le_bytes());
+        info.extend(&0u32.to_le_bytes());
+        info.push(8);
+        info.extend(dies);
+    }
+    (info, abbrev)
+}
+
+/// A Mach-O object with the given debug info, as a dSYM has it.
+fn dsym(info: &[u8], abbrev: &[u8]) -> Vec<u8> {
+    let mut obj = write::Object::new(
+        BinaryFormat::MachO,
+        Architecture::X86_64,
+        Endianness::Little,
+    );
+    for (name, data) in [(&b"__debug_info"[..], info), (b"__debug_abbrev", abbrev)] {
+        let section = obj.add_section(b"__DWARF".to_vec(), name.to_vec(), SectionKind::Debug);
+        obj.append_section_data(section, data, 1);
+    }
+    obj.write().unwrap()
+}
+
+#[test]
+fn peak_memory_is_within_twice_the_debug_info() {
+    let data = binary();
+    let (info, abbrev) = debug_info();
+    let debug_size = info.len() + abbrev.len();
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("dwarf_memory.dSYM");
+    fs::write(&path, dsym(&info, &abbrev)).unwrap();
+    drop((info, abbrev));
+
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_dsym(Some(path));
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+    let items =
+        twiggy_parser::parse_file_with_options("-", &data, traits::ParseMode::Dwarf, &options)
+            .unwrap();
+    let peak = PEAK.load(Ordering::SeqCst) - before;
+
+    // Every function got its size from the debug info.
+    let sized = items
+        .iter()
+        .filter(|item| item.size() == FUNCTION_SIZE)
+        .count();
+    assert_eq!(sized as u64, UNITS * FUNCTIONS_PER_UNIT);
+    assert!(
+        peak <= 2 * debug_size,
+        "parsing peaked at {} bytes, more than twice the {} bytes of debug info",
+        peak,
+        debug_size
+    );
+}
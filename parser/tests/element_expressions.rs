@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn parse(fixture: &str) -> ir::Items {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures")
+        .join(fixture);
+    twiggy_parser::parse(&fs::read(path).unwrap()).unwrap()
+}
+
+/// Every edge, by the names of the items at either end.
+fn edges(items: &ir::Items) -> Vec<(String, String, ir::EdgeKind)> {
+    let mut edges: Vec<_> = items
+        .iter()
+        .flat_map(|item| {
+            items.edges(item.id()).map(move |(to, kind)| {
+                (item.name().to_string(), items[to].name().to_string(), kind)
+            })
+        })
+        .collect();
+    edges.sort();
+    edges
+}
+
+fn table_elements(items: &ir::Items) -> Vec<(String, String)> {
+    edges(items)
+        .into_iter()
+        .filter(|&(_, _, kind)| kind == ir::EdgeKind::TableElement)
+        .map(|(from, to, _)| (from, to))
+        .collect()
+}
+
+// `elem_functions.wasm` and `elem_expressions.wasm` only differ in how their
+// element segment lists `a` and `b`: as function indices, or as `ref.func`
+// expressions.
+#[test]
+fn expression_element_segments_have_the_same_edges() {
+    let functions = parse("elem_functions.wasm");
+    let expressions = parse("elem_expressions.wasm");
+
+    assert_eq!(edges(&functions), edges(&expressions));
+    assert_eq!(
+        table_elements(&expressions),
+        [
+            ("elem[0]".to_string(), "a".to_string()),
+            ("elem[0]".to_string(), "b".to_string()),
+            ("table[0]".to_string(), "elem[0]".to_string()),
+        ]
+    );
+}
+
+// `ref.null` entries do not refer to anything.
+#[test]
+fn expression_element_segments_skip_null_entries() {
+    let items = parse("elem_expressions_null.wasm");
+
+    assert_eq!(
+        table_elements(&items),
+        [
+            ("elem[0]".to_string(), "a".to_string()),
+            ("elem[0]".to_string(), "b".to_string()),
+            ("table[0]".to_string(), "elem[0]".to_string()),
+        ]
+    );
+}
+
+// Element segments may fill a table that the module imports.
+#[test]
+fn element_segments_for_imported_tables() {
+    let items = parse("elem_imported_table.wasm");
+
+    assert!(items.warnings().is_empty());
+    assert_eq!(
+        table_elements(&items),
+        [
+            ("elem[0]".to_string(), "code[0]".to_string()),
+            ("import env::table".to_string(), "elem[0]".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+// `empty_sections.wasm` has a type, import, function, table, memory, global,
+// export, element, data count, code, and data section, each with no entries:
+// one byte of id, one of size, and one of count. The data count section's
+// count is an entry of its own, even when it is zero.
+fn empty_sections() -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures/empty_sections.wasm");
+    fs::read(path).unwrap()
+}
+
+fn sections(items: &ir::Items) -> Vec<(&str, u64)> {
+    items
+        .iter()
+        .filter(|item| item.category() == ir::ItemCategory::Section)
+        .map(|item| (item.name(), item.size()))
+        .collect()
+}
+
+#[test]
+fn empty_sections_keep_their_header_bytes() {
+    let data = empty_sections();
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    let mut sections = sections(&items);
+    sections.sort();
+    assert_eq!(
+        sections,
+        [
+            ("code section headers", 3),
+            ("data count section headers", 2),
+            ("data section headers", 3),
+            ("element section headers", 3),
+            ("export section headers", 3),
+            ("function section headers", 3),
+            ("global section headers", 3),
+            ("import section headers", 3),
+            ("memory section headers", 3),
+            ("table section headers", 3),
+            ("type section headers", 3),
+            ("wasm magic bytes", 8),
+        ]
+    );
+
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn empty_sections_are_not_aggregated() {
+    let data = empty_sections();
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_aggregate_threshold(Some(0));
+    let items = twiggy_parser::parse_with_options(&data, &options).unwrap();
+
+    assert!(items
+        .iter()
+        .all(|item| item.category() != ir::ItemCategory::ElementSegment));
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn empty_sections_between_full_ones() {
+    // An empty element and data section around a function.
+    let data = [
+        b"\0asm\x01\0\0\0".as_slice(),
+        &[1, 4, 1, 0x60, 0, 0],
+        &[3, 2, 1, 0],
+        &[9, 1, 0],
+        &[10, 4, 1, 2, 0, 0x0b],
+        &[11, 1, 0],
+    ]
+    .concat();
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    let mut sections = sections(&items);
+    sections.sort();
+    assert_eq!(
+        sections,
+        [
+            // The function and code sections' headers.
+            ("code section headers", 6),
+            ("data section headers", 3),
+            ("element section headers", 3),
+            ("type section headers", 3),
+            ("wasm magic bytes", 8),
+        ]
+    );
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
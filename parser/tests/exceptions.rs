@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn id(items: &ir::Items, name: &str) -> ir::Id {
+    items
+        .iter()
+        .find(|item| item.name() == name)
+        .unwrap_or_else(|| panic!("no item named {}", name))
+        .id()
+}
+
+// In `exceptions.wasm`, tag 0 is the imported `env::exn` and tag 1 is the
+// defined `tag[0]`, both of type 0. `code[0]` throws tag 1 from a `try_table`
+// that catches tag 0, and `code[1]` catches tag 1 with a legacy `try`. The
+// defined tag is exported as "tag".
+#[test]
+fn tags_have_edges() {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../twiggy/tests/all/fixtures/exceptions.wasm");
+    let items = twiggy_parser::parse(&fs::read(path).unwrap()).unwrap();
+    assert!(items.warnings().is_empty());
+
+    let tag = items.get(id(&items, "tag[0]")).unwrap();
+    assert_eq!(tag.category(), ir::ItemCategory::Tag);
+    assert_eq!(tag.size(), 2);
+
+    let edge = |from, to| items.edge_kind(id(&items, from), id(&items, to));
+    assert_eq!(edge("code[0]", "tag[0]"), Some(ir::EdgeKind::Generic));
+    assert_eq!(
+        edge("code[0]", "import env::exn"),
+        Some(ir::EdgeKind::Generic)
+    );
+    assert_eq!(edge("code[1]", "tag[0]"), Some(ir::EdgeKind::Generic));
+    assert_eq!(edge("export \"tag\"", "tag[0]"), Some(ir::EdgeKind::Export));
+    assert_eq!(
+        edge("tag[0]", "type[0]: (i32) -> nil"),
+        Some(ir::EdgeKind::Type)
+    );
+    assert_eq!(
+        edge("import env::exn", "type[0]: (i32) -> nil"),
+        Some(ir::EdgeKind::Type)
+    );
+    assert_eq!(edge("code[1]", "import env::exn"), None);
+}
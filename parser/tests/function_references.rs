@@ -0,0 +1,33 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn id(items: &ir::Items, name: &str) -> ir::Id {
+    items
+        .iter()
+        .find(|item| item.name() == name)
+        .unwrap_or_else(|| panic!("no item named {}", name))
+        .id()
+}
+
+// In `function_references.wasm`, `main` calls `get_callback`, which returns a
+// `ref.func` of `callback`, and calls it with `call_ref` of type 0. The
+// exported `global[0]` is initialized with a `ref.func` of `from_global`.
+#[test]
+fn function_references_have_edges() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures/function_references.wasm");
+    let items = twiggy_parser::parse(&fs::read(path).unwrap()).unwrap();
+
+    let edge = |from, to| items.edge_kind(id(&items, from), id(&items, to));
+    assert_eq!(
+        edge("get_callback", "callback"),
+        Some(ir::EdgeKind::Generic)
+    );
+    assert_eq!(edge("main", "type[0]: () -> nil"), Some(ir::EdgeKind::Type));
+    assert_eq!(
+        edge("global[0]", "from_global"),
+        Some(ir::EdgeKind::Generic)
+    );
+    assert_eq!(edge("main", "callback"), None);
+}
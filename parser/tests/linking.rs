@@ -0,0 +1,95 @@
+use twiggy_ir as ir;
+
+mod support;
+
+// `linking.wasm` is an object file without a name section. Its only function
+// import is `log`, so `on_even` is function 1.
+fn linking() -> Vec<u8> {
+    support::fixture("linking.wasm")
+}
+
+/// Append a name section that names the function with the given index.
+fn with_function_name(mut data: Vec<u8>, index: u8, name: &str) -> Vec<u8> {
+    // One function name, in a function names subsection.
+    let mut names = vec![1, index, name.len() as u8];
+    names.extend_from_slice(name.as_bytes());
+    let mut payload = b"\x04name".to_vec();
+    payload.push(1);
+    payload.push(names.len() as u8);
+    payload.extend_from_slice(&names);
+    data.push(0);
+    data.push(payload.len() as u8);
+    data.extend_from_slice(&payload);
+    data
+}
+
+fn edges(items: &ir::Items, from: &str) -> Vec<(String, ir::EdgeKind)> {
+    let mut edges: Vec<_> = items
+        .edges(support::item(items, from).id())
+        .map(|(to, kind)| (items[to].name().to_string(), kind))
+        .collect();
+    edges.sort_by(|a, b| a.0.cmp(&b.0));
+    edges
+}
+
+#[test]
+fn symbols_name_functions_and_data() {
+    let items = twiggy_parser::parse(&linking()).unwrap();
+    for name in ["on_even", "on_odd", "dispatch", "run"] {
+        assert!(matches!(
+            support::item(&items, name).kind(),
+            ir::ItemKind::Code(_)
+        ));
+    }
+    for name in ["greeting", "counter", "handlers"] {
+        support::item(&items, &format!("data segment \"{}\"", name));
+    }
+}
+
+#[test]
+fn relocations_are_edges() {
+    let items = twiggy_parser::parse(&linking()).unwrap();
+    let edge = |to: &str, kind| (to.to_string(), kind);
+
+    // Code relocations, instead of guessing what `dispatch` calls through
+    // the table.
+    assert_eq!(
+        edges(&items, "dispatch"),
+        [
+            edge("data segment \"handlers\"", ir::EdgeKind::Data),
+            edge("import env::__linear_memory", ir::EdgeKind::Data),
+            edge("type[0]: () -> nil", ir::EdgeKind::Type),
+            edge("type[1]: (i32) -> nil", ir::EdgeKind::Type),
+        ]
+    );
+    assert_eq!(
+        edges(&items, "on_even"),
+        [
+            edge("data segment \"greeting\"", ir::EdgeKind::Data),
+            edge("import env::log", ir::EdgeKind::Call),
+            edge("type[0]: () -> nil", ir::EdgeKind::Type),
+        ]
+    );
+
+    // Data relocations.
+    assert_eq!(
+        edges(&items, "data segment \"handlers\""),
+        [
+            edge("on_even", ir::EdgeKind::Generic),
+            edge("on_odd", ir::EdgeKind::Generic),
+        ]
+    );
+    assert!(items.warnings().is_empty(), "{:?}", items.warnings());
+}
+
+#[test]
+fn name_section_is_preferred_over_symbols() {
+    let data = with_function_name(linking(), 1, "even");
+    let items = twiggy_parser::parse(&data).unwrap();
+    support::item(&items, "even");
+    support::item(&items, "on_odd");
+    assert!(items.get_item_by_name("on_even").is_none());
+
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
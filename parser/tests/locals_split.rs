@@ -0,0 +1,54 @@
+use twiggy_ir as ir;
+
+mod support;
+
+fn wee_alloc() -> Vec<u8> {
+    support::fixture("wee_alloc.wasm")
+}
+
+fn parse(data: &[u8], locals_split: bool) -> ir::Items {
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_locals_split(locals_split);
+    twiggy_parser::parse_with_options(data, &options).unwrap()
+}
+
+#[test]
+fn split_parts_add_up_to_the_function() {
+    let data = wee_alloc();
+    let whole = parse(&data, false);
+    let split = parse(&data, true);
+
+    let functions: Vec<_> = whole
+        .iter()
+        .filter(|item| matches!(item.kind(), ir::ItemKind::Code(_)))
+        .collect();
+    assert!(!functions.is_empty());
+    for function in functions {
+        let name = function.name();
+        let locals = support::item(&split, &format!("{} (locals)", name));
+        let instructions = support::item(&split, &format!("{} (instructions)", name));
+        let rest = support::item(&split, name);
+        assert_eq!(rest.id(), function.id());
+        assert_eq!(
+            rest.size() + locals.size() + instructions.size(),
+            function.size()
+        );
+        assert_eq!(locals.category(), ir::ItemCategory::Function);
+        assert_eq!(instructions.category(), ir::ItemCategory::Function);
+
+        let edges: Vec<_> = split.edges(rest.id()).collect();
+        assert!(edges.contains(&(locals.id(), ir::EdgeKind::Generic)));
+        assert!(edges.contains(&(instructions.id(), ir::EdgeKind::Generic)));
+    }
+
+    let total: u64 = split.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn functions_are_whole_by_default() {
+    let items = parse(&wee_alloc(), false);
+    assert!(items
+        .iter()
+        .all(|item| !item.name().ends_with(" (instructions)")));
+}
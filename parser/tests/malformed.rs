@@ -0,0 +1,65 @@
+// Sections whose declared number of entries is far larger than the section
+// itself must be rejected with an error, not by trying to allocate room for
+// every entry.
+
+fn module(section_id: u8, payload: &[u8]) -> Vec<u8> {
+    let mut data = b"\0asm\x01\0\0\0".to_vec();
+    data.push(section_id);
+    data.push(payload.len() as u8);
+    data.extend_from_slice(payload);
+    data
+}
+
+const HUGE_COUNT: [u8; 5] = [0xff, 0xff, 0xff, 0xff, 0x0f];
+
+#[test]
+fn huge_type_count() {
+    let data = module(1, &HUGE_COUNT);
+    assert!(twiggy_parser::parse(&data).is_err());
+}
+
+#[test]
+fn huge_code_count() {
+    let mut data = module(3, &[1, 0]);
+    data.push(10);
+    data.push(HUGE_COUNT.len() as u8);
+    data.extend_from_slice(&HUGE_COUNT);
+    assert!(twiggy_parser::parse(&data).is_err());
+}
+
+fn warnings(fixture: &str) -> Vec<String> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures")
+        .join(fixture);
+    let items = twiggy_parser::parse(&std::fs::read(path).unwrap()).unwrap();
+    items.warnings().to_vec()
+}
+
+// References to items that do not exist, as fuzzers produce, are left out of
+// the graph with a warning instead.
+#[test]
+fn unknown_indices_are_warnings() {
+    assert_eq!(
+        warnings("malformed_call_index.wasm"),
+        ["left out a reference to unknown function 5 (at offset 0x17)"]
+    );
+    assert_eq!(
+        warnings("malformed_export_index.wasm"),
+        ["left out a reference to unknown function 7 (at offset 0x15)"]
+    );
+    assert_eq!(
+        warnings("malformed_global_index.wasm"),
+        ["left out a reference to unknown global 2 (at offset 0x17)"]
+    );
+    assert_eq!(
+        warnings("malformed_start_index.wasm"),
+        ["left out a reference to unknown function 3 (at offset 0x14)"]
+    );
+    assert_eq!(
+        warnings("malformed_elem_index.wasm"),
+        [
+            "left out a reference to unknown table 0 (at offset 0x15)",
+            "left out a reference to unknown function 5 (at offset 0x1b)",
+        ]
+    );
+}
@@ -0,0 +1,33 @@
+use std::fs;
+use std::path::Path;
+use twiggy_traits as traits;
+
+fn parse(max_edges: Option<u64>) -> traits::Result<twiggy_ir::Items> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../twiggy/tests/all/fixtures/wee_alloc.wasm");
+    let data = fs::read(path).unwrap();
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_max_edges(max_edges);
+    twiggy_parser::parse_with_options(&data, &options)
+}
+
+#[test]
+fn heuristic_edges_are_dropped_to_stay_within_max_edges() {
+    let all = parse(None).unwrap();
+    assert_eq!(all.dropped_edges(), 0);
+
+    let max = all.edge_count() - 2;
+    let items = parse(Some(max)).unwrap();
+    assert_eq!(items.edge_count(), max);
+    assert_eq!(items.dropped_edges(), 2);
+}
+
+#[test]
+fn too_many_other_edges_is_an_error() {
+    match parse(Some(1)) {
+        Err(traits::Error::Analysis(msg)) => {
+            assert!(msg.contains("more than the maximum of 1"), "{}", msg)
+        }
+        other => panic!("expected an analysis error, got {:?}", other.map(|_| ())),
+    }
+}
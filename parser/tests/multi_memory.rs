@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+// `multi_memory.wasm` has a data segment at address 0 of each of its two
+// memories. `read_first` and `read_second` load from address 4 of the first
+// and the second memory, and `grow_second` grows the second.
+fn multi_memory() -> ir::Items {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures/multi_memory.wasm");
+    twiggy_parser::parse(&fs::read(path).unwrap()).unwrap()
+}
+
+fn edges(items: &ir::Items, from: &str) -> Vec<(String, ir::EdgeKind)> {
+    let from = items
+        .get_item_by_name(from)
+        .unwrap_or_else(|| panic!("no item named {}", from));
+    let mut edges: Vec<_> = items
+        .edges(from.id())
+        .filter(|&(_, kind)| kind != ir::EdgeKind::Type)
+        .map(|(to, kind)| (items[to].name().to_string(), kind))
+        .collect();
+    edges.sort_by(|a, b| a.0.cmp(&b.0));
+    edges
+}
+
+#[test]
+fn loads_find_the_data_of_their_own_memory() {
+    let items = multi_memory();
+    let edge = |to: &str, kind| (to.to_string(), kind);
+    assert_eq!(
+        edges(&items, "read_first"),
+        [
+            edge("data[0]", ir::EdgeKind::Heuristic),
+            edge("memory 'first'", ir::EdgeKind::Data),
+        ]
+    );
+    assert_eq!(
+        edges(&items, "read_second"),
+        [
+            edge("data[1]", ir::EdgeKind::Heuristic),
+            edge("memory 'second'", ir::EdgeKind::Data),
+        ]
+    );
+    assert_eq!(
+        edges(&items, "grow_second"),
+        [edge("memory 'second'", ir::EdgeKind::Data)]
+    );
+}
@@ -0,0 +1,41 @@
+use twiggy_ir as ir;
+
+mod support;
+
+#[test]
+fn export_name_bytes_are_the_length_prefixed_name() {
+    let items = twiggy_parser::parse(&support::fixture("wee_alloc.wasm")).unwrap();
+    let mut exports: Vec<(&str, u64, Option<u64>)> = items
+        .iter()
+        .filter(|item| item.category() == ir::ItemCategory::Export)
+        .map(|item| (item.name(), item.size(), items.name_bytes(item.id())))
+        .collect();
+    exports.sort_unstable();
+    // One byte of length prefix before each name, and a kind byte and a
+    // one-byte index after it.
+    assert_eq!(
+        exports,
+        [
+            ("export \"goodbye\"", 10, Some(8)),
+            ("export \"hello\"", 8, Some(6)),
+            ("export \"memory\"", 9, Some(7)),
+        ]
+    );
+}
+
+#[test]
+fn every_import_and_export_has_name_bytes() {
+    let items = twiggy_parser::parse(&support::fixture("mono.wasm")).unwrap();
+    let mut checked = 0;
+    for item in items.iter() {
+        match item.category() {
+            ir::ItemCategory::Import | ir::ItemCategory::Export => {
+                let name_bytes = items.name_bytes(item.id()).unwrap();
+                assert!(0 < name_bytes && name_bytes < item.size());
+                checked += 1;
+            }
+            _ => assert_eq!(items.name_bytes(item.id()), None),
+        }
+    }
+    assert_eq!(checked, 126 + 210);
+}
@@ -0,0 +1,69 @@
+mod support;
+
+// `duplicate_names.wasm` defines three functions and two name sections. The
+// first has two "function names" subsections, naming functions 0 and 1
+// `llvm_a` and `llvm_b`, then functions 0 and 2 `post_a` and `post_c`. The
+// second names function 1 `second_b`.
+
+#[test]
+fn duplicate_name_sections_are_merged_first_wins() {
+    let items = twiggy_parser::parse(&support::fixture("duplicate_names.wasm")).unwrap();
+    let mut names: Vec<&str> = items
+        .iter()
+        .filter(|item| matches!(item.kind(), twiggy_ir::ItemKind::Code(_)))
+        .map(|item| item.name())
+        .collect();
+    names.sort_unstable();
+    assert_eq!(names, ["llvm_a", "llvm_b", "post_c"]);
+}
+
+#[test]
+fn duplicate_name_subsections_are_each_accounted_for() {
+    let data = support::fixture("duplicate_names.wasm");
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    let mut subsections: Vec<u64> = items
+        .iter()
+        .filter(|item| item.name() == "\"function names\" subsection")
+        .map(|item| item.size())
+        .collect();
+    subsections.sort_unstable();
+    assert_eq!(subsections, [13, 19, 19]);
+
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+// `entity_names.wasm` imports global 0 and defines one each of a type, table,
+// memory, global, and element segment, all of which its name section names.
+// The imported global is named too, so the defined global is global 1.
+
+#[test]
+fn name_section_names_types_tables_memories_globals_and_elements() {
+    let items = twiggy_parser::parse(&support::fixture("entity_names.wasm")).unwrap();
+    let mut names: Vec<&str> = items
+        .iter()
+        .filter(|item| {
+            matches!(
+                item.category(),
+                twiggy_ir::ItemCategory::Type
+                    | twiggy_ir::ItemCategory::Table
+                    | twiggy_ir::ItemCategory::Memory
+                    | twiggy_ir::ItemCategory::Global
+                    | twiggy_ir::ItemCategory::ElementSegment
+            )
+        })
+        .map(|item| item.name())
+        .collect();
+    names.sort_unstable();
+    assert_eq!(
+        names,
+        [
+            "elem 'funcs'",
+            "global 'stack_pointer'",
+            "memory 'mem'",
+            "table 'tbl'",
+            "type 'sig': () -> nil",
+        ]
+    );
+}
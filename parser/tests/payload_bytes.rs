@@ -0,0 +1,39 @@
+use twiggy_ir as ir;
+
+mod support;
+
+#[test]
+fn data_segment_payload_bytes_leave_out_headers() {
+    let items = twiggy_parser::parse(&support::fixture("wee_alloc.wasm")).unwrap();
+    let mut segments: Vec<(&str, u64, Option<u64>)> = items
+        .iter()
+        .filter(|item| item.category() == ir::ItemCategory::DataSegment)
+        .map(|item| (item.name(), item.size(), items.payload_bytes(item.id())))
+        .collect();
+    segments.sort_unstable();
+    // Each active segment has a kind byte, an `i32.const` offset expression
+    // and its `end`, and a length prefix before its payload.
+    assert_eq!(
+        segments,
+        [
+            ("data[0]", 9, Some(4)),
+            ("data[1]", 25, Some(20)),
+            ("data[2]", 25, Some(20)),
+            ("data[3]", 1034, Some(1028)),
+        ]
+    );
+}
+
+#[test]
+fn aggregated_data_segments_add_up_their_payloads() {
+    let data = support::fixture("wee_alloc.wasm");
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_aggregate_threshold(Some(2));
+    let items = twiggy_parser::parse_with_options(&data, &options).unwrap();
+    let aggregate = items
+        .iter()
+        .find(|item| item.category() == ir::ItemCategory::DataSegment)
+        .unwrap();
+    assert_eq!(aggregate.size(), 1093);
+    assert_eq!(items.payload_bytes(aggregate.id()), Some(1072));
+}
@@ -0,0 +1,92 @@
+use twiggy_ir as ir;
+
+/// A module with nothing but a `producers` custom section with the given
+/// fields, each with its name and values.
+fn module(fields: &[(&str, &[(&str, &str)])]) -> Vec<u8> {
+    fn string(data: &mut Vec<u8>, s: &str) {
+        data.push(s.len() as u8);
+        data.extend_from_slice(s.as_bytes());
+    }
+
+    let mut payload = vec![];
+    string(&mut payload, "producers");
+    payload.push(fields.len() as u8);
+    for &(field, values) in fields {
+        string(&mut payload, field);
+        payload.push(values.len() as u8);
+        for &(name, version) in values {
+            string(&mut payload, name);
+            string(&mut payload, version);
+        }
+    }
+
+    let mut data = b"\0asm\x01\0\0\0".to_vec();
+    data.push(0);
+    data.push(payload.len() as u8);
+    data.extend_from_slice(&payload);
+    data
+}
+
+fn sizes(items: &ir::Items) -> Vec<(&str, u64)> {
+    let mut sizes: Vec<_> = items
+        .iter()
+        .filter(|item| item.id() != items.meta_root())
+        .map(|item| (item.name(), item.size()))
+        .collect();
+    sizes.sort();
+    sizes
+}
+
+#[test]
+fn producers_section_is_split_into_values() {
+    let data = module(&[
+        ("language", &[("Rust", "")]),
+        (
+            "processed-by",
+            &[("rustc", "1.77.0"), ("wasm-bindgen", "0.2.92")],
+        ),
+    ]);
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    assert_eq!(
+        sizes(&items),
+        [
+            ("custom section 'producers' headers", 37),
+            ("language: Rust", 6),
+            ("processed-by: rustc 1.77.0", 13),
+            ("processed-by: wasm-bindgen 0.2.92", 20),
+            ("wasm magic bytes", 8),
+        ]
+    );
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+
+    assert_eq!(
+        items.producers(),
+        [
+            ir::Producer::new("language", "Rust", ""),
+            ir::Producer::new("processed-by", "rustc", "1.77.0"),
+            ir::Producer::new("processed-by", "wasm-bindgen", "0.2.92"),
+        ]
+    );
+}
+
+#[test]
+fn malformed_producers_section_is_one_item() {
+    // Only `language`, `processed-by` and `sdk` are valid field names.
+    let data = module(&[
+        ("language", &[("Rust", "")]),
+        ("compiled-by", &[("rustc", "1.77.0")]),
+    ]);
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    assert_eq!(
+        sizes(&items),
+        [
+            ("custom section 'producers'", 43),
+            ("custom section 'producers' headers", 12),
+            ("wasm magic bytes", 8),
+        ]
+    );
+    assert!(items.producers().is_empty());
+}
@@ -0,0 +1,65 @@
+use twiggy_ir as ir;
+
+mod support;
+
+const TYPES: (u8, &[u8]) = (1, &[1, 0x60, 0, 0]);
+const EMPTY_FUNCTIONS: (u8, &[u8]) = (3, &[0]);
+const EMPTY_CODE: (u8, &[u8]) = (10, &[0]);
+
+fn code_size(items: &ir::Items, name: &str) -> u64 {
+    items
+        .iter()
+        .find(|item| item.name() == name && matches!(item.kind(), ir::ItemKind::Code(_)))
+        .unwrap_or_else(|| panic!("no function named {}", name))
+        .size()
+}
+
+// `interleaved_custom.wasm` has two custom sections between its function and
+// code sections, and its export section sits between them too. `main` calls
+// `helper`.
+
+#[test]
+fn custom_sections_between_function_and_code() {
+    let data = support::fixture("interleaved_custom.wasm");
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    assert_eq!(code_size(&items, "main"), 6);
+    assert_eq!(code_size(&items, "helper"), 8);
+
+    let main = items.iter().find(|item| item.name() == "main").unwrap();
+    let helper = items.iter().find(|item| item.name() == "helper").unwrap();
+    assert!(items.neighbors(main.id()).any(|id| id == helper.id()));
+
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn empty_function_section_without_code_section() {
+    let data = support::module(&[TYPES, EMPTY_FUNCTIONS]);
+    let items = twiggy_parser::parse(&data).unwrap();
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn empty_code_section_without_function_section() {
+    let data = support::module(&[TYPES, EMPTY_CODE]);
+    let items = twiggy_parser::parse(&data).unwrap();
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn empty_function_and_code_sections() {
+    let data = support::module(&[TYPES, EMPTY_FUNCTIONS, EMPTY_CODE]);
+    let items = twiggy_parser::parse(&data).unwrap();
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn code_section_before_function_section_is_an_error() {
+    let data = support::module(&[TYPES, (10, &[1, 2, 0, 0x0b]), (3, &[1, 0])]);
+    assert!(twiggy_parser::parse(&data).is_err());
+}
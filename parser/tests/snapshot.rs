@@ -0,0 +1,23 @@
+use twiggy_ir as ir;
+use twiggy_traits as traits;
+
+#[test]
+fn unreadable_snapshot_is_a_snapshot_error() {
+    // The magic bytes and version, but no items.
+    let mut data = ir::SNAPSHOT_MAGIC.to_vec();
+    data.extend_from_slice(&ir::SNAPSHOT_VERSION.to_le_bytes());
+
+    let error = twiggy_parser::parse_file_with_options(
+        "old.twiggy",
+        &data,
+        traits::ParseMode::Auto,
+        &twiggy_parser::ParseOptions::new(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(error, traits::Error::Snapshot(ref message)
+            if message.starts_with("malformed twiggy snapshot")),
+        "{:?}",
+        error
+    );
+}
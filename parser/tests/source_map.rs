@@ -0,0 +1,93 @@
+use std::path::Path;
+use twiggy_ir as ir;
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures")
+        .join(name)
+}
+
+fn parse_with_source_map(source_map: &Path) -> twiggy_traits::Result<ir::Items> {
+    let mut options = twiggy_parser::ParseOptions::new();
+    options.set_source_map(Some(source_map.to_path_buf()));
+    twiggy_parser::read_and_parse_with_options(
+        fixture("sourcemap.wasm"),
+        twiggy_traits::ParseMode::Auto,
+        &options,
+    )
+}
+
+/// A function's name, and the file and line of its source location.
+type Location<'a> = (&'a str, Option<(&'a str, Option<u32>)>);
+
+fn source_locations(items: &ir::Items) -> Vec<Location<'_>> {
+    let mut locations: Vec<_> = items
+        .iter()
+        .filter(|item| matches!(item.kind(), ir::ItemKind::Code(_)))
+        .map(|item| {
+            let location = item
+                .source_location()
+                .map(|location| (location.file().as_str(), location.line()));
+            (item.name(), location)
+        })
+        .collect();
+    locations.sort();
+    locations
+}
+
+// `sourcemap.py` maps each function body to a source file, except for one
+// that it leaves unmapped, and one that it splits between two files.
+#[test]
+fn functions_get_the_source_file_most_of_their_body_is_mapped_to() {
+    let items = parse_with_source_map(&fixture("sourcemap.wasm.map")).unwrap();
+    let lib = "src/lib.rs";
+    let imp = "src/imp_static_array.rs";
+    let size_classes = "src/size_classes.rs";
+    assert_eq!(
+        source_locations(&items),
+        [
+            (
+                "<wee_alloc::LargeAllocPolicy as wee_alloc::AllocPolicy>::min_cell_size::hc7cee2a550987099",
+                Some((imp, Some(19)))
+            ),
+            (
+                "<wee_alloc::LargeAllocPolicy as wee_alloc::AllocPolicy>::new_cell_for_free_list::h8f071b7bce0301ba",
+                Some((lib, Some(1)))
+            ),
+            (
+                "<wee_alloc::size_classes::SizeClassAllocPolicy<'a> as wee_alloc::AllocPolicy>::min_cell_size::h6f746be886573355",
+                Some((imp, Some(110)))
+            ),
+            (
+                "<wee_alloc::size_classes::SizeClassAllocPolicy<'a> as wee_alloc::AllocPolicy>::new_cell_for_free_list::h3987e3054b8224e6",
+                Some((lib, Some(76)))
+            ),
+            ("__wasm_nullptr", Some((lib, Some(160)))),
+            ("core::ptr::drop_in_place::h4e5cdfd7b9310648.18", Some((lib, Some(108)))),
+            ("core::ptr::drop_in_place::h8e9fdc2437d43666", Some((size_classes, Some(74)))),
+            ("goodbye", Some((lib, Some(151)))),
+            ("hello", Some((size_classes, Some(111)))),
+            // A third of its body is mapped to `src/lib.rs`, and the rest to
+            // `src/size_classes.rs`, from line 38 on.
+            ("wee_alloc::alloc_first_fit::h9a72de3af77ef93f", Some((size_classes, Some(38)))),
+            ("wee_alloc::alloc_with_refill::hb32c1bbce9ebda8e", None),
+        ]
+    );
+}
+
+#[test]
+fn source_map_is_found_by_the_source_mapping_url() {
+    let by_path = parse_with_source_map(&fixture("sourcemap.wasm.map")).unwrap();
+    let by_url = parse_with_source_map(&fixture("")).unwrap();
+    assert_eq!(source_locations(&by_path), source_locations(&by_url));
+}
+
+#[test]
+fn malformed_source_map_is_an_error() {
+    let err = parse_with_source_map(&fixture("wee_alloc_budget.json")).unwrap_err();
+    assert!(
+        err.to_string().starts_with("invalid source map: "),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,42 @@
+use twiggy_ir as ir;
+
+mod support;
+
+// `start_data_count.wasm` has a start section naming `init`, and a data count
+// section for `init`'s `memory.init` and `data.drop` to refer to its passive
+// data segment by.
+fn start_data_count() -> Vec<u8> {
+    support::fixture("start_data_count.wasm")
+}
+
+#[test]
+fn start_and_data_count_sections_have_entries() {
+    let data = start_data_count();
+    let items = twiggy_parser::parse(&data).unwrap();
+
+    for (entry, headers) in [
+        ("start function", "start section headers"),
+        ("data count", "data count section headers"),
+    ] {
+        let entry = support::item(&items, entry);
+        assert_eq!(entry.size(), 1);
+        assert!(items.is_root(entry.id()));
+        assert_eq!(items.byte_ranges(entry.id()).len(), 1);
+        assert_eq!(support::item(&items, headers).size(), 2);
+    }
+
+    let total: u64 = items.iter().map(|item| item.size()).sum();
+    assert_eq!(total, data.len() as u64);
+}
+
+#[test]
+fn start_function_is_kept_alive_by_the_start_section() {
+    let items = twiggy_parser::parse(&start_data_count()).unwrap();
+    let start = support::item(&items, "start function").id();
+    let init = support::item(&items, "init").id();
+
+    assert_eq!(
+        items.edges(start).collect::<Vec<_>>(),
+        [(init, ir::EdgeKind::Call)]
+    );
+}
@@ -0,0 +1,35 @@
+//! Helpers shared by the parser's tests.
+
+// Each test uses what it needs of these.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+/// The contents of the fixture with the given name, from the CLI's tests.
+pub fn fixture(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../twiggy/tests/all/fixtures")
+        .join(name);
+    fs::read(path).unwrap()
+}
+
+/// The first item with the given name.
+pub fn item<'a>(items: &'a ir::Items, name: &str) -> &'a ir::Item {
+    items
+        .get_item_by_name(name)
+        .unwrap_or_else(|| panic!("no item named {}", name))
+}
+
+/// A wasm module made of the given sections, each an id and a payload of
+/// fewer than 128 bytes.
+pub fn module(sections: &[(u8, &[u8])]) -> Vec<u8> {
+    let mut data = b"\0asm\x01\0\0\0".to_vec();
+    for &(id, payload) in sections {
+        data.push(id);
+        data.push(payload.len() as u8);
+        data.extend_from_slice(payload);
+    }
+    data
+}
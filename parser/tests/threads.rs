@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::Path;
+use twiggy_ir as ir;
+
+/// Everything about the parsed items that an analysis can observe.
+fn describe(items: &ir::Items) -> Vec<String> {
+    items
+        .iter()
+        .map(|item| {
+            let edges: Vec<String> = items
+                .neighbors(item.id())
+                .map(|to| format!("{}:{:?}", to, items.edge_kind(item.id(), to)))
+                .collect();
+            format!(
+                "{} {} {} {:?} -> {}",
+                item.id(),
+                item.name(),
+                item.size(),
+                item.category(),
+                edges.join(", ")
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn parallel_parse_matches_sequential_parse() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("../twiggy/tests/all/fixtures");
+    let mut parsed = 0;
+    for entry in fs::read_dir(fixtures).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("wasm") {
+            continue;
+        }
+        let data = fs::read(&path).unwrap();
+
+        let sequential = twiggy_parser::parse_with_threads(&data, 1);
+        for threads in [2, 4, 16] {
+            let parallel = twiggy_parser::parse_with_threads(&data, threads);
+            match (&sequential, &parallel) {
+                (Ok(sequential), Ok(parallel)) => {
+                    assert_eq!(
+                        describe(sequential),
+                        describe(parallel),
+                        "{} parsed with {} threads",
+                        path.display(),
+                        threads
+                    );
+                }
+                (Err(sequential), Err(parallel)) => {
+                    assert_eq!(sequential.to_string(), parallel.to_string());
+                }
+                _ => panic!(
+                    "{} parsed with {} threads: only one parse failed",
+                    path.display(),
+                    threads
+                ),
+            }
+        }
+        parsed += 1;
+    }
+    assert!(parsed > 0);
+}
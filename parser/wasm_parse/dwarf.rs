@@ -0,0 +1,207 @@
+//! Splitting up the DWARF debug info in a wasm module's `.debug_info` and
+//! `.debug_line` custom sections, by the compilation units and functions it
+//! describes.
+//!
+//! The addresses in wasm DWARF are offsets into the code section's payload,
+//! so a function's debug info can be tied to its body. A section that does not
+//! decode is left as a single item, like any other custom section.
+
+use foldhash::{HashMap, HashMapExt};
+use std::ops::Range;
+use twiggy_ir::{self as ir, Id};
+use twiggy_traits as traits;
+
+use super::{iterate_with_range, CodeSection, IndexedSection};
+use crate::dwarf;
+
+/// A part of a DWARF section that gets an item of its own.
+struct Entry {
+    name: String,
+    // The ranges of the binary that the entry takes up. A unit's entry is
+    // whatever the entries of its functions leave of it.
+    ranges: Vec<Range<u64>>,
+    // The code section offset of the function that the entry describes.
+    low_pc: Option<u64>,
+}
+
+/// The entries of a module's DWARF sections, by section index. A section that
+/// could not be split up has none.
+#[derive(Default)]
+pub(crate) struct Dwarf {
+    entries: HashMap<usize, Vec<Entry>>,
+}
+
+impl Dwarf {
+    /// Split up the DWARF sections among the given ones.
+    pub(crate) fn new(sections: &[IndexedSection<'_>]) -> Dwarf {
+        let mut dwarf = dwarf::Sections::default();
+        let (mut info, mut line) = (None, None);
+        for IndexedSection(idx, section) in sections {
+            let wasmparser::Payload::CustomSection(reader) = section else {
+                continue;
+            };
+            let data = Some(reader.data());
+            match reader.name() {
+                ".debug_info" => {
+                    dwarf.info = data;
+                    info = Some((*idx, reader.data_offset() as u64));
+                }
+                ".debug_abbrev" => dwarf.abbrev = data,
+                ".debug_str" => dwarf.str = data,
+                ".debug_line_str" => dwarf.line_str = data,
+                ".debug_str_offsets" => dwarf.str_offsets = data,
+                ".debug_addr" => dwarf.addr = data,
+                ".debug_line" => line = Some((*idx, reader.data(), reader.data_offset() as u64)),
+                _ => {}
+            }
+        }
+
+        let mut entries = HashMap::new();
+        // The units of `.debug_line` are named after the units of
+        // `.debug_info` that refer to them, if there are any.
+        let mut line_units = HashMap::new();
+        if let Some((idx, offset)) = info {
+            match info_entries(dwarf.units(), offset, &mut line_units) {
+                Some(info) => {
+                    entries.insert(idx, info);
+                }
+                None => line_units.clear(),
+            }
+        }
+        if let Some((idx, data, offset)) = line {
+            if let Some(units) = line_entries(data, offset, &line_units) {
+                entries.insert(idx, units);
+            }
+        }
+        Dwarf { entries }
+    }
+
+    /// Whether the section with the given index was split up.
+    pub(crate) fn is_split(&self, idx: usize) -> bool {
+        self.entries.contains_key(&idx)
+    }
+
+    /// Add an item for each entry of the section with the given index.
+    pub(crate) fn add_items(&self, items: &mut ir::ItemsBuilder, idx: usize) {
+        for (i, entry) in self.entries.get(&idx).into_iter().flatten().enumerate() {
+            let id = Id::entry(idx, i);
+            let size = entry
+                .ranges
+                .iter()
+                .map(|range| range.end - range.start)
+                .sum();
+            for range in &entry.ranges {
+                items.add_byte_range(id, range.clone());
+            }
+            items.add_item(ir::Item::new(
+                id,
+                entry.name.clone(),
+                size,
+                ir::DebugInfo::new(),
+            ));
+        }
+    }
+
+    /// Add an edge from the debug info of each function to the body in the
+    /// code section that its address falls within.
+    pub(crate) fn add_edges(
+        &self,
+        items: &mut ir::ItemsBuilder,
+        code_section: Option<&CodeSection<'_>>,
+    ) -> traits::Result<()> {
+        let Some(code_section) = code_section else {
+            return Ok(());
+        };
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        let payload = code_section.reader.range().start as u64;
+        let mut bodies = vec![];
+        for body in iterate_with_range(code_section.reader.clone()) {
+            let (_, range) = body?;
+            bodies.push(range.start - payload..range.end - payload);
+        }
+
+        for (&idx, entries) in &self.entries {
+            for (i, entry) in entries.iter().enumerate() {
+                let Some(low_pc) = entry.low_pc else {
+                    continue;
+                };
+                let b = bodies.partition_point(|body| body.end <= low_pc);
+                if bodies.get(b).is_some_and(|body| body.contains(&low_pc)) {
+                    items.add_edge(Id::entry(idx, i), Id::entry(code_section.index, b));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Split `.debug_info`, at the given offset in the binary, into an entry for
+/// each unit and for each function defined in it, and record the name of
+/// each unit by the offset of its line program. `None` if a unit is
+/// malformed.
+fn info_entries(
+    units: dwarf::Units<'_, '_>,
+    offset: u64,
+    line_units: &mut HashMap<u64, String>,
+) -> Option<Vec<Entry>> {
+    let at = |range: Range<usize>| offset + range.start as u64..offset + range.end as u64;
+    let mut entries = vec![];
+    for unit in units {
+        let unit = unit?;
+        let label = match &unit.name {
+            Some(name) => format!("unit '{}'", name),
+            None => format!("unit at offset {:#x}", unit.range.start),
+        };
+        if let Some(stmt_list) = unit.stmt_list {
+            line_units.insert(stmt_list, label.clone());
+        }
+
+        let mut ranges = vec![];
+        let mut last = unit.range.start;
+        for function in &unit.functions {
+            ranges.push(at(last..function.range.start));
+            last = function.range.end;
+        }
+        ranges.push(at(last..unit.range.end));
+        ranges.retain(|range| range.start < range.end);
+        entries.push(Entry {
+            name: format!(".debug_info: {}", label),
+            ranges,
+            low_pc: None,
+        });
+        for function in unit.functions {
+            let low_pc = function.low_pc;
+            let name = function
+                .name
+                .unwrap_or_else(|| format!("function at {:#x}", low_pc));
+            entries.push(Entry {
+                name: format!(".debug_info: {}", name),
+                ranges: vec![at(function.range)],
+                low_pc: Some(low_pc),
+            });
+        }
+    }
+    Some(entries)
+}
+
+/// Split `.debug_line`, at the given offset in the binary, into an entry for
+/// each unit's line program, or `None` if it is malformed.
+fn line_entries(data: &[u8], offset: u64, units: &HashMap<u64, String>) -> Option<Vec<Entry>> {
+    let entries = dwarf::line_programs(data)?
+        .into_iter()
+        .map(|program| {
+            let label = units
+                .get(&(program.start as u64))
+                .cloned()
+                .unwrap_or_else(|| format!("unit at offset {:#x}", program.start));
+            Entry {
+                name: format!(".debug_line: {}", label),
+                ranges: vec![offset + program.start as u64..offset + program.end as u64],
+                low_pc: None,
+            }
+        })
+        .collect();
+    Some(entries)
+}
@@ -1,30 +1,224 @@
 use super::Parse;
-use anyhow::anyhow;
-use std::collections::HashMap;
+use crate::source_map::SourceMap;
+use foldhash::fast::{FixedState, FoldHasher};
+use foldhash::{HashMap, HashMapExt};
+use std::cmp;
+use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hasher};
 use std::ops::Range;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
 use twiggy_ir::{self as ir, Id};
+use twiggy_traits as traits;
 use wasmparser::{self, FromReader, NameSectionReader, Operator, RefType, SectionLimited, ValType};
 
-#[derive(Default)]
+mod dwarf;
+
+#[derive(Debug, Default)]
 pub struct SectionIndices {
-    type_: Option<usize>,
+    types: Vec<Option<Id>>,
+    // The index of the first type with the same signature as each type, which
+    // `call_indirect` treats the same. Types in explicit recursion groups are
+    // only the same as themselves.
+    canonical_types: Vec<u32>,
     code: Option<usize>,
     functions: Vec<Id>,
+    // The canonical type index of each function.
+    function_types: Vec<u32>,
     tables: Vec<Id>,
     memories: Vec<Id>,
     globals: Vec<Id>,
+    // The value of each global that is immutable and initialized with a
+    // constant, which code may add offsets to and load data from.
+    global_values: Vec<Option<i64>>,
+    tags: Vec<Id>,
+    // The functions that active element segments place in each table, by
+    // table index and canonical type index: the candidates for an indirect
+    // call through that table with that type.
+    table_functions: HashMap<(u32, u32), Vec<Id>>,
+    // The item that each symbol in the `linking` section of an object file
+    // stands for, if the module defines it.
+    symbols: Vec<Option<Id>>,
+    // The sections that an object file's relocations apply to, by the number
+    // of the section in the module's order.
+    relocatable: HashMap<u32, RelocatableSection>,
+    // Whether the code section has relocations, which say exactly what its
+    // function bodies refer to, so that no heuristics are needed.
+    code_relocated: bool,
+}
+
+/// A section that relocations apply to: the offset its contents start at, which
+/// relocation offsets are relative to, and the bytes and item of each entry.
+#[derive(Debug)]
+struct RelocatableSection {
+    start: u64,
+    entries: Vec<(Range<u64>, Id)>,
+}
+
+impl SectionIndices {
+    /// The canonical index of the type with the given index, or the index
+    /// itself if there is no such type.
+    fn canonical_type(&self, index: u32) -> u32 {
+        self.canonical_types
+            .get(index as usize)
+            .copied()
+            .unwrap_or(index)
+    }
+
+    /// Record that an active element segment places the function with the
+    /// given index in the given table. Indices that are out of bounds are left
+    /// for the element section's own edges to report.
+    fn add_table_function(&mut self, table: u32, function: u32) {
+        let (Some(&id), Some(&ty)) = (
+            self.functions.get(function as usize),
+            self.function_types.get(function as usize),
+        ) else {
+            return;
+        };
+        let candidates = self.table_functions.entry((table, ty)).or_default();
+        if !candidates.contains(&id) {
+            candidates.push(id);
+        }
+    }
+}
+
+/// The value of the given global, if it is immutable and initialized with a
+/// constant.
+fn constant_value(global: &wasmparser::Global<'_>) -> Option<i64> {
+    if global.ty.mutable {
+        return None;
+    }
+    let mut ops = global.init_expr.get_operators_reader();
+    let value = match ops.read().ok()? {
+        Operator::I32Const { value } => i64::from(value),
+        Operator::I64Const { value } => value,
+        _ => return None,
+    };
+    match ops.read().ok()? {
+        Operator::End => Some(value),
+        _ => None,
+    }
+}
+
+/// How many entries to reserve room for when reading the given section. Every
+/// entry takes at least one byte, so a malformed count that is larger than the
+/// section itself does not cause a huge allocation.
+fn capacity<T>(section: &SectionLimited<'_, T>) -> usize {
+    cmp::min(section.count() as usize, section.range().len())
+}
+
+/// How many items to reserve room for the entries of the given section, other
+/// than the function and code sections.
+fn entry_capacity(section: &wasmparser::Payload<'_>) -> usize {
+    match section {
+        wasmparser::Payload::TypeSection(reader) => capacity(reader),
+        wasmparser::Payload::ImportSection(reader) => capacity(reader),
+        wasmparser::Payload::TableSection(reader) => capacity(reader),
+        wasmparser::Payload::MemorySection(reader) => capacity(reader),
+        wasmparser::Payload::GlobalSection(reader) => capacity(reader),
+        wasmparser::Payload::TagSection(reader) => capacity(reader),
+        wasmparser::Payload::ExportSection(reader) => capacity(reader),
+        wasmparser::Payload::ElementSection(reader) => capacity(reader),
+        wasmparser::Payload::DataSection(reader) => capacity(reader),
+        _ => 0,
+    }
+}
+
+/// Whether a section with `count` entries is represented by a single aggregate
+/// item, rather than by one item per entry.
+fn aggregated(count: u32, threshold: Option<u32>) -> bool {
+    threshold.is_some_and(|threshold| count > threshold)
+}
+
+/// The name of the item aggregating all `count` entries of a section, which
+/// records the threshold that caused them to be aggregated.
+fn aggregate_name(prefix: &str, count: u32, size: u64, threshold: Option<u32>) -> String {
+    format!(
+        "{}[0..{}] ({} entries, {} bytes; aggregated by --aggregate-threshold {})",
+        prefix,
+        count,
+        count,
+        size,
+        threshold.unwrap_or_default()
+    )
+}
+
+/// Look up the `Id` at `index` in one of a module's index spaces. If the module
+/// refers to something that does not exist, as malformed and fuzzed modules
+/// do, a warning is added instead, and the edge to it is left out.
+fn lookup<T: Copy>(
+    items: &mut ir::ItemsBuilder,
+    space: &[T],
+    kind: &str,
+    index: u32,
+    offset: usize,
+) -> Option<T> {
+    let found = space.get(index as usize).copied();
+    if found.is_none() {
+        items.add_warning(format!(
+            "left out a reference to unknown {} {} (at offset {:#x})",
+            kind, index, offset
+        ));
+    }
+    found
+}
+
+/// Check that none of the sections that a module may only have one of is
+/// repeated. Only one of each is kept while parsing, so the sizes of the items
+/// would no longer add up to the size of the module. `wasmparser` rejects a
+/// repeated section too, but only as out of order, without naming it.
+///
+/// Other problems with the section headers are left for `wasmparser` to
+/// report.
+fn check_unique_sections(data: &[u8]) -> traits::Result<()> {
+    const UNIQUE: [(u8, &str); 4] = [
+        (3, "function"),
+        (8, "start"),
+        (10, "code"),
+        (12, "data count"),
+    ];
+    let mut first: [Option<usize>; UNIQUE.len()] = [None; UNIQUE.len()];
+    let mut reader = wasmparser::BinaryReader::new(data, 0);
+    if reader.read_bytes(8).is_err() {
+        return Ok(());
+    }
+    while !reader.eof() {
+        let start = reader.original_position();
+        let (Ok(id), Ok(size)) = (reader.read_u8(), reader.read_var_u32()) else {
+            break;
+        };
+        if let Some(i) = UNIQUE.iter().position(|&(unique, _)| unique == id) {
+            let name = UNIQUE[i].1;
+            if let Some(first) = first[i] {
+                return Err(traits::Error::Malformed {
+                    section: Some(format!("{} section headers", name)),
+                    offset: start,
+                    message: format!(
+                        "duplicate {} section, the first one is at offset {:#x}",
+                        name, first
+                    ),
+                });
+            }
+            first[i] = Some(start);
+        }
+        if reader.read_bytes(size as usize).is_err() {
+            break;
+        }
+    }
+    Ok(())
 }
 
 struct IndexedSection<'a>(usize, wasmparser::Payload<'a>);
 
+#[derive(Clone)]
 struct CodeSection<'a> {
     index: usize,
     reader: wasmparser::CodeSectionReader<'a>,
     byte_size: usize,
 }
 
+#[derive(Clone)]
 struct FunctionSection<'a> {
-    index: usize,
     reader: wasmparser::FunctionSectionReader<'a>,
     byte_size: usize,
 }
@@ -52,11 +246,11 @@ impl<'a> ModuleReader<'a> {
         self.offset == self.data.len()
     }
 
-    fn read(&mut self) -> anyhow::Result<wasmparser::Payload<'a>> {
+    fn read(&mut self) -> traits::Result<wasmparser::Payload<'a>> {
         let (section, bytes_consumed) =
             match self.parser.parse(&self.data[self.offset..], self.eof())? {
                 wasmparser::Chunk::NeedMoreData { .. } => {
-                    return Err(anyhow!("wasm binary cannot be fully parsed"));
+                    return Err(self.truncated());
                 }
                 wasmparser::Chunk::Parsed { consumed, payload } => (payload, consumed),
             };
@@ -64,59 +258,70 @@ impl<'a> ModuleReader<'a> {
         Ok(section)
     }
 
+    fn truncated(&self) -> traits::Error {
+        traits::Error::Malformed {
+            section: None,
+            offset: self.offset,
+            message: "wasm binary cannot be fully parsed".to_string(),
+        }
+    }
+
     fn new_code_section(
         &self,
         index: usize,
         start_offset: usize,
         byte_range: Range<usize>,
-    ) -> anyhow::Result<CodeSection<'a>> {
+    ) -> traits::Result<CodeSection<'a>> {
+        let data = self
+            .data
+            .get(byte_range.clone())
+            .ok_or_else(|| self.truncated())?;
         Ok(CodeSection {
             index,
             reader: wasmparser::CodeSectionReader::new(wasmparser::BinaryReader::new(
-                &self.data[byte_range.start..byte_range.end],
+                data,
                 byte_range.start,
             ))?,
             byte_size: byte_range.end - start_offset,
         })
     }
-}
 
-impl<'a> Parse<'a> for ModuleReader<'a> {
-    type ItemsExtra = ();
+    /// Decode every section of the module, without parsing their entries.
+    pub(crate) fn read_module(mut self) -> traits::Result<Module<'a>> {
+        check_unique_sections(self.data)?;
 
-    fn parse_items(mut self, items: &mut ir::ItemsBuilder, _extra: ()) -> anyhow::Result<()> {
-        let mut sections: Vec<IndexedSection<'_>> = Vec::new();
-        let mut code_section: Option<CodeSection<'_>> = None;
-        let mut function_section: Option<FunctionSection<'_>> = None;
-        let mut names: Option<NameSectionReader<'_>> = None;
-        let mut sizes: HashMap<usize, u32> = HashMap::new();
+        let mut sections: Vec<IndexedSection<'a>> = Vec::new();
+        let mut code_section: Option<CodeSection<'a>> = None;
+        let mut function_section: Option<FunctionSection<'a>> = None;
+        let mut names: Vec<NameSectionReader<'a>> = Vec::new();
+        let mut linking = None;
+        let mut section_numbers = Vec::new();
+        let mut sizes: HashMap<usize, u64> = HashMap::new();
 
         // The function and code sections must be handled differently, so these
-        // are not placed in the same `sections` array as the rest.
+        // are not placed in the same `sections` array as the rest. `wasmparser`
+        // has already checked that their counts agree, so an empty one may
+        // appear without the other and is treated like any other section.
         let mut idx = 0;
-        loop {
+        while !self.eof() {
             let start = self.current_position();
-            let at_eof = self.offset == self.data.len();
-            if at_eof {
-                break;
-            }
-            let (section, bytes_consumed) =
-                match self.parser.parse(&self.data[self.offset..], at_eof)? {
-                    wasmparser::Chunk::NeedMoreData { .. } => {
-                        return Err(anyhow!("wasm binary cannot be fully parsed"));
-                    }
-                    wasmparser::Chunk::Parsed { consumed, payload } => (payload, consumed),
-                };
-            self.offset += bytes_consumed;
+            let section = self.read()?;
             let size = self.current_position() - start;
             let indexed_section = IndexedSection(idx, section);
+            if !matches!(
+                indexed_section.1,
+                wasmparser::Payload::Version { .. }
+                    | wasmparser::Payload::CodeSectionEntry { .. }
+                    | wasmparser::Payload::End(_)
+            ) {
+                section_numbers.push(idx);
+            }
             match indexed_section.1 {
-                wasmparser::Payload::CodeSectionStart { range, .. } => {
+                wasmparser::Payload::CodeSectionStart { count, range, .. } if count > 0 => {
                     code_section = Some(self.new_code_section(idx, start, range)?);
                 }
-                wasmparser::Payload::FunctionSection(reader) => {
+                wasmparser::Payload::FunctionSection(reader) if reader.count() > 0 => {
                     function_section = Some(FunctionSection {
-                        index: idx,
                         byte_size: reader.range().end - start,
                         reader: reader,
                     });
@@ -125,136 +330,385 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
                     // Ignore.
                 }
                 wasmparser::Payload::CustomSection(ref custom_reader) => {
-                    if let wasmparser::KnownCustom::Name(reader) = custom_reader.as_known() {
-                        names = Some(reader);
+                    match custom_reader.as_known() {
+                        wasmparser::KnownCustom::Name(reader) => names.push(reader),
+                        wasmparser::KnownCustom::Linking(reader) if linking.is_none() => {
+                            linking = Some(reader);
+                        }
+                        _ => {}
                     }
                     sections.push(indexed_section);
                 }
                 _ => sections.push(indexed_section),
             };
-            sizes.insert(idx, size as u32);
+            sizes.insert(idx, size as u64);
             idx += 1;
         }
 
+        let dwarf = dwarf::Dwarf::new(&sections);
+        Ok(Module {
+            data: self.data,
+            sections,
+            function_section,
+            code_section,
+            names,
+            linking,
+            section_numbers,
+            sizes,
+            dwarf,
+        })
+    }
+}
+
+/// A wasm module whose sections have been decoded once, by
+/// `ModuleReader::read_module`, and are then shared by the items pass and the
+/// edges pass. Every section only borrows the module's bytes, so each pass
+/// reads their entries from a cheap copy of the section's reader.
+pub(crate) struct Module<'a> {
+    data: &'a [u8],
+    sections: Vec<IndexedSection<'a>>,
+    function_section: Option<FunctionSection<'a>>,
+    code_section: Option<CodeSection<'a>>,
+    names: Vec<NameSectionReader<'a>>,
+    // The `linking` section of an object file, with its symbol table.
+    linking: Option<wasmparser::LinkingSectionReader<'a>>,
+    // The index of each section, in the order of the module's sections, which
+    // is how relocations refer to them.
+    section_numbers: Vec<usize>,
+    // The size of each section, by index.
+    sizes: HashMap<usize, u64>,
+    // The units and functions that the DWARF sections are split into.
+    dwarf: dwarf::Dwarf,
+}
+
+impl<'a> Module<'a> {
+    /// The URL of the module's source map, from its `sourceMappingURL` custom
+    /// section, if it has one.
+    pub(crate) fn source_mapping_url(&self) -> Option<&'a str> {
+        self.sections
+            .iter()
+            .find_map(|IndexedSection(_, section)| match section {
+                wasmparser::Payload::CustomSection(reader)
+                    if reader.name() == "sourceMappingURL" =>
+                {
+                    wasmparser::BinaryReader::new(reader.data(), reader.data_offset())
+                        .read_string()
+                        .ok()
+                }
+                _ => None,
+            })
+    }
+
+    /// Give each function the source file that most of its body's bytes were
+    /// compiled from, according to the source map. Functions that are mostly
+    /// unmapped are left without one.
+    pub(crate) fn add_source_locations(
+        &self,
+        items: &mut ir::ItemsBuilder,
+        map: &SourceMap,
+    ) -> traits::Result<()> {
+        let Some(code_section) = &self.code_section else {
+            return Ok(());
+        };
+        for (i, body) in iterate_with_range(code_section.reader.clone()).enumerate() {
+            let (_, range) = body?;
+            if let Some((file, line)) = map.dominant_source(range) {
+                items.set_source_location(Id::entry(code_section.index, i), file, Some(line));
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve the symbols of an object file's `linking` section to items, and
+    /// record the entries of the code and data sections that its relocations
+    /// apply to. Must be called once every function's index is known.
+    fn add_relocation_indices(
+        &self,
+        indices: &mut SectionIndices,
+        linking: wasmparser::LinkingSectionReader<'a>,
+        aggregate_threshold: Option<u32>,
+    ) -> traits::Result<()> {
+        let data_section = self
+            .sections
+            .iter()
+            .find_map(|IndexedSection(idx, section)| match section {
+                wasmparser::Payload::DataSection(reader) => Some((*idx, reader.clone())),
+                _ => None,
+            });
+
+        for subsection in linking {
+            let wasmparser::Linking::SymbolTable(symbols) = subsection? else {
+                continue;
+            };
+            indices.symbols.reserve(capacity(&symbols));
+            for symbol in symbols {
+                let id = match symbol? {
+                    wasmparser::SymbolInfo::Func { index, .. } => {
+                        indices.functions.get(index as usize).copied()
+                    }
+                    wasmparser::SymbolInfo::Global { index, .. } => {
+                        indices.globals.get(index as usize).copied()
+                    }
+                    wasmparser::SymbolInfo::Table { index, .. } => {
+                        indices.tables.get(index as usize).copied()
+                    }
+                    wasmparser::SymbolInfo::Event { index, .. } => {
+                        indices.tags.get(index as usize).copied()
+                    }
+                    wasmparser::SymbolInfo::Data {
+                        symbol: Some(symbol),
+                        ..
+                    } => data_section.as_ref().and_then(|(idx, reader)| {
+                        let count = reader.count();
+                        if symbol.index >= count {
+                            None
+                        } else if aggregated(count, aggregate_threshold) {
+                            Some(Id::entry(*idx, 0))
+                        } else {
+                            Some(Id::entry(*idx, symbol.index as usize))
+                        }
+                    }),
+                    _ => None,
+                };
+                indices.symbols.push(id);
+            }
+        }
+
+        for (number, &idx) in self.section_numbers.iter().enumerate() {
+            let (start, entries) = match (&self.code_section, &data_section) {
+                (Some(code_section), _) if code_section.index == idx => {
+                    let entries = iterate_with_range(code_section.reader.clone())
+                        .enumerate()
+                        .map(|(i, body)| Ok((body?.1, Id::entry(idx, i))))
+                        .collect::<traits::Result<_>>()?;
+                    (code_section.reader.range().start, entries)
+                }
+                (_, Some((data_idx, reader))) if *data_idx == idx => {
+                    let aggregate = aggregated(reader.count(), aggregate_threshold);
+                    let entries = iterate_with_range(reader.clone())
+                        .enumerate()
+                        .map(|(i, segment)| {
+                            let id = Id::entry(idx, if aggregate { 0 } else { i });
+                            Ok((segment?.1, id))
+                        })
+                        .collect::<traits::Result<_>>()?;
+                    (reader.range().start, entries)
+                }
+                _ => continue,
+            };
+            let section = RelocatableSection {
+                start: start as u64,
+                entries,
+            };
+            indices.relocatable.insert(number as u32, section);
+        }
+
+        let code_number = self.code_section.as_ref().and_then(|code| {
+            self.section_numbers
+                .iter()
+                .position(|&idx| idx == code.index)
+        });
+        indices.code_relocated = self.sections.iter().any(|IndexedSection(_, section)| {
+            matches!(
+                section,
+                wasmparser::Payload::CustomSection(reader)
+                    if matches!(
+                        reader.as_known(),
+                        wasmparser::KnownCustom::Reloc(reloc)
+                            if Some(reloc.section_index() as usize) == code_number
+                    )
+            )
+        });
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for &Module<'a> {
+    /// The threshold above which element and data sections are aggregated,
+    /// and whether to split each function into its locals and instructions.
+    type ItemsExtra = (Option<u32>, bool);
+
+    fn parse_items(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (aggregate_threshold, locals_split): Self::ItemsExtra,
+    ) -> traits::Result<()> {
         // Before we actually parse any items prepare to parse a few sections
         // below, namely the code section. When parsing the code section we want
         // to try to assign human-readable names so we need the name section, if
-        // present. Additionally we need to look at the number of imported
-        // functions to handle the wasm function index space correctly.
-        let names = names
-            .map(parse_names_section)
-            .unwrap_or(Ok(Names::default()))?;
-        let imported_functions = count_imported_functions(&sections)?;
+        // present. Additionally we need to look at the number of imports to
+        // handle the wasm function, table, memory and global index spaces
+        // correctly.
+        let mut names = parse_names_sections(self.names.clone())?;
+        if let Some(linking) = &self.linking {
+            add_linking_names(&mut names, linking.clone())
+                .map_err(|e| e.in_section("custom section 'linking' headers"))?;
+        }
+        let imports = count_imports(&self.sections)?;
+
+        // Every section is an item, besides its entries.
+        let entries: usize = self
+            .sections
+            .iter()
+            .map(|IndexedSection(_, section)| entry_capacity(section))
+            .sum();
+        let functions = self
+            .code_section
+            .as_ref()
+            .map_or(0, |code_section| capacity(&code_section.reader));
+        items.reserve_items(self.sections.len() + entries + functions);
 
         // Next, we parse the function and code sections together, so that we
         // can collapse corresponding entries from the code and function
         // sections into a single representative IR item.
-        match (function_section, code_section) {
-            (Some(function_section), Some(code_section)) => (function_section, code_section)
-                .parse_items(items, (imported_functions, &names.function_names))?,
-            _ => Err(anyhow!("function or code section is missing",))?,
+        match (&self.function_section, &self.code_section) {
+            (Some(function_section), Some(code_section)) => {
+                (function_section.clone(), code_section.clone())
+                    .parse_items(
+                        items,
+                        (imports.functions, &names.function_names, locals_split),
+                    )
+                    .map_err(|e| e.in_section(&get_code_section_name()))?
+            }
+            // A module without any functions defined has neither section, or
+            // only empty ones.
+            (None, None) => {}
+            _ => Err(traits::Error::Malformed {
+                section: None,
+                offset: self.data.len(),
+                message: "function or code section is missing".to_string(),
+            })?,
         };
 
-        for IndexedSection(idx, section) in sections.into_iter() {
+        for &IndexedSection(idx, ref section) in &self.sections {
             let start = items.size_added();
-            let name = get_section_name(&section);
-            match section {
+            let name = get_section_name(section);
+            let coredump = matches!(
+                section,
+                wasmparser::Payload::CustomSection(reader) if is_coredump_section(reader.name())
+            );
+            let parsed = match section {
+                wasmparser::Payload::CustomSection(_) if self.dwarf.is_split(idx) => {
+                    self.dwarf.add_items(items, idx);
+                    Ok(())
+                }
                 wasmparser::Payload::CustomSection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    reader.clone().parse_items(items, idx)
                 }
                 wasmparser::Payload::TypeSection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    reader.clone().parse_items(items, (idx, &names.type_names))
                 }
                 wasmparser::Payload::ImportSection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    reader.clone().parse_items(items, idx)
                 }
                 wasmparser::Payload::TableSection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    let extra = (idx, imports.tables, &names.table_names);
+                    reader.clone().parse_items(items, extra)
                 }
                 wasmparser::Payload::MemorySection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    let extra = (idx, imports.memories, &names.memory_names);
+                    reader.clone().parse_items(items, extra)
                 }
                 wasmparser::Payload::GlobalSection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    let extra = (idx, imports.globals, &names.global_names);
+                    reader.clone().parse_items(items, extra)
                 }
+                wasmparser::Payload::TagSection(reader) => reader.clone().parse_items(items, idx),
                 wasmparser::Payload::ExportSection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    reader.clone().parse_items(items, idx)
                 }
-                wasmparser::Payload::StartSection { func, range } => {
-                    StartSection {
-                        function_index: func,
-                        _data: &self.data[range.start..range.end],
-                    }
-                    .parse_items(items, idx)?;
+                wasmparser::Payload::StartSection { func, range } => StartSection {
+                    function_index: *func,
+                    range: range.clone(),
                 }
+                .parse_items(items, idx),
+                wasmparser::Payload::DataCountSection { range, .. } => DataCountSection {
+                    range: range.clone(),
+                }
+                .parse_items(items, idx),
                 wasmparser::Payload::ElementSection(reader) => {
-                    reader.parse_items(items, idx)?;
+                    let extra = (idx, &names.element_names, aggregate_threshold);
+                    reader.clone().parse_items(items, extra)
                 }
                 wasmparser::Payload::DataSection(reader) => {
-                    reader.parse_items(items, (idx, &names.data_names))?;
-                }
-                wasmparser::Payload::CodeSectionStart { .. }
-                | wasmparser::Payload::FunctionSection(_) => {
-                    unreachable!("unexpected code or function section found");
+                    let extra = (idx, &names.data_names, aggregate_threshold);
+                    reader.clone().parse_items(items, extra)
                 }
-                _ => {}
+                // Only empty function and code sections get here, and they
+                // have no entries to parse.
+                _ => Ok(()),
             };
+            parsed.map_err(|e| e.in_section(&name))?;
             let id = Id::section(idx);
             let added = items.size_added() - start;
-            let size = sizes
-                .get(&idx)
-                .ok_or_else(|| anyhow!("Could not find section size"))?;
-            assert!(added <= *size);
-            items.add_root(ir::Item::new(id, name, size - added, ir::Misc::new()));
+            let size = self.sizes.get(&idx).ok_or_else(|| {
+                traits::Error::Analysis("Could not find section size".to_string())
+            })?;
+            let mut item = section_item(id, name, *size, added)?;
+            if coredump {
+                item = item.with_category(ir::ItemCategory::Coredump);
+            }
+            items.add_root(item);
         }
 
         Ok(())
     }
 
-    type EdgesExtra = ();
-
-    fn parse_edges(mut self, items: &mut ir::ItemsBuilder, _extra: ()) -> anyhow::Result<()> {
-        let mut sections: Vec<IndexedSection<'_>> = Vec::new();
-        let mut code_section: Option<CodeSection<'a>> = None;
-        let mut function_section: Option<FunctionSection<'a>> = None;
-
-        let mut idx = 0;
-        while !self.eof() {
-            let section = self.read()?;
-            let start = self.current_position();
-            match section {
-                wasmparser::Payload::CodeSectionStart { range, .. } => {
-                    code_section = Some(self.new_code_section(idx, start, range)?);
-                }
-                wasmparser::Payload::FunctionSection(reader) => {
-                    function_section = Some(FunctionSection {
-                        index: idx,
-                        byte_size: reader.range().end - start,
-                        reader: reader,
-                    });
-                }
-                _ => sections.push(IndexedSection(idx, section)),
-            };
-            idx += 1;
-        }
+    /// The number of worker threads to walk function bodies with, the
+    /// threshold above which element and data sections are aggregated, and
+    /// what else to record about function bodies.
+    type EdgesExtra = (usize, Option<u32>, BodyWalk);
 
-        // Like above we do some preprocessing here before actually drawing all
-        // the edges below. Here we primarily want to learn some properties of
+    fn parse_edges(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (threads, aggregate_threshold, walk): Self::EdgesExtra,
+    ) -> traits::Result<()> {
+        let sections = &self.sections;
+        let function_section = self.function_section.clone();
+        let code_section = self.code_section.clone();
+
+        // Like for items, we do some preprocessing here before actually drawing
+        // all the edges below. Here we primarily want to learn some properties of
         // the wasm module, such as what `Id` is mapped to all index spaces in
         // the wasm module. To handle that we build up all this data in
         // `SectionIndices` here as we parse all the various sections.
         let mut indices = SectionIndices::default();
         for IndexedSection(idx, section) in sections.iter() {
             match section {
-                wasmparser::Payload::TypeSection(_reader) => {
-                    indices.type_ = Some(*idx);
+                wasmparser::Payload::TypeSection(reader) => {
+                    // Items are only created for types outside of explicit
+                    // recursion groups, so types inside them get no `Id`.
+                    indices.types.reserve(capacity(reader));
+                    let mut signatures = HashMap::new();
+                    for (i, rec_group) in reader.clone().into_iter().enumerate() {
+                        let rec_group = rec_group?;
+                        if rec_group.is_explicit_rec_group() {
+                            let len = rec_group.types().len();
+                            let first = indices.types.len() as u32;
+                            indices.types.extend(std::iter::repeat_n(None, len));
+                            indices.canonical_types.extend(first..first + len as u32);
+                        } else {
+                            let index = indices.types.len() as u32;
+                            let ty = rec_group.into_types().next();
+                            let canonical = match ty.as_ref().map(|ty| &ty.composite_type.inner) {
+                                Some(wasmparser::CompositeInnerType::Func(func)) => {
+                                    *signatures.entry(func.clone()).or_insert(index)
+                                }
+                                _ => index,
+                            };
+                            indices.types.push(Some(Id::entry(*idx, i)));
+                            indices.canonical_types.push(canonical);
+                        }
+                    }
                 }
                 wasmparser::Payload::ImportSection(reader) => {
                     for (i, import) in reader.clone().into_iter().enumerate() {
                         let id = Id::entry(*idx, i);
                         match import?.ty {
-                            wasmparser::TypeRef::Func(_) => {
+                            wasmparser::TypeRef::Func(ty) => {
                                 indices.functions.push(id);
+                                indices.function_types.push(indices.canonical_type(ty));
                             }
                             wasmparser::TypeRef::Table(_) => {
                                 indices.tables.push(id);
@@ -264,34 +718,36 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
                             }
                             wasmparser::TypeRef::Global(_) => {
                                 indices.globals.push(id);
+                                indices.global_values.push(None);
+                            }
+                            wasmparser::TypeRef::Tag(_) => {
+                                indices.tags.push(id);
                             }
-                            wasmparser::TypeRef::Tag(_) => {}
                         }
                     }
                 }
                 wasmparser::Payload::GlobalSection(reader) => {
-                    for i in 0..reader.count() {
-                        let id = Id::entry(*idx, i as usize);
-                        indices.globals.push(id);
+                    for (i, global) in reader.clone().into_iter().enumerate() {
+                        let global = global?;
+                        indices.globals.push(Id::entry(*idx, i));
+                        indices.global_values.push(constant_value(&global));
                     }
                 }
                 wasmparser::Payload::MemorySection(reader) => {
-                    for i in 0..reader.count() {
-                        let id = Id::entry(*idx, i as usize);
-                        indices.memories.push(id);
-                    }
+                    let count = reader.count() as usize;
+                    indices
+                        .memories
+                        .extend((0..count).map(|i| Id::entry(*idx, i)));
                 }
                 wasmparser::Payload::TableSection(reader) => {
-                    for i in 0..reader.count() {
-                        let id = Id::entry(*idx, i as usize);
-                        indices.tables.push(id);
-                    }
-                }
-                wasmparser::Payload::CodeSectionStart { .. } => {
-                    Err(anyhow!("unexpected code section"))?
+                    let count = reader.count() as usize;
+                    indices
+                        .tables
+                        .extend((0..count).map(|i| Id::entry(*idx, i)));
                 }
-                wasmparser::Payload::FunctionSection(_reader) => {
-                    Err(anyhow!("unexpected function section"))?
+                wasmparser::Payload::TagSection(reader) => {
+                    let count = reader.count() as usize;
+                    indices.tags.extend((0..count).map(|i| Id::entry(*idx, i)));
                 }
                 _ => {}
             }
@@ -300,60 +756,98 @@ impl<'a> Parse<'a> for ModuleReader<'a> {
             (function_section.as_ref(), code_section.as_ref())
         {
             indices.code = Some(code_section.index);
-            for i in 0..function_section.reader.count() {
-                let id = Id::entry(code_section.index, i as usize);
-                indices.functions.push(id);
+            let count = function_section.reader.count() as usize;
+            indices
+                .functions
+                .extend((0..count).map(|i| Id::entry(code_section.index, i)));
+            for ty in function_section.reader.clone() {
+                let ty = indices.canonical_type(ty?);
+                indices.function_types.push(ty);
             }
         }
+        // Only now that every function's index is known can the functions
+        // that element segments place in tables be resolved.
+        for IndexedSection(_, section) in sections.iter() {
+            if let wasmparser::Payload::ElementSection(reader) = section {
+                for elem in reader.clone() {
+                    let elem = elem?;
+                    let wasmparser::ElementKind::Active { table_index, .. } = elem.kind else {
+                        continue;
+                    };
+                    let table = table_index.unwrap_or(0);
+                    match elem.items {
+                        wasmparser::ElementItems::Functions(functions) => {
+                            for function in functions {
+                                indices.add_table_function(table, function?);
+                            }
+                        }
+                        wasmparser::ElementItems::Expressions(_, exprs) => {
+                            for expr in exprs {
+                                let mut ops = expr?.get_operators_reader();
+                                if let Operator::RefFunc { function_index } = ops.read()? {
+                                    indices.add_table_function(table, function_index);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (i, &function) in indices.functions.iter().enumerate() {
+            items.set_binary_index(function, i as u32);
+        }
+        if let Some(linking) = &self.linking {
+            self.add_relocation_indices(&mut indices, linking.clone(), aggregate_threshold)
+                .map_err(|e| e.in_section("custom section 'linking' headers"))?;
+        }
+        self.dwarf
+            .add_edges(items, code_section.as_ref())
+            .map_err(|e| e.in_section(&get_code_section_name()))?;
 
         match (function_section, code_section) {
-            (Some(function_section), Some(code_section)) => {
-                (function_section, code_section).parse_edges(items, &indices)?
-            }
-            _ => panic!("function or code section is missing"),
+            (Some(function_section), Some(code_section)) => (function_section, code_section)
+                .parse_edges(items, (&indices, threads, walk))
+                .map_err(|e| e.in_section(&get_code_section_name()))?,
+            (None, None) => {}
+            _ => Err(traits::Error::Malformed {
+                section: None,
+                offset: self.data.len(),
+                message: "function or code section is missing".to_string(),
+            })?,
         };
-        for IndexedSection(idx, section) in sections.into_iter() {
-            match section {
+        for &IndexedSection(idx, ref section) in sections {
+            let name = get_section_name(section);
+            let parsed = match section {
                 wasmparser::Payload::CustomSection(reader) => {
-                    reader.parse_edges(items, ())?;
-                }
-                wasmparser::Payload::TypeSection(reader) => {
-                    reader.parse_edges(items, ())?;
+                    reader.clone().parse_edges(items, &indices)
                 }
+                wasmparser::Payload::TypeSection(reader) => reader.clone().parse_edges(items, ()),
                 wasmparser::Payload::ImportSection(reader) => {
-                    reader.parse_edges(items, ())?;
-                }
-                wasmparser::Payload::TableSection(reader) => {
-                    reader.parse_edges(items, ())?;
-                }
-                wasmparser::Payload::MemorySection(reader) => {
-                    reader.parse_edges(items, ())?;
+                    reader.clone().parse_edges(items, (&indices, idx))
                 }
+                wasmparser::Payload::TableSection(reader) => reader.clone().parse_edges(items, ()),
+                wasmparser::Payload::MemorySection(reader) => reader.clone().parse_edges(items, ()),
                 wasmparser::Payload::GlobalSection(reader) => {
-                    reader.parse_edges(items, ())?;
-                }
-                wasmparser::Payload::ExportSection(reader) => {
-                    reader.parse_edges(items, (&indices, idx))?;
+                    reader.clone().parse_edges(items, (&indices, idx))
                 }
-                wasmparser::Payload::StartSection { func, range } => {
-                    StartSection {
-                        function_index: func,
-                        _data: &self.data[range.start..range.end],
-                    }
-                    .parse_edges(items, (&indices, idx))?;
-                }
-                wasmparser::Payload::ElementSection(reader) => {
-                    reader.parse_edges(items, (&indices, idx))?;
+                wasmparser::Payload::TagSection(reader) => {
+                    reader.clone().parse_edges(items, (&indices, idx))
                 }
-                wasmparser::Payload::DataSection(reader) => {
-                    reader.parse_edges(items, ())?;
+                wasmparser::Payload::ExportSection(reader) => {
+                    reader.clone().parse_edges(items, (&indices, idx))
                 }
-                wasmparser::Payload::CodeSectionStart { .. }
-                | wasmparser::Payload::FunctionSection { .. } => {
-                    unreachable!("unexpected code or function section found");
+                wasmparser::Payload::StartSection { func, range } => StartSection {
+                    function_index: *func,
+                    range: range.clone(),
                 }
-                _ => {}
-            }
+                .parse_edges(items, (&indices, idx)),
+                wasmparser::Payload::ElementSection(reader) => reader
+                    .clone()
+                    .parse_edges(items, (&indices, idx, aggregate_threshold)),
+                wasmparser::Payload::DataSection(reader) => reader.clone().parse_edges(items, ()),
+                _ => Ok(()),
+            };
+            parsed.map_err(|e| e.in_section(&name))?;
         }
 
         Ok(())
@@ -375,6 +869,7 @@ fn get_section_name(section: &wasmparser::Payload<'_>) -> String {
         wasmparser::Payload::TableSection(_) => "table section headers".to_string(),
         wasmparser::Payload::MemorySection(_) => "memory section headers".to_string(),
         wasmparser::Payload::GlobalSection(_) => "global section headers".to_string(),
+        wasmparser::Payload::TagSection(_) => "tag section headers".to_string(),
         wasmparser::Payload::ExportSection(_) => "export section headers".to_string(),
         wasmparser::Payload::StartSection { .. } => "start section headers".to_string(),
         wasmparser::Payload::ElementSection(_) => "element section headers".to_string(),
@@ -383,9 +878,7 @@ fn get_section_name(section: &wasmparser::Payload<'_>) -> String {
         wasmparser::Payload::DataCountSection { .. } => "data count section headers".to_string(),
         wasmparser::Payload::Version { .. } => "wasm magic bytes".to_string(),
 
-        wasmparser::Payload::CodeSectionEntry { .. } => {
-            panic!("unexpected CodeSectionEntry");
-        }
+        wasmparser::Payload::CodeSectionEntry { .. } => get_code_section_name(),
         _ => format!("{:?}", section),
     }
 }
@@ -393,196 +886,1015 @@ fn get_section_name(section: &wasmparser::Payload<'_>) -> String {
 #[derive(Default)]
 struct Names<'a> {
     function_names: HashMap<usize, &'a str>,
+    type_names: HashMap<usize, &'a str>,
+    table_names: HashMap<usize, &'a str>,
+    memory_names: HashMap<usize, &'a str>,
+    global_names: HashMap<usize, &'a str>,
+    element_names: HashMap<usize, &'a str>,
     data_names: HashMap<usize, &'a str>,
 }
 
-fn parse_names_section<'a>(reader: NameSectionReader<'a>) -> anyhow::Result<Names<'a>> {
+/// Collect the names from every name section, in order.
+///
+/// Some toolchains emit more than one name section, or more than one subsection
+/// of the same kind, e.g. when a post-processor appends its own names after
+/// LLVM's. These are merged, and the first name given to an index wins, so that
+/// later sections only fill in names that earlier ones lack.
+fn parse_names_sections<'a>(readers: Vec<NameSectionReader<'a>>) -> traits::Result<Names<'a>> {
     let mut names = Names::default();
 
     // This is a workaround to skip new types of name subsections instead of aborting.
-    for name in reader.filter_map(Result::ok) {
-        match name {
-            wasmparser::Name::Function(map) => {
-                for naming in map {
-                    let naming = naming?;
-                    names
-                        .function_names
-                        .insert(naming.index as usize, naming.name);
+    for name in readers.into_iter().flatten().filter_map(Result::ok) {
+        let (map, known) = match name {
+            wasmparser::Name::Function(map) => (map, &mut names.function_names),
+            wasmparser::Name::Type(map) => (map, &mut names.type_names),
+            wasmparser::Name::Table(map) => (map, &mut names.table_names),
+            wasmparser::Name::Memory(map) => (map, &mut names.memory_names),
+            wasmparser::Name::Global(map) => (map, &mut names.global_names),
+            wasmparser::Name::Element(map) => (map, &mut names.element_names),
+            wasmparser::Name::Data(map) => (map, &mut names.data_names),
+            _ => continue,
+        };
+        known.reserve(capacity(&map));
+        for naming in map {
+            let naming = naming?;
+            known.entry(naming.index as usize).or_insert(naming.name);
+        }
+    }
+    Ok(names)
+}
+
+/// Fill in the names that the name sections leave out from the symbol table in
+/// the `linking` section of an object file. A data segment is named after the
+/// symbol at its start, or else by the name that the `linking` section gives
+/// the segment itself.
+fn add_linking_names<'a>(
+    names: &mut Names<'a>,
+    linking: wasmparser::LinkingSectionReader<'a>,
+) -> traits::Result<()> {
+    let mut data_symbols = HashMap::new();
+    let mut segment_names = HashMap::new();
+    for subsection in linking {
+        match subsection? {
+            wasmparser::Linking::SymbolTable(symbols) => {
+                for symbol in symbols {
+                    let (known, index, name) = match symbol? {
+                        wasmparser::SymbolInfo::Func {
+                            index,
+                            name: Some(name),
+                            ..
+                        } => (&mut names.function_names, index, name),
+                        wasmparser::SymbolInfo::Global {
+                            index,
+                            name: Some(name),
+                            ..
+                        } => (&mut names.global_names, index, name),
+                        wasmparser::SymbolInfo::Table {
+                            index,
+                            name: Some(name),
+                            ..
+                        } => (&mut names.table_names, index, name),
+                        wasmparser::SymbolInfo::Data {
+                            name,
+                            symbol: Some(symbol),
+                            ..
+                        } if symbol.offset == 0 => (&mut data_symbols, symbol.index, name),
+                        _ => continue,
+                    };
+                    known.entry(index as usize).or_insert(name);
                 }
             }
-            wasmparser::Name::Data(map) => {
-                for naming in map {
-                    let naming = naming?;
-                    names.data_names.insert(naming.index as usize, naming.name);
+            wasmparser::Linking::SegmentInfo(segments) => {
+                for (i, segment) in segments.into_iter().enumerate() {
+                    segment_names.insert(i, segment?.name);
                 }
             }
-            _ => continue,
-        };
+            _ => {}
+        }
     }
-    Ok(names)
+    for (i, name) in data_symbols.into_iter().chain(segment_names) {
+        names.data_names.entry(i).or_insert(name);
+    }
+    Ok(())
+}
+
+/// The name of the `i`th entry of a section, which is `index` in its index
+/// space: the name the name section gives it, or else its index in the section.
+fn entry_name(
+    names: &HashMap<usize, &str>,
+    prefix: &'static str,
+    index: usize,
+    i: usize,
+) -> ir::Name {
+    match names.get(&index) {
+        Some(name) => format!("{} '{}'", prefix, name).into(),
+        None => ir::Name::indexed(prefix, i),
+    }
+}
+
+/// The number of imports of each kind that has an index space the name section
+/// can refer to. Imports come before any definitions in their index space.
+#[derive(Default)]
+struct ImportCounts {
+    functions: usize,
+    tables: usize,
+    memories: usize,
+    globals: usize,
 }
 
-fn count_imported_functions<'a>(indexed_sections: &[IndexedSection<'a>]) -> anyhow::Result<usize> {
-    let mut imported_functions = 0;
+fn count_imports<'a>(indexed_sections: &[IndexedSection<'a>]) -> traits::Result<ImportCounts> {
+    let mut counts = ImportCounts::default();
     for IndexedSection(_, section) in indexed_sections.iter() {
         if let wasmparser::Payload::ImportSection(reader) = section {
             for import in reader.clone() {
-                if let wasmparser::TypeRef::Func(_) = import?.ty {
-                    imported_functions += 1;
+                match import?.ty {
+                    wasmparser::TypeRef::Func(_) => counts.functions += 1,
+                    wasmparser::TypeRef::Table(_) => counts.tables += 1,
+                    wasmparser::TypeRef::Memory(_) => counts.memories += 1,
+                    wasmparser::TypeRef::Global(_) => counts.globals += 1,
+                    _ => {}
                 }
             }
         }
     }
-    Ok(imported_functions)
+    Ok(counts)
 }
 
 impl<'a> Parse<'a> for (FunctionSection<'a>, CodeSection<'a>) {
-    type ItemsExtra = (usize, &'a HashMap<usize, &'a str>);
+    type ItemsExtra = (usize, &'a HashMap<usize, &'a str>, bool);
 
     fn parse_items(
         self,
         items: &mut ir::ItemsBuilder,
-        (imported_functions, names): Self::ItemsExtra,
-    ) -> anyhow::Result<()> {
+        (imported_functions, names, locals_split): Self::ItemsExtra,
+    ) -> traits::Result<()> {
         let (func_section, code_section) = self;
 
-        let func_section_index = func_section.index;
-        let func_items: Vec<ir::Item> = iterate_with_size(func_section.reader)
-            .enumerate()
-            .map(|(i, func)| {
-                let (_func, size) = func?;
-                let id = Id::entry(func_section_index, i);
-                let name = format!("func[{}]", i);
-                let item = ir::Item::new(id, name, size, ir::Misc::new());
-                Ok(item)
-            })
-            .collect::<anyhow::Result<_>>()?;
+        let func_ranges: Vec<Range<u64>> = iterate_with_range(func_section.reader)
+            .map(|func| func.map(|(_func, range)| range))
+            .collect::<traits::Result<_>>()?;
 
         let code_section_index = code_section.index;
-        let code_items: Vec<ir::Item> = iterate_with_size(code_section.reader)
-            .zip(func_items.into_iter())
-            .enumerate()
-            .map(|(i, (body, func))| {
-                let (_body, size) = body?;
-                let id = Id::entry(code_section_index, i);
-                let name = names
-                    .get(&(i + imported_functions))
-                    .map_or_else(|| format!("code[{}]", i), |name| name.to_string());
-                let code = ir::Code::new(&name);
-                let item = ir::Item::new(id, name, size + func.size(), code);
-                Ok(item)
-            })
-            .collect::<anyhow::Result<_>>()?;
+        let count = code_section.reader.count() as usize;
+        let code_items: Vec<(ir::Item, [Range<u64>; 2], Option<LocalsSplit>)> =
+            iterate_with_range(code_section.reader)
+                .zip(func_ranges)
+                .enumerate()
+                .map(|(i, (body, func_range))| {
+                    let (body, range) = body?;
+                    let func_size = func_range.end - func_range.start;
+                    let id = Id::entry(code_section_index, i);
+                    // Split off the locals and the instructions, leaving the
+                    // function with its type index and the size of its body.
+                    let (range, split) = if locals_split {
+                        let locals = body.range().start as u64;
+                        let instructions = body.get_operators_reader()?.original_position() as u64;
+                        let split = LocalsSplit {
+                            locals: locals..instructions,
+                            instructions: instructions..range.end,
+                        };
+                        (range.start..locals, Some(split))
+                    } else {
+                        (range, None)
+                    };
+                    let size = range.end - range.start;
+                    let item = match names.get(&(i + imported_functions)) {
+                        Some(name) => {
+                            ir::Item::new(id, *name, size + func_size, ir::Code::new(name))
+                        }
+                        None => ir::Item::new(
+                            id,
+                            ir::Name::indexed("code", i),
+                            size + func_size,
+                            ir::Code::unnamed(),
+                        ),
+                    };
+                    Ok((item, [func_range, range], split))
+                })
+                .collect::<traits::Result<_>>()?;
 
         let start = items.size_added();
         let name = get_code_section_name();
-        for item in code_items.into_iter() {
-            items.add_item(item);
+        for (i, (item, ranges, split)) in code_items.into_iter().enumerate() {
+            let function = item.name().to_string();
+            let id = items.add_item(item);
+            for range in ranges {
+                items.add_byte_range(id, range);
+            }
+            if let Some(split) = split {
+                // The parts are numbered after the bodies, so that the
+                // functions keep the ids they have without the split.
+                let parts = [
+                    (count + 2 * i, "locals", split.locals),
+                    (count + 2 * i + 1, "instructions", split.instructions),
+                ];
+                for (entry, part, range) in parts {
+                    let part = items.add_item(
+                        ir::Item::new(
+                            Id::entry(code_section_index, entry),
+                            format!("{} ({})", function, part),
+                            range.end - range.start,
+                            ir::Misc::new(),
+                        )
+                        .with_category(ir::ItemCategory::Function),
+                    );
+                    items.add_byte_range(part, range);
+                    items.add_edge_kind(id, part, ir::EdgeKind::Generic);
+                }
+            }
         }
         let id = Id::section(code_section.index);
         let added = items.size_added() - start;
-        let code_section_size = code_section.byte_size as u32;
-        let func_section_size = func_section.byte_size as u32;
+        let code_section_size = code_section.byte_size as u64;
+        let func_section_size = func_section.byte_size as u64;
         let size = code_section_size + func_section_size;
 
-        assert!(added <= size);
-        items.add_root(ir::Item::new(id, name, size - added, ir::Misc::new()));
+        items.add_root(section_item(id, name, size, added)?);
 
         Ok(())
     }
 
-    type EdgesExtra = &'a SectionIndices;
+    type EdgesExtra = (&'a SectionIndices, usize, BodyWalk);
 
     fn parse_edges(
         self,
         items: &mut ir::ItemsBuilder,
-        indices: Self::EdgesExtra,
-    ) -> anyhow::Result<()> {
+        (indices, threads, walk): Self::EdgesExtra,
+    ) -> traits::Result<()> {
         let (function_section, code_section) = self;
 
-        type Edge = (ir::Id, ir::Id);
-
-        let mut edges: Vec<Edge> = Vec::new();
-
-        // Function section reader parsing.
-        for (func_i, type_ref) in iterate_with_size(function_section.reader).enumerate() {
-            let (type_ref, _) = type_ref?;
-            if let Some(type_idx) = indices.type_ {
-                let type_id = Id::entry(type_idx, type_ref as usize);
+        // Function section reader parsing. Each function's body has an edge to
+        // its type.
+        items.reserve_edges(capacity(&function_section.reader));
+        for (func_i, type_ref) in function_section.reader.into_iter_with_offsets().enumerate() {
+            let (offset, type_ref) = type_ref?;
+            if let Some(type_id) = lookup(items, &indices.types, "type", type_ref, offset).flatten()
+            {
                 if let Some(code_idx) = indices.code {
                     let body_id = Id::entry(code_idx, func_i);
-                    edges.push((body_id, type_id));
+                    items.add_edge_kind(body_id, type_id, ir::EdgeKind::Type);
                 }
             }
         }
 
-        // Code section reader parsing.
-        for (b_i, body) in iterate_with_size(code_section.reader).enumerate() {
-            let (body, _size) = body?;
+        // Code section reader parsing. Walking the operators of each body is
+        // independent of every other body, so the bodies are split between
+        // worker threads, by their sizes. Each worker only records what it
+        // finds; references are resolved to `Id`s and added to the builder
+        // afterwards, in body order, so the result is identical to walking
+        // them sequentially.
+        let mut bodies = Vec::with_capacity(capacity(&code_section.reader));
+        let mut unreadable_body = None;
+        for body in iterate_with_size(code_section.reader) {
+            match body {
+                Ok((body, _size)) => bodies.push(body),
+                Err(error) => {
+                    unreadable_body = Some(error);
+                    break;
+                }
+            }
+        }
+        let references = walk_bodies(&bodies, threads, walk);
+
+        // Edges are added to the builder as they are resolved. A body that
+        // calls the same function many times yields a single edge, because
+        // adding an edge that already exists has no effect.
+        for reference in references.into_iter().flatten() {
+            let (b_i, offset, reference) = match reference {
+                BodyReference::Found(b_i, offset, reference) => (b_i, offset, reference),
+                BodyReference::ControlFlow(b_i, control_flow) => {
+                    let body_id = Id::entry(code_section.index, b_i);
+                    items.set_control_flow(body_id, control_flow);
+                    continue;
+                }
+                BodyReference::CallSite(b_i, index, size) => {
+                    let body_id = Id::entry(code_section.index, b_i);
+                    // An unknown callee is warned about along with the call's
+                    // own edge.
+                    let Some(&f_id) = indices.functions.get(index as usize) else {
+                        continue;
+                    };
+                    items.add_call_site_bytes(body_id, f_id, size);
+                    continue;
+                }
+                BodyReference::Hash(b_i, hash) => {
+                    let body_id = Id::entry(code_section.index, b_i);
+                    items.set_body_hash(body_id, hash);
+                    continue;
+                }
+                BodyReference::Invalid(error) => return Err(error),
+            };
             let body_id = Id::entry(code_section.index, b_i);
-
-            let mut cache = None;
-            for op in body.get_operators_reader()? {
-                let prev = cache.take();
-                match op? {
-                    Operator::Call { function_index } => {
-                        let f_id = indices.functions[function_index as usize];
-                        edges.push((body_id, f_id));
+            match reference {
+                Reference::Function(index) => {
+                    let Some(f_id) = lookup(items, &indices.functions, "function", index, offset)
+                    else {
+                        continue;
+                    };
+                    items.add_edge_kind(body_id, f_id, ir::EdgeKind::Call);
+                    items.add_call_site(f_id);
+                }
+                Reference::FunctionRef(index) => {
+                    let Some(f_id) = lookup(items, &indices.functions, "function", index, offset)
+                    else {
+                        continue;
+                    };
+                    items.add_edge_kind(body_id, f_id, ir::EdgeKind::Generic);
+                }
+                Reference::Type(index) => {
+                    if let Some(type_id) =
+                        lookup(items, &indices.types, "type", index, offset).flatten()
+                    {
+                        items.add_edge_kind(body_id, type_id, ir::EdgeKind::Type);
+                    }
+                }
+                Reference::Global(index) => {
+                    let Some(g_id) = lookup(items, &indices.globals, "global", index, offset)
+                    else {
+                        continue;
+                    };
+                    items.add_edge_kind(body_id, g_id, ir::EdgeKind::Data);
+                }
+                Reference::Tag(index) => {
+                    let Some(t_id) = lookup(items, &indices.tags, "tag", index, offset) else {
+                        continue;
+                    };
+                    items.add_edge_kind(body_id, t_id, ir::EdgeKind::Generic);
+                }
+                // Relocations say exactly which data and functions a body
+                // refers to, so there is no need to guess.
+                Reference::Memory(index) => {
+                    let Some(m_id) = lookup(items, &indices.memories, "memory", index, offset)
+                    else {
+                        continue;
+                    };
+                    items.add_edge_kind(body_id, m_id, ir::EdgeKind::Data);
+                }
+                Reference::Address { .. } | Reference::GlobalAddress { .. }
+                    if indices.code_relocated =>
+                {
+                    continue;
+                }
+                Reference::Address { memory, address } => {
+                    if let Some(data_id) = items.get_data(memory, address) {
+                        items.add_edge_kind(body_id, data_id, ir::EdgeKind::Heuristic);
+                        items.add_data_reference(body_id, memory, address);
                     }
+                }
+                Reference::GlobalAddress {
+                    memory,
+                    global,
+                    offset,
+                } => {
+                    let address = indices
+                        .global_values
+                        .get(global as usize)
+                        .copied()
+                        .flatten()
+                        .and_then(|value| value.checked_add(offset))
+                        .and_then(|address| u64::try_from(address).ok());
+                    let Some(address) = address else {
+                        continue;
+                    };
+                    if let Some(data_id) = items.get_data(memory, address) {
+                        items.add_edge_kind(body_id, data_id, ir::EdgeKind::Heuristic);
+                        items.add_data_reference(body_id, memory, address);
+                    }
+                }
+                Reference::Indirect { table, type_index } => {
+                    if let Some(type_id) =
+                        lookup(items, &indices.types, "type", type_index, offset).flatten()
+                    {
+                        items.add_edge_kind(body_id, type_id, ir::EdgeKind::Type);
+                    }
+                    if indices.code_relocated {
+                        continue;
+                    }
+                    let ty = indices.canonical_type(type_index);
+                    for &f_id in indices
+                        .table_functions
+                        .get(&(table, ty))
+                        .into_iter()
+                        .flatten()
+                    {
+                        items.add_edge_kind(body_id, f_id, ir::EdgeKind::Heuristic);
+                    }
+                }
+            }
+        }
+
+        if let Some(error) = unreadable_body {
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+/// The bytes of a function body that declare its locals, and those of its
+/// instructions, when they are split off into items of their own.
+struct LocalsSplit {
+    locals: Range<u64>,
+    instructions: Range<u64>,
+}
+
+/// Something a function body refers to, as found by walking its operators and
+/// before it is resolved to an item.
+enum Reference {
+    Function(u32),
+    // A `ref.func` of the function with the given index, which may be called
+    // later through the reference.
+    FunctionRef(u32),
+    // A `call_ref` of a function with the given type. Which function is not
+    // known, but the type is needed.
+    Type(u32),
+    Global(u32),
+    // A `throw` of an exception with the given tag, or a `catch` of one.
+    Tag(u32),
+    // Any use of the memory with the given index: a load, a store, or an
+    // instruction on the memory as a whole.
+    Memory(u32),
+    // A load from the given address of the memory with the given index.
+    Address {
+        memory: u32,
+        address: u64,
+    },
+    // A load from the given offset past the value of the global with the given
+    // index. Only the values of immutable globals with constant initializers
+    // are known.
+    GlobalAddress {
+        memory: u32,
+        global: u32,
+        offset: i64,
+    },
+    // An indirect call through the given table, of a function with the given
+    // type. Any function of that type in the table may be called.
+    Indirect {
+        table: u32,
+        type_index: u32,
+    },
+}
+
+/// What to record about each function body while walking its operators,
+/// besides the references it makes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BodyWalk {
+    /// Record the body's control structures.
+    pub control_flow: bool,
+    /// Record the hashes of the body's bytes.
+    pub body_hashes: bool,
+    /// Record the size of each of the body's direct calls.
+    pub call_site_bytes: bool,
+}
+
+/// A reference found in the body with the given index at the given offset, a
+/// call of the function with the given index and the call's size in bytes, the
+/// control structures or hashes of the body with the given index, or the error
+/// that stopped the walk over a body's operators.
+enum BodyReference {
+    Found(usize, usize, Reference),
+    CallSite(usize, u32, u64),
+    ControlFlow(usize, ir::ControlFlow),
+    Hash(usize, ir::BodyHash),
+    Invalid(traits::Error),
+}
+
+/// Find the references of every body, split between at most `threads` worker
+/// threads, in body order.
+#[cfg(not(target_arch = "wasm32"))]
+fn walk_bodies(
+    bodies: &[wasmparser::FunctionBody<'_>],
+    threads: usize,
+    walk: BodyWalk,
+) -> Vec<Vec<BodyReference>> {
+    let chunks = balanced_chunks(bodies, threads);
+    if chunks.len() <= 1 {
+        return vec![body_references(0, bodies, walk)];
+    }
+    thread::scope(|scope| {
+        let workers: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let first = chunk.start;
+                let chunk = &bodies[chunk];
+                scope.spawn(move || body_references(first, chunk, walk))
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("edge worker thread panicked"))
+            .collect()
+    })
+}
+
+/// WebAssembly cannot spawn threads, so there every body is walked on the
+/// current one.
+#[cfg(target_arch = "wasm32")]
+fn walk_bodies(
+    bodies: &[wasmparser::FunctionBody<'_>],
+    _threads: usize,
+    walk: BodyWalk,
+) -> Vec<Vec<BodyReference>> {
+    vec![body_references(0, bodies, walk)]
+}
+
+/// Split the given function bodies into at most `threads` runs of consecutive
+/// bodies with about as many bytes each. Walking a body takes time in
+/// proportion to its size, and a module's bodies vary in size by orders of
+/// magnitude, so runs of as many bodies each would leave some workers with
+/// several times as much to walk as others.
+#[cfg(not(target_arch = "wasm32"))]
+fn balanced_chunks(bodies: &[wasmparser::FunctionBody<'_>], threads: usize) -> Vec<Range<usize>> {
+    let size = |body: &wasmparser::FunctionBody<'_>| body.range().len() as u64;
+    let total: u64 = bodies.iter().map(size).sum();
+    let chunk_bytes = cmp::max(1, total.div_ceil(cmp::max(1, threads) as u64));
+    let mut chunks = vec![];
+    let (mut start, mut bytes) = (0, 0);
+    for (i, body) in bodies.iter().enumerate() {
+        bytes += size(body);
+        if bytes >= chunk_bytes {
+            chunks.push(start..i + 1);
+            start = i + 1;
+            bytes = 0;
+        }
+    }
+    if start < bodies.len() {
+        chunks.push(start..bodies.len());
+    }
+    chunks
+}
 
-                    // TODO: Rather than looking at indirect calls, need to look
-                    // at where the vtables get initialized and/or vtable
-                    // indices get pushed onto the stack.
-                    Operator::CallIndirect { .. } => continue,
+/// Walk the operators of the given function bodies, whose indices start at
+/// `first`, and record every reference they make, as well as whatever else
+/// `walk` asks for. Stops at the first error.
+fn body_references(
+    first: usize,
+    bodies: &[wasmparser::FunctionBody<'_>],
+    walk: BodyWalk,
+) -> Vec<BodyReference> {
+    let mut references = Vec::new();
+    for (b_i, body) in bodies.iter().enumerate() {
+        if let Err(error) = operator_references(first + b_i, body, walk, &mut references) {
+            references.push(BodyReference::Invalid(error));
+            break;
+        }
+    }
+    references
+}
 
-                    Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
-                        let g_id = indices.globals[global_index as usize];
-                        edges.push((body_id, g_id));
+fn operator_references(
+    b_i: usize,
+    body: &wasmparser::FunctionBody<'_>,
+    walk: BodyWalk,
+    references: &mut Vec<BodyReference>,
+) -> traits::Result<()> {
+    let mut addresses = Addresses::default();
+    let mut structures = ControlStructures::default();
+    let operators = body.get_operators_reader()?;
+    let mut hasher = BodyHasher::new(body, operators.original_position());
+    // The offset and function index of the previous operator, if it was a
+    // call. Its size is only known once the next operator's offset is.
+    let mut call: Option<(usize, u32)> = None;
+    // The tables and types of the indirect calls found so far, since every
+    // indirect call with the same ones has the same candidates.
+    let mut indirect_calls: Vec<(u32, u32)> = Vec::new();
+    // The memories used so far, since one reference to each is enough.
+    let mut memories: Vec<u32> = Vec::new();
+    for op in operators.into_iter_with_offsets() {
+        let (op, offset) = op?;
+        let address = addresses.top();
+        addresses.visit(&op);
+        if let Some((start, function_index)) = call.take() {
+            let size = (offset - start) as u64;
+            references.push(BodyReference::CallSite(b_i, function_index, size));
+        }
+        if walk.control_flow {
+            structures.visit(&op, offset);
+        }
+        if walk.body_hashes {
+            hasher.visit(&op, offset);
+        }
+        for &memory in operator_memories(&op).iter().flatten() {
+            if !memories.contains(&memory) {
+                memories.push(memory);
+                references.push(BodyReference::Found(b_i, offset, Reference::Memory(memory)));
+            }
+        }
+        let reference = match op {
+            Operator::Call { function_index } => {
+                if walk.call_site_bytes {
+                    call = Some((offset, function_index));
+                }
+                Reference::Function(function_index)
+            }
+
+            // Without knowing which table index is on the stack, the call
+            // may be of any function of its type in its table.
+            Operator::CallIndirect {
+                type_index,
+                table_index,
+            }
+            | Operator::ReturnCallIndirect {
+                type_index,
+                table_index,
+            } => {
+                if indirect_calls.contains(&(table_index, type_index)) {
+                    continue;
+                }
+                indirect_calls.push((table_index, type_index));
+                Reference::Indirect {
+                    table: table_index,
+                    type_index,
+                }
+            }
+
+            Operator::RefFunc { function_index } => Reference::FunctionRef(function_index),
+
+            Operator::CallRef { type_index } | Operator::ReturnCallRef { type_index } => {
+                Reference::Type(type_index)
+            }
+
+            Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
+                Reference::Global(global_index)
+            }
+
+            Operator::Throw { tag_index } | Operator::Catch { tag_index } => {
+                Reference::Tag(tag_index)
+            }
+
+            // A `try_table` may catch exceptions with any number of tags.
+            Operator::TryTable { try_table } => {
+                for catch in try_table.catches {
+                    if let wasmparser::Catch::One { tag, .. }
+                    | wasmparser::Catch::OneRef { tag, .. } = catch
+                    {
+                        references.push(BodyReference::Found(b_i, offset, Reference::Tag(tag)));
                     }
+                }
+                continue;
+            }
 
-                    Operator::I32Load { memarg }
-                    | Operator::I32Load8S { memarg }
-                    | Operator::I32Load8U { memarg }
-                    | Operator::I32Load16S { memarg }
-                    | Operator::I32Load16U { memarg }
-                    | Operator::I64Load { memarg }
-                    | Operator::I64Load8S { memarg }
-                    | Operator::I64Load8U { memarg }
-                    | Operator::I64Load16S { memarg }
-                    | Operator::I64Load16U { memarg }
-                    | Operator::I64Load32S { memarg }
-                    | Operator::I64Load32U { memarg }
-                    | Operator::F32Load { memarg }
-                    | Operator::F64Load { memarg } => {
-                        if let Some(Operator::I32Const { value }) = prev {
-                            if let Some(data_id) = items.get_data(value as u64 + memarg.offset) {
-                                edges.push((body_id, data_id));
-                            }
+            Operator::I32Load { memarg }
+            | Operator::I32Load8S { memarg }
+            | Operator::I32Load8U { memarg }
+            | Operator::I32Load16S { memarg }
+            | Operator::I32Load16U { memarg }
+            | Operator::I64Load { memarg }
+            | Operator::I64Load8S { memarg }
+            | Operator::I64Load8U { memarg }
+            | Operator::I64Load16S { memarg }
+            | Operator::I64Load16U { memarg }
+            | Operator::I64Load32S { memarg }
+            | Operator::I64Load32U { memarg }
+            | Operator::F32Load { memarg }
+            | Operator::F64Load { memarg } => {
+                let Ok(displacement) = i64::try_from(memarg.offset) else {
+                    continue;
+                };
+                let memory = memarg.memory;
+                match address {
+                    Address::Constant(value) => {
+                        match value
+                            .checked_add(displacement)
+                            .and_then(|address| u64::try_from(address).ok())
+                        {
+                            Some(address) => Reference::Address { memory, address },
+                            None => continue,
                         }
                     }
-                    other => cache = Some(other),
+                    Address::Global(global, value) => match value.checked_add(displacement) {
+                        Some(offset) => Reference::GlobalAddress {
+                            memory,
+                            global,
+                            offset,
+                        },
+                        None => continue,
+                    },
+                    Address::Unknown => continue,
                 }
             }
+            _ => continue,
+        };
+        references.push(BodyReference::Found(b_i, offset, reference));
+    }
+    if let Some((start, function_index)) = call {
+        let size = (body.range().end - start) as u64;
+        references.push(BodyReference::CallSite(b_i, function_index, size));
+    }
+    if walk.control_flow {
+        references.push(BodyReference::ControlFlow(b_i, structures.finish()));
+    }
+    if walk.body_hashes {
+        references.push(BodyReference::Hash(b_i, hasher.finish()));
+    }
+    Ok(())
+}
+
+/// The memories that the given operator uses, if any: the memory it loads from
+/// or stores to, or the memories of an instruction on whole memories, of which
+/// only `memory.copy` uses two.
+fn operator_memories(op: &Operator<'_>) -> [Option<u32>; 2] {
+    let memory = match *op {
+        Operator::I32Load { memarg, .. }
+        | Operator::I64Load { memarg, .. }
+        | Operator::F32Load { memarg, .. }
+        | Operator::F64Load { memarg, .. }
+        | Operator::I32Load8S { memarg, .. }
+        | Operator::I32Load8U { memarg, .. }
+        | Operator::I32Load16S { memarg, .. }
+        | Operator::I32Load16U { memarg, .. }
+        | Operator::I64Load8S { memarg, .. }
+        | Operator::I64Load8U { memarg, .. }
+        | Operator::I64Load16S { memarg, .. }
+        | Operator::I64Load16U { memarg, .. }
+        | Operator::I64Load32S { memarg, .. }
+        | Operator::I64Load32U { memarg, .. }
+        | Operator::I32Store { memarg, .. }
+        | Operator::I64Store { memarg, .. }
+        | Operator::F32Store { memarg, .. }
+        | Operator::F64Store { memarg, .. }
+        | Operator::I32Store8 { memarg, .. }
+        | Operator::I32Store16 { memarg, .. }
+        | Operator::I64Store8 { memarg, .. }
+        | Operator::I64Store16 { memarg, .. }
+        | Operator::I64Store32 { memarg, .. }
+        | Operator::MemoryAtomicNotify { memarg, .. }
+        | Operator::MemoryAtomicWait32 { memarg, .. }
+        | Operator::MemoryAtomicWait64 { memarg, .. }
+        | Operator::I32AtomicLoad { memarg, .. }
+        | Operator::I64AtomicLoad { memarg, .. }
+        | Operator::I32AtomicLoad8U { memarg, .. }
+        | Operator::I32AtomicLoad16U { memarg, .. }
+        | Operator::I64AtomicLoad8U { memarg, .. }
+        | Operator::I64AtomicLoad16U { memarg, .. }
+        | Operator::I64AtomicLoad32U { memarg, .. }
+        | Operator::I32AtomicStore { memarg, .. }
+        | Operator::I64AtomicStore { memarg, .. }
+        | Operator::I32AtomicStore8 { memarg, .. }
+        | Operator::I32AtomicStore16 { memarg, .. }
+        | Operator::I64AtomicStore8 { memarg, .. }
+        | Operator::I64AtomicStore16 { memarg, .. }
+        | Operator::I64AtomicStore32 { memarg, .. }
+        | Operator::I32AtomicRmwAdd { memarg, .. }
+        | Operator::I64AtomicRmwAdd { memarg, .. }
+        | Operator::I32AtomicRmw8AddU { memarg, .. }
+        | Operator::I32AtomicRmw16AddU { memarg, .. }
+        | Operator::I64AtomicRmw8AddU { memarg, .. }
+        | Operator::I64AtomicRmw16AddU { memarg, .. }
+        | Operator::I64AtomicRmw32AddU { memarg, .. }
+        | Operator::I32AtomicRmwSub { memarg, .. }
+        | Operator::I64AtomicRmwSub { memarg, .. }
+        | Operator::I32AtomicRmw8SubU { memarg, .. }
+        | Operator::I32AtomicRmw16SubU { memarg, .. }
+        | Operator::I64AtomicRmw8SubU { memarg, .. }
+        | Operator::I64AtomicRmw16SubU { memarg, .. }
+        | Operator::I64AtomicRmw32SubU { memarg, .. }
+        | Operator::I32AtomicRmwAnd { memarg, .. }
+        | Operator::I64AtomicRmwAnd { memarg, .. }
+        | Operator::I32AtomicRmw8AndU { memarg, .. }
+        | Operator::I32AtomicRmw16AndU { memarg, .. }
+        | Operator::I64AtomicRmw8AndU { memarg, .. }
+        | Operator::I64AtomicRmw16AndU { memarg, .. }
+        | Operator::I64AtomicRmw32AndU { memarg, .. }
+        | Operator::I32AtomicRmwOr { memarg, .. }
+        | Operator::I64AtomicRmwOr { memarg, .. }
+        | Operator::I32AtomicRmw8OrU { memarg, .. }
+        | Operator::I32AtomicRmw16OrU { memarg, .. }
+        | Operator::I64AtomicRmw8OrU { memarg, .. }
+        | Operator::I64AtomicRmw16OrU { memarg, .. }
+        | Operator::I64AtomicRmw32OrU { memarg, .. }
+        | Operator::I32AtomicRmwXor { memarg, .. }
+        | Operator::I64AtomicRmwXor { memarg, .. }
+        | Operator::I32AtomicRmw8XorU { memarg, .. }
+        | Operator::I32AtomicRmw16XorU { memarg, .. }
+        | Operator::I64AtomicRmw8XorU { memarg, .. }
+        | Operator::I64AtomicRmw16XorU { memarg, .. }
+        | Operator::I64AtomicRmw32XorU { memarg, .. }
+        | Operator::I32AtomicRmwXchg { memarg, .. }
+        | Operator::I64AtomicRmwXchg { memarg, .. }
+        | Operator::I32AtomicRmw8XchgU { memarg, .. }
+        | Operator::I32AtomicRmw16XchgU { memarg, .. }
+        | Operator::I64AtomicRmw8XchgU { memarg, .. }
+        | Operator::I64AtomicRmw16XchgU { memarg, .. }
+        | Operator::I64AtomicRmw32XchgU { memarg, .. }
+        | Operator::I32AtomicRmwCmpxchg { memarg, .. }
+        | Operator::I64AtomicRmwCmpxchg { memarg, .. }
+        | Operator::I32AtomicRmw8CmpxchgU { memarg, .. }
+        | Operator::I32AtomicRmw16CmpxchgU { memarg, .. }
+        | Operator::I64AtomicRmw8CmpxchgU { memarg, .. }
+        | Operator::I64AtomicRmw16CmpxchgU { memarg, .. }
+        | Operator::I64AtomicRmw32CmpxchgU { memarg, .. }
+        | Operator::V128Load { memarg, .. }
+        | Operator::V128Load8x8S { memarg, .. }
+        | Operator::V128Load8x8U { memarg, .. }
+        | Operator::V128Load16x4S { memarg, .. }
+        | Operator::V128Load16x4U { memarg, .. }
+        | Operator::V128Load32x2S { memarg, .. }
+        | Operator::V128Load32x2U { memarg, .. }
+        | Operator::V128Load8Splat { memarg, .. }
+        | Operator::V128Load16Splat { memarg, .. }
+        | Operator::V128Load32Splat { memarg, .. }
+        | Operator::V128Load64Splat { memarg, .. }
+        | Operator::V128Load32Zero { memarg, .. }
+        | Operator::V128Load64Zero { memarg, .. }
+        | Operator::V128Store { memarg, .. }
+        | Operator::V128Load8Lane { memarg, .. }
+        | Operator::V128Load16Lane { memarg, .. }
+        | Operator::V128Load32Lane { memarg, .. }
+        | Operator::V128Load64Lane { memarg, .. }
+        | Operator::V128Store8Lane { memarg, .. }
+        | Operator::V128Store16Lane { memarg, .. }
+        | Operator::V128Store32Lane { memarg, .. }
+        | Operator::V128Store64Lane { memarg, .. } => memarg.memory,
+        Operator::MemorySize { mem }
+        | Operator::MemoryGrow { mem }
+        | Operator::MemoryInit { mem, .. }
+        | Operator::MemoryFill { mem }
+        | Operator::MemoryDiscard { mem } => mem,
+        Operator::MemoryCopy { dst_mem, src_mem } => return [Some(dst_mem), Some(src_mem)],
+        _ => return [None, None],
+    };
+    [Some(memory), None]
+}
+
+/// What is known about a value on the operand stack that may be used as an
+/// address.
+#[derive(Clone, Copy, Debug)]
+enum Address {
+    Unknown,
+    Constant(i64),
+    // The given offset past the value of the global with the given index.
+    Global(u32, i64),
+}
+
+/// How many values on top of the operand stack `Addresses` keeps track of.
+const MAX_ADDRESSES: usize = 4;
+
+/// Tracks the values on top of the operand stack while a function body's
+/// operators are walked, so that loads from constant addresses, from sums of
+/// constants, and from constant offsets past a global can be attributed to
+/// the data they read. Only the few operators that such addresses are built
+/// from are understood; any other operator forgets every value.
+#[derive(Default)]
+struct Addresses {
+    stack: Vec<Address>,
+}
+
+impl Addresses {
+    fn visit(&mut self, op: &Operator<'_>) {
+        let value = match *op {
+            Operator::I32Const { value } => Address::Constant(i64::from(value)),
+            Operator::I64Const { value } => Address::Constant(value),
+            Operator::GlobalGet { global_index } => Address::Global(global_index, 0),
+            Operator::LocalGet { .. } => Address::Unknown,
+            Operator::I32Add | Operator::I64Add => {
+                let (b, a) = (self.pop(), self.pop());
+                match (a, b) {
+                    (Address::Constant(a), Address::Constant(b)) => {
+                        a.checked_add(b).map_or(Address::Unknown, Address::Constant)
+                    }
+                    (Address::Global(global, a), Address::Constant(b))
+                    | (Address::Constant(b), Address::Global(global, a)) => a
+                        .checked_add(b)
+                        .map_or(Address::Unknown, |offset| Address::Global(global, offset)),
+                    _ => Address::Unknown,
+                }
+            }
+            // These leave the top of the stack as it is.
+            Operator::Nop | Operator::LocalTee { .. } => return,
+            _ => {
+                self.stack.clear();
+                return;
+            }
+        };
+        if self.stack.len() == MAX_ADDRESSES {
+            self.stack.remove(0);
         }
+        self.stack.push(value);
+    }
 
-        edges
-            .into_iter()
-            .for_each(|(from, to)| items.add_edge(from, to));
+    fn pop(&mut self) -> Address {
+        self.stack.pop().unwrap_or(Address::Unknown)
+    }
 
-        Ok(())
+    /// What is known about the value on top of the stack.
+    fn top(&self) -> Address {
+        self.stack.last().copied().unwrap_or(Address::Unknown)
+    }
+}
+
+/// Tracks the control structures of a function body while its operators are
+/// walked.
+#[derive(Default)]
+struct ControlStructures {
+    structures: u32,
+    depth: u32,
+    max_depth: u32,
+    largest_br_table: u64,
+    // The offset of the previous operator, if it was a `br_table`. Its size is
+    // only known once the next operator's offset is.
+    br_table: Option<usize>,
+}
+
+/// Hashes a function body while its operators are walked: once exactly, and
+/// once with the immediates that refer to other items by index left out, so
+/// that bodies that only differ in how the items they call or use are
+/// numbered hash the same.
+struct BodyHasher<'a> {
+    bytes: &'a [u8],
+    start: usize,
+    normalized: FoldHasher,
+    // The offset of the previous operator, and whether to leave out its
+    // immediates. Its bytes are only known once the next operator's offset is.
+    pending: Option<(usize, bool)>,
+}
+
+impl<'a> BodyHasher<'a> {
+    fn new(body: &wasmparser::FunctionBody<'a>, operators_start: usize) -> BodyHasher<'a> {
+        let bytes = body.as_bytes();
+        let start = body.range().start;
+        let mut normalized = FixedState::default().build_hasher();
+        // The local declarations.
+        normalized.write(&bytes[..operators_start - start]);
+        BodyHasher {
+            bytes,
+            start,
+            normalized,
+            pending: None,
+        }
+    }
+
+    fn visit(&mut self, op: &Operator<'_>, offset: usize) {
+        self.flush(offset);
+        let refers_by_index = matches!(
+            op,
+            Operator::Call { .. }
+                | Operator::ReturnCall { .. }
+                | Operator::CallIndirect { .. }
+                | Operator::ReturnCallIndirect { .. }
+                | Operator::RefFunc { .. }
+                | Operator::GlobalGet { .. }
+                | Operator::GlobalSet { .. }
+                | Operator::Throw { .. }
+                | Operator::Catch { .. }
+        );
+        self.pending = Some((offset, refers_by_index));
+    }
+
+    fn flush(&mut self, end: usize) {
+        if let Some((offset, refers_by_index)) = self.pending.take() {
+            let op = &self.bytes[offset - self.start..end - self.start];
+            // Each of these operators' opcodes is a single byte.
+            self.normalized
+                .write(if refers_by_index { &op[..1] } else { op });
+        }
+    }
+
+    fn finish(mut self) -> ir::BodyHash {
+        self.flush(self.start + self.bytes.len());
+        let exact = FixedState::default().hash_one(self.bytes);
+        ir::BodyHash::new(exact, self.normalized.finish())
+    }
+}
+
+impl ControlStructures {
+    fn visit(&mut self, op: &Operator<'_>, offset: usize) {
+        if let Some(start) = self.br_table.take() {
+            self.largest_br_table = cmp::max(self.largest_br_table, (offset - start) as u64);
+        }
+        match op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Try { .. }
+            | Operator::TryTable { .. } => {
+                self.structures += 1;
+                self.depth += 1;
+                self.max_depth = cmp::max(self.max_depth, self.depth);
+            }
+            // The body's own final `end` has no matching structure.
+            Operator::End | Operator::Delegate { .. } => self.depth = self.depth.saturating_sub(1),
+            Operator::BrTable { .. } => self.br_table = Some(offset),
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> ir::ControlFlow {
+        ir::ControlFlow::new(self.structures, self.max_depth, self.largest_br_table)
     }
 }
 
 impl<'a> Parse<'a> for wasmparser::NameSectionReader<'a> {
     type ItemsExtra = usize;
 
-    fn parse_items(mut self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
+    fn parse_items(mut self, items: &mut ir::ItemsBuilder, idx: usize) -> traits::Result<()> {
         let mut i = 0;
         let mut prev_offset = self.original_position();
 
         while let Some(name_entry_res) = self.next() {
             let current_offset = self.original_position();
-            let size = (current_offset - prev_offset) as u32;
+            let range = prev_offset as u64..current_offset as u64;
+            let size = range.end - range.start;
             prev_offset = current_offset;
 
             // This is a workaround to skip new types of name subsections instead of aborting.
@@ -609,6 +1921,7 @@ impl<'a> Parse<'a> for wasmparser::NameSectionReader<'a> {
             };
             let id = Id::entry(idx, i);
             items.add_root(ir::Item::new(id, name, size, ir::DebugInfo::new()));
+            items.add_byte_range(id, range);
             i += 1;
         }
 
@@ -617,7 +1930,7 @@ impl<'a> Parse<'a> for wasmparser::NameSectionReader<'a> {
 
     type EdgesExtra = ();
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> anyhow::Result<()> {
+    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
         Ok(())
     }
 }
@@ -625,46 +1938,271 @@ impl<'a> Parse<'a> for wasmparser::NameSectionReader<'a> {
 impl<'a> Parse<'a> for wasmparser::CustomSectionReader<'a> {
     type ItemsExtra = usize;
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
+    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> traits::Result<()> {
         match self.as_known() {
             wasmparser::KnownCustom::Name(reader) => {
                 return reader.parse_items(items, idx);
             }
-            _ => {
-                let size = self.data().len() as u32;
-                let id = Id::entry(idx, 0);
-                let name = format!("custom section '{}'", self.name());
-                items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            _ if is_coredump_section(self.name()) => {
+                // A section that does not decode is still part of the core
+                // dump, just not one that can be split up.
+                let entries = coredump_entries(&self).unwrap_or_else(|| {
+                    vec![(
+                        format!("coredump: custom section '{}'", self.name()),
+                        self.data().len() as u64,
+                    )]
+                });
+                for (i, (name, size)) in entries.into_iter().enumerate() {
+                    let id = Id::entry(idx, i);
+                    items.add_item(
+                        ir::Item::new(id, name, size, ir::Misc::new())
+                            .with_category(ir::ItemCategory::Coredump),
+                    );
+                }
             }
+            // A section that does not decode is left as a single item, like
+            // any other custom section.
+            wasmparser::KnownCustom::Producers(reader) => match producers(reader) {
+                Ok(producers) => {
+                    for (i, (producer, range)) in producers.into_iter().enumerate() {
+                        let id = Id::entry(idx, i);
+                        let size = range.end - range.start;
+                        items.add_byte_range(id, range);
+                        items.add_item(ir::Item::new(
+                            id,
+                            producer.to_string(),
+                            size,
+                            ir::Misc::new(),
+                        ));
+                        items.add_producer(producer);
+                    }
+                }
+                Err(_) => add_custom_section_item(&self, items, idx),
+            },
+            _ => add_custom_section_item(&self, items, idx),
         }
         Ok(())
     }
 
-    type EdgesExtra = ();
+    type EdgesExtra = &'a SectionIndices;
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> anyhow::Result<()> {
+    fn parse_edges(
+        self,
+        items: &mut ir::ItemsBuilder,
+        indices: &'a SectionIndices,
+    ) -> traits::Result<()> {
+        if let wasmparser::KnownCustom::Reloc(reader) = self.as_known() {
+            add_relocation_edges(items, indices, reader)?;
+        }
         Ok(())
     }
 }
 
+/// Add an edge for every relocation in a `reloc.*` section of an object file,
+/// from the function body or data segment that the relocated bytes are in, to
+/// the item that the relocation refers to.
+fn add_relocation_edges(
+    items: &mut ir::ItemsBuilder,
+    indices: &SectionIndices,
+    reader: wasmparser::RelocSectionReader<'_>,
+) -> traits::Result<()> {
+    use wasmparser::RelocationType as R;
+
+    let Some(section) = indices.relocatable.get(&reader.section_index()) else {
+        return Ok(());
+    };
+    for relocation in reader.entries().into_iter_with_offsets() {
+        let (offset, relocation) = relocation?;
+        let at = section.start + u64::from(relocation.offset);
+        let i = section
+            .entries
+            .partition_point(|(range, _)| range.end <= at);
+        let Some(&(_, from)) = section
+            .entries
+            .get(i)
+            .filter(|(range, _)| range.contains(&at))
+        else {
+            continue;
+        };
+        let kind = match relocation.ty {
+            R::TypeIndexLeb => {
+                if let Some(type_id) =
+                    lookup(items, &indices.types, "type", relocation.index, offset).flatten()
+                {
+                    items.add_edge_kind(from, type_id, ir::EdgeKind::Type);
+                }
+                continue;
+            }
+            // These locate debugging information, and refer to nothing.
+            R::FunctionOffsetI32 | R::FunctionOffsetI64 | R::SectionOffsetI32 => continue,
+            R::FunctionIndexLeb => ir::EdgeKind::Call,
+            R::GlobalIndexLeb
+            | R::GlobalIndexI32
+            | R::MemoryAddrLeb
+            | R::MemoryAddrSleb
+            | R::MemoryAddrI32
+            | R::MemoryAddrRelSleb
+            | R::MemoryAddrLeb64
+            | R::MemoryAddrSleb64
+            | R::MemoryAddrI64
+            | R::MemoryAddrRelSleb64
+            | R::MemoryAddrTlsSleb
+            | R::MemoryAddrTlsSleb64
+            | R::MemoryAddrLocrelI32 => ir::EdgeKind::Data,
+            _ => ir::EdgeKind::Generic,
+        };
+        // A symbol that the module does not define, like an imported data
+        // symbol, has no item to refer to.
+        if let Some(to) =
+            lookup(items, &indices.symbols, "symbol", relocation.index, offset).flatten()
+        {
+            items.add_edge_kind(from, to, kind);
+        }
+    }
+    Ok(())
+}
+
+/// Add a custom section that `twiggy` does not split up as a single item.
+fn add_custom_section_item(
+    section: &wasmparser::CustomSectionReader<'_>,
+    items: &mut ir::ItemsBuilder,
+    idx: usize,
+) {
+    let size = section.data().len() as u64;
+    let id = Id::entry(idx, 0);
+    let name = format!("custom section '{}'", section.name());
+    let start = section.data_offset() as u64;
+    let item = if is_debug_section(section.name()) {
+        ir::Item::new(id, name, size, ir::DebugInfo::new())
+    } else {
+        ir::Item::new(id, name, size, ir::Misc::new()).with_category(ir::ItemCategory::Section)
+    };
+    items.add_byte_range(id, start..start + size);
+    items.add_item(item);
+}
+
+/// Whether a custom section is debug info: DWARF, or a pointer to debug info
+/// kept outside of the module, as described in the tool conventions:
+/// https://github.com/WebAssembly/tool-conventions/blob/main/Debugging.md
+fn is_debug_section(name: &str) -> bool {
+    name.starts_with(".debug_") || matches!(name, "external_debug_info" | "sourceMappingURL")
+}
+
+/// The values of every field of a `producers` custom section, with the range
+/// of the binary that each takes up: its name and version. The fields' names
+/// and counts are left to the section's headers.
+fn producers(
+    reader: wasmparser::ProducersSectionReader<'_>,
+) -> traits::Result<Vec<(ir::Producer, Range<u64>)>> {
+    let mut producers = vec![];
+    for field in reader {
+        let field = field?;
+        for value in iterate_with_range(field.values) {
+            let (value, range) = value?;
+            producers.push((
+                ir::Producer::new(field.name, value.name, value.version),
+                range,
+            ));
+        }
+    }
+    Ok(producers)
+}
+
+/// Whether a custom section is one of those that make up a core dump, as
+/// described in the tool conventions:
+/// https://github.com/WebAssembly/tool-conventions/blob/main/Coredump.md
+fn is_coredump_section(name: &str) -> bool {
+    matches!(name, "core" | "coremodules" | "coreinstances" | "corestack")
+}
+
+/// Split a core dump custom section into its entries, with their names and
+/// sizes, or `None` if it is malformed. What precedes the entries, such as
+/// their count, is left to the section's headers.
+fn coredump_entries(section: &wasmparser::CustomSectionReader<'_>) -> Option<Vec<(String, u64)>> {
+    let mut reader = wasmparser::BinaryReader::new(section.data(), section.data_offset());
+    let mut entries = vec![];
+    match section.as_known() {
+        wasmparser::KnownCustom::CoreDump(core) => {
+            entries.push((
+                format!("coredump: process '{}'", core.name),
+                section.data().len() as u64,
+            ));
+        }
+        wasmparser::KnownCustom::CoreDumpModules(modules) => {
+            reader.read_var_u32().ok()?;
+            for (i, module) in modules.modules.iter().enumerate() {
+                let start = reader.original_position();
+                reader.read_u8().ok()?;
+                reader.read_string().ok()?;
+                let size = (reader.original_position() - start) as u64;
+                entries.push((format!("coredump: module[{}] '{}'", i, module), size));
+            }
+        }
+        wasmparser::KnownCustom::CoreDumpInstances(instances) => {
+            reader.read_var_u32().ok()?;
+            for (i, instance) in instances.instances.iter().enumerate() {
+                let start = reader.original_position();
+                reader.read::<wasmparser::CoreDumpInstance>().ok()?;
+                let size = (reader.original_position() - start) as u64;
+                entries.push((
+                    format!(
+                        "coredump: instance[{}] of module[{}]",
+                        i, instance.module_index
+                    ),
+                    size,
+                ));
+            }
+        }
+        wasmparser::KnownCustom::CoreDumpStack(stack) => {
+            reader.read_u8().ok()?;
+            reader.read_string().ok()?;
+            reader.read_var_u32().ok()?;
+            for (i, frame) in stack.frames.iter().enumerate() {
+                let start = reader.original_position();
+                reader.read::<wasmparser::CoreDumpStackFrame>().ok()?;
+                let size = (reader.original_position() - start) as u64;
+                entries.push((
+                    format!(
+                        "coredump: thread '{}' frame[{}] in func[{}] of instance[{}]",
+                        stack.name, i, frame.funcidx, frame.instanceidx
+                    ),
+                    size,
+                ));
+            }
+        }
+        // The section did not decode.
+        _ => return None,
+    }
+    Some(entries)
+}
+
 impl<'a> Parse<'a> for wasmparser::TypeSectionReader<'a> {
-    type ItemsExtra = usize;
+    type ItemsExtra = (usize, &'a HashMap<usize, &'a str>);
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, ty) in iterate_with_size(self).enumerate() {
-            let (ty, size) = ty?;
+    fn parse_items(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (idx, names): Self::ItemsExtra,
+    ) -> traits::Result<()> {
+        for (i, ty) in iterate_with_range(self).enumerate() {
+            let (ty, range) = ty?;
+            let size = range.end - range.start;
             let id = Id::entry(idx, i);
 
             if ty.is_explicit_rec_group() {
                 continue;
             }
+            items.add_byte_range(id, range);
 
             // If the RecGroup is not an explicit recursive group,
             // it contains exactly one composite type.
             let comp_type = &ty.types().next().unwrap().composite_type.inner;
             match comp_type {
                 wasmparser::CompositeInnerType::Func(func) => {
-                    let mut name = format!("type[{}]: (", i);
+                    let mut name = match names.get(&i) {
+                        Some(name) => format!("type '{}': (", name),
+                        None => format!("type[{}]: (", i),
+                    };
                     for (i, param) in func.params().iter().enumerate() {
                         if i != 0 {
                             name.push_str(", ");
@@ -690,7 +2228,10 @@ impl<'a> Parse<'a> for wasmparser::TypeSectionReader<'a> {
                         }
                     }
 
-                    items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+                    items.add_item(
+                        ir::Item::new(id, name, size, ir::Misc::new())
+                            .with_category(ir::ItemCategory::Type),
+                    );
                 }
                 wasmparser::CompositeInnerType::Array(_) => {}
                 wasmparser::CompositeInnerType::Struct(_) => {}
@@ -702,7 +2243,7 @@ impl<'a> Parse<'a> for wasmparser::TypeSectionReader<'a> {
 
     type EdgesExtra = ();
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> anyhow::Result<()> {
+    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
         Ok(())
     }
 }
@@ -710,80 +2251,209 @@ impl<'a> Parse<'a> for wasmparser::TypeSectionReader<'a> {
 impl<'a> Parse<'a> for wasmparser::ImportSectionReader<'a> {
     type ItemsExtra = usize;
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, imp) in iterate_with_size(self).enumerate() {
-            let (imp, size) = imp?;
+    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> traits::Result<()> {
+        // Some bundlers import the same thing by the same module and name
+        // more than once. Every copy after the first is marked, and numbered
+        // in its name.
+        // For each type imported by a module and name: its first import, and
+        // how many times it has been imported.
+        type Copies = Vec<(wasmparser::TypeRef, Id, usize)>;
+        let mut seen: HashMap<(&str, &str), Copies> = HashMap::new();
+        for (i, imp) in iterate_with_range(self).enumerate() {
+            let (imp, range) = imp?;
+            let size = range.end - range.start;
             let id = Id::entry(idx, i);
-            let name = format!("import {}::{}", imp.module, imp.name);
-            items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_byte_range(id, range);
+            let copies = seen.entry((imp.module, imp.name)).or_default();
+            let (name, first) = match copies.iter_mut().find(|(ty, _, _)| *ty == imp.ty) {
+                Some((_, first, count)) => {
+                    *count += 1;
+                    let name =
+                        format!("import {}::{} (duplicate #{})", imp.module, imp.name, count);
+                    (name, Some(*first))
+                }
+                None => {
+                    copies.push((imp.ty, id, 1));
+                    (format!("import {}::{}", imp.module, imp.name), None)
+                }
+            };
+            items.add_item(
+                ir::Item::new(id, name, size, ir::Misc::new())
+                    .with_category(ir::ItemCategory::Import),
+            );
+            let name_bytes = string_size(imp.module) + string_size(imp.name);
+            items.set_name_bytes(id, cmp::min(name_bytes, size));
+            if let Some(first) = first {
+                items.set_duplicate_import(id, first);
+            }
         }
         Ok(())
     }
 
-    type EdgesExtra = ();
+    type EdgesExtra = (&'a SectionIndices, usize);
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, (): ()) -> anyhow::Result<()> {
+    fn parse_edges(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (indices, idx): Self::EdgesExtra,
+    ) -> traits::Result<()> {
+        for (i, imp) in self.into_iter_with_offsets().enumerate() {
+            let (offset, imp) = imp?;
+            if let wasmparser::TypeRef::Func(type_ref)
+            | wasmparser::TypeRef::Tag(wasmparser::TagType {
+                func_type_idx: type_ref,
+                ..
+            }) = imp.ty
+            {
+                if let Some(type_id) =
+                    lookup(items, &indices.types, "type", type_ref, offset).flatten()
+                {
+                    items.add_edge_kind(Id::entry(idx, i), type_id, ir::EdgeKind::Type);
+                }
+            }
+        }
         Ok(())
     }
 }
 
 impl<'a> Parse<'a> for wasmparser::TableSectionReader<'a> {
-    type ItemsExtra = usize;
+    type ItemsExtra = (usize, usize, &'a HashMap<usize, &'a str>);
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, entry) in iterate_with_size(self).enumerate() {
-            let (_entry, size) = entry?;
+    fn parse_items(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (idx, imported, names): Self::ItemsExtra,
+    ) -> traits::Result<()> {
+        for (i, entry) in iterate_with_range(self).enumerate() {
+            let (_entry, range) = entry?;
+            let size = range.end - range.start;
             let id = Id::entry(idx, i);
-            let name = format!("table[{}]", i);
-            items.add_root(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_byte_range(id, range);
+            let name = entry_name(names, "table", imported + i, i);
+            items.add_root(
+                ir::Item::new(id, name, size, ir::Misc::new())
+                    .with_category(ir::ItemCategory::Table),
+            );
         }
         Ok(())
     }
 
     type EdgesExtra = ();
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> anyhow::Result<()> {
+    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
         Ok(())
     }
 }
 
 impl<'a> Parse<'a> for wasmparser::MemorySectionReader<'a> {
-    type ItemsExtra = usize;
+    type ItemsExtra = (usize, usize, &'a HashMap<usize, &'a str>);
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, mem) in iterate_with_size(self).enumerate() {
-            let (_mem, size) = mem?;
+    fn parse_items(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (idx, imported, names): Self::ItemsExtra,
+    ) -> traits::Result<()> {
+        for (i, mem) in iterate_with_range(self).enumerate() {
+            let (_mem, range) = mem?;
+            let size = range.end - range.start;
             let id = Id::entry(idx, i);
-            let name = format!("memory[{}]", i);
-            items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_byte_range(id, range);
+            let name = entry_name(names, "memory", imported + i, i);
+            items.add_item(
+                ir::Item::new(id, name, size, ir::Misc::new())
+                    .with_category(ir::ItemCategory::Memory),
+            );
         }
         Ok(())
     }
 
     type EdgesExtra = ();
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> anyhow::Result<()> {
+    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
         Ok(())
     }
 }
 
 impl<'a> Parse<'a> for wasmparser::GlobalSectionReader<'a> {
-    type ItemsExtra = usize;
+    type ItemsExtra = (usize, usize, &'a HashMap<usize, &'a str>);
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, g) in iterate_with_size(self).enumerate() {
-            let (g, size) = g?;
+    fn parse_items(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (idx, imported, names): Self::ItemsExtra,
+    ) -> traits::Result<()> {
+        for (i, g) in iterate_with_range(self).enumerate() {
+            let (g, range) = g?;
+            let size = range.end - range.start;
             let id = Id::entry(idx, i);
-            let name = format!("global[{}]", i);
+            items.add_byte_range(id, range);
+            let name = entry_name(names, "global", imported + i, i);
             let ty = ty2str(g.ty.content_type).to_string();
-            items.add_item(ir::Item::new(id, name, size, ir::Data::new(Some(ty))));
+            items.add_item(
+                ir::Item::new(id, name, size, ir::Data::new(Some(ty)))
+                    .with_category(ir::ItemCategory::Global),
+            );
         }
         Ok(())
     }
 
-    type EdgesExtra = ();
+    type EdgesExtra = (&'a SectionIndices, usize);
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> anyhow::Result<()> {
+    fn parse_edges(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (indices, idx): Self::EdgesExtra,
+    ) -> traits::Result<()> {
+        // A global of a function reference type may be initialized with a
+        // `ref.func`.
+        for (i, g) in self.into_iter().enumerate() {
+            for op in g?.init_expr.get_operators_reader().into_iter_with_offsets() {
+                if let (Operator::RefFunc { function_index }, offset) = op? {
+                    let functions = &indices.functions;
+                    if let Some(function) =
+                        lookup(items, functions, "function", function_index, offset)
+                    {
+                        items.add_edge_kind(Id::entry(idx, i), function, ir::EdgeKind::Generic);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for wasmparser::TagSectionReader<'a> {
+    type ItemsExtra = usize;
+
+    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> traits::Result<()> {
+        for (i, tag) in iterate_with_range(self).enumerate() {
+            let (_tag, range) = tag?;
+            let size = range.end - range.start;
+            let id = Id::entry(idx, i);
+            items.add_byte_range(id, range);
+            let name = ir::Name::indexed("tag", i);
+            items.add_item(
+                ir::Item::new(id, name, size, ir::Misc::new()).with_category(ir::ItemCategory::Tag),
+            );
+        }
+        Ok(())
+    }
+
+    type EdgesExtra = (&'a SectionIndices, usize);
+
+    fn parse_edges(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (indices, idx): Self::EdgesExtra,
+    ) -> traits::Result<()> {
+        for (i, tag) in self.into_iter_with_offsets().enumerate() {
+            let (offset, tag) = tag?;
+            if let Some(type_id) =
+                lookup(items, &indices.types, "type", tag.func_type_idx, offset).flatten()
+            {
+                items.add_edge_kind(Id::entry(idx, i), type_id, ir::EdgeKind::Type);
+            }
+        }
         Ok(())
     }
 }
@@ -791,12 +2461,18 @@ impl<'a> Parse<'a> for wasmparser::GlobalSectionReader<'a> {
 impl<'a> Parse<'a> for wasmparser::ExportSectionReader<'a> {
     type ItemsExtra = usize;
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, exp) in iterate_with_size(self).enumerate() {
-            let (exp, size) = exp?;
+    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> traits::Result<()> {
+        for (i, exp) in iterate_with_range(self).enumerate() {
+            let (exp, range) = exp?;
+            let size = range.end - range.start;
             let id = Id::entry(idx, i);
+            items.add_byte_range(id, range);
             let name = format!("export \"{}\"", exp.name);
-            items.add_root(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_root(
+                ir::Item::new(id, name, size, ir::Misc::new())
+                    .with_category(ir::ItemCategory::Export),
+            );
+            items.set_name_bytes(id, cmp::min(string_size(exp.name), size));
         }
         Ok(())
     }
@@ -807,24 +2483,20 @@ impl<'a> Parse<'a> for wasmparser::ExportSectionReader<'a> {
         self,
         items: &mut ir::ItemsBuilder,
         (indices, idx): Self::EdgesExtra,
-    ) -> anyhow::Result<()> {
-        for (i, exp) in iterate_with_size(self).enumerate() {
-            let (exp, _) = exp?;
+    ) -> traits::Result<()> {
+        items.reserve_edges(capacity(&self));
+        for (i, exp) in self.into_iter_with_offsets().enumerate() {
+            let (offset, exp) = exp?;
             let exp_id = Id::entry(idx, i);
-            match exp.kind {
-                wasmparser::ExternalKind::Func => {
-                    items.add_edge(exp_id, indices.functions[exp.index as usize]);
-                }
-                wasmparser::ExternalKind::Table => {
-                    items.add_edge(exp_id, indices.tables[exp.index as usize]);
-                }
-                wasmparser::ExternalKind::Memory => {
-                    items.add_edge(exp_id, indices.memories[exp.index as usize]);
-                }
-                wasmparser::ExternalKind::Global => {
-                    items.add_edge(exp_id, indices.globals[exp.index as usize]);
-                }
-                wasmparser::ExternalKind::Tag => {}
+            let (space, kind) = match exp.kind {
+                wasmparser::ExternalKind::Func => (&indices.functions, "function"),
+                wasmparser::ExternalKind::Table => (&indices.tables, "table"),
+                wasmparser::ExternalKind::Memory => (&indices.memories, "memory"),
+                wasmparser::ExternalKind::Global => (&indices.globals, "global"),
+                wasmparser::ExternalKind::Tag => (&indices.tags, "tag"),
+            };
+            if let Some(target) = lookup(items, space, kind, exp.index, offset) {
+                items.add_edge_kind(exp_id, target, ir::EdgeKind::Export);
             }
         }
 
@@ -832,15 +2504,22 @@ impl<'a> Parse<'a> for wasmparser::ExportSectionReader<'a> {
     }
 }
 
-struct StartSection<'a> {
+/// The start section's function index, as an entry of its own: it is what
+/// keeps the start function alive.
+struct StartSection {
     function_index: u32,
-    _data: &'a [u8], // We only need the size.
+    range: Range<usize>,
 }
 
-impl<'a> Parse<'a> for StartSection<'a> {
+impl<'a> Parse<'a> for StartSection {
     type ItemsExtra = usize;
 
-    fn parse_items(self, _: &mut ir::ItemsBuilder, _: usize) -> anyhow::Result<()> {
+    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> traits::Result<()> {
+        let id = Id::entry(idx, 0);
+        let range = self.range.start as u64..self.range.end as u64;
+        let size = range.end - range.start;
+        items.add_byte_range(id, range);
+        items.add_root(ir::Item::new(id, "start function", size, ir::Misc::new()));
         Ok(())
     }
 
@@ -850,42 +2529,103 @@ impl<'a> Parse<'a> for StartSection<'a> {
         self,
         items: &mut ir::ItemsBuilder,
         (indices, idx): Self::EdgesExtra,
-    ) -> anyhow::Result<()> {
-        items.add_edge(
-            Id::section(idx),
-            indices.functions[self.function_index as usize],
-        );
+    ) -> traits::Result<()> {
+        if let Some(function) = lookup(
+            items,
+            &indices.functions,
+            "function",
+            self.function_index,
+            self.range.start,
+        ) {
+            items.add_edge_kind(Id::entry(idx, 0), function, ir::EdgeKind::Call);
+        }
         Ok(())
     }
 }
 
-impl<'a> Parse<'a> for wasmparser::ElementSectionReader<'a> {
+/// The data count section's count of data segments, as an entry of its own.
+struct DataCountSection {
+    range: Range<usize>,
+}
+
+impl<'a> Parse<'a> for DataCountSection {
     type ItemsExtra = usize;
 
-    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> anyhow::Result<()> {
-        for (i, elem) in iterate_with_size(self).enumerate() {
-            let (_elem, size) = elem?;
+    fn parse_items(self, items: &mut ir::ItemsBuilder, idx: usize) -> traits::Result<()> {
+        let id = Id::entry(idx, 0);
+        let range = self.range.start as u64..self.range.end as u64;
+        let size = range.end - range.start;
+        items.add_byte_range(id, range);
+        items.add_root(ir::Item::new(id, "data count", size, ir::Misc::new()));
+        Ok(())
+    }
+
+    type EdgesExtra = ();
+
+    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> Parse<'a> for wasmparser::ElementSectionReader<'a> {
+    type ItemsExtra = (usize, &'a HashMap<usize, &'a str>, Option<u32>);
+
+    fn parse_items(
+        self,
+        items: &mut ir::ItemsBuilder,
+        (idx, names, aggregate_threshold): Self::ItemsExtra,
+    ) -> traits::Result<()> {
+        let count = self.count();
+        if aggregated(count, aggregate_threshold) {
+            let mut size = 0;
+            for elem in iterate_with_range(self) {
+                let range = elem?.1;
+                size += range.end - range.start;
+                items.add_byte_range(Id::entry(idx, 0), range);
+            }
+            let name = aggregate_name("elem", count, size, aggregate_threshold);
+            items.add_item(
+                ir::Item::new(Id::entry(idx, 0), name, size, ir::Misc::new())
+                    .with_category(ir::ItemCategory::ElementSegment),
+            );
+            return Ok(());
+        }
+
+        for (i, elem) in iterate_with_range(self).enumerate() {
+            let (_elem, range) = elem?;
+            let size = range.end - range.start;
             let id = Id::entry(idx, i);
-            let name = format!("elem[{}]", i);
-            items.add_item(ir::Item::new(id, name, size, ir::Misc::new()));
+            items.add_byte_range(id, range);
+            let name = entry_name(names, "elem", i, i);
+            items.add_item(
+                ir::Item::new(id, name, size, ir::Misc::new())
+                    .with_category(ir::ItemCategory::ElementSegment),
+            );
         }
         Ok(())
     }
 
-    type EdgesExtra = (&'a SectionIndices, usize);
+    type EdgesExtra = (&'a SectionIndices, usize, Option<u32>);
 
     fn parse_edges(
         self,
         items: &mut ir::ItemsBuilder,
-        (indices, idx): Self::EdgesExtra,
-    ) -> anyhow::Result<()> {
-        for (i, elem) in iterate_with_size(self).enumerate() {
-            let (elem, _size) = elem?;
-            let elem_id = Id::entry(idx, i);
+        (indices, idx, aggregate_threshold): Self::EdgesExtra,
+    ) -> traits::Result<()> {
+        // Every entry of an aggregated section is represented by its first.
+        let aggregate = aggregated(self.count(), aggregate_threshold);
+        for (i, elem) in self.into_iter_with_offsets().enumerate() {
+            let (offset, elem) = elem?;
+            let elem_id = Id::entry(idx, if aggregate { 0 } else { i });
 
             match elem.kind {
                 wasmparser::ElementKind::Active { table_index, .. } => {
-                    items.add_edge(indices.tables[table_index.unwrap_or(0) as usize], elem_id);
+                    let table_index = table_index.unwrap_or(0);
+                    if let Some(table) =
+                        lookup(items, &indices.tables, "table", table_index, offset)
+                    {
+                        items.add_edge_kind(table, elem_id, ir::EdgeKind::TableElement);
+                    }
                 }
                 wasmparser::ElementKind::Declared => {}
                 wasmparser::ElementKind::Passive => {}
@@ -893,11 +2633,36 @@ impl<'a> Parse<'a> for wasmparser::ElementSectionReader<'a> {
 
             match elem.items {
                 wasmparser::ElementItems::Functions(section_limited) => {
-                    for func_idx in section_limited {
-                        items.add_edge(elem_id, indices.functions[func_idx? as usize]);
+                    for func_idx in section_limited.into_iter_with_offsets() {
+                        let (offset, func_idx) = func_idx?;
+                        let functions = &indices.functions;
+                        if let Some(function) =
+                            lookup(items, functions, "function", func_idx, offset)
+                        {
+                            items.add_edge_kind(elem_id, function, ir::EdgeKind::TableElement);
+                        }
+                    }
+                }
+                // Each expression is a single `ref.func` or `ref.null`; only
+                // the former refers to a function.
+                wasmparser::ElementItems::Expressions(_ref_type, section_limited) => {
+                    for expr in section_limited {
+                        for op in expr?.get_operators_reader().into_iter_with_offsets() {
+                            if let (Operator::RefFunc { function_index }, offset) = op? {
+                                let functions = &indices.functions;
+                                if let Some(function) =
+                                    lookup(items, functions, "function", function_index, offset)
+                                {
+                                    items.add_edge_kind(
+                                        elem_id,
+                                        function,
+                                        ir::EdgeKind::TableElement,
+                                    );
+                                }
+                            }
+                        }
                     }
                 }
-                wasmparser::ElementItems::Expressions(_ref_type, _section_limited) => {}
             }
         }
 
@@ -906,25 +2671,41 @@ impl<'a> Parse<'a> for wasmparser::ElementSectionReader<'a> {
 }
 
 impl<'a> Parse<'a> for wasmparser::DataSectionReader<'a> {
-    type ItemsExtra = (usize, &'a HashMap<usize, &'a str>);
+    type ItemsExtra = (usize, &'a HashMap<usize, &'a str>, Option<u32>);
 
     fn parse_items(
         self,
         items: &mut ir::ItemsBuilder,
-        (idx, names): Self::ItemsExtra,
-    ) -> anyhow::Result<()> {
-        for (i, d) in iterate_with_size(self).enumerate() {
-            let (d, size) = d?;
-            let id = Id::entry(idx, i);
-            let name = names.get(&i).map_or_else(
-                || format!("data[{}]", i),
-                |name| format!("data segment \"{}\"", name),
-            );
-            items.add_item(ir::Item::new(id, name, size, ir::Data::new(None)));
+        (idx, names, aggregate_threshold): Self::ItemsExtra,
+    ) -> traits::Result<()> {
+        let count = self.count();
+        let aggregate = aggregated(count, aggregate_threshold);
+        let mut aggregate_size = 0;
+        for (i, d) in iterate_with_range(self).enumerate() {
+            let (d, range) = d?;
+            let size = range.end - range.start;
+            let id = if aggregate {
+                aggregate_size += size;
+                Id::entry(idx, 0)
+            } else {
+                let id = Id::entry(idx, i);
+                let name = names.get(&i).map_or_else(
+                    || ir::Name::indexed("data", i),
+                    |name| format!("data segment \"{}\"", name).into(),
+                );
+                items.add_item(ir::Item::new(id, name, size, ir::Data::new(None)));
+                id
+            };
+            items.add_byte_range(id, range);
+            items.add_payload_bytes(id, d.data.len() as u64);
 
             // Get the constant address (if any) from the initialization
             // expression.
-            if let wasmparser::DataKind::Active { offset_expr, .. } = d.kind {
+            if let wasmparser::DataKind::Active {
+                memory_index,
+                offset_expr,
+            } = d.kind
+            {
                 let mut iter = offset_expr.get_operators_reader();
                 let offset = match iter.read()? {
                     Operator::I32Const { value } => Some(i64::from(value)),
@@ -932,37 +2713,92 @@ impl<'a> Parse<'a> for wasmparser::DataSectionReader<'a> {
                     _ => None,
                 };
 
-                if let Some(off) = offset {
-                    let length = d.data.len(); // size of data
-                    items.link_data(off, length, id);
+                if let Some(off) = offset.and_then(|off| u64::try_from(off).ok()) {
+                    let length = d.data.len() as u64; // size of data
+                    items.link_data(memory_index, off..off.saturating_add(length), id);
                 }
             }
         }
+
+        if aggregate {
+            let name = aggregate_name("data", count, aggregate_size, aggregate_threshold);
+            items.add_item(ir::Item::new(
+                Id::entry(idx, 0),
+                name,
+                aggregate_size,
+                ir::Data::new(None),
+            ));
+        }
         Ok(())
     }
 
     type EdgesExtra = ();
 
-    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> anyhow::Result<()> {
+    fn parse_edges(self, _: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
         Ok(())
     }
 }
 
 fn iterate_with_size<'a, T: FromReader<'a> + 'a>(
     s: SectionLimited<'a, T>,
-) -> impl Iterator<Item = anyhow::Result<(T, u32)>> + 'a {
+) -> impl Iterator<Item = traits::Result<(T, u64)>> + 'a {
+    iterate_with_range(s).map(|entry| entry.map(|(entry, range)| (entry, range.end - range.start)))
+}
+
+/// Like `iterate_with_size`, but with the range of offsets into the binary
+/// that each entry takes up.
+fn iterate_with_range<'a, T: FromReader<'a> + 'a>(
+    s: SectionLimited<'a, T>,
+) -> impl Iterator<Item = traits::Result<(T, Range<u64>)>> + 'a {
+    // A section without entries, as some linkers emit, is all header: it
+    // yields nothing, and its bytes stay with the section's own item.
     let count = s.count() as usize;
+    if count == 0 {
+        return None.into_iter().flatten();
+    }
     let end = s.range().end;
     let mut iter = s.into_iter_with_offsets().peekable();
-    (0..count).map(move |_| {
-        let (offset, item) = iter.next().unwrap()?;
+    Some((0..count).map(move |_| {
+        let (offset, item) = iter.next().ok_or_else(|| traits::Error::Malformed {
+            section: None,
+            offset: end,
+            message: "section ended before all of its entries were read".to_string(),
+        })??;
         let next_offset = match iter.peek() {
             Some(Ok((n, _))) => *n,
             Some(Err(err)) => return Err(err.clone().into()),
             None => end,
         };
-        Ok((item, (next_offset - offset) as u32))
-    })
+        Ok((item, offset as u64..next_offset as u64))
+    }))
+    .into_iter()
+    .flatten()
+}
+
+/// The item for a section itself: the bytes of its headers, and whatever its
+/// entries' items, which took up `added` of its `size` bytes, did not cover.
+/// For a section without entries, that is all of it.
+fn section_item(id: Id, name: String, size: u64, added: u64) -> traits::Result<ir::Item> {
+    if added > size {
+        return Err(traits::Error::Analysis(format!(
+            "{}: entries take up {} bytes, more than the section's {}",
+            name, added, size
+        )));
+    }
+    Ok(ir::Item::new(id, name, size - added, ir::Misc::new())
+        .with_category(ir::ItemCategory::Section))
+}
+
+/// The number of bytes a string takes up in a wasm binary: its UTF-8 bytes,
+/// preceded by their count as LEB128. Assumes the count is minimally encoded,
+/// as every known toolchain does.
+fn string_size(s: &str) -> u64 {
+    let len = s.len() as u64;
+    let mut prefix = 1;
+    while len >> (7 * prefix) != 0 {
+        prefix += 1;
+    }
+    prefix + len
 }
 
 fn ty2str(t: ValType) -> &'static str {
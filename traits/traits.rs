@@ -7,6 +7,94 @@ use std::io;
 use std::str::FromStr;
 use twiggy_ir as ir;
 
+/// An error that occurred while parsing a binary or running an analysis on it.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input is not in any binary format that `twiggy` can parse.
+    #[error("input is not a recognized binary format")]
+    NotAFormat,
+
+    /// Reading the input or writing the output failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The input is in a recognized format, but is malformed.
+    #[error(
+        "{message} (at offset {offset:#x}{})",
+        .section.as_ref().map(|s| format!(", while parsing {}", s)).unwrap_or_default()
+    )]
+    Malformed {
+        /// The section being parsed, if known.
+        section: Option<String>,
+        /// The offset in the input at which the problem was found.
+        offset: usize,
+        /// A description of the problem.
+        message: String,
+    },
+
+    /// The input or the requested analysis uses something that `twiggy` does
+    /// not support yet.
+    #[error("{feature} is not supported")]
+    Unsupported {
+        /// The unsupported feature.
+        feature: String,
+    },
+
+    /// The analysis could not be run with the given options.
+    #[error("{0}")]
+    Analysis(String),
+
+    /// The input is a snapshot that cannot be read back, because it is of
+    /// another version of the snapshot format, or is malformed.
+    #[error("{0}")]
+    Snapshot(String),
+}
+
+impl Error {
+    /// Attach the name of the section being parsed to a `Malformed` error that
+    /// does not have one yet. Other errors are returned unchanged.
+    pub fn in_section(self, name: &str) -> Error {
+        match self {
+            Error::Malformed {
+                section: None,
+                offset,
+                message,
+            } => Error::Malformed {
+                section: Some(name.to_string()),
+                offset,
+                message,
+            },
+            e => e,
+        }
+    }
+}
+
+impl From<wasmparser::BinaryReaderError> for Error {
+    fn from(e: wasmparser::BinaryReaderError) -> Error {
+        Error::Malformed {
+            section: None,
+            offset: e.offset(),
+            message: e.message().to_string(),
+        }
+    }
+}
+
+impl From<regex::Error> for Error {
+    fn from(e: regex::Error) -> Error {
+        Error::Analysis(e.to_string())
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(e: csv::Error) -> Error {
+        Error::Io(e.into())
+    }
+}
+
+/// A `Result` whose error is a `twiggy_traits::Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
 /// An analysis takes our IR and returns some kind of data results that can be
 /// emitted.
 pub trait Analyze {
@@ -14,7 +102,7 @@ pub trait Analyze {
     type Data: Emit;
 
     /// Run this analysis on the given IR items.
-    fn analyze(items: &mut ir::Items) -> anyhow::Result<Self::Data>;
+    fn analyze(items: &mut ir::Items) -> Result<Self::Data>;
 }
 
 /// Selects the parse mode for the input data.
@@ -58,8 +146,6 @@ pub enum OutputFormat {
 
     // /// Hyper Text Markup Language.
     // Html,
-    // /// Graphviz dot format.
-    // Dot,
     /// Comma-separated values (CSV) format.
     #[cfg(feature = "emit_csv")]
     Csv,
@@ -67,6 +153,15 @@ pub enum OutputFormat {
     /// JavaScript Object Notation format.
     #[cfg(feature = "emit_json")]
     Json,
+
+    /// Folded stacks, one `frame;frame;frame size` line per stack, as read by
+    /// flamegraph tools such as `inferno` and speedscope.
+    #[cfg(feature = "emit_text")]
+    Folded,
+
+    /// A Graphviz DOT digraph.
+    #[cfg(feature = "emit_text")]
+    Dot,
 }
 
 #[cfg(feature = "emit_text")]
@@ -81,7 +176,7 @@ impl Default for OutputFormat {
 impl FromStr for OutputFormat {
     type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> anyhow::Result<Self> {
         match s {
             #[cfg(feature = "emit_text")]
             "text" => Ok(OutputFormat::Text),
@@ -89,6 +184,10 @@ impl FromStr for OutputFormat {
             "json" => Ok(OutputFormat::Json),
             #[cfg(feature = "emit_csv")]
             "csv" => Ok(OutputFormat::Csv),
+            #[cfg(feature = "emit_text")]
+            "folded" => Ok(OutputFormat::Folded),
+            #[cfg(feature = "emit_text")]
+            "dot" => Ok(OutputFormat::Dot),
             _ => Err(anyhow!("Unknown output format: {}", s)),
         }
     }
@@ -103,34 +202,52 @@ pub trait Emit {
         items: &ir::Items,
         destination: &mut dyn io::Write,
         format: OutputFormat,
-    ) -> anyhow::Result<()> {
+    ) -> Result<()> {
         match format {
             #[cfg(feature = "emit_text")]
             OutputFormat::Text => self.emit_text(items, destination),
             // OutputFormat::Html => self.emit_html(destination),
-            // OutputFormat::Dot => self.emit_dot(destination),
             #[cfg(feature = "emit_csv")]
             OutputFormat::Csv => self.emit_csv(items, destination),
             #[cfg(feature = "emit_json")]
             OutputFormat::Json => self.emit_json(items, destination),
+            #[cfg(feature = "emit_text")]
+            OutputFormat::Folded => self.emit_folded(items, destination),
+            #[cfg(feature = "emit_text")]
+            OutputFormat::Dot => self.emit_dot(items, destination),
         }
     }
 
     /// Emit human readable text.
     #[cfg(feature = "emit_text")]
-    fn emit_text(&self, items: &ir::Items, destination: &mut dyn io::Write) -> anyhow::Result<()>;
+    fn emit_text(&self, items: &ir::Items, destination: &mut dyn io::Write) -> Result<()>;
 
     // /// Emit HTML.
     // fn emit_html(&self, destination: &mut dyn io::Write) -> Result<(), Error>;
 
-    // /// Emit Graphviz's dot format.
-    // fn emit_dot(&self, destination: &mut dyn io::Write) -> Result<(), Error>;
-
     /// Emit CSV.
     #[cfg(feature = "emit_csv")]
-    fn emit_csv(&self, items: &ir::Items, destination: &mut dyn io::Write) -> anyhow::Result<()>;
+    fn emit_csv(&self, items: &ir::Items, destination: &mut dyn io::Write) -> Result<()>;
 
     /// Emit JSON.
     #[cfg(feature = "emit_json")]
-    fn emit_json(&self, items: &ir::Items, destination: &mut dyn io::Write) -> anyhow::Result<()>;
+    fn emit_json(&self, items: &ir::Items, destination: &mut dyn io::Write) -> Result<()>;
+
+    /// Emit folded stacks. Only analyses whose results are trees support this
+    /// format.
+    #[cfg(feature = "emit_text")]
+    fn emit_folded(&self, _items: &ir::Items, _destination: &mut dyn io::Write) -> Result<()> {
+        Err(Error::Unsupported {
+            feature: "folded output for this analysis".to_string(),
+        })
+    }
+
+    /// Emit a Graphviz DOT digraph. Only analyses whose results are trees or
+    /// graphs support this format.
+    #[cfg(feature = "emit_text")]
+    fn emit_dot(&self, _items: &ir::Items, _destination: &mut dyn io::Write) -> Result<()> {
+        Err(Error::Unsupported {
+            feature: "dot output for this analysis".to_string(),
+        })
+    }
 }
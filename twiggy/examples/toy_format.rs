@@ -0,0 +1,103 @@
+//! Feed a custom binary format into `twiggy`'s analyses.
+//!
+//! The toy format parsed here has one item per line: whether it is a `root` or
+//! an `item`, its name, its size in bytes, and then the names of the items it
+//! references. The binary's total size is the sum of its items' sizes.
+//!
+//! Run with `cargo run --example toy_format`.
+
+use std::collections::HashMap;
+use std::io;
+
+use twiggy_ir as ir;
+use twiggy_parser::Parse;
+use twiggy_traits::{self as traits, Emit};
+
+const TOY_BINARY: &str = "\
+root main 10 parse render
+item parse 120 alloc
+item render 300 alloc format
+item format 80
+item alloc 45
+item unused 64 format
+";
+
+#[derive(Clone)]
+struct ToyFormat<'a> {
+    source: &'a str,
+}
+
+impl<'a> ToyFormat<'a> {
+    fn lines(&self) -> impl Iterator<Item = (ir::Id, Vec<&'a str>)> {
+        self.source
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(i, line)| (ir::Id::entry(0, i), line.split_whitespace().collect()))
+    }
+
+    fn size(&self) -> traits::Result<u64> {
+        self.lines()
+            .map(|(_, fields)| fields.get(2).map_or(Ok(0), |size| self.parse_size(size)))
+            .sum()
+    }
+
+    fn parse_size(&self, size: &str) -> traits::Result<u64> {
+        size.parse()
+            .map_err(|_| self.malformed(size, "invalid toy item size"))
+    }
+
+    fn malformed(&self, field: &str, message: &str) -> traits::Error {
+        traits::Error::Malformed {
+            section: None,
+            offset: field.as_ptr() as usize - self.source.as_ptr() as usize,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl<'a> Parse<'a> for ToyFormat<'a> {
+    type ItemsExtra = ();
+
+    fn parse_items(self, items: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
+        for (id, fields) in self.lines() {
+            let (kind, name, size) = match fields[..] {
+                [kind, name, size, ..] => (kind, name, self.parse_size(size)?),
+                _ => return Err(self.malformed(fields[0], "missing toy item size")),
+            };
+            let item = ir::Item::new(id, name, size, ir::Code::new(name));
+            match kind {
+                "root" => items.add_root(item),
+                _ => items.add_item(item),
+            };
+        }
+        Ok(())
+    }
+
+    type EdgesExtra = ();
+
+    fn parse_edges(self, items: &mut ir::ItemsBuilder, _: ()) -> traits::Result<()> {
+        let ids: HashMap<&str, ir::Id> = self.lines().map(|(id, fields)| (fields[1], id)).collect();
+        for (from, fields) in self.lines() {
+            for name in &fields[3..] {
+                let to = ids
+                    .get(name)
+                    .ok_or_else(|| self.malformed(name, "unknown toy item"))?;
+                items.add_edge(from, *to);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn main() -> traits::Result<()> {
+    let input = ToyFormat { source: TOY_BINARY };
+    let mut items = twiggy_parser::build_items(input.size()?, input, (), ())?;
+
+    let mut options = twiggy_opt::Top::default();
+    options.set_retained(true);
+    let top = twiggy_analyze::top(&mut items, &options)?;
+
+    let mut stdout = io::stdout();
+    top.emit(&items, &mut stdout, traits::OutputFormat::Text)
+}
@@ -0,0 +1,34 @@
+test!(
+    call_indices_mono_top_5,
+    "call-indices",
+    "./fixtures/mono.wasm",
+    "-n",
+    "5"
+);
+
+test!(
+    call_indices_mono_top_3_json,
+    "call-indices",
+    "./fixtures/mono.wasm",
+    "-n",
+    "3",
+    "-f",
+    "json"
+);
+
+test!(
+    call_indices_mono_top_3_csv,
+    "call-indices",
+    "./fixtures/mono.wasm",
+    "-n",
+    "3",
+    "-f",
+    "csv"
+);
+
+// Every function is called from a one-byte index already.
+test!(
+    call_indices_nothing_to_save,
+    "call-indices",
+    "./fixtures/paths_test.wasm"
+);
@@ -0,0 +1,48 @@
+test!(
+    callsites_mono_memcpy,
+    "callsites",
+    "./fixtures/mono.wasm",
+    "_memcpy",
+    "-n",
+    "5"
+);
+
+test!(
+    callsites_mono_regex,
+    "callsites",
+    "./fixtures/mono.wasm",
+    "--regex",
+    "^_(malloc|free)$",
+    "-n",
+    "3"
+);
+
+test!(
+    callsites_mono_malloc_json,
+    "callsites",
+    "./fixtures/mono.wasm",
+    "_malloc",
+    "-n",
+    "2",
+    "-f",
+    "json"
+);
+
+test!(
+    callsites_mono_malloc_csv,
+    "callsites",
+    "./fixtures/mono.wasm",
+    "_malloc",
+    "-n",
+    "2",
+    "-f",
+    "csv"
+);
+
+// `calledOnce` is only called by `woof`.
+test!(
+    callsites_paths_test,
+    "callsites",
+    "./fixtures/paths_test.wasm",
+    "calledOnce"
+);
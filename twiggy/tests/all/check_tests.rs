@@ -0,0 +1,35 @@
+test!(
+    check_wee_alloc,
+    "check",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc_budget.toml"
+);
+
+test!(
+    check_wee_alloc_csv,
+    "check",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc_budget.toml",
+    "-f",
+    "csv"
+);
+
+test!(
+    check_wee_alloc_baseline_json,
+    "check",
+    "./fixtures/wee_alloc.2.wasm",
+    "./fixtures/wee_alloc_budget.json",
+    "--baseline",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "json"
+);
+
+test_error!(
+    check_wee_alloc_over_budget,
+    "check",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc_budget.toml",
+    "--baseline",
+    "./fixtures/wee_alloc.2.wasm"
+);
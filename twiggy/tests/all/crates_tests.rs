@@ -0,0 +1,39 @@
+test!(crates_wee_alloc, "crates", "./fixtures/wee_alloc.wasm");
+
+test!(
+    crates_monos_retained,
+    "crates",
+    "./fixtures/monos.wasm",
+    "-n",
+    "5",
+    "--sort",
+    "retained"
+);
+
+test!(
+    crates_monos_by_name,
+    "crates",
+    "./fixtures/monos.wasm",
+    "-n",
+    "3",
+    "--sort",
+    "name"
+);
+
+test!(
+    crates_monos_json,
+    "crates",
+    "./fixtures/monos.wasm",
+    "-n",
+    "5",
+    "-f",
+    "json"
+);
+
+test!(
+    crates_wee_alloc_csv,
+    "crates",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "csv"
+);
@@ -0,0 +1,37 @@
+test!(data_strings, "data", "./fixtures/data_strings.wasm");
+
+test!(
+    data_strings_json,
+    "data",
+    "./fixtures/data_strings.wasm",
+    "-f",
+    "json"
+);
+
+test!(
+    data_strings_csv,
+    "data",
+    "./fixtures/data_strings.wasm",
+    "-f",
+    "csv"
+);
+
+// Shorter previews, and shorter runs of text counted as strings.
+test!(
+    data_strings_short,
+    "data",
+    "./fixtures/data_strings.wasm",
+    "-n",
+    "3",
+    "--preview",
+    "8",
+    "--min-len",
+    "1"
+);
+
+// Rust's panic messages and the source files of panic locations.
+test!(data_monos, "data", "./fixtures/monos.wasm", "-n", "5");
+
+// The object file's relocations say which function refers to the string's
+// segment.
+test!(data_linking, "data", "./fixtures/linking.wasm");
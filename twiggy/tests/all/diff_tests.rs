@@ -89,3 +89,204 @@ test!(
     "hello",
     "goodbye"
 );
+
+test!(
+    diff_wee_alloc_keep_disambiguators,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--disambiguators",
+    "keep"
+);
+
+test!(
+    diff_rehashed,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.rehashed.wasm"
+);
+
+test!(
+    diff_rehashed_keep_disambiguators,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.rehashed.wasm",
+    "--disambiguators",
+    "keep"
+);
+
+test!(
+    diff_wee_alloc_fuzzy,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--match",
+    "fuzzy",
+    "--disambiguators",
+    "keep"
+);
+
+test!(
+    diff_wee_alloc_fuzzy_regex,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--match",
+    "fuzzy",
+    "--disambiguators",
+    "keep",
+    "--regex",
+    "alloc_first_fit"
+);
+
+test!(
+    diff_map,
+    "diff",
+    "./fixtures/map_original.wasm",
+    "./fixtures/map_optimized.wasm",
+    "--map",
+    "./fixtures/map_original.wasm"
+);
+
+// `shared` is a function in the old binary and a data segment in the new one.
+test!(
+    diff_moves_untracked,
+    "diff",
+    "./fixtures/moves_old.wasm",
+    "./fixtures/moves_new.wasm"
+);
+
+test!(
+    diff_track_moves,
+    "diff",
+    "./fixtures/moves_old.wasm",
+    "./fixtures/moves_new.wasm",
+    "--track-moves"
+);
+
+test!(
+    diff_track_moves_json,
+    "diff",
+    "./fixtures/moves_old.wasm",
+    "./fixtures/moves_new.wasm",
+    "--track-moves",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_by_crate,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--by-crate"
+);
+
+test!(
+    diff_by_crate_verbose,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--by-crate",
+    "--verbose"
+);
+
+test!(
+    diff_by_crate_json,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--by-crate",
+    "-n",
+    "2",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_by_crate_csv,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--by-crate",
+    "-f",
+    "csv"
+);
+
+test!(
+    diff_wire,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--wire"
+);
+
+test!(
+    diff_wire_json,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--wire",
+    "-n",
+    "3",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_wire_csv,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--wire",
+    "-n",
+    "3",
+    "-f",
+    "csv"
+);
+
+test!(
+    diff_wire_by_crate,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--wire",
+    "--by-crate"
+);
+
+test!(
+    diff_compressed,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--compressed"
+);
+
+test!(
+    diff_compressed_json,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--compressed",
+    "-f",
+    "json"
+);
+
+test!(
+    diff_compressed_csv,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--compressed",
+    "-f",
+    "csv"
+);
+
+test_error!(
+    diff_compressed_by_crate,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "--by-crate",
+    "--compressed"
+);
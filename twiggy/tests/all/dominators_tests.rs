@@ -20,6 +20,16 @@ test!(
     "csv"
 );
 
+test!(
+    dominators_wee_alloc_csv_threads,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "csv",
+    "--threads",
+    "4"
+);
+
 test!(
     dominators_wee_alloc_with_depth_and_row,
     "dominators",
@@ -56,6 +66,46 @@ test!(
     "called.*"
 );
 
+test!(
+    dominators_regex_subtrees,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "--regex",
+    "alloc_with_refill|hello",
+    "-d",
+    "2",
+    "-r",
+    "1"
+);
+
+test!(
+    dominators_regex_subtrees_json,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "--regex",
+    "alloc_with_refill|hello",
+    "-d",
+    "2",
+    "-r",
+    "1",
+    "-f",
+    "json"
+);
+
+test!(
+    dominators_regex_subtrees_csv,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "--regex",
+    "alloc_with_refill|hello",
+    "-d",
+    "2",
+    "-r",
+    "1",
+    "-f",
+    "csv"
+);
+
 test!(
     dominators_regex_any_func,
     "dominators",
@@ -79,3 +129,132 @@ test!(
     "-d",
     "1"
 );
+
+// Dropping the heuristic edges from constant addresses to data leaves that
+// data unreachable from the functions using it.
+test!(
+    dominators_max_edges,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "--max-edges",
+    "33"
+);
+
+// There are 33 edges besides the heuristic ones, so leaving those out is not
+// enough to stay within 20.
+test_error!(
+    dominators_max_edges_exceeded,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "--max-edges",
+    "20"
+);
+
+test!(
+    dominators_function_references,
+    "dominators",
+    "./fixtures/function_references.wasm"
+);
+
+test!(
+    dominators_wee_alloc_folded,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "folded"
+);
+
+test!(
+    dominators_garbage_folded_with_depth,
+    "dominators",
+    "./fixtures/garbage.wasm",
+    "-f",
+    "folded",
+    "-d",
+    "1"
+);
+
+// Frames are separated by semicolons, so the one in `<[u8; 4]>::fill` is
+// written as a comma.
+test!(
+    dominators_garbage_reasons_folded,
+    "dominators",
+    "./fixtures/garbage_reasons.wasm",
+    "-f",
+    "folded"
+);
+
+test!(
+    dominators_wee_alloc_folded_all,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "folded",
+    "-d",
+    "1",
+    "-r",
+    "2",
+    "--all"
+);
+
+test!(
+    dominators_wee_alloc_dot,
+    "dominators",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "dot",
+    "-d",
+    "2",
+    "-r",
+    "6"
+);
+
+test_error!(
+    top_does_not_support_dot_output,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "dot"
+);
+
+test_error!(
+    top_does_not_support_folded_output,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-f",
+    "folded"
+);
+
+// Every byte of the binary is in exactly one folded stack, however the tree is
+// cut short, so that flame graphs of the output are to scale.
+#[test]
+fn folded_stacks_add_up_to_the_whole_binary() {
+    use crate::slurp;
+    use std::fs;
+
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/all/");
+    let mut checked = 0;
+    for entry in fs::read_dir(format!("{}expectations", dir)).unwrap() {
+        let name = entry.unwrap().file_name().into_string().unwrap();
+        let fixture = match name
+            .strip_prefix("dominators_")
+            .and_then(|rest| rest.find("_folded").map(|end| &rest[..end]))
+        {
+            Some(fixture) => fixture,
+            None => continue,
+        };
+
+        let folded =
+            String::from_utf8(slurp(format!("{}expectations/{}", dir, name)).unwrap()).unwrap();
+        let total: u64 = folded
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap().parse::<u64>().unwrap())
+            .sum();
+        let size = fs::metadata(format!("{}fixtures/{}.wasm", dir, fixture))
+            .unwrap()
+            .len();
+        assert_eq!(total, size, "the folded stacks of `{}`", name);
+        checked += 1;
+    }
+    assert!(checked > 0);
+}
@@ -0,0 +1,33 @@
+// `crate_versions.wasm` has two versions of `serde`, told apart by their crate
+// disambiguators, and two of `itoa`, told apart by their semver versions. Its
+// `drop_in_place` instantiations only differ by their legacy hash, so they are
+// not duplicates.
+test!(duplicates, "duplicates", "./fixtures/crate_versions.wasm");
+
+test!(
+    duplicates_truncated,
+    "duplicates",
+    "./fixtures/crate_versions.wasm",
+    "-n",
+    "1",
+    "-m",
+    "1"
+);
+
+test!(
+    duplicates_json,
+    "duplicates",
+    "./fixtures/crate_versions.wasm",
+    "-f",
+    "json"
+);
+
+test!(
+    duplicates_csv,
+    "duplicates",
+    "./fixtures/crate_versions.wasm",
+    "-f",
+    "csv"
+);
+
+test!(duplicates_none, "duplicates", "./fixtures/wee_alloc.wasm");
@@ -56,3 +56,31 @@ test!(
     "3" //"-f",
         //"json"
 );
+
+test!(
+    elf_top_group_by_file,
+    "top",
+    "--group-by",
+    "file",
+    "./fixtures/hello_elf"
+);
+
+test!(
+    elf_top_3_json_source_locations,
+    "top",
+    "-n",
+    "3",
+    "-f",
+    "json",
+    "./fixtures/hello_elf"
+);
+
+// `unsized` has no size in the symbol table, so it extends to `square`.
+test!(elf_top_unsized_symbol, "top", "./fixtures/elf_symbols");
+
+// Without `.symtab`, the exported symbols in `.dynsym` are still items.
+test!(
+    elf_top_dynamic_symbols,
+    "top",
+    "./fixtures/elf_symbols_stripped"
+);
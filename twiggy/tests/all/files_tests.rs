@@ -0,0 +1,64 @@
+test!(
+    files_source_map,
+    "files",
+    "./fixtures/sourcemap.wasm",
+    "--source-map",
+    "./fixtures/sourcemap.wasm.map"
+);
+
+// A directory is searched for the source map that the input's
+// `sourceMappingURL` names.
+test!(
+    files_source_mapping_url_json,
+    "files",
+    "./fixtures/sourcemap.wasm",
+    "--source-map",
+    "./fixtures",
+    "-f",
+    "json"
+);
+
+test!(
+    files_source_map_csv,
+    "files",
+    "./fixtures/sourcemap.wasm",
+    "--source-map",
+    "./fixtures",
+    "-f",
+    "csv"
+);
+
+test!(
+    files_source_map_top_2,
+    "files",
+    "./fixtures/sourcemap.wasm",
+    "--source-map",
+    "./fixtures",
+    "-n",
+    "2"
+);
+
+// Without a source map, no function has a known source file.
+test!(
+    files_without_source_map,
+    "files",
+    "./fixtures/sourcemap.wasm"
+);
+
+test!(
+    files_macho_dsym,
+    "files",
+    "./fixtures/macho_fat",
+    "--arch",
+    "arm64",
+    "--dsym",
+    "./fixtures/macho_fat.dSYM"
+);
+
+test_error!(
+    files_source_map_directory_without_url,
+    "files",
+    "./fixtures/wee_alloc.wasm",
+    "--source-map",
+    "./fixtures"
+);
@@ -0,0 +1,13 @@
+(module
+  (type $void (func))
+  (type $i32 (func (param i32)))
+  ;; The same signature as `$void`, so `call_indirect` treats them the same.
+  (type $void2 (func))
+  (table 3 funcref)
+  (elem (i32.const 0) $a $b $c)
+  (func $main (export "main") (type $void)
+    (call_indirect (type $void2) (i32.const 0)))
+  (func $a (type $void))
+  (func $b (type $i32))
+  (func $c (type $void))
+  (func $unused (type $void)))
@@ -0,0 +1,31 @@
+;; Data segments holding strings, some of them panic and format strings, mixed
+;; with binary data. Each function loads from one of the strings, except for
+;; `checked`, which loads from the binary data between them.
+(module
+  (memory (export "memory") 1)
+
+  (func $greet (export "greet") (result i32)
+    i32.const 1024
+    i32.load8_u)
+  (func $unwrap_failed (export "unwrap_failed") (result i32)
+    i32.const 1040
+    i32.load8_u)
+  (func $panic_location (export "panic_location") (result i32)
+    i32.const 1088
+    i32.load16_u offset=2)
+  (func $checked (export "checked") (result i32)
+    i32.const 1100
+    i32.load)
+  (func $bytes_left (export "bytes_left") (result i32)
+    i32.const 2048
+    i32.load8_u)
+
+  (data (i32.const 1024) "Hello, world!\00\00\00")
+  (data (i32.const 1040)
+    "called `Option::unwrap()` on a `None` value\00\00\00\00\00"
+    "src/lib.rs\01\02"
+    "\01\02\03\04\ff\fe\00\00"
+    "\e2\9c\93 done\n\00"
+    "ab\00\00")
+  (data "passive text")
+  (data (i32.const 2048) "{} bytes left"))
@@ -0,0 +1,30 @@
+//! Rebuild `dwarf.wasm` with:
+//!
+//! ```
+//! rustc +nightly --target wasm32-unknown-unknown -C debuginfo=2 -C opt-level=0 -C codegen-units=1 -C panic=abort --crate-type rlib --crate-name dep dwarf_dep.rs -o libdep.rlib
+//! rustc +nightly --target wasm32-unknown-unknown -C debuginfo=2 -C opt-level=0 -C codegen-units=1 -C panic=abort --crate-type cdylib --crate-name dwarf dwarf.rs --extern dep=libdep.rlib -o dwarf.wasm
+//! ```
+//!
+//! Neither crate links `core`, to keep the debug info down to their own
+//! functions.
+
+#![feature(no_core)]
+#![no_core]
+#![no_std]
+
+extern crate dep;
+
+#[no_mangle]
+pub extern "C" fn add(a: i32, b: i32) -> i32 {
+    dep::first(a, b)
+}
+
+#[no_mangle]
+pub extern "C" fn sub(a: i32, b: i32) -> i32 {
+    helper(a, b)
+}
+
+#[inline(never)]
+fn helper(a: i32, b: i32) -> i32 {
+    dep::second(a, b)
+}
@@ -0,0 +1,27 @@
+//! The dependency of `dwarf.rs`, where the commands to rebuild `dwarf.wasm`
+//! are.
+
+#![feature(no_core, lang_items)]
+#![allow(internal_features)]
+#![no_core]
+#![no_std]
+
+#[lang = "pointee_sized"]
+pub trait PointeeSized {}
+#[lang = "meta_sized"]
+pub trait MetaSized: PointeeSized {}
+#[lang = "sized"]
+pub trait Sized: MetaSized {}
+#[lang = "copy"]
+pub trait Copy {}
+impl Copy for i32 {}
+
+#[inline(never)]
+pub fn first(a: i32, _b: i32) -> i32 {
+    a
+}
+
+#[inline(never)]
+pub fn second(_a: i32, b: i32) -> i32 {
+    b
+}
@@ -0,0 +1,27 @@
+/*
+ * Rebuild `elf_symbols` and `elf_symbols_stripped` with:
+ *
+ *     gcc -O1 -fPIC -shared -nostdlib -fno-asynchronous-unwind-tables -Wl,--build-id=none elf_symbols.c -o elf_symbols
+ *     strip --strip-all -o elf_symbols_stripped elf_symbols
+ *
+ * The stripped library only has its `.dynsym` left.
+ */
+
+int counter = 1;
+
+int square(int x) {
+    return x * x;
+}
+
+int increment(void) {
+    return counter++;
+}
+
+/* Hand-written assembly without a `.size`, so its symbol has a size of 0. */
+__asm__(
+    ".text\n"
+    ".globl unsized\n"
+    ".type unsized, @function\n"
+    "unsized:\n"
+    "    movl $42, %eax\n"
+    "    ret\n");
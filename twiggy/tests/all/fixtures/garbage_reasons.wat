@@ -0,0 +1,39 @@
+(module
+    ;; -------------------------------------------------------------------------
+    ;; This is a WebAssembly text file that can be compiled in a wasm module to
+    ;; test why `twiggy garbage` says each item is garbage, and how
+    ;; `twiggy dominators -f folded` writes names with semicolons in them.
+    ;; -------------------------------------------------------------------------
+    ;; NOTE: The test cases expect that this module is compiled with debug
+    ;; names written to the binary file, which affects the size percentages.
+    ;; Compile this file using the following command:
+    ;;
+    ;; wat2wasm --debug-names garbage_reasons.wat -o garbage_reasons.wasm
+    ;; -------------------------------------------------------------------------
+
+    ;; The only export, which calls `used` and `fill`.
+    (func $main (export "main")
+        call $used
+        call $fill)
+
+    (func $used)
+
+    ;; Folded stacks are separated by semicolons, so this name's is escaped.
+    (func $fill (@name "<[u8; 4]>::fill"))
+
+    ;; Nothing calls `dead`, which is all that calls `dead_child`.
+    (func $dead
+        call $dead_child)
+
+    (func $dead_child)
+
+    ;; Garbage that only garbage calls: `cycle_a` and `cycle_b` call each
+    ;; other, and nothing else calls either.
+    (func $cycle_a
+        call $cycle_b)
+
+    (func $cycle_b
+        call $cycle_a)
+
+    (func $lonely)
+)
@@ -0,0 +1,41 @@
+; Rebuild `linking.wasm`, an object file with a `linking` section and
+; `reloc.CODE` and `reloc.DATA` sections but no name section, with:
+;
+;     llc -mtriple=wasm32-unknown-unknown -filetype=obj linking.ll -o linking.wasm
+;
+; `dispatch` calls through the function pointers in `handlers`, which only the
+; relocations of the data section tie to `on_even` and `on_odd`.
+
+target datalayout = "e-m:e-p:32:32-i64:64-n32:64-S128"
+target triple = "wasm32-unknown-unknown"
+
+@greeting = internal constant [14 x i8] c"Hello, world!\00", align 1
+@counter = internal global i32 0, align 4
+@handlers = internal constant [2 x void ()*] [void ()* @on_even, void ()* @on_odd], align 4
+
+declare void @log(i8*)
+
+define internal void @on_even() {
+  call void @log(i8* getelementptr ([14 x i8], [14 x i8]* @greeting, i32 0, i32 0))
+  ret void
+}
+
+define internal void @on_odd() {
+  %n = load i32, i32* @counter, align 4
+  %m = add i32 %n, 1
+  store i32 %m, i32* @counter, align 4
+  ret void
+}
+
+define internal void @dispatch(i32 %i) {
+  %slot = getelementptr [2 x void ()*], [2 x void ()*]* @handlers, i32 0, i32 %i
+  %f = load void ()*, void ()** %slot, align 4
+  call void %f()
+  ret void
+}
+
+define void @run(i32 %i) {
+  %parity = and i32 %i, 1
+  call void @dispatch(i32 %parity)
+  ret void
+}
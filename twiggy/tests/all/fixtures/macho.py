@@ -0,0 +1,328 @@
+#!/usr/bin/env python3
+"""
+Regenerate `macho_fat` and `macho_fat.dSYM` with:
+
+    python3 macho.py
+
+Linking Mach-O takes Apple's toolchain, so rather than build them from source,
+this script writes them out directly. `macho_fat` is a universal binary with
+an `x86_64` and an `arm64` executable, laid out like a C program built with
+`-g` from two source files: `macho.c`, with the external functions `main` and
+`add`, and `util.c`, with the static functions `helper` and `unlisted`. The
+local symbol for `unlisted` has been stripped, as some stripping tools would
+leave it. Function bodies are a prologue, `nop`s and an epilogue.
+`macho_fat.dSYM` holds the DWARF for both architectures, as `dsymutil` would
+write it: a unit per source file, with the address range of each function.
+"""
+
+import os
+import struct
+
+TEXT_ADDRESS = 0x100000000
+TEXT_OFFSET = 0x200
+ALIGN = 12
+
+
+def pad(data, align):
+    return data + b"\0" * (-len(data) % align)
+
+
+ARCHES = [
+    {
+        "name": "x86_64",
+        "cputype": 0x01000007,
+        "cpusubtype": 3,
+        "uuid": bytes(range(0x10, 0x20)),
+        "prologue": b"\x55\x48\x89\xe5",
+        "nop": b"\x90",
+        "epilogue": b"\x5d\xc3",
+        "align": 16,
+        # Function name, unit, size and whether the symbol is external, or
+        # `None` if it was stripped.
+        "functions": [
+            ("main", "macho.c", 22, True),
+            ("add", "macho.c", 9, True),
+            ("helper", "util.c", 13, False),
+            ("unlisted", "util.c", 18, None),
+        ],
+    },
+    {
+        "name": "arm64",
+        "cputype": 0x0100000C,
+        "cpusubtype": 0,
+        "uuid": bytes(range(0x20, 0x30)),
+        "prologue": b"\xfd\x7b\xbf\xa9",
+        "nop": b"\x1f\x20\x03\xd5",
+        "epilogue": b"\xfd\x7b\xc1\xa8\xc0\x03\x5f\xd6",
+        "align": 4,
+        "functions": [
+            ("main", "macho.c", 28, True),
+            ("add", "macho.c", 16, True),
+            ("helper", "util.c", 24, False),
+            ("unlisted", "util.c", 32, None),
+        ],
+    },
+]
+
+CSTRING = b"%d\n\0"
+
+
+def layout(arch):
+    """The code of `__text`, and the address of each function in it."""
+    text = b""
+    addresses = []
+    for _, _, size, _ in arch["functions"]:
+        text = pad(text, arch["align"])
+        addresses.append(TEXT_ADDRESS + TEXT_OFFSET + len(text))
+        nops = size - len(arch["prologue"]) - len(arch["epilogue"])
+        text += arch["prologue"] + arch["nop"] * (nops // len(arch["nop"])) + arch["epilogue"]
+    return text, addresses
+
+
+def header(arch, filetype, commands):
+    return struct.pack(
+        "<IIIIIIII",
+        0xFEEDFACF,
+        arch["cputype"],
+        arch["cpusubtype"],
+        filetype,
+        len(commands),
+        sum(len(command) for command in commands),
+        0,
+        0,
+    )
+
+
+def name16(name):
+    return name.encode().ljust(16, b"\0")
+
+
+def segment(name, address, vmsize, offset, filesize, sections):
+    command = struct.pack(
+        "<II16sQQQQIIII",
+        0x19,
+        72 + 80 * len(sections),
+        name16(name),
+        address,
+        vmsize,
+        offset,
+        filesize,
+        5,
+        5,
+        len(sections),
+        0,
+    )
+    for sectname, address, size, offset, flags in sections:
+        command += struct.pack(
+            "<16s16sQQIIIIIIII",
+            name16(sectname),
+            name16(name),
+            address,
+            size,
+            offset,
+            0,
+            0,
+            0,
+            flags,
+            0,
+            0,
+            0,
+        )
+    return command
+
+
+def uuid(arch):
+    return struct.pack("<II16s", 0x1B, 24, arch["uuid"])
+
+
+def executable(arch):
+    text, addresses = layout(arch)
+    cstring_offset = TEXT_OFFSET + len(pad(text, 16))
+    linkedit_offset = cstring_offset + len(CSTRING)
+
+    strtab = b" \0"
+    symbols = []
+    # Local symbols come first, then the external ones.
+    for external in (False, True):
+        for (name, _, _, is_external), address in zip(arch["functions"], addresses):
+            if is_external is external:
+                symbols.append((len(strtab), 0x0F if external else 0x0E, address))
+                strtab += b"_" + name.encode() + b"\0"
+    symtab = b"".join(struct.pack("<IBBHQ", strx, ty, 1, 0, value) for strx, ty, value in symbols)
+    strtab = pad(strtab, 8)
+    linkedit = symtab + strtab
+
+    commands = [
+        segment(
+            "__TEXT",
+            TEXT_ADDRESS,
+            linkedit_offset,
+            0,
+            linkedit_offset,
+            [
+                ("__text", TEXT_ADDRESS + TEXT_OFFSET, len(text), TEXT_OFFSET, 0x80000400),
+                ("__cstring", TEXT_ADDRESS + cstring_offset, len(CSTRING), cstring_offset, 2),
+            ],
+        ),
+        segment(
+            "__LINKEDIT",
+            TEXT_ADDRESS + linkedit_offset,
+            len(linkedit),
+            linkedit_offset,
+            len(linkedit),
+            [],
+        ),
+        struct.pack(
+            "<IIIIII",
+            0x2,
+            24,
+            linkedit_offset,
+            len(symbols),
+            linkedit_offset + len(symtab),
+            len(strtab),
+        ),
+        uuid(arch),
+        struct.pack("<IIQQ", 0x80000028, 24, TEXT_OFFSET, 0),
+    ]
+    data = header(arch, 2, commands) + b"".join(commands)
+    assert len(data) <= TEXT_OFFSET
+    data = data.ljust(TEXT_OFFSET, b"\0") + pad(text, 16) + CSTRING + linkedit
+    assert len(data) == linkedit_offset + len(linkedit)
+    return data
+
+
+def dwarf(arch):
+    """The `__debug_abbrev`, `__debug_info` and `__debug_str` of the dSYM."""
+    _, addresses = layout(arch)
+
+    strings = b""
+
+    def strp(s):
+        nonlocal strings
+        offset = len(strings)
+        strings += s.encode() + b"\0"
+        return struct.pack("<I", offset)
+
+    def uleb(n):
+        return bytes([n])
+
+    DW_FORM_ADDR, DW_FORM_DATA1, DW_FORM_DATA2, DW_FORM_DATA4 = 0x01, 0x0B, 0x05, 0x06
+    DW_FORM_STRP, DW_FORM_FLAG_PRESENT = 0x0E, 0x19
+    abbrev = b""
+    for code, tag, children, attrs in [
+        # DW_TAG_compile_unit: producer, language, name, comp_dir, low_pc and
+        # high_pc.
+        (
+            1,
+            0x11,
+            1,
+            [
+                (0x25, DW_FORM_STRP),
+                (0x13, DW_FORM_DATA2),
+                (0x03, DW_FORM_STRP),
+                (0x1B, DW_FORM_STRP),
+                (0x11, DW_FORM_ADDR),
+                (0x12, DW_FORM_DATA4),
+            ],
+        ),
+        # DW_TAG_subprogram: low_pc, high_pc, name, decl_file, decl_line and,
+        # for external functions, external.
+        (
+            2,
+            0x2E,
+            0,
+            [
+                (0x11, DW_FORM_ADDR),
+                (0x12, DW_FORM_DATA4),
+                (0x03, DW_FORM_STRP),
+                (0x3A, DW_FORM_DATA1),
+                (0x3B, DW_FORM_DATA1),
+                (0x3F, DW_FORM_FLAG_PRESENT),
+            ],
+        ),
+        (
+            3,
+            0x2E,
+            0,
+            [
+                (0x11, DW_FORM_ADDR),
+                (0x12, DW_FORM_DATA4),
+                (0x03, DW_FORM_STRP),
+                (0x3A, DW_FORM_DATA1),
+                (0x3B, DW_FORM_DATA1),
+            ],
+        ),
+    ]:
+        abbrev += uleb(code) + uleb(tag) + bytes([children])
+        for attr, form in attrs:
+            abbrev += uleb(attr) + uleb(form)
+        abbrev += b"\0\0"
+    abbrev += b"\0"
+
+    producer = strp("clang")
+    comp_dir = strp("/tmp")
+    info = b""
+    for unit in ("macho.c", "util.c"):
+        functions = [
+            (name, size, external, address)
+            for (name, file, size, external), address in zip(arch["functions"], addresses)
+            if file == unit
+        ]
+        low_pc = functions[0][3]
+        high_pc = functions[-1][3] + functions[-1][1]
+        dies = uleb(1) + producer + struct.pack("<H", 0x0C) + strp(unit) + comp_dir
+        dies += struct.pack("<QI", low_pc, high_pc - low_pc)
+        for line, (name, size, external, address) in enumerate(functions, 1):
+            dies += uleb(2 if external else 3) + struct.pack("<QI", address, size)
+            dies += strp(name) + bytes([1, line])
+        dies += b"\0"
+        body = struct.pack("<HIB", 4, 0, 8) + dies
+        info += struct.pack("<I", len(body)) + body
+    return abbrev, info, strings
+
+
+def dsym(arch):
+    abbrev, info, strings = dwarf(arch)
+    sections = [("__debug_abbrev", abbrev), ("__debug_info", info), ("__debug_str", strings)]
+    commands_size = 32 + 24 + 24 + 72 + 80 * len(sections)
+    offset = (commands_size + 0xF) & ~0xF
+    contents = b""
+    table = []
+    for name, data in sections:
+        table.append((name, 0x200000000 + len(contents), len(data), offset + len(contents), 0))
+        contents += data
+    commands = [
+        uuid(arch),
+        struct.pack("<IIIIII", 0x2, 24, 0, 0, 0, 0),
+        segment("__DWARF", 0x200000000, len(contents), offset, len(contents), table),
+    ]
+    data = header(arch, 0xA, commands) + b"".join(commands)
+    assert len(data) == commands_size
+    return data.ljust(offset, b"\0") + contents
+
+
+def fat(slices):
+    data = struct.pack(">II", 0xCAFEBABE, len(slices))
+    offset = 1 << ALIGN
+    contents = b""
+    for arch, slice in slices:
+        contents = pad(contents, 1 << ALIGN)
+        data += struct.pack(
+            ">IIIII", arch["cputype"], arch["cpusubtype"], offset + len(contents), len(slice), ALIGN
+        )
+        contents += slice
+    return data.ljust(offset, b"\0") + contents
+
+
+def write(path, data):
+    os.makedirs(os.path.dirname(path) or ".", exist_ok=True)
+    with open(path, "wb") as f:
+        f.write(data)
+
+
+here = os.path.dirname(os.path.abspath(__file__))
+write(os.path.join(here, "macho_fat"), fat([(arch, executable(arch)) for arch in ARCHES]))
+write(
+    os.path.join(here, "macho_fat.dSYM", "Contents", "Resources", "DWARF", "macho_fat"),
+    fat([(arch, dsym(arch)) for arch in ARCHES]),
+)
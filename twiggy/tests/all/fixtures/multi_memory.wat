@@ -0,0 +1,14 @@
+;; Two memories, each with a data segment at address 0. `read_first` and
+;; `read_second` load from the same address, but of different memories, so
+;; only the memory index tells which segment each of them reads.
+(module
+  (memory $first 1)
+  (memory $second 1)
+  (data (memory $first) (i32.const 0) "the first memory")
+  (data (memory $second) (i32.const 0) "the second memory")
+  (func $read_first (export "read_first") (result i32)
+    (i32.load $first (i32.const 4)))
+  (func $read_second (export "read_second") (result i32)
+    (i32.load $second (i32.const 4)))
+  (func $grow_second (export "grow_second") (result i32)
+    (memory.grow $second (i32.const 1))))
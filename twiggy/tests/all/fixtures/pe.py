@@ -0,0 +1,317 @@
+#!/usr/bin/env python3
+"""
+Regenerate `pe_msvc.exe` and `pe_mingw.exe` with:
+
+    python3 pe.py
+
+Linking PE takes a Windows toolchain, so rather than build them from source,
+this script writes them out directly. Both are x86-64 executables with a COFF
+symbol table, and no PDB.
+
+`pe_msvc.exe` is laid out like a C++ program built with MSVC: its symbols
+have MSVC-mangled names, `helper` is a static function, and nothing in
+`.rdata` has a symbol. `pe_mingw.exe` is laid out like a C++ program built
+with MinGW from `main.cpp` and `util.cpp`: its symbols have Itanium-mangled
+names, each source file's symbols follow a `.file` symbol for it, and it
+embeds the DWARF of both units, with the address range of each function. The symbol for the static function `unlisted` has been
+stripped. Function bodies are a prologue, `nop`s and an epilogue.
+"""
+
+import os
+import struct
+
+IMAGE_BASE = 0x140000000
+SECTION_ALIGNMENT = 0x1000
+FILE_ALIGNMENT = 0x200
+
+PROLOGUE = b"\x55\x48\x89\xe5"
+NOP = b"\x90"
+EPILOGUE = b"\x5d\xc3"
+
+SCN_CODE = 0x00000020 | 0x20000000 | 0x40000000
+SCN_RDATA = 0x00000040 | 0x40000000
+SCN_DATA = 0x00000040 | 0x40000000 | 0x80000000
+SCN_DEBUG = 0x00000040 | 0x02000000 | 0x40000000
+
+SYM_CLASS_EXTERNAL = 2
+SYM_CLASS_STATIC = 3
+SYM_CLASS_FILE = 103
+SYM_DTYPE_FUNCTION = 0x20
+
+
+def pad(data, align, fill=b"\0"):
+    return data + fill * (-len(data) % align)
+
+
+def function(size):
+    nops = size - len(PROLOGUE) - len(EPILOGUE)
+    return PROLOGUE + NOP * nops + EPILOGUE
+
+
+def text(functions):
+    """The code of `.text`, and the offset of each function in it."""
+    code = b""
+    offsets = []
+    for size in functions:
+        code = pad(code, 16, b"\xcc")
+        offsets.append(len(code))
+        code += function(size)
+    return code, offsets
+
+
+class Strings:
+    """The COFF string table, for names longer than eight bytes."""
+
+    def __init__(self):
+        self.data = b""
+
+    def offset(self, name):
+        offset = 4 + len(self.data)
+        self.data += name.encode() + b"\0"
+        return offset
+
+    def table(self):
+        return struct.pack("<I", 4 + len(self.data)) + self.data
+
+
+def symbol(strings, name, value, section, type, storage_class, aux=()):
+    if len(name) <= 8:
+        raw_name = name.encode().ljust(8, b"\0")
+    else:
+        raw_name = struct.pack("<II", 0, strings.offset(name))
+    record = raw_name + struct.pack("<IhHBB", value, section, type, storage_class, len(aux))
+    return record + b"".join(aux)
+
+
+def section_symbol(strings, name, number, length):
+    aux = struct.pack("<IHHIHB3x", length, 0, 0, 0, number, 0)
+    return symbol(strings, name, 0, number, 0, SYM_CLASS_STATIC, [aux])
+
+
+def file_symbol(strings, name):
+    aux = name.encode().ljust(18, b"\0")
+    return symbol(strings, ".file", 0, -2, 0, SYM_CLASS_FILE, [aux])
+
+
+def executable(sections, symbols, strings, entry):
+    """A PE32+ executable with the given sections, as `(name, characteristics,
+    data)`, and COFF symbols, entered at the given address."""
+    headers_size = 0x40 + 4 + 20 + 240 + 40 * len(sections)
+    offset = headers_size + -headers_size % FILE_ALIGNMENT
+    rva = SECTION_ALIGNMENT
+
+    section_table = b""
+    contents = b""
+    for name, characteristics, data in sections:
+        if len(name) > 8:
+            raw_name = "/{}".format(strings.offset(name)).encode().ljust(8, b"\0")
+        else:
+            raw_name = name.encode().ljust(8, b"\0")
+        raw = pad(data, FILE_ALIGNMENT)
+        section_table += raw_name + struct.pack(
+            "<IIIIIIHHI",
+            len(data),
+            rva,
+            len(raw),
+            offset + len(contents),
+            0,
+            0,
+            0,
+            0,
+            characteristics,
+        )
+        contents += raw
+        rva += len(pad(data, SECTION_ALIGNMENT))
+    size_of_image = rva
+    symbol_table = b"".join(symbols)
+    number_of_symbols = len(symbol_table) // 18
+
+    dos = b"MZ".ljust(0x3C, b"\0") + struct.pack("<I", 0x40)
+    file_header = struct.pack(
+        "<HHIIIHH",
+        0x8664,
+        len(sections),
+        0,
+        offset + len(contents),
+        number_of_symbols,
+        240,
+        0x0022,
+    )
+    optional = struct.pack(
+        "<HBBIIIIIQIIHHHHHHIIIIHHQQQQII",
+        0x20B,
+        14,
+        0,
+        len(sections[0][2]),
+        0,
+        0,
+        entry - IMAGE_BASE,
+        SECTION_ALIGNMENT,
+        IMAGE_BASE,
+        SECTION_ALIGNMENT,
+        FILE_ALIGNMENT,
+        6,
+        0,
+        0,
+        0,
+        6,
+        0,
+        0,
+        size_of_image,
+        offset,
+        0,
+        3,
+        0x8160,
+        0x100000,
+        0x1000,
+        0x100000,
+        0x1000,
+        0,
+        16,
+    ) + b"\0" * 16 * 8
+    assert len(optional) == 240
+    headers = dos + b"PE\0\0" + file_header + optional + section_table
+    return headers.ljust(offset, b"\0") + contents + symbol_table + strings.table()
+
+
+def msvc():
+    functions = [
+        ("mainCRTStartup", 24, True),
+        ("?add@@YAHHH@Z", 10, True),
+        ("?scale@math@@YANNN@Z", 14, True),
+        ("??0Widget@@QEAA@XZ", 12, True),
+        ("helper", 9, False),
+    ]
+    code, offsets = text([size for _, size, _ in functions])
+
+    rdata = b"hello\0"
+    data = struct.pack("<I", 42)
+
+    strings = Strings()
+    symbols = [
+        section_symbol(strings, ".text", 1, len(code)),
+        section_symbol(strings, ".rdata", 2, len(rdata)),
+        section_symbol(strings, ".data", 3, len(data)),
+    ]
+    for (name, _, external), offset in zip(functions, offsets):
+        storage_class = SYM_CLASS_EXTERNAL if external else SYM_CLASS_STATIC
+        symbols.append(symbol(strings, name, offset, 1, SYM_DTYPE_FUNCTION, storage_class))
+    symbols.append(symbol(strings, "?counter@@3HA", 0, 3, 0, SYM_CLASS_EXTERNAL))
+
+    sections = [(".text", SCN_CODE, code), (".rdata", SCN_RDATA, rdata), (".data", SCN_DATA, data)]
+    return executable(sections, symbols, strings, IMAGE_BASE + SECTION_ALIGNMENT)
+
+
+# Symbol name, name in the debug info, unit, size and whether the symbol is
+# external, or `None` if it was stripped.
+MINGW_FUNCTIONS = [
+    ("main", "main", "main.cpp", 22, True),
+    ("_Z3addii", "add", "main.cpp", 9, True),
+    ("_ZL6helperv", "helper", "util.cpp", 13, False),
+    ("_ZL8unlistedv", "unlisted", "util.cpp", 18, None),
+]
+
+
+def dwarf(addresses):
+    """The `.debug_abbrev`, `.debug_info` and `.debug_str` of `pe_mingw.exe`."""
+    strings = b""
+
+    def strp(s):
+        nonlocal strings
+        offset = len(strings)
+        strings += s.encode() + b"\0"
+        return struct.pack("<I", offset)
+
+    DW_FORM_ADDR, DW_FORM_DATA2, DW_FORM_DATA4, DW_FORM_STRP = 0x01, 0x05, 0x06, 0x0E
+    abbrev = b""
+    for code, tag, children, attrs in [
+        # DW_TAG_compile_unit: producer, language, name, low_pc and high_pc.
+        (
+            1,
+            0x11,
+            1,
+            [
+                (0x25, DW_FORM_STRP),
+                (0x13, DW_FORM_DATA2),
+                (0x03, DW_FORM_STRP),
+                (0x11, DW_FORM_ADDR),
+                (0x12, DW_FORM_DATA4),
+            ],
+        ),
+        # DW_TAG_subprogram: low_pc, high_pc, linkage_name and name.
+        (
+            2,
+            0x2E,
+            0,
+            [
+                (0x11, DW_FORM_ADDR),
+                (0x12, DW_FORM_DATA4),
+                (0x6E, DW_FORM_STRP),
+                (0x03, DW_FORM_STRP),
+            ],
+        ),
+    ]:
+        abbrev += bytes([code, tag, children])
+        for attr, form in attrs:
+            abbrev += bytes([attr, form])
+        abbrev += b"\0\0"
+    abbrev += b"\0"
+
+    producer = strp("GNU C++17 13.2.0")
+    info = b""
+    for unit in ("main.cpp", "util.cpp"):
+        functions = [
+            (linkage_name, name, size, address)
+            for (linkage_name, name, file, size, _), address in zip(MINGW_FUNCTIONS, addresses)
+            if file == unit
+        ]
+        low_pc = functions[0][3]
+        high_pc = functions[-1][3] + functions[-1][2]
+        dies = bytes([1]) + producer + struct.pack("<H", 0x21) + strp(unit)
+        dies += struct.pack("<QI", low_pc, high_pc - low_pc)
+        for linkage_name, name, size, address in functions:
+            dies += bytes([2]) + struct.pack("<QI", address, size)
+            dies += strp(linkage_name) + strp(name)
+        dies += b"\0"
+        body = struct.pack("<HIB", 4, 0, 8) + dies
+        info += struct.pack("<I", len(body)) + body
+    return abbrev, info, strings
+
+
+def mingw():
+    code, offsets = text([size for _, _, _, size, _ in MINGW_FUNCTIONS])
+    addresses = [IMAGE_BASE + SECTION_ALIGNMENT + offset for offset in offsets]
+    data = struct.pack("<I", 7)
+    abbrev, info, debug_strings = dwarf(addresses)
+
+    strings = Strings()
+    symbols = []
+    current_file = None
+    for (name, _, unit, _, external), offset in zip(MINGW_FUNCTIONS, offsets):
+        if unit != current_file:
+            symbols.append(file_symbol(strings, unit))
+            current_file = unit
+        if external is None:
+            continue
+        storage_class = SYM_CLASS_EXTERNAL if external else SYM_CLASS_STATIC
+        symbols.append(symbol(strings, name, offset, 1, SYM_DTYPE_FUNCTION, storage_class))
+    symbols.append(symbol(strings, "counter", 0, 2, 0, SYM_CLASS_EXTERNAL))
+
+    sections = [
+        (".text", SCN_CODE, code),
+        (".data", SCN_DATA, data),
+        (".debug_abbrev", SCN_DEBUG, abbrev),
+        (".debug_info", SCN_DEBUG, info),
+        (".debug_str", SCN_DEBUG, debug_strings),
+    ]
+    return executable(sections, symbols, strings, addresses[0])
+
+
+def write(path, data):
+    with open(path, "wb") as f:
+        f.write(data)
+
+
+here = os.path.dirname(os.path.abspath(__file__))
+write(os.path.join(here, "pe_msvc.exe"), msvc())
+write(os.path.join(here, "pe_mingw.exe"), mingw())
@@ -0,0 +1,26 @@
+#!/usr/bin/env python3
+"""
+Regenerate `wee_alloc.rehashed.wasm` with:
+
+    python3 rehash.py
+
+`wee_alloc.rehashed.wasm` is `wee_alloc.wasm` as if it were rebuilt from the
+same code by another compiler: the hash at the end of each mangled Rust symbol
+name is different, and nothing else is. Each hash is reversed, so that the
+names keep their length and the binary its layout.
+"""
+
+import os
+import re
+
+here = os.path.dirname(os.path.abspath(__file__))
+with open(os.path.join(here, "wee_alloc.wasm"), "rb") as f:
+    data = f.read()
+
+rehashed, count = re.subn(
+    rb"17h([0-9a-f]{16})E", lambda m: b"17h" + m.group(1)[::-1] + b"E", data
+)
+assert count > 0 and len(rehashed) == len(data)
+
+with open(os.path.join(here, "wee_alloc.rehashed.wasm"), "wb") as f:
+    f.write(rehashed)
@@ -0,0 +1,139 @@
+#!/usr/bin/env python3
+"""
+Regenerate `sourcemap.wasm` and `sourcemap.wasm.map` with:
+
+    python3 sourcemap.py
+
+`sourcemap.wasm` is `wee_alloc.wasm` with a `sourceMappingURL` custom section
+naming `sourcemap.wasm.map`, a source map like the ones that Emscripten writes:
+a mapping at every fourth byte of each function body, past its size and
+locals. Bodies are attributed to made up source files by their index in the
+code section. One body is left unmapped, and one is split between two files.
+"""
+
+import json
+import os
+
+# The source files of each function body, by index. `None` leaves the body
+# unmapped, and a pair splits it, a third to the first file and the rest to
+# the second.
+BODIES = [
+    "src/lib.rs",
+    "src/imp_static_array.rs",
+    None,
+    ("src/lib.rs", "src/size_classes.rs"),
+    "src/size_classes.rs",
+    "src/lib.rs",
+    "src/lib.rs",
+    "src/imp_static_array.rs",
+    "src/size_classes.rs",
+    "src/lib.rs",
+    "src/lib.rs",
+]
+
+BASE64 = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+
+
+def leb(data, at):
+    """The unsigned LEB128 at `at`, and the offset after it."""
+    value = shift = 0
+    while True:
+        byte = data[at]
+        at += 1
+        value |= (byte & 0x7F) << shift
+        shift += 7
+        if byte & 0x80 == 0:
+            return value, at
+
+
+def encode_leb(value):
+    out = b""
+    while True:
+        byte = value & 0x7F
+        value >>= 7
+        if value:
+            out += bytes([byte | 0x80])
+        else:
+            return out + bytes([byte])
+
+
+def vlq(value):
+    value = (-value << 1) | 1 if value < 0 else value << 1
+    out = ""
+    while True:
+        digit = value & 0x1F
+        value >>= 5
+        if value:
+            out += BASE64[digit | 0x20]
+        else:
+            return out + BASE64[digit]
+
+
+def code_bodies(data):
+    """The offset of the instructions of each function body, past its size
+    and locals, and the offset of its end."""
+    at = 8
+    while at < len(data):
+        section = data[at]
+        size, at = leb(data, at + 1)
+        if section != 10:
+            at += size
+            continue
+        count, at = leb(data, at)
+        bodies = []
+        for _ in range(count):
+            body_size, start = leb(data, at)
+            end = start + body_size
+            locals_count, at = leb(data, start)
+            for _ in range(locals_count):
+                _, at = leb(data, at)
+                at += 1
+            bodies.append((at, end))
+            at = end
+        return bodies
+    raise ValueError("no code section")
+
+
+here = os.path.dirname(os.path.abspath(__file__))
+with open(os.path.join(here, "wee_alloc.wasm"), "rb") as f:
+    data = f.read()
+
+sources = sorted(
+    {file for files in BODIES if files is not None for file in ([files] if isinstance(files, str) else files)}
+)
+bodies = code_bodies(data)
+assert len(bodies) == len(BODIES)
+
+# Every field of a mapping is relative to the same field of the previous one.
+# Each mapped instruction is on a line of its own, counting from zero.
+segments = []
+last = {"offset": 0, "source": 0, "line": 0}
+line = 0
+for (start, end), files in zip(bodies, BODIES):
+    if isinstance(files, str):
+        files = (files, files)
+    for i, offset in enumerate(range(start, end, 4)):
+        fields = [offset - last["offset"]]
+        last["offset"] = offset
+        if files is not None:
+            source = sources.index(files[0] if i < (end - start) // 12 else files[1])
+            fields += [source - last["source"], line - last["line"], 0]
+            last["source"], last["line"] = source, line
+            line += 1
+        segments.append("".join(vlq(field) for field in fields))
+
+source_map = {
+    "version": 3,
+    "sources": sources,
+    "names": [],
+    "mappings": ",".join(segments),
+}
+with open(os.path.join(here, "sourcemap.wasm.map"), "w") as f:
+    json.dump(source_map, f)
+    f.write("\n")
+
+url = b"sourcemap.wasm.map"
+name = b"sourceMappingURL"
+payload = encode_leb(len(name)) + name + encode_leb(len(url)) + url
+with open(os.path.join(here, "sourcemap.wasm"), "wb") as f:
+    f.write(data + b"\x00" + encode_leb(len(payload)) + payload)
@@ -0,0 +1,11 @@
+;; A module with a start section, and a data count section for `memory.init`
+;; and `data.drop` to refer to its passive data segment by.
+(module
+  (import "env" "log" (func $log (param i32)))
+  (memory (export "memory") 1)
+  (data $greeting "hello")
+  (func $init
+    (memory.init $greeting (i32.const 0) (i32.const 0) (i32.const 5))
+    (data.drop $greeting)
+    (call $log (i32.const 0)))
+  (start $init))
@@ -91,3 +91,96 @@ test!(
     "-n",
     "2"
 );
+
+test!(
+    garbage_wee_alloc_all_threads,
+    "garbage",
+    "./fixtures/wee_alloc.wasm",
+    "-a",
+    "--threads",
+    "4"
+);
+
+test!(
+    garbage_wee_alloc_exclude_heuristic_edges,
+    "garbage",
+    "./fixtures/wee_alloc.wasm",
+    "--exclude-edges",
+    "heuristic"
+);
+
+// `dead` is referenced by nothing and retains `dead_child`, while `cycle_a`
+// and `cycle_b` are only referenced by each other.
+test!(
+    garbage_reasons,
+    "garbage",
+    "./fixtures/garbage_reasons.wasm"
+);
+
+// Without call edges, what `main` calls is garbage that `main` would retain.
+test!(
+    garbage_reasons_exclude_calls,
+    "garbage",
+    "./fixtures/garbage_reasons.wasm",
+    "--exclude-edges",
+    "call"
+);
+
+test!(
+    garbage_extra_root,
+    "garbage",
+    "./fixtures/garbage.wasm",
+    "--extra-root",
+    "unusedAddOne"
+);
+
+test!(
+    garbage_extra_root_regex,
+    "garbage",
+    "./fixtures/garbage.wasm",
+    "--extra-root",
+    "^unusedAdd"
+);
+
+test!(
+    garbage_top_2_csv,
+    "garbage",
+    "./fixtures/garbage.wasm",
+    "-f",
+    "csv",
+    "-n",
+    "2"
+);
+
+// The type of `main`'s indirect call is only used by that call.
+test!(
+    garbage_call_indirect,
+    "garbage",
+    "./fixtures/call_indirect.wasm"
+);
+
+// `callback` is only referenced by a `ref.func` in `get_callback`, and
+// `from_global` by a `ref.func` initializing an exported global. Only
+// `unused` and the segment declaring the referenced functions are garbage.
+test!(
+    garbage_function_references,
+    "garbage",
+    "./fixtures/function_references.wasm"
+);
+
+// `data_addresses.wasm` loads from every data segment but `data[2]`, which it
+// only reaches through a mutable global.
+test!(
+    garbage_data_addresses,
+    "garbage",
+    "./fixtures/data_addresses.wasm",
+    "--show-data-segments"
+);
+
+test!(
+    garbage_kind_code,
+    "garbage",
+    "--kind",
+    "code",
+    "./fixtures/garbage.wasm"
+);
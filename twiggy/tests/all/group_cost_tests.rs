@@ -0,0 +1,48 @@
+test!(
+    group_cost_wee_alloc,
+    "group-cost",
+    "./fixtures/wee_alloc.wasm",
+    "wee_alloc"
+);
+
+test!(
+    group_cost_wee_alloc_json,
+    "group-cost",
+    "./fixtures/wee_alloc.wasm",
+    "wee_alloc",
+    "-f",
+    "json"
+);
+
+test!(
+    group_cost_wee_alloc_csv,
+    "group-cost",
+    "./fixtures/wee_alloc.wasm",
+    "wee_alloc",
+    "-f",
+    "csv"
+);
+
+// Blank lines in the names file are ignored.
+test!(
+    group_cost_names_file,
+    "group-cost",
+    "./fixtures/wee_alloc.wasm",
+    "--names-file",
+    "./fixtures/wee_alloc_group.txt"
+);
+
+test!(
+    group_cost_name_and_regex,
+    "group-cost",
+    "./fixtures/wee_alloc.wasm",
+    "alloc_first_fit",
+    "--name",
+    "hello"
+);
+
+test_error!(
+    group_cost_empty_group,
+    "group-cost",
+    "./fixtures/wee_alloc.wasm"
+);
@@ -0,0 +1,66 @@
+test!(
+    imports_mono_top_5,
+    "imports",
+    "./fixtures/mono.wasm",
+    "-n",
+    "5"
+);
+
+test!(
+    imports_wee_alloc_2,
+    "imports",
+    "./fixtures/wee_alloc.2.wasm"
+);
+
+test!(
+    imports_cpp_monos_json,
+    "imports",
+    "./fixtures/cpp-monos.wasm",
+    "-f",
+    "json"
+);
+
+test!(
+    imports_mono_top_3_csv,
+    "imports",
+    "./fixtures/mono.wasm",
+    "-n",
+    "3",
+    "-f",
+    "csv"
+);
+
+// The functions calling `hello` are 16 bytes, so none of them are shims here.
+test!(
+    imports_cpp_monos_max_shim_size,
+    "imports",
+    "./fixtures/cpp-monos.wasm",
+    "--max-shim-size",
+    "15"
+);
+
+test!(imports_no_imports, "imports", "./fixtures/garbage.wasm");
+
+// `duplicate_imports.wasm` imports `env::now` three times with the same type,
+// and once more with another type, which is not a duplicate.
+test!(
+    imports_duplicate_imports,
+    "imports",
+    "./fixtures/duplicate_imports.wasm"
+);
+
+test!(
+    imports_duplicate_imports_json,
+    "imports",
+    "./fixtures/duplicate_imports.wasm",
+    "-f",
+    "json"
+);
+
+test!(
+    imports_duplicate_imports_csv,
+    "imports",
+    "./fixtures/duplicate_imports.wasm",
+    "-f",
+    "csv"
+);
@@ -0,0 +1,48 @@
+// `macho_fat` has an `x86_64` and an `arm64` slice, and one of their
+// functions, `unlisted`, has no symbol. See `fixtures/macho.py`.
+
+test_error!(
+    macho_top_universal_without_arch,
+    "top",
+    "./fixtures/macho_fat"
+);
+
+test_error!(
+    macho_top_missing_arch,
+    "top",
+    "--arch",
+    "ppc",
+    "./fixtures/macho_fat"
+);
+
+// Without debug info, `_helper` extends over `unlisted`, up to the end of
+// `__text`.
+test!(
+    macho_top_arm64,
+    "top",
+    "--arch",
+    "arm64",
+    "./fixtures/macho_fat"
+);
+
+test!(
+    macho_top_dsym,
+    "top",
+    "--arch",
+    "x86_64",
+    "--dsym",
+    "./fixtures/macho_fat.dSYM",
+    "./fixtures/macho_fat"
+);
+
+test!(
+    macho_top_dsym_group_by_file,
+    "top",
+    "--arch",
+    "arm64",
+    "--dsym",
+    "./fixtures/macho_fat.dSYM/Contents/Resources/DWARF/macho_fat",
+    "--group-by",
+    "file",
+    "./fixtures/macho_fat"
+);
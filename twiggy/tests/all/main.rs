@@ -26,6 +26,8 @@ macro_rules! test {
                     .arg($args)
                 )*
                 .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/all/"))
+                // Keep the timestamps of `--provenance` output stable.
+                .env("SOURCE_DATE_EPOCH", "0")
                 .output()
                 .unwrap();
 
@@ -95,10 +97,75 @@ macro_rules! test {
     }
 }
 
+/// Like `test!`, but for inputs that `twiggy` must reject: checks that it exits
+/// with an error rather than a panic, and compares the error it printed against
+/// the expectation.
+macro_rules! test_error {
+    ( $name:ident $( , $args:expr )* ) => {
+        #[test]
+        fn $name() {
+            use std::fs;
+            use std::process::Command;
+            use crate::slurp;
+
+            let output = Command::new("cargo")
+                .arg("run")
+                .arg("--")
+                $(
+                    .arg($args)
+                )*
+                .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/all/"))
+                .output()
+                .unwrap();
+
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(
+                !output.status.success() && !stderr.contains("panicked"),
+                "should have failed with an error, not a panic\n\n\
+                 ============================== stderr ==============================\n\n\
+                 {}\n\n",
+                stderr,
+            );
+
+            let expected_path = concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/all/expectations/",
+                stringify!($name)
+            );
+            let actual = stderr.lines().last().unwrap_or("");
+
+            if ::std::env::var("TWIGGY_UPDATE_TEST_EXPECTATIONS").is_ok() {
+                fs::write(expected_path, format!("{}\n", actual)).unwrap();
+                return;
+            }
+
+            let expected = slurp(expected_path).unwrap_or(vec![]);
+            assert_eq!(String::from_utf8_lossy(&expected).trim_end(), actual);
+        }
+    }
+}
+
+mod call_indices_tests;
+mod callsites_tests;
+mod check_tests;
+mod crates_tests;
+mod data_tests;
 mod diff_tests;
 mod dominators_tests;
+mod duplicates_tests;
 mod elf_format_tests;
+mod files_tests;
 mod garbage_tests;
+mod group_cost_tests;
+mod imports_tests;
+mod macho_format_tests;
+mod malformed_tests;
 mod monos_tests;
+mod neighbors_tests;
 mod paths_tests;
+mod pe_format_tests;
+mod provenance_tests;
+mod sections_tests;
+mod snapshot_tests;
+mod stdin_tests;
 mod top_tests;
@@ -0,0 +1,45 @@
+// References to functions, globals and tables that do not exist are left out of
+// the graph with a warning, rather than stopping the whole analysis.
+test!(
+    malformed_call_index,
+    "top",
+    "./fixtures/malformed_call_index.wasm"
+);
+
+test!(
+    malformed_export_index,
+    "top",
+    "./fixtures/malformed_export_index.wasm"
+);
+
+test!(
+    malformed_global_index,
+    "top",
+    "./fixtures/malformed_global_index.wasm"
+);
+
+test!(
+    malformed_start_index,
+    "top",
+    "./fixtures/malformed_start_index.wasm"
+);
+
+// An element segment for a table the module does not have, which lists a
+// function it does not have either.
+test!(
+    malformed_elem_index,
+    "top",
+    "./fixtures/malformed_elem_index.wasm"
+);
+
+test_error!(
+    malformed_truncated_code,
+    "top",
+    "./fixtures/malformed_truncated_code.wasm"
+);
+
+test_error!(
+    duplicate_code_section,
+    "top",
+    "./fixtures/duplicate_code_section.wasm"
+);
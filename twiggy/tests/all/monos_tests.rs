@@ -6,6 +6,8 @@ test!(
     monos_maxes,
     "monos",
     "./fixtures/monos.wasm",
+    "--disambiguators",
+    "keep",
     "-m",
     "2",
     "-n",
@@ -43,6 +45,8 @@ test!(
     monos_all_monos,
     "monos",
     "./fixtures/monos.wasm",
+    "--disambiguators",
+    "keep",
     "--all-monos"
 );
 
@@ -75,3 +79,45 @@ test!(
     "alloc::slice::insert_head",
     "alloc::slice::merge_sort"
 );
+
+test!(
+    monos_group_by_crate,
+    "monos",
+    "./fixtures/monos.wasm",
+    "--group-by",
+    "crate"
+);
+
+test!(
+    monos_group_by_crate_json,
+    "monos",
+    "./fixtures/monos.wasm",
+    "--group-by",
+    "crate",
+    "-m",
+    "2",
+    "-n",
+    "1",
+    "-f",
+    "json"
+);
+
+test!(
+    monos_group_by_crate_csv,
+    "monos",
+    "./fixtures/monos.wasm",
+    "--group-by",
+    "crate",
+    "-m",
+    "3",
+    "-f",
+    "csv"
+);
+
+test_error!(
+    monos_group_by_file,
+    "monos",
+    "./fixtures/monos.wasm",
+    "--group-by",
+    "file"
+);
@@ -0,0 +1,57 @@
+test!(
+    neighbors_called_once,
+    "neighbors",
+    "./fixtures/paths_test.wasm",
+    "calledOnce"
+);
+
+test!(
+    neighbors_called_once_incoming,
+    "neighbors",
+    "./fixtures/paths_test.wasm",
+    "calledOnce",
+    "--direction",
+    "incoming"
+);
+
+test!(
+    neighbors_called_twice_both_calls,
+    "neighbors",
+    "./fixtures/paths_test.wasm",
+    "calledTwice",
+    "--direction",
+    "both",
+    "--edges",
+    "calls"
+);
+
+test!(
+    neighbors_regex_json,
+    "neighbors",
+    "./fixtures/paths_test.wasm",
+    "--regex",
+    "called.*",
+    "--direction",
+    "both",
+    "-f",
+    "json"
+);
+
+test!(
+    neighbors_called_once_csv,
+    "neighbors",
+    "./fixtures/paths_test.wasm",
+    "calledOnce",
+    "--direction",
+    "both",
+    "-f",
+    "csv"
+);
+
+test!(
+    neighbors_by_id,
+    "neighbors",
+    "./fixtures/paths_test.wasm",
+    "--id",
+    "s4.e0"
+);
@@ -187,6 +187,26 @@ test!(
     "--regex"
 );
 
+test!(
+    paths_test_regex_json,
+    "paths",
+    "./fixtures/paths_test.wasm",
+    "calledOnce",
+    "called.*",
+    "--regex",
+    "-f",
+    "json"
+);
+
+test_error!(
+    paths_test_regex_no_match,
+    "paths",
+    "./fixtures/paths_test.wasm",
+    "called.*",
+    "nothing_matches_this",
+    "--regex"
+);
+
 test!(
     issue_16,
     "paths",
@@ -194,9 +214,13 @@ test!(
     "compute_column_spans"
 );
 
+// The indirect calls in `mappings.wasm` may call most of its table, so its
+// retaining paths are limited in depth to keep them readable.
 test!(
     paths_error_test_no_max_paths,
     "paths",
+    "-d",
+    "3",
     "./fixtures/mappings.wasm",
     "std::io::error::Error::new::h8c006d5367bc92ed"
 );
@@ -206,6 +230,8 @@ test!(
     "paths",
     "-f",
     "csv",
+    "-d",
+    "3",
     "./fixtures/mappings.wasm",
     "std::io::error::Error::new::h8c006d5367bc92ed"
 );
@@ -215,6 +241,8 @@ test!(
     "paths",
     "-f",
     "json",
+    "-d",
+    "3",
     "./fixtures/mappings.wasm",
     "std::io::error::Error::new::h8c006d5367bc92ed"
 );
@@ -249,3 +277,95 @@ test!(
     "./fixtures/mappings.wasm",
     "std::io::error::Error::new::h8c006d5367bc92ed"
 );
+
+test!(
+    paths_test_called_once_edges_calls,
+    "paths",
+    "./fixtures/paths_test.wasm",
+    "calledOnce",
+    "--edges",
+    "calls"
+);
+
+test!(
+    paths_by_id,
+    "paths",
+    "./fixtures/paths_test.wasm",
+    "--id",
+    "s4.e0"
+);
+
+test_error!(
+    paths_unknown_id,
+    "paths",
+    "./fixtures/paths_test.wasm",
+    "--id",
+    "s99.e1"
+);
+
+// `main` calls `a` and `c` indirectly through the table. `b` is in the table
+// too, but has another type.
+test!(
+    paths_call_indirect,
+    "paths",
+    "./fixtures/call_indirect.wasm",
+    "a",
+    "b",
+    "c"
+);
+
+// The same module, with its element segment listing `a` and `b` as function
+// indices, and as `ref.func` expressions.
+test!(
+    paths_elem_functions,
+    "paths",
+    "./fixtures/elem_functions.wasm",
+    "a",
+    "b"
+);
+
+test!(
+    paths_elem_expressions,
+    "paths",
+    "./fixtures/elem_expressions.wasm",
+    "a",
+    "b"
+);
+
+// Without any functions of its own, the start section, exports and element
+// segments still refer to imported functions.
+test!(
+    paths_imported_functions_only,
+    "paths",
+    "./fixtures/imported_functions_only.wasm",
+    "import env::init",
+    "import env::log"
+);
+
+// The edges of `linking.wasm` are its relocations.
+test!(paths_linking, "paths", "./fixtures/linking.wasm", "on_even");
+
+// Edges point from each item to the item it refers to, and the quotes in
+// names are escaped.
+test!(
+    paths_linking_dot,
+    "paths",
+    "./fixtures/linking.wasm",
+    "data segment \"greeting\"",
+    "-f",
+    "dot"
+);
+
+test!(
+    paths_linking_dot_descending,
+    "paths",
+    "./fixtures/linking.wasm",
+    "run",
+    "--descending",
+    "-f",
+    "dot"
+);
+
+// Each function of `multi_memory.wasm` loads from address 4, but of a
+// different memory, and so from a different data segment.
+test!(paths_multi_memory, "paths", "./fixtures/multi_memory.wasm");
@@ -0,0 +1,24 @@
+// `pe_msvc.exe` has MSVC-mangled symbols, and `pe_mingw.exe` embeds DWARF
+// that describes `unlisted`, which has no symbol. See `fixtures/pe.py`.
+
+test!(pe_top_msvc, "top", "./fixtures/pe_msvc.exe");
+
+test!(pe_top_mingw, "top", "./fixtures/pe_mingw.exe");
+
+test!(
+    pe_top_mingw_group_by_file,
+    "top",
+    "--group-by",
+    "file",
+    "./fixtures/pe_mingw.exe"
+);
+
+// Each symbol is a child of its section.
+test!(pe_dominators_msvc, "dominators", "./fixtures/pe_msvc.exe");
+
+test!(
+    pe_paths_mingw,
+    "paths",
+    "./fixtures/pe_mingw.exe",
+    "unlisted"
+);
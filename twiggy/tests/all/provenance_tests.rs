@@ -0,0 +1,31 @@
+test!(
+    provenance_text,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "2",
+    "--provenance"
+);
+
+test!(
+    provenance_json,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "2",
+    "--provenance",
+    "-f",
+    "json"
+);
+
+test!(
+    provenance_csv_diff,
+    "diff",
+    "./fixtures/wee_alloc.wasm",
+    "./fixtures/wee_alloc.2.wasm",
+    "-n",
+    "2",
+    "--provenance",
+    "-f",
+    "csv"
+);
@@ -0,0 +1,29 @@
+test!(
+    sections_elem_functions,
+    "sections",
+    "./fixtures/elem_functions.wasm"
+);
+
+test!(
+    sections_elem_functions_file_order,
+    "sections",
+    "./fixtures/elem_functions.wasm",
+    "--sort",
+    "file"
+);
+
+test!(
+    sections_elem_functions_json,
+    "sections",
+    "./fixtures/elem_functions.wasm",
+    "-f",
+    "json"
+);
+
+test!(
+    sections_elem_functions_csv,
+    "sections",
+    "./fixtures/elem_functions.wasm",
+    "-f",
+    "csv"
+);
@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Run `twiggy` with the given arguments.
+fn run(args: &[&str]) -> Output {
+    Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .args(args)
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/all/"))
+        .output()
+        .unwrap()
+}
+
+/// Run `twiggy` with the given arguments, and return what it wrote to stdout.
+fn stdout(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(
+        output.status.success(),
+        "should have run `twiggy` OK\n\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Take a snapshot of the given fixture, and return the path to it.
+fn snapshot(fixture: &str, name: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    stdout(&[
+        "snapshot",
+        &format!("./fixtures/{}", fixture),
+        "-o",
+        path.to_str().unwrap(),
+    ]);
+    path
+}
+
+#[test]
+fn diff_against_snapshots_matches_diff_against_binaries() {
+    let old = snapshot("wee_alloc.wasm", "diff_old.twiggy");
+    let new = snapshot("wee_alloc.2.wasm", "diff_new.twiggy");
+    let old = old.to_str().unwrap();
+    let new = new.to_str().unwrap();
+
+    for args in [
+        &[][..],
+        &["-a"],
+        &["-n", "5"],
+        &["-f", "json"],
+        &["-f", "csv"],
+    ] {
+        let diff = |old: &str, new: &str| {
+            let mut diff = vec!["diff", old, new];
+            diff.extend_from_slice(args);
+            stdout(&diff)
+        };
+        let expected = diff("./fixtures/wee_alloc.wasm", "./fixtures/wee_alloc.2.wasm");
+        assert_eq!(diff(old, "./fixtures/wee_alloc.2.wasm"), expected);
+        assert_eq!(diff("./fixtures/wee_alloc.wasm", new), expected);
+        assert_eq!(diff(old, new), expected);
+    }
+}
+
+#[test]
+fn snapshots_keep_source_locations() {
+    let old = snapshot("dwarf.wasm", "dwarf.twiggy");
+    assert_eq!(
+        stdout(&["top", old.to_str().unwrap(), "--group-by", "file"]),
+        stdout(&["top", "./fixtures/dwarf.wasm", "--group-by", "file"])
+    );
+}
+
+#[test]
+fn snapshot_of_another_version_is_an_error() {
+    let path = snapshot("wee_alloc.wasm", "other_version.twiggy");
+    let mut data = std::fs::read(&path).unwrap();
+    let version = twiggy_ir::SNAPSHOT_MAGIC.len();
+    data[version..version + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    std::fs::write(&path, data).unwrap();
+
+    let output = run(&[
+        "diff",
+        path.to_str().unwrap(),
+        "./fixtures/wee_alloc.2.wasm",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains(&format!(
+            "error: the snapshot is in version {} of the twiggy snapshot format, but this \
+             twiggy only reads version {}",
+            u32::MAX,
+            twiggy_ir::SNAPSHOT_VERSION
+        )),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn diff_wire_needs_binaries() {
+    let old = snapshot("wee_alloc.wasm", "wire_old.twiggy");
+    let output = run(&[
+        "diff",
+        old.to_str().unwrap(),
+        "./fixtures/wee_alloc.2.wasm",
+        "--wire",
+    ]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("--wire compares the bytes of both binaries"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn data_needs_a_binary() {
+    let snapshot = snapshot("data_strings.wasm", "data_strings.twiggy");
+    let output = run(&["data", snapshot.to_str().unwrap()]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("data looks into the bytes of the data segments"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,67 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::slurp;
+
+/// Run `twiggy` with the given arguments and the given input piped into its
+/// stdin, and return what it wrote to stdout.
+fn run_with_stdin(args: &[&str], input: &[u8]) -> String {
+    let mut child = Command::new("cargo")
+        .arg("run")
+        .arg("--")
+        .args(args)
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/all/"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "should have run `twiggy` OK\n\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn expectation(name: &str) -> String {
+    let path = format!(
+        "{}/tests/all/expectations/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    String::from_utf8(slurp(path).unwrap()).unwrap()
+}
+
+// Without an extension to go by, the input is recognized by its magic number.
+#[test]
+fn top_reads_input_from_stdin() {
+    let input = slurp(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/all/fixtures/wee_alloc.wasm"
+    ))
+    .unwrap();
+    assert_eq!(
+        run_with_stdin(&["top", "-n", "10", "-"], &input),
+        expectation("top_wee_alloc")
+    );
+}
+
+#[test]
+fn diff_reads_either_input_from_stdin() {
+    let input = slurp(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/all/fixtures/wee_alloc.2.wasm"
+    ))
+    .unwrap();
+    assert_eq!(
+        run_with_stdin(&["diff", "./fixtures/wee_alloc.wasm", "-"], &input),
+        expectation("diff_wee_alloc")
+    );
+}
+
+test_error!(top_empty_stdin, "top", "-");
+
+test_error!(diff_both_inputs_from_stdin, "diff", "-", "-");
@@ -80,6 +80,28 @@ test!(
 // Regression test for https://github.com/rustwasm/twiggy/issues/151
 test!(top_mono, "top", "./fixtures/mono.wasm", "-n", "10");
 
+// Parsing with several threads gives the same items as parsing sequentially.
+test!(
+    top_mono_threads,
+    "top",
+    "./fixtures/mono.wasm",
+    "-n",
+    "10",
+    "--threads",
+    "4"
+);
+
+// A data section with more entries than the threshold becomes a single item.
+test!(
+    top_aggregate_threshold,
+    "top",
+    "./fixtures/mappings.wasm",
+    "-n",
+    "5",
+    "--aggregate-threshold",
+    "100"
+);
+
 test!(
     top_memory_module,
     "top",
@@ -87,3 +109,316 @@ test!(
     "-n",
     "10"
 );
+
+test!(
+    top_retained_mappings_threads,
+    "top",
+    "-n",
+    "10",
+    "--retained",
+    "--threads",
+    "4",
+    "./fixtures/mappings.wasm"
+);
+
+test!(
+    top_kind_function_wee_alloc,
+    "top",
+    "-n",
+    "5",
+    "--kind",
+    "function",
+    "./fixtures/wee_alloc.wasm"
+);
+
+test!(
+    top_exclude_kind_wee_alloc,
+    "top",
+    "-n",
+    "5",
+    "--exclude-kind",
+    "data_segment",
+    "--exclude-kind",
+    "debug_info",
+    "./fixtures/wee_alloc.wasm"
+);
+
+// The broad kinds `code`, `data`, `debug` and `misc` can be mixed with the
+// finer ones.
+test!(
+    top_kind_misc_wee_alloc,
+    "top",
+    "-n",
+    "5",
+    "--kind",
+    "misc",
+    "--exclude-kind",
+    "export",
+    "./fixtures/wee_alloc.wasm"
+);
+
+// `--only` applies before `--exclude`, and the footer counts what either
+// left out.
+test!(
+    top_only_exclude_wee_alloc,
+    "top",
+    "-n",
+    "5",
+    "--only",
+    "wee_alloc",
+    "--exclude",
+    "alloc_with|new_cell",
+    "./fixtures/wee_alloc.wasm"
+);
+
+// Items already left out by kind are not counted again as filtered by name.
+test!(
+    top_kind_exclude_name_wee_alloc,
+    "top",
+    "-n",
+    "3",
+    "--kind",
+    "code",
+    "--exclude",
+    "^wee_alloc::",
+    "./fixtures/wee_alloc.wasm"
+);
+
+test_error!(
+    top_only_invalid_regex,
+    "top",
+    "--only",
+    "(",
+    "./fixtures/wee_alloc.wasm"
+);
+
+test!(
+    top_kind_code_wee_alloc_csv,
+    "top",
+    "-n",
+    "5",
+    "-f",
+    "csv",
+    "--kind",
+    "code",
+    "./fixtures/wee_alloc.wasm"
+);
+
+test!(
+    top_group_by_file_source_map,
+    "top",
+    "-n",
+    "6",
+    "--group-by",
+    "file",
+    "--source-map",
+    "./fixtures/sourcemap.wasm.map",
+    "./fixtures/sourcemap.wasm"
+);
+
+test!(top_empty_module, "top", "./fixtures/empty_module.wasm");
+
+// Every function is imported, so there is no function or code section.
+test!(
+    top_imported_functions_only,
+    "top",
+    "./fixtures/imported_functions_only.wasm"
+);
+
+// The start and data count sections each have an entry, besides their
+// headers.
+test!(
+    top_start_data_count,
+    "top",
+    "./fixtures/start_data_count.wasm"
+);
+
+// A core dump's modules, instances and stack frames are each an item.
+test!(top_coredump, "top", "./fixtures/coredump.wasm");
+
+test!(
+    top_coredump_excluded,
+    "top",
+    "--exclude-kind",
+    "coredump",
+    "./fixtures/coredump.wasm"
+);
+
+test!(
+    top_coredump_json,
+    "top",
+    "-f",
+    "json",
+    "--kind",
+    "coredump",
+    "./fixtures/coredump.wasm"
+);
+
+// Two name sections, the first with two "function names" subsections. Every
+// subsection is its own item, and the first name given to a function wins.
+test!(
+    top_duplicate_names,
+    "top",
+    "./fixtures/duplicate_names.wasm"
+);
+
+test!(
+    top_min_brtable_bytes,
+    "top",
+    "./fixtures/mono.wasm",
+    "-n",
+    "5",
+    "--min-brtable-bytes",
+    "200"
+);
+
+test!(
+    top_control_flow_json,
+    "top",
+    "./fixtures/control_flow.wasm",
+    "--control-flow",
+    "-f",
+    "json"
+);
+
+test!(
+    top_name_bytes,
+    "top",
+    "./fixtures/mono.wasm",
+    "-n",
+    "3",
+    "--name-bytes"
+);
+
+// The only data segment in `memory.wasm` is empty, so it is all headers.
+test!(
+    top_data_headers,
+    "top",
+    "./fixtures/memory.wasm",
+    "-n",
+    "3",
+    "--data-headers"
+);
+
+// `map_optimized.wasm` is `map_original.wasm` without its unused function and
+// its names, with one function changed.
+test!(
+    top_map,
+    "top",
+    "./fixtures/map_optimized.wasm",
+    "--map",
+    "./fixtures/map_original.wasm"
+);
+
+test!(top_empty_sections, "top", "./fixtures/empty_sections.wasm");
+
+// `exceptions.wasm` imports one tag, defines another, and throws and catches
+// them with both `try_table` and the legacy `try`.
+test!(top_exceptions, "top", "./fixtures/exceptions.wasm");
+
+// The DWARF in `dwarf.wasm` is split up by its units and functions.
+test!(top_dwarf, "top", "./fixtures/dwarf.wasm");
+
+// The DWARF sections that are not split up are still debug info, and not
+// sections like `target_features`.
+test!(
+    top_dwarf_kind_debug_info,
+    "top",
+    "./fixtures/dwarf.wasm",
+    "--kind",
+    "debug_info"
+);
+
+test!(
+    top_dwarf_kind_debug,
+    "top",
+    "./fixtures/dwarf.wasm",
+    "--kind",
+    "debug",
+    "-f",
+    "csv"
+);
+
+test!(
+    top_edges_json,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "6",
+    "--edges",
+    "-f",
+    "json"
+);
+
+test!(
+    top_locals_split,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "12",
+    "--locals-split"
+);
+
+test!(
+    top_locals_split_json,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "6",
+    "--locals-split",
+    "-f",
+    "json"
+);
+
+// `linking.wasm` is an object file, named by the symbols of its `linking`
+// section.
+test!(top_linking, "top", "./fixtures/linking.wasm");
+
+test!(
+    top_compressed_wee_alloc,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "8",
+    "--compressed"
+);
+
+test!(
+    top_compressed_wee_alloc_json,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "3",
+    "--compressed",
+    "-f",
+    "json"
+);
+
+test!(
+    top_compressed_wee_alloc_csv,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "-n",
+    "3",
+    "--compressed",
+    "-f",
+    "csv"
+);
+
+// Symbols and sections no symbol is in have byte ranges in the file, but
+// `.text` is all symbols but for its padding, so it has none.
+test!(
+    top_compressed_pe_mingw,
+    "top",
+    "./fixtures/pe_mingw.exe",
+    "--compressed"
+);
+
+test_error!(
+    top_compressed_group_by,
+    "top",
+    "./fixtures/wee_alloc.wasm",
+    "--group-by",
+    "file",
+    "--compressed"
+);
@@ -3,11 +3,16 @@
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
 
+use std::fs;
+use std::io::Write;
+use std::path;
 use std::process;
 use structopt::StructOpt;
 use twiggy_analyze as analyze;
+use twiggy_ir as ir;
 use twiggy_opt::{self as opt, CommonCliOptions};
 use twiggy_parser as parser;
+use twiggy_traits as traits;
 
 fn main() {
     let options = opt::Options::from_args();
@@ -17,22 +22,297 @@ fn main() {
     }
 }
 
+/// Name the unnamed functions of the input at `path` after `original`'s, and
+/// say how that went.
+fn map_names(items: &mut ir::Items, path: &path::Path, original: &ir::Items) {
+    let mapped = analyze::map_names(items, original);
+    eprintln!(
+        "note: {}: --map found {} exact body matches and {} matches up to indices; {} unmatched",
+        path.display(),
+        mapped.exact(),
+        mapped.normalized(),
+        mapped.unmatched()
+    );
+}
+
+/// Say how large the input's graph is, if asked to, and whether heuristic
+/// edges had to be left out to keep it within `--max-edges`.
+fn report_edges(items: &ir::Items, opts: &opt::Options) {
+    if opts.verbose() {
+        eprintln!(
+            "note: {}: {} items, {} edges",
+            opts.input().display(),
+            items
+                .iter()
+                .filter(|item| item.id() != items.meta_root())
+                .count(),
+            items.edge_count()
+        );
+    }
+    if items.dropped_edges() > 0 {
+        eprintln!(
+            "warning: left out {} heuristic edges to stay within --max-edges {}",
+            items.dropped_edges(),
+            opts.max_edges().unwrap_or_default()
+        );
+    }
+}
+
+/// Start recording how the output is produced, with the sub-command's
+/// effective options.
+fn provenance(opts: &opt::Options) -> analyze::Provenance {
+    let (subcommand, options) = match opts {
+        opt::Options::Top(ref top) => ("top", format!("{:?}", top)),
+        opt::Options::Dominators(ref doms) => ("dominators", format!("{:?}", doms)),
+        opt::Options::Paths(ref paths) => ("paths", format!("{:?}", paths)),
+        opt::Options::Neighbors(ref neighbors) => ("neighbors", format!("{:?}", neighbors)),
+        opt::Options::Monos(ref monos) => ("monos", format!("{:?}", monos)),
+        opt::Options::Diff(ref diff) => ("diff", format!("{:?}", diff)),
+        opt::Options::Garbage(ref garbo) => ("garbage", format!("{:?}", garbo)),
+        opt::Options::Imports(ref imports) => ("imports", format!("{:?}", imports)),
+        opt::Options::CallIndices(ref calls) => ("call-indices", format!("{:?}", calls)),
+        opt::Options::Callsites(ref callsites) => ("callsites", format!("{:?}", callsites)),
+        opt::Options::Duplicates(ref dupes) => ("duplicates", format!("{:?}", dupes)),
+        opt::Options::GroupCost(ref group) => ("group-cost", format!("{:?}", group)),
+        opt::Options::Sections(ref sections) => ("sections", format!("{:?}", sections)),
+        opt::Options::Crates(ref crates) => ("crates", format!("{:?}", crates)),
+        opt::Options::Check(ref check) => ("check", format!("{:?}", check)),
+        opt::Options::Files(ref files) => ("files", format!("{:?}", files)),
+        opt::Options::Data(ref data) => ("data", format!("{:?}", data)),
+        opt::Options::Snapshot(ref snapshot) => ("snapshot", format!("{:?}", snapshot)),
+    };
+    analyze::Provenance::new(env!("CARGO_PKG_VERSION"), subcommand, options)
+}
+
+/// Read and parse the file at `path`, or stdin if it is `-`, recording it in
+/// `provenance`, if any. The input's bytes are returned along with its items,
+/// since stdin cannot be read twice.
+fn read_and_parse(
+    path: &path::Path,
+    opts: &opt::Options,
+    parse_options: &parser::ParseOptions,
+    provenance: &mut Option<analyze::Provenance>,
+) -> traits::Result<(ir::Items, Vec<u8>)> {
+    let data = parser::read_input(path)?;
+    if let Some(provenance) = provenance {
+        provenance.add_input(path, &data);
+    }
+    let items = parser::parse_file_with_options(path, &data, opts.parse_mode(), parse_options)?;
+    report_warnings(path, &items);
+    Ok((items, data))
+}
+
+/// Check that at most one of the inputs is stdin, which can only be read once.
+fn check_stdin_inputs(opts: &opt::Options) -> anyhow::Result<()> {
+    let other_input = match opts {
+        opt::Options::Diff(ref diff) => Some(diff.new_input()),
+        opt::Options::Check(ref check) => check.baseline(),
+        _ => None,
+    };
+    let stdin_inputs = [Some(opts.input()), other_input, opts.map()]
+        .iter()
+        .flatten()
+        .filter(|path| **path == path::Path::new(parser::STDIN_PATH))
+        .count();
+    if stdin_inputs > 1 {
+        anyhow::bail!("only one input can be read from stdin, but `-` was given more than once");
+    }
+    Ok(())
+}
+
+/// The most warnings to print for a single input. Fuzzed inputs can have
+/// thousands of problems, and the first few say what is wrong.
+const MAX_WARNINGS: usize = 10;
+
+/// Say what parsing the file at `path` had to leave out of its graph.
+fn report_warnings(path: &path::Path, items: &ir::Items) {
+    let warnings = items.warnings();
+    for warning in warnings.iter().take(MAX_WARNINGS) {
+        eprintln!("warning: {}: {}", path.display(), warning);
+    }
+    if warnings.len() > MAX_WARNINGS {
+        eprintln!(
+            "warning: {}: ... and {} more warnings",
+            path.display(),
+            warnings.len() - MAX_WARNINGS
+        );
+    }
+}
+
 fn run(opts: &opt::Options) -> anyhow::Result<()> {
-    let mut items = parser::read_and_parse(opts.input(), opts.parse_mode())?;
-
-    let data = match opts {
-        opt::Options::Top(ref top) => analyze::top(&mut items, top)?,
-        opt::Options::Dominators(ref doms) => analyze::dominators(&mut items, doms)?,
-        opt::Options::Paths(ref paths) => analyze::paths(&mut items, paths)?,
-        opt::Options::Monos(ref monos) => analyze::monos(&mut items, monos)?,
-        opt::Options::Garbage(ref garbo) => analyze::garbage(&items, garbo)?,
+    let mut parse_options = parser::ParseOptions::new();
+    parse_options.set_threads(opts.threads());
+    parse_options.set_aggregate_threshold(opts.aggregate_threshold());
+    parse_options.set_control_flow(opts.control_flow());
+    parse_options.set_body_hashes(opts.map().is_some());
+    parse_options.set_call_site_bytes(opts.call_site_bytes());
+    parse_options.set_locals_split(opts.locals_split());
+    parse_options.set_max_edges(opts.max_edges());
+    parse_options.set_arch(opts.arch().map(str::to_string));
+    parse_options.set_dsym(opts.dsym().map(path::Path::to_path_buf));
+    parse_options.set_source_map(opts.source_map().map(path::Path::to_path_buf));
+    let mut provenance = if opts.provenance() {
+        Some(provenance(opts))
+    } else {
+        None
+    };
+
+    check_stdin_inputs(opts)?;
+    let (mut items, data) = read_and_parse(opts.input(), opts, &parse_options, &mut provenance)?;
+    report_edges(&items, opts);
+    if let opt::Options::Snapshot(_) = opts {
+        let mut dest = opts.output_destination().open()?;
+        items.write_snapshot(&mut *dest)?;
+        dest.flush()?;
+        return Ok(());
+    }
+    let original = match opts.map() {
+        Some(path) => Some(read_and_parse(path, opts, &parse_options, &mut provenance)?.0),
+        None => None,
+    };
+    if let Some(original) = &original {
+        map_names(&mut items, opts.input(), original);
+    }
+    for pattern in analyze::add_extra_roots(&mut items, opts.extra_roots())? {
+        eprintln!("warning: --extra-root `{}` did not match any item", pattern);
+    }
+
+    let mut failed_rules = 0;
+    let data: Box<dyn traits::Emit> = match opts {
+        opt::Options::Top(ref top) => {
+            let mut result = analyze::top(&mut items, top)?;
+            if top.compressed() {
+                if ir::Items::is_snapshot(&data) {
+                    anyhow::bail!(
+                        "--compressed compresses the bytes of the binary, but {} is a snapshot",
+                        opts.input().display()
+                    );
+                }
+                if top.group_by().is_some() {
+                    anyhow::bail!(
+                        "grouped items do not have a compressed size, so `--group-by` cannot be \
+                         combined with `--compressed`"
+                    );
+                }
+                result.set_compressed(analyze::compressed_sizes(&items, &data)?);
+            }
+            Box::new(result)
+        }
+        opt::Options::Dominators(ref doms) => Box::new(analyze::dominators(&mut items, doms)?),
+        opt::Options::Paths(ref paths) => Box::new(analyze::paths(&mut items, paths)?),
+        opt::Options::Neighbors(ref neighbors) => {
+            Box::new(analyze::neighbors(&mut items, neighbors)?)
+        }
+        opt::Options::Monos(ref monos) => Box::new(analyze::monos(&mut items, monos)?),
+        opt::Options::Garbage(ref garbo) => Box::new(analyze::garbage(&mut items, garbo)?),
+        opt::Options::Imports(ref imports) => Box::new(analyze::imports(&mut items, imports)?),
+        opt::Options::CallIndices(ref calls) => Box::new(analyze::call_indices(&mut items, calls)?),
+        opt::Options::Callsites(ref callsites) => {
+            Box::new(analyze::callsites(&mut items, callsites)?)
+        }
+        opt::Options::Duplicates(ref dupes) => Box::new(analyze::duplicates(&mut items, dupes)?),
+        opt::Options::GroupCost(ref group) => {
+            let mut group = group.clone();
+            if let Some(path) = group.names_file() {
+                for name in fs::read_to_string(path)?.lines().map(str::trim) {
+                    if !name.is_empty() {
+                        group.add_name(name.to_string());
+                    }
+                }
+            }
+            Box::new(analyze::group_cost(&mut items, &group)?)
+        }
+        opt::Options::Sections(ref sections) => Box::new(analyze::sections(&mut items, sections)?),
+        opt::Options::Crates(ref crates) => Box::new(analyze::crates(&mut items, crates)?),
+        opt::Options::Files(ref files) => Box::new(analyze::files(&mut items, files)?),
+        opt::Options::Data(ref data_opts) => {
+            if ir::Items::is_snapshot(&data) {
+                anyhow::bail!(
+                    "data looks into the bytes of the data segments, but {} is a snapshot",
+                    opts.input().display()
+                );
+            }
+            Box::new(analyze::data(&mut items, &data, data_opts)?)
+        }
+        opt::Options::Snapshot(_) => unreachable!("snapshots are written before any analysis"),
+        opt::Options::Check(ref check) => {
+            let budget = fs::read_to_string(check.budget())?;
+            let budget = if check.budget().extension() == Some("json".as_ref()) {
+                analyze::Budget::from_json(&budget)?
+            } else {
+                analyze::Budget::from_toml(&budget)?
+            };
+            let baseline = match check.baseline() {
+                Some(path) => Some(read_and_parse(path, opts, &parse_options, &mut provenance)?.0),
+                None => None,
+            };
+            let result = analyze::check(&items, baseline.as_ref(), &budget)?;
+            failed_rules = result.failed();
+            Box::new(result)
+        }
         opt::Options::Diff(ref diff) => {
-            let mut new_items = parser::read_and_parse(diff.new_input(), opts.parse_mode())?;
-            analyze::diff(&mut items, &mut new_items, diff)?
+            let (mut new_items, new_data) =
+                read_and_parse(diff.new_input(), opts, &parse_options, &mut provenance)?;
+            if let Some(original) = &original {
+                map_names(&mut new_items, diff.new_input(), original);
+            }
+            let mut result = analyze::diff(&mut items, &mut new_items, diff)?;
+            if diff.wire() {
+                for (path, data) in [(opts.input(), &data), (diff.new_input(), &new_data)] {
+                    if ir::Items::is_snapshot(data) {
+                        anyhow::bail!(
+                            "--wire compares the bytes of both binaries, but {} is a snapshot",
+                            path.display()
+                        );
+                    }
+                }
+                let wire = analyze::wire_diff(&result, &items, &data, &new_items, &new_data)?;
+                result.set_wire(wire);
+            }
+            if diff.compressed() {
+                for (path, data) in [(opts.input(), &data), (diff.new_input(), &new_data)] {
+                    if ir::Items::is_snapshot(data) {
+                        anyhow::bail!(
+                            "--compressed compresses the bytes of both binaries, but {} is a \
+                             snapshot",
+                            path.display()
+                        );
+                    }
+                }
+                if diff.by_crate() {
+                    anyhow::bail!(
+                        "crates do not have a compressed size, so `--by-crate` cannot be \
+                         combined with `--compressed`"
+                    );
+                }
+                result.set_compressed(
+                    analyze::compressed_sizes(&items, &data)?,
+                    analyze::compressed_sizes(&new_items, &new_data)?,
+                );
+            }
+            Box::new(result)
         }
     };
 
     let mut dest = opts.output_destination().open()?;
 
-    data.emit(&items, &mut *dest, opts.output_format())
+    let with_provenance;
+    let data: &dyn traits::Emit = match provenance {
+        Some(ref provenance) => {
+            with_provenance = provenance.wrap(&*data);
+            &with_provenance
+        }
+        None => &*data,
+    };
+    data.emit(&items, &mut *dest, opts.output_format())?;
+    // Exit with an error once the results are out, for CI to fail on.
+    if failed_rules > 0 {
+        dest.flush()?;
+        anyhow::bail!(
+            "{} broke {} of the budget's rules",
+            opts.input().display(),
+            failed_rules
+        );
+    }
+    Ok(())
 }
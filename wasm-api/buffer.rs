@@ -0,0 +1,55 @@
+//! Feeding a binary's data to the module in chunks.
+//!
+//! Passing a whole binary to `parse` as one `Uint8Array` copies all of it into
+//! the module's memory while the JavaScript copy is still alive, so for a
+//! moment the binary exists twice. An `InputBuffer` instead lives in the
+//! module's memory from the start and is filled one chunk at a time, for
+//! example as the chunks of a `ReadableStream` arrive. JavaScript only needs
+//! to hold on to the current chunk, so there is only ever one full copy of the
+//! binary, which `Items.parse_buffer` parses in place and then frees.
+
+use wasm_bindgen::prelude::*;
+
+/// A buffer in the module's memory that a binary's data is written to chunk
+/// by chunk, and then parsed with `Items.parse_buffer`.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct InputBuffer {
+    data: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl InputBuffer {
+    /// Create an empty buffer with room for `capacity` bytes.
+    ///
+    /// When the binary's total size is known, such as from a `Content-Length`
+    /// header or a `File`'s `size`, passing it here means the buffer never has
+    /// to grow. Growing copies the data written so far, so it briefly needs
+    /// room for both the old and the new allocation.
+    pub fn new(capacity: usize) -> InputBuffer {
+        InputBuffer {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append a chunk of the binary's data to the buffer.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.data.extend_from_slice(chunk);
+    }
+
+    /// The number of bytes written to the buffer so far.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Has nothing been written to the buffer yet?
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl InputBuffer {
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
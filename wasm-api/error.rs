@@ -0,0 +1,55 @@
+//! Turning errors into JavaScript exceptions.
+//!
+//! Every error is thrown as a JavaScript `Error`, rather than trapping, so that
+//! callers can catch it. Its `message` is the error followed by its causes, and
+//! its `code` property says what kind of error it is:
+//!
+//! * `"not_a_format"`: the input is not in any format twiggy can parse.
+//! * `"malformed"`: the input is in a recognized format, but is malformed.
+//! * `"unsupported"`: the input uses something twiggy does not support yet.
+//! * `"analysis"`: the analysis could not be run with the given options, such
+//!   as when a regular expression does not compile.
+//! * `"invalid_options"`: an options object has an unknown field, or a field of
+//!   the wrong type.
+//! * `"io"`: writing the result failed.
+
+use twiggy_traits as traits;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Reflect, js_name = set)]
+    fn reflect_set(target: &JsValue, key: &JsValue, value: &JsValue) -> bool;
+}
+
+/// Convert one of twiggy's errors into a JavaScript `Error`.
+pub(crate) fn error_to_js(e: traits::Error) -> JsValue {
+    let code = match e {
+        traits::Error::NotAFormat => "not_a_format",
+        traits::Error::Io(_) => "io",
+        traits::Error::Malformed { .. } => "malformed",
+        traits::Error::Unsupported { .. } => "unsupported",
+        traits::Error::Analysis(_) => "analysis",
+        _ => "error",
+    };
+
+    let mut message = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    js_error(&message, code)
+}
+
+/// A JavaScript `Error` for an options object that could not be used.
+pub(crate) fn invalid_options(message: String) -> JsValue {
+    js_error(&message, "invalid_options")
+}
+
+fn js_error(message: &str, code: &str) -> JsValue {
+    let error: JsValue = JsError::new(message).into();
+    reflect_set(&error, &JsValue::from_str("code"), &JsValue::from_str(code));
+    error
+}
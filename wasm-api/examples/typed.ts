@@ -0,0 +1,42 @@
+// A typed example of using twiggy's WebAssembly API from TypeScript, against
+// bindings built with `wasm-bindgen --target web` or `--target bundler`.
+
+import {
+  Items,
+  TopRow,
+  PathsEntry,
+  top_options,
+  paths_options,
+  garbage_value,
+  garbage_options,
+} from './twiggy_wasm_api';
+
+export function report(data: Uint8Array): void {
+  // Parse once, then run every analysis on the same handle.
+  const items = Items.parse(data);
+
+  // Option objects are checked against the `TopOptions` interface, and the
+  // result is a `TopRow[]` rather than a JSON string.
+  const rows: TopRow[] = items.top_value(top_options({ max_items: 10, retained: true }));
+  for (const row of rows) {
+    console.log(`${row.retained_size ?? row.shallow_size}\t${row.name}`);
+  }
+
+  const largest = rows.find(row => row.id !== undefined);
+  if (largest) {
+    const paths: PathsEntry[] = items.paths_value([], paths_options({ ids: [largest.id!], max_depth: 3 }));
+    printCallers(paths, 0);
+  }
+
+  const garbage = garbage_value(data, garbage_options({ all: true }));
+  console.log(`${garbage.length} unreachable items`);
+
+  items.free();
+}
+
+function printCallers(entries: PathsEntry[], depth: number): void {
+  for (const entry of entries) {
+    console.log(`${'  '.repeat(depth)}${entry.name}`);
+    printCallers(entry.callers, depth + 1);
+  }
+}
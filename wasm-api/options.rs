@@ -0,0 +1,564 @@
+//! Building analysis options from plain JavaScript objects, such as
+//! `{ max_items: 10, retained: true }`, for callers that would rather not
+//! construct an options class and call its setters one by one.
+//!
+//! Unknown fields and fields of the wrong type are rejected with an error that
+//! names the field, rather than being silently ignored.
+//!
+//! WebAssembly cannot spawn threads, so `threads` is not in the options types,
+//! and any value other than 1 is rejected.
+
+use crate::error::invalid_options;
+use std::fmt::Display;
+use std::str::FromStr;
+use twiggy_ir as ir;
+use twiggy_opt as opt;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Object, js_name = keys)]
+    fn object_keys(object: &JsValue) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = Reflect, js_name = get, catch)]
+    fn reflect_get(target: &JsValue, key: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = Array, js_name = isArray)]
+    fn is_array(value: &JsValue) -> bool;
+
+    #[wasm_bindgen(typescript_type = "TopOptions")]
+    pub type TopOptions;
+
+    #[wasm_bindgen(typescript_type = "DominatorsOptions")]
+    pub type DominatorsOptions;
+
+    #[wasm_bindgen(typescript_type = "PathsOptions")]
+    pub type PathsOptions;
+
+    #[wasm_bindgen(typescript_type = "NeighborsOptions")]
+    pub type NeighborsOptions;
+
+    #[wasm_bindgen(typescript_type = "MonosOptions")]
+    pub type MonosOptions;
+
+    #[wasm_bindgen(typescript_type = "GarbageOptions")]
+    pub type GarbageOptions;
+
+    #[wasm_bindgen(typescript_type = "DiffOptions")]
+    pub type DiffOptions;
+
+    #[wasm_bindgen(typescript_type = "ImportsOptions")]
+    pub type ImportsOptions;
+
+    #[wasm_bindgen(typescript_type = "CallIndicesOptions")]
+    pub type CallIndicesOptions;
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const OPTIONS_TYPES: &'static str = r#"
+export interface TopOptions {
+  max_items?: number;
+  retaining_paths?: boolean;
+  retained?: boolean;
+  kinds?: string[];
+  exclude_kinds?: string[];
+  only?: string[];
+  exclude?: string[];
+}
+
+export interface DominatorsOptions {
+  items?: string[];
+  ids?: string[];
+  max_depth?: number;
+  max_rows?: number;
+  regex?: boolean;
+}
+
+export interface PathsOptions {
+  functions?: string[];
+  ids?: string[];
+  max_depth?: number;
+  max_paths?: number;
+  descending?: boolean;
+  regex?: boolean;
+  edges?: string[];
+}
+
+export interface NeighborsOptions {
+  items?: string[];
+  ids?: string[];
+  regex?: boolean;
+  direction?: "outgoing" | "incoming" | "both";
+  edges?: string[];
+}
+
+export interface MonosOptions {
+  functions?: string[];
+  max_generics?: number;
+  max_monos?: number;
+  only_generics?: boolean;
+  all?: boolean;
+  regex?: boolean;
+  disambiguators?: "keep" | "strip";
+}
+
+export interface GarbageOptions {
+  max_items?: number;
+  all?: boolean;
+  show_data_segments?: boolean;
+  exclude_edges?: string[];
+  kinds?: string[];
+  exclude_kinds?: string[];
+}
+
+export interface DiffOptions {
+  items?: string[];
+  max_items?: number;
+  all?: boolean;
+  regex?: boolean;
+  match?: "exact" | "fuzzy";
+  disambiguators?: "keep" | "strip";
+  track_moves?: boolean;
+}
+
+export interface ImportsOptions {
+  max_items?: number;
+  all?: boolean;
+  max_shim_size?: number;
+}
+
+export interface CallIndicesOptions {
+  max_items?: number;
+  all?: boolean;
+}
+"#;
+
+/// Build `Top` options from a plain object.
+#[wasm_bindgen]
+pub fn top_options(options: TopOptions) -> Result<opt::Top, JsValue> {
+    let fields = Fields::new(
+        options.into(),
+        &[
+            "max_items",
+            "retaining_paths",
+            "retained",
+            "threads",
+            "kinds",
+            "exclude_kinds",
+            "only",
+            "exclude",
+        ],
+    )?;
+
+    let mut top = opt::Top::new();
+    if let Some(max_items) = fields.u32("max_items")? {
+        top.set_max_items(max_items);
+    }
+    if let Some(retaining_paths) = fields.bool("retaining_paths")? {
+        top.set_retaining_paths(retaining_paths);
+    }
+    if let Some(retained) = fields.bool("retained")? {
+        top.set_retained(retained);
+    }
+    fields.single_thread()?;
+    for kind in fields.parsed_list::<ir::KindFilter>("kinds")? {
+        top.add_kind(kind);
+    }
+    for kind in fields.parsed_list::<ir::KindFilter>("exclude_kinds")? {
+        top.add_excluded_kind(kind);
+    }
+    for pattern in fields.strings("only")? {
+        top.add_only(pattern);
+    }
+    for pattern in fields.strings("exclude")? {
+        top.add_excluded_name(pattern);
+    }
+    Ok(top)
+}
+
+/// Build `Dominators` options from a plain object.
+#[wasm_bindgen]
+pub fn dominators_options(options: DominatorsOptions) -> Result<opt::Dominators, JsValue> {
+    let fields = Fields::new(
+        options.into(),
+        &["items", "ids", "max_depth", "max_rows", "regex", "threads"],
+    )?;
+
+    let mut dominators = opt::Dominators::new();
+    for item in fields.strings("items")? {
+        dominators.add_item(item);
+    }
+    for id in fields.parsed_list::<ir::Id>("ids")? {
+        dominators.add_id(id);
+    }
+    if let Some(max_depth) = fields.u32("max_depth")? {
+        dominators.set_max_depth(max_depth);
+    }
+    if let Some(max_rows) = fields.u32("max_rows")? {
+        dominators.set_max_rows(max_rows);
+    }
+    if let Some(regex) = fields.bool("regex")? {
+        dominators.set_using_regexps(regex);
+    }
+    fields.single_thread()?;
+    Ok(dominators)
+}
+
+/// Build `Paths` options from a plain object.
+#[wasm_bindgen]
+pub fn paths_options(options: PathsOptions) -> Result<opt::Paths, JsValue> {
+    let fields = Fields::new(
+        options.into(),
+        &[
+            "functions",
+            "ids",
+            "max_depth",
+            "max_paths",
+            "descending",
+            "regex",
+            "edges",
+        ],
+    )?;
+
+    let mut paths = opt::Paths::new();
+    for function in fields.strings("functions")? {
+        paths.add_function(function);
+    }
+    for id in fields.parsed_list::<ir::Id>("ids")? {
+        paths.add_id(id);
+    }
+    if let Some(max_depth) = fields.u32("max_depth")? {
+        paths.set_max_depth(max_depth);
+    }
+    if let Some(max_paths) = fields.u32("max_paths")? {
+        paths.set_max_paths(max_paths);
+    }
+    if let Some(descending) = fields.bool("descending")? {
+        paths.set_descending(descending);
+    }
+    if let Some(regex) = fields.bool("regex")? {
+        paths.set_using_regexps(regex);
+    }
+    for kind in fields.parsed_list::<ir::EdgeKind>("edges")? {
+        paths.add_edge_kind(kind);
+    }
+    Ok(paths)
+}
+
+/// Build `Neighbors` options from a plain object.
+#[wasm_bindgen]
+pub fn neighbors_options(options: NeighborsOptions) -> Result<opt::Neighbors, JsValue> {
+    let fields = Fields::new(
+        options.into(),
+        &["items", "ids", "regex", "direction", "edges"],
+    )?;
+
+    let mut neighbors = opt::Neighbors::new();
+    for item in fields.strings("items")? {
+        neighbors.add_item(item);
+    }
+    for id in fields.parsed_list::<ir::Id>("ids")? {
+        neighbors.add_id(id);
+    }
+    if let Some(regex) = fields.bool("regex")? {
+        neighbors.set_using_regexps(regex);
+    }
+    if let Some(direction) = fields.parsed::<ir::Direction>("direction")? {
+        neighbors.set_direction(direction);
+    }
+    for kind in fields.parsed_list::<ir::EdgeKind>("edges")? {
+        neighbors.add_edge_kind(kind);
+    }
+    Ok(neighbors)
+}
+
+/// Build `Monos` options from a plain object.
+#[wasm_bindgen]
+pub fn monos_options(options: MonosOptions) -> Result<opt::Monos, JsValue> {
+    let fields = Fields::new(
+        options.into(),
+        &[
+            "functions",
+            "max_generics",
+            "max_monos",
+            "only_generics",
+            "all",
+            "regex",
+            "disambiguators",
+        ],
+    )?;
+
+    let mut monos = opt::Monos::new();
+    for function in fields.strings("functions")? {
+        monos.add_function(function);
+    }
+    if let Some(max_generics) = fields.u32("max_generics")? {
+        monos.set_max_generics(max_generics);
+    }
+    if let Some(max_monos) = fields.u32("max_monos")? {
+        monos.set_max_monos(max_monos);
+    }
+    if let Some(only_generics) = fields.bool("only_generics")? {
+        monos.set_only_generics(only_generics);
+    }
+    if let Some(all) = fields.bool("all")? {
+        monos.set_all(all);
+    }
+    if let Some(regex) = fields.bool("regex")? {
+        monos.set_using_regexps(regex);
+    }
+    if let Some(disambiguators) = fields.parsed::<opt::Disambiguators>("disambiguators")? {
+        monos.set_disambiguators(disambiguators);
+    }
+    Ok(monos)
+}
+
+/// Build `Garbage` options from a plain object.
+#[wasm_bindgen]
+pub fn garbage_options(options: GarbageOptions) -> Result<opt::Garbage, JsValue> {
+    let fields = Fields::new(
+        options.into(),
+        &[
+            "max_items",
+            "all",
+            "show_data_segments",
+            "threads",
+            "exclude_edges",
+            "kinds",
+            "exclude_kinds",
+        ],
+    )?;
+
+    let mut garbage = opt::Garbage::new();
+    if let Some(max_items) = fields.u32("max_items")? {
+        garbage.set_max_items(max_items);
+    }
+    if let Some(all) = fields.bool("all")? {
+        garbage.set_all_items(all);
+    }
+    if let Some(show) = fields.bool("show_data_segments")? {
+        garbage.set_show_data_segments(show);
+    }
+    fields.single_thread()?;
+    for kind in fields.parsed_list::<ir::EdgeKind>("exclude_edges")? {
+        garbage.add_excluded_edge(kind);
+    }
+    for kind in fields.parsed_list::<ir::KindFilter>("kinds")? {
+        garbage.add_kind(kind);
+    }
+    for kind in fields.parsed_list::<ir::KindFilter>("exclude_kinds")? {
+        garbage.add_excluded_kind(kind);
+    }
+    Ok(garbage)
+}
+
+/// Build `Diff` options from a plain object.
+#[wasm_bindgen]
+pub fn diff_options(options: DiffOptions) -> Result<opt::Diff, JsValue> {
+    let fields = Fields::new(
+        options.into(),
+        &[
+            "items",
+            "max_items",
+            "all",
+            "regex",
+            "match",
+            "disambiguators",
+            "track_moves",
+        ],
+    )?;
+
+    let mut diff = opt::Diff::new();
+    for item in fields.strings("items")? {
+        diff.add_item(item);
+    }
+    if let Some(max_items) = fields.u32("max_items")? {
+        diff.set_max_items(max_items);
+    }
+    if let Some(all) = fields.bool("all")? {
+        diff.set_all_items(all);
+    }
+    if let Some(regex) = fields.bool("regex")? {
+        diff.set_using_regexps(regex);
+    }
+    if let Some(name_matching) = fields.parsed::<opt::NameMatching>("match")? {
+        diff.set_name_matching(name_matching);
+    }
+    if let Some(disambiguators) = fields.parsed::<opt::Disambiguators>("disambiguators")? {
+        diff.set_disambiguators(disambiguators);
+    }
+    if let Some(track_moves) = fields.bool("track_moves")? {
+        diff.set_track_moves(track_moves);
+    }
+    Ok(diff)
+}
+
+/// Build `Imports` options from a plain object.
+#[wasm_bindgen]
+pub fn imports_options(options: ImportsOptions) -> Result<opt::Imports, JsValue> {
+    let fields = Fields::new(options.into(), &["max_items", "all", "max_shim_size"])?;
+
+    let mut imports = opt::Imports::new();
+    if let Some(max_items) = fields.u32("max_items")? {
+        imports.set_max_items(max_items);
+    }
+    if let Some(all) = fields.bool("all")? {
+        imports.set_all_items(all);
+    }
+    if let Some(size) = fields.u32("max_shim_size")? {
+        imports.set_max_shim_size(size);
+    }
+    Ok(imports)
+}
+
+/// Build `CallIndices` options from a plain object.
+#[wasm_bindgen]
+pub fn call_indices_options(options: CallIndicesOptions) -> Result<opt::CallIndices, JsValue> {
+    let fields = Fields::new(options.into(), &["max_items", "all"])?;
+
+    let mut calls = opt::CallIndices::new();
+    if let Some(max_items) = fields.u32("max_items")? {
+        calls.set_max_items(max_items);
+    }
+    if let Some(all) = fields.bool("all")? {
+        calls.set_all_items(all);
+    }
+    Ok(calls)
+}
+
+/// The fields of a plain options object, checked against the names that an
+/// analysis accepts.
+struct Fields {
+    object: JsValue,
+}
+
+impl Fields {
+    /// `undefined` and `null` are accepted as an object without any fields.
+    fn new(object: JsValue, known: &[&str]) -> Result<Fields, JsValue> {
+        if object.is_undefined() || object.is_null() {
+            return Ok(Fields { object });
+        }
+        if !object.is_object() || is_array(&object) {
+            return Err(invalid_options("options must be an object".to_string()));
+        }
+
+        for key in array_elements(&object_keys(&object))? {
+            let key = key.as_string().unwrap_or_default();
+            if !known.contains(&key.as_str()) {
+                return Err(invalid_options(format!("unknown option `{}`", key)));
+            }
+        }
+        Ok(Fields { object })
+    }
+
+    fn get(&self, field: &str) -> Result<Option<JsValue>, JsValue> {
+        if self.object.is_undefined() || self.object.is_null() {
+            return Ok(None);
+        }
+        let value = reflect_get(&self.object, &JsValue::from_str(field))?;
+        Ok(if value.is_undefined() {
+            None
+        } else {
+            Some(value)
+        })
+    }
+
+    fn bool(&self, field: &str) -> Result<Option<bool>, JsValue> {
+        self.get(field)?
+            .map(|value| {
+                value
+                    .as_bool()
+                    .ok_or_else(|| invalid_options(format!("option `{}` must be a boolean", field)))
+            })
+            .transpose()
+    }
+
+    fn u32(&self, field: &str) -> Result<Option<u32>, JsValue> {
+        self.get(field)?
+            .map(|value| match value.as_f64() {
+                Some(n) if n >= 0.0 && n <= f64::from(u32::MAX) && n.fract() == 0.0 => Ok(n as u32),
+                _ => Err(invalid_options(format!(
+                    "option `{}` must be a non-negative integer",
+                    field
+                ))),
+            })
+            .transpose()
+    }
+
+    /// `threads` may only be 1, the default, as WebAssembly has no threads to
+    /// spread an analysis over.
+    fn single_thread(&self) -> Result<(), JsValue> {
+        match self.u32("threads")? {
+            None | Some(1) => Ok(()),
+            Some(_) => Err(invalid_options(
+                "option `threads` must be 1, as the WebAssembly API cannot spawn threads"
+                    .to_string(),
+            )),
+        }
+    }
+
+    fn string(&self, field: &str) -> Result<Option<String>, JsValue> {
+        self.get(field)?
+            .map(|value| {
+                value
+                    .as_string()
+                    .ok_or_else(|| invalid_options(format!("option `{}` must be a string", field)))
+            })
+            .transpose()
+    }
+
+    fn strings(&self, field: &str) -> Result<Vec<String>, JsValue> {
+        let value = match self.get(field)? {
+            Some(value) => value,
+            None => return Ok(vec![]),
+        };
+        let not_strings =
+            || invalid_options(format!("option `{}` must be an array of strings", field));
+        if !is_array(&value) {
+            return Err(not_strings());
+        }
+        array_elements(&value)?
+            .into_iter()
+            .map(|element| element.as_string().ok_or_else(not_strings))
+            .collect()
+    }
+
+    fn parsed<T>(&self, field: &str) -> Result<Option<T>, JsValue>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.string(field)?
+            .map(|s| parse_field(field, &s))
+            .transpose()
+    }
+
+    fn parsed_list<T>(&self, field: &str) -> Result<Vec<T>, JsValue>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.strings(field)?
+            .iter()
+            .map(|s| parse_field(field, s))
+            .collect()
+    }
+}
+
+fn parse_field<T>(field: &str, s: &str) -> Result<T, JsValue>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.parse()
+        .map_err(|e| invalid_options(format!("option `{}`: {}", field, e)))
+}
+
+fn array_elements(array: &JsValue) -> Result<Vec<JsValue>, JsValue> {
+    let len = reflect_get(array, &JsValue::from_str("length"))?
+        .as_f64()
+        .unwrap_or(0.0) as u32;
+    (0..len)
+        .map(|i| reflect_get(array, &JsValue::from(i)))
+        .collect()
+}
@@ -0,0 +1,50 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const { InputBuffer, Items, Top } = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+const data = fs.readFileSync(path.join(fixtures, 'wee_alloc.wasm'));
+
+function chunks(bytes, size) {
+  const result = [];
+  for (let i = 0; i < bytes.length; i += size) {
+    result.push(bytes.subarray(i, i + size));
+  }
+  return result;
+}
+
+test('parse a buffer filled in chunks', () => {
+  const buffer = InputBuffer.new(data.length);
+  for (const chunk of chunks(data, 1000)) {
+    buffer.push(chunk);
+  }
+  assert.strictEqual(buffer.len(), data.length);
+
+  const items = Items.parse_buffer(buffer);
+  const expected = Items.parse(data);
+  assert.deepStrictEqual(items.top_value(Top.new()), expected.top_value(Top.new()));
+  items.free();
+  expected.free();
+});
+
+test('a buffer can grow past its capacity', () => {
+  const buffer = InputBuffer.new(0);
+  assert.ok(buffer.is_empty());
+  for (const chunk of chunks(data, 4096)) {
+    buffer.push(chunk);
+  }
+  assert.strictEqual(buffer.len(), data.length);
+  Items.parse_buffer(buffer).free();
+});
+
+test('a parsed buffer is consumed', () => {
+  const buffer = InputBuffer.new(data.length);
+  buffer.push(data);
+  Items.parse_buffer(buffer).free();
+  assert.throws(() => buffer.push(data));
+});
@@ -0,0 +1,23 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const { call_indices_value, call_indices_options } = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+
+test('call indices of the most frequently called imports', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'mono.wasm'));
+  const rows = call_indices_value(data, call_indices_options({ max_items: 1 }));
+  assert.deepStrictEqual(
+    rows.map(row => [row.name, row.bytes, row.optimal_bytes, row.savings]),
+    [
+      ['import env::_time', 64, 32, 32],
+      ['... and 27 more', 423, 212, 211],
+      ['Σ [5029 Called Functions]', 155132, 154916, 216],
+    ],
+  );
+});
@@ -0,0 +1,83 @@
+// Run with `node --test wasm-api/tests/*.js` after building the bindings for
+// node, as CI does:
+//
+//     cargo build --release --target wasm32-unknown-unknown -p twiggy-wasm-api
+//     wasm-bindgen --target nodejs --out-dir wasm-api/pkg \
+//         target/wasm32-unknown-unknown/release/twiggy_wasm_api.wasm
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const { diff, Diff } = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+const oldBytes = fs.readFileSync(path.join(fixtures, 'wee_alloc.wasm'));
+const newBytes = fs.readFileSync(path.join(fixtures, 'wee_alloc.2.wasm'));
+
+test('diff with max items', () => {
+  const opts = Diff.new();
+  opts.set_max_items(3);
+
+  const rows = JSON.parse(diff(oldBytes, newBytes, opts));
+  assert.deepStrictEqual(rows, [
+    { delta_bytes: -1034, name: 'data[3]', old_size: 1034, new_size: 0, status: 'removed' },
+    {
+      delta_bytes: -593,
+      name: '"function names" subsection',
+      old_size: 777,
+      new_size: 184,
+      status: 'changed',
+    },
+    { delta_bytes: 243, name: 'goodbye', old_size: 45, new_size: 288, status: 'changed' },
+    {
+      delta_bytes: -92,
+      name: '... and 30 more items totaling -92 bytes (-3.27%)',
+      old_size: 886,
+      new_size: 794,
+      truncated_count: 30,
+      truncated_bytes: -92,
+    },
+    { delta_bytes: -1476, name: 'Σ [33 Total Rows]', old_size: 2817, new_size: 1341 },
+  ]);
+});
+
+test('diff all items', () => {
+  const opts = Diff.new();
+  opts.set_all_items(true);
+
+  const rows = JSON.parse(diff(oldBytes, newBytes, opts));
+  assert.strictEqual(rows.length, 34);
+  assert.ok(rows.every(row => !row.name.startsWith('... and')));
+});
+
+test('diff by regex', () => {
+  const opts = Diff.new();
+  opts.add_item('goodbye');
+  opts.add_item('hello');
+  opts.set_using_regexps(true);
+
+  const rows = JSON.parse(diff(oldBytes, newBytes, opts));
+  const names = rows.map(row => row.name);
+  assert.ok(names.includes('goodbye'));
+  assert.ok(names.every(name => /goodbye|hello|Σ/.test(name)));
+});
+
+test('diff with moves tracked', () => {
+  const movesOld = fs.readFileSync(path.join(fixtures, 'moves_old.wasm'));
+  const movesNew = fs.readFileSync(path.join(fixtures, 'moves_new.wasm'));
+  const opts = Diff.new();
+  opts.set_track_moves(true);
+
+  const rows = JSON.parse(diff(movesOld, movesNew, opts));
+  assert.deepStrictEqual(rows.find(row => row.moved_from), {
+    delta_bytes: 6,
+    name: 'data segment "shared"',
+    old_size: 10,
+    new_size: 16,
+    status: 'changed',
+    moved_from: 'function in code section',
+    moved_to: 'data_segment in data section',
+  });
+});
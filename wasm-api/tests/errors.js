@@ -0,0 +1,63 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const Twiggy = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+
+function assertThrowsError(f, code, message) {
+  assert.throws(f, error => {
+    assert.ok(error instanceof Error, 'should throw an Error');
+    assert.strictEqual(error.code, code);
+    assert.match(error.message, message);
+    return true;
+  });
+}
+
+test('a truncated module throws', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'paths_test.wasm')).subarray(0, 9);
+  assertThrowsError(() => Twiggy.parse(data), 'malformed', /cannot be fully parsed \(at offset 0x8\)/);
+  assertThrowsError(
+    () => Twiggy.garbage_value(data, Twiggy.Garbage.new()),
+    'malformed',
+    /cannot be fully parsed/,
+  );
+});
+
+test('a malformed module throws', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'malformed_truncated_code.wasm'));
+  assertThrowsError(
+    () => Twiggy.monos(data, Twiggy.Monos.new()),
+    'malformed',
+    /^wasm binary cannot be fully parsed \(at offset 0x16\)$/,
+  );
+});
+
+test('a buffer that is not wasm throws', () => {
+  const data = new TextEncoder().encode('hello world, not a binary');
+  assertThrowsError(() => Twiggy.parse(data), 'not_a_format', /not a recognized binary format/);
+  assertThrowsError(
+    () => Twiggy.diff(data, data, Twiggy.Diff.new()),
+    'not_a_format',
+    /not a recognized binary format/,
+  );
+});
+
+test('a bad analysis option throws', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'paths_test.wasm'));
+  const items = Twiggy.parse(data);
+  assertThrowsError(
+    () => items.paths_value(['('], Twiggy.paths_options({ regex: true })),
+    'analysis',
+    /unclosed group/,
+  );
+  assertThrowsError(() => Twiggy.paths_options({ regexp: true }), 'invalid_options', /`regexp`/);
+
+  // The handle is still usable after an error.
+  assert.strictEqual(items.paths_value(['calledOnce'], Twiggy.Paths.new()).length, 1);
+  items.free();
+});
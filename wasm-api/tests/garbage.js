@@ -0,0 +1,34 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const { garbage, Garbage } = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+
+test('garbage with max items', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'garbage.wasm'));
+  const opts = Garbage.new();
+  opts.set_max_items(2);
+  opts.set_show_data_segments(true);
+
+  const rows = JSON.parse(garbage(data, opts));
+  assert.deepStrictEqual(rows.map(row => [row.name, row.bytes]), [
+    ['unusedAddThreeNumbers', 12],
+    ['unusedAddOne', 9],
+    ['... and 4 more items totaling 22 bytes (11.17%)', 22],
+    ['Σ [6 Total Rows]', 43],
+  ]);
+  assert.strictEqual(rows[0].id, 's4.e2');
+});
+
+test('garbage rejects a malformed module', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'malformed_truncated_code.wasm'));
+  assert.throws(
+    () => garbage(data, Garbage.new()),
+    error => error.message === 'wasm binary cannot be fully parsed (at offset 0x16)',
+  );
+});
@@ -0,0 +1,30 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const { imports_value, imports_options, Imports } = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+
+test('imports with their shims', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'cpp-monos.wasm'));
+  const rows = imports_value(data, Imports.new());
+  assert.deepStrictEqual(
+    rows.map(row => [row.name, row.import_size, row.type_size, row.shim_size, row.total_size]),
+    [
+      ['import env::hello', 12, 4, 48, 64],
+      ['Σ [1 Total Rows]', 12, 4, 48, 64],
+    ],
+  );
+  assert.strictEqual(rows[0].shims.length, 3);
+});
+
+test('imports with a smaller maximum shim size', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'cpp-monos.wasm'));
+  const rows = imports_value(data, imports_options({ max_shim_size: 15 }));
+  assert.deepStrictEqual(rows[0].shims, []);
+  assert.strictEqual(rows[0].total_size, 16);
+});
@@ -0,0 +1,43 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const Twiggy = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+
+test('parse once and run several analyses', () => {
+  let data = fs.readFileSync(path.join(fixtures, 'paths_test.wasm'));
+  const items = Twiggy.parse(data);
+  // The handle owns the parsed items, not the input buffer.
+  data.fill(0);
+  data = null;
+
+  // The largest item, and the row totaling the rest.
+  const top = items.top_value(Twiggy.top_options({ max_items: 1 }));
+  assert.strictEqual(top.length, 2);
+  assert.strictEqual(top[1].truncated_count, 15);
+
+  const tree = items.dominators_value(Twiggy.dominators_options({ items: ['woof'] }));
+  assert.strictEqual(tree.items[0].name, 'woof');
+
+  const paths = items.paths_value(['calledOnce'], Twiggy.Paths.new());
+  assert.strictEqual(paths[0].callers[0].name, 'woof');
+
+  // Running an analysis again on the same handle gives the same result.
+  assert.deepStrictEqual(items.paths_value(['calledOnce'], Twiggy.Paths.new()), paths);
+
+  items.free();
+});
+
+test('parse rejects a malformed module', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'malformed_truncated_code.wasm'));
+  assert.throws(
+    () => Twiggy.parse(data),
+    error => error.message === 'wasm binary cannot be fully parsed (at offset 0x16)',
+  );
+  assert.throws(() => Twiggy.Items.parse(data), /cannot be fully parsed/);
+});
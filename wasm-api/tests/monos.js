@@ -0,0 +1,40 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const { monos, Monos } = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+const data = fs.readFileSync(path.join(fixtures, 'monos.wasm'));
+
+test('monos with max generics and monos', () => {
+  const opts = Monos.new();
+  opts.set_max_generics(2);
+  opts.set_max_monos(1);
+
+  const rows = JSON.parse(monos(data, opts));
+  assert.deepStrictEqual(rows.map(row => row.generic), [
+    'alloc::slice::merge_sort',
+    "<&'a T as core::fmt::Debug>::fmt",
+    '... and 178 more items totaling 31160 bytes (53.54%)',
+    'Σ [182 Total Rows]',
+  ]);
+  // With their hashes stripped, the monomorphizations share one name.
+  assert.deepStrictEqual(rows[0].monomorphizations.map(mono => mono.name), [
+    'alloc::slice::merge_sort',
+  ]);
+  assert.strictEqual(rows[0].approximate_monomorphization_bloat_bytes, 2141);
+});
+
+test('monos only generics', () => {
+  const opts = Monos.new();
+  opts.set_all(true);
+  opts.set_only_generics(true);
+
+  const rows = JSON.parse(monos(data, opts));
+  assert.ok(rows.every(row => row.monomorphizations.length === 0));
+  assert.ok(rows.every(row => !row.generic.startsWith('... and')));
+});
@@ -0,0 +1,60 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const {
+  Items,
+  dominators_options,
+  garbage_value,
+  garbage_options,
+  neighbors_options,
+  top_options,
+} = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+
+test('options from plain objects', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'garbage.wasm'));
+  const rows = garbage_value(data, garbage_options({ max_items: 2, show_data_segments: true }));
+  assert.deepStrictEqual(rows.map(row => row.name), [
+    'unusedAddThreeNumbers',
+    'unusedAddOne',
+    '... and 4 more items totaling 22 bytes (11.17%)',
+    'Σ [6 Total Rows]',
+  ]);
+});
+
+test('missing options are the defaults', () => {
+  const data = fs.readFileSync(path.join(fixtures, 'paths_test.wasm'));
+  const opts = neighbors_options({ items: ['calledOnce'], direction: 'incoming' });
+  const entries = Items.parse(data).neighbors_value(opts);
+  assert.deepStrictEqual(entries[0].neighbors.map(n => [n.name, n.direction]), [
+    ['woof', 'incoming'],
+  ]);
+  assert.doesNotThrow(() => top_options({}));
+  assert.doesNotThrow(() => top_options(undefined));
+});
+
+test('bad options name the field', () => {
+  assert.throws(() => top_options({ max_itemz: 1 }), /unknown option `max_itemz`/);
+  assert.throws(() => top_options({ max_items: -1 }), /option `max_items` must be a non-negative integer/);
+  assert.throws(() => top_options({ retained: 'yes' }), /option `retained` must be a boolean/);
+  assert.throws(() => top_options({ kinds: 'function' }), /option `kinds` must be an array of strings/);
+  assert.throws(() => top_options({ kinds: ['nope'] }), /option `kinds`: /);
+  assert.throws(() => neighbors_options({ direction: 'sideways' }), /option `direction`: /);
+  assert.throws(() => top_options([]), /options must be an object/);
+});
+
+test('threads other than 1 are rejected', () => {
+  for (const options of [top_options, dominators_options, garbage_options]) {
+    assert.doesNotThrow(() => options({ threads: 1 }));
+    assert.throws(() => options({ threads: 4 }), error => {
+      assert.strictEqual(error.code, 'invalid_options');
+      assert.match(error.message, /option `threads` must be 1/);
+      return true;
+    });
+  }
+});
@@ -0,0 +1,61 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const { dominators, Dominators, paths, Paths } = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+const data = fs.readFileSync(path.join(fixtures, 'paths_test.wasm'));
+
+test('paths to a known item', () => {
+  const rows = JSON.parse(paths(data, ['calledOnce'], Paths.new()));
+  assert.strictEqual(rows.length, 1);
+  assert.strictEqual(rows[0].name, 'calledOnce');
+
+  const woof = rows[0].callers[0];
+  assert.strictEqual(woof.name, 'woof');
+  assert.strictEqual(woof.edge_kind, 'call');
+  assert.deepStrictEqual(woof.callers.map(caller => caller.name), ['export "woof"']);
+});
+
+test('paths descending with max depth', () => {
+  const opts = Paths.new();
+  opts.set_descending(true);
+  opts.set_max_depth(1);
+
+  const rows = JSON.parse(paths(data, ['woof'], opts));
+  assert.deepStrictEqual(rows[0].callers.map(callee => callee.name), [
+    'type[0]: () -> i32',
+    'calledOnce',
+    'calledTwice',
+  ]);
+});
+
+test('paths rejects an invalid regex', () => {
+  const opts = Paths.new();
+  opts.set_using_regexps(true);
+  assert.throws(() => paths(data, ['('], opts), error => error instanceof Error && error.code === 'analysis');
+});
+
+test('dominators subtree of a known item', () => {
+  const opts = Dominators.new();
+  opts.add_item('woof');
+
+  const tree = JSON.parse(dominators(data, opts));
+  assert.strictEqual(tree.items.length, 1);
+  assert.strictEqual(tree.items[0].name, 'woof');
+  assert.strictEqual(tree.items[0].retained_size, 15);
+  assert.deepStrictEqual(tree.items[0].children.map(child => child.name), ['calledOnce']);
+});
+
+test('dominators with max rows', () => {
+  const opts = Dominators.new();
+  opts.set_max_depth(1);
+  opts.set_max_rows(2);
+
+  const tree = JSON.parse(dominators(data, opts));
+  assert.strictEqual(tree.items[0].name, '<meta root>');
+});
@@ -0,0 +1,73 @@
+// See `diff.js` for how to build the bindings that these tests run against.
+
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const test = require('node:test');
+
+const {
+  Items,
+  Top,
+  Diff,
+  Dominators,
+  Garbage,
+  Monos,
+  Paths,
+  diff,
+  diff_value,
+  garbage,
+  garbage_value,
+  paths_value,
+} = require('../pkg/twiggy_wasm_api');
+
+const fixtures = path.join(__dirname, '../../twiggy/tests/all/fixtures');
+const data = fs.readFileSync(path.join(fixtures, 'garbage.wasm'));
+
+test('top returns objects', () => {
+  const opts = Top.new();
+  opts.set_max_items(1);
+
+  const rows = Items.parse(data).top_value(opts);
+  assert.ok(Array.isArray(rows));
+  assert.strictEqual(typeof rows[0].name, 'string');
+  assert.strictEqual(typeof rows[0].shallow_size, 'number');
+});
+
+test('top splits exports into name and descriptor bytes', () => {
+  const rows = Items.parse(data).top_value(Top.new());
+  const exports = rows.filter(row => row.kind === 'export');
+  assert.ok(exports.length > 0);
+  for (const row of exports) {
+    assert.strictEqual(row.name_bytes + row.descriptor_bytes, row.shallow_size);
+  }
+  for (const row of rows.filter(row => row.kind === 'function')) {
+    assert.strictEqual(row.name_bytes, undefined);
+  }
+});
+
+test('value and string forms agree', () => {
+  const opts = Garbage.new();
+  assert.deepStrictEqual(garbage_value(data, opts), JSON.parse(garbage(data, opts)));
+
+  const items = Items.parse(data);
+  assert.deepStrictEqual(items.top_value(Top.new()), JSON.parse(items.top(Top.new())));
+  assert.deepStrictEqual(
+    items.dominators_value(Dominators.new()),
+    JSON.parse(items.dominators(Dominators.new())),
+  );
+  assert.deepStrictEqual(items.monos_value(Monos.new()), JSON.parse(items.monos(Monos.new())));
+  items.free();
+
+  const oldBytes = fs.readFileSync(path.join(fixtures, 'wee_alloc.wasm'));
+  const newBytes = fs.readFileSync(path.join(fixtures, 'wee_alloc.2.wasm'));
+  assert.deepStrictEqual(
+    diff_value(oldBytes, newBytes, Diff.new()),
+    JSON.parse(diff(oldBytes, newBytes, Diff.new())),
+  );
+});
+
+test('paths returns nested objects', () => {
+  const pathsData = fs.readFileSync(path.join(fixtures, 'paths_test.wasm'));
+  const rows = paths_value(pathsData, ['calledOnce'], Paths.new());
+  assert.strictEqual(rows[0].callers[0].name, 'woof');
+});
@@ -1,12 +1,210 @@
 #![cfg(target_arch = "wasm32")]
 #![cfg(feature = "emit_json")]
 
+//! Every analysis is available in two forms: one that returns its result as a
+//! JSON string, and one with a `_value` suffix that returns the same result as
+//! plain JavaScript objects and arrays. Those are built straight from the
+//! analysis' result with `serde-wasm-bindgen`, without going through JSON.
+//!
+//! The string forms are deprecated and will be removed in the next release.
+//!
+//! The `_value` forms are typed in the generated TypeScript definitions, and
+//! options can also be built from plain objects with functions like
+//! `top_options({ max_items: 10 })`.
+//!
+//! Nothing here traps on bad input: when a binary cannot be parsed or
+//! analyzed, a JavaScript `Error` is thrown instead, as described in
+//! `error.rs`.
+
+mod buffer;
+mod error;
+mod options;
+
+use buffer::InputBuffer;
+use error::error_to_js;
+use serde::Serialize;
+use std::io;
 use twiggy_analyze as analyze;
 use twiggy_ir as ir;
 use twiggy_opt as opt;
 use twiggy_parser as parser;
+use twiggy_traits::{self as traits, Emit};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "TopRow[]")]
+    pub type TopRows;
+
+    #[wasm_bindgen(typescript_type = "DominatorsTree")]
+    pub type DominatorsTree;
+
+    #[wasm_bindgen(typescript_type = "PathsEntry[]")]
+    pub type PathsEntries;
+
+    #[wasm_bindgen(typescript_type = "NeighborsEntry[]")]
+    pub type NeighborsEntries;
+
+    #[wasm_bindgen(typescript_type = "MonosEntry[]")]
+    pub type MonosEntries;
+
+    #[wasm_bindgen(typescript_type = "GarbageRow[]")]
+    pub type GarbageRows;
+
+    #[wasm_bindgen(typescript_type = "DiffRow[]")]
+    pub type DiffRows;
+
+    #[wasm_bindgen(typescript_type = "ImportsRow[]")]
+    pub type ImportsRows;
+
+    #[wasm_bindgen(typescript_type = "CallIndicesRow[]")]
+    pub type CallIndicesRows;
+}
+
+// These mirror the `-f json` output of the analyses. Summary rows, like
+// `"... and 3 more"`, lack the fields that only describe a single item.
+#[wasm_bindgen(typescript_custom_section)]
+const RESULT_TYPES: &'static str = r#"
+export interface SourceLocation {
+  file: string;
+  line?: number;
+}
+
+export interface TopRow {
+  id?: string;
+  name: string;
+  kind?: string;
+  shallow_size: number;
+  shallow_size_percent: number;
+  retained_size?: number;
+  retained_size_percent?: number;
+  source_location?: SourceLocation;
+  name_bytes?: number;
+  descriptor_bytes?: number;
+  payload_bytes?: number;
+  header_bytes?: number;
+  truncated_count?: number;
+  truncated_bytes?: number;
+}
+
+export interface DominatorsItem {
+  id: string;
+  name: string;
+  kind: string;
+  shallow_size: number;
+  shallow_size_percent: number;
+  retained_size: number;
+  retained_size_percent: number;
+  source_location?: SourceLocation;
+  children?: DominatorsItem[];
+}
+
+export interface DominatorsTree {
+  items: DominatorsItem[];
+  summary?: { name: string; retained_size: number; retained_size_percent: number }[];
+}
+
+export interface PathsEntry {
+  id: string;
+  name: string;
+  shallow_size: number;
+  shallow_size_percent: number;
+  edge_kind?: string;
+  matched_by?: string[];
+  callers: PathsEntry[];
+}
+
+export interface Neighbor {
+  id: string;
+  name: string;
+  size: number;
+  edge_kind: string;
+  direction: "outgoing" | "incoming";
+}
+
+export interface NeighborsEntry {
+  id: string;
+  name: string;
+  size: number;
+  neighbors: Neighbor[];
+}
+
+export interface MonosEntry {
+  generic: string;
+  approximate_monomorphization_bloat_bytes: number;
+  approximate_monomorphization_bloat_percent: number;
+  total_size: number;
+  total_size_percent: number;
+  truncated_count?: number;
+  truncated_bytes?: number;
+  monomorphizations: {
+    name: string;
+    shallow_size: number;
+    shallow_size_percent: number;
+    truncated_count?: number;
+    truncated_bytes?: number;
+  }[];
+}
+
+export interface GarbageRow {
+  id?: string;
+  name: string;
+  kind?: string;
+  bytes: number;
+  size_percent: number;
+  referenced_by?: string;
+  incoming_edges?: number;
+  retainer?: string;
+  source_location?: SourceLocation;
+  truncated_count?: number;
+  truncated_bytes?: number;
+}
+
+export interface DiffRow {
+  name: string;
+  delta_bytes: number;
+  old_size: number;
+  new_size: number;
+  status?: "added" | "removed" | "changed";
+  moved_from?: string;
+  moved_to?: string;
+  truncated_count?: number;
+  truncated_bytes?: number;
+}
+
+export interface ImportsRow {
+  id?: string;
+  name: string;
+  import_size: number;
+  type_size: number;
+  shim_size: number;
+  total_size: number;
+  total_size_percent: number;
+  duplicate_of?: string;
+  shims?: { id: string; name: string; size: number }[];
+  duplicate_imports?: number;
+  duplicate_import_size?: number;
+}
+
+export interface CallIndicesRow {
+  id?: string;
+  name: string;
+  index?: number;
+  optimal_index?: number;
+  call_sites: number;
+  bytes: number;
+  optimal_bytes: number;
+  savings: number;
+}
+"#;
+
+/// A parsed binary, which any number of analyses can be run on without parsing
+/// it again.
+///
+/// The handle owns the items parsed from the binary, not the bytes that were
+/// passed to `parse`, so the caller is free to drop those. Call `free` when
+/// done with the handle to release the items.
 #[wasm_bindgen]
 pub struct Items {
     items: ir::Items,
@@ -14,43 +212,253 @@ pub struct Items {
 
 #[wasm_bindgen]
 impl Items {
-    pub fn parse(data: &[u8]) -> Items {
-        let items = parser::parse(data).unwrap();
-        Items { items }
+    /// Parse a binary into a handle.
+    pub fn parse(data: &[u8]) -> Result<Items, JsValue> {
+        let items = parser::parse(data).map_err(error_to_js)?;
+        Ok(Items { items })
+    }
+
+    /// Parse a binary whose data was written to the given buffer, without
+    /// copying it again. The buffer is consumed, and its memory is freed once
+    /// the binary has been parsed.
+    pub fn parse_buffer(buffer: InputBuffer) -> Result<Items, JsValue> {
+        Items::parse(buffer.data())
+    }
+
+    pub fn top(&mut self, options: &opt::Top) -> Result<String, JsValue> {
+        let top = analyze::top(&mut self.items, options).map_err(error_to_js)?;
+        emit_string(&top, &self.items).map_err(error_to_js)
+    }
+
+    pub fn top_value(&mut self, options: &opt::Top) -> Result<TopRows, JsValue> {
+        let top = analyze::top(&mut self.items, options).map_err(error_to_js)?;
+        to_value(top.json_value(&self.items))
+    }
+
+    pub fn dominators(&mut self, options: &opt::Dominators) -> Result<String, JsValue> {
+        let dominators = analyze::dominators(&mut self.items, options).map_err(error_to_js)?;
+        emit_string(&dominators, &self.items).map_err(error_to_js)
+    }
+
+    pub fn dominators_value(
+        &mut self,
+        options: &opt::Dominators,
+    ) -> Result<DominatorsTree, JsValue> {
+        let dominators = analyze::dominators(&mut self.items, options).map_err(error_to_js)?;
+        to_value(dominators.json_value(&self.items))
+    }
+
+    pub fn paths(&mut self, options: &opt::Paths) -> Result<String, JsValue> {
+        let paths = analyze::paths(&mut self.items, options).map_err(error_to_js)?;
+        emit_string(&paths, &self.items).map_err(error_to_js)
+    }
+
+    /// Find the call paths to the items with the given names, in addition to
+    /// any functions already added to the options.
+    pub fn paths_value(
+        &mut self,
+        names: Vec<String>,
+        options: &opt::Paths,
+    ) -> Result<PathsEntries, JsValue> {
+        let mut options = options.clone();
+        for name in names {
+            options.add_function(name);
+        }
+        let paths = analyze::paths(&mut self.items, &options).map_err(error_to_js)?;
+        to_value(paths.json_value(&self.items))
+    }
+
+    pub fn neighbors(&mut self, options: &opt::Neighbors) -> Result<String, JsValue> {
+        let neighbors = analyze::neighbors(&mut self.items, options).map_err(error_to_js)?;
+        emit_string(&neighbors, &self.items).map_err(error_to_js)
     }
 
-    pub fn top(&mut self, options: &opt::Top) -> String {
-        let top = analyze::top(&mut self.items, options).unwrap();
-        let mut buf = Vec::new();
-        top.emit_json(&self.items, &mut buf).unwrap();
-        String::from_utf8(buf).unwrap()
+    pub fn neighbors_value(
+        &mut self,
+        options: &opt::Neighbors,
+    ) -> Result<NeighborsEntries, JsValue> {
+        let neighbors = analyze::neighbors(&mut self.items, options).map_err(error_to_js)?;
+        to_value(neighbors.json_value(&self.items))
     }
 
-    pub fn dominators(&mut self, options: &opt::Dominators) -> String {
-        let dominators = analyze::dominators(&mut self.items, options).unwrap();
-        let mut buf = Vec::new();
-        dominators.emit_json(&self.items, &mut buf).unwrap();
-        String::from_utf8(buf).unwrap()
+    pub fn monos(&mut self, options: &opt::Monos) -> Result<String, JsValue> {
+        let monos = analyze::monos(&mut self.items, options).map_err(error_to_js)?;
+        emit_string(&monos, &self.items).map_err(error_to_js)
     }
 
-    pub fn paths(&mut self, options: &opt::Paths) -> String {
-        let paths = analyze::paths(&mut self.items, options).unwrap();
-        let mut buf = Vec::new();
-        paths.emit_json(&self.items, &mut buf).unwrap();
-        String::from_utf8(buf).unwrap()
+    pub fn monos_value(&mut self, options: &opt::Monos) -> Result<MonosEntries, JsValue> {
+        let monos = analyze::monos(&mut self.items, options).map_err(error_to_js)?;
+        to_value(monos.json_value(&self.items))
     }
 
-    pub fn monos(&mut self, options: &opt::Monos) -> String {
-        let monos = analyze::monos(&mut self.items, options).unwrap();
-        let mut buf = Vec::new();
-        monos.emit_json(&self.items, &mut buf).unwrap();
-        String::from_utf8(buf).unwrap()
+    pub fn garbage(&mut self, options: &opt::Garbage) -> Result<String, JsValue> {
+        let garbage = analyze::garbage(&mut self.items, options).map_err(error_to_js)?;
+        emit_string(&garbage, &self.items).map_err(error_to_js)
     }
 
-    pub fn diff(&mut self, new_items: &mut Items, options: &opt::Diff) -> String {
-        let diff = analyze::diff(&mut self.items, &mut new_items.items, options).unwrap();
-        let mut buf = Vec::new();
-        diff.emit_json(&self.items, &mut buf).unwrap();
-        String::from_utf8(buf).unwrap()
+    pub fn garbage_value(&mut self, options: &opt::Garbage) -> Result<GarbageRows, JsValue> {
+        let garbage = analyze::garbage(&mut self.items, options).map_err(error_to_js)?;
+        to_value(garbage.json_value(&self.items))
     }
+
+    pub fn diff(&mut self, new_items: &mut Items, options: &opt::Diff) -> Result<String, JsValue> {
+        let diff =
+            analyze::diff(&mut self.items, &mut new_items.items, options).map_err(error_to_js)?;
+        emit_string(&diff, &self.items).map_err(error_to_js)
+    }
+
+    pub fn diff_value(
+        &mut self,
+        new_items: &mut Items,
+        options: &opt::Diff,
+    ) -> Result<DiffRows, JsValue> {
+        let diff =
+            analyze::diff(&mut self.items, &mut new_items.items, options).map_err(error_to_js)?;
+        to_value(diff.json_value())
+    }
+
+    pub fn imports_value(&mut self, options: &opt::Imports) -> Result<ImportsRows, JsValue> {
+        let imports = analyze::imports(&mut self.items, options).map_err(error_to_js)?;
+        to_value(imports.json_value(&self.items))
+    }
+
+    pub fn call_indices_value(
+        &mut self,
+        options: &opt::CallIndices,
+    ) -> Result<CallIndicesRows, JsValue> {
+        let calls = analyze::call_indices(&mut self.items, options).map_err(error_to_js)?;
+        to_value(calls.json_value(&self.items))
+    }
+}
+
+// The functions below parse their input on every call. They are convenient for
+// running a single analysis, but when running several on the same binary,
+// `parse` it once and use the methods of the returned `Items` instead.
+
+/// Parse a binary into an `Items` handle, so that several analyses can be run
+/// on it without parsing it again.
+#[wasm_bindgen]
+pub fn parse(data: &[u8]) -> Result<Items, JsValue> {
+    Items::parse(data)
+}
+
+/// Parse a binary and list the generic function monomorphizations that are
+/// contributing to code bloat, returning the list as JSON.
+#[wasm_bindgen]
+pub fn monos(data: &[u8], options: &opt::Monos) -> Result<String, JsValue> {
+    Items::parse(data)?.monos(options)
+}
+
+/// Like `monos`, but returns the list as JavaScript objects.
+#[wasm_bindgen]
+pub fn monos_value(data: &[u8], options: &opt::Monos) -> Result<MonosEntries, JsValue> {
+    Items::parse(data)?.monos_value(options)
+}
+
+/// Parse the old and new versions of a binary and diff them, returning the
+/// diff as JSON.
+#[wasm_bindgen]
+pub fn diff(old_bytes: &[u8], new_bytes: &[u8], options: &opt::Diff) -> Result<String, JsValue> {
+    let mut old_items = Items::parse(old_bytes)?;
+    let mut new_items = Items::parse(new_bytes)?;
+    old_items.diff(&mut new_items, options)
+}
+
+/// Like `diff`, but returns the diff as JavaScript objects.
+#[wasm_bindgen]
+pub fn diff_value(
+    old_bytes: &[u8],
+    new_bytes: &[u8],
+    options: &opt::Diff,
+) -> Result<DiffRows, JsValue> {
+    let mut old_items = Items::parse(old_bytes)?;
+    let mut new_items = Items::parse(new_bytes)?;
+    old_items.diff_value(&mut new_items, options)
+}
+
+/// Parse a binary and find the call paths to the items with the given names,
+/// returning the paths as JSON. The names are used in addition to any
+/// functions already added to the options.
+#[wasm_bindgen]
+pub fn paths(data: &[u8], names: Vec<String>, options: &opt::Paths) -> Result<String, JsValue> {
+    let mut options = options.clone();
+    for name in names {
+        options.add_function(name);
+    }
+    let mut items = Items::parse(data)?;
+    let paths = analyze::paths(&mut items.items, &options).map_err(error_to_js)?;
+    emit_string(&paths, &items.items).map_err(error_to_js)
+}
+
+/// Like `paths`, but returns the paths as JavaScript objects.
+#[wasm_bindgen]
+pub fn paths_value(
+    data: &[u8],
+    names: Vec<String>,
+    options: &opt::Paths,
+) -> Result<PathsEntries, JsValue> {
+    Items::parse(data)?.paths_value(names, options)
+}
+
+/// Parse a binary and compute its dominator tree, returning the tree, or the
+/// subtrees of the items added to the options, as JSON.
+#[wasm_bindgen]
+pub fn dominators(data: &[u8], options: &opt::Dominators) -> Result<String, JsValue> {
+    let mut items = Items::parse(data)?;
+    let dominators = analyze::dominators(&mut items.items, options).map_err(error_to_js)?;
+    emit_string(&dominators, &items.items).map_err(error_to_js)
+}
+
+/// Like `dominators`, but returns the tree as JavaScript objects.
+#[wasm_bindgen]
+pub fn dominators_value(data: &[u8], options: &opt::Dominators) -> Result<DominatorsTree, JsValue> {
+    Items::parse(data)?.dominators_value(options)
+}
+
+/// Parse a binary and find the code and data that is not transitively
+/// referenced by any exports, returning the items as JSON.
+#[wasm_bindgen]
+pub fn garbage(data: &[u8], options: &opt::Garbage) -> Result<String, JsValue> {
+    let mut items = Items::parse(data)?;
+    let garbage = analyze::garbage(&mut items.items, options).map_err(error_to_js)?;
+    emit_string(&garbage, &items.items).map_err(error_to_js)
+}
+
+/// Like `garbage`, but returns the items as JavaScript objects.
+#[wasm_bindgen]
+pub fn garbage_value(data: &[u8], options: &opt::Garbage) -> Result<GarbageRows, JsValue> {
+    Items::parse(data)?.garbage_value(options)
+}
+
+/// Parse a binary and list its imports along with what each of them costs, as
+/// JavaScript objects.
+#[wasm_bindgen]
+pub fn imports_value(data: &[u8], options: &opt::Imports) -> Result<ImportsRows, JsValue> {
+    Items::parse(data)?.imports_value(options)
+}
+
+/// Parse a binary and estimate how many bytes of call instructions' function
+/// indices renumbering its functions would save, as JavaScript objects.
+#[wasm_bindgen]
+pub fn call_indices_value(
+    data: &[u8],
+    options: &opt::CallIndices,
+) -> Result<CallIndicesRows, JsValue> {
+    Items::parse(data)?.call_indices_value(options)
+}
+
+fn emit_string(data: &dyn Emit, items: &ir::Items) -> traits::Result<String> {
+    let mut buf = Vec::new();
+    data.emit_json(items, &mut buf)?;
+    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+}
+
+// The rows an analysis' JSON output is written from, as the equivalent
+// JavaScript objects and arrays.
+fn to_value<T: JsCast>(value: impl Serialize) -> Result<T, JsValue> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value
+        .serialize(&serializer)
+        .map(JsCast::unchecked_into)
+        .map_err(|e| error_to_js(io::Error::other(e.to_string()).into()))
 }